@@ -0,0 +1,7 @@
+CREATE TABLE object_media_metadata (
+	object_id BIGINT PRIMARY KEY REFERENCES objects(id) ON DELETE CASCADE,
+	captured_at timestamptz,
+	location GEOGRAPHY(Point, 4326),
+	raw JSONB NOT NULL DEFAULT '{}'::jsonb,
+	extracted_at timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,26 @@
+CREATE TABLE object_derivatives (
+	object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	kind TEXT NOT NULL CHECK (kind <> ''),
+	storage_key TEXT NOT NULL UNIQUE,
+	content_type TEXT NOT NULL,
+	width INTEGER NOT NULL CHECK (width > 0),
+	height INTEGER NOT NULL CHECK (height > 0),
+	byte_size BIGINT NOT NULL CHECK (byte_size >= 0),
+	created_at timestamptz NOT NULL DEFAULT now(),
+	PRIMARY KEY (object_id, kind)
+);
+
+CREATE TABLE object_derivative_jobs (
+	object_id BIGINT PRIMARY KEY REFERENCES objects(id) ON DELETE CASCADE,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	attempts INTEGER NOT NULL DEFAULT 0 CHECK (attempts >= 0),
+	last_attempt_at timestamptz,
+	next_attempt_at timestamptz NOT NULL DEFAULT now(),
+	last_error TEXT
+);
+
+CREATE INDEX object_derivative_jobs_claim_idx
+	ON object_derivative_jobs (next_attempt_at, created_at);
+
+INSERT INTO object_derivative_jobs (object_id)
+SELECT id FROM objects WHERE storage_state = 'available';
@@ -0,0 +1,15 @@
+CREATE TABLE object_media_info (
+	object_id BIGINT PRIMARY KEY REFERENCES objects(id) ON DELETE CASCADE,
+	duration_seconds DOUBLE PRECISION CHECK (duration_seconds > 0),
+	width INTEGER CHECK (width > 0),
+	height INTEGER CHECK (height > 0),
+	video_codec TEXT,
+	audio_codec TEXT,
+	probed_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- Videos were skipped by the derivative worker until it could probe them.
+INSERT INTO object_derivative_jobs (object_id)
+SELECT id FROM objects
+WHERE storage_state = 'available' AND content_type IN ('video/mp4', 'video/webm', 'video/ogg')
+ON CONFLICT (object_id) DO NOTHING;
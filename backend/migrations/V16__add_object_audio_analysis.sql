@@ -0,0 +1,11 @@
+ALTER TABLE object_media_info
+	ADD COLUMN title TEXT,
+	ADD COLUMN artist TEXT,
+	ADD COLUMN recorded_date TEXT,
+	ADD COLUMN peaks REAL[];
+
+-- Audio was skipped by the derivative worker until it could be analysed.
+INSERT INTO object_derivative_jobs (object_id)
+SELECT id FROM objects
+WHERE storage_state = 'available' AND content_type LIKE 'audio/%'
+ON CONFLICT (object_id) DO NOTHING;
@@ -0,0 +1,12 @@
+ALTER TABLE objects
+	ADD COLUMN content_hash TEXT CHECK (content_hash ~ '^[0-9a-f]{64}$');
+
+CREATE INDEX objects_user_content_hash_idx
+	ON objects (user_id, content_hash)
+	WHERE storage_state = 'available' AND content_hash IS NOT NULL;
+
+-- Existing objects are hashed by the content hash backfill, which pages
+-- through the objects still missing one.
+CREATE INDEX objects_unhashed_idx
+	ON objects (id)
+	WHERE storage_state = 'available' AND content_hash IS NULL;
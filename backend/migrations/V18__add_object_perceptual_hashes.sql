@@ -0,0 +1,31 @@
+-- A NULL hash marks an image that could not be hashed (undecodable or over the
+-- size limit), so the worker does not retry it.
+CREATE TABLE object_perceptual_hashes (
+	object_id BIGINT PRIMARY KEY REFERENCES objects(id) ON DELETE CASCADE,
+	hash BIGINT,
+	hashed_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- Pairs a user has reviewed and kept both of, stored lowest id first.
+CREATE TABLE object_duplicate_dismissals (
+	first_object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	second_object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	PRIMARY KEY (first_object_id, second_object_id),
+	CHECK (first_object_id < second_object_id)
+);
+
+-- Pairs of one user's images whose hashes are within the candidate distance,
+-- recorded as each image is hashed so that possible duplicates are read from
+-- an index instead of comparing every pair of images. Stored lowest id first.
+CREATE TABLE object_duplicate_candidates (
+	first_object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	second_object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	distance INTEGER NOT NULL CHECK (distance BETWEEN 0 AND 64),
+	PRIMARY KEY (first_object_id, second_object_id),
+	CHECK (first_object_id < second_object_id)
+);
+
+CREATE INDEX object_duplicate_candidates_user_distance_idx
+	ON object_duplicate_candidates (user_id, distance, first_object_id, second_object_id);
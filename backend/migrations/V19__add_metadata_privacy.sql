@@ -0,0 +1,35 @@
+-- How much embedded metadata (EXIF, XMP, IPTC) people other than the owner see
+-- in an original: all of it, all but the GPS position, or none.
+CREATE TYPE metadata_privacy AS ENUM ('keep', 'strip_location', 'strip_all');
+CREATE TYPE metadata_privacy_override AS ENUM ('default', 'keep', 'strip_location', 'strip_all');
+
+ALTER TABLE users ADD COLUMN metadata_privacy metadata_privacy NOT NULL DEFAULT 'keep';
+ALTER TABLE objects ADD COLUMN metadata_privacy metadata_privacy_override NOT NULL DEFAULT 'default';
+
+-- A job queued because privacy settings changed only rewrites the object's
+-- sanitized copy; its thumbnails and probed media info do not depend on them.
+ALTER TABLE object_derivative_jobs ADD COLUMN sanitized_only BOOLEAN NOT NULL DEFAULT false;
+
+-- A sanitized copy of an audio file has no width or height.
+ALTER TABLE object_derivatives
+	ALTER COLUMN width DROP NOT NULL,
+	ALTER COLUMN height DROP NOT NULL;
+
+CREATE OR REPLACE VIEW available_objects_with_users AS
+SELECT
+	o.id,
+	o.name,
+	o.storage_key,
+	o.content_type,
+	o.made_on,
+	ST_Y(o.location::geometry) AS latitude,
+	ST_X(o.location::geometry) AS longitude,
+	o.user_id,
+	o.publicity,
+	COALESCE(array_agg(u.email) FILTER (WHERE u.email IS NOT NULL), '{}') AS allowed_users,
+	o.metadata_privacy
+FROM objects o
+LEFT JOIN object_allowed_users oau ON o.id = oau.object_id
+LEFT JOIN users u ON oau.user_id = u.id
+WHERE o.storage_state = 'available'
+GROUP BY o.id;
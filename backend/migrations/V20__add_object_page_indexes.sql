@@ -0,0 +1,37 @@
+-- Keyset pagination for s3ObjectsConnection. Capture-time orderings sort on
+-- COALESCE(made_on, '-infinity') so undated objects have a place in the order;
+-- the expression must match made_on_sort_key! in the backend queries. Each
+-- filter gets an index leading with its column and ending in the sort key, so
+-- a filtered page is still read in order and stops at the limit.
+CREATE INDEX objects_available_made_on_idx
+	ON objects ((COALESCE(made_on, '-infinity'::TIMESTAMPTZ)), id)
+	WHERE storage_state = 'available';
+
+CREATE INDEX objects_available_user_made_on_idx
+	ON objects (user_id, (COALESCE(made_on, '-infinity'::TIMESTAMPTZ)), id)
+	WHERE storage_state = 'available';
+
+CREATE INDEX objects_available_media_type_made_on_idx
+	ON objects (
+		(split_part(content_type, '/', 1)),
+		(COALESCE(made_on, '-infinity'::TIMESTAMPTZ)),
+		id
+	)
+	WHERE storage_state = 'available';
+
+CREATE INDEX objects_available_publicity_made_on_idx
+	ON objects (publicity, (COALESCE(made_on, '-infinity'::TIMESTAMPTZ)), id)
+	WHERE storage_state = 'available';
+
+CREATE INDEX objects_available_located_made_on_idx
+	ON objects ((COALESCE(made_on, '-infinity'::TIMESTAMPTZ)), id)
+	WHERE storage_state = 'available' AND location IS NOT NULL;
+
+CREATE INDEX objects_available_unlocated_made_on_idx
+	ON objects ((COALESCE(made_on, '-infinity'::TIMESTAMPTZ)), id)
+	WHERE storage_state = 'available' AND location IS NULL;
+
+-- Upload-order pages filtered by owner; the primary key serves the rest.
+CREATE INDEX objects_available_user_id_idx
+	ON objects (user_id, id)
+	WHERE storage_state = 'available';
@@ -0,0 +1,11 @@
+-- Spatial lookups for the map. Radius searches compare geographies, so they
+-- use the index on the column itself; bounding boxes compare the location as a
+-- geometry (the box edges are lines of latitude), so they get an expression
+-- index on the cast.
+CREATE INDEX objects_available_location_idx
+	ON objects USING GIST (location)
+	WHERE storage_state = 'available';
+
+CREATE INDEX objects_available_location_geometry_idx
+	ON objects USING GIST ((location::geometry))
+	WHERE storage_state = 'available';
@@ -0,0 +1,26 @@
+-- Full-text search for searchS3Objects. Names are split into words on anything
+-- that is not a letter or digit, so IMG_2023-05_beach.jpg is found by "beach"
+-- or "2023". Name words weigh more than content type words. The 'simple'
+-- configuration neither stems nor drops stop words, as names are not prose.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE objects ADD COLUMN search_document TSVECTOR GENERATED ALWAYS AS (
+	setweight(to_tsvector('simple', regexp_replace(name, '[^[:alnum:]]+', ' ', 'g')), 'A')
+	|| setweight(
+		to_tsvector('simple', regexp_replace(content_type, '[^[:alnum:]]+', ' ', 'g')),
+		'B'
+	)
+) STORED;
+
+CREATE INDEX objects_available_search_document_idx
+	ON objects USING GIN (search_document)
+	WHERE storage_state = 'available';
+
+-- Trigram indexes for names close to the search text, which catch typos the
+-- word match misses, and for owner emails matched as substrings.
+CREATE INDEX objects_available_name_trgm_idx
+	ON objects USING GIN (name gin_trgm_ops)
+	WHERE storage_state = 'available';
+
+CREATE INDEX users_email_trgm_idx
+	ON users USING GIN (email gin_trgm_ops);
@@ -0,0 +1,34 @@
+-- Albums are ordered collections of their owner's objects. An album's
+-- publicity works like an object's: 'default' follows the owner's default
+-- publicity and 'selected_users' shares it with album_allowed_users. Whoever
+-- can see an album can see the objects in it.
+CREATE TABLE albums (
+	id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	title TEXT NOT NULL,
+	description TEXT NOT NULL DEFAULT '',
+	-- NULL shows the first object as the cover.
+	cover_object_id BIGINT REFERENCES objects(id) ON DELETE SET NULL,
+	publicity publicity_override NOT NULL DEFAULT 'default',
+	created_at timestamptz NOT NULL DEFAULT now(),
+	updated_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX albums_user_id_idx ON albums (user_id, id);
+
+-- Objects are listed by position, then id. Positions are not unique, so a
+-- reorder can rewrite them in any order.
+CREATE TABLE album_objects (
+	album_id BIGINT NOT NULL REFERENCES albums(id) ON DELETE CASCADE,
+	object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	position INTEGER NOT NULL,
+	PRIMARY KEY (album_id, object_id)
+);
+
+CREATE INDEX album_objects_object_id_idx ON album_objects (object_id);
+
+CREATE TABLE album_allowed_users (
+	album_id BIGINT REFERENCES albums(id) ON DELETE CASCADE,
+	user_id BIGINT REFERENCES users(id) ON DELETE CASCADE,
+	PRIMARY KEY (album_id, user_id)
+);
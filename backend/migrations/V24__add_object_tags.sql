@@ -0,0 +1,45 @@
+-- Free-form tags on objects. Each user has their own tags, which are put on
+-- their own objects. Names are stored normalized (trimmed, single-spaced and
+-- lowercase), so "Beach " and "beach" are one tag.
+CREATE TABLE tags (
+	id BIGINT PRIMARY KEY GENERATED ALWAYS AS IDENTITY,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	name TEXT NOT NULL,
+	UNIQUE (user_id, name)
+);
+
+-- Prefix lookups for tag autocomplete.
+CREATE INDEX tags_user_id_name_pattern_idx ON tags (user_id, name text_pattern_ops);
+
+CREATE TABLE object_tags (
+	object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	tag_id BIGINT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+	PRIMARY KEY (object_id, tag_id)
+);
+
+CREATE INDEX object_tags_tag_id_idx ON object_tags (tag_id, object_id);
+
+CREATE OR REPLACE VIEW available_objects_with_users AS
+SELECT
+	o.id,
+	o.name,
+	o.storage_key,
+	o.content_type,
+	o.made_on,
+	ST_Y(o.location::geometry) AS latitude,
+	ST_X(o.location::geometry) AS longitude,
+	o.user_id,
+	o.publicity,
+	COALESCE(array_agg(u.email) FILTER (WHERE u.email IS NOT NULL), '{}') AS allowed_users,
+	o.metadata_privacy,
+	COALESCE((
+		SELECT array_agg(t.name ORDER BY t.name)
+		FROM object_tags ot
+		JOIN tags t ON t.id = ot.tag_id
+		WHERE ot.object_id = o.id
+	), '{}') AS tags
+FROM objects o
+LEFT JOIN object_allowed_users oau ON o.id = oau.object_id
+LEFT JOIN users u ON oau.user_id = u.id
+WHERE o.storage_state = 'available'
+GROUP BY o.id;
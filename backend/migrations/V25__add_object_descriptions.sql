@@ -0,0 +1,67 @@
+-- The story behind an object: a short title, a Markdown description shown to
+-- whoever can see the object, and a note only its owner can read. Empty text
+-- means none.
+ALTER TABLE objects
+	ADD COLUMN title TEXT NOT NULL DEFAULT '',
+	ADD COLUMN description TEXT NOT NULL DEFAULT '',
+	ADD COLUMN note TEXT NOT NULL DEFAULT '';
+
+-- Titles weigh as much as names, and descriptions more than content types.
+-- They are split into words the way names are, which also drops Markdown
+-- markup. Notes are kept in a document of their own, as only their owner may
+-- find an object by them.
+ALTER TABLE objects DROP COLUMN search_document;
+
+ALTER TABLE objects ADD COLUMN search_document TSVECTOR GENERATED ALWAYS AS (
+	setweight(to_tsvector('simple', regexp_replace(name, '[^[:alnum:]]+', ' ', 'g')), 'A')
+	|| setweight(to_tsvector('simple', regexp_replace(title, '[^[:alnum:]]+', ' ', 'g')), 'A')
+	|| setweight(
+		to_tsvector('simple', regexp_replace(description, '[^[:alnum:]]+', ' ', 'g')),
+		'B'
+	)
+	|| setweight(
+		to_tsvector('simple', regexp_replace(content_type, '[^[:alnum:]]+', ' ', 'g')),
+		'C'
+	)
+) STORED;
+
+ALTER TABLE objects ADD COLUMN note_search_document TSVECTOR
+	GENERATED ALWAYS AS (
+		to_tsvector('simple', regexp_replace(note, '[^[:alnum:]]+', ' ', 'g'))
+	) STORED;
+
+CREATE INDEX objects_available_search_document_idx
+	ON objects USING GIN (search_document)
+	WHERE storage_state = 'available';
+
+CREATE INDEX objects_available_note_search_document_idx
+	ON objects USING GIN (note_search_document)
+	WHERE storage_state = 'available';
+
+CREATE OR REPLACE VIEW available_objects_with_users AS
+SELECT
+	o.id,
+	o.name,
+	o.storage_key,
+	o.content_type,
+	o.made_on,
+	ST_Y(o.location::geometry) AS latitude,
+	ST_X(o.location::geometry) AS longitude,
+	o.user_id,
+	o.publicity,
+	COALESCE(array_agg(u.email) FILTER (WHERE u.email IS NOT NULL), '{}') AS allowed_users,
+	o.metadata_privacy,
+	COALESCE((
+		SELECT array_agg(t.name ORDER BY t.name)
+		FROM object_tags ot
+		JOIN tags t ON t.id = ot.tag_id
+		WHERE ot.object_id = o.id
+	), '{}') AS tags,
+	o.title,
+	o.description,
+	o.note
+FROM objects o
+LEFT JOIN object_allowed_users oau ON o.id = oau.object_id
+LEFT JOIN users u ON oau.user_id = u.id
+WHERE o.storage_state = 'available'
+GROUP BY o.id;
@@ -0,0 +1,9 @@
+-- Object names were unique across every user, so two people uploading
+-- IMG_0001.JPG collided. They are now unique per owner. Names unique across
+-- everyone are unique per owner too, so existing rows all satisfy the new
+-- index. Objects without an owner share one namespace.
+CREATE UNIQUE INDEX objects_active_user_name_key
+	ON objects (user_id, name) NULLS NOT DISTINCT
+	WHERE storage_state <> 'delete_pending';
+
+DROP INDEX objects_active_name_key;
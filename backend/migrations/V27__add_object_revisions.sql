@@ -0,0 +1,12 @@
+-- Each metadata edit of an object, numbered from 1 per object. old_values and
+-- new_values hold only the fields the edit changed, keyed by their GraphQL
+-- names. The editor is kept as NULL once their account is deleted.
+CREATE TABLE object_revisions (
+	object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	revision INTEGER NOT NULL,
+	user_id BIGINT REFERENCES users(id) ON DELETE SET NULL,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	old_values JSONB NOT NULL,
+	new_values JSONB NOT NULL,
+	PRIMARY KEY (object_id, revision)
+);
@@ -0,0 +1,4 @@
+-- Deleted objects first move to their owner's trash, from which they can be
+-- restored. A new enum value cannot be used in the transaction that adds it,
+-- so the columns and indexes that refer to it follow in V29.
+ALTER TYPE object_storage_state ADD VALUE 'trashed' AFTER 'available';
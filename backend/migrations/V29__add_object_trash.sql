@@ -0,0 +1,14 @@
+-- When each trashed object was deleted. Maintenance moves it into the storage
+-- deletion outbox once it has been in the trash for the retention window.
+ALTER TABLE objects ADD COLUMN trashed_at timestamptz;
+
+CREATE INDEX objects_trashed_at_idx
+	ON objects (trashed_at)
+	WHERE storage_state = 'trashed';
+
+-- A trashed object no longer holds its name, so a file can be uploaded again
+-- under it. Restoring the trashed object then fails until one is renamed.
+DROP INDEX objects_active_user_name_key;
+CREATE UNIQUE INDEX objects_active_user_name_key
+	ON objects (user_id, name) NULLS NOT DISTINCT
+	WHERE storage_state IN ('pending_upload', 'available');
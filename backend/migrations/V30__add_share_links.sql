@@ -0,0 +1,25 @@
+-- Links that let anyone holding their token see one object or one album,
+-- without an account. Like password reset tokens, only a hash of the token
+-- is stored. A link stops working once revoked, expired, or viewed max_views
+-- times. Wrong passwords are counted, and after too many in a row the link
+-- takes no more guesses until password_locked_until.
+CREATE TABLE share_links (
+	id BIGSERIAL PRIMARY KEY,
+	token_hash TEXT NOT NULL UNIQUE,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	object_id BIGINT REFERENCES objects(id) ON DELETE CASCADE,
+	album_id BIGINT REFERENCES albums(id) ON DELETE CASCADE,
+	password_hash TEXT,
+	expires_at timestamptz,
+	max_views INTEGER CHECK (max_views > 0),
+	view_count INTEGER NOT NULL DEFAULT 0,
+	revoked_at timestamptz,
+	failed_password_attempts INTEGER NOT NULL DEFAULT 0,
+	password_locked_until timestamptz,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	CHECK (num_nonnulls(object_id, album_id) = 1)
+);
+
+CREATE INDEX share_links_user_id_idx ON share_links (user_id);
+CREATE INDEX share_links_object_id_idx ON share_links (object_id);
+CREATE INDEX share_links_album_id_idx ON share_links (album_id);
@@ -0,0 +1,67 @@
+-- Groups of users their owner shares with all at once, such as family or a
+-- club. A 'selected_users' object or album is shared with the users it lists
+-- and with the members of the groups it lists, which must be its owner's.
+CREATE TABLE user_groups (
+	id BIGSERIAL PRIMARY KEY,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	name TEXT NOT NULL,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	UNIQUE (user_id, name)
+);
+
+CREATE TABLE user_group_members (
+	group_id BIGINT NOT NULL REFERENCES user_groups(id) ON DELETE CASCADE,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	PRIMARY KEY (group_id, user_id)
+);
+
+CREATE INDEX user_group_members_user_id_idx ON user_group_members (user_id);
+
+CREATE TABLE object_allowed_groups (
+	object_id BIGINT NOT NULL REFERENCES objects(id) ON DELETE CASCADE,
+	group_id BIGINT NOT NULL REFERENCES user_groups(id) ON DELETE CASCADE,
+	PRIMARY KEY (object_id, group_id)
+);
+
+CREATE INDEX object_allowed_groups_group_id_idx ON object_allowed_groups (group_id);
+
+CREATE TABLE album_allowed_groups (
+	album_id BIGINT NOT NULL REFERENCES albums(id) ON DELETE CASCADE,
+	group_id BIGINT NOT NULL REFERENCES user_groups(id) ON DELETE CASCADE,
+	PRIMARY KEY (album_id, group_id)
+);
+
+CREATE INDEX album_allowed_groups_group_id_idx ON album_allowed_groups (group_id);
+
+CREATE OR REPLACE VIEW available_objects_with_users AS
+SELECT
+	o.id,
+	o.name,
+	o.storage_key,
+	o.content_type,
+	o.made_on,
+	ST_Y(o.location::geometry) AS latitude,
+	ST_X(o.location::geometry) AS longitude,
+	o.user_id,
+	o.publicity,
+	COALESCE(array_agg(u.email) FILTER (WHERE u.email IS NOT NULL), '{}') AS allowed_users,
+	o.metadata_privacy,
+	COALESCE((
+		SELECT array_agg(t.name ORDER BY t.name)
+		FROM object_tags ot
+		JOIN tags t ON t.id = ot.tag_id
+		WHERE ot.object_id = o.id
+	), '{}') AS tags,
+	o.title,
+	o.description,
+	o.note,
+	COALESCE((
+		SELECT array_agg(oag.group_id ORDER BY oag.group_id)
+		FROM object_allowed_groups oag
+		WHERE oag.object_id = o.id
+	), '{}') AS allowed_groups
+FROM objects o
+LEFT JOIN object_allowed_users oau ON o.id = oau.object_id
+LEFT JOIN users u ON oau.user_id = u.id
+WHERE o.storage_state = 'available'
+GROUP BY o.id;
@@ -0,0 +1,83 @@
+-- How precisely people other than the owner see where an object was made:
+-- exactly, snapped to the centre of a grid cell about 100 m, 1 km or (for
+-- 'city') 10 km across, or not at all.
+CREATE TYPE location_precision AS ENUM ('exact', 'approx_100m', 'approx_1km', 'city', 'hidden');
+CREATE TYPE location_precision_override AS ENUM ('default', 'exact', 'approx_100m', 'approx_1km', 'city', 'hidden');
+
+ALTER TABLE users ADD COLUMN location_precision location_precision NOT NULL DEFAULT 'exact';
+ALTER TABLE objects ADD COLUMN location_precision location_precision_override NOT NULL DEFAULT 'default';
+
+-- `original` as shown at `level`: the centre of the cell of the 0.001, 0.01
+-- or 0.1 degree grid it falls in, kept within range at the north pole and the
+-- antimeridian, or NULL when hidden. Snapping rather than jittering means
+-- repeated queries cannot average the position back out. `Location::coarsened`
+-- must match it.
+CREATE FUNCTION coarsen_location(original geography, level location_precision)
+RETURNS geography
+LANGUAGE SQL
+IMMUTABLE
+AS $$
+	SELECT CASE level
+		WHEN 'exact' THEN original
+		WHEN 'hidden' THEN NULL
+		ELSE ST_SetSRID(
+			ST_MakePoint(
+				LEAST(
+					floor(ST_X(original::geometry) / cell.degrees) * cell.degrees + cell.degrees / 2,
+					180 - cell.degrees / 2
+				),
+				LEAST(
+					floor(ST_Y(original::geometry) / cell.degrees) * cell.degrees + cell.degrees / 2,
+					90 - cell.degrees / 2
+				)
+			),
+			4326
+		)::geography
+	END
+	FROM (
+		SELECT CASE level
+			WHEN 'approx_100m' THEN 0.001
+			WHEN 'approx_1km' THEN 0.01
+			ELSE 0.1
+		END::DOUBLE PRECISION AS degrees
+	) cell
+$$;
+
+CREATE OR REPLACE VIEW available_objects_with_users AS
+SELECT
+	o.id,
+	o.name,
+	o.storage_key,
+	o.content_type,
+	o.made_on,
+	ST_Y(o.location::geometry) AS latitude,
+	ST_X(o.location::geometry) AS longitude,
+	o.user_id,
+	o.publicity,
+	COALESCE(array_agg(u.email) FILTER (WHERE u.email IS NOT NULL), '{}') AS allowed_users,
+	o.metadata_privacy,
+	COALESCE((
+		SELECT array_agg(t.name ORDER BY t.name)
+		FROM object_tags ot
+		JOIN tags t ON t.id = ot.tag_id
+		WHERE ot.object_id = o.id
+	), '{}') AS tags,
+	o.title,
+	o.description,
+	o.note,
+	COALESCE((
+		SELECT array_agg(oag.group_id ORDER BY oag.group_id)
+		FROM object_allowed_groups oag
+		WHERE oag.object_id = o.id
+	), '{}') AS allowed_groups,
+	o.location_precision,
+	COALESCE(
+		NULLIF(o.location_precision::TEXT, 'default'),
+		(SELECT owner.location_precision::TEXT FROM users owner WHERE owner.id = o.user_id),
+		'exact'
+	)::location_precision AS effective_location_precision
+FROM objects o
+LEFT JOIN object_allowed_users oau ON o.id = oau.object_id
+LEFT JOIN users u ON oau.user_id = u.id
+WHERE o.storage_state = 'available'
+GROUP BY o.id;
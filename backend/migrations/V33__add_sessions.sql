@@ -0,0 +1,14 @@
+-- Signed-in browsers and devices. The auth cookie holds a session's token and,
+-- like share link tokens, only a hash of it is stored. A session stops working
+-- once deleted, or once idle or old for longer than the configured timeouts.
+CREATE TABLE sessions (
+	id BIGSERIAL PRIMARY KEY,
+	token_hash TEXT NOT NULL UNIQUE,
+	user_id BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+	user_agent TEXT,
+	ip_address INET,
+	created_at timestamptz NOT NULL DEFAULT now(),
+	last_seen_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
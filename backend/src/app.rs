@@ -3,7 +3,9 @@ use {
 		AppState,
 		CachedGraphqlResponse,
 		CallerIdentity,
+		CasbinObject,
 		CasbinUser,
+		ClientInfo,
 		Config,
 		GraphqlMutationCacheEffect,
 		GraphqlResponseCacheKey,
@@ -12,13 +14,19 @@ use {
 			GRAPHQL_BODY_LIMIT_BYTES,
 			GRAPHQL_RESPONSE_CACHE_MAX_CAPACITY_BYTES,
 			GRAPHQL_RESPONSE_CACHE_TTL_SECONDS,
+			SESSION_USER_AGENT_MAX_LENGTH,
+			VECTOR_TILE_CONTENT_TYPE,
+			VECTOR_TILE_MAX_ZOOM,
 		},
-		db::queries::SELECT_USER_BY_ID_QUERY,
+		db::queries::SELECT_VECTOR_TILE_QUERY,
 		errors::AppError,
 		graphiql,
-		graphql::queries::{
-			mutation::Mutation,
-			query::Query,
+		graphql::{
+			objects::session::Session,
+			queries::{
+				mutation::Mutation,
+				query::Query,
+			},
 		},
 		storage::StorageClient,
 	},
@@ -42,10 +50,13 @@ use {
 			Bytes,
 		},
 		extract::{
+			ConnectInfo,
 			Extension,
+			Path,
 			State,
 		},
 		http::{
+			Extensions,
 			HeaderMap,
 			HeaderValue,
 			Method,
@@ -67,7 +78,10 @@ use {
 		Key,
 		PrivateCookieJar,
 	},
-	casbin::Enforcer,
+	casbin::{
+		CoreApi,
+		Enforcer,
+	},
 	deadpool::managed::{
 		Object,
 		Pool,
@@ -75,6 +89,7 @@ use {
 	deadpool_postgres::Manager,
 	moka::future::Cache,
 	std::{
+		net::SocketAddr,
 		sync::{
 			Arc,
 			atomic::AtomicU64,
@@ -141,6 +156,7 @@ pub fn build_app(shared_state: Arc<BackendSharedState>) -> Router {
 				.route_layer(RequestBodyLimitLayer::new(GRAPHQL_BODY_LIMIT_BYTES))
 				.with_state(app_state.clone()),
 		)
+		.route("/tiles/{z}/{x}/{tile}", get(vector_tile_handler).with_state(app_state.clone()))
 		.layer(Extension(schema))
 		.layer(Extension(key))
 		.route_layer(cors)
@@ -174,25 +190,41 @@ async fn authenticated_caller_identity(
 	state: &BackendState,
 	jar: &PrivateCookieJar,
 ) -> Result<Option<CallerIdentity>, AppError> {
-	let Some(user_id) = jar.get("auth_token").and_then(|cookie| cookie.value().parse::<i64>().ok())
-	else {
+	let Some(cookie) = jar.get("auth_token") else {
 		return Ok(None);
 	};
 	let client = state.inner.pool.get().await?;
-	let statement = client.prepare_cached(SELECT_USER_BY_ID_QUERY).await?;
-	let Some(row) = client.query_opt(&statement, &[&user_id]).await? else {
+	let Some(caller) = Session::caller(&client, cookie.value(), &state.inner.config.auth).await?
+	else {
 		return Ok(None);
 	};
-	let role = row.try_get("role")?;
 	Ok(Some(CallerIdentity {
-		user_id,
+		user_id: caller.user_id,
+		session_id: caller.session_id,
 		casbin_user: CasbinUser {
-			id: user_id,
-			role,
+			id: caller.user_id,
+			role: caller.role,
 		},
 	}))
 }
 
+/// The user agent and address of the client making a request. The address is
+/// that of the connection, so behind a reverse proxy it is the proxy's.
+fn client_info(
+	headers: &HeaderMap,
+	extensions: &Extensions,
+) -> ClientInfo {
+	ClientInfo {
+		user_agent: headers
+			.get(header::USER_AGENT)
+			.and_then(|user_agent| user_agent.to_str().ok())
+			.map(|user_agent| user_agent.chars().take(SESSION_USER_AGENT_MAX_LENGTH).collect()),
+		ip_address: extensions
+			.get::<ConnectInfo<SocketAddr>>()
+			.map(|ConnectInfo(address)| address.ip()),
+	}
+}
+
 fn graphql_request_operation_details(
 	request: &mut GraphqlRequestInner
 ) -> Option<GraphqlRequestOperationDetails> {
@@ -263,9 +295,11 @@ fn hash_cache_component(
 	hasher.update(bytes);
 }
 
+/// Keyed per session rather than per user, as some responses (such as
+/// `mySessions`) depend on which of the caller's sessions asks.
 fn graphql_response_cache_key(
 	request: &GraphqlRequestInner,
-	user_id: Option<i64>,
+	session_id: Option<i64>,
 	authorization: Option<&HeaderValue>,
 	cache_epoch: u64,
 ) -> Option<GraphqlResponseCacheKey> {
@@ -277,10 +311,10 @@ fn graphql_response_cache_key(
 	hash_cache_component(&mut hasher, b"memory-map/graphql-response-cache/v1");
 	hasher.update(&cache_epoch.to_le_bytes());
 
-	match user_id {
-		Some(user_id) => {
+	match session_id {
+		Some(session_id) => {
 			hasher.update(&[1]);
-			hasher.update(&user_id.to_le_bytes());
+			hasher.update(&session_id.to_le_bytes());
 		}
 		None => {
 			hasher.update(&[0]);
@@ -344,16 +378,17 @@ async fn graphql_handler(
 	State(state): State<BackendState>,
 	Extension(schema): Extension<BackendSchema>,
 	headers: HeaderMap,
+	extensions: Extensions,
 	jar: PrivateCookieJar,
 	req: GraphQLRequest,
 ) -> (PrivateCookieJar, Response) {
-	let mut req = req.into_inner();
+	let mut req = req.into_inner().data(client_info(&headers, &extensions));
 
 	let caller_identity = match authenticated_caller_identity(&state, &jar).await {
 		Ok(caller_identity) => caller_identity,
 		Err(error) => return (jar, error.into_response()),
 	};
-	let user_id = caller_identity.as_ref().map(|identity| identity.user_id);
+	let session_id = caller_identity.as_ref().map(|identity| identity.session_id);
 	if let Some(caller_identity) = caller_identity {
 		req = req.data(caller_identity);
 	}
@@ -364,7 +399,12 @@ async fn graphql_handler(
 		operation_details.and_then(|details| details.mutation_root_field_count);
 	let cache_epoch = state.inner.graphql_response_cache_epoch();
 	let cache_key = if matches!(operation_type, Some(OperationType::Query)) {
-		graphql_response_cache_key(&req, user_id, headers.get(header::AUTHORIZATION), cache_epoch)
+		graphql_response_cache_key(
+			&req,
+			session_id,
+			headers.get(header::AUTHORIZATION),
+			cache_epoch,
+		)
 	} else {
 		None
 	};
@@ -417,6 +457,136 @@ async fn graphql_handler(
 	(jar, cache_entry_to_response(cached_response))
 }
 
+/// A Web Mercator tile address, as `/tiles/{z}/{x}/{y}.mvt` names it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct TileCoordinates {
+	zoom: i32,
+	column: i32,
+	row: i32,
+}
+
+impl TileCoordinates {
+	/// Parses the segments of a tile path, rejecting addresses outside the grid
+	/// of their zoom level. The router cannot match the `.mvt` suffix, so it
+	/// arrives as part of `file`.
+	fn parse(
+		z: &str,
+		x: &str,
+		file: &str,
+	) -> Result<Self, AppError> {
+		let invalid =
+			|| AppError::Validation(format!("/tiles/{z}/{x}/{file} is not a vector tile"));
+		let y = file.strip_suffix(".mvt").ok_or_else(invalid)?;
+		let zoom: i32 = z.parse().map_err(|_| invalid())?;
+		let column: i32 = x.parse().map_err(|_| invalid())?;
+		let row: i32 = y.parse().map_err(|_| invalid())?;
+		if !(0 ..= VECTOR_TILE_MAX_ZOOM).contains(&zoom) {
+			return Err(invalid());
+		}
+		let tiles_per_side = 1_i64 << zoom;
+		if !(0 .. tiles_per_side).contains(&i64::from(column)) ||
+			!(0 .. tiles_per_side).contains(&i64::from(row))
+		{
+			return Err(invalid());
+		}
+		Ok(Self {
+			zoom,
+			column,
+			row,
+		})
+	}
+}
+
+/// Vector tiles share the GraphQL response cache, so the writes that invalidate
+/// cached queries invalidate tiles too. Keyed like query responses: per caller,
+/// since each sees different objects, and per cache epoch.
+fn vector_tile_cache_key(
+	tile: TileCoordinates,
+	user_id: Option<i64>,
+	cache_epoch: u64,
+) -> GraphqlResponseCacheKey {
+	let mut hasher = blake3::Hasher::new();
+	hash_cache_component(&mut hasher, b"memory-map/vector-tile-cache/v1");
+	hasher.update(&cache_epoch.to_le_bytes());
+	match user_id {
+		Some(user_id) => {
+			hasher.update(&[1]);
+			hasher.update(&user_id.to_le_bytes());
+		}
+		None => {
+			hasher.update(&[0]);
+		}
+	};
+	for coordinate in [tile.zoom, tile.column, tile.row] {
+		hasher.update(&coordinate.to_le_bytes());
+	}
+
+	let mut bytes = [0; 32];
+	bytes.copy_from_slice(hasher.finalize().as_bytes());
+	GraphqlResponseCacheKey::new(bytes)
+}
+
+async fn vector_tile(
+	state: &BackendState,
+	jar: &PrivateCookieJar,
+	tile: TileCoordinates,
+) -> Result<CachedGraphqlResponse, AppError> {
+	let caller_identity = authenticated_caller_identity(state, jar).await?;
+	let user_id = caller_identity.as_ref().map(|identity| identity.user_id);
+	let cache_epoch = state.inner.graphql_response_cache_epoch();
+	let cache_key = vector_tile_cache_key(tile, user_id, cache_epoch);
+	if let Some(cached_response) = state.inner.graphql_response_cache.get(&cache_key).await {
+		return Ok(cached_response);
+	}
+
+	let read_all = match &caller_identity {
+		Some(caller_identity) => state.inner.enforcer.read().await.enforce((
+			caller_identity.casbin_user.clone(),
+			CasbinObject {
+				user_id: 0,
+			},
+			"read_all_s3_objects",
+		))?,
+		None => false,
+	};
+	let client = state.inner.pool.get().await?;
+	let statement = client.prepare_cached(SELECT_VECTOR_TILE_QUERY).await?;
+	let row = client
+		.query_one(&statement, &[&user_id, &read_all, &tile.zoom, &tile.column, &tile.row])
+		.await?;
+	// PostGIS aggregates an empty tile to NULL on some versions.
+	let bytes: Option<Vec<u8>> = row.try_get("tile")?;
+
+	let mut headers = HeaderMap::new();
+	headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(VECTOR_TILE_CONTENT_TYPE));
+	let response = CachedGraphqlResponse {
+		status: StatusCode::OK,
+		headers,
+		bytes: Bytes::from(bytes.unwrap_or_default()),
+	};
+	if state.inner.graphql_response_cache_epoch() == cache_epoch {
+		state.inner.graphql_response_cache.insert(cache_key, response.clone()).await;
+	}
+	Ok(response)
+}
+
+/// Serves `/tiles/{z}/{x}/{y}.mvt`: the objects the caller can see in that tile
+/// as a Mapbox Vector Tile (see `SELECT_VECTOR_TILE_QUERY` for its layout).
+async fn vector_tile_handler(
+	State(state): State<BackendState>,
+	Path((z, x, file)): Path<(String, String, String)>,
+	jar: PrivateCookieJar,
+) -> Response {
+	let tile = match TileCoordinates::parse(&z, &x, &file) {
+		Ok(tile) => tile,
+		Err(error) => return error.into_response(),
+	};
+	match vector_tile(&state, &jar, tile).await {
+		Ok(response) => cache_entry_to_response(response),
+		Err(error) => error.into_response(),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use {
@@ -435,10 +605,10 @@ mod tests {
 
 	fn cache_key(
 		request: &GraphqlRequestInner,
-		user_id: Option<i64>,
+		session_id: Option<i64>,
 		cache_epoch: u64,
 	) -> anyhow::Result<GraphqlResponseCacheKey> {
-		graphql_response_cache_key(request, user_id, None, cache_epoch)
+		graphql_response_cache_key(request, session_id, None, cache_epoch)
 			.ok_or_else(|| anyhow::anyhow!("request should be cacheable"))
 	}
 
@@ -566,4 +736,52 @@ mod tests {
 		assert_ne!(cache_key(&request, Some(10), 0)?, key);
 		Ok(())
 	}
+
+	#[test]
+	fn tile_coordinates_parse_within_the_zoom_level_grid() -> anyhow::Result<()> {
+		assert_eq!(
+			TileCoordinates::parse("2", "3", "1.mvt")?,
+			TileCoordinates {
+				zoom: 2,
+				column: 3,
+				row: 1,
+			}
+		);
+		for (z, x, file) in [
+			("2", "4", "1.mvt"),
+			("2", "1", "-1.mvt"),
+			("2", "1", "1.png"),
+			("2", "1", "1"),
+			("25", "0", "0.mvt"),
+			("-1", "0", "0.mvt"),
+		] {
+			assert!(matches!(TileCoordinates::parse(z, x, file), Err(AppError::Validation(_))));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn vector_tile_cache_key_scopes_by_tile_actor_and_epoch() {
+		let tile = TileCoordinates {
+			zoom: 2,
+			column: 3,
+			row: 1,
+		};
+		let key = vector_tile_cache_key(tile, Some(10), 0);
+		assert_eq!(vector_tile_cache_key(tile, Some(10), 0), key);
+		assert_ne!(vector_tile_cache_key(tile, None, 0), key);
+		assert_ne!(vector_tile_cache_key(tile, Some(11), 0), key);
+		assert_ne!(vector_tile_cache_key(tile, Some(10), 1), key);
+		assert_ne!(
+			vector_tile_cache_key(
+				TileCoordinates {
+					row: 2,
+					..tile
+				},
+				Some(10),
+				0
+			),
+			key
+		);
+	}
 }
@@ -12,3 +12,60 @@ pub const GRAPHQL_BODY_LIMIT_BYTES: usize = 1024 * 1024;
 // Prevents bursts of password-reset emails to a single account, without
 // causing user enumeration: throttled requests still return success.
 pub const PASSWORD_RESET_RATE_LIMIT_SECONDS: i64 = 60;
+
+// Wrong passwords in a row after which a share link stops taking guesses for
+// `SHARE_LINK_PASSWORD_LOCKOUT_SECONDS`. After that, each guess locks it again
+// until the right password resets the count.
+pub const SHARE_LINK_MAX_PASSWORD_ATTEMPTS: i32 = 5;
+pub const SHARE_LINK_PASSWORD_LOCKOUT_SECONDS: i64 = 15 * 60;
+
+// Longest `User-Agent` kept on a session, in characters; the rest is dropped.
+pub const SESSION_USER_AGENT_MAX_LENGTH: usize = 512;
+
+// Seconds between passes that delete timed-out sessions.
+pub const SESSION_CLEANUP_INTERVAL_SECONDS: u64 = 3600;
+
+// Most near-duplicate pairs returned by one `possibleDuplicates` query. The
+// review queue works through a page at a time.
+pub const POSSIBLE_DUPLICATES_MAX_LIMIT: i32 = 200;
+
+// Widest perceptual hash distance, in bits, at which two images are recorded
+// as possible duplicates when the later one is hashed. `possibleDuplicates`
+// reads those records, so it cannot search wider.
+pub const DUPLICATE_CANDIDATE_MAX_DISTANCE: i32 = 10;
+
+// Most objects returned by one `s3ObjectsConnection` page. Each one is
+// presigned, so pages are kept to what a client shows at once.
+pub const S3_OBJECTS_CONNECTION_MAX_PAGE_SIZE: i32 = 200;
+
+// Longest `searchS3Objects` query accepted, in characters.
+pub const SEARCH_QUERY_MAX_LENGTH: usize = 200;
+
+// Longest object title accepted, in characters, after trimming.
+pub const OBJECT_TITLE_MAX_LENGTH: usize = 200;
+
+// Longest object description or note accepted, in characters.
+pub const OBJECT_TEXT_MAX_LENGTH: usize = 20_000;
+
+// Longest tag name accepted, in characters, after normalizing.
+pub const TAG_MAX_LENGTH: usize = 64;
+
+// Most suggestions one `tags` autocomplete query returns.
+pub const TAG_SUGGESTIONS_MAX_LIMIT: i32 = 50;
+
+// `mapClusters` divides each 256-pixel map tile into this many cells per side,
+// so a cluster stands for roughly a 64-pixel square of the map.
+pub const MAP_CLUSTER_CELLS_PER_TILE: f64 = 4.0;
+
+// Deepest zoom level `mapClusters` accepts; leaflet's tile layers stop well
+// before it.
+pub const MAP_CLUSTER_MAX_ZOOM: i32 = 24;
+
+// How many of a cluster's object ids `mapClusters` returns for previews.
+pub const MAP_CLUSTER_SAMPLE_SIZE: i32 = 4;
+
+// Deepest zoom level `/tiles` serves; past it a tile is smaller than the
+// precision locations are stored with.
+pub const VECTOR_TILE_MAX_ZOOM: i32 = 24;
+
+pub const VECTOR_TILE_CONTENT_TYPE: &str = "application/vnd.mapbox-vector-tile";
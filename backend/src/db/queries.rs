@@ -1,3 +1,21 @@
+/// The location precision people other than the owner see the object row
+/// `$object` at: its own override, or else its owner's setting.
+macro_rules! effective_location_precision {
+	($object:literal) => {
+		concat!(
+			"COALESCE(
+		NULLIF(",
+			$object,
+			".location_precision::TEXT, 'default'),
+		(SELECT object_owner.location_precision::TEXT FROM users object_owner WHERE object_owner.id = ",
+			$object,
+			".user_id),
+		'exact'
+	)::location_precision"
+		)
+	};
+}
+
 /// The `S3Object` column projection shared by the mutation queries that RETURN an
 /// object in a transitional state (and so cannot use the
 /// `available_objects_with_users` view). Single-sourced because every column must
@@ -6,7 +24,11 @@
 /// a string literal so it composes into the query consts via `concat!`.
 macro_rules! object_returning_columns {
 	() => {
-		"id, name, storage_key, content_type, made_on, ST_Y(location::geometry) AS latitude, ST_X(location::geometry) AS longitude, user_id, publicity"
+		concat!(
+			"id, name, storage_key, content_type, made_on, ST_Y(location::geometry) AS latitude, ST_X(location::geometry) AS longitude, user_id, publicity, metadata_privacy, location_precision, ",
+			effective_location_precision!("objects"),
+			" AS effective_location_precision, title, description, note"
+		)
 	};
 }
 
@@ -40,18 +62,51 @@ macro_rules! upload_session_columns {
 	};
 }
 
-pub const MARK_OBJECTS_DELETE_PENDING_QUERY: &str = concat!(
+/// Moves the available objects `$1` to their owners' trash.
+pub const MARK_OBJECTS_TRASHED_QUERY: &str = concat!(
 	"UPDATE objects
-SET storage_state = 'delete_pending', storage_state_updated_at = now()
+SET storage_state = 'trashed', storage_state_updated_at = now(), trashed_at = now()
 WHERE id = ANY($1) AND storage_state = 'available'
 RETURNING ",
 	object_returning_columns!(),
 	";"
 );
 
+/// Takes the object `$1` back out of the trash.
+pub const RESTORE_TRASHED_OBJECT_QUERY: &str = concat!(
+	"UPDATE objects
+SET storage_state = 'available', storage_state_updated_at = now(), trashed_at = NULL
+WHERE id = $1 AND storage_state = 'trashed'
+RETURNING ",
+	object_returning_columns!(),
+	";"
+);
+
+/// Queues everything in user `$1`'s trash for deletion.
+pub const MARK_USER_TRASH_DELETE_PENDING_QUERY: &str = concat!(
+	"UPDATE objects
+SET storage_state = 'delete_pending', storage_state_updated_at = now()
+WHERE user_id = $1 AND storage_state = 'trashed'
+RETURNING ",
+	object_returning_columns!(),
+	";"
+);
+
+/// Queues the objects that have been in the trash for more than `$1` seconds
+/// for deletion.
+pub const MARK_EXPIRED_TRASH_DELETE_PENDING_QUERY: &str = concat!(
+	"UPDATE objects
+SET storage_state = 'delete_pending', storage_state_updated_at = now()
+WHERE storage_state = 'trashed'
+	AND trashed_at < now() - ($1::BIGINT * interval '1 second')
+RETURNING ",
+	object_returning_columns!(),
+	";"
+);
+
 pub const INSERT_OBJECT_QUERY: &str = concat!(
-	"INSERT INTO objects (name, storage_key, content_type, storage_state, made_on, location, user_id, publicity)
-VALUES ($1, $2, $3, 'pending_upload', $4::timestamptz, ST_GeomFromEWKT($5), $6, $7)
+	"INSERT INTO objects (name, storage_key, content_type, storage_state, made_on, location, user_id, publicity, title, description, note)
+VALUES ($1, $2, $3, 'pending_upload', $4::timestamptz, ST_GeomFromEWKT($5), $6, $7, $8, $9, $10)
 RETURNING ",
 	object_returning_columns!(),
 	";"
@@ -126,11 +181,12 @@ RETURNING ",
 pub const DELETE_OBJECT_UPLOAD_SESSION_QUERY: &str =
 	"DELETE FROM object_upload_sessions WHERE object_id = $1";
 
-pub const FINALIZE_OBJECT_UPLOAD_QUERY: &str = "WITH finalized AS (
+pub const FINALIZE_OBJECT_UPLOAD_QUERY: &str = concat!(
+	"WITH finalized AS (
 	UPDATE objects
-	SET storage_state = 'available', storage_state_updated_at = now()
+	SET storage_state = 'available', storage_state_updated_at = now(), content_type = $3
 	WHERE id = $1 AND storage_key = $2 AND storage_state = 'pending_upload'
-	RETURNING id, name, storage_key, content_type, made_on, location, user_id, publicity
+	RETURNING id, name, storage_key, content_type, made_on, location, user_id, publicity, metadata_privacy, location_precision, title, description, note
 )
 SELECT
 	finalized.id,
@@ -142,17 +198,45 @@ SELECT
 	ST_X(finalized.location::geometry) AS longitude,
 	finalized.user_id,
 	finalized.publicity,
+	finalized.metadata_privacy,
+	finalized.location_precision,
+	",
+	effective_location_precision!("finalized"),
+	" AS effective_location_precision,
+	finalized.title,
+	finalized.description,
+	finalized.note,
 	COALESCE((
 		SELECT array_agg(users.email)
 		FROM object_allowed_users allowed
 		JOIN users ON allowed.user_id = users.id
 		WHERE allowed.object_id = finalized.id
-	), '{}') AS allowed_users
-FROM finalized;";
+	), '{}') AS allowed_users,
+	COALESCE((
+		SELECT array_agg(allowed.group_id ORDER BY allowed.group_id)
+		FROM object_allowed_groups allowed
+		WHERE allowed.object_id = finalized.id
+	), '{}') AS allowed_groups,
+	COALESCE((
+		SELECT array_agg(tags.name ORDER BY tags.name)
+		FROM object_tags
+		JOIN tags ON tags.id = object_tags.tag_id
+		WHERE object_tags.object_id = finalized.id
+	), '{}') AS tags
+FROM finalized;"
+);
 
 pub const SELECT_AVAILABLE_OBJECT_FOR_USER_QUERY: &str =
 	"SELECT * FROM available_objects_with_users WHERE id = $1 AND user_id = $2;";
 
+/// The user's oldest available object with the given content hash, if any.
+pub const SELECT_AVAILABLE_OBJECT_BY_CONTENT_HASH_FOR_USER_QUERY: &str = "SELECT available.*
+FROM available_objects_with_users available
+JOIN objects o ON o.id = available.id
+WHERE o.user_id = $1 AND o.content_hash = $2
+ORDER BY available.id
+LIMIT 1;";
+
 pub const DELETE_PENDING_OBJECT_UPLOAD_QUERY: &str = "DELETE FROM objects
 WHERE id = $1
 	AND storage_key = $2
@@ -200,22 +284,70 @@ RETURNING ",
 
 /// Query to update an existing object in the database.
 /// It updates the name, made_on timestamp, and location based on the provided ID.
+/// A NULL `$6` keeps the metadata privacy override and a NULL `$10` the
+/// location precision override; `metadata_privacy_changed` reports whether
+/// either was changed, as its sanitized copy then needs rewriting. A NULL
+/// title `$7`, description `$8` or note `$9` keeps the current one.
 pub const UPDATE_OBJECT_QUERY: &str = concat!(
 	"UPDATE objects
-SET name = $2, made_on = $3::timestamptz, location = ST_GeomFromEWKT($4), publicity = $5
+SET name = $2,
+	made_on = $3::timestamptz,
+	location = ST_GeomFromEWKT($4),
+	publicity = $5,
+	metadata_privacy = COALESCE($6, objects.metadata_privacy),
+	title = COALESCE($7, objects.title),
+	description = COALESCE($8, objects.description),
+	note = COALESCE($9, objects.note),
+	location_precision = COALESCE($10, objects.location_precision)
+FROM (
+	SELECT metadata_privacy AS previous_metadata_privacy,
+		location_precision AS previous_location_precision
+	FROM objects
+	WHERE id = $1
+	FOR UPDATE
+) previous
+WHERE id = $1 AND storage_state = 'available'
+RETURNING ",
+	object_returning_columns!(),
+	",
+	metadata_privacy <> previous_metadata_privacy
+		OR location_precision <> previous_location_precision AS metadata_privacy_changed;"
+);
+
+/// Fills `made_on`/`location` from extracted media metadata, but only where the
+/// uploader left them blank; user-supplied values always win.
+pub const FILL_OBJECT_METADATA_FROM_MEDIA_QUERY: &str = concat!(
+	"UPDATE objects
+SET made_on = COALESCE(made_on, $2::timestamptz),
+	location = COALESCE(location, ST_GeomFromEWKT($3)::geography)
 WHERE id = $1 AND storage_state = 'available'
 RETURNING ",
 	object_returning_columns!(),
 	";"
 );
 
+pub const UPSERT_OBJECT_MEDIA_METADATA_QUERY: &str = "INSERT INTO object_media_metadata (
+	object_id,
+	captured_at,
+	location,
+	raw,
+	extracted_at
+)
+VALUES ($1, $2::timestamptz, ST_GeomFromEWKT($3), $4::TEXT::jsonb, now())
+ON CONFLICT (object_id) DO UPDATE
+SET captured_at = EXCLUDED.captured_at,
+	location = EXCLUDED.location,
+	raw = EXCLUDED.raw,
+	extracted_at = EXCLUDED.extracted_at;";
+
 pub const SELECT_ALL_OBJECTS_QUERY: &str = "SELECT * FROM available_objects_with_users;";
 
 pub const SELECT_OBJECT_BY_ID_QUERY: &str =
 	"SELECT * FROM available_objects_with_users WHERE id = $1;";
 
+/// User `$1`'s object named `$2`.
 pub const SELECT_OBJECT_BY_NAME_QUERY: &str =
-	"SELECT * FROM available_objects_with_users WHERE name = $1;";
+	"SELECT * FROM available_objects_with_users WHERE user_id = $1 AND name = $2;";
 
 pub const SELECT_OBJECTS_BY_IDS_QUERY: &str =
 	"SELECT * FROM available_objects_with_users WHERE id = ANY($1);";
@@ -223,34 +355,664 @@ pub const SELECT_OBJECTS_BY_IDS_QUERY: &str =
 pub const SELECT_OBJECTS_BY_USER_ID_QUERY: &str =
 	"SELECT * FROM available_objects_with_users WHERE user_id = $1;";
 
-pub const SELECT_VISIBLE_OBJECTS_QUERY: &str = "SELECT v.*
-FROM available_objects_with_users v
-JOIN users owner ON owner.id = v.user_id
-WHERE
-	($1::BIGINT IS NOT NULL AND v.user_id = $1)
-	OR v.publicity = 'public'
-	OR (v.publicity = 'default' AND owner.default_publicity = 'public')
+/// The key `s3ObjectsConnection` sorts by capture time on. Undated objects sort
+/// as the earliest, and `'-infinity'` is never read back into a cursor. Must
+/// match the `objects_available_*_made_on_idx` index expressions.
+macro_rules! made_on_sort_key {
+	() => {
+		"COALESCE(o.made_on, '-infinity'::TIMESTAMPTZ)"
+	};
+}
+
+/// The `FROM` and `WHERE` picking out the available objects `$1` (NULL when
+/// signed out) may see, or every one when `$2` is true. Reads `objects`
+/// directly so conditions appended to the `WHERE` reach its indexes; the
+/// aggregate view would group every available object first. `shown.location`
+/// is where the viewer sees each object: exactly when it is theirs or they
+/// may see every object, or else at its effective location precision, so
+/// spatial conditions must match on it rather than on `o.location`. Expands
+/// to a string literal so it composes into the query consts via `concat!`.
+macro_rules! visible_objects_from {
+	() => {
+		concat!(
+			"FROM objects o
+LEFT JOIN users owner ON owner.id = o.user_id
+CROSS JOIN LATERAL (
+	SELECT CASE
+		WHEN $2::BOOLEAN OR ($1::BIGINT IS NOT NULL AND o.user_id = $1) THEN o.location
+		ELSE coarsen_location(o.location, ",
+			effective_location_precision!("o"),
+			")
+	END AS location
+) shown
+WHERE o.storage_state = 'available'
+	AND (
+		$2::BOOLEAN
+		OR ($1::BIGINT IS NOT NULL AND o.user_id = $1)
+		OR o.publicity = 'public'
+		OR (o.publicity = 'default' AND owner.default_publicity = 'public')
+		OR (
+			o.publicity = 'selected_users'
+			AND $1::BIGINT IS NOT NULL
+			AND EXISTS (
+				SELECT 1
+				FROM object_allowed_users allowed
+				WHERE allowed.object_id = o.id
+					AND allowed.user_id = $1
+				UNION ALL
+				SELECT 1
+				FROM object_allowed_groups allowed
+				JOIN user_group_members member ON member.group_id = allowed.group_id
+				WHERE allowed.object_id = o.id
+					AND member.user_id = $1
+			)
+		)
+	)"
+		)
+	};
+}
+
+/// The columns of `available_objects_with_users`, read from `objects o`.
+macro_rules! object_columns {
+	() => {
+		concat!(
+			"o.id, o.name, o.storage_key, o.content_type, o.made_on,
+	ST_Y(o.location::geometry) AS latitude,
+	ST_X(o.location::geometry) AS longitude,
+	o.user_id, o.publicity, o.metadata_privacy, o.location_precision,
+	",
+			effective_location_precision!("o"),
+			" AS effective_location_precision,
+	o.title, o.description, o.note,
+	COALESCE((
+		SELECT array_agg(u.email)
+		FROM object_allowed_users oau
+		JOIN users u ON u.id = oau.user_id
+		WHERE oau.object_id = o.id
+	), '{}') AS allowed_users,
+	COALESCE((
+		SELECT array_agg(oag.group_id ORDER BY oag.group_id)
+		FROM object_allowed_groups oag
+		WHERE oag.object_id = o.id
+	), '{}') AS allowed_groups,
+	COALESCE((
+		SELECT array_agg(t.name ORDER BY t.name)
+		FROM object_tags ot
+		JOIN tags t ON t.id = ot.tag_id
+		WHERE ot.object_id = o.id
+	), '{}') AS tags"
+		)
+	};
+}
+
+/// [`visible_objects_from!`] with the same columns as
+/// `available_objects_with_users`.
+macro_rules! visible_objects_select {
+	() => {
+		concat!("SELECT ", object_columns!(), "\n", visible_objects_from!())
+	};
+}
+
+/// The `S3ObjectFilter` conditions to append to [`visible_objects_from!`], one
+/// parameter per field in the order the filter declares them: owner id, media
+/// type, earliest and latest capture time, publicity, whether located (as the
+/// viewer sees it) and the tags an object must all have. A NULL parameter
+/// leaves its field unfiltered.
+/// Queries place the filter at different parameter numbers, so they pass
+/// theirs in.
+macro_rules! object_filter_conditions {
+	(
+		$owner_id:literal,
+		$media_type:literal,
+		$made_on_from:literal,
+		$made_on_to:literal,
+		$publicity:literal,
+		$has_location:literal,
+		$tags:literal
+	) => {
+		concat!(
+			"
+	AND (",
+			$owner_id,
+			"::BIGINT IS NULL OR o.user_id = ",
+			$owner_id,
+			")
+	AND (",
+			$media_type,
+			"::TEXT IS NULL OR split_part(o.content_type, '/', 1) = ",
+			$media_type,
+			")
+	AND (",
+			$made_on_from,
+			"::TIMESTAMPTZ IS NULL OR ",
+			made_on_sort_key!(),
+			" >= ",
+			$made_on_from,
+			")
+	AND (",
+			$made_on_to,
+			"::TIMESTAMPTZ IS NULL OR (o.made_on IS NOT NULL AND ",
+			made_on_sort_key!(),
+			" <= ",
+			$made_on_to,
+			"))
+	AND (",
+			$publicity,
+			"::publicity_override IS NULL OR o.publicity = ",
+			$publicity,
+			")
+	AND (",
+			$has_location,
+			"::BOOLEAN IS NULL OR (shown.location IS NOT NULL) = ",
+			$has_location,
+			")
+	AND (",
+			$tags,
+			"::TEXT[] IS NULL OR cardinality(",
+			$tags,
+			") = (
+		SELECT count(*)
+		FROM object_tags ot
+		JOIN tags t ON t.id = ot.tag_id
+		WHERE ot.object_id = o.id
+			AND t.name = ANY(",
+			$tags,
+			")
+	))"
+		)
+	};
+}
+
+/// One page of `s3ObjectsConnection`: `$3` to `$9` are the filters (NULL when
+/// unset) and `$10` the row limit. Each ordering appends its keyset condition on
+/// the cursor (`$11` onward, NULL on the first page) and its `ORDER BY`.
+macro_rules! object_page_query {
+	($keyset:expr, $order_by:expr) => {
+		concat!(
+			visible_objects_select!(),
+			object_filter_conditions!("$3", "$4", "$5", "$6", "$7", "$8", "$9"),
+			"
+	AND ",
+			$keyset,
+			"
+ORDER BY ",
+			$order_by,
+			"
+LIMIT $10;"
+		)
+	};
+}
+
+/// Latest capture time first. `$11` and `$12` are the cursor's id and capture
+/// time.
+pub const SELECT_OBJECT_PAGE_BY_MADE_ON_DESC_QUERY: &str = object_page_query!(
+	concat!(
+		"($11::BIGINT IS NULL OR (",
+		made_on_sort_key!(),
+		", o.id) < (COALESCE($12::TIMESTAMPTZ, '-infinity'::TIMESTAMPTZ), $11))"
+	),
+	concat!(made_on_sort_key!(), " DESC, o.id DESC")
+);
+
+/// Earliest capture time first. `$11` and `$12` are the cursor's id and capture
+/// time.
+pub const SELECT_OBJECT_PAGE_BY_MADE_ON_ASC_QUERY: &str = object_page_query!(
+	concat!(
+		"($11::BIGINT IS NULL OR (",
+		made_on_sort_key!(),
+		", o.id) > (COALESCE($12::TIMESTAMPTZ, '-infinity'::TIMESTAMPTZ), $11))"
+	),
+	concat!(made_on_sort_key!(), " ASC, o.id ASC")
+);
+
+/// Most recently uploaded first. `$11` is the cursor's id.
+pub const SELECT_OBJECT_PAGE_BY_ID_DESC_QUERY: &str =
+	object_page_query!("($11::BIGINT IS NULL OR o.id < $11)", "o.id DESC");
+
+/// Earliest uploaded first. `$11` is the cursor's id.
+pub const SELECT_OBJECT_PAGE_BY_ID_ASC_QUERY: &str =
+	object_page_query!("($11::BIGINT IS NULL OR o.id > $11)", "o.id ASC");
+
+/// How many degrees `coarsen_location` moves a location by at most: half its
+/// widest cell, with room to spare. Conditions on `o.location` widened by it
+/// let the spatial indexes narrow down a search on `shown.location`.
+macro_rules! coarsening_margin_degrees {
+	() => {
+		"0.1"
+	};
+}
+
+/// [`coarsening_margin_degrees!`] in meters, allowing for a diagonal move.
+macro_rules! coarsening_margin_meters {
+	() => {
+		"16000"
+	};
+}
+
+/// Whether `$location` lies within the box from `$3` south, `$4` west to `$5`
+/// north, `$6` east, grown by `$margin` degrees on every side. A box whose west
+/// edge is east of its east edge crosses the antimeridian and is matched as
+/// the two boxes either side of it. Compares the location as a geometry so the
+/// box edges follow lines of latitude, as they do on the map, rather than
+/// great circles.
+macro_rules! location_in_bounds {
+	($location:literal, $margin:expr) => {
+		concat!(
+			"(
+		(
+			$4::DOUBLE PRECISION <= $6::DOUBLE PRECISION
+			AND ",
+			$location,
+			"::geometry && ST_Expand(ST_MakeEnvelope($4, $3, $6, $5, 4326), ",
+			$margin,
+			")
+		)
+		OR (
+			$4::DOUBLE PRECISION > $6::DOUBLE PRECISION
+			AND (
+				",
+			$location,
+			"::geometry && ST_Expand(ST_MakeEnvelope($4, $3, 180, $5, 4326), ",
+			$margin,
+			")
+				OR ",
+			$location,
+			"::geometry && ST_Expand(ST_MakeEnvelope(-180, $3, $6, $5, 4326), ",
+			$margin,
+			")
+			)
+		)
+	)"
+		)
+	};
+}
+
+/// Restricts [`visible_objects_from!`] to objects the viewer sees within the
+/// box `$3` to `$6` (see [`location_in_bounds!`]). The condition on the exact
+/// location only lets the spatial index narrow the search.
+macro_rules! in_bounds_condition {
+	() => {
+		concat!(
+			"
+	AND ",
+			location_in_bounds!("o.location", coarsening_margin_degrees!()),
+			"
+	AND ",
+			location_in_bounds!("shown.location", "0")
+		)
+	};
+}
+
+/// The objects `$1` (NULL when signed out) may see, or every one when `$2` is
+/// true.
+pub const SELECT_VISIBLE_OBJECTS_QUERY: &str =
+	concat!(visible_objects_select!(), "\nORDER BY o.id;");
+
+/// Visible objects located within the box `$3` to `$6` and matching the filter
+/// `$7` to `$13`.
+pub const SELECT_VISIBLE_OBJECTS_IN_BOUNDS_QUERY: &str = concat!(
+	visible_objects_select!(),
+	in_bounds_condition!(),
+	object_filter_conditions!("$7", "$8", "$9", "$10", "$11", "$12", "$13"),
+	"\nORDER BY o.id;"
+);
+
+/// Visible objects within the box `$3` to `$6` and matching the filter `$9` to
+/// `$15`, grouped into square cells `$7` degrees across, most populous first.
+/// Each cluster carries up to `$8` of its objects' ids, latest capture time
+/// first.
+pub const SELECT_MAP_CLUSTERS_QUERY: &str = concat!(
+	"SELECT count(*) AS count,
+	avg(ST_Y(shown.location::geometry)) AS latitude,
+	avg(ST_X(shown.location::geometry)) AS longitude,
+	min(ST_Y(shown.location::geometry)) AS south,
+	min(ST_X(shown.location::geometry)) AS west,
+	max(ST_Y(shown.location::geometry)) AS north,
+	max(ST_X(shown.location::geometry)) AS east,
+	min(o.made_on) AS earliest_made_on,
+	max(o.made_on) AS latest_made_on,
+	(array_agg(o.id ORDER BY o.made_on DESC NULLS LAST, o.id DESC))[1:$8::INTEGER] AS object_ids
+",
+	visible_objects_from!(),
+	in_bounds_condition!(),
+	object_filter_conditions!("$9", "$10", "$11", "$12", "$13", "$14", "$15"),
+	"
+GROUP BY
+	floor(ST_X(shown.location::geometry) / $7::DOUBLE PRECISION),
+	floor(ST_Y(shown.location::geometry) / $7::DOUBLE PRECISION)
+ORDER BY count(*) DESC, min(o.id);"
+);
+
+/// How many visible objects matching the filter `$4` to `$10` were made in each
+/// `$3` (a `date_trunc` field) of UTC time, and the latest of their capture
+/// times, earliest bucket first. Undated objects are left out.
+pub const SELECT_TIMELINE_HISTOGRAM_QUERY: &str = concat!(
+	"SELECT date_trunc($3::TEXT, o.made_on, 'UTC') AS bucket_start,
+	count(*) AS count,
+	max(o.made_on) AS latest_made_on
+",
+	visible_objects_from!(),
+	"
+	AND o.made_on IS NOT NULL",
+	object_filter_conditions!("$4", "$5", "$6", "$7", "$8", "$9", "$10"),
+	"
+GROUP BY bucket_start
+ORDER BY bucket_start;"
+);
+
+/// The located objects visible in Web Mercator tile `$3`/`$4`/`$5` (zoom,
+/// column, row) as a Mapbox Vector Tile with one `objects` layer. Each point
+/// carries the object's `id`, its `content_type_family` (the part of the content
+/// type before the slash) and its `made_on` in Unix seconds, left out when
+/// unknown. Points within the 64-unit buffer around the tile are included so
+/// markers drawn across a tile edge are not cut off.
+pub const SELECT_VECTOR_TILE_QUERY: &str = concat!(
+	"SELECT ST_AsMVT(tile_points, 'objects', 4096, 'geom') AS tile
+FROM (
+	SELECT
+		ST_AsMVTGeom(
+			ST_Transform(shown.location::geometry, 3857),
+			ST_TileEnvelope($3::INTEGER, $4::INTEGER, $5::INTEGER),
+			4096,
+			64,
+			true
+		) AS geom,
+		o.id,
+		split_part(o.content_type, '/', 1) AS content_type_family,
+		extract(epoch FROM o.made_on)::BIGINT AS made_on
+",
+	visible_objects_from!(),
+	"
+		AND o.location::geometry && ST_Expand(
+			ST_Transform(
+				ST_TileEnvelope($3::INTEGER, $4::INTEGER, $5::INTEGER, margin => 64.0 / 4096),
+				4326
+			),
+			",
+	coarsening_margin_degrees!(),
+	"
+		)
+		AND shown.location::geometry && ST_Transform(
+			ST_TileEnvelope($3::INTEGER, $4::INTEGER, $5::INTEGER, margin => 64.0 / 4096),
+			4326
+		)
+) AS tile_points;"
+);
+
+/// Whether the searcher `$1` may read the note on `o`: their own notes, or every
+/// one when `$2` lets them read all objects.
+macro_rules! readable_note {
+	() => {
+		"($2::BOOLEAN OR o.user_id = $1)"
+	};
+}
+
+/// How well an object matches a search, higher being better: the weight of its
+/// name, title, description and content type words prefixed by the
+/// `to_tsquery` text `$3`, and of its note's words when the searcher may read
+/// the note (see [`readable_note!`]), how closely a run of its name's words
+/// resembles the search text `$4`, and a fixed bonus when its owner's email
+/// matches the `ILIKE` pattern `$5`.
+macro_rules! search_rank {
+	() => {
+		concat!(
+			"(
+		ts_rank(o.search_document, to_tsquery('simple', $3))
+		+ CASE WHEN ",
+			readable_note!(),
+			" THEN ts_rank(o.note_search_document, to_tsquery('simple', $3)) ELSE 0 END
+		+ word_similarity($4, o.name)
+		+ CASE WHEN owner.email ILIKE $5 THEN 0.5 ELSE 0 END
+	)::DOUBLE PRECISION"
+		)
+	};
+}
+
+/// Visible objects matching the search `$3` to `$5` (see [`search_rank!`]) on
+/// any of their words, their name or their owner, best match first and then
+/// latest uploaded. `$6` is the row limit and `$7`, `$8` the cursor's id and
+/// rank, NULL on the first page.
+pub const SEARCH_VISIBLE_OBJECTS_QUERY: &str = concat!(
+	"SELECT ",
+	object_columns!(),
+	",
+	",
+	search_rank!(),
+	" AS rank
+",
+	visible_objects_from!(),
+	"
+	AND (
+		o.search_document @@ to_tsquery('simple', $3)
+		OR (",
+	readable_note!(),
+	" AND o.note_search_document @@ to_tsquery('simple', $3))
+		OR $4 <% o.name
+		OR owner.email ILIKE $5
+	)
+	AND ($7::BIGINT IS NULL OR (",
+	search_rank!(),
+	", o.id) < ($8::DOUBLE PRECISION, $7))
+ORDER BY rank DESC, o.id DESC
+LIMIT $6;"
+);
+
+/// Visible objects the viewer sees within `$4` meters of the EWKT point `$3`,
+/// nearest first. The condition on the exact location only lets the spatial
+/// index narrow the search.
+pub const SELECT_VISIBLE_OBJECTS_NEAR_QUERY: &str = concat!(
+	visible_objects_select!(),
+	"
+	AND ST_DWithin(o.location, ST_GeomFromEWKT($3)::geography, $4::DOUBLE PRECISION + ",
+	coarsening_margin_meters!(),
+	")
+	AND ST_DWithin(shown.location, ST_GeomFromEWKT($3)::geography, $4::DOUBLE PRECISION)
+ORDER BY ST_Distance(shown.location, ST_GeomFromEWKT($3)::geography), o.id;"
+);
+
+pub const DELETE_OBJECT_ALLOWED_USERS_QUERY: &str =
+	"DELETE FROM object_allowed_users WHERE object_id = $1";
+
+pub const REPLACE_OBJECT_ALLOWED_USERS_QUERY: &str = "WITH valid AS (
+	SELECT id, email
+	FROM users
+	WHERE email = ANY($2)
+),
+inserted AS (
+	INSERT INTO object_allowed_users (object_id, user_id)
+	SELECT $1, id
+	FROM valid
+	RETURNING user_id
+)
+SELECT valid.email
+FROM valid
+JOIN inserted ON inserted.user_id = valid.id";
+
+pub const DELETE_OBJECT_ALLOWED_GROUPS_QUERY: &str =
+	"DELETE FROM object_allowed_groups WHERE object_id = $1";
+
+/// Shares object `$1` with those of the groups `$2` its owner has, returning
+/// their ids.
+pub const REPLACE_OBJECT_ALLOWED_GROUPS_QUERY: &str =
+	"INSERT INTO object_allowed_groups (object_id, group_id)
+SELECT o.id, g.id
+FROM objects o
+JOIN user_groups g ON g.user_id = o.user_id
+WHERE o.id = $1 AND g.id = ANY($2)
+RETURNING group_id";
+
+/// The columns of an album row, read from `albums a`, with the emails of the
+/// users and the ids of the groups a `selected_users` album is shared with.
+macro_rules! album_columns {
+	() => {
+		"a.id, a.user_id, a.title, a.description, a.cover_object_id, a.publicity,
+	a.created_at, a.updated_at,
+	COALESCE((
+		SELECT array_agg(u.email)
+		FROM album_allowed_users aau
+		JOIN users u ON u.id = aau.user_id
+		WHERE aau.album_id = a.id
+	), '{}') AS allowed_users,
+	COALESCE((
+		SELECT array_agg(aag.group_id ORDER BY aag.group_id)
+		FROM album_allowed_groups aag
+		WHERE aag.album_id = a.id
+	), '{}') AS allowed_groups"
+	};
+}
+
+/// The `FROM` and `WHERE` picking out the albums `$1` (NULL when signed out)
+/// may see, or every one when `$2` is true. The same rules as
+/// [`visible_objects_from!`], applied to the album's own publicity.
+macro_rules! visible_albums_from {
+	() => {
+		"FROM albums a
+JOIN users owner ON owner.id = a.user_id
+WHERE (
+	$2::BOOLEAN
+	OR ($1::BIGINT IS NOT NULL AND a.user_id = $1)
+	OR a.publicity = 'public'
+	OR (a.publicity = 'default' AND owner.default_publicity = 'public')
 	OR (
-		v.publicity = 'selected_users'
+		a.publicity = 'selected_users'
 		AND $1::BIGINT IS NOT NULL
 		AND EXISTS (
 			SELECT 1
-			FROM object_allowed_users allowed
-			WHERE allowed.object_id = v.id
+			FROM album_allowed_users allowed
+			WHERE allowed.album_id = a.id
 				AND allowed.user_id = $1
+			UNION ALL
+			SELECT 1
+			FROM album_allowed_groups allowed
+			JOIN user_group_members member ON member.group_id = allowed.group_id
+			WHERE allowed.album_id = a.id
+				AND member.user_id = $1
 		)
-	);";
+	)
+)"
+	};
+}
 
-pub const DELETE_OBJECT_ALLOWED_USERS_QUERY: &str =
-	"DELETE FROM object_allowed_users WHERE object_id = $1";
+pub const SELECT_ALBUM_BY_ID_QUERY: &str =
+	concat!("SELECT ", album_columns!(), "\nFROM albums a\nWHERE a.id = $1;");
 
-pub const REPLACE_OBJECT_ALLOWED_USERS_QUERY: &str = "WITH valid AS (
+/// Album `$3`, if `$1` may see it.
+pub const SELECT_VISIBLE_ALBUM_BY_ID_QUERY: &str =
+	concat!("SELECT ", album_columns!(), "\n", visible_albums_from!(), "\n\tAND a.id = $3;");
+
+/// The albums `$1` may see, owned by `$3` when it is not NULL, newest first.
+pub const SELECT_VISIBLE_ALBUMS_QUERY: &str = concat!(
+	"SELECT ",
+	album_columns!(),
+	"\n",
+	visible_albums_from!(),
+	"
+	AND ($3::BIGINT IS NULL OR a.user_id = $3)
+ORDER BY a.id DESC;"
+);
+
+pub const INSERT_ALBUM_QUERY: &str = "INSERT INTO albums (user_id, title, description, publicity)
+VALUES ($1, $2, $3, $4)
+RETURNING id";
+
+/// Updates album `$1`, unless the cover `$4` is not one of its objects, in
+/// which case no row is updated.
+pub const UPDATE_ALBUM_QUERY: &str = "UPDATE albums
+SET title = $2, description = $3, cover_object_id = $4, publicity = $5, updated_at = now()
+WHERE id = $1
+	AND (
+		$4::BIGINT IS NULL
+		OR EXISTS (
+			SELECT 1
+			FROM album_objects
+			WHERE album_id = $1
+				AND object_id = $4
+		)
+	)";
+
+pub const DELETE_ALBUM_QUERY: &str = "DELETE FROM albums WHERE id = $1";
+
+/// Serializes changes to album `$1`'s objects, so appended objects take
+/// positions after every other.
+pub const LOCK_ALBUM_FOR_UPDATE_QUERY: &str = "SELECT id FROM albums WHERE id = $1 FOR UPDATE";
+
+/// Appends the objects `$2` to album `$1` in the order given, skipping those
+/// already in it.
+pub const INSERT_ALBUM_OBJECTS_QUERY: &str =
+	"INSERT INTO album_objects (album_id, object_id, position)
+SELECT
+	$1,
+	added.object_id,
+	(
+		COALESCE((SELECT max(position) FROM album_objects WHERE album_id = $1), 0)
+		+ added.ordinality
+	)::INTEGER
+FROM unnest($2::BIGINT[]) WITH ORDINALITY AS added(object_id, ordinality)
+ON CONFLICT (album_id, object_id) DO NOTHING";
+
+/// Removes the objects `$2` from album `$1`, clearing the cover if it was one
+/// of them.
+pub const DELETE_ALBUM_OBJECTS_QUERY: &str = "WITH removed AS (
+	DELETE FROM album_objects
+	WHERE album_id = $1
+		AND object_id = ANY($2)
+	RETURNING object_id
+)
+UPDATE albums
+SET cover_object_id = NULL, updated_at = now()
+WHERE id = $1
+	AND cover_object_id IN (SELECT object_id FROM removed)";
+
+/// The ids of album `$1`'s available objects.
+pub const SELECT_ALBUM_OBJECT_IDS_QUERY: &str = "SELECT ao.object_id
+FROM album_objects ao
+JOIN objects o ON o.id = ao.object_id
+WHERE ao.album_id = $1
+	AND o.storage_state = 'available'";
+
+/// Numbers album `$1`'s objects by their place in `$2`.
+pub const REORDER_ALBUM_OBJECTS_QUERY: &str = "UPDATE album_objects ao
+SET position = ordered.position::INTEGER
+FROM unnest($2::BIGINT[]) WITH ORDINALITY AS ordered(object_id, position)
+WHERE ao.album_id = $1
+	AND ao.object_id = ordered.object_id";
+
+/// Album `$1`'s available objects in album order.
+pub const SELECT_ALBUM_OBJECTS_QUERY: &str = concat!(
+	"SELECT ",
+	object_columns!(),
+	"
+FROM album_objects ao
+JOIN objects o ON o.id = ao.object_id
+WHERE ao.album_id = $1
+	AND o.storage_state = 'available'
+ORDER BY ao.position, o.id;"
+);
+
+/// Album `$1`'s cover: the object chosen as it, or else the first one.
+pub const SELECT_ALBUM_COVER_QUERY: &str = concat!(
+	"SELECT ",
+	object_columns!(),
+	"
+FROM albums a
+JOIN album_objects ao ON ao.album_id = a.id
+JOIN objects o ON o.id = ao.object_id
+WHERE a.id = $1
+	AND o.storage_state = 'available'
+ORDER BY (o.id = a.cover_object_id) IS TRUE DESC, ao.position, o.id
+LIMIT 1;"
+);
+
+pub const DELETE_ALBUM_ALLOWED_USERS_QUERY: &str =
+	"DELETE FROM album_allowed_users WHERE album_id = $1";
+
+pub const REPLACE_ALBUM_ALLOWED_USERS_QUERY: &str = "WITH valid AS (
 	SELECT id, email
 	FROM users
 	WHERE email = ANY($2)
 ),
 inserted AS (
-	INSERT INTO object_allowed_users (object_id, user_id)
+	INSERT INTO album_allowed_users (album_id, user_id)
 	SELECT $1, id
 	FROM valid
 	RETURNING user_id
@@ -259,14 +1021,125 @@ SELECT valid.email
 FROM valid
 JOIN inserted ON inserted.user_id = valid.id";
 
-pub const SELECT_ALL_USERS_QUERY: &str =
-	"SELECT id, email, role, created_at, updated_at, default_publicity FROM users";
+pub const DELETE_ALBUM_ALLOWED_GROUPS_QUERY: &str =
+	"DELETE FROM album_allowed_groups WHERE album_id = $1";
+
+/// Shares album `$1` with those of the groups `$2` its owner has, returning
+/// their ids.
+pub const REPLACE_ALBUM_ALLOWED_GROUPS_QUERY: &str =
+	"INSERT INTO album_allowed_groups (album_id, group_id)
+SELECT a.id, g.id
+FROM albums a
+JOIN user_groups g ON g.user_id = a.user_id
+WHERE a.id = $1 AND g.id = ANY($2)
+RETURNING group_id";
+
+/// Creates whichever of the tags `$2` the owners of the objects `$1` do not
+/// have yet.
+pub const INSERT_TAGS_QUERY: &str = "INSERT INTO tags (user_id, name)
+SELECT DISTINCT o.user_id, names.name
+FROM objects o
+CROSS JOIN unnest($2::TEXT[]) AS names(name)
+WHERE o.id = ANY($1)
+	AND o.user_id IS NOT NULL
+ON CONFLICT (user_id, name) DO NOTHING";
+
+/// Tags the objects `$1` with their owners' tags named in `$2`.
+pub const INSERT_OBJECT_TAGS_QUERY: &str = "INSERT INTO object_tags (object_id, tag_id)
+SELECT o.id, t.id
+FROM objects o
+JOIN tags t ON t.user_id = o.user_id
+WHERE o.id = ANY($1)
+	AND t.name = ANY($2)
+ON CONFLICT (object_id, tag_id) DO NOTHING";
+
+/// Takes the tags named in `$2` off the objects `$1`.
+pub const DELETE_OBJECT_TAGS_QUERY: &str = "DELETE FROM object_tags ot
+USING tags t
+WHERE t.id = ot.tag_id
+	AND ot.object_id = ANY($1)
+	AND t.name = ANY($2)";
+
+/// Takes every tag off the objects `$1`.
+pub const DELETE_ALL_OBJECT_TAGS_QUERY: &str = "DELETE FROM object_tags WHERE object_id = ANY($1)";
+
+pub const SELECT_OBJECT_TAG_NAMES_QUERY: &str = "SELECT t.name
+FROM object_tags ot
+JOIN tags t ON t.id = ot.tag_id
+WHERE ot.object_id = $1
+ORDER BY t.name";
+
+/// Up to `$3` of user `$1`'s tags starting with the `LIKE` pattern `$2`, most
+/// used first. Tags left on no available object are not suggested.
+pub const SELECT_TAG_SUGGESTIONS_QUERY: &str = "SELECT t.name, count(*) AS object_count
+FROM tags t
+JOIN object_tags ot ON ot.tag_id = t.id
+JOIN objects o ON o.id = ot.object_id
+WHERE t.user_id = $1
+	AND t.name LIKE $2
+	AND o.storage_state = 'available'
+GROUP BY t.name
+ORDER BY object_count DESC, t.name
+LIMIT $3";
+
+/// Locks an available object against concurrent edits while its metadata is
+/// read, changed and recorded as a revision.
+pub const LOCK_AVAILABLE_OBJECT_FOR_UPDATE_QUERY: &str =
+	"SELECT id FROM objects WHERE id = $1 AND storage_state = 'available' FOR UPDATE";
+
+/// Records user `$2`'s edit of object `$1` as its next revision. `$3` and `$4`
+/// are the changed fields' old and new values as JSON objects.
+pub const INSERT_OBJECT_REVISION_QUERY: &str = "INSERT INTO object_revisions (
+	object_id,
+	revision,
+	user_id,
+	old_values,
+	new_values
+)
+SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3::TEXT::jsonb, $4::TEXT::jsonb
+FROM object_revisions
+WHERE object_id = $1";
+
+/// The revisions of object `$1`, newest first, with their editors' emails.
+pub const SELECT_OBJECT_REVISIONS_QUERY: &str = "SELECT
+	r.revision,
+	u.email AS editor_email,
+	r.created_at,
+	r.old_values::TEXT AS old_values,
+	r.new_values::TEXT AS new_values
+FROM object_revisions r
+LEFT JOIN users u ON u.id = r.user_id
+WHERE r.object_id = $1
+ORDER BY r.revision DESC";
+
+/// The old values of the fields revision `$2` of object `$1` changed.
+pub const SELECT_OBJECT_REVISION_OLD_VALUES_QUERY: &str = "SELECT old_values::TEXT AS old_values FROM object_revisions WHERE object_id = $1 AND revision = $2";
+
+/// User `$1`'s trashed objects, most recently deleted first, with when each
+/// was deleted.
+pub const SELECT_TRASHED_OBJECTS_FOR_USER_QUERY: &str = concat!(
+	"SELECT ",
+	object_columns!(),
+	", o.trashed_at
+FROM objects o
+WHERE o.user_id = $1 AND o.storage_state = 'trashed'
+ORDER BY o.trashed_at DESC, o.id DESC"
+);
+
+/// The objects `$1` that are in the trash.
+pub const SELECT_TRASHED_OBJECTS_BY_IDS_QUERY: &str = concat!(
+	"SELECT ",
+	object_columns!(),
+	"
+FROM objects o
+WHERE o.id = ANY($1) AND o.storage_state = 'trashed'"
+);
 
-pub const SELECT_USER_BY_ID_QUERY: &str =
-	"SELECT id, email, role, created_at, updated_at, default_publicity FROM users WHERE id = $1";
+pub const SELECT_ALL_USERS_QUERY: &str = "SELECT id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision FROM users";
 
-pub const SELECT_USER_BY_EMAIL_QUERY: &str =
-	"SELECT id, email, role, created_at, updated_at, default_publicity FROM users WHERE email = $1";
+pub const SELECT_USER_BY_ID_QUERY: &str = "SELECT id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision FROM users WHERE id = $1";
+
+pub const SELECT_USER_BY_EMAIL_QUERY: &str = "SELECT id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision FROM users WHERE email = $1";
 
 pub const SELECT_USER_ID_BY_EMAIL_FOR_UPDATE_QUERY: &str =
 	"SELECT id FROM users WHERE email = $1 FOR UPDATE";
@@ -323,18 +1196,35 @@ pub const SELECT_USER_COUNT_BY_EMAIL_EXCLUDING_ID_QUERY: &str =
 pub const SELECT_USER_PASSWORD_HASH_BY_ID_QUERY: &str =
 	"SELECT password_hash FROM users WHERE id = $1";
 
-pub const SELECT_USER_WITH_PASSWORD_BY_EMAIL_QUERY: &str = "SELECT id, email, password_hash, role, created_at, updated_at, default_publicity FROM users WHERE email = $1";
+pub const SELECT_USER_WITH_PASSWORD_BY_EMAIL_QUERY: &str = "SELECT id, email, password_hash, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision FROM users WHERE email = $1";
+
+pub const INSERT_USER_QUERY: &str = "INSERT INTO users (email, password_hash) VALUES ($1, $2) RETURNING id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision";
+
+pub const UPDATE_USER_PUBLICITY_QUERY: &str = "UPDATE users SET default_publicity = $1, updated_at = now() WHERE id = $2 RETURNING id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision";
 
-pub const INSERT_USER_QUERY: &str = "INSERT INTO users (email, password_hash) VALUES ($1, $2) RETURNING id, email, role, created_at, updated_at, default_publicity";
+pub const UPDATE_USER_LOCATION_PRECISION_QUERY: &str = "UPDATE users SET location_precision = $1, updated_at = now() WHERE id = $2 RETURNING id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision";
 
-pub const UPDATE_USER_PUBLICITY_QUERY: &str = "UPDATE users SET default_publicity = $1, updated_at = now() WHERE id = $2 RETURNING id, email, role, created_at, updated_at, default_publicity";
+pub const UPDATE_USER_METADATA_PRIVACY_QUERY: &str = "UPDATE users SET metadata_privacy = $1, updated_at = now() WHERE id = $2 RETURNING id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision";
+
+/// Queues sanitized-copy jobs for the user's available objects that follow
+/// their metadata privacy or location precision setting. An object already
+/// waiting for a full job keeps it, since that rewrites the copy too.
+pub const INSERT_USER_DEFAULT_PRIVACY_DERIVATIVE_JOBS_QUERY: &str =
+	"INSERT INTO object_derivative_jobs (object_id, sanitized_only)
+SELECT id, true
+FROM objects
+WHERE user_id = $1
+	AND storage_state = 'available'
+	AND (metadata_privacy = 'default' OR location_precision = 'default')
+	AND content_type = ANY($2)
+ON CONFLICT (object_id) DO NOTHING";
 
 pub const UPDATE_USER_PASSWORD_QUERY: &str =
 	"UPDATE users SET password_hash = $1, updated_at = now() WHERE id = $2";
 
-pub const UPDATE_USER_EMAIL_QUERY: &str = "UPDATE users SET email = $1, updated_at = now() WHERE id = $2 RETURNING id, email, role, created_at, updated_at, default_publicity";
+pub const UPDATE_USER_EMAIL_QUERY: &str = "UPDATE users SET email = $1, updated_at = now() WHERE id = $2 RETURNING id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision";
 
-pub const ADMIN_UPDATE_USER_QUERY: &str = "UPDATE users SET role = $1, email = $2, updated_at = now() WHERE id = $3 RETURNING id, email, role, created_at, updated_at, default_publicity";
+pub const ADMIN_UPDATE_USER_QUERY: &str = "UPDATE users SET role = $1, email = $2, updated_at = now() WHERE id = $3 RETURNING id, email, role, created_at, updated_at, default_publicity, metadata_privacy, location_precision";
 
 pub const INSERT_PASSWORD_RESET_TOKEN_QUERY: &str = "INSERT INTO password_reset_tokens (token, user_id, expires_at) VALUES ($1, $2, now() + interval '10 minutes')";
 
@@ -388,3 +1278,498 @@ pub const MARK_EMAIL_OUTBOX_FAILED_QUERY: &str = "UPDATE email_outbox
 SET next_attempt_at = now() + ($3::BIGINT * interval '1 second'),
 	last_error = $2
 WHERE id = ANY($1)";
+
+/// The metadata privacy the original of `o` (with its owner joined as
+/// `owner`) is served to other people with: its own override, or else its
+/// owner's setting, but withholding at least the GPS position when they do
+/// not see the exact location.
+macro_rules! effective_metadata_privacy {
+	() => {
+		concat!(
+			"CASE
+		WHEN COALESCE(NULLIF(o.metadata_privacy::TEXT, 'default'), owner.metadata_privacy::TEXT, 'keep') = 'keep'
+			AND ",
+			effective_location_precision!("o"),
+			" <> 'exact'
+		THEN 'strip_location'
+		ELSE COALESCE(NULLIF(o.metadata_privacy::TEXT, 'default'), owner.metadata_privacy::TEXT, 'keep')
+	END::metadata_privacy"
+		)
+	};
+}
+
+/// Queues a full derivative job for object `$1`, widening a sanitized-copy
+/// job already waiting for it.
+pub const INSERT_OBJECT_DERIVATIVE_JOB_QUERY: &str =
+	"INSERT INTO object_derivative_jobs (object_id)
+VALUES ($1)
+ON CONFLICT (object_id) DO UPDATE SET sanitized_only = false";
+
+/// Queues a job rewriting only object `$1`'s sanitized copy, unless a job is
+/// already waiting for it.
+pub const INSERT_OBJECT_SANITIZED_COPY_JOB_QUERY: &str =
+	"INSERT INTO object_derivative_jobs (object_id, sanitized_only)
+VALUES ($1, true)
+ON CONFLICT (object_id) DO NOTHING";
+
+/// Claims up to `$1` derivative jobs whose scheduled retry/lease time has
+/// arrived and which still have retry budget left, returning the source object's
+/// storage key and content type. Rows past `$3::INTEGER` attempts are parked with
+/// their last error for operator triage.
+pub const CLAIM_OBJECT_DERIVATIVE_JOBS_QUERY: &str = concat!(
+	"WITH claimed AS MATERIALIZED (
+	SELECT object_id
+	FROM object_derivative_jobs
+	WHERE attempts < $3::INTEGER
+		AND next_attempt_at <= now()
+	ORDER BY next_attempt_at, created_at
+	LIMIT $1
+	FOR UPDATE SKIP LOCKED
+)
+UPDATE object_derivative_jobs job
+SET attempts = attempts + 1,
+	last_attempt_at = now(),
+	next_attempt_at = now() + ($2::BIGINT * interval '1 second'),
+	last_error = NULL
+FROM claimed, objects o
+LEFT JOIN users owner ON owner.id = o.user_id
+WHERE job.object_id = claimed.object_id
+	AND o.id = job.object_id
+RETURNING
+	job.object_id,
+	job.sanitized_only,
+	o.storage_key,
+	o.content_type,
+	o.storage_state = 'available' AS available,
+	o.content_hash,
+	EXISTS (
+		SELECT 1
+		FROM object_derivatives rendition
+		WHERE rendition.object_id = o.id AND rendition.kind <> 'sanitized'
+	) AS rendered,
+	",
+	effective_metadata_privacy!(),
+	" AS metadata_privacy"
+);
+
+pub const DELETE_OBJECT_DERIVATIVE_JOBS_QUERY: &str =
+	"DELETE FROM object_derivative_jobs WHERE object_id = ANY($1)";
+
+pub const MARK_OBJECT_DERIVATIVE_JOBS_FAILED_QUERY: &str = "UPDATE object_derivative_jobs
+SET next_attempt_at = now() + ($3::BIGINT * interval '1 second'),
+	last_error = $2
+WHERE object_id = ANY($1)";
+
+/// Locks an available object against a concurrent delete while its derivative
+/// rows are recorded. A delete that commits first leaves no row to lock.
+pub const LOCK_AVAILABLE_OBJECT_FOR_SHARE_QUERY: &str =
+	"SELECT id FROM objects WHERE id = $1 AND storage_state = 'available' FOR SHARE";
+
+pub const UPDATE_OBJECT_CONTENT_HASH_QUERY: &str =
+	"UPDATE objects SET content_hash = $2 WHERE id = $1";
+
+/// A page of available objects without a content hash, such as those uploaded
+/// before hashing existed, after id `$2`. Objects with a pending derivative job
+/// are left to it.
+pub const SELECT_UNHASHED_OBJECTS_QUERY: &str = "SELECT o.id, o.storage_key
+FROM objects o
+WHERE o.storage_state = 'available'
+	AND o.content_hash IS NULL
+	AND o.id > $2
+	AND NOT EXISTS (SELECT 1 FROM object_derivative_jobs job WHERE job.object_id = o.id)
+ORDER BY o.id
+LIMIT $1";
+
+pub const UPSERT_OBJECT_DERIVATIVES_QUERY: &str = "INSERT INTO object_derivatives (
+	object_id,
+	kind,
+	storage_key,
+	content_type,
+	width,
+	height,
+	byte_size
+)
+SELECT $1, UNNEST($2::TEXT[]), UNNEST($3::TEXT[]), UNNEST($4::TEXT[]), UNNEST($5::INTEGER[]), UNNEST($6::INTEGER[]), UNNEST($7::BIGINT[])
+ON CONFLICT (object_id, kind) DO UPDATE
+SET storage_key = EXCLUDED.storage_key,
+	content_type = EXCLUDED.content_type,
+	width = EXCLUDED.width,
+	height = EXCLUDED.height,
+	byte_size = EXCLUDED.byte_size,
+	created_at = now()";
+
+pub const SELECT_OBJECT_DERIVATIVE_STORAGE_KEY_QUERY: &str =
+	"SELECT storage_key FROM object_derivatives WHERE object_id = $1 AND kind = $2";
+
+/// The metadata privacy an object is served to other people with (see
+/// [`effective_metadata_privacy!`]).
+pub const SELECT_OBJECT_EFFECTIVE_METADATA_PRIVACY_QUERY: &str = concat!(
+	"SELECT ",
+	effective_metadata_privacy!(),
+	" AS metadata_privacy
+FROM objects o
+LEFT JOIN users owner ON owner.id = o.user_id
+WHERE o.id = $1"
+);
+
+/// Queues every derivative of the objects `$1` for storage deletion alongside
+/// the originals.
+pub const INSERT_OBJECT_DERIVATIVE_STORAGE_DELETIONS_QUERY: &str =
+	"INSERT INTO object_storage_deletions (storage_key, object_id)
+SELECT storage_key, object_id FROM object_derivatives WHERE object_id = ANY($1)
+ON CONFLICT (storage_key) DO NOTHING";
+
+pub const UPSERT_OBJECT_MEDIA_INFO_QUERY: &str = "INSERT INTO object_media_info (
+	object_id,
+	duration_seconds,
+	width,
+	height,
+	video_codec,
+	audio_codec,
+	title,
+	artist,
+	recorded_date,
+	peaks,
+	probed_at
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, now())
+ON CONFLICT (object_id) DO UPDATE
+SET duration_seconds = EXCLUDED.duration_seconds,
+	width = EXCLUDED.width,
+	height = EXCLUDED.height,
+	video_codec = EXCLUDED.video_codec,
+	audio_codec = EXCLUDED.audio_codec,
+	title = EXCLUDED.title,
+	artist = EXCLUDED.artist,
+	recorded_date = EXCLUDED.recorded_date,
+	peaks = EXCLUDED.peaks,
+	probed_at = EXCLUDED.probed_at";
+
+pub const SELECT_OBJECT_MEDIA_INFO_QUERY: &str =
+	"SELECT duration_seconds, width, height, video_codec, audio_codec, title, artist,
+	recorded_date, peaks
+FROM object_media_info
+WHERE object_id = $1";
+
+/// Available images without a perceptual hash row, oldest first from after the
+/// given id, so a pass can move past objects it failed to read.
+pub const SELECT_UNHASHED_IMAGE_OBJECTS_QUERY: &str = "SELECT o.id, o.storage_key
+FROM objects o
+WHERE o.storage_state = 'available'
+	AND o.content_type = ANY($1)
+	AND o.id > $3
+	AND NOT EXISTS (
+		SELECT 1 FROM object_perceptual_hashes hashed WHERE hashed.object_id = o.id
+	)
+ORDER BY o.id
+LIMIT $2";
+
+/// Records a perceptual hash, or NULL for an image that could not be hashed.
+/// Nothing is recorded for an object that has since been deleted.
+pub const UPSERT_OBJECT_PERCEPTUAL_HASH_QUERY: &str =
+	"INSERT INTO object_perceptual_hashes (object_id, hash)
+SELECT id, $2 FROM objects WHERE id = $1
+ON CONFLICT (object_id) DO UPDATE
+SET hash = EXCLUDED.hash,
+	hashed_at = now()";
+
+/// The owner's other available objects within a Hamming distance of the given
+/// object's perceptual hash, closest first.
+pub const SELECT_SIMILAR_OBJECTS_QUERY: &str = "SELECT v.*
+FROM object_perceptual_hashes target
+JOIN objects target_object ON target_object.id = target.object_id
+JOIN object_perceptual_hashes candidate
+	ON candidate.object_id <> target.object_id
+	AND bit_count((target.hash # candidate.hash)::BIT(64)) <= $2
+JOIN available_objects_with_users v
+	ON v.id = candidate.object_id
+	AND v.user_id = target_object.user_id
+WHERE target.object_id = $1
+ORDER BY bit_count((target.hash # candidate.hash)::BIT(64)), v.id";
+
+/// Records the pairs the object `$1`'s perceptual hash forms with its owner's
+/// other hashed images within `$2` bits, lowest id first. An unhashable image
+/// (a NULL hash) forms none.
+pub const INSERT_OBJECT_DUPLICATE_CANDIDATES_QUERY: &str =
+	"INSERT INTO object_duplicate_candidates (
+	first_object_id,
+	second_object_id,
+	user_id,
+	distance
+)
+SELECT
+	LEAST(hashed.object_id, other.object_id),
+	GREATEST(hashed.object_id, other.object_id),
+	hashed_object.user_id,
+	bit_count((hashed.hash # other.hash)::BIT(64))::INTEGER
+FROM object_perceptual_hashes hashed
+JOIN objects hashed_object ON hashed_object.id = hashed.object_id
+JOIN objects other_object
+	ON other_object.user_id = hashed_object.user_id
+	AND other_object.id <> hashed_object.id
+JOIN object_perceptual_hashes other ON other.object_id = other_object.id
+WHERE hashed.object_id = $1
+	AND bit_count((hashed.hash # other.hash)::BIT(64)) <= $2::INTEGER
+ON CONFLICT (first_object_id, second_object_id) DO UPDATE
+SET distance = EXCLUDED.distance";
+
+/// Recorded pairs of a user's available objects within a Hamming distance of
+/// each other that have not been dismissed, closest first and lowest id first
+/// in a pair.
+pub const SELECT_POSSIBLE_DUPLICATES_QUERY: &str = "SELECT
+	candidate.first_object_id,
+	candidate.second_object_id,
+	candidate.distance
+FROM object_duplicate_candidates candidate
+JOIN objects first_object ON first_object.id = candidate.first_object_id
+JOIN objects second_object ON second_object.id = candidate.second_object_id
+WHERE candidate.user_id = $1
+	AND candidate.distance <= $2
+	AND first_object.storage_state = 'available'
+	AND second_object.storage_state = 'available'
+	AND NOT EXISTS (
+		SELECT 1
+		FROM object_duplicate_dismissals dismissal
+		WHERE dismissal.first_object_id = candidate.first_object_id
+			AND dismissal.second_object_id = candidate.second_object_id
+	)
+ORDER BY candidate.distance, candidate.first_object_id, candidate.second_object_id
+LIMIT $3";
+
+pub const INSERT_OBJECT_DUPLICATE_DISMISSAL_QUERY: &str =
+	"INSERT INTO object_duplicate_dismissals (first_object_id, second_object_id)
+VALUES ($1, $2)
+ON CONFLICT DO NOTHING";
+
+/// The conditions under which a share link can still be opened.
+macro_rules! share_link_openable {
+	() => {
+		"revoked_at IS NULL
+	AND (expires_at IS NULL OR expires_at > now())
+	AND (max_views IS NULL OR view_count < max_views)"
+	};
+}
+
+/// The `ShareLink` column projection, single-sourced for the queries that
+/// return share links. `active` is whether the link can still be opened.
+macro_rules! share_link_columns {
+	() => {
+		concat!(
+			"id, user_id, object_id, album_id, password_hash IS NOT NULL AS has_password,
+	expires_at, max_views, view_count, revoked_at, created_at,
+	(",
+			share_link_openable!(),
+			") AS active"
+		)
+	};
+}
+
+pub const INSERT_SHARE_LINK_QUERY: &str = concat!(
+	"INSERT INTO share_links (token_hash, user_id, object_id, album_id, password_hash, expires_at, max_views)
+VALUES ($1, $2, $3, $4, $5, $6::timestamptz, $7)
+RETURNING ",
+	share_link_columns!()
+);
+
+pub const SELECT_SHARE_LINK_BY_ID_QUERY: &str =
+	concat!("SELECT ", share_link_columns!(), "\nFROM share_links\nWHERE id = $1");
+
+/// User `$1`'s share links, only those of object `$2` or album `$3` when
+/// given, newest first.
+pub const SELECT_SHARE_LINKS_FOR_USER_QUERY: &str = concat!(
+	"SELECT ",
+	share_link_columns!(),
+	"
+FROM share_links
+WHERE user_id = $1
+	AND ($2::BIGINT IS NULL OR object_id = $2)
+	AND ($3::BIGINT IS NULL OR album_id = $3)
+ORDER BY created_at DESC, id DESC"
+);
+
+pub const REVOKE_SHARE_LINK_QUERY: &str = concat!(
+	"UPDATE share_links
+SET revoked_at = COALESCE(revoked_at, now())
+WHERE id = $1
+RETURNING ",
+	share_link_columns!()
+);
+
+/// The share link whose token hashes to `$1`, if it can still be opened.
+pub const SELECT_OPENABLE_SHARE_LINK_QUERY: &str = concat!(
+	"SELECT id, object_id, album_id, password_hash, expires_at
+FROM share_links
+WHERE token_hash = $1
+	AND ",
+	share_link_openable!()
+);
+
+/// Counts a password attempt on share link `$1`, locking it for `$3` seconds
+/// once `$2` attempts have gone without the right password. Returns nothing
+/// while the link is locked, without counting the attempt. The first attempt
+/// after a lock expires starts a fresh count.
+pub const COUNT_SHARE_LINK_PASSWORD_ATTEMPT_QUERY: &str = "UPDATE share_links
+SET failed_password_attempts = CASE
+		WHEN password_locked_until <= now() THEN 1
+		ELSE failed_password_attempts + 1
+	END,
+	password_locked_until = CASE
+		WHEN (CASE
+			WHEN password_locked_until <= now() THEN 1
+			ELSE failed_password_attempts + 1
+		END) >= $2::INTEGER
+			THEN now() + ($3::BIGINT * interval '1 second')
+	END
+WHERE id = $1
+	AND (password_locked_until IS NULL OR password_locked_until <= now())
+RETURNING id";
+
+/// Clears share link `$1`'s failed password attempts once the right password
+/// is given.
+pub const RESET_SHARE_LINK_PASSWORD_ATTEMPTS_QUERY: &str = "UPDATE share_links
+SET failed_password_attempts = 0, password_locked_until = NULL
+WHERE id = $1";
+
+/// Counts a view of share link `$1`, returning nothing when it can no longer
+/// be opened, such as when a concurrent view used up its last one.
+pub const COUNT_SHARE_LINK_VIEW_QUERY: &str = concat!(
+	"UPDATE share_links
+SET view_count = view_count + 1
+WHERE id = $1
+	AND ",
+	share_link_openable!(),
+	"
+RETURNING id"
+);
+
+/// The columns of a user group, read from `user_groups g`, with its members'
+/// emails in alphabetical order.
+macro_rules! user_group_columns {
+	() => {
+		"g.id, g.user_id, g.name, g.created_at,
+	COALESCE((
+		SELECT array_agg(u.email ORDER BY u.email)
+		FROM user_group_members m
+		JOIN users u ON u.id = m.user_id
+		WHERE m.group_id = g.id
+	), '{}') AS members"
+	};
+}
+
+pub const INSERT_USER_GROUP_QUERY: &str =
+	"INSERT INTO user_groups (user_id, name) VALUES ($1, $2) RETURNING id";
+
+pub const SELECT_USER_GROUP_BY_ID_QUERY: &str =
+	concat!("SELECT ", user_group_columns!(), "\nFROM user_groups g\nWHERE g.id = $1");
+
+/// User `$1`'s groups, by name.
+pub const SELECT_USER_GROUPS_FOR_USER_QUERY: &str = concat!(
+	"SELECT ",
+	user_group_columns!(),
+	"\nFROM user_groups g\nWHERE g.user_id = $1\nORDER BY g.name, g.id"
+);
+
+pub const RENAME_USER_GROUP_QUERY: &str = "UPDATE user_groups SET name = $2 WHERE id = $1";
+
+pub const DELETE_USER_GROUP_QUERY: &str = "DELETE FROM user_groups WHERE id = $1";
+
+/// Adds the users with the emails `$2` to group `$1`, returning the emails of
+/// every one of them there is, whether or not they were members already.
+pub const ADD_USER_GROUP_MEMBERS_QUERY: &str = "WITH valid AS (
+	SELECT id, email
+	FROM users
+	WHERE email = ANY($2)
+),
+inserted AS (
+	INSERT INTO user_group_members (group_id, user_id)
+	SELECT $1, id
+	FROM valid
+	ON CONFLICT DO NOTHING
+)
+SELECT email
+FROM valid";
+
+pub const REMOVE_USER_GROUP_MEMBERS_QUERY: &str = "DELETE FROM user_group_members member
+USING users
+WHERE member.group_id = $1
+	AND member.user_id = users.id
+	AND users.email = ANY($2)";
+
+/// Whether a session last seen at `$last_seen_at` and started at `$created_at`
+/// is still within the idle timeout `$idle` and absolute timeout `$absolute`,
+/// both in seconds.
+macro_rules! session_active {
+	($last_seen_at:literal, $created_at:literal, $idle:literal, $absolute:literal) => {
+		concat!(
+			$last_seen_at,
+			" > now() - (",
+			$idle,
+			"::BIGINT * interval '1 second')\n\t\tAND ",
+			$created_at,
+			" > now() - (",
+			$absolute,
+			"::BIGINT * interval '1 second')"
+		)
+	};
+}
+
+pub const INSERT_SESSION_QUERY: &str =
+	"INSERT INTO sessions (token_hash, user_id, user_agent, ip_address)
+VALUES ($1, $2, $3, $4)
+RETURNING id";
+
+/// The session with the token hash `$1` and its user's role, unless it has
+/// been idle for `$2` seconds or started more than `$3` seconds ago. Marks it
+/// seen, at most once a minute so busy sessions are not rewritten on every
+/// request.
+pub const SELECT_SESSION_CALLER_QUERY: &str = concat!(
+	"WITH current_session AS (
+	SELECT s.id, s.user_id, s.last_seen_at
+	FROM sessions s
+	WHERE s.token_hash = $1
+		AND ",
+	session_active!("s.last_seen_at", "s.created_at", "$2", "$3"),
+	"
+),
+seen AS (
+	UPDATE sessions
+	SET last_seen_at = now()
+	FROM current_session
+	WHERE sessions.id = current_session.id
+		AND current_session.last_seen_at < now() - interval '1 minute'
+)
+SELECT current_session.id, current_session.user_id, u.role
+FROM current_session
+JOIN users u ON u.id = current_session.user_id"
+);
+
+/// User `$1`'s sessions that are still within the idle timeout `$2` and the
+/// absolute timeout `$3`, most recently seen first, with when each will end if
+/// left idle.
+pub const SELECT_SESSIONS_FOR_USER_QUERY: &str = concat!(
+	"SELECT id, user_id, user_agent, ip_address, created_at, last_seen_at,
+	LEAST(
+		last_seen_at + ($2::BIGINT * interval '1 second'),
+		created_at + ($3::BIGINT * interval '1 second')
+	) AS expires_at
+FROM sessions
+WHERE user_id = $1
+	AND ",
+	session_active!("last_seen_at", "created_at", "$2", "$3"),
+	"
+ORDER BY last_seen_at DESC, id DESC"
+);
+
+pub const DELETE_SESSION_QUERY: &str = "DELETE FROM sessions WHERE id = $1 AND user_id = $2";
+
+/// Deletes user `$1`'s sessions other than session `$2`.
+pub const DELETE_OTHER_SESSIONS_QUERY: &str =
+	"DELETE FROM sessions WHERE user_id = $1 AND id != $2";
+
+pub const DELETE_SESSIONS_BY_USER_QUERY: &str = "DELETE FROM sessions WHERE user_id = $1";
+
+/// Deletes the sessions idle for `$1` seconds or started more than `$2`
+/// seconds ago, which no longer sign anyone in.
+pub const DELETE_EXPIRED_SESSIONS_QUERY: &str = "DELETE FROM sessions
+WHERE last_seen_at <= now() - ($1::BIGINT * interval '1 second')
+	OR created_at <= now() - ($2::BIGINT * interval '1 second')";
@@ -0,0 +1,2222 @@
+use {
+	crate::{
+		db::queries::{
+			CLAIM_OBJECT_DERIVATIVE_JOBS_QUERY,
+			DELETE_OBJECT_DERIVATIVE_JOBS_QUERY,
+			INSERT_OBJECT_STORAGE_DELETIONS_QUERY,
+			LOCK_AVAILABLE_OBJECT_FOR_SHARE_QUERY,
+			MARK_OBJECT_DERIVATIVE_JOBS_FAILED_QUERY,
+			SELECT_UNHASHED_OBJECTS_QUERY,
+			UPDATE_OBJECT_CONTENT_HASH_QUERY,
+			UPSERT_OBJECT_DERIVATIVES_QUERY,
+			UPSERT_OBJECT_MEDIA_INFO_QUERY,
+		},
+		errors::AppError,
+		media::{
+			MediaInfo,
+			MetadataSource,
+			audio::{
+				analyze_audio,
+				supports_audio_analysis,
+			},
+			raw::{
+				decode_embedded_preview,
+				supports_raw_previews,
+			},
+			sanitize::{
+				MetadataPrivacy,
+				Mp4Patch,
+				Mp4Sanitizer,
+				SANITIZED_KIND,
+				SanitizedCopy,
+				SanitizedMp4,
+				sanitize_copy,
+				sanitized_extension,
+			},
+			thumbnail::{
+				DISPLAY_KIND,
+				Display,
+				POSTER_KIND,
+				Poster,
+				Thumbnail,
+				decode_oriented,
+				needs_display_rendition,
+				render_display,
+				render_poster,
+				render_thumbnails,
+				supports_thumbnails,
+			},
+			video::{
+				embedded_poster,
+				h264_keyframe,
+				probe_video,
+				supports_probing,
+			},
+		},
+		outbox::{
+			DrainOutcome,
+			FailedGroup,
+			OutboxProcessor,
+			OutboxQueue,
+			OutboxRetryConfig,
+			drain_outbox,
+			ensure_positive,
+		},
+		storage::{
+			CompletedUploadPart,
+			MultipartUploadCompleteOutcome,
+			StorageClient,
+		},
+		worker::MaintenanceTask,
+	},
+	anyhow::Context,
+	deadpool::managed::Pool,
+	deadpool_postgres::{
+		Client,
+		Manager,
+	},
+	image::DynamicImage,
+	serde::Deserialize,
+	std::{
+		future::Future,
+		io::Write,
+		ops::Range,
+		path::Path,
+		process::Stdio,
+		time::Duration,
+	},
+	tempfile::NamedTempFile,
+	tokio_postgres::{
+		Row,
+		Transaction,
+	},
+};
+
+/// The latest point a poster frame is taken from, so long videos do not need
+/// seeking far into.
+const POSTER_SEEK_SECONDS: f64 = 1.0;
+/// How much of an object is read per request while hashing it, so objects of
+/// any size are hashed without being held in memory.
+const HASH_READ_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+/// The smallest part a patched copy is assembled from. Parts grow for objects
+/// too large to fit the 10,000 parts a multipart upload allows.
+const PATCHED_COPY_PART_BYTES: u64 = 8 * 1024 * 1024;
+const MAX_UPLOAD_PARTS: u64 = 10_000;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ObjectDerivativeConfig {
+	#[serde(default = "ObjectDerivativeConfig::default_retry_seconds")]
+	pub retry_seconds: i64,
+	#[serde(default = "ObjectDerivativeConfig::default_lease_seconds")]
+	pub lease_seconds: i64,
+	#[serde(default = "ObjectDerivativeConfig::default_worker_interval_seconds")]
+	pub worker_interval_seconds: i64,
+	#[serde(default = "ObjectDerivativeConfig::default_batch_size")]
+	pub batch_size: i64,
+	#[serde(default = "ObjectDerivativeConfig::default_max_attempts")]
+	pub max_attempts: i32,
+	/// Objects larger than this are not downloaded for rendering and get no
+	/// derivatives; clients fall back to the original.
+	#[serde(default = "ObjectDerivativeConfig::default_max_source_bytes")]
+	pub max_source_bytes: i64,
+	/// How much of a video is read to probe its container metadata.
+	#[serde(default = "ObjectDerivativeConfig::default_probe_read_max_bytes")]
+	pub probe_read_max_bytes: i64,
+	/// An `ffmpeg` binary used to grab a poster frame from videos without
+	/// embedded cover art, and to decode HEIC photos and RAW files without an
+	/// embedded preview. Without one, these get no poster or display rendition.
+	#[serde(default)]
+	pub ffmpeg_path: Option<String>,
+	/// How long one `ffmpeg` run may take before it is killed, so a file that
+	/// makes it hang cannot stall the worker past its lease.
+	#[serde(default = "ObjectDerivativeConfig::default_ffmpeg_timeout_seconds")]
+	pub ffmpeg_timeout_seconds: i64,
+}
+
+impl ObjectDerivativeConfig {
+	pub const fn default_retry_seconds() -> i64 {
+		300
+	}
+
+	pub const fn default_lease_seconds() -> i64 {
+		900
+	}
+
+	pub const fn default_worker_interval_seconds() -> i64 {
+		30
+	}
+
+	pub const fn default_batch_size() -> i64 {
+		10
+	}
+
+	pub const fn default_max_attempts() -> i32 {
+		5
+	}
+
+	pub const fn default_max_source_bytes() -> i64 {
+		100 * 1024 * 1024
+	}
+
+	pub const fn default_probe_read_max_bytes() -> i64 {
+		8 * 1024 * 1024
+	}
+
+	pub const fn default_ffmpeg_timeout_seconds() -> i64 {
+		60
+	}
+
+	pub fn validate(&self) -> anyhow::Result<()> {
+		self.retry().validate("object_derivatives")?;
+		ensure_positive!(self, worker_interval_seconds);
+		ensure_positive!(self, max_source_bytes);
+		ensure_positive!(self, probe_read_max_bytes);
+		ensure_positive!(self, ffmpeg_timeout_seconds);
+		Ok(())
+	}
+
+	/// Lease/retry policy for the derivative job queue, as a runtime view.
+	pub fn retry(&self) -> OutboxRetryConfig {
+		OutboxRetryConfig {
+			retry_seconds: self.retry_seconds,
+			lease_seconds: self.lease_seconds,
+			batch_size: self.batch_size,
+			max_attempts: self.max_attempts,
+		}
+	}
+
+	fn worker_interval(&self) -> Duration {
+		Duration::from_secs(self.worker_interval_seconds as u64)
+	}
+
+	/// Whether the configured `ffmpeg` can decode the HEIC photos phones take:
+	/// it must have an HEVC decoder and be 7.1 or newer, the first release that
+	/// reassembles their tiled images. Run once at startup to decide whether
+	/// HEIC uploads are accepted.
+	pub async fn decodes_heic(&self) -> bool {
+		let Some(ffmpeg) = self.ffmpeg() else {
+			return false;
+		};
+		match ffmpeg.decodes_heic().await {
+			Ok(decodes) => decodes,
+			Err(error) => {
+				tracing::warn!(error = ?error, "Failed to probe ffmpeg");
+				false
+			}
+		}
+	}
+
+	/// The configured `ffmpeg`, if any.
+	fn ffmpeg(&self) -> Option<Ffmpeg<'_>> {
+		self.ffmpeg_path.as_deref().map(|path| Ffmpeg {
+			path: Path::new(path),
+			timeout: Duration::from_secs(self.ffmpeg_timeout_seconds as u64),
+		})
+	}
+}
+
+impl Default for ObjectDerivativeConfig {
+	fn default() -> Self {
+		Self {
+			retry_seconds: Self::default_retry_seconds(),
+			lease_seconds: Self::default_lease_seconds(),
+			worker_interval_seconds: Self::default_worker_interval_seconds(),
+			batch_size: Self::default_batch_size(),
+			max_attempts: Self::default_max_attempts(),
+			max_source_bytes: Self::default_max_source_bytes(),
+			probe_read_max_bytes: Self::default_probe_read_max_bytes(),
+			ffmpeg_path: None,
+			ffmpeg_timeout_seconds: Self::default_ffmpeg_timeout_seconds(),
+		}
+	}
+}
+
+/// The storage key of derivative `kind` of the object stored at `source_key`.
+/// Derived from the source key so it is as unguessable as the original.
+pub fn derivative_storage_key(
+	source_key: &str,
+	kind: &str,
+	extension: &str,
+) -> String {
+	let source = source_key.strip_prefix("objects/").unwrap_or(source_key);
+	format!("derivatives/{source}/{kind}.{extension}")
+}
+
+/// A rendition written to storage, as recorded in `object_derivatives`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StoredDerivative {
+	pub kind: String,
+	pub storage_key: String,
+	pub content_type: String,
+	pub width: Option<i32>,
+	pub height: Option<i32>,
+	pub byte_size: i64,
+}
+
+/// A claimed derivative job. `derivatives` and `media_info` start empty and are
+/// filled by the processor with the renditions it wrote and what it probed, for
+/// `clear` to record; `content_hash` starts as the stored BLAKE3 hash of the
+/// original, if any, and is filled in when missing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectDerivativeJob {
+	pub object_id: i64,
+	pub storage_key: String,
+	pub content_type: String,
+	pub available: bool,
+	/// What the owner's settings withhold from other people, decided when the
+	/// job is claimed.
+	pub metadata_privacy: MetadataPrivacy,
+	/// Whether the object already has renditions other than a sanitized copy.
+	/// An original never changes, so these are not rendered again.
+	pub rendered: bool,
+	/// Whether the job was queued because privacy settings changed, so only
+	/// the sanitized copy is rewritten, whatever else the object lacks.
+	pub sanitized_only: bool,
+	pub derivatives: Vec<StoredDerivative>,
+	pub media_info: Option<MediaInfo>,
+	pub content_hash: Option<String>,
+}
+
+impl TryFrom<Row> for ObjectDerivativeJob {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(Self {
+			object_id: row
+				.try_get("object_id")
+				.context("Failed to read derivative job object id")?,
+			storage_key: row
+				.try_get("storage_key")
+				.context("Failed to read derivative job storage key")?,
+			content_type: row
+				.try_get("content_type")
+				.context("Failed to read derivative job content type")?,
+			available: row
+				.try_get("available")
+				.context("Failed to read derivative job availability")?,
+			metadata_privacy: row
+				.try_get("metadata_privacy")
+				.context("Failed to read derivative job metadata privacy")?,
+			rendered: row
+				.try_get("rendered")
+				.context("Failed to read whether a derivative job's object is rendered")?,
+			sanitized_only: row
+				.try_get("sanitized_only")
+				.context("Failed to read whether a derivative job is sanitized only")?,
+			derivatives: Vec::new(),
+			media_info: None,
+			content_hash: row
+				.try_get("content_hash")
+				.context("Failed to read derivative job content hash")?,
+		})
+	}
+}
+
+#[derive(Clone)]
+pub struct ObjectDerivativeWorker {
+	pool: Pool<Manager>,
+	storage: StorageClient,
+	config: ObjectDerivativeConfig,
+}
+
+impl ObjectDerivativeWorker {
+	pub fn new(
+		pool: Pool<Manager>,
+		storage: StorageClient,
+		config: ObjectDerivativeConfig,
+	) -> Self {
+		Self {
+			pool,
+			storage,
+			config,
+		}
+	}
+}
+
+impl MaintenanceTask for ObjectDerivativeWorker {
+	fn name(&self) -> &'static str {
+		"object_derivatives"
+	}
+
+	fn interval(&self) -> Duration {
+		self.config.worker_interval()
+	}
+
+	async fn run_once(&self) -> Result<(), AppError> {
+		let mut client = self.pool.get().await?;
+		let processor = ObjectDerivativeProcessor {
+			storage: &self.storage,
+			max_source_bytes: self.config.max_source_bytes,
+			probe_read_max_bytes: self.config.probe_read_max_bytes,
+			ffmpeg: self.config.ffmpeg(),
+		};
+		let mut queue = ObjectDerivativeQueue(&mut client);
+		drain_outbox(&mut queue, &processor, &self.config.retry()).await
+	}
+}
+
+/// Hashes available objects that have no content hash, such as those uploaded
+/// before hashing existed, so exact duplicates of them are recognised. Only the
+/// hash is computed: objects are read in chunks and nothing is rendered. Runs
+/// on the derivative worker's interval and batch size.
+#[derive(Clone)]
+pub struct ContentHashBackfill {
+	pool: Pool<Manager>,
+	storage: StorageClient,
+	config: ObjectDerivativeConfig,
+}
+
+impl ContentHashBackfill {
+	pub fn new(
+		pool: Pool<Manager>,
+		storage: StorageClient,
+		config: ObjectDerivativeConfig,
+	) -> Self {
+		Self {
+			pool,
+			storage,
+			config,
+		}
+	}
+}
+
+impl MaintenanceTask for ContentHashBackfill {
+	fn name(&self) -> &'static str {
+		"content_hashes"
+	}
+
+	fn interval(&self) -> Duration {
+		self.config.worker_interval()
+	}
+
+	/// Works through every unhashed object in batches. An object that cannot
+	/// be read is skipped for the rest of the pass and retried on the next one.
+	async fn run_once(&self) -> Result<(), AppError> {
+		let client = self.pool.get().await?;
+		let select = client.prepare_cached(SELECT_UNHASHED_OBJECTS_QUERY).await?;
+		let update = client.prepare_cached(UPDATE_OBJECT_CONTENT_HASH_QUERY).await?;
+		let mut after_id = 0i64;
+		loop {
+			let rows = client.query(&select, &[&self.config.batch_size, &after_id]).await?;
+			for row in &rows {
+				let object_id: i64 = row.try_get("id")?;
+				let storage_key: String = row.try_get("storage_key")?;
+				after_id = object_id;
+				match self.storage.hash_source(&storage_key).await {
+					Ok(content_hash) => {
+						client.execute(&update, &[&object_id, &content_hash]).await?;
+					}
+					Err(error) => tracing::warn!(
+						object_id,
+						error = ?error,
+						"Failed to read object for content hashing"
+					),
+				}
+			}
+			if (rows.len() as i64) < self.config.batch_size {
+				return Ok(());
+			}
+		}
+	}
+}
+
+/// Reads sources and writes renditions. The explicit `Send` bounds let the
+/// generic processor compose these into its `Send`-bounded future, as with the
+/// email outbox's sender.
+trait DerivativeStorage {
+	/// Reads the whole object, or `None` when it is larger than `max_bytes`.
+	fn read_source(
+		&self,
+		storage_key: &str,
+		max_bytes: i64,
+	) -> impl Future<Output = anyhow::Result<Option<Vec<u8>>>> + Send;
+
+	/// Reads the parts of the object that carry container metadata.
+	fn read_probe_source(
+		&self,
+		storage_key: &str,
+		max_bytes: i64,
+	) -> impl Future<Output = anyhow::Result<MetadataSource>> + Send;
+
+	/// Reads `length` bytes of the object starting at `offset`.
+	fn read_range(
+		&self,
+		storage_key: &str,
+		offset: u64,
+		length: u64,
+	) -> impl Future<Output = anyhow::Result<Vec<u8>>> + Send;
+
+	/// The object's length in bytes.
+	fn source_length(
+		&self,
+		storage_key: &str,
+	) -> impl Future<Output = anyhow::Result<u64>> + Send;
+
+	/// Hashes the whole object with BLAKE3, returning the hex digest.
+	fn hash_source(
+		&self,
+		storage_key: &str,
+	) -> impl Future<Output = anyhow::Result<String>> + Send;
+
+	fn write_derivative(
+		&self,
+		storage_key: &str,
+		bytes: Vec<u8>,
+		content_type: &str,
+	) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+	/// Writes a copy of the `length` bytes of `source_key` with `patches`
+	/// applied, reading only the parts they change.
+	fn write_patched_copy(
+		&self,
+		source_key: &str,
+		length: u64,
+		storage_key: &str,
+		content_type: &str,
+		patches: &[Mp4Patch],
+	) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+impl DerivativeStorage for StorageClient {
+	async fn read_source(
+		&self,
+		storage_key: &str,
+		max_bytes: i64,
+	) -> anyhow::Result<Option<Vec<u8>>> {
+		let metadata = self.head_object(storage_key).await?;
+		if metadata.content_length > max_bytes {
+			return Ok(None);
+		}
+		let length = u64::try_from(metadata.content_length).context("Negative object length")?;
+		self.get_object_range(storage_key, 0, length).await.map(Some)
+	}
+
+	async fn read_probe_source(
+		&self,
+		storage_key: &str,
+		max_bytes: i64,
+	) -> anyhow::Result<MetadataSource> {
+		let metadata = self.head_object(storage_key).await?;
+		MetadataSource::read(self, storage_key, metadata.content_length, max_bytes).await
+	}
+
+	async fn read_range(
+		&self,
+		storage_key: &str,
+		offset: u64,
+		length: u64,
+	) -> anyhow::Result<Vec<u8>> {
+		self.get_object_range(storage_key, offset, length).await
+	}
+
+	async fn source_length(
+		&self,
+		storage_key: &str,
+	) -> anyhow::Result<u64> {
+		let metadata = self.head_object(storage_key).await?;
+		u64::try_from(metadata.content_length).context("Negative object length")
+	}
+
+	async fn hash_source(
+		&self,
+		storage_key: &str,
+	) -> anyhow::Result<String> {
+		let metadata = self.head_object(storage_key).await?;
+		let length = u64::try_from(metadata.content_length).context("Negative object length")?;
+		let mut hasher = blake3::Hasher::new();
+		let mut offset = 0;
+		while offset < length {
+			let chunk = self
+				.get_object_range(storage_key, offset, HASH_READ_CHUNK_BYTES.min(length - offset))
+				.await?;
+			anyhow::ensure!(!chunk.is_empty(), "Object ended before its reported length");
+			hasher.update(&chunk);
+			offset += chunk.len() as u64;
+		}
+		Ok(hasher.finalize().to_string())
+	}
+
+	async fn write_derivative(
+		&self,
+		storage_key: &str,
+		bytes: Vec<u8>,
+		content_type: &str,
+	) -> anyhow::Result<()> {
+		self.upload_object(storage_key, bytes, content_type).await
+	}
+
+	/// Assembles the copy as a multipart upload: parts a patch touches are read
+	/// and uploaded patched, and the rest are copied within the storage
+	/// service. A failed copy is aborted so no parts are left behind.
+	async fn write_patched_copy(
+		&self,
+		source_key: &str,
+		length: u64,
+		storage_key: &str,
+		content_type: &str,
+		patches: &[Mp4Patch],
+	) -> anyhow::Result<()> {
+		let upload_id = self.create_multipart_upload(storage_key, content_type).await?;
+		let parts =
+			upload_patched_parts(self, source_key, length, storage_key, &upload_id, patches).await;
+		let completed = match parts {
+			Ok(parts) => self.complete_multipart_upload(storage_key, &upload_id, &parts).await,
+			Err(error) => Err(error),
+		};
+		match completed {
+			Ok(MultipartUploadCompleteOutcome::Completed) => Ok(()),
+			Ok(MultipartUploadCompleteOutcome::UploadNotFound) =>
+				anyhow::bail!("Patched copy upload was not found"),
+			Err(error) => {
+				if let Err(abort_error) = self.abort_multipart_upload(storage_key, &upload_id).await
+				{
+					tracing::warn!(
+						storage_key,
+						error = ?abort_error,
+						"Failed to abort a patched copy upload"
+					);
+				}
+				Err(error)
+			}
+		}
+	}
+}
+
+async fn upload_patched_parts(
+	storage: &StorageClient,
+	source_key: &str,
+	length: u64,
+	storage_key: &str,
+	upload_id: &str,
+	patches: &[Mp4Patch],
+) -> anyhow::Result<Vec<CompletedUploadPart>> {
+	let mut completed = Vec::new();
+	for (part_number, (range, patched)) in (1 ..).zip(patched_copy_parts(length, patches)) {
+		let e_tag = if patched {
+			let mut bytes =
+				storage.get_object_range(source_key, range.start, range.end - range.start).await?;
+			for patch in patches {
+				patch.apply(range.start, &mut bytes);
+			}
+			storage.upload_part(storage_key, upload_id, part_number, bytes).await?
+		} else {
+			storage.upload_part_copy(storage_key, upload_id, part_number, source_key, range).await?
+		};
+		completed.push(CompletedUploadPart {
+			part_number,
+			e_tag,
+		});
+	}
+	Ok(completed)
+}
+
+/// The parts a patched copy of `length` bytes is assembled from, each with
+/// whether a patch touches it.
+fn patched_copy_parts(
+	length: u64,
+	patches: &[Mp4Patch],
+) -> Vec<(Range<u64>, bool)> {
+	let part_bytes = PATCHED_COPY_PART_BYTES.max(length.div_ceil(MAX_UPLOAD_PARTS));
+	(0 .. length.div_ceil(part_bytes))
+		.map(|index| {
+			let start = index * part_bytes;
+			let end = length.min(start + part_bytes);
+			let patched = patches.iter().any(|patch| {
+				let patch = patch.range();
+				patch.start < end && start < patch.end
+			});
+			(start .. end, patched)
+		})
+		.collect()
+}
+
+/// The derivative job table as an [`OutboxQueue`]. `clear` records what each job
+/// produced and deletes the job rows in one transaction.
+struct ObjectDerivativeQueue<'a>(&'a mut Client);
+
+impl OutboxQueue for ObjectDerivativeQueue<'_> {
+	type Item = ObjectDerivativeJob;
+
+	async fn claim(
+		&mut self,
+		retry: &OutboxRetryConfig,
+	) -> Result<Vec<ObjectDerivativeJob>, AppError> {
+		let rows = self
+			.0
+			.query(
+				CLAIM_OBJECT_DERIVATIVE_JOBS_QUERY,
+				&[&retry.batch_size, &retry.lease_seconds, &retry.max_attempts],
+			)
+			.await
+			.context("Failed to claim object derivative jobs")?;
+		rows.into_iter().map(ObjectDerivativeJob::try_from).collect()
+	}
+
+	async fn clear(
+		&mut self,
+		jobs: &[ObjectDerivativeJob],
+	) -> Result<(), AppError> {
+		if jobs.is_empty() {
+			return Ok(());
+		}
+		let transaction = self.0.transaction().await?;
+		for job in jobs.iter().filter(|job| {
+			!job.derivatives.is_empty() || job.media_info.is_some() || job.content_hash.is_some()
+		}) {
+			record_results(&transaction, job).await?;
+		}
+		let ids = jobs.iter().map(|job| job.object_id).collect::<Vec<_>>();
+		transaction
+			.execute(DELETE_OBJECT_DERIVATIVE_JOBS_QUERY, &[&ids])
+			.await
+			.context("Failed to clear completed object derivative jobs")?;
+		transaction.commit().await?;
+		Ok(())
+	}
+
+	async fn mark_failed(
+		&mut self,
+		jobs: &[ObjectDerivativeJob],
+		error_message: &str,
+		retry_after_seconds: i64,
+	) -> Result<(), AppError> {
+		if jobs.is_empty() {
+			return Ok(());
+		}
+		let ids = jobs.iter().map(|job| job.object_id).collect::<Vec<_>>();
+		self.0
+			.execute(
+				MARK_OBJECT_DERIVATIVE_JOBS_FAILED_QUERY,
+				&[&ids, &error_message, &retry_after_seconds],
+			)
+			.await
+			.context("Failed to record object derivative job failure")?;
+		Ok(())
+	}
+}
+
+/// Records a job's content hash, renditions and probed media info against its
+/// object. The object row is share-locked so a concurrent delete either waits
+/// and then queues these derivatives with the original, or has already
+/// committed, in which case the renditions are queued for storage deletion
+/// here instead of being recorded.
+async fn record_results(
+	transaction: &Transaction<'_>,
+	job: &ObjectDerivativeJob,
+) -> Result<(), AppError> {
+	let storage_keys =
+		job.derivatives.iter().map(|derivative| derivative.storage_key.clone()).collect::<Vec<_>>();
+	let available = transaction
+		.query_opt(LOCK_AVAILABLE_OBJECT_FOR_SHARE_QUERY, &[&job.object_id])
+		.await
+		.context("Failed to lock object for derivative recording")?
+		.is_some();
+	if !available {
+		let object_ids = vec![job.object_id; storage_keys.len()];
+		transaction
+			.execute(INSERT_OBJECT_STORAGE_DELETIONS_QUERY, &[&storage_keys, &object_ids])
+			.await
+			.context("Failed to enqueue derivatives of a deleted object")?;
+		return Ok(());
+	}
+
+	if let Some(content_hash) = &job.content_hash {
+		transaction
+			.execute(UPDATE_OBJECT_CONTENT_HASH_QUERY, &[&job.object_id, content_hash])
+			.await
+			.context("Failed to record object content hash")?;
+	}
+	if let Some(info) = &job.media_info {
+		let dimension = |value: Option<u32>| value.and_then(|value| i32::try_from(value).ok());
+		transaction
+			.execute(
+				UPSERT_OBJECT_MEDIA_INFO_QUERY,
+				&[
+					&job.object_id,
+					&info.duration_seconds,
+					&dimension(info.width),
+					&dimension(info.height),
+					&info.video_codec,
+					&info.audio_codec,
+					&info.tags.title,
+					&info.tags.artist,
+					&info.tags.recorded_date,
+					&info.peaks,
+				],
+			)
+			.await
+			.context("Failed to record object media info")?;
+	}
+	if job.derivatives.is_empty() {
+		return Ok(());
+	}
+
+	let kinds =
+		job.derivatives.iter().map(|derivative| derivative.kind.clone()).collect::<Vec<_>>();
+	let content_types = job
+		.derivatives
+		.iter()
+		.map(|derivative| derivative.content_type.clone())
+		.collect::<Vec<_>>();
+	let widths = job.derivatives.iter().map(|derivative| derivative.width).collect::<Vec<_>>();
+	let heights = job.derivatives.iter().map(|derivative| derivative.height).collect::<Vec<_>>();
+	let byte_sizes =
+		job.derivatives.iter().map(|derivative| derivative.byte_size).collect::<Vec<_>>();
+	transaction
+		.execute(
+			UPSERT_OBJECT_DERIVATIVES_QUERY,
+			&[
+				&job.object_id,
+				&kinds,
+				&storage_keys,
+				&content_types,
+				&widths,
+				&heights,
+				&byte_sizes,
+			],
+		)
+		.await
+		.context("Failed to record object derivatives")?;
+	Ok(())
+}
+
+/// Renders each claimed job independently, so one bad source does not fail the
+/// rest of the batch. Every available object is hashed; jobs for objects that
+/// cannot have derivatives (unsupported type, over the size limit) clear with
+/// only their hash, and jobs for deleted objects with nothing recorded.
+struct ObjectDerivativeProcessor<'a, S> {
+	storage: &'a S,
+	max_source_bytes: i64,
+	probe_read_max_bytes: i64,
+	ffmpeg: Option<Ffmpeg<'a>>,
+}
+
+impl<S: DerivativeStorage + Sync> OutboxProcessor for ObjectDerivativeProcessor<'_, S> {
+	type Item = ObjectDerivativeJob;
+
+	async fn process(
+		&self,
+		jobs: Vec<ObjectDerivativeJob>,
+	) -> DrainOutcome<ObjectDerivativeJob> {
+		let mut cleared = Vec::new();
+		let mut failed = Vec::new();
+		for mut job in jobs {
+			match self.process_job(&mut job).await {
+				Ok(()) => cleared.push(job),
+				Err(error) => failed.push(FailedGroup {
+					items: vec![job],
+					error,
+				}),
+			}
+		}
+		DrainOutcome {
+			cleared,
+			failed,
+		}
+	}
+}
+
+impl<S: DerivativeStorage + Sync> ObjectDerivativeProcessor<'_, S> {
+	/// Renders an object that has no renditions yet, or only rewrites its
+	/// sanitized copy when it has or the job asks for no more, then hashes the
+	/// original if it has no hash. An original already read in full is hashed
+	/// from memory rather than read a second time; a sanitized-only job leaves
+	/// any other missing hash to the backfill.
+	async fn process_job(
+		&self,
+		job: &mut ObjectDerivativeJob,
+	) -> anyhow::Result<()> {
+		if !job.available {
+			return Ok(());
+		}
+		if job.rendered || job.sanitized_only {
+			self.rewrite_sanitized(job).await?;
+		} else {
+			self.render(job).await?;
+			// Photos with thumbnails get their sanitized copy while rendering;
+			// AVIF photos are read in full only for theirs, and MP4s are
+			// patched from ranged reads.
+			if !supports_thumbnails(&job.content_type) {
+				self.rewrite_sanitized(job).await?;
+			}
+		}
+		if job.content_hash.is_none() && !job.sanitized_only {
+			job.content_hash = Some(self.storage.hash_source(&job.storage_key).await?);
+		}
+		Ok(())
+	}
+
+	async fn render(
+		&self,
+		job: &mut ObjectDerivativeJob,
+	) -> anyhow::Result<()> {
+		if supports_thumbnails(&job.content_type) {
+			let Some(source) = self.read_source(job).await? else {
+				return Ok(());
+			};
+			let content_type = job.content_type.clone();
+			let privacy = job.metadata_privacy;
+			let (thumbnails, sanitized) = tokio::task::spawn_blocking(move || {
+				let sanitized = sanitize_copy(&source, &content_type, privacy);
+				render_thumbnails(&source).map(|thumbnails| (thumbnails, sanitized))
+			})
+			.await
+			.context("Thumbnail rendering task failed")??;
+			job.derivatives = store_thumbnails(self.storage, job, thumbnails).await?;
+			self.record_sanitized(job, sanitized).await?;
+		} else if needs_display_rendition(&job.content_type) {
+			let Some(source) = self.read_source(job).await? else {
+				return Ok(());
+			};
+			let Some(image) = self.decode_for_display(job, source).await? else {
+				return Ok(());
+			};
+			let (display, thumbnails) = tokio::task::spawn_blocking(move || render_display(image))
+				.await
+				.context("Display rendering task failed")??;
+			let mut derivatives = vec![store_display(self.storage, job, display).await?];
+			derivatives.extend(store_thumbnails(self.storage, job, thumbnails).await?);
+			job.derivatives = derivatives;
+		} else if supports_probing(&job.content_type) {
+			let source =
+				self.storage.read_probe_source(&job.storage_key, self.probe_read_max_bytes).await?;
+			job.media_info = probe_video(&source);
+			let frame = match embedded_poster(&source) {
+				Some(cover_art) => Some(cover_art.to_vec()),
+				None => self.take_poster_frame(job, &source).await?,
+			};
+			let Some(frame) = frame else {
+				return Ok(());
+			};
+			let (poster, thumbnails) = tokio::task::spawn_blocking(move || render_poster(frame))
+				.await
+				.context("Poster rendering task failed")??;
+			let mut derivatives = vec![store_poster(self.storage, job, poster).await?];
+			derivatives.extend(store_thumbnails(self.storage, job, thumbnails).await?);
+			job.derivatives = derivatives;
+		} else if supports_audio_analysis(&job.content_type) {
+			let Some(source) = self.read_source(job).await? else {
+				return Ok(());
+			};
+			let content_type = job.content_type.clone();
+			let info = tokio::task::spawn_blocking(move || analyze_audio(source, &content_type))
+				.await
+				.context("Audio analysis task failed")?;
+			// A file that does not decode keeps its plain player; retrying
+			// will not change the bytes.
+			match info {
+				Ok(info) => job.media_info = Some(info),
+				Err(error) => tracing::warn!(
+					object_id = job.object_id,
+					error = ?error,
+					"Failed to analyze audio"
+				),
+			}
+		}
+		Ok(())
+	}
+
+	/// Writes the sanitized copy of an object rendering does not read in full,
+	/// or rewrites that of an already rendered object, whose owner's privacy
+	/// settings may have changed since.
+	async fn rewrite_sanitized(
+		&self,
+		job: &mut ObjectDerivativeJob,
+	) -> anyhow::Result<()> {
+		if job.metadata_privacy == MetadataPrivacy::Keep ||
+			sanitized_extension(&job.content_type).is_none()
+		{
+			return Ok(());
+		}
+		if matches!(job.content_type.as_str(), "video/mp4" | "audio/mp4") {
+			return self.rewrite_sanitized_mp4(job).await;
+		}
+		let Some(source) = self.read_source(job).await? else {
+			return Ok(());
+		};
+		let content_type = job.content_type.clone();
+		let privacy = job.metadata_privacy;
+		let sanitized =
+			tokio::task::spawn_blocking(move || sanitize_copy(&source, &content_type, privacy))
+				.await
+				.context("Sanitizing task failed")?;
+		self.record_sanitized(job, sanitized).await
+	}
+
+	/// Writes the sanitized copy of an MP4 from ranged reads of its boxes, so a
+	/// file of any size gets one: only the movie box is read whole, and the
+	/// media data is copied within the storage service.
+	async fn rewrite_sanitized_mp4(
+		&self,
+		job: &mut ObjectDerivativeJob,
+	) -> anyhow::Result<()> {
+		let length = self.storage.source_length(&job.storage_key).await?;
+		let max_movie_bytes =
+			u64::try_from(self.probe_read_max_bytes).context("Negative metadata read limit")?;
+		let mut sanitizer = Mp4Sanitizer::new(length, max_movie_bytes);
+		let sanitized = loop {
+			let Some(range) = sanitizer.next_read() else {
+				break sanitizer.finish(&job.content_type);
+			};
+			let bytes = self
+				.storage
+				.read_range(&job.storage_key, range.start, range.end - range.start)
+				.await?;
+			if let Err(error) = sanitizer.feed(&bytes) {
+				break Err(error);
+			}
+		};
+		match sanitized {
+			Ok(copy) => {
+				let derivative = store_sanitized_mp4(self.storage, job, length, copy).await?;
+				job.derivatives.push(derivative);
+			}
+			Err(error) => tracing::warn!(
+				object_id = job.object_id,
+				error = ?error,
+				"Failed to write a sanitized copy"
+			),
+		}
+		Ok(())
+	}
+
+	/// Stores a sanitized copy, if the privacy setting calls for one. Without a
+	/// sanitized copy other people are refused the original, so a file that
+	/// cannot be rewritten fails closed.
+	async fn record_sanitized(
+		&self,
+		job: &mut ObjectDerivativeJob,
+		sanitized: anyhow::Result<Option<SanitizedCopy>>,
+	) -> anyhow::Result<()> {
+		match sanitized {
+			Ok(Some(copy)) => {
+				let derivative = store_sanitized(self.storage, job, copy).await?;
+				job.derivatives.push(derivative);
+			}
+			Ok(None) => {}
+			Err(error) => tracing::warn!(
+				object_id = job.object_id,
+				error = ?error,
+				"Failed to write a sanitized copy"
+			),
+		}
+		Ok(())
+	}
+
+	/// Reads the whole source, or `None` when it is over the size limit. A
+	/// source that is read is hashed here if the object has no hash yet.
+	async fn read_source(
+		&self,
+		job: &mut ObjectDerivativeJob,
+	) -> anyhow::Result<Option<Vec<u8>>> {
+		let source = self.storage.read_source(&job.storage_key, self.max_source_bytes).await?;
+		match &source {
+			Some(source) if job.content_hash.is_none() =>
+				job.content_hash = Some(blake3::hash(source).to_string()),
+			Some(_) => {}
+			None => tracing::info!(
+				object_id = job.object_id,
+				"Skipping derivatives for an object over the source size limit"
+			),
+		}
+		Ok(source)
+	}
+
+	/// Takes a poster frame a second or a tenth of the way in, whichever comes
+	/// first: from the nearest H.264 keyframe when the video has one, otherwise
+	/// with the configured `ffmpeg`. A frame that cannot be taken (no `ffmpeg`,
+	/// an unsupported codec) leaves the video without a poster rather than
+	/// failing the job, so its probed media info is still recorded.
+	async fn take_poster_frame(
+		&self,
+		job: &mut ObjectDerivativeJob,
+		source: &MetadataSource,
+	) -> anyhow::Result<Option<Vec<u8>>> {
+		let seek_seconds = job
+			.media_info
+			.as_ref()
+			.and_then(|info| info.duration_seconds)
+			.map_or(0.0, |duration| (duration / 10.0).min(POSTER_SEEK_SECONDS));
+		let keyframe = h264_keyframe(source, seek_seconds).filter(|keyframe| {
+			i64::try_from(keyframe.length).is_ok_and(|length| length <= self.max_source_bytes)
+		});
+		if let Some(keyframe) = keyframe {
+			let sample =
+				self.storage.read_range(&job.storage_key, keyframe.offset, keyframe.length).await?;
+			let frame = tokio::task::spawn_blocking(move || keyframe.decode(&sample))
+				.await
+				.context("Keyframe decoding task failed")?;
+			match frame {
+				Ok(frame) => return Ok(Some(frame)),
+				Err(error) => tracing::warn!(
+					object_id = job.object_id,
+					error = ?error,
+					"Failed to decode an H.264 keyframe"
+				),
+			}
+		}
+		let Some(ffmpeg) = self.ffmpeg else {
+			return Ok(None);
+		};
+		let Some(source) = self.read_source(job).await? else {
+			return Ok(None);
+		};
+		match ffmpeg.extract_frame(source, seek_seconds).await {
+			Ok(frame) => Ok(Some(frame)),
+			Err(error) => {
+				tracing::warn!(
+					object_id = job.object_id,
+					error = ?error,
+					"Failed to extract a poster frame with ffmpeg"
+				);
+				Ok(None)
+			}
+		}
+	}
+
+	/// Decodes a photo browsers cannot show from a RAW file's embedded preview
+	/// or, failing that, with the configured `ffmpeg`. As with posters, a photo
+	/// that cannot be decoded is left without a display rendition rather than
+	/// failing the job; the original stays downloadable.
+	async fn decode_for_display(
+		&self,
+		job: &ObjectDerivativeJob,
+		source: Vec<u8>,
+	) -> anyhow::Result<Option<DynamicImage>> {
+		let source = if supports_raw_previews(&job.content_type) {
+			let (source, preview) = tokio::task::spawn_blocking(move || {
+				let preview = decode_embedded_preview(&source);
+				(source, preview)
+			})
+			.await
+			.context("RAW preview decoding task failed")?;
+			if preview.is_some() {
+				return Ok(preview);
+			}
+			source
+		} else {
+			source
+		};
+		let Some(ffmpeg) = self.ffmpeg else {
+			tracing::info!(
+				object_id = job.object_id,
+				"Skipping display rendition of a photo that needs ffmpeg to decode"
+			);
+			return Ok(None);
+		};
+		let decoded = match ffmpeg.extract_frame(source, 0.0).await {
+			Ok(frame) => tokio::task::spawn_blocking(move || decode_oriented(&frame))
+				.await
+				.context("Display decoding task failed")?,
+			Err(error) => Err(error),
+		};
+		match decoded {
+			Ok(image) => Ok(Some(image)),
+			Err(error) => {
+				tracing::warn!(
+					object_id = job.object_id,
+					error = ?error,
+					"Failed to decode a photo with ffmpeg"
+				);
+				Ok(None)
+			}
+		}
+	}
+}
+
+/// The configured `ffmpeg` binary and how long one run of it may take.
+#[derive(Clone, Copy, Debug)]
+struct Ffmpeg<'a> {
+	path: &'a Path,
+	timeout: Duration,
+}
+
+impl Ffmpeg<'_> {
+	async fn decodes_heic(&self) -> anyhow::Result<bool> {
+		let version = self.run(&["-hide_banner", "-version"]).await?;
+		if !supports_tiled_heic(&version) {
+			tracing::warn!(
+				version = version.lines().next().unwrap_or_default(),
+				"ffmpeg is older than 7.1 and cannot decode HEIC photos"
+			);
+			return Ok(false);
+		}
+		let decoders = self.run(&["-hide_banner", "-decoders"]).await?;
+		let hevc = decoders.lines().any(|line| line.split_whitespace().nth(1) == Some("hevc"));
+		if !hevc {
+			tracing::warn!("ffmpeg has no HEVC decoder and cannot decode HEIC photos");
+		}
+		Ok(hevc)
+	}
+
+	/// Runs `ffmpeg` with `args` and no input, returning what it printed.
+	async fn run(
+		&self,
+		args: &[&str],
+	) -> anyhow::Result<String> {
+		let output = tokio::time::timeout(
+			self.timeout,
+			tokio::process::Command::new(self.path)
+				.args(args)
+				.stdin(Stdio::null())
+				.kill_on_drop(true)
+				.output(),
+		)
+		.await
+		.map_err(|_| anyhow::anyhow!("ffmpeg did not finish within {}s", self.timeout.as_secs()))?
+		.context("Failed to run ffmpeg")?;
+		if !output.status.success() {
+			anyhow::bail!("ffmpeg exited with {}", output.status);
+		}
+		Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+	}
+
+	/// Writes `source` to a private temporary file, since `ffmpeg` cannot seek
+	/// in piped MP4s whose movie box follows the media data, and reads one PNG
+	/// frame from its output. A run that outlasts the timeout is killed.
+	async fn extract_frame(
+		&self,
+		source: Vec<u8>,
+		seek_seconds: f64,
+	) -> anyhow::Result<Vec<u8>> {
+		// A new file with a random name, so nothing planted in the shared
+		// temporary directory is followed or overwritten.
+		let input = tokio::task::spawn_blocking(move || -> anyhow::Result<NamedTempFile> {
+			let mut input = tempfile::Builder::new()
+				.prefix("memory-map-ffmpeg-")
+				.tempfile()
+				.context("Failed to create ffmpeg input file")?;
+			input.write_all(&source).context("Failed to write ffmpeg input")?;
+			Ok(input)
+		})
+		.await
+		.context("ffmpeg input writing task failed")??;
+		let output = tokio::time::timeout(
+			self.timeout,
+			tokio::process::Command::new(self.path)
+				.args(["-nostdin", "-v", "error", "-ss", &format!("{seek_seconds:.3}"), "-i"])
+				.arg(input.path())
+				.args(["-frames:v", "1", "-f", "image2pipe", "-c:v", "png", "pipe:1"])
+				.stdin(Stdio::null())
+				.kill_on_drop(true)
+				.output(),
+		)
+		.await;
+		if let Err(error) = input.close() {
+			tracing::warn!(error = ?error, "Failed to remove ffmpeg input file");
+		}
+		let output = output
+			.map_err(|_| {
+				anyhow::anyhow!("ffmpeg did not finish within {}s", self.timeout.as_secs())
+			})?
+			.context("Failed to run ffmpeg")?;
+		if !output.status.success() || output.stdout.is_empty() {
+			anyhow::bail!(
+				"ffmpeg exited with {}: {}",
+				output.status,
+				String::from_utf8_lossy(&output.stderr).trim()
+			);
+		}
+		Ok(output.stdout)
+	}
+}
+
+/// Whether `ffmpeg -version` output names release 7.1 or newer. Builds from
+/// the development branch (`N-...`) are taken to be new enough.
+fn supports_tiled_heic(version_output: &str) -> bool {
+	let Some(version) = version_output
+		.lines()
+		.next()
+		.and_then(|line| line.strip_prefix("ffmpeg version "))
+		.and_then(|rest| rest.split_whitespace().next())
+	else {
+		return false;
+	};
+	if version.starts_with("N-") {
+		return true;
+	}
+	let mut numbers =
+		version.trim_start_matches('n').split(|c: char| !c.is_ascii_digit()).map(str::parse::<u32>);
+	match (numbers.next(), numbers.next()) {
+		(Some(Ok(major)), Some(Ok(minor))) => (major, minor) >= (7, 1),
+		(Some(Ok(major)), _) => major > 7,
+		_ => false,
+	}
+}
+
+async fn store_poster(
+	storage: &impl DerivativeStorage,
+	job: &ObjectDerivativeJob,
+	poster: Poster,
+) -> anyhow::Result<StoredDerivative> {
+	let derivative = StoredDerivative {
+		kind: POSTER_KIND.to_string(),
+		storage_key: derivative_storage_key(&job.storage_key, POSTER_KIND, poster.extension),
+		content_type: poster.content_type.to_string(),
+		width: Some(i32::try_from(poster.width).context("Poster width out of range")?),
+		height: Some(i32::try_from(poster.height).context("Poster height out of range")?),
+		byte_size: i64::try_from(poster.bytes.len()).context("Poster size out of range")?,
+	};
+	storage
+		.write_derivative(&derivative.storage_key, poster.bytes, &derivative.content_type)
+		.await?;
+	Ok(derivative)
+}
+
+async fn store_display(
+	storage: &impl DerivativeStorage,
+	job: &ObjectDerivativeJob,
+	display: Display,
+) -> anyhow::Result<StoredDerivative> {
+	let derivative = StoredDerivative {
+		kind: DISPLAY_KIND.to_string(),
+		storage_key: derivative_storage_key(&job.storage_key, DISPLAY_KIND, Display::EXTENSION),
+		content_type: Display::CONTENT_TYPE.to_string(),
+		width: Some(i32::try_from(display.width).context("Display rendition width out of range")?),
+		height: Some(
+			i32::try_from(display.height).context("Display rendition height out of range")?,
+		),
+		byte_size: i64::try_from(display.bytes.len())
+			.context("Display rendition size out of range")?,
+	};
+	storage
+		.write_derivative(&derivative.storage_key, display.bytes, &derivative.content_type)
+		.await?;
+	Ok(derivative)
+}
+
+async fn store_sanitized(
+	storage: &impl DerivativeStorage,
+	job: &ObjectDerivativeJob,
+	copy: SanitizedCopy,
+) -> anyhow::Result<StoredDerivative> {
+	let (width, height) = sanitized_dimensions(copy.dimensions)?;
+	let derivative = StoredDerivative {
+		kind: SANITIZED_KIND.to_string(),
+		storage_key: derivative_storage_key(&job.storage_key, SANITIZED_KIND, copy.extension),
+		content_type: job.content_type.clone(),
+		width,
+		height,
+		byte_size: i64::try_from(copy.bytes.len()).context("Sanitized copy size out of range")?,
+	};
+	storage.write_derivative(&derivative.storage_key, copy.bytes, &derivative.content_type).await?;
+	Ok(derivative)
+}
+
+async fn store_sanitized_mp4(
+	storage: &impl DerivativeStorage,
+	job: &ObjectDerivativeJob,
+	length: u64,
+	copy: SanitizedMp4,
+) -> anyhow::Result<StoredDerivative> {
+	let (width, height) = sanitized_dimensions(copy.dimensions)?;
+	let derivative = StoredDerivative {
+		kind: SANITIZED_KIND.to_string(),
+		storage_key: derivative_storage_key(&job.storage_key, SANITIZED_KIND, copy.extension),
+		content_type: job.content_type.clone(),
+		width,
+		height,
+		byte_size: i64::try_from(length).context("Sanitized copy size out of range")?,
+	};
+	storage
+		.write_patched_copy(
+			&job.storage_key,
+			length,
+			&derivative.storage_key,
+			&derivative.content_type,
+			&copy.patches,
+		)
+		.await?;
+	Ok(derivative)
+}
+
+fn sanitized_dimensions(
+	dimensions: Option<(u32, u32)>
+) -> anyhow::Result<(Option<i32>, Option<i32>)> {
+	let Some((width, height)) = dimensions else {
+		return Ok((None, None));
+	};
+	Ok((
+		Some(i32::try_from(width).context("Sanitized copy width out of range")?),
+		Some(i32::try_from(height).context("Sanitized copy height out of range")?),
+	))
+}
+
+async fn store_thumbnails(
+	storage: &impl DerivativeStorage,
+	job: &ObjectDerivativeJob,
+	thumbnails: Vec<Thumbnail>,
+) -> anyhow::Result<Vec<StoredDerivative>> {
+	let mut derivatives = Vec::with_capacity(thumbnails.len());
+	for thumbnail in thumbnails {
+		let kind = thumbnail.kind();
+		let derivative = StoredDerivative {
+			storage_key: derivative_storage_key(
+				&job.storage_key,
+				&kind,
+				thumbnail.format.extension(),
+			),
+			kind,
+			content_type: thumbnail.format.content_type().to_string(),
+			width: Some(i32::try_from(thumbnail.width).context("Thumbnail width out of range")?),
+			height: Some(i32::try_from(thumbnail.height).context("Thumbnail height out of range")?),
+			byte_size: i64::try_from(thumbnail.bytes.len())
+				.context("Thumbnail size out of range")?,
+		};
+		storage
+			.write_derivative(&derivative.storage_key, thumbnail.bytes, &derivative.content_type)
+			.await?;
+		derivatives.push(derivative);
+	}
+	Ok(derivatives)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			DerivativeStorage,
+			Ffmpeg,
+			ObjectDerivativeConfig,
+			ObjectDerivativeJob,
+			ObjectDerivativeProcessor,
+			derivative_storage_key,
+			patched_copy_parts,
+			supports_tiled_heic,
+		},
+		crate::{
+			media::{
+				MetadataSource,
+				sanitize::{
+					MetadataPrivacy,
+					Mp4Patch,
+				},
+			},
+			outbox::OutboxProcessor,
+		},
+		image::{
+			DynamicImage,
+			ImageFormat,
+			RgbImage,
+		},
+		std::{
+			collections::HashMap,
+			io::Cursor,
+			path::Path,
+			sync::{
+				Mutex,
+				atomic::{
+					AtomicUsize,
+					Ordering,
+				},
+			},
+			time::Duration,
+		},
+	};
+
+	#[derive(Default)]
+	struct FakeStorage {
+		sources: HashMap<String, Vec<u8>>,
+		written: Mutex<Vec<(String, String)>>,
+		/// The bytes of each patched copy, by key.
+		patched: Mutex<HashMap<String, Vec<u8>>>,
+		/// How many times an object was read in full just to hash it.
+		hash_reads: AtomicUsize,
+	}
+
+	impl FakeStorage {
+		fn written(&self) -> anyhow::Result<Vec<(String, String)>> {
+			self.written
+				.lock()
+				.map(|written| written.clone())
+				.map_err(|_| anyhow::anyhow!("written mutex poisoned"))
+		}
+
+		fn patched(
+			&self,
+			storage_key: &str,
+		) -> anyhow::Result<Vec<u8>> {
+			self.patched
+				.lock()
+				.map_err(|_| anyhow::anyhow!("patched mutex poisoned"))?
+				.get(storage_key)
+				.cloned()
+				.ok_or_else(|| anyhow::anyhow!("no patched copy of {storage_key}"))
+		}
+	}
+
+	impl DerivativeStorage for FakeStorage {
+		async fn read_source(
+			&self,
+			storage_key: &str,
+			max_bytes: i64,
+		) -> anyhow::Result<Option<Vec<u8>>> {
+			let source = self
+				.sources
+				.get(storage_key)
+				.ok_or_else(|| anyhow::anyhow!("missing source {storage_key}"))?;
+			Ok((source.len() as i64 <= max_bytes).then(|| source.clone()))
+		}
+
+		async fn read_probe_source(
+			&self,
+			storage_key: &str,
+			_max_bytes: i64,
+		) -> anyhow::Result<MetadataSource> {
+			let source = self
+				.sources
+				.get(storage_key)
+				.ok_or_else(|| anyhow::anyhow!("missing source {storage_key}"))?;
+			Ok(MetadataSource {
+				head: source.clone(),
+				..MetadataSource::default()
+			})
+		}
+
+		async fn read_range(
+			&self,
+			storage_key: &str,
+			offset: u64,
+			length: u64,
+		) -> anyhow::Result<Vec<u8>> {
+			let source = self
+				.sources
+				.get(storage_key)
+				.ok_or_else(|| anyhow::anyhow!("missing source {storage_key}"))?;
+			let start = usize::try_from(offset)?.min(source.len());
+			let end = start.saturating_add(usize::try_from(length)?).min(source.len());
+			Ok(source.get(start .. end).unwrap_or_default().to_vec())
+		}
+
+		async fn source_length(
+			&self,
+			storage_key: &str,
+		) -> anyhow::Result<u64> {
+			let source = self
+				.sources
+				.get(storage_key)
+				.ok_or_else(|| anyhow::anyhow!("missing source {storage_key}"))?;
+			Ok(source.len() as u64)
+		}
+
+		async fn hash_source(
+			&self,
+			storage_key: &str,
+		) -> anyhow::Result<String> {
+			let source = self
+				.sources
+				.get(storage_key)
+				.ok_or_else(|| anyhow::anyhow!("missing source {storage_key}"))?;
+			self.hash_reads.fetch_add(1, Ordering::Relaxed);
+			Ok(blake3::hash(source).to_string())
+		}
+
+		async fn write_derivative(
+			&self,
+			storage_key: &str,
+			_bytes: Vec<u8>,
+			content_type: &str,
+		) -> anyhow::Result<()> {
+			self.written
+				.lock()
+				.map_err(|_| anyhow::anyhow!("written mutex poisoned"))?
+				.push((storage_key.to_string(), content_type.to_string()));
+			Ok(())
+		}
+
+		async fn write_patched_copy(
+			&self,
+			source_key: &str,
+			length: u64,
+			storage_key: &str,
+			content_type: &str,
+			patches: &[Mp4Patch],
+		) -> anyhow::Result<()> {
+			let mut bytes = self.read_range(source_key, 0, length).await?;
+			for patch in patches {
+				patch.apply(0, &mut bytes);
+			}
+			self.patched
+				.lock()
+				.map_err(|_| anyhow::anyhow!("patched mutex poisoned"))?
+				.insert(storage_key.to_string(), bytes);
+			self.write_derivative(storage_key, Vec::new(), content_type).await
+		}
+	}
+
+	fn job(
+		object_id: i64,
+		storage_key: &str,
+		content_type: &str,
+	) -> ObjectDerivativeJob {
+		ObjectDerivativeJob {
+			object_id,
+			storage_key: storage_key.to_string(),
+			content_type: content_type.to_string(),
+			available: true,
+			metadata_privacy: MetadataPrivacy::Keep,
+			rendered: false,
+			sanitized_only: false,
+			derivatives: Vec::new(),
+			media_info: None,
+			content_hash: None,
+		}
+	}
+
+	/// An `ffmpeg` that does not exist, for jobs that should never run it.
+	fn missing_ffmpeg() -> Ffmpeg<'static> {
+		Ffmpeg {
+			path: Path::new("/nonexistent/ffmpeg"),
+			timeout: Duration::from_secs(5),
+		}
+	}
+
+	fn png() -> anyhow::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::new(24, 12))
+			.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+		Ok(bytes)
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_writes_and_reports_every_rendition() -> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([("objects/abc".to_string(), png()?)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: None,
+		};
+
+		let outcome = processor.process(vec![job(7, "objects/abc", "image/png")]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		let recorded = cleared
+			.derivatives
+			.iter()
+			.map(|derivative| (derivative.storage_key.clone(), derivative.content_type.clone()))
+			.collect::<Vec<_>>();
+		assert_eq!(recorded, storage.written()?);
+		assert_eq!(recorded.len(), 6);
+		assert!(recorded.contains(&(
+			"derivatives/abc/thumbnail_small_webp.webp".to_string(),
+			"image/webp".to_string()
+		)));
+		assert!(
+			cleared
+				.derivatives
+				.iter()
+				.all(|derivative| (derivative.width, derivative.height) == (Some(24), Some(12)))
+		);
+		// The source read for rendering is hashed rather than read again.
+		assert_eq!(cleared.content_hash, Some(blake3::hash(&png()?).to_string()));
+		assert_eq!(storage.hash_reads.load(Ordering::Relaxed), 0);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_stores_a_sanitized_copy_when_metadata_is_withheld()
+	-> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([("objects/abc".to_string(), png()?)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: None,
+		};
+		let stripped = ObjectDerivativeJob {
+			metadata_privacy: MetadataPrivacy::StripLocation,
+			..job(7, "objects/abc", "image/png")
+		};
+
+		let outcome = processor.process(vec![stripped]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		assert_eq!(cleared.derivatives.len(), 7);
+		let sanitized = cleared
+			.derivatives
+			.iter()
+			.find(|derivative| derivative.kind == "sanitized")
+			.ok_or_else(|| anyhow::anyhow!("no sanitized copy was recorded"))?;
+		assert_eq!(sanitized.storage_key, "derivatives/abc/sanitized.png");
+		assert_eq!(sanitized.content_type, "image/png");
+		assert_eq!((sanitized.width, sanitized.height), (Some(24), Some(12)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_only_rewrites_the_sanitized_copy_of_rendered_objects()
+	-> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([
+				("objects/abc".to_string(), png()?),
+				("objects/clip".to_string(), b"an unhashed video".to_vec()),
+			]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: None,
+		};
+		let photo = ObjectDerivativeJob {
+			metadata_privacy: MetadataPrivacy::StripAll,
+			rendered: true,
+			content_hash: Some("ab".repeat(32)),
+			..job(7, "objects/abc", "image/png")
+		};
+		let video = ObjectDerivativeJob {
+			rendered: true,
+			..job(8, "objects/clip", "video/mp4")
+		};
+
+		let outcome = processor.process(vec![photo, video]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [photo, video] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly two cleared jobs");
+		};
+		assert_eq!(
+			photo.derivatives.iter().map(|derivative| derivative.kind.as_str()).collect::<Vec<_>>(),
+			vec!["sanitized"]
+		);
+		assert_eq!(
+			storage.written()?,
+			vec![("derivatives/abc/sanitized.png".to_string(), "image/png".to_string())]
+		);
+		assert_eq!(photo.content_hash, Some("ab".repeat(32)));
+		// Nothing is probed again; the missing hash is all that is computed.
+		assert!(video.derivatives.is_empty());
+		assert_eq!(video.media_info, None);
+		assert_eq!(video.content_hash, Some(blake3::hash(b"an unhashed video").to_string()));
+		assert_eq!(storage.hash_reads.load(Ordering::Relaxed), 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_only_rewrites_the_sanitized_copy_for_privacy_changes()
+	-> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([
+				("objects/abc".to_string(), png()?),
+				("objects/clip".to_string(), b"an unrendered video".to_vec()),
+			]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: None,
+		};
+		// Neither has been rendered, say because their first jobs gave up.
+		let photo = ObjectDerivativeJob {
+			metadata_privacy: MetadataPrivacy::StripLocation,
+			sanitized_only: true,
+			..job(7, "objects/abc", "image/png")
+		};
+		let video = ObjectDerivativeJob {
+			sanitized_only: true,
+			..job(8, "objects/clip", "video/mp4")
+		};
+
+		let outcome = processor.process(vec![photo, video]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [photo, video] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly two cleared jobs");
+		};
+		assert_eq!(
+			photo.derivatives.iter().map(|derivative| derivative.kind.as_str()).collect::<Vec<_>>(),
+			vec!["sanitized"]
+		);
+		assert_eq!(
+			storage.written()?,
+			vec![("derivatives/abc/sanitized.png".to_string(), "image/png".to_string())]
+		);
+		assert!(video.derivatives.is_empty());
+		assert_eq!(video.media_info, None);
+		assert_eq!(video.content_hash, None);
+		assert_eq!(storage.hash_reads.load(Ordering::Relaxed), 0);
+		Ok(())
+	}
+
+	/// A little-endian TIFF whose only IFD points at `jpeg` as its preview.
+	fn raw_with_preview(jpeg: &[u8]) -> anyhow::Result<Vec<u8>> {
+		let entry = |tag: u16, value: usize| -> anyhow::Result<Vec<u8>> {
+			Ok([
+				tag.to_le_bytes().as_slice(),
+				&4u16.to_le_bytes(),
+				&1u32.to_le_bytes(),
+				&u32::try_from(value)?.to_le_bytes(),
+			]
+			.concat())
+		};
+		// Header, entry count, two entries and the next IFD offset.
+		let preview_start = 8 + 2 + 2 * 12 + 4;
+		Ok([
+			b"II*\0\x08\0\0\0\x02\0".as_slice(),
+			&entry(0x0201, preview_start)?,
+			&entry(0x0202, jpeg.len())?,
+			&0u32.to_le_bytes(),
+			jpeg,
+		]
+		.concat())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_renders_a_display_jpeg_from_a_raw_preview() -> anyhow::Result<()>
+	{
+		let mut jpeg = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::new(24, 12))
+			.write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)?;
+		let storage = FakeStorage {
+			sources: HashMap::from([("objects/raw".to_string(), raw_with_preview(&jpeg)?)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			// Never run: the preview decodes.
+			ffmpeg: Some(missing_ffmpeg()),
+		};
+
+		let outcome = processor.process(vec![job(5, "objects/raw", "image/x-canon-cr2")]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		assert_eq!(cleared.derivatives.len(), 7);
+		let display = cleared
+			.derivatives
+			.iter()
+			.find(|derivative| derivative.kind == "display")
+			.ok_or_else(|| anyhow::anyhow!("no display rendition was recorded"))?;
+		assert_eq!(display.storage_key, "derivatives/raw/display.jpg");
+		assert_eq!(display.content_type, "image/jpeg");
+		assert_eq!((display.width, display.height), (Some(24), Some(12)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn heic_decoding_needs_ffmpeg_7_1() {
+		for (version, supported) in [
+			("ffmpeg version 7.1 Copyright (c) 2000-2024 the FFmpeg developers", true),
+			("ffmpeg version n7.1.1 Copyright (c) 2000-2025 the FFmpeg developers", true),
+			("ffmpeg version 8.0-static https://johnvansickle.com/ffmpeg/", true),
+			("ffmpeg version N-118000-g0123456789 Copyright (c) 2000-2025", true),
+			("ffmpeg version 7.0.2-3ubuntu1 Copyright (c) 2000-2024", false),
+			("ffmpeg version 6.1.1-3ubuntu5 Copyright (c) 2000-2023", false),
+			("", false),
+		] {
+			assert_eq!(supports_tiled_heic(version), supported, "{version}");
+		}
+
+		let missing = ObjectDerivativeConfig {
+			ffmpeg_path: Some("/nonexistent/ffmpeg".to_string()),
+			..ObjectDerivativeConfig::default()
+		};
+		assert!(!missing.decodes_heic().await);
+		assert!(!ObjectDerivativeConfig::default().decodes_heic().await);
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_leaves_heic_without_ffmpeg_undecoded() -> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([(
+				"objects/photo".to_string(),
+				b"\0\0\0\x18ftypheic\0\0\0\0mif1heic".to_vec(),
+			)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: None,
+		};
+
+		let outcome = processor.process(vec![job(6, "objects/photo", "image/heic")]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		assert!(cleared.derivatives.is_empty());
+		assert!(cleared.content_hash.is_some());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_probes_videos_and_stores_their_cover_art() -> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([(
+				"objects/clip".to_string(),
+				include_bytes!("../tests/fixtures/av1-cover-art.mp4").to_vec(),
+			)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			// Never run: the fixture carries cover art.
+			ffmpeg: Some(missing_ffmpeg()),
+		};
+
+		let outcome = processor.process(vec![job(4, "objects/clip", "video/mp4")]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		let info = cleared.media_info.clone().unwrap_or_default();
+		assert_eq!(info.duration_seconds, Some(1.0));
+		assert_eq!((info.width, info.height), (Some(64), Some(48)));
+		assert_eq!(info.video_codec.as_deref(), Some("av1"));
+		assert_eq!(cleared.derivatives.len(), 7);
+		assert!(
+			storage
+				.written()?
+				.contains(&("derivatives/clip/poster.jpg".to_string(), "image/jpeg".to_string()))
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_stores_a_sanitized_copy_of_videos() -> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([(
+				"objects/clip".to_string(),
+				include_bytes!("../tests/fixtures/av1-cover-art.mp4").to_vec(),
+			)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: Some(missing_ffmpeg()),
+		};
+		let stripped = ObjectDerivativeJob {
+			metadata_privacy: MetadataPrivacy::StripLocation,
+			..job(4, "objects/clip", "video/mp4")
+		};
+
+		let outcome = processor.process(vec![stripped]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		// The poster, its thumbnails and the sanitized copy.
+		assert_eq!(cleared.derivatives.len(), 8);
+		let sanitized = cleared
+			.derivatives
+			.iter()
+			.find(|derivative| derivative.kind == "sanitized")
+			.ok_or_else(|| anyhow::anyhow!("no sanitized copy was recorded"))?;
+		assert_eq!(sanitized.storage_key, "derivatives/clip/sanitized.mp4");
+		assert_eq!(sanitized.content_type, "video/mp4");
+		assert_eq!((sanitized.width, sanitized.height), (Some(64), Some(48)));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_sanitizes_videos_larger_than_the_source_limit()
+	-> anyhow::Result<()> {
+		let video = include_bytes!("../tests/fixtures/av1-cover-art.mp4");
+		let storage = FakeStorage {
+			sources: HashMap::from([("objects/clip".to_string(), video.to_vec())]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			// Below the file size: only the movie box is read.
+			max_source_bytes: 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: Some(missing_ffmpeg()),
+		};
+		let stripped = ObjectDerivativeJob {
+			metadata_privacy: MetadataPrivacy::StripAll,
+			..job(4, "objects/clip", "video/mp4")
+		};
+
+		let outcome = processor.process(vec![stripped]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		let sanitized = cleared
+			.derivatives
+			.iter()
+			.find(|derivative| derivative.kind == "sanitized")
+			.ok_or_else(|| anyhow::anyhow!("no sanitized copy was recorded"))?;
+		assert_eq!(sanitized.byte_size, video.len() as i64);
+		assert_eq!((sanitized.width, sanitized.height), (Some(64), Some(48)));
+		let copy = storage.patched("derivatives/clip/sanitized.mp4")?;
+		assert_eq!(copy.len(), video.len());
+		assert!(!copy.windows(4).any(|window| window == b"\xa9xyz"));
+		let media_data = video.windows(4).position(|window| window == b"mdat").unwrap_or_default();
+		assert_eq!(copy.get(media_data ..), video.get(media_data ..));
+		Ok(())
+	}
+
+	#[test]
+	fn patched_copies_only_read_the_parts_patches_touch() {
+		let mib = 1024 * 1024;
+		let patches = [
+			Mp4Patch::Write {
+				offset: 36,
+				bytes: b"free".to_vec(),
+			},
+			Mp4Patch::Zero(17 * mib .. 17 * mib + 10),
+		];
+
+		assert_eq!(
+			patched_copy_parts(20 * mib, &patches),
+			[(0 .. 8 * mib, true), (8 * mib .. 16 * mib, false), (16 * mib .. 20 * mib, true),]
+		);
+		// Parts grow to keep within the upload's part limit.
+		let parts = patched_copy_parts(200_000 * mib, &[]);
+		assert_eq!(parts.len(), 10_000);
+		assert_eq!(parts.first(), Some(&(0 .. 20 * mib, false)));
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_decodes_an_h264_keyframe_for_the_poster() -> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([(
+				"objects/h264".to_string(),
+				include_bytes!("../tests/fixtures/h264-keyframes.mp4").to_vec(),
+			)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			// Only the keyframe is read, so a limit below the file size still
+			// gets a poster.
+			max_source_bytes: 4096,
+			probe_read_max_bytes: 1024 * 1024,
+			// Never run: the keyframe is decoded in process.
+			ffmpeg: Some(missing_ffmpeg()),
+		};
+
+		let outcome = processor.process(vec![job(9, "objects/h264", "video/mp4")]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		assert_eq!(
+			cleared.media_info.as_ref().and_then(|info| info.video_codec.as_deref()),
+			Some("h264")
+		);
+		assert_eq!(cleared.derivatives.len(), 7);
+		assert!(
+			storage.written()?.iter().any(|(key, _)| key.starts_with("derivatives/h264/poster"))
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_records_video_info_when_no_poster_can_be_taken()
+	-> anyhow::Result<()> {
+		// An Ogg file with a lone Opus stream: no cover art, and the configured
+		// ffmpeg does not exist.
+		let mut opus_head = b"OpusHead\x01\x02\0\0\x80\xbb\0\0\0\0\0".to_vec();
+		let mut page = b"OggS\0\x02".to_vec();
+		page.extend_from_slice(&[0; 20]);
+		page.push(1);
+		page.push(opus_head.len() as u8);
+		page.append(&mut opus_head);
+		let storage = FakeStorage {
+			sources: HashMap::from([("objects/ogg".to_string(), page)]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024,
+			probe_read_max_bytes: 1024,
+			ffmpeg: Some(missing_ffmpeg()),
+		};
+
+		let outcome = processor.process(vec![job(5, "objects/ogg", "video/ogg")]).await;
+
+		assert!(outcome.failed.is_empty());
+		let [cleared] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected exactly one cleared job");
+		};
+		assert!(cleared.derivatives.is_empty());
+		assert_eq!(
+			cleared.media_info.as_ref().and_then(|info| info.audio_codec.as_deref()),
+			Some("opus")
+		);
+		assert!(storage.written()?.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_clears_jobs_it_cannot_render() -> anyhow::Result<()> {
+		let large = png()?;
+		let storage = FakeStorage {
+			sources: HashMap::from([
+				("objects/svg".to_string(), b"<svg />".to_vec()),
+				("objects/large".to_string(), large.clone()),
+			]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 8,
+			probe_read_max_bytes: 8,
+			ffmpeg: None,
+		};
+		let deleted = ObjectDerivativeJob {
+			available: false,
+			..job(1, "objects/deleted", "image/png")
+		};
+
+		let outcome = processor
+			.process(vec![
+				deleted,
+				job(2, "objects/svg", "image/svg+xml"),
+				job(3, "objects/large", "image/png"),
+			])
+			.await;
+
+		assert!(outcome.failed.is_empty());
+		assert_eq!(
+			outcome.cleared.iter().map(|job| job.object_id).collect::<Vec<_>>(),
+			vec![1, 2, 3]
+		);
+		assert!(outcome.cleared.iter().all(|job| job.derivatives.is_empty()));
+		assert!(storage.written()?.is_empty());
+		// Objects without derivatives are still hashed, whatever their size.
+		assert_eq!(
+			outcome.cleared.iter().map(|job| job.content_hash.clone()).collect::<Vec<_>>(),
+			vec![
+				None,
+				Some(blake3::hash(b"<svg />").to_string()),
+				Some(blake3::hash(&large).to_string()),
+			]
+		);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_isolates_render_failures_per_job() -> anyhow::Result<()> {
+		let storage = FakeStorage {
+			sources: HashMap::from([("objects/corrupt".to_string(), b"not a png".to_vec())]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024,
+			probe_read_max_bytes: 1024,
+			ffmpeg: None,
+		};
+
+		let outcome = processor.process(vec![job(9, "objects/corrupt", "image/png")]).await;
+
+		assert!(outcome.cleared.is_empty());
+		let [group] = outcome.failed.as_slice() else {
+			anyhow::bail!("expected exactly one failed group");
+		};
+		assert_eq!(group.items.iter().map(|job| job.object_id).collect::<Vec<_>>(), vec![9]);
+		Ok(())
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn ffmpeg_runs_are_killed_at_the_timeout() -> anyhow::Result<()> {
+		use std::os::unix::fs::PermissionsExt;
+
+		let directory = tempfile::tempdir()?;
+		let path = directory.path().join("ffmpeg");
+		std::fs::write(&path, "#!/bin/sh\nexec sleep 30\n")?;
+		std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+		let ffmpeg = Ffmpeg {
+			path: &path,
+			timeout: Duration::from_millis(200),
+		};
+
+		let started = std::time::Instant::now();
+		let result = ffmpeg.extract_frame(b"not a video".to_vec(), 0.0).await;
+
+		let error = result.err().ok_or_else(|| anyhow::anyhow!("a hung ffmpeg must fail"))?;
+		assert!(error.to_string().contains("did not finish"), "{error:?}");
+		assert!(started.elapsed() < Duration::from_secs(10));
+		Ok(())
+	}
+
+	#[test]
+	fn derivative_storage_keys_extend_the_source_key() {
+		assert_eq!(
+			derivative_storage_key("objects/abc", "thumbnail_large_avif", "avif"),
+			"derivatives/abc/thumbnail_large_avif.avif"
+		);
+		assert_eq!(
+			derivative_storage_key("legacy.png", "thumbnail_small_webp", "webp"),
+			"derivatives/legacy.png/thumbnail_small_webp.webp"
+		);
+	}
+
+	#[tokio::test]
+	async fn derivative_processor_analyzes_audio_without_storing_renditions() -> anyhow::Result<()>
+	{
+		let storage = FakeStorage {
+			sources: HashMap::from([
+				(
+					"objects/tone".to_string(),
+					include_bytes!("../tests/fixtures/tagged-tone.wav").to_vec(),
+				),
+				("objects/noise".to_string(), b"not really audio".to_vec()),
+			]),
+			..FakeStorage::default()
+		};
+		let processor = ObjectDerivativeProcessor {
+			storage: &storage,
+			max_source_bytes: 1024 * 1024,
+			probe_read_max_bytes: 1024 * 1024,
+			ffmpeg: None,
+		};
+
+		let outcome = processor
+			.process(vec![
+				job(8, "objects/tone", "audio/wav"),
+				job(9, "objects/noise", "audio/mpeg"),
+			])
+			.await;
+
+		// Undecodable audio clears with nothing recorded rather than retrying.
+		assert!(outcome.failed.is_empty());
+		let [tone, noise] = outcome.cleared.as_slice() else {
+			anyhow::bail!("expected both jobs to clear");
+		};
+		let info = tone.media_info.clone().unwrap_or_default();
+		assert_eq!(info.tags.title.as_deref(), Some("Harbour at dawn"));
+		assert_eq!(info.duration_seconds, Some(1.0));
+		assert!(info.peaks.is_some_and(|peaks| !peaks.is_empty()));
+		assert!(noise.media_info.is_none());
+		assert!(tone.derivatives.is_empty());
+		assert!(storage.written()?.is_empty());
+		Ok(())
+	}
+}
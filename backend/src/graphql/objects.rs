@@ -3,10 +3,22 @@ use {
 	tokio_postgres::Row,
 };
 
+pub mod album;
 pub mod config;
+pub mod duplicate_pair;
 pub mod location;
+pub mod map_cluster;
+pub mod object_revision;
 pub mod s3_object;
+pub mod s3_object_connection;
+pub mod search;
+pub mod session;
+pub mod share_link;
+pub mod tag;
+pub mod timeline;
+pub mod trashed_object;
 pub mod upload_session;
 pub mod user;
+pub mod user_group;
 
 pub struct RowContext<'a>(pub Row, pub Context<'a>);
@@ -0,0 +1,446 @@
+use {
+	crate::{
+		ContextWrapper,
+		db::queries::{
+			DELETE_ALBUM_ALLOWED_GROUPS_QUERY,
+			DELETE_ALBUM_ALLOWED_USERS_QUERY,
+			DELETE_ALBUM_OBJECTS_QUERY,
+			DELETE_ALBUM_QUERY,
+			INSERT_ALBUM_OBJECTS_QUERY,
+			INSERT_ALBUM_QUERY,
+			LOCK_ALBUM_FOR_UPDATE_QUERY,
+			REORDER_ALBUM_OBJECTS_QUERY,
+			REPLACE_ALBUM_ALLOWED_GROUPS_QUERY,
+			REPLACE_ALBUM_ALLOWED_USERS_QUERY,
+			SELECT_ALBUM_BY_ID_QUERY,
+			SELECT_ALBUM_COVER_QUERY,
+			SELECT_ALBUM_OBJECT_IDS_QUERY,
+			SELECT_ALBUM_OBJECTS_QUERY,
+			SELECT_VISIBLE_ALBUM_BY_ID_QUERY,
+			SELECT_VISIBLE_ALBUMS_QUERY,
+			UPDATE_ALBUM_QUERY,
+		},
+		errors::AppError,
+		graphql::objects::{
+			s3_object::{
+				PublicityOverride,
+				S3Object,
+			},
+			user_group::require_granted_groups,
+		},
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		Error as GraphQLError,
+		ID,
+		Object,
+	},
+	jiff::Timestamp,
+	std::collections::HashSet,
+	tokio_postgres::{
+		Row,
+		Transaction,
+	},
+};
+
+/// An ordered collection of its owner's objects. Whoever can see an album,
+/// by the same publicity rules as objects, can see every object in it.
+#[derive(Clone, Debug)]
+pub struct Album {
+	pub id: i64,
+	pub user_id: i64,
+	pub title: String,
+	pub description: String,
+	pub cover_object_id: Option<i64>,
+	pub publicity: PublicityOverride,
+	pub allowed_users: Vec<String>,
+	pub allowed_groups: Vec<i64>,
+	pub created_at: Timestamp,
+	pub updated_at: Timestamp,
+}
+
+/// The fields of an album its owner sets, shared by creating and updating one.
+/// `None` allowed groups keeps the album's current ones.
+pub struct AlbumDetails {
+	pub title: String,
+	pub description: String,
+	pub publicity: PublicityOverride,
+	pub allowed_users: Vec<String>,
+	pub allowed_groups: Option<Vec<i64>>,
+}
+
+impl AlbumDetails {
+	pub fn new(
+		title: &str,
+		description: Option<String>,
+		publicity: PublicityOverride,
+		allowed_users: Option<Vec<String>>,
+		allowed_groups: Option<Vec<i64>>,
+	) -> Result<Self, AppError> {
+		let title = title.trim();
+		if title.is_empty() {
+			return Err(AppError::Validation("Album title must not be empty".to_string()));
+		}
+		Ok(Self {
+			title: title.to_string(),
+			description: description.unwrap_or_default(),
+			publicity,
+			allowed_users: allowed_users.unwrap_or_default(),
+			allowed_groups,
+		})
+	}
+}
+
+/// `ids` without repeats, each where it first appears.
+pub fn distinct_ids(ids: &[i64]) -> Vec<i64> {
+	let mut seen = HashSet::with_capacity(ids.len());
+	ids.iter().copied().filter(|id| seen.insert(*id)).collect()
+}
+
+/// Whether `order` lists each of `current` exactly once.
+fn is_reordering_of(
+	order: &[i64],
+	current: &[i64],
+) -> bool {
+	let mut order = order.to_vec();
+	let mut current = current.to_vec();
+	order.sort_unstable();
+	current.sort_unstable();
+	order == current
+}
+
+impl TryFrom<Row> for Album {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(Album {
+			id: row.try_get("id").context("Failed to read album id")?,
+			user_id: row.try_get("user_id").context("Failed to read album user_id")?,
+			title: row.try_get("title").context("Failed to read album title")?,
+			description: row.try_get("description").context("Failed to read album description")?,
+			cover_object_id: row
+				.try_get("cover_object_id")
+				.context("Failed to read album cover_object_id")?,
+			publicity: row.try_get("publicity").context("Failed to read album publicity")?,
+			allowed_users: row
+				.try_get("allowed_users")
+				.context("Failed to read album allowed_users")?,
+			allowed_groups: row
+				.try_get("allowed_groups")
+				.context("Failed to read album allowed_groups")?,
+			created_at: row.try_get("created_at").context("Failed to read album created_at")?,
+			updated_at: row.try_get("updated_at").context("Failed to read album updated_at")?,
+		})
+	}
+}
+
+impl Album {
+	pub async fn by_id(
+		ctx: &Context<'_>,
+		id: i64,
+	) -> Result<Self, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_ALBUM_BY_ID_QUERY).await?;
+		client
+			.query_opt(&statement, &[&id])
+			.await?
+			.map(Self::try_from)
+			.transpose()?
+			.ok_or_else(|| AppError::NotFound("Album not found".to_string()))
+	}
+
+	/// Album `id`, if `user_id` may see it (any album when `read_all`).
+	pub async fn visible_by_id(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		id: i64,
+	) -> Result<Option<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_VISIBLE_ALBUM_BY_ID_QUERY).await?;
+		client
+			.query_opt(&statement, &[&user_id, &read_all, &id])
+			.await?
+			.map(Self::try_from)
+			.transpose()
+	}
+
+	/// The albums `user_id` may see (every album when `read_all`), only those
+	/// of `owner_id` when given, newest first.
+	pub async fn visible(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		owner_id: Option<i64>,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_VISIBLE_ALBUMS_QUERY).await?;
+		client
+			.query(&statement, &[&user_id, &read_all, &owner_id])
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	pub async fn create(
+		ctx: &Context<'_>,
+		user_id: i64,
+		details: AlbumDetails,
+	) -> Result<Self, AppError> {
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		let id: i64 = transaction
+			.query_one(
+				INSERT_ALBUM_QUERY,
+				&[&user_id, &details.title, &details.description, &details.publicity],
+			)
+			.await
+			.context("Failed to insert album")?
+			.try_get("id")
+			.context("Failed to read album id")?;
+		replace_allowed_users(&transaction, id, details.allowed_users).await?;
+		if let Some(allowed_groups) = details.allowed_groups {
+			replace_allowed_groups(&transaction, id, allowed_groups).await?;
+		}
+		transaction.commit().await?;
+		Self::by_id(ctx, id).await
+	}
+
+	/// Replaces the album's details. `cover_object_id` must be one of its
+	/// objects, or `None` to show the first one as the cover.
+	pub async fn update(
+		ctx: &Context<'_>,
+		id: i64,
+		details: AlbumDetails,
+		cover_object_id: Option<i64>,
+	) -> Result<Self, AppError> {
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		let updated = transaction
+			.execute(
+				UPDATE_ALBUM_QUERY,
+				&[&id, &details.title, &details.description, &cover_object_id, &details.publicity],
+			)
+			.await
+			.context("Failed to update album")?;
+		if updated == 0 {
+			return Err(AppError::Validation(
+				"An album's cover must be one of its objects".to_string(),
+			));
+		}
+		replace_allowed_users(&transaction, id, details.allowed_users).await?;
+		if let Some(allowed_groups) = details.allowed_groups {
+			replace_allowed_groups(&transaction, id, allowed_groups).await?;
+		}
+		transaction.commit().await?;
+		Self::by_id(ctx, id).await
+	}
+
+	pub async fn delete(
+		ctx: &Context<'_>,
+		id: i64,
+	) -> Result<(), AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		client.execute(DELETE_ALBUM_QUERY, &[&id]).await.context("Failed to delete album")?;
+		Ok(())
+	}
+
+	/// Appends `object_ids` to the album in the order given. Objects already
+	/// in it keep their place.
+	pub async fn add_objects(
+		ctx: &Context<'_>,
+		id: i64,
+		object_ids: &[i64],
+	) -> Result<(), AppError> {
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		transaction
+			.execute(LOCK_ALBUM_FOR_UPDATE_QUERY, &[&id])
+			.await
+			.context("Failed to lock album")?;
+		transaction
+			.execute(INSERT_ALBUM_OBJECTS_QUERY, &[&id, &object_ids])
+			.await
+			.context("Failed to add objects to album")?;
+		transaction.commit().await?;
+		Ok(())
+	}
+
+	pub async fn remove_objects(
+		ctx: &Context<'_>,
+		id: i64,
+		object_ids: &[i64],
+	) -> Result<(), AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		client
+			.execute(DELETE_ALBUM_OBJECTS_QUERY, &[&id, &object_ids])
+			.await
+			.context("Failed to remove objects from album")?;
+		Ok(())
+	}
+
+	/// Puts the album's objects in the order of `object_ids`, which must list
+	/// each of them once.
+	pub async fn reorder(
+		ctx: &Context<'_>,
+		id: i64,
+		object_ids: &[i64],
+	) -> Result<(), AppError> {
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		transaction
+			.execute(LOCK_ALBUM_FOR_UPDATE_QUERY, &[&id])
+			.await
+			.context("Failed to lock album")?;
+		let current = transaction
+			.query(SELECT_ALBUM_OBJECT_IDS_QUERY, &[&id])
+			.await
+			.context("Failed to load album objects")?
+			.into_iter()
+			.map(|row| row.try_get("object_id").context("Failed to read album object id"))
+			.collect::<Result<Vec<i64>, _>>()?;
+		if !is_reordering_of(object_ids, &current) {
+			return Err(AppError::Validation(
+				"The new order must list each of the album's objects once".to_string(),
+			));
+		}
+		transaction
+			.execute(REORDER_ALBUM_OBJECTS_QUERY, &[&id, &object_ids])
+			.await
+			.context("Failed to reorder album")?;
+		transaction.commit().await?;
+		Ok(())
+	}
+}
+
+async fn replace_allowed_users(
+	transaction: &Transaction<'_>,
+	album_id: i64,
+	allowed_users: Vec<String>,
+) -> Result<(), AppError> {
+	transaction
+		.execute(DELETE_ALBUM_ALLOWED_USERS_QUERY, &[&album_id])
+		.await
+		.context("Failed to delete album allowed users from database")?;
+	if !allowed_users.is_empty() {
+		transaction
+			.execute(REPLACE_ALBUM_ALLOWED_USERS_QUERY, &[&album_id, &allowed_users])
+			.await
+			.context("Failed to replace album allowed users in database")?;
+	}
+	Ok(())
+}
+
+/// Shares the album with `allowed_groups` instead of the groups it was shared
+/// with, all of which must be its owner's.
+async fn replace_allowed_groups(
+	transaction: &Transaction<'_>,
+	album_id: i64,
+	allowed_groups: Vec<i64>,
+) -> Result<(), AppError> {
+	transaction
+		.execute(DELETE_ALBUM_ALLOWED_GROUPS_QUERY, &[&album_id])
+		.await
+		.context("Failed to delete album allowed groups from database")?;
+	let allowed_groups = distinct_ids(&allowed_groups);
+	let granted = transaction
+		.query(REPLACE_ALBUM_ALLOWED_GROUPS_QUERY, &[&album_id, &allowed_groups])
+		.await
+		.context("Failed to replace album allowed groups in database")?
+		.into_iter()
+		.map(|row| row.try_get("group_id").context("Failed to get group id from database row"))
+		.collect::<Result<Vec<i64>, _>>()?;
+	require_granted_groups(&allowed_groups, &granted)
+}
+
+#[Object]
+impl Album {
+	async fn id(&self) -> ID {
+		ID(self.id.to_string())
+	}
+
+	async fn title(&self) -> &str {
+		&self.title
+	}
+
+	async fn description(&self) -> &str {
+		&self.description
+	}
+
+	async fn publicity(&self) -> PublicityOverride {
+		self.publicity
+	}
+
+	async fn allowed_users(&self) -> Vec<String> {
+		self.allowed_users.clone()
+	}
+
+	/// The owner's groups the album is shared with when `SELECTED_USERS`.
+	async fn allowed_groups(&self) -> Vec<ID> {
+		self.allowed_groups.iter().map(|id| ID(id.to_string())).collect()
+	}
+
+	async fn created_at(&self) -> String {
+		self.created_at.to_string()
+	}
+
+	async fn updated_at(&self) -> String {
+		self.updated_at.to_string()
+	}
+
+	/// The album's objects in album order.
+	async fn objects(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_ALBUM_OBJECTS_QUERY).await?;
+		client
+			.query(&statement, &[&self.id])
+			.await?
+			.into_iter()
+			.map(S3Object::try_from)
+			.collect::<Result<_, _>>()
+			.map_err(AppError::graphql)
+	}
+
+	/// The object chosen as the cover, or else the first one. Null for an
+	/// empty album.
+	async fn cover(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<S3Object>, GraphQLError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_ALBUM_COVER_QUERY).await?;
+		client
+			.query_opt(&statement, &[&self.id])
+			.await?
+			.map(S3Object::try_from)
+			.transpose()
+			.map_err(AppError::graphql)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		distinct_ids,
+		is_reordering_of,
+	};
+
+	#[test]
+	fn distinct_ids_keep_first_appearances_in_order() {
+		assert_eq!(distinct_ids(&[3, 1, 3, 2, 1]), vec![3, 1, 2]);
+	}
+
+	#[test]
+	fn reorderings_list_every_object_once() {
+		assert!(is_reordering_of(&[3, 1, 2], &[1, 2, 3]));
+		assert!(is_reordering_of(&[], &[]));
+		assert!(!is_reordering_of(&[1, 2], &[1, 2, 3]));
+		assert!(!is_reordering_of(&[1, 1, 2], &[1, 2, 3]));
+		assert!(!is_reordering_of(&[1, 2, 4], &[1, 2, 3]));
+	}
+}
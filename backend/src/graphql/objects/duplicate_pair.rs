@@ -0,0 +1,67 @@
+use {
+	crate::{
+		ContextWrapper,
+		db::queries::SELECT_POSSIBLE_DUPLICATES_QUERY,
+		errors::AppError,
+		graphql::objects::s3_object::S3Object,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		SimpleObject,
+	},
+	std::collections::HashMap,
+};
+
+/// Two of a user's images whose perceptual hashes are close enough that one
+/// may be a resized, recompressed or lightly edited copy of the other. `first`
+/// is the older upload.
+#[derive(Debug, SimpleObject)]
+pub struct DuplicatePair {
+	pub first: S3Object,
+	pub second: S3Object,
+	/// How many of the 64 hash bits differ; 0 means the images look the same.
+	pub distance: i32,
+}
+
+impl DuplicatePair {
+	/// The user's undismissed pairs within `max_distance`, closest first.
+	pub async fn possible_for_user(
+		ctx: &Context<'_>,
+		user_id: i64,
+		max_distance: i64,
+		limit: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_POSSIBLE_DUPLICATES_QUERY).await?;
+		let rows = client.query(&statement, &[&user_id, &max_distance, &limit]).await?;
+		let mut pairs = Vec::with_capacity(rows.len());
+		for row in rows {
+			let first: i64 =
+				row.try_get("first_object_id").context("Failed to read duplicate pair id")?;
+			let second: i64 =
+				row.try_get("second_object_id").context("Failed to read duplicate pair id")?;
+			let distance: i32 =
+				row.try_get("distance").context("Failed to read duplicate pair distance")?;
+			pairs.push((first, second, distance));
+		}
+
+		let ids: Vec<i64> = pairs.iter().flat_map(|(first, second, _)| [*first, *second]).collect();
+		let objects: HashMap<i64, S3Object> = S3Object::where_ids(ctx, &ids)
+			.await?
+			.into_iter()
+			.map(|object| (object.id, object))
+			.collect();
+		// An object deleted between the two queries drops its pairs.
+		Ok(pairs
+			.into_iter()
+			.filter_map(|(first, second, distance)| {
+				Some(Self {
+					first: objects.get(&first)?.clone(),
+					second: objects.get(&second)?.clone(),
+					distance,
+				})
+			})
+			.collect())
+	}
+}
@@ -5,13 +5,21 @@ use {
 		parse_longitude,
 	},
 	async_graphql::{
+		Enum,
 		InputObject,
 		SimpleObject,
 	},
-	serde::Serialize,
+	postgres_types::{
+		FromSql,
+		ToSql,
+	},
+	serde::{
+		Deserialize,
+		Serialize,
+	},
 };
 
-#[derive(SimpleObject, InputObject, Clone, Debug, Serialize)]
+#[derive(SimpleObject, InputObject, Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[graphql(concrete(name = "Location", input_name = "LocationInput", params()))]
 pub struct Location {
 	pub latitude: f64,
@@ -31,12 +39,113 @@ impl Location {
 		let longitude = parse_longitude(self.longitude)?;
 		Ok(format!("SRID=4326;POINT({longitude} {latitude})"))
 	}
+
+	/// The location as shown at `precision`, or `None` when it is hidden. Must
+	/// match the `coarsen_location` SQL function, which spatial queries show
+	/// other people's objects at.
+	pub fn coarsened(
+		&self,
+		precision: LocationPrecision,
+	) -> Option<Self> {
+		let snap = |degrees: f64, limit: f64, cell: f64| {
+			((degrees / cell).floor() * cell + cell / 2.0).min(limit - cell / 2.0)
+		};
+		if precision == LocationPrecision::Hidden {
+			return None;
+		}
+		let Some(cell) = precision.cell_degrees() else {
+			return Some(self.clone());
+		};
+		Some(Self {
+			latitude: snap(self.latitude, 90.0, cell),
+			longitude: snap(self.longitude, 180.0, cell),
+		})
+	}
+}
+
+/// How precisely people other than the owner see where an object was made.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default, ToSql, FromSql)]
+#[postgres(name = "location_precision")]
+pub enum LocationPrecision {
+	/// Show the location as recorded.
+	#[default]
+	#[postgres(name = "exact")]
+	Exact,
+	/// Show the centre of the 0.001 degree (about 100 m) cell it falls in.
+	#[graphql(name = "APPROX_100M")]
+	#[postgres(name = "approx_100m")]
+	Approx100M,
+	/// Show the centre of the 0.01 degree (about 1 km) cell it falls in.
+	#[graphql(name = "APPROX_1KM")]
+	#[postgres(name = "approx_1km")]
+	Approx1Km,
+	/// Show the centre of the 0.1 degree (about 10 km) cell it falls in,
+	/// roughly which town or city.
+	#[postgres(name = "city")]
+	City,
+	/// Show no location at all.
+	#[postgres(name = "hidden")]
+	Hidden,
+}
+
+impl LocationPrecision {
+	/// How many degrees across the grid cells are that locations are snapped
+	/// to, or `None` when they are not snapped.
+	fn cell_degrees(self) -> Option<f64> {
+		match self {
+			LocationPrecision::Exact | LocationPrecision::Hidden => None,
+			LocationPrecision::Approx100M => Some(0.001),
+			LocationPrecision::Approx1Km => Some(0.01),
+			LocationPrecision::City => Some(0.1),
+		}
+	}
+}
+
+/// A latitude/longitude box, as a map viewport describes it. `west` may be east
+/// of `east` when the box crosses the antimeridian.
+#[derive(SimpleObject, InputObject, Clone, Debug, PartialEq)]
+#[graphql(concrete(name = "Bounds", input_name = "BoundsInput", params()))]
+pub struct Bounds {
+	pub south: f64,
+	pub west: f64,
+	pub north: f64,
+	pub east: f64,
+}
+
+impl Bounds {
+	pub fn new(
+		south: f64,
+		west: f64,
+		north: f64,
+		east: f64,
+	) -> Result<Self, AppError> {
+		let bounds = Self {
+			south: parse_latitude(south)?,
+			west: parse_longitude(west)?,
+			north: parse_latitude(north)?,
+			east: parse_longitude(east)?,
+		};
+		if bounds.south > bounds.north {
+			return Err(AppError::Validation(format!(
+				"The south edge ({south}) is north of the north edge ({north})."
+			)));
+		}
+		Ok(bounds)
+	}
+
+	pub fn validated(self) -> Result<Self, AppError> {
+		Self::new(self.south, self.west, self.north, self.east)
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use {
-		super::Location,
+		super::{
+			Bounds,
+			Location,
+			LocationPrecision,
+		},
 		crate::errors::AppError,
 	};
 
@@ -88,4 +197,56 @@ mod tests {
 			assert_eq!(geometry, "SRID=4326;POINT(-45.25 12.5)");
 		}
 	}
+
+	#[test]
+	fn coarsened_snaps_to_the_centre_of_the_cell() -> anyhow::Result<()> {
+		let location = Location {
+			latitude: 51.50735,
+			longitude: -0.12776,
+		};
+
+		assert_eq!(location.coarsened(LocationPrecision::Exact), Some(location.clone()));
+		assert_eq!(location.coarsened(LocationPrecision::Hidden), None);
+		let coarsened = location
+			.coarsened(LocationPrecision::Approx1Km)
+			.ok_or_else(|| anyhow::anyhow!("location was hidden"))?;
+		assert!((coarsened.latitude - 51.505).abs() < 1e-9, "{coarsened:?}");
+		assert!((coarsened.longitude + 0.125).abs() < 1e-9, "{coarsened:?}");
+		// Every location in a cell shows as the same point.
+		let neighbour = Location {
+			latitude: 51.5001,
+			longitude: -0.1299,
+		};
+		assert_eq!(neighbour.coarsened(LocationPrecision::Approx1Km), Some(coarsened));
+		Ok(())
+	}
+
+	#[test]
+	fn coarsened_stays_within_range() -> anyhow::Result<()> {
+		let corner = Location {
+			latitude: 90.0,
+			longitude: 180.0,
+		};
+		for precision in
+			[LocationPrecision::Approx100M, LocationPrecision::Approx1Km, LocationPrecision::City]
+		{
+			let coarsened = corner
+				.coarsened(precision)
+				.ok_or_else(|| anyhow::anyhow!("location was hidden"))?;
+			assert!(coarsened.validated().is_ok(), "{precision:?} left the valid range");
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn bounds_accept_boxes_crossing_the_antimeridian() {
+		assert!(Bounds::new(-10.0, 170.0, 10.0, -170.0).is_ok());
+	}
+
+	#[test]
+	fn bounds_reject_inverted_or_out_of_range_edges() {
+		assert!(matches!(Bounds::new(10.0, 0.0, -10.0, 1.0), Err(AppError::Validation(_))));
+		assert!(matches!(Bounds::new(-91.0, 0.0, 10.0, 1.0), Err(AppError::Validation(_))));
+		assert!(matches!(Bounds::new(0.0, 0.0, 10.0, 181.0), Err(AppError::Validation(_))));
+	}
 }
@@ -0,0 +1,169 @@
+use {
+	crate::{
+		ContextWrapper,
+		constants::{
+			MAP_CLUSTER_CELLS_PER_TILE,
+			MAP_CLUSTER_SAMPLE_SIZE,
+		},
+		db::queries::SELECT_MAP_CLUSTERS_QUERY,
+		errors::AppError,
+		graphql::objects::{
+			location::{
+				Bounds,
+				Location,
+			},
+			s3_object_connection::ObjectPageFilter,
+		},
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		ID,
+		Object,
+	},
+	jiff::Timestamp,
+	tokio_postgres::Row,
+};
+
+/// Nearby objects the map shows as one marker. Clusters are cells of a grid
+/// whose size follows the zoom level, so zooming in splits them.
+#[derive(Clone, Debug)]
+pub struct MapCluster {
+	pub count: i64,
+	pub centroid: Location,
+	pub bounds: Bounds,
+	pub earliest_made_on: Option<Timestamp>,
+	pub latest_made_on: Option<Timestamp>,
+	pub object_ids: Vec<i64>,
+}
+
+impl TryFrom<Row> for MapCluster {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		let coordinate = |column: &str| -> Result<f64, AppError> {
+			Ok(row
+				.try_get(column)
+				.with_context(|| format!("Failed to read map cluster {column}"))?)
+		};
+		Ok(Self {
+			count: row.try_get("count").context("Failed to read map cluster count")?,
+			centroid: Location {
+				latitude: coordinate("latitude")?,
+				longitude: coordinate("longitude")?,
+			},
+			bounds: Bounds {
+				south: coordinate("south")?,
+				west: coordinate("west")?,
+				north: coordinate("north")?,
+				east: coordinate("east")?,
+			},
+			earliest_made_on: row
+				.try_get("earliest_made_on")
+				.context("Failed to read map cluster earliest_made_on")?,
+			latest_made_on: row
+				.try_get("latest_made_on")
+				.context("Failed to read map cluster latest_made_on")?,
+			object_ids: row
+				.try_get("object_ids")
+				.context("Failed to read map cluster object_ids")?,
+		})
+	}
+}
+
+impl MapCluster {
+	/// The side of a grid cell at `zoom`, in degrees. Web Mercator tiles are
+	/// 360 degrees of longitude across at zoom 0 and halve with each level. The
+	/// cells are square in degrees, so they show taller than wide towards the
+	/// poles.
+	pub fn cell_degrees(zoom: i32) -> f64 {
+		360.0 / 2_f64.powi(zoom) / MAP_CLUSTER_CELLS_PER_TILE
+	}
+
+	/// The clusters of objects visible to `user_id` (every object when
+	/// `read_all`) located in `bounds` and matching `filter`, as the map shows
+	/// them at `zoom`.
+	pub async fn in_bounds(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		bounds: &Bounds,
+		zoom: i32,
+		filter: &ObjectPageFilter,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_MAP_CLUSTERS_QUERY).await?;
+		client
+			.query(
+				&statement,
+				&[
+					&user_id,
+					&read_all,
+					&bounds.south,
+					&bounds.west,
+					&bounds.north,
+					&bounds.east,
+					&Self::cell_degrees(zoom),
+					&MAP_CLUSTER_SAMPLE_SIZE,
+					&filter.owner_id,
+					&filter.media_type,
+					&filter.made_on_from,
+					&filter.made_on_to,
+					&filter.publicity,
+					&filter.has_location,
+					&filter.tags,
+				],
+			)
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+}
+
+#[Object]
+impl MapCluster {
+	/// How many objects the cluster holds.
+	async fn count(&self) -> i64 {
+		self.count
+	}
+
+	/// The average position of its objects, where the map draws it.
+	async fn centroid(&self) -> Location {
+		self.centroid.clone()
+	}
+
+	/// The smallest box holding all of its objects. Fitting the map to it
+	/// shows the cluster split up; a box with no area means every object was
+	/// taken at the same spot.
+	async fn bounds(&self) -> Bounds {
+		self.bounds.clone()
+	}
+
+	/// The earliest capture time among its objects.
+	async fn earliest_made_on(&self) -> Option<String> {
+		self.earliest_made_on.map(|made_on| made_on.to_string())
+	}
+
+	/// The latest capture time among its objects.
+	async fn latest_made_on(&self) -> Option<String> {
+		self.latest_made_on.map(|made_on| made_on.to_string())
+	}
+
+	/// A few of its objects, latest capture time first, for previews.
+	async fn object_ids(&self) -> Vec<ID> {
+		self.object_ids.iter().map(|id| ID::from(*id)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::MapCluster;
+
+	#[test]
+	fn cells_halve_with_each_zoom_level() {
+		assert_eq!(MapCluster::cell_degrees(0), 90.0);
+		assert_eq!(MapCluster::cell_degrees(1), 45.0);
+		assert_eq!(MapCluster::cell_degrees(10), 360.0 / 1024.0 / 4.0);
+	}
+}
@@ -0,0 +1,317 @@
+use {
+	crate::{
+		ContextWrapper,
+		db::queries::{
+			INSERT_OBJECT_REVISION_QUERY,
+			SELECT_OBJECT_REVISIONS_QUERY,
+		},
+		errors::AppError,
+		graphql::objects::{
+			location::Location,
+			s3_object::{
+				LocationPrecisionOverride,
+				MetadataPrivacyOverride,
+				PublicityOverride,
+				S3Object,
+			},
+		},
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		SimpleObject,
+	},
+	jiff::Timestamp,
+	serde::{
+		Deserialize,
+		Serialize,
+	},
+	serde_json::{
+		Map,
+		Value,
+	},
+	tokio_postgres::{
+		Row,
+		Transaction,
+	},
+};
+
+/// Fields of an object that a revision changed, by name, with their old or new
+/// values.
+pub type RevisionValues = Map<String, Value>;
+
+/// A changed field of an object in one revision.
+#[derive(Clone, Debug, PartialEq, SimpleObject)]
+pub struct ObjectRevisionChange {
+	/// The field's name on `S3Object`, such as `location`.
+	pub field: String,
+	/// The value before the revision, as text, or null when it had none.
+	pub old_value: Option<String>,
+	/// The value the revision set, as text, or null when it cleared it.
+	pub new_value: Option<String>,
+}
+
+/// One edit of an object's metadata.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct ObjectRevision {
+	/// The edit's number, counting from 1 for each object.
+	pub revision: i32,
+	/// The editor's email, or null once their account is deleted.
+	pub editor: Option<String>,
+	pub created_at: String,
+	pub changes: Vec<ObjectRevisionChange>,
+}
+
+impl TryFrom<Row> for ObjectRevision {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		let created_at: Timestamp =
+			row.try_get("created_at").context("Failed to read object revision created_at")?;
+		let old_values = revision_values(
+			row.try_get("old_values").context("Failed to read object revision old_values")?,
+		)?;
+		let new_values = revision_values(
+			row.try_get("new_values").context("Failed to read object revision new_values")?,
+		)?;
+		Ok(Self {
+			revision: row.try_get("revision").context("Failed to read object revision")?,
+			editor: row
+				.try_get("editor_email")
+				.context("Failed to read object revision editor_email")?,
+			created_at: created_at.to_string(),
+			changes: changes(old_values, new_values),
+		})
+	}
+}
+
+impl ObjectRevision {
+	/// The revisions of `object_id`, newest first.
+	pub async fn for_object(
+		ctx: &Context<'_>,
+		object_id: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_OBJECT_REVISIONS_QUERY).await?;
+		client.query(&statement, &[&object_id]).await?.into_iter().map(Self::try_from).collect()
+	}
+}
+
+/// The metadata of an object that its revisions record, with the field names
+/// of `S3Object`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectMetadataSnapshot {
+	pub name: String,
+	pub made_on: Option<String>,
+	pub location: Option<Location>,
+	pub publicity: PublicityOverride,
+	pub metadata_privacy: MetadataPrivacyOverride,
+	pub location_precision: LocationPrecisionOverride,
+	pub allowed_users: Vec<String>,
+	pub allowed_groups: Vec<i64>,
+	pub tags: Vec<String>,
+	pub title: String,
+	pub description: String,
+	pub note: String,
+}
+
+impl From<&S3Object> for ObjectMetadataSnapshot {
+	fn from(object: &S3Object) -> Self {
+		// Allowed users come back in no particular order.
+		let mut allowed_users = object.allowed_users.clone();
+		allowed_users.sort();
+		Self {
+			name: object.name.clone(),
+			made_on: object.made_on.map(|made_on| made_on.to_string()),
+			location: object.location.clone(),
+			publicity: object.publicity,
+			metadata_privacy: object.metadata_privacy,
+			location_precision: object.location_precision,
+			allowed_users,
+			allowed_groups: object.allowed_groups.clone(),
+			tags: object.tags.clone(),
+			title: object.title.clone(),
+			description: object.description.clone(),
+			note: object.note.clone(),
+		}
+	}
+}
+
+impl ObjectMetadataSnapshot {
+	fn values(&self) -> Result<RevisionValues, AppError> {
+		match serde_json::to_value(self).context("Failed to write object metadata snapshot")? {
+			Value::Object(values) => Ok(values),
+			_ => Err(AppError::Internal(anyhow::anyhow!(
+				"Object metadata snapshot is not a JSON object"
+			))),
+		}
+	}
+
+	/// The old and new values of the fields that differ in `after`.
+	pub fn changes_to(
+		&self,
+		after: &Self,
+	) -> Result<(RevisionValues, RevisionValues), AppError> {
+		let before = self.values()?;
+		let mut old_values = RevisionValues::new();
+		let mut new_values = RevisionValues::new();
+		for (field, new_value) in after.values()? {
+			let old_value = before.get(&field).cloned().unwrap_or(Value::Null);
+			if old_value != new_value {
+				old_values.insert(field.clone(), old_value);
+				new_values.insert(field, new_value);
+			}
+		}
+		Ok((old_values, new_values))
+	}
+
+	/// This metadata with the fields in `values`, as a revision records them,
+	/// set to those values.
+	pub fn with_values(
+		&self,
+		values: RevisionValues,
+	) -> Result<Self, AppError> {
+		let mut snapshot = self.values()?;
+		snapshot.extend(values);
+		Ok(serde_json::from_value(Value::Object(snapshot))
+			.context("Failed to read object revision values")?)
+	}
+}
+
+/// The values stored in a revision's `old_values` or `new_values`.
+pub fn revision_values(json: String) -> Result<RevisionValues, AppError> {
+	Ok(serde_json::from_str(&json).context("Failed to read object revision values")?)
+}
+
+/// Records `editor_id`'s edit of `object_id` from `before` to `after` as its
+/// next revision. An edit that changed nothing is not recorded.
+pub async fn record_object_revision(
+	transaction: &Transaction<'_>,
+	object_id: i64,
+	editor_id: i64,
+	before: &ObjectMetadataSnapshot,
+	after: &ObjectMetadataSnapshot,
+) -> Result<(), AppError> {
+	let (old_values, new_values) = before.changes_to(after)?;
+	if new_values.is_empty() {
+		return Ok(());
+	}
+	let old_values = serde_json::to_string(&old_values).context("Failed to write old values")?;
+	let new_values = serde_json::to_string(&new_values).context("Failed to write new values")?;
+	transaction
+		.execute(INSERT_OBJECT_REVISION_QUERY, &[&object_id, &editor_id, &old_values, &new_values])
+		.await
+		.context("Failed to record object revision")?;
+	Ok(())
+}
+
+/// The fields in `new_values`, in order, with their values as text.
+fn changes(
+	mut old_values: RevisionValues,
+	new_values: RevisionValues,
+) -> Vec<ObjectRevisionChange> {
+	new_values
+		.into_iter()
+		.map(|(field, new_value)| ObjectRevisionChange {
+			old_value: old_values.remove(&field).and_then(value_text),
+			new_value: value_text(new_value),
+			field,
+		})
+		.collect()
+}
+
+/// `value` as text, or `None` for no value: null, an empty string or an empty
+/// list. Lists are comma-separated, and a location is its latitude and
+/// longitude.
+fn value_text(value: Value) -> Option<String> {
+	let text = match value {
+		Value::Null => return None,
+		Value::String(text) => text,
+		Value::Array(values) =>
+			values.into_iter().filter_map(value_text).collect::<Vec<_>>().join(", "),
+		Value::Object(mut values) =>
+			match (values.remove("latitude"), values.remove("longitude")) {
+				(Some(latitude), Some(longitude)) => format!("{latitude}, {longitude}"),
+				_ => Value::Object(values).to_string(),
+			},
+		value => value.to_string(),
+	};
+	(!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			ObjectMetadataSnapshot,
+			ObjectRevisionChange,
+			changes,
+		},
+		crate::graphql::objects::{
+			location::Location,
+			s3_object::{
+				LocationPrecisionOverride,
+				MetadataPrivacyOverride,
+				PublicityOverride,
+			},
+		},
+	};
+
+	fn snapshot() -> ObjectMetadataSnapshot {
+		ObjectMetadataSnapshot {
+			name: "IMG_0001.JPG".to_string(),
+			made_on: Some("2024-05-01T10:00:00Z".to_string()),
+			location: Some(Location {
+				latitude: 12.5,
+				longitude: -45.25,
+			}),
+			publicity: PublicityOverride::Default,
+			metadata_privacy: MetadataPrivacyOverride::Default,
+			location_precision: LocationPrecisionOverride::Default,
+			allowed_users: Vec::new(),
+			allowed_groups: Vec::new(),
+			tags: vec!["beach".to_string()],
+			title: String::new(),
+			description: String::new(),
+			note: String::new(),
+		}
+	}
+
+	#[test]
+	fn only_changed_fields_are_recorded_and_can_be_put_back() -> anyhow::Result<()> {
+		let before = snapshot();
+		let after = ObjectMetadataSnapshot {
+			location: None,
+			publicity: PublicityOverride::Public,
+			title: "Harbour".to_string(),
+			..before.clone()
+		};
+
+		let (old_values, new_values) = before.changes_to(&after)?;
+		assert_eq!(old_values.keys().collect::<Vec<_>>(), vec!["location", "publicity", "title"]);
+		assert_eq!(
+			changes(old_values.clone(), new_values),
+			vec![
+				ObjectRevisionChange {
+					field: "location".to_string(),
+					old_value: Some("12.5, -45.25".to_string()),
+					new_value: None,
+				},
+				ObjectRevisionChange {
+					field: "publicity".to_string(),
+					old_value: Some("Default".to_string()),
+					new_value: Some("Public".to_string()),
+				},
+				ObjectRevisionChange {
+					field: "title".to_string(),
+					old_value: None,
+					new_value: Some("Harbour".to_string()),
+				},
+			]
+		);
+		assert_eq!(after.with_values(old_values)?, before);
+		assert!(before.changes_to(&before)?.1.is_empty());
+		Ok(())
+	}
+}
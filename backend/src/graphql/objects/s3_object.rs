@@ -2,15 +2,60 @@ use {
 	crate::{
 		ContextWrapper,
 		db::queries::{
+			SEARCH_VISIBLE_OBJECTS_QUERY,
 			SELECT_ALL_OBJECTS_QUERY,
 			SELECT_OBJECT_BY_ID_QUERY,
 			SELECT_OBJECT_BY_NAME_QUERY,
+			SELECT_OBJECT_DERIVATIVE_STORAGE_KEY_QUERY,
+			SELECT_OBJECT_EFFECTIVE_METADATA_PRIVACY_QUERY,
+			SELECT_OBJECT_MEDIA_INFO_QUERY,
+			SELECT_OBJECT_PAGE_BY_ID_ASC_QUERY,
+			SELECT_OBJECT_PAGE_BY_ID_DESC_QUERY,
+			SELECT_OBJECT_PAGE_BY_MADE_ON_ASC_QUERY,
+			SELECT_OBJECT_PAGE_BY_MADE_ON_DESC_QUERY,
 			SELECT_OBJECTS_BY_IDS_QUERY,
 			SELECT_OBJECTS_BY_USER_ID_QUERY,
+			SELECT_SIMILAR_OBJECTS_QUERY,
+			SELECT_VISIBLE_OBJECTS_IN_BOUNDS_QUERY,
+			SELECT_VISIBLE_OBJECTS_NEAR_QUERY,
 			SELECT_VISIBLE_OBJECTS_QUERY,
 		},
+		derivative_worker::derivative_storage_key,
 		errors::AppError,
-		graphql::objects::location::Location,
+		graphql::objects::{
+			location::{
+				Bounds,
+				Location,
+				LocationPrecision,
+			},
+			s3_object_connection::{
+				ObjectCursor,
+				ObjectPageFilter,
+				S3ObjectOrder,
+			},
+			search::{
+				SearchCursor,
+				SearchTerms,
+			},
+		},
+		media::{
+			MediaInfo,
+			MediaTags,
+			sanitize::{
+				MetadataPrivacy,
+				SANITIZED_KIND,
+				sanitized_extension,
+			},
+			thumbnail::{
+				DISPLAY_KIND,
+				Display,
+				POSTER_KIND,
+				ThumbnailFormat,
+				ThumbnailSize,
+				needs_display_rendition,
+				thumbnail_kind,
+			},
+		},
 	},
 	anyhow::Context as AnyhowContext,
 	async_graphql::{
@@ -26,6 +71,7 @@ use {
 		ToSql,
 	},
 	serde::{
+		Deserialize,
 		Serialize,
 		Serializer,
 	},
@@ -36,7 +82,7 @@ use {
 	tokio_postgres::Row,
 };
 
-#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, ToSql, FromSql, Serialize)]
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, ToSql, FromSql, Serialize, Deserialize)]
 #[postgres(name = "publicity_override")]
 pub enum PublicityOverride {
 	#[postgres(name = "default")]
@@ -77,6 +123,42 @@ impl FromStr for PublicityOverride {
 	}
 }
 
+/// An object's own metadata privacy, or `Default` to follow its owner's
+/// setting.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, ToSql, FromSql, Serialize, Deserialize)]
+#[postgres(name = "metadata_privacy_override")]
+pub enum MetadataPrivacyOverride {
+	#[postgres(name = "default")]
+	Default,
+	#[postgres(name = "keep")]
+	Keep,
+	#[postgres(name = "strip_location")]
+	StripLocation,
+	#[postgres(name = "strip_all")]
+	StripAll,
+}
+
+/// An object's own location precision, or `Default` to follow its owner's
+/// setting.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, ToSql, FromSql, Serialize, Deserialize)]
+#[postgres(name = "location_precision_override")]
+pub enum LocationPrecisionOverride {
+	#[postgres(name = "default")]
+	Default,
+	#[postgres(name = "exact")]
+	Exact,
+	#[graphql(name = "APPROX_100M")]
+	#[postgres(name = "approx_100m")]
+	Approx100M,
+	#[graphql(name = "APPROX_1KM")]
+	#[postgres(name = "approx_1km")]
+	Approx1Km,
+	#[postgres(name = "city")]
+	City,
+	#[postgres(name = "hidden")]
+	Hidden,
+}
+
 fn serialize_timestamp<S>(
 	timestamp: &Option<Timestamp>,
 	serializer: S,
@@ -115,7 +197,7 @@ fn content_type_requires_attachment(content_type: &str) -> bool {
 	matches!(content_type, "image/svg+xml")
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct S3Object {
 	#[serde(serialize_with = "serialize_i64_as_string")]
 	pub id: i64,
@@ -127,7 +209,20 @@ pub struct S3Object {
 	pub location: Option<Location>,
 	pub user_id: Option<i64>,
 	pub publicity: PublicityOverride,
+	pub metadata_privacy: MetadataPrivacyOverride,
+	pub location_precision: LocationPrecisionOverride,
+	/// The precision people other than the owner see the location at: the
+	/// object's own, or else its owner's setting.
+	#[serde(skip)]
+	pub effective_location_precision: LocationPrecision,
 	pub allowed_users: Vec<String>,
+	/// The ids of the owner's groups a `selected_users` object is shared with.
+	pub allowed_groups: Vec<i64>,
+	pub tags: Vec<String>,
+	pub title: String,
+	pub description: String,
+	#[serde(skip)]
+	pub note: String,
 }
 
 impl TryFrom<Row> for S3Object {
@@ -146,8 +241,23 @@ impl TryFrom<Row> for S3Object {
 			row.try_get("user_id").context("Failed to read object user_id")?;
 		let publicity: PublicityOverride =
 			row.try_get("publicity").context("Failed to read object publicity")?;
-		// Some lifecycle queries return partial object rows and fill allowed users later.
+		let metadata_privacy: MetadataPrivacyOverride =
+			row.try_get("metadata_privacy").context("Failed to read object metadata_privacy")?;
+		let location_precision: LocationPrecisionOverride = row
+			.try_get("location_precision")
+			.context("Failed to read object location_precision")?;
+		let effective_location_precision: LocationPrecision = row
+			.try_get("effective_location_precision")
+			.context("Failed to read object effective_location_precision")?;
+		let title: String = row.try_get("title").context("Failed to read object title")?;
+		let description: String =
+			row.try_get("description").context("Failed to read object description")?;
+		let note: String = row.try_get("note").context("Failed to read object note")?;
+		// Some lifecycle queries return partial object rows and fill allowed users,
+		// groups and tags later.
 		let allowed_users: Vec<String> = row.try_get("allowed_users").unwrap_or_default();
+		let allowed_groups: Vec<i64> = row.try_get("allowed_groups").unwrap_or_default();
+		let tags: Vec<String> = row.try_get("tags").unwrap_or_default();
 
 		// Distinguish "no location set" (both NULL) from a decode failure.
 		// ST_Y/ST_X return NULL only when `location` is NULL, so a single-NULL pair
@@ -181,7 +291,15 @@ impl TryFrom<Row> for S3Object {
 			location,
 			user_id,
 			publicity,
+			metadata_privacy,
+			location_precision,
+			effective_location_precision,
 			allowed_users,
+			allowed_groups,
+			tags,
+			title,
+			description,
+			note,
 		})
 	}
 }
@@ -204,11 +322,12 @@ impl S3Object {
 
 	pub async fn where_name(
 		ctx: &Context<'_>,
+		user_id: i64,
 		name: String,
 	) -> Result<Self, AppError> {
 		let client = ContextWrapper::new(ctx)?.db_client().await?;
 		let statement = client.prepare_cached(SELECT_OBJECT_BY_NAME_QUERY).await?;
-		Self::try_from(client.query_one(&statement, &[&name]).await?)
+		Self::try_from(client.query_one(&statement, &[&user_id, &name]).await?)
 	}
 
 	pub async fn where_ids(
@@ -235,7 +354,281 @@ impl S3Object {
 	) -> Result<Vec<Self>, AppError> {
 		let client = ContextWrapper::new(ctx)?.db_client().await?;
 		let statement = client.prepare_cached(SELECT_VISIBLE_OBJECTS_QUERY).await?;
-		client.query(&statement, &[&user_id]).await?.into_iter().map(Self::try_from).collect()
+		client
+			.query(&statement, &[&user_id, &false])
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	/// Objects visible to `user_id` (every object when `read_all`) located in
+	/// `bounds` and matching `filter`.
+	pub async fn in_bounds(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		bounds: &Bounds,
+		filter: &ObjectPageFilter,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_VISIBLE_OBJECTS_IN_BOUNDS_QUERY).await?;
+		client
+			.query(
+				&statement,
+				&[
+					&user_id,
+					&read_all,
+					&bounds.south,
+					&bounds.west,
+					&bounds.north,
+					&bounds.east,
+					&filter.owner_id,
+					&filter.media_type,
+					&filter.made_on_from,
+					&filter.made_on_to,
+					&filter.publicity,
+					&filter.has_location,
+					&filter.tags,
+				],
+			)
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	/// Objects visible to `user_id` (every object when `read_all`) within
+	/// `radius_meters` of `location`, nearest first.
+	pub async fn near(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		location: &Location,
+		radius_meters: f64,
+	) -> Result<Vec<Self>, AppError> {
+		let point = location.geometry()?;
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_VISIBLE_OBJECTS_NEAR_QUERY).await?;
+		client
+			.query(&statement, &[&user_id, &read_all, &point, &radius_meters])
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	/// Up to `limit` objects visible to `user_id` (every object when
+	/// `read_all`) that match `filter`, in `order`, starting after `after`.
+	pub async fn page(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		filter: &ObjectPageFilter,
+		order: S3ObjectOrder,
+		after: Option<&ObjectCursor>,
+		limit: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let query = match order {
+			S3ObjectOrder::MadeOnDesc => SELECT_OBJECT_PAGE_BY_MADE_ON_DESC_QUERY,
+			S3ObjectOrder::MadeOnAsc => SELECT_OBJECT_PAGE_BY_MADE_ON_ASC_QUERY,
+			S3ObjectOrder::UploadedDesc => SELECT_OBJECT_PAGE_BY_ID_DESC_QUERY,
+			S3ObjectOrder::UploadedAsc => SELECT_OBJECT_PAGE_BY_ID_ASC_QUERY,
+		};
+		let statement = client.prepare_cached(query).await?;
+		let media_type = filter.media_type;
+		let after_id = after.map(|cursor| cursor.id);
+		let after_made_on = after.and_then(|cursor| cursor.made_on);
+		let mut params: Vec<&(dyn ToSql + Sync)> = vec![
+			&user_id,
+			&read_all,
+			&filter.owner_id,
+			&media_type,
+			&filter.made_on_from,
+			&filter.made_on_to,
+			&filter.publicity,
+			&filter.has_location,
+			&filter.tags,
+			&limit,
+			&after_id,
+		];
+		if order.sorts_by_made_on() {
+			params.push(&after_made_on);
+		}
+		client.query(&statement, &params).await?.into_iter().map(Self::try_from).collect()
+	}
+
+	/// Up to `limit` objects visible to `user_id` (every object when
+	/// `read_all`) matching `terms` after `after`, best match first, each with
+	/// the cursor of its position in the results.
+	pub async fn search(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		terms: &SearchTerms,
+		after: Option<&SearchCursor>,
+		limit: i64,
+	) -> Result<Vec<(Self, SearchCursor)>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SEARCH_VISIBLE_OBJECTS_QUERY).await?;
+		let after_id = after.map(|cursor| cursor.id);
+		let after_rank = after.map(|cursor| cursor.rank);
+		client
+			.query(
+				&statement,
+				&[
+					&user_id,
+					&read_all,
+					&terms.prefix_query,
+					&terms.text,
+					&terms.email_pattern,
+					&limit,
+					&after_id,
+					&after_rank,
+				],
+			)
+			.await?
+			.into_iter()
+			.map(|row| -> Result<_, AppError> {
+				let rank: f64 = row.try_get("rank").context("Failed to read search rank")?;
+				let object = Self::try_from(row)?;
+				let cursor = SearchCursor {
+					id: object.id,
+					rank,
+				};
+				Ok((object, cursor))
+			})
+			.collect()
+	}
+
+	/// Where this object sits in `s3ObjectsConnection` orderings.
+	pub fn cursor(&self) -> ObjectCursor {
+		ObjectCursor {
+			id: self.id,
+			made_on: self.made_on,
+		}
+	}
+
+	/// The owner's other images whose perceptual hash is within `max_distance`
+	/// bits of this one's, closest first. Empty until this image is hashed.
+	pub async fn similar_to(
+		ctx: &Context<'_>,
+		id: i64,
+		max_distance: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_SIMILAR_OBJECTS_QUERY).await?;
+		client
+			.query(&statement, &[&id, &max_distance])
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	/// A presigned URL for the derivative of `kind`, or `None` when the
+	/// derivative worker has not stored one.
+	async fn derivative_url(
+		&self,
+		ctx: &Context<'_>,
+		kind: &str,
+	) -> Result<Option<String>, AppError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let client = wrapper.db_client().await?;
+		let statement = client.prepare_cached(SELECT_OBJECT_DERIVATIVE_STORAGE_KEY_QUERY).await?;
+		let Some(row) = client.query_opt(&statement, &[&self.id, &kind]).await? else {
+			return Ok(None);
+		};
+		let storage_key: String =
+			row.try_get("storage_key").context("Failed to read derivative storage key")?;
+		Ok(Some(wrapper.shared_state().storage.presigned_get_url(&storage_key, None, None).await?))
+	}
+
+	/// The key and content type of the file the caller is served. The owner
+	/// gets the original; other people get a copy without embedded metadata
+	/// when the object's metadata privacy withholds any: the sanitized copy, or
+	/// for a photo that cannot be rewritten in place (HEIC, RAW), its display
+	/// rendition. Those keys are fixed, so until the derivative worker writes
+	/// them the URL fails rather than falling back to the original.
+	async fn served_file(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<(String, &str), AppError> {
+		let (kind, extension, content_type) = match sanitized_extension(&self.content_type) {
+			Some(extension) => (SANITIZED_KIND, extension, self.content_type.as_str()),
+			None if needs_display_rendition(&self.content_type) =>
+				(DISPLAY_KIND, Display::EXTENSION, Display::CONTENT_TYPE),
+			None => return Ok((self.storage_key.clone(), &self.content_type)),
+		};
+		let wrapper = ContextWrapper::new(ctx)?;
+		if self.user_id.is_some() && wrapper.user_id_opt() == self.user_id {
+			return Ok((self.storage_key.clone(), &self.content_type));
+		}
+		let client = wrapper.db_client().await?;
+		let statement =
+			client.prepare_cached(SELECT_OBJECT_EFFECTIVE_METADATA_PRIVACY_QUERY).await?;
+		let privacy: MetadataPrivacy = client
+			.query_one(&statement, &[&self.id])
+			.await?
+			.try_get("metadata_privacy")
+			.context("Failed to read object metadata privacy")?;
+		Ok(match privacy {
+			MetadataPrivacy::Keep => (self.storage_key.clone(), &self.content_type),
+			MetadataPrivacy::StripLocation | MetadataPrivacy::StripAll =>
+				(derivative_storage_key(&self.storage_key, kind, extension), content_type),
+		})
+	}
+
+	/// A presigned URL for the file the caller is served.
+	async fn served_url(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<String, AppError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let (storage_key, content_type) = self.served_file(ctx).await?;
+		let content_disposition =
+			content_type_requires_attachment(content_type).then_some("attachment");
+		Ok(wrapper
+			.shared_state()
+			.storage
+			.presigned_get_url(&storage_key, content_disposition, Some(content_type))
+			.await?)
+	}
+
+	/// What the derivative worker probed from the object's container, or `None`
+	/// when it has not been probed.
+	async fn media_info(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<MediaInfo>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_OBJECT_MEDIA_INFO_QUERY).await?;
+		let Some(row) = client.query_opt(&statement, &[&self.id]).await? else {
+			return Ok(None);
+		};
+		let dimension = |column| -> Result<Option<u32>, AppError> {
+			let value: Option<i32> =
+				row.try_get(column).context("Failed to read object media dimension")?;
+			Ok(value.and_then(|value| u32::try_from(value).ok()))
+		};
+		Ok(Some(MediaInfo {
+			duration_seconds: row
+				.try_get("duration_seconds")
+				.context("Failed to read object media duration")?,
+			width: dimension("width")?,
+			height: dimension("height")?,
+			video_codec: row.try_get("video_codec").context("Failed to read object video codec")?,
+			audio_codec: row.try_get("audio_codec").context("Failed to read object audio codec")?,
+			tags: MediaTags {
+				title: row.try_get("title").context("Failed to read object media title")?,
+				artist: row.try_get("artist").context("Failed to read object media artist")?,
+				recorded_date: row
+					.try_get("recorded_date")
+					.context("Failed to read object media recording date")?,
+			},
+			peaks: row.try_get("peaks").context("Failed to read object waveform peaks")?,
+		}))
 	}
 }
 
@@ -249,12 +642,59 @@ impl S3Object {
 		self.name.to_string()
 	}
 
+	/// A short caption, empty when the object has none.
+	async fn title(&self) -> String {
+		self.title.clone()
+	}
+
+	/// The story behind the object in Markdown, empty when it has none. Clients
+	/// must render it without raw HTML.
+	async fn description(&self) -> String {
+		self.description.clone()
+	}
+
+	/// The owner's private note on the object in Markdown, null for anyone
+	/// else.
+	async fn note(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<String>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let is_owner = self.user_id.is_some() && wrapper.user_id_opt() == self.user_id;
+		if is_owner || wrapper.can_read_all_objects().await? {
+			Ok(Some(self.note.clone()))
+		} else {
+			Ok(None)
+		}
+	}
+
 	async fn made_on(&self) -> Option<String> {
 		self.made_on.map(|made_on| made_on.to_string())
 	}
 
-	async fn location(&self) -> Option<Location> {
-		self.location.clone()
+	/// Where the object was made. People other than the owner see it at the
+	/// object's effective location precision, the same as spatial queries
+	/// match and cluster it at.
+	async fn location(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<Location>, GraphQLError> {
+		let Some(location) = &self.location else {
+			return Ok(None);
+		};
+		let wrapper = ContextWrapper::new(ctx)?;
+		let is_owner = self.user_id.is_some() && wrapper.user_id_opt() == self.user_id;
+		if is_owner || wrapper.can_read_all_objects().await? {
+			Ok(Some(location.clone()))
+		} else {
+			Ok(location.coarsened(self.effective_location_precision))
+		}
+	}
+
+	/// The object's own location precision, or `DEFAULT` when it follows its
+	/// owner's setting.
+	async fn location_precision(&self) -> LocationPrecisionOverride {
+		self.location_precision
 	}
 
 	async fn publicity(&self) -> PublicityOverride {
@@ -265,19 +705,132 @@ impl S3Object {
 		self.allowed_users.clone()
 	}
 
+	/// The owner's groups the object is shared with when `SELECTED_USERS`.
+	async fn allowed_groups(&self) -> Vec<ID> {
+		self.allowed_groups.iter().map(|id| ID(id.to_string())).collect()
+	}
+
+	/// The owner's tags on the object, in alphabetical order.
+	async fn tags(&self) -> Vec<String> {
+		self.tags.clone()
+	}
+
+	/// The object's own metadata privacy, or `DEFAULT` when it follows its
+	/// owner's setting.
+	async fn metadata_privacy(&self) -> MetadataPrivacyOverride {
+		self.metadata_privacy
+	}
+
+	/// A presigned URL for the original, or for people other than the owner, a
+	/// copy without the metadata its privacy setting withholds.
 	async fn url(
 		&self,
 		ctx: &Context<'_>,
 	) -> Result<String, GraphQLError> {
-		let wrapper = ContextWrapper::new(ctx)?;
-		let content_disposition =
-			content_type_requires_attachment(&self.content_type).then_some("attachment");
-		wrapper
-			.shared_state()
-			.storage
-			.presigned_get_url(&self.storage_key, content_disposition)
-			.await
-			.map_err(AppError::graphql)
+		self.served_url(ctx).await.map_err(AppError::graphql)
+	}
+
+	/// A presigned URL for a version of the object browsers can show. For HEIC
+	/// and camera RAW photos this is a JPEG rendition, null until the derivative
+	/// worker has rendered one (or when the photo cannot be decoded); for
+	/// everything else it is `url`.
+	async fn display_url(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<String>, GraphQLError> {
+		if needs_display_rendition(&self.content_type) {
+			self.derivative_url(ctx, DISPLAY_KIND).await.map_err(AppError::graphql)
+		} else {
+			self.served_url(ctx).await.map(Some).map_err(AppError::graphql)
+		}
+	}
+
+	/// A presigned URL for a resized rendition of the object, or null when none
+	/// has been rendered (yet, or at all for non-image types). Clients should fall
+	/// back to `url`.
+	async fn thumbnail_url(
+		&self,
+		ctx: &Context<'_>,
+		size: ThumbnailSize,
+		#[graphql(default)] format: ThumbnailFormat,
+	) -> Result<Option<String>, GraphQLError> {
+		self.derivative_url(ctx, &thumbnail_kind(size, format)).await.map_err(AppError::graphql)
+	}
+
+	/// A presigned URL for a still frame of a video, taken from its embedded
+	/// cover art or its opening seconds, or null when none could be taken.
+	async fn poster_url(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<String>, GraphQLError> {
+		self.derivative_url(ctx, POSTER_KIND).await.map_err(AppError::graphql)
+	}
+
+	/// The playback length of a video or audio recording, in seconds, or null
+	/// until it has been probed (or when its container does not record one).
+	async fn duration_seconds(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<f64>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.and_then(|info| info.duration_seconds))
+	}
+
+	/// The pixel width of a video, as probed from its container.
+	async fn width(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<u32>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.and_then(|info| info.width))
+	}
+
+	/// The pixel height of a video, as probed from its container.
+	async fn height(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<u32>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.and_then(|info| info.height))
+	}
+
+	/// The video codec, such as `h264`, `vp9` or `av1`.
+	async fn video_codec(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<String>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.and_then(|info| info.video_codec))
+	}
+
+	/// The codec of the first audio track, such as `aac` or `opus`.
+	async fn audio_codec(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<String>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.and_then(|info| info.audio_codec))
+	}
+
+	/// The title, artist and recording date tagged in an audio file, or null
+	/// when it carries none or has not been analysed.
+	async fn media_tags(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<MediaTags>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.map(|info| info.tags).filter(|tags| !tags.is_empty()))
+	}
+
+	/// Peak amplitudes between 0 and 1, evenly spaced over an audio
+	/// recording, for drawing its waveform. Null until the audio has been
+	/// decoded.
+	async fn waveform_peaks(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Option<Vec<f32>>, GraphQLError> {
+		let info = self.media_info(ctx).await.map_err(AppError::graphql)?;
+		Ok(info.and_then(|info| info.peaks))
 	}
 
 	async fn content_type(&self) -> String {
@@ -0,0 +1,184 @@
+use {
+	crate::{
+		errors::AppError,
+		graphql::objects::{
+			s3_object::PublicityOverride,
+			tag::normalize_tags,
+		},
+	},
+	async_graphql::{
+		Enum,
+		ID,
+		InputObject,
+		connection::CursorType,
+	},
+	jiff::Timestamp,
+};
+
+/// The top-level media type of an object's content type.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ContentTypeFamily {
+	Image,
+	Video,
+	Audio,
+}
+
+impl ContentTypeFamily {
+	/// The part of a content type before the slash.
+	pub const fn media_type(self) -> &'static str {
+		match self {
+			ContentTypeFamily::Image => "image",
+			ContentTypeFamily::Video => "video",
+			ContentTypeFamily::Audio => "audio",
+		}
+	}
+}
+
+/// The order `s3ObjectsConnection` pages through objects in. Objects without a
+/// capture time sort as the earliest.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum S3ObjectOrder {
+	#[default]
+	MadeOnDesc,
+	MadeOnAsc,
+	/// Most recently uploaded first.
+	UploadedDesc,
+	UploadedAsc,
+}
+
+impl S3ObjectOrder {
+	pub const fn sorts_by_made_on(self) -> bool {
+		matches!(self, S3ObjectOrder::MadeOnDesc | S3ObjectOrder::MadeOnAsc)
+	}
+}
+
+/// Narrows an object query, such as `s3ObjectsConnection`, to the objects
+/// matching every field that is set.
+#[derive(InputObject, Clone, Debug, Default)]
+pub struct S3ObjectFilter {
+	/// Only objects owned by this user.
+	pub owner_id: Option<ID>,
+	pub content_type_family: Option<ContentTypeFamily>,
+	/// Only objects made at or after this RFC 3339 timestamp.
+	pub made_on_from: Option<String>,
+	/// Only objects made at or before this RFC 3339 timestamp.
+	pub made_on_to: Option<String>,
+	/// Only objects with this publicity setting, as stored: `DEFAULT` matches
+	/// objects following their owner's default whatever it is.
+	pub publicity: Option<PublicityOverride>,
+	/// Only objects with (`true`) or without (`false`) a location.
+	pub has_location: Option<bool>,
+	/// Only objects with every one of these tags.
+	pub tags: Option<Vec<String>>,
+}
+
+/// [`S3ObjectFilter`] with its fields parsed into query parameters.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectPageFilter {
+	pub owner_id: Option<i64>,
+	pub media_type: Option<&'static str>,
+	pub made_on_from: Option<Timestamp>,
+	pub made_on_to: Option<Timestamp>,
+	pub publicity: Option<PublicityOverride>,
+	pub has_location: Option<bool>,
+	/// Normalized and without repeats; `None` rather than empty.
+	pub tags: Option<Vec<String>>,
+}
+
+impl TryFrom<S3ObjectFilter> for ObjectPageFilter {
+	type Error = AppError;
+
+	fn try_from(filter: S3ObjectFilter) -> Result<Self, Self::Error> {
+		let parse_made_on = |field: &str, value: Option<String>| {
+			value
+				.map(|value| {
+					value.parse::<Timestamp>().map_err(|e| {
+						AppError::Validation(format!("Invalid {field} timestamp: {e}"))
+					})
+				})
+				.transpose()
+		};
+		Ok(Self {
+			owner_id: filter
+				.owner_id
+				.map(|id| id.parse::<i64>())
+				.transpose()
+				.map_err(|_| AppError::Validation("Invalid ownerId".to_string()))?,
+			media_type: filter.content_type_family.map(ContentTypeFamily::media_type),
+			made_on_from: parse_made_on("madeOnFrom", filter.made_on_from)?,
+			made_on_to: parse_made_on("madeOnTo", filter.made_on_to)?,
+			publicity: filter.publicity,
+			has_location: filter.has_location,
+			tags: filter
+				.tags
+				.map(|tags| normalize_tags(&tags))
+				.transpose()?
+				.filter(|tags| !tags.is_empty()),
+		})
+	}
+}
+
+/// The position of an object in an `s3ObjectsConnection` ordering: its id,
+/// which breaks ties, and its capture time. A cursor stays valid while the
+/// object it came from is deleted or edited, and is encoded as hex so clients
+/// treat it as opaque.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ObjectCursor {
+	pub id: i64,
+	pub made_on: Option<Timestamp>,
+}
+
+impl CursorType for ObjectCursor {
+	type Error = AppError;
+
+	fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+		let invalid = || AppError::Validation("Invalid cursor".to_string());
+		let decoded = hex::decode(s).map_err(|_| invalid())?;
+		let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+		let (id, made_on) = decoded.split_once('|').ok_or_else(invalid)?;
+		Ok(Self {
+			id: id.parse().map_err(|_| invalid())?,
+			made_on: match made_on {
+				"" => None,
+				made_on => Some(made_on.parse().map_err(|_| invalid())?),
+			},
+		})
+	}
+
+	fn encode_cursor(&self) -> String {
+		let made_on = self.made_on.map(|made_on| made_on.to_string()).unwrap_or_default();
+		hex::encode(format!("{}|{made_on}", self.id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::ObjectCursor,
+		async_graphql::connection::CursorType,
+	};
+
+	#[test]
+	fn cursors_round_trip_with_and_without_a_capture_time() -> anyhow::Result<()> {
+		for cursor in [
+			ObjectCursor {
+				id: 42,
+				made_on: Some("2024-06-01T12:30:00Z".parse()?),
+			},
+			ObjectCursor {
+				id: 7,
+				made_on: None,
+			},
+		] {
+			assert_eq!(ObjectCursor::decode_cursor(&cursor.encode_cursor())?, cursor);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn malformed_cursors_are_rejected() {
+		for cursor in ["", "zz", &hex::encode("42"), &hex::encode("x|"), &hex::encode("1|soon")] {
+			assert!(ObjectCursor::decode_cursor(cursor).is_err());
+		}
+	}
+}
@@ -0,0 +1,165 @@
+use {
+	crate::{
+		constants::SEARCH_QUERY_MAX_LENGTH,
+		errors::AppError,
+		graphql::objects::s3_object::S3Object,
+	},
+	async_graphql::{
+		OutputType,
+		connection::{
+			Connection,
+			ConnectionNameType,
+			CursorType,
+			EdgeNameType,
+			EmptyFields,
+		},
+	},
+};
+
+/// The text of a `searchS3Objects` query in the forms its SQL matches on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchTerms {
+	/// Every word of the text as a prefix, for `to_tsquery`.
+	pub prefix_query: String,
+	/// The text as typed, for trigram similarity to names.
+	pub text: String,
+	/// The text anywhere in an email, for `ILIKE`.
+	pub email_pattern: String,
+}
+
+impl SearchTerms {
+	/// Splits `query` into words the way object names are split when indexed:
+	/// on anything that is not a letter or digit.
+	pub fn parse(query: &str) -> Result<Self, AppError> {
+		let text = query.trim();
+		if text.chars().count() > SEARCH_QUERY_MAX_LENGTH {
+			return Err(AppError::Validation(format!(
+				"Search queries can be at most {SEARCH_QUERY_MAX_LENGTH} characters long"
+			)));
+		}
+		let words: Vec<String> = text
+			.split(|c: char| !c.is_alphanumeric())
+			.filter(|word| !word.is_empty())
+			.map(|word| format!("{}:*", word.to_lowercase()))
+			.collect();
+		if words.is_empty() {
+			return Err(AppError::Validation(
+				"Search queries must contain a letter or digit".to_string(),
+			));
+		}
+		let mut email_pattern = String::from("%");
+		for c in text.chars() {
+			if matches!(c, '%' | '_' | '\\') {
+				email_pattern.push('\\');
+			}
+			email_pattern.push(c);
+		}
+		email_pattern.push('%');
+		Ok(Self {
+			prefix_query: words.join(" & "),
+			text: text.to_string(),
+			email_pattern,
+		})
+	}
+}
+
+/// The position of an object in the results of one search: how well it
+/// matched, then its id, which breaks ties. Only meaningful with the query it
+/// came from. Encoded as hex so clients treat it as opaque.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchCursor {
+	pub id: i64,
+	pub rank: f64,
+}
+
+impl CursorType for SearchCursor {
+	type Error = AppError;
+
+	fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+		let invalid = || AppError::Validation("Invalid cursor".to_string());
+		let decoded = hex::decode(s).map_err(|_| invalid())?;
+		let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+		let (id, rank) = decoded.split_once('|').ok_or_else(invalid)?;
+		let rank: f64 = rank.parse().map_err(|_| invalid())?;
+		if !rank.is_finite() {
+			return Err(invalid());
+		}
+		Ok(Self {
+			id: id.parse().map_err(|_| invalid())?,
+			rank,
+		})
+	}
+
+	fn encode_cursor(&self) -> String {
+		// Displaying an f64 gives the shortest text that parses back to it, so
+		// the rank compares equal to the one the database computed.
+		hex::encode(format!("{}|{}", self.id, self.rank))
+	}
+}
+
+/// Names the `searchS3Objects` connection types apart from those of
+/// `s3ObjectsConnection`, which pages through the same node type.
+pub struct SearchConnectionName;
+
+impl ConnectionNameType for SearchConnectionName {
+	fn type_name<T: OutputType>() -> String {
+		"S3ObjectSearchConnection".to_string()
+	}
+}
+
+pub struct SearchEdgeName;
+
+impl EdgeNameType for SearchEdgeName {
+	fn type_name<T: OutputType>() -> String {
+		"S3ObjectSearchEdge".to_string()
+	}
+}
+
+/// A page of `searchS3Objects` results.
+pub type SearchConnection = Connection<
+	SearchCursor,
+	S3Object,
+	EmptyFields,
+	EmptyFields,
+	SearchConnectionName,
+	SearchEdgeName,
+>;
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			SearchCursor,
+			SearchTerms,
+		},
+		async_graphql::connection::CursorType,
+	};
+
+	#[test]
+	fn names_split_into_prefix_words_on_separators() -> anyhow::Result<()> {
+		let terms = SearchTerms::parse("  IMG_2023-05 beach.jpg ")?;
+		assert_eq!(terms.prefix_query, "img:* & 2023:* & 05:* & beach:* & jpg:*");
+		assert_eq!(terms.text, "IMG_2023-05 beach.jpg");
+		assert_eq!(terms.email_pattern, "%IMG\\_2023-05 beach.jpg%");
+		Ok(())
+	}
+
+	#[test]
+	fn queries_without_words_are_rejected() {
+		for query in ["", "   ", "_-.!", &"a".repeat(201)] {
+			assert!(SearchTerms::parse(query).is_err(), "{query:?} was accepted");
+		}
+	}
+
+	#[test]
+	fn cursors_round_trip_their_rank_exactly() -> anyhow::Result<()> {
+		let cursor = SearchCursor {
+			id: 42,
+			rank: 0.1 + 0.2,
+		};
+		assert_eq!(SearchCursor::decode_cursor(&cursor.encode_cursor())?, cursor);
+		assert!(SearchCursor::decode_cursor(&hex::encode("1|NaN")).is_err());
+		assert!(SearchCursor::decode_cursor(&hex::encode("1")).is_err());
+		Ok(())
+	}
+}
@@ -0,0 +1,232 @@
+use {
+	crate::{
+		AuthConfig,
+		ClientInfo,
+		ContextWrapper,
+		db::queries::{
+			DELETE_OTHER_SESSIONS_QUERY,
+			DELETE_SESSION_QUERY,
+			DELETE_SESSIONS_BY_USER_QUERY,
+			INSERT_SESSION_QUERY,
+			SELECT_SESSION_CALLER_QUERY,
+			SELECT_SESSIONS_FOR_USER_QUERY,
+		},
+		errors::AppError,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		Error as GraphQLError,
+		ID,
+		Object,
+	},
+	jiff::Timestamp,
+	rand::{
+		RngExt,
+		distr::Alphanumeric,
+	},
+	std::net::IpAddr,
+	tokio_postgres::{
+		Row,
+		Transaction,
+	},
+};
+
+/// A signed-in browser or device. The auth cookie holds its token, of which
+/// only a hash is kept, so a session can be listed and revoked but not taken
+/// over from the database.
+#[derive(Clone, Debug)]
+pub struct Session {
+	pub id: i64,
+	pub user_id: i64,
+	pub user_agent: Option<String>,
+	pub ip_address: Option<IpAddr>,
+	pub created_at: Timestamp,
+	pub last_seen_at: Timestamp,
+	pub expires_at: Timestamp,
+}
+
+/// Who a session token signs in.
+#[derive(Clone, Debug)]
+pub struct SessionCaller {
+	pub session_id: i64,
+	pub user_id: i64,
+	pub role: String,
+}
+
+fn hash_token(token: &str) -> String {
+	blake3::hash(token.as_bytes()).to_string()
+}
+
+impl TryFrom<Row> for Session {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(Session {
+			id: row.try_get("id").context("Failed to read session id")?,
+			user_id: row.try_get("user_id").context("Failed to read session user_id")?,
+			user_agent: row.try_get("user_agent").context("Failed to read session user_agent")?,
+			ip_address: row.try_get("ip_address").context("Failed to read session ip_address")?,
+			created_at: row.try_get("created_at").context("Failed to read session created_at")?,
+			last_seen_at: row
+				.try_get("last_seen_at")
+				.context("Failed to read session last_seen_at")?,
+			expires_at: row.try_get("expires_at").context("Failed to read session expires_at")?,
+		})
+	}
+}
+
+impl Session {
+	/// Starts a session for `user_id` on the client described by `client`,
+	/// returning the token its cookie holds.
+	pub async fn start(
+		transaction: &Transaction<'_>,
+		user_id: i64,
+		client: &ClientInfo,
+	) -> Result<String, AppError> {
+		let token: String =
+			rand::rng().sample_iter(Alphanumeric).take(32).map(char::from).collect();
+		transaction
+			.execute(
+				INSERT_SESSION_QUERY,
+				&[&hash_token(&token), &user_id, &client.user_agent, &client.ip_address],
+			)
+			.await
+			.context("Failed to insert session")?;
+		Ok(token)
+	}
+
+	/// Who `token` signs in, unless its session has been revoked or has timed
+	/// out. Counts as using the session.
+	pub async fn caller(
+		client: &deadpool_postgres::Client,
+		token: &str,
+		config: &AuthConfig,
+	) -> Result<Option<SessionCaller>, AppError> {
+		let statement = client.prepare_cached(SELECT_SESSION_CALLER_QUERY).await?;
+		let Some(row) = client
+			.query_opt(
+				&statement,
+				&[
+					&hash_token(token),
+					&config.session_idle_timeout_seconds,
+					&config.session_absolute_timeout_seconds,
+				],
+			)
+			.await?
+		else {
+			return Ok(None);
+		};
+		Ok(Some(SessionCaller {
+			session_id: row.try_get("id").context("Failed to read session id")?,
+			user_id: row.try_get("user_id").context("Failed to read session user_id")?,
+			role: row.try_get("role").context("Failed to read session user role")?,
+		}))
+	}
+
+	/// `user_id`'s sessions that have not timed out, most recently used first.
+	pub async fn for_user(
+		ctx: &Context<'_>,
+		user_id: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let config = &wrapper.shared_state().config.auth;
+		let client = wrapper.db_client().await?;
+		let statement = client.prepare_cached(SELECT_SESSIONS_FOR_USER_QUERY).await?;
+		client
+			.query(
+				&statement,
+				&[
+					&user_id,
+					&config.session_idle_timeout_seconds,
+					&config.session_absolute_timeout_seconds,
+				],
+			)
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	/// Ends `user_id`'s session `id`, signing out whoever holds its cookie.
+	/// Returns whether they had such a session.
+	pub async fn revoke(
+		ctx: &Context<'_>,
+		user_id: i64,
+		id: i64,
+	) -> Result<bool, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let revoked = client
+			.execute(DELETE_SESSION_QUERY, &[&id, &user_id])
+			.await
+			.context("Failed to revoke session")?;
+		Ok(revoked > 0)
+	}
+
+	/// Ends every one of `user_id`'s sessions but `kept_id`.
+	pub async fn revoke_others(
+		ctx: &Context<'_>,
+		user_id: i64,
+		kept_id: i64,
+	) -> Result<(), AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		client
+			.execute(DELETE_OTHER_SESSIONS_QUERY, &[&user_id, &kept_id])
+			.await
+			.context("Failed to revoke sessions")?;
+		Ok(())
+	}
+
+	/// Ends every one of `user_id`'s sessions, as after their password changes.
+	pub async fn revoke_all(
+		transaction: &Transaction<'_>,
+		user_id: i64,
+	) -> Result<(), AppError> {
+		transaction
+			.execute(DELETE_SESSIONS_BY_USER_QUERY, &[&user_id])
+			.await
+			.context("Failed to revoke sessions")?;
+		Ok(())
+	}
+}
+
+#[Object]
+impl Session {
+	async fn id(&self) -> ID {
+		ID(self.id.to_string())
+	}
+
+	/// The `User-Agent` the session was signed in with.
+	async fn user_agent(&self) -> Option<&str> {
+		self.user_agent.as_deref()
+	}
+
+	/// The address the session was signed in from, as the server saw it.
+	async fn ip_address(&self) -> Option<String> {
+		self.ip_address.map(|ip_address| ip_address.to_string())
+	}
+
+	async fn created_at(&self) -> String {
+		self.created_at.to_string()
+	}
+
+	async fn last_seen_at(&self) -> String {
+		self.last_seen_at.to_string()
+	}
+
+	/// When the session stops working unless it is used again sooner. It
+	/// never outlasts the absolute session timeout.
+	async fn expires_at(&self) -> String {
+		self.expires_at.to_string()
+	}
+
+	/// Whether this is the session making the request.
+	async fn current(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<bool, GraphQLError> {
+		Ok(ContextWrapper::new(ctx)?
+			.caller_identity_opt()
+			.is_some_and(|identity| identity.session_id == self.id))
+	}
+}
@@ -0,0 +1,478 @@
+use {
+	crate::{
+		ContextWrapper,
+		constants::{
+			SHARE_LINK_MAX_PASSWORD_ATTEMPTS,
+			SHARE_LINK_PASSWORD_LOCKOUT_SECONDS,
+		},
+		db::queries::{
+			COUNT_SHARE_LINK_PASSWORD_ATTEMPT_QUERY,
+			COUNT_SHARE_LINK_VIEW_QUERY,
+			INSERT_SHARE_LINK_QUERY,
+			RESET_SHARE_LINK_PASSWORD_ATTEMPTS_QUERY,
+			REVOKE_SHARE_LINK_QUERY,
+			SELECT_ALBUM_OBJECTS_QUERY,
+			SELECT_OBJECT_BY_ID_QUERY,
+			SELECT_OPENABLE_SHARE_LINK_QUERY,
+			SELECT_SHARE_LINK_BY_ID_QUERY,
+			SELECT_SHARE_LINKS_FOR_USER_QUERY,
+		},
+		errors::AppError,
+		graphql::objects::{
+			album::Album,
+			s3_object::S3Object,
+		},
+	},
+	anyhow::Context as AnyhowContext,
+	argon2::{
+		Argon2,
+		PasswordHash,
+		PasswordHasher,
+		PasswordVerifier,
+		password_hash::{
+			SaltString,
+			rand_core::OsRng,
+		},
+	},
+	async_graphql::{
+		Context,
+		ID,
+		Object,
+		SimpleObject,
+	},
+	jiff::Timestamp,
+	rand::{
+		RngExt,
+		distr::Alphanumeric,
+	},
+	tokio_postgres::Row,
+};
+
+/// The one error for a token that does not open anything, so visitors cannot
+/// tell a revoked, expired or used-up link from one that never existed.
+const INVALID_SHARE_LINK: &str = "Invalid or expired share link";
+
+/// A link that lets anyone holding its token see one object or one album
+/// without an account. Only a hash of the token is kept, so it is shown once,
+/// when the link is created.
+#[derive(Clone, Debug)]
+pub struct ShareLink {
+	pub id: i64,
+	pub user_id: i64,
+	pub object_id: Option<i64>,
+	pub album_id: Option<i64>,
+	pub has_password: bool,
+	pub expires_at: Option<Timestamp>,
+	pub max_views: Option<i32>,
+	pub view_count: i32,
+	pub revoked_at: Option<Timestamp>,
+	pub created_at: Timestamp,
+	pub active: bool,
+}
+
+/// What a share link shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShareLinkTarget {
+	Object(i64),
+	Album(i64),
+}
+
+impl ShareLinkTarget {
+	/// The target named by exactly one of `object_id` and `album_id`.
+	pub fn new(
+		object_id: Option<i64>,
+		album_id: Option<i64>,
+	) -> Result<Self, AppError> {
+		match (object_id, album_id) {
+			(Some(object_id), None) => Ok(Self::Object(object_id)),
+			(None, Some(album_id)) => Ok(Self::Album(album_id)),
+			_ => Err(AppError::Validation(
+				"A share link must be for either one object or one album".to_string(),
+			)),
+		}
+	}
+}
+
+/// The limits its owner puts on a share link.
+#[derive(Clone, Debug, Default)]
+pub struct ShareLinkOptions {
+	pub expires_at: Option<Timestamp>,
+	pub password: Option<String>,
+	pub max_views: Option<i32>,
+}
+
+impl ShareLinkOptions {
+	pub fn new(
+		expires_at: Option<String>,
+		password: Option<String>,
+		max_views: Option<i32>,
+		now: Timestamp,
+	) -> Result<Self, AppError> {
+		let expires_at = expires_at
+			.map(|expires_at| {
+				expires_at
+					.parse::<Timestamp>()
+					.map_err(|e| AppError::Validation(format!("Invalid timestamp format: {e}")))
+			})
+			.transpose()?;
+		if expires_at.is_some_and(|expires_at| expires_at <= now) {
+			return Err(AppError::Validation(
+				"A share link's expiry must be in the future".to_string(),
+			));
+		}
+		if password.as_ref().is_some_and(String::is_empty) {
+			return Err(AppError::Validation("Share link password must not be empty".to_string()));
+		}
+		if max_views.is_some_and(|max_views| max_views < 1) {
+			return Err(AppError::Validation(
+				"A share link must allow at least one view".to_string(),
+			));
+		}
+		Ok(Self {
+			expires_at,
+			password,
+			max_views,
+		})
+	}
+}
+
+/// A new share link with its token, which cannot be read back later.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct CreatedShareLink {
+	pub token: String,
+	pub link: ShareLink,
+}
+
+/// What a visitor sees through a share link.
+#[derive(Clone, Debug, Default, SimpleObject)]
+pub struct SharedItems {
+	/// Whether the link needs a password. Nothing else is filled in until the
+	/// right one is given.
+	pub password_required: bool,
+	pub title: String,
+	/// Markdown.
+	pub description: String,
+	/// When the link stops working, if it expires.
+	pub expires_at: Option<String>,
+	pub objects: Vec<S3Object>,
+}
+
+fn hash_token(token: &str) -> String {
+	blake3::hash(token.as_bytes()).to_string()
+}
+
+impl TryFrom<Row> for ShareLink {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(ShareLink {
+			id: row.try_get("id").context("Failed to read share link id")?,
+			user_id: row.try_get("user_id").context("Failed to read share link user_id")?,
+			object_id: row.try_get("object_id").context("Failed to read share link object_id")?,
+			album_id: row.try_get("album_id").context("Failed to read share link album_id")?,
+			has_password: row
+				.try_get("has_password")
+				.context("Failed to read share link has_password")?,
+			expires_at: row
+				.try_get("expires_at")
+				.context("Failed to read share link expires_at")?,
+			max_views: row.try_get("max_views").context("Failed to read share link max_views")?,
+			view_count: row
+				.try_get("view_count")
+				.context("Failed to read share link view_count")?,
+			revoked_at: row
+				.try_get("revoked_at")
+				.context("Failed to read share link revoked_at")?,
+			created_at: row
+				.try_get("created_at")
+				.context("Failed to read share link created_at")?,
+			active: row.try_get("active").context("Failed to read share link active")?,
+		})
+	}
+}
+
+impl ShareLink {
+	pub async fn by_id(
+		ctx: &Context<'_>,
+		id: i64,
+	) -> Result<Self, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_SHARE_LINK_BY_ID_QUERY).await?;
+		client
+			.query_opt(&statement, &[&id])
+			.await?
+			.map(Self::try_from)
+			.transpose()?
+			.ok_or_else(|| AppError::NotFound("Share link not found".to_string()))
+	}
+
+	/// `user_id`'s share links, only those of `object_id` or `album_id` when
+	/// given, newest first.
+	pub async fn for_user(
+		ctx: &Context<'_>,
+		user_id: i64,
+		object_id: Option<i64>,
+		album_id: Option<i64>,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_SHARE_LINKS_FOR_USER_QUERY).await?;
+		client
+			.query(&statement, &[&user_id, &object_id, &album_id])
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+
+	/// Creates a link to `target` owned by `user_id`, along with its token.
+	pub async fn create(
+		ctx: &Context<'_>,
+		user_id: i64,
+		target: ShareLinkTarget,
+		options: ShareLinkOptions,
+	) -> Result<CreatedShareLink, AppError> {
+		let password_hash = options
+			.password
+			.map(|password| {
+				let salt = SaltString::generate(&mut OsRng);
+				Argon2::default()
+					.hash_password(password.as_bytes(), &salt)
+					.map(|hash| hash.to_string())
+			})
+			.transpose()?;
+		let (object_id, album_id) = match target {
+			ShareLinkTarget::Object(id) => (Some(id), None),
+			ShareLinkTarget::Album(id) => (None, Some(id)),
+		};
+		let token: String =
+			rand::rng().sample_iter(Alphanumeric).take(32).map(char::from).collect();
+
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let row = client
+			.query_one(
+				INSERT_SHARE_LINK_QUERY,
+				&[
+					&hash_token(&token),
+					&user_id,
+					&object_id,
+					&album_id,
+					&password_hash,
+					&options.expires_at,
+					&options.max_views,
+				],
+			)
+			.await
+			.context("Failed to insert share link")?;
+		Ok(CreatedShareLink {
+			token,
+			link: Self::try_from(row)?,
+		})
+	}
+
+	/// Stops the link from opening. Revoking it again keeps the first time.
+	pub async fn revoke(
+		ctx: &Context<'_>,
+		id: i64,
+	) -> Result<Self, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let row = client
+			.query_one(REVOKE_SHARE_LINK_QUERY, &[&id])
+			.await
+			.context("Failed to revoke share link")?;
+		Self::try_from(row)
+	}
+}
+
+#[Object]
+impl ShareLink {
+	async fn id(&self) -> ID {
+		ID(self.id.to_string())
+	}
+
+	async fn object_id(&self) -> Option<ID> {
+		self.object_id.map(|id| ID(id.to_string()))
+	}
+
+	async fn album_id(&self) -> Option<ID> {
+		self.album_id.map(|id| ID(id.to_string()))
+	}
+
+	async fn has_password(&self) -> bool {
+		self.has_password
+	}
+
+	async fn expires_at(&self) -> Option<String> {
+		self.expires_at.map(|expires_at| expires_at.to_string())
+	}
+
+	/// How many times the link may be opened, or null for no limit.
+	async fn max_views(&self) -> Option<i32> {
+		self.max_views
+	}
+
+	async fn view_count(&self) -> i32 {
+		self.view_count
+	}
+
+	async fn revoked_at(&self) -> Option<String> {
+		self.revoked_at.map(|revoked_at| revoked_at.to_string())
+	}
+
+	async fn created_at(&self) -> String {
+		self.created_at.to_string()
+	}
+
+	/// Whether the link still opens: not revoked, expired or used up.
+	async fn active(&self) -> bool {
+		self.active
+	}
+}
+
+impl SharedItems {
+	/// Opens the link with `token`, counting a view. A link with a password
+	/// only says it needs one, without counting a view, until it is given.
+	pub async fn open(
+		ctx: &Context<'_>,
+		token: &str,
+		password: Option<String>,
+	) -> Result<Self, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let link = client
+			.query_opt(SELECT_OPENABLE_SHARE_LINK_QUERY, &[&hash_token(token)])
+			.await
+			.context("Failed to query share link")?
+			.ok_or_else(|| AppError::Validation(INVALID_SHARE_LINK.to_string()))?;
+		let id: i64 = link.try_get("id").context("Failed to read share link id")?;
+		let object_id: Option<i64> =
+			link.try_get("object_id").context("Failed to read share link object_id")?;
+		let album_id: Option<i64> =
+			link.try_get("album_id").context("Failed to read share link album_id")?;
+		let password_hash: Option<String> =
+			link.try_get("password_hash").context("Failed to read share link password_hash")?;
+		let expires_at: Option<Timestamp> =
+			link.try_get("expires_at").context("Failed to read share link expires_at")?;
+
+		if let Some(password_hash) = password_hash {
+			let Some(password) = password else {
+				return Ok(Self {
+					password_required: true,
+					..Self::default()
+				});
+			};
+			// Each attempt is counted before the password is checked, so
+			// concurrent guesses cannot get past the limit.
+			let counted = client
+				.query_opt(
+					COUNT_SHARE_LINK_PASSWORD_ATTEMPT_QUERY,
+					&[&id, &SHARE_LINK_MAX_PASSWORD_ATTEMPTS, &SHARE_LINK_PASSWORD_LOCKOUT_SECONDS],
+				)
+				.await
+				.context("Failed to count share link password attempt")?;
+			if counted.is_none() {
+				return Err(AppError::Validation(
+					"Too many incorrect share link passwords; try again later".to_string(),
+				));
+			}
+			let parsed_hash = PasswordHash::new(&password_hash)?;
+			Argon2::default()
+				.verify_password(password.as_bytes(), &parsed_hash)
+				.map_err(|_| AppError::Validation("Incorrect share link password".to_string()))?;
+			client
+				.execute(RESET_SHARE_LINK_PASSWORD_ATTEMPTS_QUERY, &[&id])
+				.await
+				.context("Failed to reset share link password attempts")?;
+		}
+
+		let mut shared = match ShareLinkTarget::new(object_id, album_id)? {
+			ShareLinkTarget::Object(object_id) => {
+				// A trashed object is no longer shared, though restoring it
+				// shares it again.
+				let object = client
+					.query_opt(SELECT_OBJECT_BY_ID_QUERY, &[&object_id])
+					.await?
+					.map(S3Object::try_from)
+					.transpose()?
+					.ok_or_else(|| AppError::Validation(INVALID_SHARE_LINK.to_string()))?;
+				Self {
+					title: if object.title.is_empty() {
+						object.name.clone()
+					} else {
+						object.title.clone()
+					},
+					description: object.description.clone(),
+					objects: vec![object],
+					..Self::default()
+				}
+			}
+			ShareLinkTarget::Album(album_id) => {
+				let album = Album::by_id(ctx, album_id).await?;
+				let objects = client
+					.query(SELECT_ALBUM_OBJECTS_QUERY, &[&album_id])
+					.await?
+					.into_iter()
+					.map(S3Object::try_from)
+					.collect::<Result<Vec<_>, _>>()?;
+				Self {
+					title: album.title,
+					description: album.description,
+					objects,
+					..Self::default()
+				}
+			}
+		};
+
+		// Counted last and only while the link still opens, so concurrent views
+		// cannot go past its limit.
+		let counted = client
+			.query_opt(COUNT_SHARE_LINK_VIEW_QUERY, &[&id])
+			.await
+			.context("Failed to count share link view")?;
+		if counted.is_none() {
+			return Err(AppError::Validation(INVALID_SHARE_LINK.to_string()));
+		}
+
+		// Who else an object is shared with is its owner's business.
+		for object in &mut shared.objects {
+			object.allowed_users.clear();
+			object.allowed_groups.clear();
+		}
+		shared.expires_at = expires_at.map(|expires_at| expires_at.to_string());
+		Ok(shared)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			ShareLinkOptions,
+			ShareLinkTarget,
+		},
+		jiff::Timestamp,
+	};
+
+	#[test]
+	fn share_links_target_exactly_one_object_or_album() {
+		assert_eq!(ShareLinkTarget::new(Some(1), None).ok(), Some(ShareLinkTarget::Object(1)));
+		assert_eq!(ShareLinkTarget::new(None, Some(2)).ok(), Some(ShareLinkTarget::Album(2)));
+		assert!(ShareLinkTarget::new(Some(1), Some(2)).is_err());
+		assert!(ShareLinkTarget::new(None, None).is_err());
+	}
+
+	#[test]
+	fn share_link_options_reject_past_expiry_empty_passwords_and_no_views() -> anyhow::Result<()> {
+		let now: Timestamp = "2026-01-01T00:00:00Z".parse()?;
+		assert!(
+			ShareLinkOptions::new(Some("2026-02-01T00:00:00Z".to_string()), None, Some(1), now)
+				.is_ok()
+		);
+		assert!(
+			ShareLinkOptions::new(Some("2025-12-31T00:00:00Z".to_string()), None, None, now)
+				.is_err()
+		);
+		assert!(ShareLinkOptions::new(Some("tomorrow".to_string()), None, None, now).is_err());
+		assert!(ShareLinkOptions::new(None, Some(String::new()), None, now).is_err());
+		assert!(ShareLinkOptions::new(None, None, Some(0), now).is_err());
+		Ok(())
+	}
+}
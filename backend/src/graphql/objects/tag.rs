@@ -0,0 +1,184 @@
+use {
+	crate::{
+		ContextWrapper,
+		constants::TAG_MAX_LENGTH,
+		db::queries::{
+			DELETE_ALL_OBJECT_TAGS_QUERY,
+			DELETE_OBJECT_TAGS_QUERY,
+			INSERT_OBJECT_TAGS_QUERY,
+			INSERT_TAGS_QUERY,
+			SELECT_OBJECT_TAG_NAMES_QUERY,
+			SELECT_TAG_SUGGESTIONS_QUERY,
+		},
+		errors::AppError,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		SimpleObject,
+	},
+	tokio_postgres::{
+		Row,
+		Transaction,
+	},
+};
+
+/// One of the caller's tags, as suggested by `tags`.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct Tag {
+	pub name: String,
+	/// How many of the caller's objects have the tag.
+	pub object_count: i64,
+}
+
+impl TryFrom<Row> for Tag {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(Self {
+			name: row.try_get("name").context("Failed to read tag name")?,
+			object_count: row.try_get("object_count").context("Failed to read tag object_count")?,
+		})
+	}
+}
+
+impl Tag {
+	/// Up to `limit` of the user's tags starting with `prefix`, most used first.
+	pub async fn suggestions(
+		ctx: &Context<'_>,
+		user_id: i64,
+		prefix: &str,
+		limit: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let mut pattern = String::new();
+		for c in normalized(prefix).chars() {
+			if matches!(c, '%' | '_' | '\\') {
+				pattern.push('\\');
+			}
+			pattern.push(c);
+		}
+		pattern.push('%');
+
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_TAG_SUGGESTIONS_QUERY).await?;
+		client
+			.query(&statement, &[&user_id, &pattern, &limit])
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+}
+
+/// `name` trimmed, with each run of whitespace as one space, in lowercase.
+fn normalized(name: &str) -> String {
+	name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// `names` as tags are stored, without repeats. Lists of tags are edited as
+/// comma-separated text, so tags cannot contain commas.
+pub fn normalize_tags(names: &[String]) -> Result<Vec<String>, AppError> {
+	let mut tags: Vec<String> = Vec::with_capacity(names.len());
+	for name in names {
+		let tag = normalized(name);
+		if tag.is_empty() {
+			return Err(AppError::Validation("Tags must not be empty".to_string()));
+		}
+		if tag.contains(',') {
+			return Err(AppError::Validation("Tags must not contain commas".to_string()));
+		}
+		if tag.chars().count() > TAG_MAX_LENGTH {
+			return Err(AppError::Validation(format!(
+				"Tags can be at most {TAG_MAX_LENGTH} characters long"
+			)));
+		}
+		if !tags.contains(&tag) {
+			tags.push(tag);
+		}
+	}
+	Ok(tags)
+}
+
+/// Puts the normalized `tags` on each of `object_ids`, creating the owners'
+/// tags that do not exist yet.
+pub async fn add_object_tags(
+	transaction: &Transaction<'_>,
+	object_ids: &[i64],
+	tags: &[String],
+) -> Result<(), AppError> {
+	if tags.is_empty() {
+		return Ok(());
+	}
+	transaction
+		.execute(INSERT_TAGS_QUERY, &[&object_ids, &tags])
+		.await
+		.context("Failed to insert tags")?;
+	transaction
+		.execute(INSERT_OBJECT_TAGS_QUERY, &[&object_ids, &tags])
+		.await
+		.context("Failed to tag objects")?;
+	Ok(())
+}
+
+/// Takes the normalized `tags` off each of `object_ids`.
+pub async fn remove_object_tags(
+	transaction: &Transaction<'_>,
+	object_ids: &[i64],
+	tags: &[String],
+) -> Result<(), AppError> {
+	transaction
+		.execute(DELETE_OBJECT_TAGS_QUERY, &[&object_ids, &tags])
+		.await
+		.context("Failed to untag objects")?;
+	Ok(())
+}
+
+/// Replaces the tags on `object_id` with the normalized `tags`, returning them
+/// in order.
+pub async fn replace_object_tags(
+	transaction: &Transaction<'_>,
+	object_id: i64,
+	tags: &[String],
+) -> Result<Vec<String>, AppError> {
+	let object_ids = [object_id];
+	transaction
+		.execute(DELETE_ALL_OBJECT_TAGS_QUERY, &[&object_ids.as_slice()])
+		.await
+		.context("Failed to clear object tags")?;
+	add_object_tags(transaction, &object_ids, tags).await?;
+	object_tag_names(transaction, object_id).await
+}
+
+/// The names of the tags on `object_id`, in order.
+pub async fn object_tag_names(
+	transaction: &Transaction<'_>,
+	object_id: i64,
+) -> Result<Vec<String>, AppError> {
+	transaction
+		.query(SELECT_OBJECT_TAG_NAMES_QUERY, &[&object_id])
+		.await
+		.context("Failed to load object tags")?
+		.into_iter()
+		.map(|row| row.try_get("name").context("Failed to read tag name"))
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(AppError::from)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::normalize_tags;
+
+	#[test]
+	fn tags_are_trimmed_single_spaced_lowercase_and_distinct() -> anyhow::Result<()> {
+		let names = ["  Summer  Holiday ", "summer holiday", "Beach"].map(String::from);
+		assert_eq!(normalize_tags(&names)?, vec!["summer holiday", "beach"]);
+		Ok(())
+	}
+
+	#[test]
+	fn empty_comma_separated_and_overlong_tags_are_rejected() {
+		for name in ["", "   ", "beach, sea", &"a".repeat(65)] {
+			assert!(normalize_tags(&[name.to_string()]).is_err(), "{name:?} was accepted");
+		}
+	}
+}
@@ -0,0 +1,113 @@
+use {
+	crate::{
+		ContextWrapper,
+		db::queries::SELECT_TIMELINE_HISTOGRAM_QUERY,
+		errors::AppError,
+		graphql::objects::s3_object_connection::ObjectPageFilter,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		Enum,
+		Object,
+	},
+	jiff::Timestamp,
+	tokio_postgres::Row,
+};
+
+/// The span of time `timelineHistogram` counts objects over, in UTC.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum TimelineBucket {
+	Day,
+	#[default]
+	Month,
+	Year,
+}
+
+impl TimelineBucket {
+	/// The PostgreSQL `date_trunc` field that truncates to the bucket's start.
+	pub const fn date_trunc_field(self) -> &'static str {
+		match self {
+			TimelineBucket::Day => "day",
+			TimelineBucket::Month => "month",
+			TimelineBucket::Year => "year",
+		}
+	}
+}
+
+/// How many objects were made in one bucket of the timeline.
+#[derive(Clone, Debug)]
+pub struct TimelineBucketCount {
+	pub start: Timestamp,
+	pub count: i64,
+	pub latest_made_on: Timestamp,
+}
+
+impl TryFrom<Row> for TimelineBucketCount {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(Self {
+			start: row.try_get("bucket_start").context("Failed to read timeline bucket_start")?,
+			count: row.try_get("count").context("Failed to read timeline count")?,
+			latest_made_on: row
+				.try_get("latest_made_on")
+				.context("Failed to read timeline latest_made_on")?,
+		})
+	}
+}
+
+impl TimelineBucketCount {
+	/// The dated objects visible to `user_id` (every object when `read_all`)
+	/// and matching `filter`, counted per `bucket`. Buckets without objects are
+	/// left out.
+	pub async fn histogram(
+		ctx: &Context<'_>,
+		user_id: Option<i64>,
+		read_all: bool,
+		bucket: TimelineBucket,
+		filter: &ObjectPageFilter,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_TIMELINE_HISTOGRAM_QUERY).await?;
+		client
+			.query(
+				&statement,
+				&[
+					&user_id,
+					&read_all,
+					&bucket.date_trunc_field(),
+					&filter.owner_id,
+					&filter.media_type,
+					&filter.made_on_from,
+					&filter.made_on_to,
+					&filter.publicity,
+					&filter.has_location,
+					&filter.tags,
+				],
+			)
+			.await?
+			.into_iter()
+			.map(Self::try_from)
+			.collect()
+	}
+}
+
+#[Object]
+impl TimelineBucketCount {
+	/// When the bucket starts, as an RFC 3339 timestamp.
+	async fn start(&self) -> String {
+		self.start.to_string()
+	}
+
+	/// How many objects were made in the bucket.
+	async fn count(&self) -> i64 {
+		self.count
+	}
+
+	/// The latest capture time in the bucket. Filtering `madeOnTo` by it takes
+	/// in the whole bucket without reaching into the next.
+	async fn latest_made_on(&self) -> String {
+		self.latest_made_on.to_string()
+	}
+}
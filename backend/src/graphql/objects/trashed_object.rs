@@ -0,0 +1,69 @@
+use {
+	crate::{
+		ContextWrapper,
+		db::queries::{
+			SELECT_TRASHED_OBJECTS_BY_IDS_QUERY,
+			SELECT_TRASHED_OBJECTS_FOR_USER_QUERY,
+		},
+		errors::AppError,
+		graphql::objects::s3_object::S3Object,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		SimpleObject,
+	},
+	jiff::{
+		SignedDuration,
+		Timestamp,
+	},
+};
+
+/// An object in its owner's trash.
+#[derive(Clone, Debug, SimpleObject)]
+pub struct TrashedObject {
+	pub object: S3Object,
+	/// When the object was deleted.
+	pub trashed_at: String,
+	/// When maintenance deletes the object for good, unless it is restored or
+	/// the trash is emptied first.
+	pub purge_at: String,
+}
+
+impl TrashedObject {
+	/// `user_id`'s trashed objects, most recently deleted first.
+	pub async fn for_user(
+		ctx: &Context<'_>,
+		user_id: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let retention = SignedDuration::from_secs(
+			wrapper.shared_state().config.object_lifecycle.trash_retention_seconds,
+		);
+		let client = wrapper.db_client().await?;
+		let statement = client.prepare_cached(SELECT_TRASHED_OBJECTS_FOR_USER_QUERY).await?;
+		let mut trashed = Vec::new();
+		for row in client.query(&statement, &[&user_id]).await? {
+			let trashed_at: Timestamp =
+				row.try_get("trashed_at").context("Failed to read object trashed_at")?;
+			let purge_at =
+				trashed_at.saturating_add(retention).context("Invalid trash retention")?;
+			trashed.push(Self {
+				object: S3Object::try_from(row)?,
+				trashed_at: trashed_at.to_string(),
+				purge_at: purge_at.to_string(),
+			});
+		}
+		Ok(trashed)
+	}
+
+	/// Those of the objects `ids` that are in the trash.
+	pub async fn objects_where_ids(
+		ctx: &Context<'_>,
+		ids: &[i64],
+	) -> Result<Vec<S3Object>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_TRASHED_OBJECTS_BY_IDS_QUERY).await?;
+		client.query(&statement, &[&ids]).await?.into_iter().map(S3Object::try_from).collect()
+	}
+}
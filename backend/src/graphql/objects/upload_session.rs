@@ -1,7 +1,9 @@
 use {
 	crate::{
+		graphql::objects::s3_object::S3Object,
 		object_lifecycle::{
 			CreatedObjectUploadSession as CreatedObjectUploadSessionInner,
+			DeduplicatedUploadSession,
 			PresignedObjectUploadPart as PresignedObjectUploadPartInner,
 		},
 		storage::PresignedHeader as PresignedHeaderInner,
@@ -9,6 +11,7 @@ use {
 	async_graphql::{
 		ID,
 		Object,
+		Union,
 	},
 	jiff::Timestamp,
 };
@@ -41,6 +44,23 @@ impl From<CreatedObjectUploadSessionInner> for CreatedObjectUploadSession {
 	}
 }
 
+/// Either the caller's existing object with the same content, or a session to
+/// upload the new content through.
+#[derive(Debug, Union)]
+pub enum DeduplicatedObjectUpload {
+	Existing(Box<S3Object>),
+	Created(CreatedObjectUploadSession),
+}
+
+impl From<DeduplicatedUploadSession> for DeduplicatedObjectUpload {
+	fn from(upload: DeduplicatedUploadSession) -> Self {
+		match upload {
+			DeduplicatedUploadSession::Existing(object) => Self::Existing(object),
+			DeduplicatedUploadSession::Created(session) => Self::Created(session.into()),
+		}
+	}
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct AbortedObjectUpload {
 	object_id: i64,
@@ -7,6 +7,8 @@ use {
 			SELECT_USER_BY_ID_QUERY,
 		},
 		errors::AppError,
+		graphql::objects::location::LocationPrecision,
+		media::sanitize::MetadataPrivacy,
 	},
 	anyhow::Context as AnyhowContext,
 	async_graphql::{
@@ -96,6 +98,8 @@ pub struct User {
 	pub email: String,
 	pub role: UserRole,
 	pub default_publicity: PublicityDefault,
+	pub metadata_privacy: MetadataPrivacy,
+	pub location_precision: LocationPrecision,
 	pub created_at: Timestamp,
 	pub updated_at: Timestamp,
 }
@@ -115,6 +119,12 @@ impl TryFrom<Row> for User {
 			email: row.try_get("email").context("Failed to read user email")?,
 			role,
 			default_publicity,
+			metadata_privacy: row
+				.try_get("metadata_privacy")
+				.context("Failed to read user metadata_privacy")?,
+			location_precision: row
+				.try_get("location_precision")
+				.context("Failed to read user location_precision")?,
 			created_at: row.try_get("created_at").context("Failed to read user created_at")?,
 			updated_at: row.try_get("updated_at").context("Failed to read user updated_at")?,
 		})
@@ -171,6 +181,18 @@ impl User {
 		self.default_publicity
 	}
 
+	/// What other people are served of the metadata embedded in this user's
+	/// originals, unless an object overrides it.
+	async fn metadata_privacy(&self) -> MetadataPrivacy {
+		self.metadata_privacy
+	}
+
+	/// How precisely other people see where this user's objects were made,
+	/// unless an object overrides it.
+	async fn location_precision(&self) -> LocationPrecision {
+		self.location_precision
+	}
+
 	async fn created_at(&self) -> String {
 		self.created_at.to_string()
 	}
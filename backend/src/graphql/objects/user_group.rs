@@ -0,0 +1,271 @@
+use {
+	crate::{
+		ContextWrapper,
+		db::queries::{
+			ADD_USER_GROUP_MEMBERS_QUERY,
+			DELETE_USER_GROUP_QUERY,
+			INSERT_USER_GROUP_QUERY,
+			REMOVE_USER_GROUP_MEMBERS_QUERY,
+			RENAME_USER_GROUP_QUERY,
+			SELECT_USER_GROUP_BY_ID_QUERY,
+			SELECT_USER_GROUPS_FOR_USER_QUERY,
+		},
+		errors::AppError,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::{
+		Context,
+		ID,
+		Object,
+	},
+	jiff::Timestamp,
+	std::collections::HashSet,
+	tokio_postgres::{
+		Row,
+		error::SqlState,
+	},
+};
+
+/// A named group of users, such as family or a club, that its owner shares
+/// `SELECTED_USERS` objects and albums with all at once. Only the owner sees
+/// it; its members see just what is shared with them.
+#[derive(Clone, Debug)]
+pub struct UserGroup {
+	pub id: i64,
+	pub user_id: i64,
+	pub name: String,
+	pub members: Vec<String>,
+	pub created_at: Timestamp,
+}
+
+/// `name` without surrounding whitespace, if anything is left.
+fn group_name(name: &str) -> Result<String, AppError> {
+	let name = name.trim();
+	if name.is_empty() {
+		return Err(AppError::Validation("Group name must not be empty".to_string()));
+	}
+	Ok(name.to_string())
+}
+
+/// The trimmed, non-empty `emails`, without repeats.
+fn member_emails(emails: &[String]) -> Vec<String> {
+	let mut seen = HashSet::with_capacity(emails.len());
+	emails
+		.iter()
+		.map(|email| email.trim())
+		.filter(|email| !email.is_empty() && seen.insert(*email))
+		.map(str::to_string)
+		.collect()
+}
+
+/// Fails naming those of `requested` that are not in `found`.
+fn require_known_emails(
+	requested: &[String],
+	found: &[String],
+) -> Result<(), AppError> {
+	let unknown = requested
+		.iter()
+		.filter(|email| !found.contains(email))
+		.map(String::as_str)
+		.collect::<Vec<_>>();
+	if unknown.is_empty() {
+		return Ok(());
+	}
+	Err(AppError::Validation(format!("No user has the email {}", unknown.join(", "))))
+}
+
+/// Fails unless every one of `requested` is in `granted`, as a group that is
+/// not the owner's is left out of a grant.
+pub fn require_granted_groups(
+	requested: &[i64],
+	granted: &[i64],
+) -> Result<(), AppError> {
+	if requested.iter().all(|id| granted.contains(id)) {
+		return Ok(());
+	}
+	Err(AppError::Validation("Only your own groups can be shared with".to_string()))
+}
+
+fn group_name_error(
+	error: tokio_postgres::Error,
+	name: &str,
+) -> AppError {
+	if error.as_db_error().is_some_and(|db_error| {
+		db_error.code() == &SqlState::UNIQUE_VIOLATION &&
+			db_error.constraint() == Some("user_groups_user_id_name_key")
+	}) {
+		return AppError::Validation(format!("You already have a group named '{name}'"));
+	}
+
+	AppError::from(error)
+}
+
+impl TryFrom<Row> for UserGroup {
+	type Error = AppError;
+
+	fn try_from(row: Row) -> Result<Self, Self::Error> {
+		Ok(UserGroup {
+			id: row.try_get("id").context("Failed to read user group id")?,
+			user_id: row.try_get("user_id").context("Failed to read user group user_id")?,
+			name: row.try_get("name").context("Failed to read user group name")?,
+			members: row.try_get("members").context("Failed to read user group members")?,
+			created_at: row
+				.try_get("created_at")
+				.context("Failed to read user group created_at")?,
+		})
+	}
+}
+
+impl UserGroup {
+	pub async fn by_id(
+		ctx: &Context<'_>,
+		id: i64,
+	) -> Result<Self, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_USER_GROUP_BY_ID_QUERY).await?;
+		client
+			.query_opt(&statement, &[&id])
+			.await?
+			.map(Self::try_from)
+			.transpose()?
+			.ok_or_else(|| AppError::NotFound("Group not found".to_string()))
+	}
+
+	/// The groups `user_id` owns, by name.
+	pub async fn for_user(
+		ctx: &Context<'_>,
+		user_id: i64,
+	) -> Result<Vec<Self>, AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let statement = client.prepare_cached(SELECT_USER_GROUPS_FOR_USER_QUERY).await?;
+		client.query(&statement, &[&user_id]).await?.into_iter().map(Self::try_from).collect()
+	}
+
+	pub async fn create(
+		ctx: &Context<'_>,
+		user_id: i64,
+		name: &str,
+	) -> Result<Self, AppError> {
+		let name = group_name(name)?;
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		let id: i64 = client
+			.query_one(INSERT_USER_GROUP_QUERY, &[&user_id, &name])
+			.await
+			.map_err(|error| group_name_error(error, &name))?
+			.try_get("id")
+			.context("Failed to read user group id")?;
+		Self::by_id(ctx, id).await
+	}
+
+	pub async fn rename(
+		ctx: &Context<'_>,
+		id: i64,
+		name: &str,
+	) -> Result<Self, AppError> {
+		let name = group_name(name)?;
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		client
+			.execute(RENAME_USER_GROUP_QUERY, &[&id, &name])
+			.await
+			.map_err(|error| group_name_error(error, &name))?;
+		Self::by_id(ctx, id).await
+	}
+
+	/// Deletes a group, which stops sharing anything with its members through
+	/// it.
+	pub async fn delete(
+		ctx: &Context<'_>,
+		id: i64,
+	) -> Result<(), AppError> {
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		client.execute(DELETE_USER_GROUP_QUERY, &[&id]).await.context("Failed to delete group")?;
+		Ok(())
+	}
+
+	/// Adds the users with `emails` to the group. Adds none of them if any
+	/// email is not a user's.
+	pub async fn add_members(
+		ctx: &Context<'_>,
+		id: i64,
+		emails: &[String],
+	) -> Result<Self, AppError> {
+		let emails = member_emails(emails);
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		let found = transaction
+			.query(ADD_USER_GROUP_MEMBERS_QUERY, &[&id, &emails])
+			.await
+			.context("Failed to add group members")?
+			.into_iter()
+			.map(|row| row.try_get("email").context("Failed to read group member email"))
+			.collect::<Result<Vec<String>, _>>()?;
+		require_known_emails(&emails, &found)?;
+		transaction.commit().await?;
+		Self::by_id(ctx, id).await
+	}
+
+	pub async fn remove_members(
+		ctx: &Context<'_>,
+		id: i64,
+		emails: &[String],
+	) -> Result<Self, AppError> {
+		let emails = member_emails(emails);
+		let client = ContextWrapper::new(ctx)?.db_client().await?;
+		client
+			.execute(REMOVE_USER_GROUP_MEMBERS_QUERY, &[&id, &emails])
+			.await
+			.context("Failed to remove group members")?;
+		Self::by_id(ctx, id).await
+	}
+}
+
+#[Object]
+impl UserGroup {
+	async fn id(&self) -> ID {
+		ID(self.id.to_string())
+	}
+
+	async fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// The members' emails, in alphabetical order.
+	async fn members(&self) -> Vec<String> {
+		self.members.clone()
+	}
+
+	async fn created_at(&self) -> String {
+		self.created_at.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		member_emails,
+		require_granted_groups,
+		require_known_emails,
+	};
+
+	#[test]
+	fn member_emails_are_trimmed_and_deduplicated() {
+		let emails =
+			[" a@example.com", "", "b@example.com", "a@example.com ", "  "].map(str::to_string);
+		assert_eq!(member_emails(&emails), vec!["a@example.com", "b@example.com"]);
+	}
+
+	#[test]
+	fn unknown_emails_and_groups_are_rejected() -> anyhow::Result<()> {
+		let requested = ["a@example.com", "b@example.com"].map(str::to_string);
+		require_known_emails(&requested, &requested)?;
+		let error = require_known_emails(&requested, &["a@example.com".to_string()])
+			.err()
+			.ok_or_else(|| anyhow::anyhow!("unknown email was accepted"))?;
+		assert!(error.to_string().contains("b@example.com"));
+
+		require_granted_groups(&[1, 2], &[2, 1])?;
+		require_granted_groups(&[], &[])?;
+		assert!(require_granted_groups(&[1, 3], &[1]).is_err());
+		Ok(())
+	}
+}
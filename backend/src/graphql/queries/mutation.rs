@@ -1,13 +1,16 @@
 use {
 	crate::{
 		CasbinObject,
+		ClientInfo,
 		ContextWrapper,
 		GraphqlMutationCacheEffect,
 		constants::PASSWORD_RESET_RATE_LIMIT_SECONDS,
 		db::queries::{
 			ADMIN_UPDATE_USER_QUERY,
 			DELETE_PASSWORD_RESET_TOKENS_BY_USER_QUERY,
+			INSERT_OBJECT_DUPLICATE_DISMISSAL_QUERY,
 			INSERT_PASSWORD_RESET_TOKEN_QUERY,
+			INSERT_USER_DEFAULT_PRIVACY_DERIVATIVE_JOBS_QUERY,
 			INSERT_USER_QUERY,
 			RECENT_PASSWORD_RESET_TOKEN_EXISTS_QUERY,
 			SELECT_PASSWORD_RESET_TOKEN_QUERY,
@@ -17,28 +20,63 @@ use {
 			SELECT_USER_PASSWORD_HASH_BY_ID_QUERY,
 			SELECT_USER_WITH_PASSWORD_BY_EMAIL_QUERY,
 			UPDATE_USER_EMAIL_QUERY,
+			UPDATE_USER_LOCATION_PRECISION_QUERY,
+			UPDATE_USER_METADATA_PRIVACY_QUERY,
 			UPDATE_USER_PASSWORD_QUERY,
 			UPDATE_USER_PUBLICITY_QUERY,
 		},
 		email_worker::enqueue_password_reset_email,
 		errors::AppError,
 		graphql::objects::{
-			location::Location,
+			album::{
+				Album,
+				AlbumDetails,
+				distinct_ids,
+			},
+			location::{
+				Location,
+				LocationPrecision,
+			},
 			s3_object::{
+				LocationPrecisionOverride,
+				MetadataPrivacyOverride,
 				PublicityOverride,
 				S3Object,
 			},
+			session::Session,
+			share_link::{
+				CreatedShareLink,
+				ShareLink,
+				ShareLinkOptions,
+				ShareLinkTarget,
+				SharedItems,
+			},
+			tag::{
+				add_object_tags,
+				normalize_tags,
+				remove_object_tags,
+			},
+			trashed_object::TrashedObject,
 			upload_session::{
 				AbortedObjectUpload,
 				CreatedObjectUploadSession,
+				DeduplicatedObjectUpload,
 				PresignedObjectUploadPart,
 			},
 			user::{
 				PublicityDefault,
 				User,
 			},
+			user_group::UserGroup,
+		},
+		media::sanitize::{
+			MetadataPrivacy,
+			SANITIZED_CONTENT_TYPES,
+		},
+		object_lifecycle::{
+			ObjectMetadataUpdate,
+			ObjectUploadSessionCreate,
 		},
-		object_lifecycle::ObjectUploadSessionCreate,
 		storage::CompletedUploadPart,
 	},
 	anyhow::Context as AnyhowContext,
@@ -71,6 +109,7 @@ use {
 	},
 	std::sync::Arc,
 	time::Duration,
+	tokio_postgres::Transaction,
 };
 
 #[derive(InputObject)]
@@ -80,7 +119,83 @@ pub struct UpdateS3ObjectInput {
 	pub made_on: Option<String>,
 	pub location: Option<Location>,
 	pub publicity: PublicityOverride,
+	/// Null keeps the object's current metadata privacy.
+	pub metadata_privacy: Option<MetadataPrivacyOverride>,
+	/// Null keeps the object's current location precision.
+	pub location_precision: Option<LocationPrecisionOverride>,
+	pub allowed_users: Option<Vec<String>>,
+	/// The caller's groups to share with. Null keeps the object's current ones.
+	pub allowed_groups: Option<Vec<ID>>,
+	/// Null keeps the object's current tags.
+	pub tags: Option<Vec<String>>,
+	/// Null keeps the object's current title.
+	pub title: Option<String>,
+	/// Markdown. Null keeps the object's current description.
+	pub description: Option<String>,
+	/// Markdown. Null keeps the object's current note.
+	pub note: Option<String>,
+}
+
+impl UpdateS3ObjectInput {
+	fn into_update(
+		self,
+		id: i64,
+		edited_by: i64,
+	) -> Result<ObjectMetadataUpdate, AppError> {
+		Ok(ObjectMetadataUpdate {
+			id,
+			edited_by,
+			name: self.name,
+			made_on: self.made_on,
+			location: self.location,
+			publicity: self.publicity,
+			metadata_privacy: self.metadata_privacy,
+			location_precision: self.location_precision,
+			allowed_users: self.allowed_users.unwrap_or_default(),
+			allowed_groups: self.allowed_groups.map(parse_ids).transpose()?,
+			tags: self.tags,
+			title: self.title,
+			description: self.description,
+			note: self.note,
+		})
+	}
+}
+
+#[derive(InputObject)]
+pub struct CreateAlbumInput {
+	pub title: String,
+	pub description: Option<String>,
+	#[graphql(default_with = "PublicityOverride::Default")]
+	pub publicity: PublicityOverride,
+	pub allowed_users: Option<Vec<String>>,
+	/// The caller's groups to share with.
+	pub allowed_groups: Option<Vec<ID>>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateAlbumInput {
+	pub id: ID,
+	pub title: String,
+	pub description: Option<String>,
+	/// One of the album's objects, or null to show the first one as the cover.
+	pub cover_object_id: Option<ID>,
+	pub publicity: PublicityOverride,
 	pub allowed_users: Option<Vec<String>>,
+	/// The caller's groups to share with. Null keeps the album's current ones.
+	pub allowed_groups: Option<Vec<ID>>,
+}
+
+/// A link to one object or one album, whichever is given.
+#[derive(InputObject)]
+pub struct CreateShareLinkInput {
+	pub object_id: Option<ID>,
+	pub album_id: Option<ID>,
+	/// Null for a link that does not expire.
+	pub expires_at: Option<String>,
+	/// Null for a link anyone holding it can open.
+	pub password: Option<String>,
+	/// Null for no limit on how many times the link is opened.
+	pub max_views: Option<i32>,
 }
 
 #[derive(InputObject)]
@@ -92,6 +207,37 @@ pub struct CreateObjectUploadSessionInput {
 	pub location: Option<Location>,
 	pub publicity: PublicityOverride,
 	pub allowed_users: Option<Vec<String>>,
+	/// The caller's groups to share with.
+	pub allowed_groups: Option<Vec<ID>>,
+	pub tags: Option<Vec<String>>,
+	pub title: Option<String>,
+	/// Markdown.
+	pub description: Option<String>,
+	/// Markdown, private to the owner.
+	pub note: Option<String>,
+}
+
+impl CreateObjectUploadSessionInput {
+	fn into_upload(
+		self,
+		user_id: i64,
+	) -> Result<ObjectUploadSessionCreate, AppError> {
+		Ok(ObjectUploadSessionCreate {
+			name: self.name,
+			content_type: self.content_type,
+			file_size_bytes: self.file_size_bytes,
+			made_on: self.made_on,
+			location: self.location,
+			user_id,
+			publicity: self.publicity,
+			allowed_users: self.allowed_users.unwrap_or_default(),
+			allowed_groups: self.allowed_groups.map(parse_ids).transpose()?.unwrap_or_default(),
+			tags: self.tags.unwrap_or_default(),
+			title: self.title.unwrap_or_default(),
+			description: self.description.unwrap_or_default(),
+			note: self.note.unwrap_or_default(),
+		})
+	}
 }
 
 #[derive(InputObject)]
@@ -100,6 +246,89 @@ pub struct CompletedObjectUploadPartInput {
 	pub e_tag: String,
 }
 
+fn parse_ids(ids: Vec<ID>) -> Result<Vec<i64>, AppError> {
+	ids.into_iter()
+		.map(|id| id.parse::<i64>().context("Invalid ID format").map_err(AppError::from))
+		.collect()
+}
+
+/// Album `id`, once the caller is known to be allowed to `action` it.
+async fn album_with_permission(
+	ctx: &Context<'_>,
+	action: &str,
+	id: ID,
+) -> Result<Album, GraphQLError> {
+	let wrapper = ContextWrapper::new(ctx)?;
+	let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+	let album = Album::by_id(ctx, id).await.map_err(AppError::graphql)?;
+	wrapper
+		.require_permission(
+			action,
+			CasbinObject {
+				user_id: album.user_id,
+			},
+		)
+		.await?;
+	Ok(album)
+}
+
+/// User group `id`, once the caller is known to be allowed to `action` it.
+async fn user_group_with_permission(
+	ctx: &Context<'_>,
+	action: &str,
+	id: ID,
+) -> Result<UserGroup, GraphQLError> {
+	let wrapper = ContextWrapper::new(ctx)?;
+	let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+	let group = UserGroup::by_id(ctx, id).await.map_err(AppError::graphql)?;
+	wrapper
+		.require_permission(
+			action,
+			CasbinObject {
+				user_id: group.user_id,
+			},
+		)
+		.await?;
+	Ok(group)
+}
+
+/// The object `ids` and normalized `tags` of an `addTags` or `removeTags`,
+/// once the caller is known to be allowed to update every object.
+async fn tagged_objects_with_permission(
+	ctx: &Context<'_>,
+	ids: Vec<ID>,
+	tags: &[String],
+) -> Result<(Vec<i64>, Vec<String>), GraphQLError> {
+	let wrapper = ContextWrapper::new(ctx)?;
+	let ids = parse_ids(ids).map_err(AppError::graphql)?;
+	let tags = normalize_tags(tags).map_err(AppError::graphql)?;
+	let objects = S3Object::where_ids(ctx, &ids).await.map_err(AppError::graphql)?;
+	wrapper
+		.require_permission_on_each(
+			"update",
+			objects.iter().map(|obj| CasbinObject {
+				user_id: obj.user_id.unwrap_or(0),
+			}),
+		)
+		.await?;
+	Ok((ids, tags))
+}
+
+/// Fails unless the caller may delete every one of `objects`.
+async fn require_delete_permission(
+	wrapper: &ContextWrapper<'_>,
+	objects: &[S3Object],
+) -> Result<(), GraphQLError> {
+	wrapper
+		.require_permission_on_each(
+			"delete",
+			objects.iter().map(|obj| CasbinObject {
+				user_id: obj.user_id.unwrap_or(0),
+			}),
+		)
+		.await
+}
+
 fn validate_password(password: &str) -> Result<(), AppError> {
 	if password.len() < 8 {
 		return Err(AppError::Validation(
@@ -130,6 +359,40 @@ fn auth_cookie(
 	builder.build()
 }
 
+/// Queues `cookie` to be set on the response.
+fn issue_cookie(
+	ctx: &Context<'_>,
+	cookie: Cookie<'static>,
+) -> Result<(), GraphQLError> {
+	let cookies = ctx
+		.data::<Arc<parking_lot::Mutex<Vec<Cookie<'static>>>>>()
+		.map_err(|e| anyhow::anyhow!(e.message).context("Cookies not found in context"))
+		.map_err(AppError::graphql)?;
+	cookies.lock().push(cookie);
+	Ok(())
+}
+
+/// Starts a session for `user_id` on the client making the request, returning
+/// the cookie that signs it in.
+async fn start_session(
+	ctx: &Context<'_>,
+	transaction: &Transaction<'_>,
+	user_id: i64,
+) -> Result<Cookie<'static>, GraphQLError> {
+	let wrapper = ContextWrapper::new(ctx)?;
+	let client_info = ctx.data_opt::<ClientInfo>().cloned().unwrap_or_default();
+	let token =
+		Session::start(transaction, user_id, &client_info).await.map_err(AppError::graphql)?;
+	Ok(auth_cookie(token, None, wrapper.shared_state().config.cookie_secure()))
+}
+
+/// Clears the auth cookie of the client making the request.
+fn expire_auth_cookie(ctx: &Context<'_>) -> Result<(), GraphQLError> {
+	let wrapper = ContextWrapper::new(ctx)?;
+	let secure = wrapper.shared_state().config.cookie_secure();
+	issue_cookie(ctx, auth_cookie(String::new(), Some(Duration::seconds(0)), secure))
+}
+
 fn presigned_url_expires_at(config: &crate::Config) -> Result<Timestamp, GraphQLError> {
 	Timestamp::now()
 		.checked_add(std::time::Duration::from_secs(config.storage.presigned_url_ttl_seconds))
@@ -148,26 +411,41 @@ impl Mutation {
 	) -> Result<CreatedObjectUploadSession, GraphQLError> {
 		let wrapper = ContextWrapper::new(ctx)?;
 		let user_id = wrapper.user_id()?;
+		let upload = input.into_upload(user_id).map_err(AppError::graphql)?;
 		let mut client = wrapper.db_client().await?;
 
 		let session = wrapper
 			.object_lifecycle_service(&mut client)
-			.create_upload_session(ObjectUploadSessionCreate {
-				name: input.name,
-				content_type: input.content_type,
-				file_size_bytes: input.file_size_bytes,
-				made_on: input.made_on,
-				location: input.location,
-				user_id,
-				publicity: input.publicity,
-				allowed_users: input.allowed_users.unwrap_or_default(),
-			})
+			.create_upload_session(upload)
 			.await
 			.map_err(AppError::graphql)?;
 
 		Ok(session.into())
 	}
 
+	/// Like `createObjectUploadSession`, but first looks for an available object
+	/// of the caller's whose content has the same hex-encoded BLAKE3 `contentHash`,
+	/// and returns that instead of a session when there is one.
+	async fn create_deduplicated_object_upload_session(
+		&self,
+		ctx: &Context<'_>,
+		input: CreateObjectUploadSessionInput,
+		content_hash: String,
+	) -> Result<DeduplicatedObjectUpload, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		let upload = input.into_upload(user_id).map_err(AppError::graphql)?;
+		let mut client = wrapper.db_client().await?;
+
+		let upload = wrapper
+			.object_lifecycle_service(&mut client)
+			.create_deduplicated_upload_session(upload, &content_hash)
+			.await
+			.map_err(AppError::graphql)?;
+
+		Ok(upload.into())
+	}
+
 	async fn presign_object_upload_parts(
 		&self,
 		ctx: &Context<'_>,
@@ -245,6 +523,8 @@ impl Mutation {
 		Ok(AbortedObjectUpload::new(object_id))
 	}
 
+	/// Moves the objects `ids` to their owners' trash. They are deleted for
+	/// good when the trash is emptied or the retention window ends.
 	async fn delete_s3_objects(
 		&self,
 		ctx: &Context<'_>,
@@ -252,28 +532,129 @@ impl Mutation {
 	) -> Result<Vec<S3Object>, GraphQLError> {
 		let wrapper = ContextWrapper::new(ctx)?;
 		let mut client = wrapper.db_client().await?;
-		let ids: Vec<i64> = ids
-			.into_iter()
-			.map(|id| id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql))
-			.collect::<Result<Vec<i64>, _>>()?;
+		let ids = parse_ids(ids).map_err(AppError::graphql)?;
 
 		let objects = S3Object::where_ids(ctx, &ids).await.map_err(AppError::graphql)?;
+		require_delete_permission(&wrapper, &objects).await?;
+
+		let result = wrapper
+			.object_lifecycle_service(&mut client)
+			.trash_objects(&ids)
+			.await
+			.map_err(AppError::graphql)?;
+
+		Ok(result)
+	}
+
+	/// Takes the objects `ids` back out of the trash.
+	async fn restore_s3_objects(
+		&self,
+		ctx: &Context<'_>,
+		ids: Vec<ID>,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let mut client = wrapper.db_client().await?;
+		let ids = parse_ids(ids).map_err(AppError::graphql)?;
+
+		let objects =
+			TrashedObject::objects_where_ids(ctx, &ids).await.map_err(AppError::graphql)?;
+		require_delete_permission(&wrapper, &objects).await?;
+
+		let restored = wrapper
+			.object_lifecycle_service(&mut client)
+			.restore_objects(&objects)
+			.await
+			.map_err(AppError::graphql)?;
+		let restored_ids = restored.iter().map(|object| object.id).collect::<Vec<_>>();
+
+		S3Object::where_ids(ctx, &restored_ids).await.map_err(AppError::graphql)
+	}
+
+	/// Deletes everything in the caller's trash for good.
+	async fn empty_trash(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		let mut client = wrapper.db_client().await?;
+
+		wrapper
+			.object_lifecycle_service(&mut client)
+			.empty_trash(user_id)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// Puts `tags` on each of the objects `ids`, creating the tags their owners
+	/// do not have yet.
+	async fn add_tags(
+		&self,
+		ctx: &Context<'_>,
+		ids: Vec<ID>,
+		tags: Vec<String>,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let (ids, tags) = tagged_objects_with_permission(ctx, ids, &tags).await?;
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		add_object_tags(&transaction, &ids, &tags).await.map_err(AppError::graphql)?;
+		transaction.commit().await?;
+		S3Object::where_ids(ctx, &ids).await.map_err(AppError::graphql)
+	}
+
+	/// Takes `tags` off each of the objects `ids`.
+	async fn remove_tags(
+		&self,
+		ctx: &Context<'_>,
+		ids: Vec<ID>,
+		tags: Vec<String>,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let (ids, tags) = tagged_objects_with_permission(ctx, ids, &tags).await?;
+		let mut client = ContextWrapper::new(ctx)?.db_client().await?;
+		let transaction = client.transaction().await?;
+		remove_object_tags(&transaction, &ids, &tags).await.map_err(AppError::graphql)?;
+		transaction.commit().await?;
+		S3Object::where_ids(ctx, &ids).await.map_err(AppError::graphql)
+	}
+
+	/// Records that two of the caller's images are not duplicates, so
+	/// `possibleDuplicates` stops offering the pair.
+	async fn dismiss_duplicate_pair(
+		&self,
+		ctx: &Context<'_>,
+		first_id: ID,
+		second_id: ID,
+	) -> Result<bool, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let first_id =
+			first_id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		let second_id =
+			second_id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		if first_id == second_id {
+			return Err(AppError::Validation("A pair needs two different objects".to_string())
+				.extend_graphql());
+		}
+
+		// Dismissals are stored lowest id first, so either order names one pair.
+		let (first_id, second_id) = (first_id.min(second_id), first_id.max(second_id));
+		let objects =
+			S3Object::where_ids(ctx, &[first_id, second_id]).await.map_err(AppError::graphql)?;
+		if objects.len() != 2 {
+			return Err(AppError::NotFound("Object not found".to_string()).extend_graphql());
+		}
 		wrapper
 			.require_permission_on_each(
-				"delete",
+				"update",
 				objects.iter().map(|obj| CasbinObject {
 					user_id: obj.user_id.unwrap_or(0),
 				}),
 			)
 			.await?;
 
-		let result = wrapper
-			.object_lifecycle_service(&mut client)
-			.delete_objects(&ids)
-			.await
-			.map_err(AppError::graphql)?;
-
-		Ok(result)
+		let client = wrapper.db_client().await?;
+		let statement = client.prepare_cached(INSERT_OBJECT_DUPLICATE_DISMISSAL_QUERY).await?;
+		client.execute(&statement, &[&first_id, &second_id]).await?;
+		Ok(true)
 	}
 
 	/// GraphQL mutation to update an S3 object.
@@ -299,24 +680,304 @@ impl Mutation {
 			)
 			.await?;
 
-		let allowed_users = input.allowed_users.unwrap_or_default();
-
+		let update = input.into_update(id_int, wrapper.user_id()?).map_err(AppError::graphql)?;
 		let result = wrapper
 			.object_lifecycle_service(&mut client)
-			.update_object_metadata(
-				id_int,
-				input.name,
-				input.made_on,
-				input.location,
-				input.publicity,
-				allowed_users,
-			)
+			.update_object_metadata(update)
 			.await
 			.map_err(AppError::graphql)?;
 
 		Ok(result)
 	}
 
+	/// Undoes `revision` of the object `id`: the fields it changed get their old
+	/// values back. The undo is recorded as a revision of its own.
+	async fn revert_object(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+		revision: i32,
+	) -> Result<S3Object, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let mut client = wrapper.db_client().await?;
+		let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+
+		let object = S3Object::where_id(ctx, id).await.map_err(AppError::graphql)?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id: object.user_id.unwrap_or(0),
+				},
+			)
+			.await?;
+
+		wrapper
+			.object_lifecycle_service(&mut client)
+			.revert_object_metadata(id, revision, wrapper.user_id()?)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	async fn create_album(
+		&self,
+		ctx: &Context<'_>,
+		input: CreateAlbumInput,
+	) -> Result<Album, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		wrapper
+			.require_permission(
+				"create",
+				CasbinObject {
+					user_id,
+				},
+			)
+			.await?;
+		let details = AlbumDetails::new(
+			&input.title,
+			input.description,
+			input.publicity,
+			input.allowed_users,
+			input.allowed_groups.map(parse_ids).transpose().map_err(AppError::graphql)?,
+		)
+		.map_err(AppError::graphql)?;
+		Album::create(ctx, user_id, details).await.map_err(AppError::graphql)
+	}
+
+	async fn update_album(
+		&self,
+		ctx: &Context<'_>,
+		input: UpdateAlbumInput,
+	) -> Result<Album, GraphQLError> {
+		let album = album_with_permission(ctx, "update", input.id).await?;
+		let cover_object_id = input
+			.cover_object_id
+			.map(|id| id.parse::<i64>().context("Invalid ID format"))
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let details = AlbumDetails::new(
+			&input.title,
+			input.description,
+			input.publicity,
+			input.allowed_users,
+			input.allowed_groups.map(parse_ids).transpose().map_err(AppError::graphql)?,
+		)
+		.map_err(AppError::graphql)?;
+		Album::update(ctx, album.id, details, cover_object_id).await.map_err(AppError::graphql)
+	}
+
+	/// Deletes an album. Its objects are left as they are.
+	async fn delete_album(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+	) -> Result<bool, GraphQLError> {
+		let album = album_with_permission(ctx, "delete", id).await?;
+		Album::delete(ctx, album.id).await.map_err(AppError::graphql)?;
+		Ok(true)
+	}
+
+	/// Appends objects to the end of an album in the order given, skipping
+	/// those already in it. Anyone who can see the album can see them, so the
+	/// caller must be allowed to update each object as well as the album.
+	async fn add_objects_to_album(
+		&self,
+		ctx: &Context<'_>,
+		album_id: ID,
+		object_ids: Vec<ID>,
+	) -> Result<Album, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let album = album_with_permission(ctx, "update", album_id).await?;
+		let object_ids = distinct_ids(&parse_ids(object_ids).map_err(AppError::graphql)?);
+		let objects = S3Object::where_ids(ctx, &object_ids).await.map_err(AppError::graphql)?;
+		if objects.len() != object_ids.len() {
+			return Err(AppError::NotFound("Object not found".to_string()).extend_graphql());
+		}
+		wrapper
+			.require_permission_on_each(
+				"update",
+				objects.iter().map(|obj| CasbinObject {
+					user_id: obj.user_id.unwrap_or(0),
+				}),
+			)
+			.await?;
+
+		Album::add_objects(ctx, album.id, &object_ids).await.map_err(AppError::graphql)?;
+		Album::by_id(ctx, album.id).await.map_err(AppError::graphql)
+	}
+
+	/// Takes objects out of an album, clearing its cover if it was one of them.
+	/// The objects themselves are left as they are.
+	async fn remove_objects_from_album(
+		&self,
+		ctx: &Context<'_>,
+		album_id: ID,
+		object_ids: Vec<ID>,
+	) -> Result<Album, GraphQLError> {
+		let album = album_with_permission(ctx, "update", album_id).await?;
+		let object_ids = parse_ids(object_ids).map_err(AppError::graphql)?;
+		Album::remove_objects(ctx, album.id, &object_ids).await.map_err(AppError::graphql)?;
+		Album::by_id(ctx, album.id).await.map_err(AppError::graphql)
+	}
+
+	/// Puts an album's objects in the order of `objectIds`, which must list
+	/// each of them once.
+	async fn reorder_album(
+		&self,
+		ctx: &Context<'_>,
+		album_id: ID,
+		object_ids: Vec<ID>,
+	) -> Result<Album, GraphQLError> {
+		let album = album_with_permission(ctx, "update", album_id).await?;
+		let object_ids = parse_ids(object_ids).map_err(AppError::graphql)?;
+		Album::reorder(ctx, album.id, &object_ids).await.map_err(AppError::graphql)?;
+		Album::by_id(ctx, album.id).await.map_err(AppError::graphql)
+	}
+
+	/// Creates a link to an object or album, which its owner must be allowed
+	/// to update. The token in the result is the only time it is shown.
+	async fn create_share_link(
+		&self,
+		ctx: &Context<'_>,
+		input: CreateShareLinkInput,
+	) -> Result<CreatedShareLink, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let object_id = input
+			.object_id
+			.map(|id| id.parse::<i64>().context("Invalid ID format"))
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let album_id = input
+			.album_id
+			.map(|id| id.parse::<i64>().context("Invalid ID format"))
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let target = ShareLinkTarget::new(object_id, album_id).map_err(AppError::graphql)?;
+		let options = ShareLinkOptions::new(
+			input.expires_at,
+			input.password,
+			input.max_views,
+			Timestamp::now(),
+		)
+		.map_err(AppError::graphql)?;
+		let owner_id = match target {
+			ShareLinkTarget::Object(id) => S3Object::where_ids(ctx, &[id])
+				.await
+				.map_err(AppError::graphql)?
+				.first()
+				.and_then(|object| object.user_id)
+				.ok_or_else(|| {
+					AppError::NotFound("Object not found".to_string()).extend_graphql()
+				})?,
+			ShareLinkTarget::Album(id) =>
+				Album::by_id(ctx, id).await.map_err(AppError::graphql)?.user_id,
+		};
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id: owner_id,
+				},
+			)
+			.await?;
+		ShareLink::create(ctx, owner_id, target, options).await.map_err(AppError::graphql)
+	}
+
+	/// Stops a share link from opening.
+	async fn revoke_share_link(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+	) -> Result<ShareLink, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		let link = ShareLink::by_id(ctx, id).await.map_err(AppError::graphql)?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id: link.user_id,
+				},
+			)
+			.await?;
+		ShareLink::revoke(ctx, link.id).await.map_err(AppError::graphql)
+	}
+
+	/// What a share link shows, for anyone holding its token, signed in or
+	/// not. A mutation because opening a link counts a view. After five wrong
+	/// passwords in a row, a link takes no guesses for 15 minutes.
+	async fn open_share_link(
+		&self,
+		ctx: &Context<'_>,
+		token: String,
+		password: Option<String>,
+	) -> Result<SharedItems, GraphQLError> {
+		SharedItems::open(ctx, &token, password).await.map_err(AppError::graphql)
+	}
+
+	async fn create_user_group(
+		&self,
+		ctx: &Context<'_>,
+		name: String,
+	) -> Result<UserGroup, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		wrapper
+			.require_permission(
+				"create",
+				CasbinObject {
+					user_id,
+				},
+			)
+			.await?;
+		UserGroup::create(ctx, user_id, &name).await.map_err(AppError::graphql)
+	}
+
+	async fn rename_user_group(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+		name: String,
+	) -> Result<UserGroup, GraphQLError> {
+		let group = user_group_with_permission(ctx, "update", id).await?;
+		UserGroup::rename(ctx, group.id, &name).await.map_err(AppError::graphql)
+	}
+
+	/// Deletes a group. Whatever was shared with it is no longer shared with
+	/// its members, unless also shared with them some other way.
+	async fn delete_user_group(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+	) -> Result<bool, GraphQLError> {
+		let group = user_group_with_permission(ctx, "delete", id).await?;
+		UserGroup::delete(ctx, group.id).await.map_err(AppError::graphql)?;
+		Ok(true)
+	}
+
+	/// Adds the users with `emails` to a group. Fails, adding none of them,
+	/// if any email is not a user's.
+	async fn add_user_group_members(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+		emails: Vec<String>,
+	) -> Result<UserGroup, GraphQLError> {
+		let group = user_group_with_permission(ctx, "update", id).await?;
+		UserGroup::add_members(ctx, group.id, &emails).await.map_err(AppError::graphql)
+	}
+
+	async fn remove_user_group_members(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+		emails: Vec<String>,
+	) -> Result<UserGroup, GraphQLError> {
+		let group = user_group_with_permission(ctx, "update", id).await?;
+		UserGroup::remove_members(ctx, group.id, &emails).await.map_err(AppError::graphql)
+	}
+
 	async fn update_user_publicity(
 		&self,
 		ctx: &Context<'_>,
@@ -342,6 +1003,81 @@ impl Mutation {
 		User::try_from(row).map_err(AppError::graphql)
 	}
 
+	/// Sets what other people are served of the metadata embedded in the
+	/// caller's originals, and queues the objects that follow the setting so
+	/// their sanitized copies are rewritten.
+	async fn update_user_metadata_privacy(
+		&self,
+		ctx: &Context<'_>,
+		metadata_privacy: MetadataPrivacy,
+	) -> Result<User, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id,
+				},
+			)
+			.await?;
+		let mut client = wrapper.db_client().await?;
+
+		let transaction = client.transaction().await?;
+		let row = transaction
+			.query_one(UPDATE_USER_METADATA_PRIVACY_QUERY, &[&metadata_privacy, &user_id])
+			.await
+			.context("Failed to update user metadata privacy in database")?;
+		transaction
+			.execute(
+				INSERT_USER_DEFAULT_PRIVACY_DERIVATIVE_JOBS_QUERY,
+				&[&user_id, &SANITIZED_CONTENT_TYPES.as_slice()],
+			)
+			.await
+			.context("Failed to enqueue derivative jobs for metadata privacy")?;
+		transaction.commit().await?;
+
+		User::try_from(row).map_err(AppError::graphql)
+	}
+
+	/// Sets how precisely other people see where the caller's objects were
+	/// made, and queues the objects that follow the setting so their sanitized
+	/// copies are rewritten: only the exact location leaves the GPS position
+	/// in what other people are served.
+	async fn update_user_location_precision(
+		&self,
+		ctx: &Context<'_>,
+		location_precision: LocationPrecision,
+	) -> Result<User, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id,
+				},
+			)
+			.await?;
+		let mut client = wrapper.db_client().await?;
+
+		let transaction = client.transaction().await?;
+		let row = transaction
+			.query_one(UPDATE_USER_LOCATION_PRECISION_QUERY, &[&location_precision, &user_id])
+			.await
+			.context("Failed to update user location precision in database")?;
+		transaction
+			.execute(
+				INSERT_USER_DEFAULT_PRIVACY_DERIVATIVE_JOBS_QUERY,
+				&[&user_id, &SANITIZED_CONTENT_TYPES.as_slice()],
+			)
+			.await
+			.context("Failed to enqueue derivative jobs for location precision")?;
+		transaction.commit().await?;
+
+		User::try_from(row).map_err(AppError::graphql)
+	}
+
 	async fn register(
 		&self,
 		ctx: &Context<'_>,
@@ -398,7 +1134,7 @@ impl Mutation {
 		password: String,
 	) -> Result<User, GraphQLError> {
 		let wrapper = ContextWrapper::new(ctx)?;
-		let client = wrapper.db_client().await?;
+		let mut client = wrapper.db_client().await?;
 
 		let statement = client.prepare_cached(SELECT_USER_WITH_PASSWORD_BY_EMAIL_QUERY).await?;
 
@@ -411,6 +1147,7 @@ impl Mutation {
 		let password_hash_str: String = row
 			.try_get("password_hash")
 			.context("Failed to get password hash from database row")?;
+		let user_id: i64 = row.try_get("id").context("Failed to get user id from database row")?;
 		let user = User::try_from(row).map_err(AppError::graphql)?;
 
 		let parsed_hash = PasswordHash::new(&password_hash_str)
@@ -421,33 +1158,76 @@ impl Mutation {
 			.verify_password(password.as_bytes(), &parsed_hash)
 			.map_err(|_| AppError::Unauthorized.extend_graphql())?;
 
-		let state = wrapper.shared_state();
-
-		let cookie = auth_cookie(user.id.to_string(), None, state.config.cookie_secure());
-
-		let cookies = ctx
-			.data::<Arc<parking_lot::Mutex<Vec<Cookie<'static>>>>>()
-			.map_err(|e| anyhow::anyhow!(e.message).context("Cookies not found in context"))
-			.map_err(AppError::graphql)?;
-		cookies.lock().push(cookie);
+		let transaction = client.transaction().await?;
+		let cookie = start_session(ctx, &transaction, user_id).await?;
+		transaction.commit().await?;
+		issue_cookie(ctx, cookie)?;
 
 		Ok(user)
 	}
 
+	/// Ends the caller's session and clears its cookie.
 	async fn logout(
 		&self,
 		ctx: &Context<'_>,
 	) -> Result<bool, GraphQLError> {
 		let wrapper = ContextWrapper::new(ctx)?;
-		let state = wrapper.shared_state();
-		let cookie =
-			auth_cookie(String::new(), Some(Duration::seconds(0)), state.config.cookie_secure());
+		if let Some(caller_identity) = wrapper.caller_identity_opt() {
+			Session::revoke(ctx, caller_identity.user_id, caller_identity.session_id)
+				.await
+				.map_err(AppError::graphql)?;
+		}
+		expire_auth_cookie(ctx)?;
+
+		Ok(true)
+	}
+
+	/// Ends one of the caller's sessions, signing out whoever holds its
+	/// cookie. Ending the current session signs the caller out.
+	async fn revoke_session(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+	) -> Result<bool, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let caller_identity = wrapper.caller_identity()?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id: caller_identity.user_id,
+				},
+			)
+			.await?;
+		let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		if !Session::revoke(ctx, caller_identity.user_id, id).await.map_err(AppError::graphql)? {
+			return Err(AppError::NotFound("Session not found".to_string()).extend_graphql());
+		}
+		if id == caller_identity.session_id {
+			expire_auth_cookie(ctx)?;
+		}
+
+		Ok(true)
+	}
 
-		let cookies = ctx
-			.data::<Arc<parking_lot::Mutex<Vec<Cookie<'static>>>>>()
-			.map_err(|e| anyhow::anyhow!(e.message).context("Cookies not found in context"))
+	/// Ends every one of the caller's sessions but the current one.
+	async fn revoke_all_other_sessions(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<bool, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let caller_identity = wrapper.caller_identity()?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id: caller_identity.user_id,
+				},
+			)
+			.await?;
+		Session::revoke_others(ctx, caller_identity.user_id, caller_identity.session_id)
+			.await
 			.map_err(AppError::graphql)?;
-		cookies.lock().push(cookie);
 
 		Ok(true)
 	}
@@ -468,7 +1248,7 @@ impl Mutation {
 				},
 			)
 			.await?;
-		let client = wrapper.db_client().await?;
+		let mut client = wrapper.db_client().await?;
 
 		validate_password(&new_password).map_err(AppError::graphql)?;
 
@@ -493,10 +1273,17 @@ impl Mutation {
 			.map_err(AppError::graphql)?
 			.to_string();
 
-		client
+		let transaction = client.transaction().await?;
+		transaction
 			.execute(UPDATE_USER_PASSWORD_QUERY, &[&new_hash, &user_id])
 			.await
 			.context("Failed to update user password in database")?;
+		// Anyone who signed in with the old password is signed out. The caller
+		// gets a new session so they stay signed in here.
+		Session::revoke_all(&transaction, user_id).await.map_err(AppError::graphql)?;
+		let cookie = start_session(ctx, &transaction, user_id).await?;
+		transaction.commit().await?;
+		issue_cookie(ctx, cookie)?;
 
 		Ok(true)
 	}
@@ -608,13 +1395,14 @@ impl Mutation {
 		new_password: String,
 	) -> Result<bool, GraphQLError> {
 		let wrapper = ContextWrapper::new(ctx)?;
-		let client = wrapper.db_client().await?;
+		let mut client = wrapper.db_client().await?;
 
 		validate_password(&new_password).map_err(AppError::graphql)?;
 
 		let token_hash = blake3::hash(token.as_bytes()).to_string();
 
-		let row_opt = client
+		let transaction = client.transaction().await?;
+		let row_opt = transaction
 			.query_opt(SELECT_PASSWORD_RESET_TOKEN_QUERY, &[&token_hash])
 			.await
 			.context("Failed to query password reset token from database")?;
@@ -630,7 +1418,7 @@ impl Mutation {
 				.map_err(AppError::graphql)?
 				.to_string();
 
-			client
+			transaction
 				.execute(UPDATE_USER_PASSWORD_QUERY, &[&new_hash, &user_id])
 				.await
 				.context("Failed to update user password in database")?;
@@ -638,10 +1426,13 @@ impl Mutation {
 			// Invalidate all outstanding reset tokens for this user, not just the consumed one.
 			// Prevents an attacker who triggered a second reset request from holding a working
 			// bypass after the legitimate user changes their password.
-			client
+			transaction
 				.execute(DELETE_PASSWORD_RESET_TOKENS_BY_USER_QUERY, &[&user_id])
 				.await
 				.context("Failed to delete password reset tokens from database")?;
+			// Likewise, whoever is signed in with the old password is signed out.
+			Session::revoke_all(&transaction, user_id).await.map_err(AppError::graphql)?;
+			transaction.commit().await?;
 
 			Ok(true)
 		} else {
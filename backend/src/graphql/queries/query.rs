@@ -2,20 +2,87 @@ use {
 	crate::{
 		CasbinObject,
 		ContextWrapper,
+		constants::{
+			DUPLICATE_CANDIDATE_MAX_DISTANCE,
+			MAP_CLUSTER_MAX_ZOOM,
+			POSSIBLE_DUPLICATES_MAX_LIMIT,
+			S3_OBJECTS_CONNECTION_MAX_PAGE_SIZE,
+			TAG_SUGGESTIONS_MAX_LIMIT,
+		},
 		errors::AppError,
 		graphql::objects::{
+			album::Album,
 			config::PublicConfig,
+			duplicate_pair::DuplicatePair,
+			location::{
+				Bounds,
+				Location,
+			},
+			map_cluster::MapCluster,
+			object_revision::ObjectRevision,
 			s3_object::S3Object,
+			s3_object_connection::{
+				ObjectCursor,
+				ObjectPageFilter,
+				S3ObjectFilter,
+				S3ObjectOrder,
+			},
+			search::{
+				SearchConnection,
+				SearchCursor,
+				SearchTerms,
+			},
+			session::Session,
+			share_link::ShareLink,
+			tag::Tag,
+			timeline::{
+				TimelineBucket,
+				TimelineBucketCount,
+			},
+			trashed_object::TrashedObject,
 			user::User,
+			user_group::UserGroup,
 		},
 	},
+	anyhow::Context as AnyhowContext,
 	async_graphql::{
 		Context,
 		Error as GraphQLError,
+		ID,
 		Object,
+		connection::{
+			Connection,
+			CursorType,
+			Edge,
+		},
 	},
 };
 
+/// Perceptual hashes are 64 bits, so no two differ in more bits than this.
+const MAX_HASH_DISTANCE: i32 = 64;
+
+fn parse_max_distance(
+	max_distance: i32,
+	widest: i32,
+) -> Result<i64, AppError> {
+	if !(0 ..= widest).contains(&max_distance) {
+		return Err(AppError::Validation(format!("maxDistance must be between 0 and {widest}")));
+	}
+	Ok(i64::from(max_distance))
+}
+
+/// The number of objects a connection page of `first` holds.
+fn parse_page_size(first: i32) -> Result<usize, AppError> {
+	usize::try_from(first)
+		.ok()
+		.filter(|_| (1 ..= S3_OBJECTS_CONNECTION_MAX_PAGE_SIZE).contains(&first))
+		.ok_or_else(|| {
+			AppError::Validation(format!(
+				"first must be between 1 and {S3_OBJECTS_CONNECTION_MAX_PAGE_SIZE}"
+			))
+		})
+}
+
 pub struct Query;
 
 #[Object]
@@ -77,13 +144,21 @@ impl Query {
 		Ok(object)
 	}
 
+	/// The object named `name` that belongs to `ownerId`, or to the caller when
+	/// `ownerId` is null. Names are only unique among one owner's objects.
 	async fn s3_object_by_name(
 		&self,
 		ctx: &Context<'_>,
 		name: String,
+		owner_id: Option<ID>,
 	) -> Result<S3Object, GraphQLError> {
 		let wrapper = ContextWrapper::new(ctx)?;
-		let object = S3Object::where_name(ctx, name).await.map_err(AppError::graphql)?;
+		let owner_id = match owner_id {
+			Some(id) =>
+				id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?,
+			None => wrapper.user_id()?,
+		};
+		let object = S3Object::where_name(ctx, owner_id, name).await.map_err(AppError::graphql)?;
 		wrapper
 			.require_permission(
 				"read",
@@ -95,6 +170,27 @@ impl Query {
 		Ok(object)
 	}
 
+	/// The metadata edits of the object `id`, newest first. Only those who may
+	/// edit the object see its history.
+	async fn object_history(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+	) -> Result<Vec<ObjectRevision>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		let object = S3Object::where_id(ctx, id).await.map_err(AppError::graphql)?;
+		wrapper
+			.require_permission(
+				"update",
+				CasbinObject {
+					user_id: object.user_id.unwrap_or(0),
+				},
+			)
+			.await?;
+		ObjectRevision::for_object(ctx, id).await.map_err(AppError::graphql)
+	}
+
 	async fn s3_objects(
 		&self,
 		ctx: &Context<'_>,
@@ -102,19 +198,330 @@ impl Query {
 		let wrapper = ContextWrapper::new(ctx)?;
 		let user_id_opt = wrapper.user_id_opt();
 
-		if wrapper.caller_identity_opt().is_some() &&
-			wrapper
-				.has_permission(
-					"read_all_s3_objects",
-					CasbinObject {
-						user_id: 0,
-					},
-				)
-				.await?
-		{
+		if wrapper.can_read_all_objects().await? {
 			return S3Object::all(ctx).await.map_err(AppError::graphql);
 		}
 
 		S3Object::visible_to_user(ctx, user_id_opt).await.map_err(AppError::graphql)
 	}
+
+	/// A page of the objects the caller can see, narrowed by `filter`. Pass a
+	/// page's `pageInfo.endCursor` as `after` to get the next one, with the same
+	/// `orderBy`: a cursor holds the position in the ordering it came from.
+	/// Only the objects on the page get presigned URLs, unlike `s3Objects`.
+	async fn s3_objects_connection(
+		&self,
+		ctx: &Context<'_>,
+		#[graphql(default = 50)] first: i32,
+		after: Option<String>,
+		#[graphql(default)] filter: S3ObjectFilter,
+		#[graphql(default)] order_by: S3ObjectOrder,
+	) -> Result<Connection<ObjectCursor, S3Object>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let page_size = parse_page_size(first).map_err(AppError::graphql)?;
+		let after = after
+			.as_deref()
+			.map(ObjectCursor::decode_cursor)
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let filter = ObjectPageFilter::try_from(filter).map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+
+		// One more than asked for tells whether another page follows.
+		let mut objects = S3Object::page(
+			ctx,
+			wrapper.user_id_opt(),
+			read_all,
+			&filter,
+			order_by,
+			after.as_ref(),
+			i64::from(first) + 1,
+		)
+		.await
+		.map_err(AppError::graphql)?;
+		let has_next_page = objects.len() > page_size;
+		objects.truncate(page_size);
+		let mut connection = Connection::new(after.is_some(), has_next_page);
+		connection
+			.edges
+			.extend(objects.into_iter().map(|object| Edge::new(object.cursor(), object)));
+		Ok(connection)
+	}
+
+	/// The objects the caller can see whose names, titles, descriptions,
+	/// content types or owners' emails match `query`, or whose notes do for
+	/// the objects the caller may read the notes of, best match first. Words in
+	/// names are split on separators, so `beach` finds `IMG_2023-05_beach.jpg`,
+	/// and each word of the query matches the start of a word. Pages like
+	/// `s3ObjectsConnection`.
+	async fn search_s3_objects(
+		&self,
+		ctx: &Context<'_>,
+		query: String,
+		#[graphql(default = 50)] first: i32,
+		after: Option<String>,
+	) -> Result<SearchConnection, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let page_size = parse_page_size(first).map_err(AppError::graphql)?;
+		let terms = SearchTerms::parse(&query).map_err(AppError::graphql)?;
+		let after = after
+			.as_deref()
+			.map(SearchCursor::decode_cursor)
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+
+		// One more than asked for tells whether another page follows.
+		let mut results = S3Object::search(
+			ctx,
+			wrapper.user_id_opt(),
+			read_all,
+			&terms,
+			after.as_ref(),
+			i64::from(first) + 1,
+		)
+		.await
+		.map_err(AppError::graphql)?;
+		let has_next_page = results.len() > page_size;
+		results.truncate(page_size);
+		let mut connection = SearchConnection::new(after.is_some(), has_next_page);
+		connection
+			.edges
+			.extend(results.into_iter().map(|(object, cursor)| Edge::new(cursor, object)));
+		Ok(connection)
+	}
+
+	/// The objects the caller can see located in the box from `south`, `west`
+	/// to `north`, `east`, such as a map viewport, and matching `filter`. A box
+	/// with `west` greater than `east` crosses the antimeridian.
+	async fn s3_objects_in_bounds(
+		&self,
+		ctx: &Context<'_>,
+		south: f64,
+		west: f64,
+		north: f64,
+		east: f64,
+		#[graphql(default)] filter: S3ObjectFilter,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let bounds = Bounds::new(south, west, north, east).map_err(AppError::graphql)?;
+		let filter = ObjectPageFilter::try_from(filter).map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+		S3Object::in_bounds(ctx, wrapper.user_id_opt(), read_all, &bounds, &filter)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// The objects the caller can see in `bounds` and matching `filter`,
+	/// grouped into clusters sized for a map at `zoom`.
+	async fn map_clusters(
+		&self,
+		ctx: &Context<'_>,
+		bounds: Bounds,
+		zoom: i32,
+		#[graphql(default)] filter: S3ObjectFilter,
+	) -> Result<Vec<MapCluster>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		if !(0 ..= MAP_CLUSTER_MAX_ZOOM).contains(&zoom) {
+			return Err(AppError::Validation(format!(
+				"zoom must be between 0 and {MAP_CLUSTER_MAX_ZOOM}"
+			))
+			.extend_graphql());
+		}
+		let bounds = bounds.validated().map_err(AppError::graphql)?;
+		let filter = ObjectPageFilter::try_from(filter).map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+		MapCluster::in_bounds(ctx, wrapper.user_id_opt(), read_all, &bounds, zoom, &filter)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// How many of the objects the caller can see and matching `filter` were
+	/// made in each `bucket` of time, earliest first. Objects without a capture
+	/// time and buckets without objects are left out.
+	async fn timeline_histogram(
+		&self,
+		ctx: &Context<'_>,
+		#[graphql(default)] bucket: TimelineBucket,
+		#[graphql(default)] filter: S3ObjectFilter,
+	) -> Result<Vec<TimelineBucketCount>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let filter = ObjectPageFilter::try_from(filter).map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+		TimelineBucketCount::histogram(ctx, wrapper.user_id_opt(), read_all, bucket, &filter)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// The objects the caller can see within `radiusMeters` of `location`,
+	/// nearest first.
+	async fn s3_objects_near(
+		&self,
+		ctx: &Context<'_>,
+		location: Location,
+		radius_meters: f64,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		if !(radius_meters.is_finite() && radius_meters > 0.0) {
+			return Err(AppError::Validation(format!(
+				"{radius_meters} is not a valid radius; it must be a positive number of meters."
+			))
+			.extend_graphql());
+		}
+		let location = location.validated().map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+		S3Object::near(ctx, wrapper.user_id_opt(), read_all, &location, radius_meters)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// The caller's tags starting with `prefix`, most used first, for
+	/// autocomplete. Tags on none of the caller's objects are left out.
+	async fn tags(
+		&self,
+		ctx: &Context<'_>,
+		#[graphql(default)] prefix: String,
+		#[graphql(default = 10)] limit: i32,
+	) -> Result<Vec<Tag>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		if !(1 ..= TAG_SUGGESTIONS_MAX_LIMIT).contains(&limit) {
+			return Err(AppError::Validation(format!(
+				"limit must be between 1 and {TAG_SUGGESTIONS_MAX_LIMIT}"
+			))
+			.extend_graphql());
+		}
+		Tag::suggestions(ctx, user_id, &prefix, i64::from(limit)).await.map_err(AppError::graphql)
+	}
+
+	/// An album the caller can see.
+	async fn album(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+	) -> Result<Album, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+		Album::visible_by_id(ctx, wrapper.user_id_opt(), read_all, id)
+			.await
+			.map_err(AppError::graphql)?
+			.ok_or_else(|| AppError::NotFound("Album not found".to_string()).extend_graphql())
+	}
+
+	/// The albums the caller can see, only those of `ownerId` when given,
+	/// newest first.
+	async fn albums(
+		&self,
+		ctx: &Context<'_>,
+		owner_id: Option<ID>,
+	) -> Result<Vec<Album>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let owner_id = owner_id
+			.map(|id| id.parse::<i64>().context("Invalid ID format"))
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let read_all = wrapper.can_read_all_objects().await?;
+		Album::visible(ctx, wrapper.user_id_opt(), read_all, owner_id)
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// The owner's other images that look like this one (resized,
+	/// recompressed or lightly edited copies), closest first. `maxDistance` is
+	/// how many of the 64 perceptual hash bits may differ.
+	async fn similar_objects(
+		&self,
+		ctx: &Context<'_>,
+		id: ID,
+		#[graphql(default = 10)] max_distance: i32,
+	) -> Result<Vec<S3Object>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let id = id.parse::<i64>().context("Invalid ID format").map_err(AppError::graphql)?;
+		let max_distance =
+			parse_max_distance(max_distance, MAX_HASH_DISTANCE).map_err(AppError::graphql)?;
+		let object = S3Object::where_id(ctx, id).await.map_err(AppError::graphql)?;
+		wrapper
+			.require_permission(
+				"read",
+				CasbinObject {
+					user_id: object.user_id.unwrap_or(0),
+				},
+			)
+			.await?;
+		S3Object::similar_to(ctx, id, max_distance).await.map_err(AppError::graphql)
+	}
+
+	/// Pairs of the caller's images that may be duplicates of each other and
+	/// have not been dismissed, closest first. Pairs are recorded as images are
+	/// hashed, up to `DUPLICATE_CANDIDATE_MAX_DISTANCE` bits apart, so
+	/// `maxDistance` cannot exceed that.
+	async fn possible_duplicates(
+		&self,
+		ctx: &Context<'_>,
+		#[graphql(default = 6)] max_distance: i32,
+		#[graphql(default = 50)] limit: i32,
+	) -> Result<Vec<DuplicatePair>, GraphQLError> {
+		let wrapper = ContextWrapper::new(ctx)?;
+		let user_id = wrapper.user_id()?;
+		let max_distance = parse_max_distance(max_distance, DUPLICATE_CANDIDATE_MAX_DISTANCE)
+			.map_err(AppError::graphql)?;
+		if !(1 ..= POSSIBLE_DUPLICATES_MAX_LIMIT).contains(&limit) {
+			return Err(AppError::Validation(format!(
+				"limit must be between 1 and {POSSIBLE_DUPLICATES_MAX_LIMIT}"
+			))
+			.extend_graphql());
+		}
+		DuplicatePair::possible_for_user(ctx, user_id, max_distance, i64::from(limit))
+			.await
+			.map_err(AppError::graphql)
+	}
+
+	/// The caller's deleted objects that can still be restored, most recently
+	/// deleted first.
+	async fn trashed_objects(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Vec<TrashedObject>, GraphQLError> {
+		let user_id = ContextWrapper::new(ctx)?.user_id()?;
+		TrashedObject::for_user(ctx, user_id).await.map_err(AppError::graphql)
+	}
+
+	/// The caller's share links, only those of `objectId` or `albumId` when
+	/// given, newest first.
+	async fn share_links(
+		&self,
+		ctx: &Context<'_>,
+		object_id: Option<ID>,
+		album_id: Option<ID>,
+	) -> Result<Vec<ShareLink>, GraphQLError> {
+		let user_id = ContextWrapper::new(ctx)?.user_id()?;
+		let object_id = object_id
+			.map(|id| id.parse::<i64>().context("Invalid ID format"))
+			.transpose()
+			.map_err(AppError::graphql)?;
+		let album_id = album_id
+			.map(|id| id.parse::<i64>().context("Invalid ID format"))
+			.transpose()
+			.map_err(AppError::graphql)?;
+		ShareLink::for_user(ctx, user_id, object_id, album_id).await.map_err(AppError::graphql)
+	}
+
+	/// The caller's sessions that have not timed out, most recently used first.
+	async fn my_sessions(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Vec<Session>, GraphQLError> {
+		let user_id = ContextWrapper::new(ctx)?.user_id()?;
+		Session::for_user(ctx, user_id).await.map_err(AppError::graphql)
+	}
+
+	/// The caller's groups, by name.
+	async fn user_groups(
+		&self,
+		ctx: &Context<'_>,
+	) -> Result<Vec<UserGroup>, GraphQLError> {
+		let user_id = ContextWrapper::new(ctx)?.user_id()?;
+		UserGroup::for_user(ctx, user_id).await.map_err(AppError::graphql)
+	}
 }
@@ -27,6 +27,7 @@ use {
 	moka::future::Cache,
 	std::{
 		fmt,
+		net::IpAddr,
 		sync::{
 			Arc,
 			atomic::{
@@ -42,21 +43,28 @@ use {
 pub mod app;
 pub mod constants;
 pub mod db;
+pub mod derivative_worker;
 pub mod email;
 pub mod email_worker;
 pub mod errors;
 pub mod graphql;
+pub mod media;
 pub mod object_lifecycle;
 pub mod outbox;
+pub mod perceptual_hash_worker;
+pub mod session_worker;
 pub mod storage;
 pub mod worker;
 
 use {
+	derivative_worker::ObjectDerivativeConfig,
 	email_worker::EmailOutboxConfig,
 	object_lifecycle::{
 		ObjectLifecycleConfig,
 		ObjectLifecycleService,
 	},
+	outbox::ensure_positive,
+	perceptual_hash_worker::PerceptualHashConfig,
 	serde::Deserialize,
 	storage::{
 		StorageClient,
@@ -98,11 +106,25 @@ pub struct AuthConfig {
 	pub enable_registration: bool,
 	#[serde(default)]
 	pub cookie_secure: Option<bool>,
+	/// How long a session may go unused before it stops signing anyone in.
+	#[serde(default = "AuthConfig::default_session_idle_timeout_seconds")]
+	pub session_idle_timeout_seconds: i64,
+	/// How long a session lasts from sign-in, however much it is used.
+	#[serde(default = "AuthConfig::default_session_absolute_timeout_seconds")]
+	pub session_absolute_timeout_seconds: i64,
 }
 
 impl AuthConfig {
 	const MIN_COOKIE_SECRET_BYTES: usize = 64;
 
+	pub const fn default_session_idle_timeout_seconds() -> i64 {
+		14 * 24 * 60 * 60
+	}
+
+	pub const fn default_session_absolute_timeout_seconds() -> i64 {
+		90 * 24 * 60 * 60
+	}
+
 	pub fn validate(&self) -> anyhow::Result<()> {
 		if self.cookie_secret.len() < Self::MIN_COOKIE_SECRET_BYTES {
 			anyhow::bail!(
@@ -110,6 +132,8 @@ impl AuthConfig {
 				Self::MIN_COOKIE_SECRET_BYTES
 			);
 		}
+		ensure_positive!(self, session_idle_timeout_seconds);
+		ensure_positive!(self, session_absolute_timeout_seconds);
 		Ok(())
 	}
 }
@@ -123,6 +147,8 @@ impl fmt::Debug for AuthConfig {
 			.field("cookie_secret", &"<redacted>")
 			.field("enable_registration", &self.enable_registration)
 			.field("cookie_secure", &self.cookie_secure)
+			.field("session_idle_timeout_seconds", &self.session_idle_timeout_seconds)
+			.field("session_absolute_timeout_seconds", &self.session_absolute_timeout_seconds)
 			.finish()
 	}
 }
@@ -150,6 +176,10 @@ pub struct Config {
 	pub object_lifecycle: ObjectLifecycleConfig,
 	#[serde(default)]
 	pub email_outbox: EmailOutboxConfig,
+	#[serde(default)]
+	pub object_derivatives: ObjectDerivativeConfig,
+	#[serde(default)]
+	pub perceptual_hashes: PerceptualHashConfig,
 }
 
 impl Config {
@@ -197,6 +227,8 @@ impl Config {
 		self.storage.validate()?;
 		self.object_lifecycle.validate()?;
 		self.email_outbox.validate()?;
+		self.object_derivatives.validate()?;
+		self.perceptual_hashes.validate()?;
 		Ok(self)
 	}
 }
@@ -216,6 +248,8 @@ impl fmt::Debug for Config {
 			.field("storage", &self.storage)
 			.field("object_lifecycle", &self.object_lifecycle)
 			.field("email_outbox", &self.email_outbox)
+			.field("object_derivatives", &self.object_derivatives)
+			.field("perceptual_hashes", &self.perceptual_hashes)
 			.finish()
 	}
 }
@@ -225,9 +259,19 @@ refinery::embed_migrations!("migrations");
 #[derive(Clone, Debug)]
 pub struct CallerIdentity {
 	pub user_id: i64,
+	/// The session whose cookie signed the request in.
+	pub session_id: i64,
 	pub casbin_user: CasbinUser,
 }
 
+/// What a request says about the browser or device making it, recorded on the
+/// sessions it starts so they can be told apart.
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+	pub user_agent: Option<String>,
+	pub ip_address: Option<IpAddr>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct GraphqlResponseCacheKey([u8; 32]);
 
@@ -425,6 +469,21 @@ impl<'a> ContextWrapper<'a> {
 			.map_err(AppError::graphql)
 	}
 
+	/// Whether the caller may see every object regardless of its publicity.
+	/// Signed-out callers never can.
+	pub async fn can_read_all_objects(&self) -> Result<bool, GraphQLError> {
+		if self.caller_identity.is_none() {
+			return Ok(false);
+		}
+		self.has_permission(
+			"read_all_s3_objects",
+			CasbinObject {
+				user_id: 0,
+			},
+		)
+		.await
+	}
+
 	pub async fn require_permission(
 		&self,
 		action: &str,
@@ -498,11 +557,13 @@ mod tests {
 			FrontendConfig,
 			ServerConfig,
 			SmtpConfig,
+			derivative_worker::ObjectDerivativeConfig,
 			email_worker::EmailOutboxConfig,
 			errors::AppError,
 			object_lifecycle::ObjectLifecycleConfig,
 			parse_latitude,
 			parse_longitude,
+			perceptual_hash_worker::PerceptualHashConfig,
 			storage::StorageConfig,
 		},
 		deadpool_postgres::Config as PostgresConfig,
@@ -602,6 +663,9 @@ mod tests {
 				cookie_secret: "debug-cookie-secret".to_string(),
 				enable_registration: true,
 				cookie_secure: None,
+				session_idle_timeout_seconds: AuthConfig::default_session_idle_timeout_seconds(),
+				session_absolute_timeout_seconds:
+					AuthConfig::default_session_absolute_timeout_seconds(),
 			},
 			frontend: FrontendConfig {
 				url: "https://memory-map.example.test".to_string(),
@@ -621,6 +685,8 @@ mod tests {
 			},
 			object_lifecycle: ObjectLifecycleConfig::default(),
 			email_outbox: EmailOutboxConfig::default(),
+			object_derivatives: ObjectDerivativeConfig::default(),
+			perceptual_hashes: PerceptualHashConfig::default(),
 		};
 
 		let debug = format!("{config:?}");
@@ -642,6 +708,9 @@ mod tests {
 			cookie_secret: "a".repeat(64),
 			enable_registration: true,
 			cookie_secure: None,
+			session_idle_timeout_seconds: AuthConfig::default_session_idle_timeout_seconds(),
+			session_absolute_timeout_seconds: AuthConfig::default_session_absolute_timeout_seconds(
+			),
 		};
 
 		assert!(config.validate().is_ok());
@@ -653,6 +722,9 @@ mod tests {
 			cookie_secret: "a".repeat(63),
 			enable_registration: true,
 			cookie_secure: None,
+			session_idle_timeout_seconds: AuthConfig::default_session_idle_timeout_seconds(),
+			session_absolute_timeout_seconds: AuthConfig::default_session_absolute_timeout_seconds(
+			),
 		};
 
 		let error = config.validate().err();
@@ -661,6 +733,23 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn auth_config_validate_rejects_non_positive_session_timeouts() {
+		let mut config = AuthConfig {
+			cookie_secret: "a".repeat(64),
+			enable_registration: true,
+			cookie_secure: None,
+			session_idle_timeout_seconds: 0,
+			session_absolute_timeout_seconds: AuthConfig::default_session_absolute_timeout_seconds(
+			),
+		};
+		assert!(config.validate().is_err());
+
+		config.session_idle_timeout_seconds = AuthConfig::default_session_idle_timeout_seconds();
+		config.session_absolute_timeout_seconds = -1;
+		assert!(config.validate().is_err());
+	}
+
 	#[test]
 	fn config_deserialization_defaults_missing_object_lifecycle_section() -> anyhow::Result<()> {
 		let config: Config = serde_json::from_value(serde_json::json!({
@@ -709,6 +798,15 @@ mod tests {
 			config.email_outbox.worker_interval_seconds,
 			EmailOutboxConfig::default().worker_interval_seconds
 		);
+		assert_eq!(
+			config.object_derivatives.max_source_bytes,
+			ObjectDerivativeConfig::default().max_source_bytes
+		);
+		assert_eq!(config.perceptual_hashes.batch_size, PerceptualHashConfig::default().batch_size);
+		assert_eq!(
+			config.auth.session_idle_timeout_seconds,
+			AuthConfig::default_session_idle_timeout_seconds()
+		);
 		Ok(())
 	}
 
@@ -749,6 +847,9 @@ mod tests {
 				cookie_secret: "a".repeat(64),
 				enable_registration: true,
 				cookie_secure: None,
+				session_idle_timeout_seconds: AuthConfig::default_session_idle_timeout_seconds(),
+				session_absolute_timeout_seconds:
+					AuthConfig::default_session_absolute_timeout_seconds(),
 			},
 			frontend: FrontendConfig {
 				url: frontend_url.to_string(),
@@ -768,6 +869,8 @@ mod tests {
 			},
 			object_lifecycle: ObjectLifecycleConfig::default(),
 			email_outbox: EmailOutboxConfig::default(),
+			object_derivatives: ObjectDerivativeConfig::default(),
+			perceptual_hashes: PerceptualHashConfig::default(),
 		}
 	}
 }
@@ -6,9 +6,15 @@ use {
 			build_app,
 			build_shared_state,
 		},
+		derivative_worker::{
+			ContentHashBackfill,
+			ObjectDerivativeWorker,
+		},
 		email_worker::EmailWorker,
 		migrations,
 		object_lifecycle::ObjectLifecycleWorker,
+		perceptual_hash_worker::PerceptualHashWorker,
+		session_worker::SessionWorker,
 		storage::StorageClient,
 		worker,
 	},
@@ -19,6 +25,7 @@ use {
 	deadpool_postgres::Runtime,
 	dotenvy::dotenv,
 	std::{
+		net::SocketAddr,
 		ops::DerefMut,
 		sync::Arc,
 	},
@@ -37,7 +44,14 @@ async fn main() -> anyhow::Result<()> {
 
 	// Read and parse dotenv config
 	dotenv().ok();
-	let cfg = Config::load().context("Failed to load configuration")?;
+	let mut cfg = Config::load().context("Failed to load configuration")?;
+
+	// HEIC photos are only accepted when they can be given a rendition
+	// browsers show.
+	cfg.object_lifecycle.heic_uploads = cfg.object_derivatives.decodes_heic().await;
+	if !cfg.object_lifecycle.heic_uploads {
+		tracing::warn!("HEIC uploads are disabled: FFMPEG_PATH does not decode them");
+	}
 
 	// Connect to DB
 	let pool = cfg
@@ -73,6 +87,22 @@ async fn main() -> anyhow::Result<()> {
 		cfg.object_lifecycle.clone(),
 	));
 	let _email_worker = worker::spawn(EmailWorker::new(pool.clone(), cfg.clone()));
+	let _object_derivative_worker = worker::spawn(ObjectDerivativeWorker::new(
+		pool.clone(),
+		storage.clone(),
+		cfg.object_derivatives.clone(),
+	));
+	let _content_hash_backfill = worker::spawn(ContentHashBackfill::new(
+		pool.clone(),
+		storage.clone(),
+		cfg.object_derivatives.clone(),
+	));
+	let _perceptual_hash_worker = worker::spawn(PerceptualHashWorker::new(
+		pool.clone(),
+		storage.clone(),
+		cfg.perceptual_hashes.clone(),
+	));
+	let _session_worker = worker::spawn(SessionWorker::new(pool.clone(), cfg.auth.clone()));
 
 	// Initialise Casbin Enforcer
 	let enforcer = Enforcer::new("authz_model.conf", "authz_policy.csv").await?;
@@ -84,9 +114,14 @@ async fn main() -> anyhow::Result<()> {
 
 	println!("GraphiQL IDE: http://{bind_addr}");
 
-	axum::serve(TcpListener::bind(bind_addr).await.context("Failed to bind to address")?, app)
-		.await
-		.context("Failed to start server")?;
+	// Serving with the peer address lets sessions record where they were
+	// signed in from.
+	axum::serve(
+		TcpListener::bind(bind_addr).await.context("Failed to bind to address")?,
+		app.into_make_service_with_connect_info::<SocketAddr>(),
+	)
+	.await
+	.context("Failed to start server")?;
 
 	Ok(())
 }
@@ -0,0 +1,168 @@
+//! Inspection of stored media objects.
+//!
+//! The lifecycle service calls into this module after an upload is finalized to
+//! read embedded capture metadata. Objects are read with ranged GETs so a large
+//! video never has to be downloaded in full: the head of the object covers
+//! image metadata, and for ISO-BMFF files the movie box is located by walking
+//! top-level box headers and fetched on its own.
+//!
+//! [`thumbnail`] renders the sized image renditions the derivative worker
+//! stores alongside the original, and [`video`] probes video containers for
+//! their duration, dimensions and codecs; [`h264`] decodes one keyframe of an
+//! H.264 video for its poster. Ogg files keep their duration at the end, so
+//! their tail is read as well. [`audio`] decodes audio uploads in full
+//! for their tags and waveform peaks. [`sniff`] checks a completed upload's
+//! declared content type against its magic bytes, and [`perceptual`] hashes
+//! images so near-duplicates can be found. [`sanitize`] removes location and
+//! other metadata from the copies of originals served to other people.
+//! [`raw`] finds the JPEG previews embedded in camera RAW files, from which
+//! photos browsers cannot show get a viewable rendition.
+
+pub mod audio;
+pub mod h264;
+mod isobmff;
+mod matroska;
+mod metadata;
+mod ogg;
+pub mod perceptual;
+pub mod raw;
+pub mod sanitize;
+pub mod sniff;
+pub mod thumbnail;
+pub mod video;
+
+pub use metadata::{
+	ExtractedMetadata,
+	extract_metadata,
+};
+use {
+	crate::storage::StorageClient,
+	anyhow::Context,
+	async_graphql::SimpleObject,
+};
+
+/// How much of the end of an Ogg file is read to find its last pages.
+const OGG_TAIL_BYTES: u64 = 64 * 1024;
+
+/// Stream properties probed from a media container. Fields the container does
+/// not record are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MediaInfo {
+	pub duration_seconds: Option<f64>,
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	/// Short codec names such as `h264`, `vp9` or `opus`.
+	pub video_codec: Option<String>,
+	pub audio_codec: Option<String>,
+	pub tags: MediaTags,
+	/// Peak amplitudes between 0 and 1, evenly spaced over the duration, for
+	/// drawing a waveform. Only audio is analysed for peaks.
+	pub peaks: Option<Vec<f32>>,
+}
+
+/// Descriptive tags embedded in an audio file (ID3, Vorbis comments, MP4
+/// metadata or RIFF INFO).
+#[derive(Clone, Debug, Default, PartialEq, SimpleObject)]
+pub struct MediaTags {
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	/// The recording date as tagged, which may be only a year.
+	pub recorded_date: Option<String>,
+}
+
+impl MediaTags {
+	pub fn is_empty(&self) -> bool {
+		self.title.is_none() && self.artist.is_none() && self.recorded_date.is_none()
+	}
+}
+
+/// The bytes metadata extraction reads from a stored object.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataSource {
+	/// The first bytes of the object, up to the configured read limit.
+	pub head: Vec<u8>,
+	/// The ISO-BMFF `moov` box (header included) when it lies beyond `head`, as
+	/// it does for MP4s written without "fast start".
+	pub movie: Option<Vec<u8>>,
+	/// The last bytes of an Ogg file when they lie beyond `head`.
+	pub tail: Option<Vec<u8>>,
+}
+
+impl MetadataSource {
+	/// Reads the parts of `storage_key` that carry metadata, never more than
+	/// `max_bytes` per range.
+	pub async fn read(
+		storage: &StorageClient,
+		storage_key: &str,
+		content_length: i64,
+		max_bytes: i64,
+	) -> anyhow::Result<Self> {
+		let content_length = u64::try_from(content_length).context("Negative object length")?;
+		let max_bytes = u64::try_from(max_bytes).context("Negative metadata read limit")?;
+		if content_length == 0 {
+			return Ok(Self::default());
+		}
+
+		let head = storage
+			.get_object_range(storage_key, 0, content_length.min(max_bytes))
+			.await
+			.context("Failed to read object head for metadata extraction")?;
+		let movie = if isobmff::is_isobmff(&head) &&
+			(head.len() as u64) < content_length &&
+			!isobmff::has_complete_box(&head, isobmff::MOVIE)
+		{
+			read_trailing_movie_box(storage, storage_key, content_length, max_bytes).await?
+		} else {
+			None
+		};
+		let tail = if ogg::is_ogg(&head) && (head.len() as u64) < content_length {
+			let length = OGG_TAIL_BYTES.min(max_bytes).min(content_length - head.len() as u64);
+			let tail = storage
+				.get_object_range(storage_key, content_length - length, length)
+				.await
+				.context("Failed to read Ogg tail")?;
+			Some(tail)
+		} else {
+			None
+		};
+
+		Ok(Self {
+			head,
+			movie,
+			tail,
+		})
+	}
+}
+
+/// Walks top-level box headers with small ranged reads until the `moov` box is
+/// found, then fetches it whole if it fits within `max_bytes`.
+async fn read_trailing_movie_box(
+	storage: &StorageClient,
+	storage_key: &str,
+	content_length: u64,
+	max_bytes: u64,
+) -> anyhow::Result<Option<Vec<u8>>> {
+	let mut offset = 0;
+	while offset < content_length {
+		let header_bytes = storage
+			.get_object_range(storage_key, offset, 16.min(content_length - offset))
+			.await
+			.context("Failed to read ISO-BMFF box header")?;
+		let Some(header) = isobmff::BoxHeader::parse(&header_bytes) else {
+			return Ok(None);
+		};
+		let size = header.size.unwrap_or(content_length - offset);
+		if header.box_type == isobmff::MOVIE {
+			if size > max_bytes {
+				return Ok(None);
+			}
+			return storage
+				.get_object_range(storage_key, offset, size)
+				.await
+				.context("Failed to read ISO-BMFF movie box")
+				.map(Some);
+		}
+		offset = offset.saturating_add(size);
+	}
+	Ok(None)
+}
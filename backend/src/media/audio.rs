@@ -0,0 +1,308 @@
+//! Tag reading and waveform peaks for uploaded audio.
+//!
+//! The whole file is decoded with Symphonia to measure its peak amplitude over
+//! time, downsampled to [`PEAK_COUNT`] values the frontend draws as a waveform.
+//! Formats Symphonia can demux but not decode (Opus) still yield their tags and
+//! duration, without peaks.
+
+use {
+	super::{
+		MediaInfo,
+		MediaTags,
+	},
+	anyhow::Context,
+	std::io::{
+		Cursor,
+		ErrorKind,
+	},
+	symphonia::core::{
+		audio::SampleBuffer,
+		codecs::{
+			CODEC_TYPE_NULL,
+			DecoderOptions,
+		},
+		errors::Error as SymphoniaError,
+		formats::{
+			FormatOptions,
+			FormatReader,
+		},
+		io::MediaSourceStream,
+		meta::{
+			MetadataOptions,
+			MetadataRevision,
+			StandardTagKey,
+		},
+		probe::Hint,
+	},
+};
+
+/// How many peaks are kept for the waveform.
+pub const PEAK_COUNT: usize = 512;
+/// Peaks are first measured over windows of this many per second, then merged
+/// down to [`PEAK_COUNT`] once the length is known.
+const PEAK_WINDOWS_PER_SECOND: u32 = 100;
+
+/// Whether `content_type` names an audio format [`analyze_audio`] reads.
+pub fn supports_audio_analysis(content_type: &str) -> bool {
+	matches!(
+		content_type,
+		"audio/mpeg" |
+			"audio/wav" |
+			"audio/ogg" |
+			"audio/webm" |
+			"audio/flac" |
+			"audio/aac" |
+			"audio/mp4"
+	)
+}
+
+/// Reads the tags of `source` and decodes its first audio track for duration
+/// and waveform peaks.
+pub fn analyze_audio(
+	source: Vec<u8>,
+	content_type: &str,
+) -> anyhow::Result<MediaInfo> {
+	let mut hint = Hint::new();
+	hint.mime_type(content_type);
+	let stream = MediaSourceStream::new(Box::new(Cursor::new(source)), Default::default());
+	let mut probed = symphonia::default::get_probe()
+		.format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+		.context("Failed to recognise audio format")?;
+
+	// Tags ahead of the container (ID3v2 before an MP3 stream) come from the
+	// probe; the container's own tags take precedence.
+	let mut tags = MediaTags::default();
+	if let Some(revision) = probed.format.metadata().skip_to_latest() {
+		tags = tags_from(revision);
+	}
+	if let Some(mut metadata) = probed.metadata.get() &&
+		let Some(revision) = metadata.skip_to_latest()
+	{
+		let outer = tags_from(revision);
+		tags = MediaTags {
+			title: tags.title.or(outer.title),
+			artist: tags.artist.or(outer.artist),
+			recorded_date: tags.recorded_date.or(outer.recorded_date),
+		};
+	}
+
+	let track = probed
+		.format
+		.tracks()
+		.iter()
+		.find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+		.context("Audio file has no audio track")?;
+	let track_id = track.id;
+	let params = track.codec_params.clone();
+	let audio_codec = symphonia::default::get_codecs()
+		.get_codec(params.codec)
+		.map(|descriptor| descriptor.short_name.to_string());
+	let declared_seconds = params
+		.n_frames
+		.zip(params.sample_rate)
+		.filter(|(_, sample_rate)| *sample_rate > 0)
+		.map(|(frames, sample_rate)| frames as f64 / f64::from(sample_rate));
+
+	let (decoded_seconds, peaks) =
+		match symphonia::default::get_codecs().make(&params, &DecoderOptions::default()) {
+			Ok(decoder) => {
+				let (seconds, peaks) = decode_peaks(probed.format.as_mut(), decoder, track_id)?;
+				(seconds, Some(peaks))
+			}
+			Err(SymphoniaError::Unsupported(_)) => (None, None),
+			Err(error) => return Err(error).context("Failed to open audio decoder"),
+		};
+
+	Ok(MediaInfo {
+		duration_seconds: declared_seconds.or(decoded_seconds).filter(|seconds| *seconds > 0.0),
+		audio_codec,
+		tags,
+		peaks,
+		..MediaInfo::default()
+	})
+}
+
+fn tags_from(revision: &MetadataRevision) -> MediaTags {
+	let tag = |keys: &[StandardTagKey]| {
+		keys.iter().find_map(|key| {
+			revision
+				.tags()
+				.iter()
+				.filter(|tag| tag.std_key == Some(*key))
+				// RIFF INFO strings keep their NUL terminator.
+				.map(|tag| {
+					tag.value
+						.to_string()
+						.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+						.to_string()
+				})
+				.find(|value| !value.is_empty())
+		})
+	};
+	MediaTags {
+		title: tag(&[StandardTagKey::TrackTitle]),
+		artist: tag(&[StandardTagKey::Artist, StandardTagKey::AlbumArtist]),
+		recorded_date: tag(&[StandardTagKey::Date, StandardTagKey::OriginalDate]),
+	}
+}
+
+/// Decodes every packet of `track_id`, returning the decoded duration (when
+/// the sample rate is known) and the downsampled peaks. Corrupt packets are
+/// skipped, as players do.
+fn decode_peaks(
+	format: &mut dyn FormatReader,
+	mut decoder: Box<dyn symphonia::core::codecs::Decoder>,
+	track_id: u32,
+) -> anyhow::Result<(Option<f64>, Vec<f32>)> {
+	let mut peaks = None;
+	let mut sample_rate = None;
+	loop {
+		let packet = match format.next_packet() {
+			Ok(packet) => packet,
+			Err(SymphoniaError::IoError(error)) if error.kind() == ErrorKind::UnexpectedEof =>
+				break,
+			Err(SymphoniaError::ResetRequired) => break,
+			Err(error) => return Err(error).context("Failed to read audio packet"),
+		};
+		if packet.track_id() != track_id {
+			continue;
+		}
+		let decoded = match decoder.decode(&packet) {
+			Ok(decoded) => decoded,
+			Err(SymphoniaError::DecodeError(_)) => continue,
+			Err(error) => return Err(error).context("Failed to decode audio"),
+		};
+		let spec = *decoded.spec();
+		let channels = spec.channels.count().max(1);
+		let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+		samples.copy_interleaved_ref(decoded);
+		sample_rate.get_or_insert(spec.rate);
+		peaks
+			.get_or_insert_with(|| PeakAccumulator::new(spec.rate))
+			.push_interleaved(samples.samples(), channels);
+	}
+
+	let peaks = peaks.unwrap_or_else(|| PeakAccumulator::new(0));
+	let seconds =
+		sample_rate.filter(|rate| *rate > 0).map(|rate| peaks.frames as f64 / f64::from(rate));
+	Ok((seconds, peaks.finish(PEAK_COUNT)))
+}
+
+/// Tracks the peak absolute sample value, across channels, per short window.
+struct PeakAccumulator {
+	window_frames: u64,
+	frames_in_window: u64,
+	current: f32,
+	windows: Vec<f32>,
+	frames: u64,
+}
+
+impl PeakAccumulator {
+	fn new(sample_rate: u32) -> Self {
+		Self {
+			window_frames: u64::from(sample_rate / PEAK_WINDOWS_PER_SECOND).max(1),
+			frames_in_window: 0,
+			current: 0.0,
+			windows: Vec::new(),
+			frames: 0,
+		}
+	}
+
+	fn push_interleaved(
+		&mut self,
+		samples: &[f32],
+		channels: usize,
+	) {
+		for frame in samples.chunks(channels) {
+			let peak = frame.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+			self.current = self.current.max(peak);
+			self.frames_in_window += 1;
+			self.frames += 1;
+			if self.frames_in_window == self.window_frames {
+				self.windows.push(self.current);
+				self.current = 0.0;
+				self.frames_in_window = 0;
+			}
+		}
+	}
+
+	/// Merges the windows into at most `count` peaks, each clamped to 1.
+	fn finish(
+		mut self,
+		count: usize,
+	) -> Vec<f32> {
+		if self.frames_in_window > 0 {
+			self.windows.push(self.current);
+		}
+		let len = self.windows.len();
+		if len <= count {
+			return self.windows.into_iter().map(|peak| peak.min(1.0)).collect();
+		}
+		(0 .. count)
+			.map(|index| {
+				self.windows
+					.get(index * len / count .. (index + 1) * len / count)
+					.unwrap_or_default()
+					.iter()
+					.fold(0.0f32, |peak, window| peak.max(*window))
+					.min(1.0)
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		PEAK_COUNT,
+		PeakAccumulator,
+		analyze_audio,
+		supports_audio_analysis,
+	};
+
+	/// One second of a 16-bit mono 8 kHz tone whose amplitude ramps up from
+	/// silence, tagged with a RIFF INFO list.
+	const FIXTURE: &[u8] = include_bytes!("../../tests/fixtures/tagged-tone.wav");
+
+	#[test]
+	fn analyze_audio_reads_tags_duration_and_peaks() -> anyhow::Result<()> {
+		let info = analyze_audio(FIXTURE.to_vec(), "audio/wav")?;
+
+		assert_eq!(info.tags.title.as_deref(), Some("Harbour at dawn"));
+		assert_eq!(info.tags.artist.as_deref(), Some("Memory Map"));
+		assert_eq!(info.tags.recorded_date.as_deref(), Some("2024-05-18"));
+		assert_eq!(info.duration_seconds, Some(1.0));
+		assert_eq!(info.audio_codec.as_deref(), Some("pcm_s16le"));
+
+		let peaks = info.peaks.unwrap_or_default();
+		assert_eq!(peaks.len(), 100);
+		let (first, last) = (peaks.first().copied(), peaks.last().copied());
+		assert!(first.is_some_and(|peak| peak < 0.05), "{first:?}");
+		assert!(last.is_some_and(|peak| peak > 0.9), "{last:?}");
+		Ok(())
+	}
+
+	#[test]
+	fn analyze_audio_rejects_unrecognised_input() {
+		assert!(analyze_audio(b"definitely not audio".to_vec(), "audio/mpeg").is_err());
+	}
+
+	#[test]
+	fn peaks_merge_down_to_the_requested_count() {
+		let mut peaks = PeakAccumulator::new(100);
+		let samples = (0 .. 3000).map(|index| index as f32 / 3000.0).collect::<Vec<_>>();
+		peaks.push_interleaved(&samples, 2);
+
+		let merged = peaks.finish(PEAK_COUNT.min(10));
+		assert_eq!(merged.len(), 10);
+		assert!(merged.windows(2).all(|pair| pair.first() < pair.last()));
+		assert!(merged.last().is_some_and(|peak| *peak > 0.99));
+	}
+
+	#[test]
+	fn only_the_accepted_audio_types_are_analysed() {
+		assert!(supports_audio_analysis("audio/flac"));
+		assert!(!supports_audio_analysis("audio/x-unknown"));
+		assert!(!supports_audio_analysis("video/mp4"));
+	}
+}
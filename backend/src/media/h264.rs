@@ -0,0 +1,142 @@
+//! In-process decoding of one H.264 keyframe, for video posters.
+//!
+//! Most phone and camera videos are H.264 in MP4 and carry no cover art. Their
+//! poster is decoded from a single sync sample with OpenH264, which is compiled
+//! into the binary, so no external `ffmpeg` is needed. Only that sample is
+//! read from storage; the sample table in the movie box says where it lies.
+
+use {
+	anyhow::Context,
+	image::{
+		DynamicImage,
+		ImageFormat,
+		RgbImage,
+	},
+	openh264::{
+		decoder::Decoder,
+		formats::YUVSource,
+		nal_units,
+	},
+	std::io::Cursor,
+};
+
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+/// A sync sample of an H.264 track and the stream parameters needed to decode
+/// it on its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keyframe {
+	/// Where the sample lies in the file.
+	pub offset: u64,
+	pub length: u64,
+	/// The byte width of the length prefix before each NAL unit in a sample.
+	length_size: usize,
+	/// The sequence and picture parameter sets, with start codes.
+	parameter_sets: Vec<u8>,
+}
+
+impl Keyframe {
+	/// Builds a keyframe from the payload of an `avcC` box (an
+	/// `AVCDecoderConfigurationRecord`) and the sample's place in the file.
+	pub(super) fn new(
+		configuration: &[u8],
+		offset: u64,
+		length: u64,
+	) -> Option<Self> {
+		let length_size = usize::from(configuration.get(4)? & 0b11) + 1;
+		let mut parameter_sets = Vec::new();
+		let mut rest = configuration.get(5 ..)?;
+		// Sequence parameter sets carry a 5-bit count, picture parameter sets an
+		// 8-bit one.
+		for count_mask in [0b1_1111, 0xff] {
+			let (count, sets) = rest.split_first()?;
+			rest = sets;
+			for _ in 0 .. count & count_mask {
+				let length = usize::from(u16::from_be_bytes(rest.get(.. 2)?.try_into().ok()?));
+				parameter_sets.extend_from_slice(&START_CODE);
+				parameter_sets.extend_from_slice(rest.get(2 .. 2 + length)?);
+				rest = rest.get(2 + length ..)?;
+			}
+		}
+		Some(Self {
+			offset,
+			length,
+			length_size,
+			parameter_sets,
+		})
+	}
+
+	/// Decodes the keyframe from its sample bytes and returns it as a PNG, the
+	/// format a poster frame is handed to the renderer in.
+	pub fn decode(
+		&self,
+		sample: &[u8],
+	) -> anyhow::Result<Vec<u8>> {
+		let stream = self.annex_b(sample)?;
+		let mut decoder = Decoder::new().context("Failed to start the H.264 decoder")?;
+		let mut frame = None;
+		for unit in nal_units(&stream) {
+			let Some(picture) = decoder.decode(unit).context("Failed to decode H.264")? else {
+				continue;
+			};
+			let (width, height) = picture.dimensions();
+			let mut rgb = vec![0; width * height * 3];
+			picture.write_rgb8(&mut rgb);
+			frame = RgbImage::from_raw(
+				u32::try_from(width).context("Frame width out of range")?,
+				u32::try_from(height).context("Frame height out of range")?,
+				rgb,
+			);
+		}
+		let frame = frame.context("The keyframe decoded to no picture")?;
+		let mut png = Vec::new();
+		DynamicImage::ImageRgb8(frame)
+			.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+			.context("Failed to encode the keyframe")?;
+		Ok(png)
+	}
+
+	/// Rewrites a sample's length-prefixed NAL units as an Annex B byte stream,
+	/// led by the parameter sets.
+	fn annex_b(
+		&self,
+		sample: &[u8],
+	) -> anyhow::Result<Vec<u8>> {
+		let mut stream = self.parameter_sets.clone();
+		let mut rest = sample;
+		while !rest.is_empty() {
+			let (prefix, units) =
+				rest.split_at_checked(self.length_size).context("Truncated NAL unit length")?;
+			let length =
+				prefix.iter().fold(0usize, |length, byte| (length << 8) | usize::from(*byte));
+			let (unit, next) = units.split_at_checked(length).context("Truncated NAL unit")?;
+			stream.extend_from_slice(&START_CODE);
+			stream.extend_from_slice(unit);
+			rest = next;
+		}
+		Ok(stream)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Keyframe;
+
+	#[test]
+	fn keyframes_rewrite_samples_as_annex_b() -> anyhow::Result<()> {
+		// Two-byte NAL lengths, one SPS and one PPS.
+		let configuration = [1, 66, 0xc0, 10, 0xfd, 0xe1, 0, 2, 0x67, 0x42, 1, 0, 1, 0x68];
+		let keyframe =
+			Keyframe::new(&configuration, 0, 0).ok_or_else(|| anyhow::anyhow!("valid avcC"))?;
+		let stream = keyframe.annex_b(&[0, 2, 0x65, 0x88, 0, 1, 0x06])?;
+		assert_eq!(
+			stream,
+			[0, 0, 0, 1, 0x67, 0x42, 0, 0, 0, 1, 0x68, 0, 0, 0, 1, 0x65, 0x88, 0, 0, 0, 1, 0x06]
+		);
+		assert!(keyframe.annex_b(&[0, 9, 0x65]).is_err());
+		assert!(
+			configuration.get(.. 9).and_then(|truncated| Keyframe::new(truncated, 0, 0)).is_none()
+		);
+		Ok(())
+	}
+}
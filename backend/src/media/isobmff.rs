@@ -0,0 +1,546 @@
+//! A minimal ISO base media file format (MP4, QuickTime, HEIF) box walker.
+//!
+//! Only what the media inspection needs: iterating boxes in a byte slice,
+//! descending a fixed path of container boxes, and decoding the handful of
+//! leaf boxes we read (`mvhd`, `tkhd`, `hdlr`, `stsd`, `\xa9xyz`, and iTunes
+//! cover art), plus the sample table needed to find one keyframe. Truncated input is tolerated: a box that runs past the end of
+//! the slice is yielded with whatever payload is present, and iteration stops
+//! after it.
+
+use {
+	crate::graphql::objects::location::Location,
+	jiff::Timestamp,
+};
+
+pub const FILE_TYPE: [u8; 4] = *b"ftyp";
+pub const MOVIE: [u8; 4] = *b"moov";
+pub const MOVIE_HEADER: [u8; 4] = *b"mvhd";
+pub const TRACK: [u8; 4] = *b"trak";
+pub const TRACK_HEADER: [u8; 4] = *b"tkhd";
+pub const MEDIA: [u8; 4] = *b"mdia";
+pub const MEDIA_HEADER: [u8; 4] = *b"mdhd";
+pub const HANDLER: [u8; 4] = *b"hdlr";
+pub const MEDIA_INFORMATION: [u8; 4] = *b"minf";
+pub const SAMPLE_TABLE: [u8; 4] = *b"stbl";
+pub const SAMPLE_DESCRIPTION: [u8; 4] = *b"stsd";
+pub const TIME_TO_SAMPLE: [u8; 4] = *b"stts";
+pub const SYNC_SAMPLE: [u8; 4] = *b"stss";
+pub const SAMPLE_TO_CHUNK: [u8; 4] = *b"stsc";
+pub const SAMPLE_SIZE: [u8; 4] = *b"stsz";
+pub const CHUNK_OFFSET: [u8; 4] = *b"stco";
+pub const CHUNK_OFFSET_64: [u8; 4] = *b"co64";
+pub const USER_DATA: [u8; 4] = *b"udta";
+pub const METADATA: [u8; 4] = *b"meta";
+pub const ITEM_LIST: [u8; 4] = *b"ilst";
+pub const COVER_ART: [u8; 4] = *b"covr";
+pub const DATA: [u8; 4] = *b"data";
+pub const VIDEO_HANDLER: [u8; 4] = *b"vide";
+pub const SOUND_HANDLER: [u8; 4] = *b"soun";
+pub const QUICKTIME_LOCATION: [u8; 4] = [0xa9, b'x', b'y', b'z'];
+
+/// Seconds between the QuickTime epoch (1904-01-01) and the Unix epoch.
+const QUICKTIME_EPOCH_OFFSET_SECONDS: i64 = 2_082_844_800;
+
+/// A box header as read from the start of a box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoxHeader {
+	pub box_type: [u8; 4],
+	pub header_len: u64,
+	/// Total box length including the header; `None` when the box extends to
+	/// the end of its container (a size field of 0).
+	pub size: Option<u64>,
+}
+
+impl BoxHeader {
+	/// Parses the header at the start of `bytes`, which must hold at least the
+	/// 8-byte compact header (16 bytes for a 64-bit `largesize` box).
+	pub fn parse(bytes: &[u8]) -> Option<Self> {
+		let size = u32::from_be_bytes(bytes.get(0 .. 4)?.try_into().ok()?);
+		let box_type: [u8; 4] = bytes.get(4 .. 8)?.try_into().ok()?;
+		let (header_len, size) = match size {
+			0 => (8, None),
+			1 => (16, Some(u64::from_be_bytes(bytes.get(8 .. 16)?.try_into().ok()?))),
+			size => (8, Some(u64::from(size))),
+		};
+		if size.is_some_and(|size| size < header_len) {
+			return None;
+		}
+		Some(Self {
+			box_type,
+			header_len,
+			size,
+		})
+	}
+}
+
+/// Iterates the sibling boxes in `data`, yielding each box type with its payload.
+pub struct Boxes<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> Boxes<'a> {
+	pub fn new(data: &'a [u8]) -> Self {
+		Self {
+			data,
+		}
+	}
+}
+
+impl<'a> Iterator for Boxes<'a> {
+	type Item = ([u8; 4], &'a [u8]);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let header = BoxHeader::parse(self.data)?;
+		let header_len = usize::try_from(header.header_len).ok()?;
+		let end = header
+			.size
+			.and_then(|size| usize::try_from(size).ok())
+			.unwrap_or(self.data.len())
+			.min(self.data.len());
+		let payload = self.data.get(header_len .. end).unwrap_or_default();
+		self.data = self.data.get(end ..).unwrap_or_default();
+		Some((header.box_type, payload))
+	}
+}
+
+/// Whether `bytes` begins like an ISO-BMFF file (a leading `ftyp` box).
+pub fn is_isobmff(bytes: &[u8]) -> bool {
+	BoxHeader::parse(bytes).is_some_and(|header| header.box_type == FILE_TYPE)
+}
+
+/// Whether `data` holds a complete top-level box of `box_type`. A box whose
+/// size runs to the end of the file never counts, since `data` may be a prefix.
+pub fn has_complete_box(
+	data: &[u8],
+	box_type: [u8; 4],
+) -> bool {
+	let mut rest = data;
+	while let Some(header) = BoxHeader::parse(rest) {
+		let Some(size) = header.size.and_then(|size| usize::try_from(size).ok()) else {
+			return false;
+		};
+		if size > rest.len() {
+			return false;
+		}
+		if header.box_type == box_type {
+			return true;
+		}
+		rest = rest.get(size ..).unwrap_or_default();
+	}
+	false
+}
+
+/// Returns the payload of the first box reached by following `path` from the
+/// top level of `data`.
+pub fn find_box<'a>(
+	data: &'a [u8],
+	path: &[[u8; 4]],
+) -> Option<&'a [u8]> {
+	let (first, rest) = path.split_first()?;
+	let payload = Boxes::new(data).find(|(box_type, _)| box_type == first)?.1;
+	if rest.is_empty() { Some(payload) } else { find_box(payload, rest) }
+}
+
+/// The fields of a movie header (`mvhd`) box the media inspection reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MovieHeader {
+	pub creation_time: Option<Timestamp>,
+	pub timescale: u32,
+	pub duration: u64,
+}
+
+impl MovieHeader {
+	/// Decodes an `mvhd` payload (version 0 or 1). A zero creation time means
+	/// "unset" in practice and is reported as `None`.
+	pub fn parse(payload: &[u8]) -> Option<Self> {
+		let (creation, timescale, duration) = match payload.first()? {
+			0 => (
+				u64::from(read_u32(payload, 4)?),
+				read_u32(payload, 12)?,
+				u64::from(read_u32(payload, 16)?),
+			),
+			1 => (read_u64(payload, 4)?, read_u32(payload, 20)?, read_u64(payload, 24)?),
+			_ => return None,
+		};
+		let creation_time =
+			i64::try_from(creation).ok().filter(|creation| *creation > 0).and_then(|creation| {
+				Timestamp::from_second(creation - QUICKTIME_EPOCH_OFFSET_SECONDS).ok()
+			});
+		Some(Self {
+			creation_time,
+			timescale,
+			duration,
+		})
+	}
+
+	pub fn duration_seconds(&self) -> Option<f64> {
+		(self.timescale > 0).then(|| self.duration as f64 / f64::from(self.timescale))
+	}
+}
+
+/// The fields of one track (`trak`) box the media inspection reads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Track {
+	/// The media handler type, e.g. `vide` or `soun`.
+	pub handler: Option<[u8; 4]>,
+	/// The type of the first sample entry, which names the codec (`avc1`, `hvc1`,
+	/// `av01`, `mp4a`, ...).
+	pub sample_entry: Option<[u8; 4]>,
+	/// Presentation width and height from the track header, zero for non-visual
+	/// tracks.
+	pub width: u32,
+	pub height: u32,
+}
+
+impl Track {
+	/// Decodes a `trak` payload. Missing or truncated child boxes leave the
+	/// corresponding fields unset.
+	pub fn parse(payload: &[u8]) -> Self {
+		let handler = find_box(payload, &[MEDIA, HANDLER])
+			.and_then(|handler| handler.get(8 .. 12)?.try_into().ok());
+		let sample_entry = sample_entry(payload).map(|(entry_type, _)| entry_type);
+		// Width and height are 16.16 fixed point at the end of the header, whose
+		// earlier fields widen from 32 to 64 bits in version 1.
+		let (width, height) = find_box(payload, &[TRACK_HEADER])
+			.and_then(|header| {
+				let offset = match header.first()? {
+					0 => 76,
+					1 => 88,
+					_ => return None,
+				};
+				Some((read_u32(header, offset)? >> 16, read_u32(header, offset + 4)? >> 16))
+			})
+			.unwrap_or_default();
+		Self {
+			handler,
+			sample_entry,
+			width,
+			height,
+		}
+	}
+}
+
+/// The tracks of a `moov` payload, in file order.
+pub fn tracks(movie: &[u8]) -> impl Iterator<Item = Track> + '_ {
+	Boxes::new(movie)
+		.filter(|(box_type, _)| *box_type == TRACK)
+		.map(|(_, payload)| Track::parse(payload))
+}
+
+/// Returns the type and payload of the first sample entry of a `trak` payload.
+pub fn sample_entry(track: &[u8]) -> Option<([u8; 4], &[u8])> {
+	let description =
+		find_box(track, &[MEDIA, MEDIA_INFORMATION, SAMPLE_TABLE, SAMPLE_DESCRIPTION])?;
+	// A full box header and an entry count precede the entries.
+	Boxes::new(description.get(8 ..)?).next()
+}
+
+/// Where one sample's bytes lie in the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SampleRange {
+	pub offset: u64,
+	pub length: u64,
+}
+
+/// Locates the last sync sample of a `trak` payload that decodes at or before
+/// `seconds`, which a decoder can start from without any earlier sample. A
+/// track without a sync sample table has only sync samples. Returns `None` when
+/// the sample table is missing or inconsistent.
+pub fn sync_sample_at(
+	track: &[u8],
+	seconds: f64,
+) -> Option<SampleRange> {
+	let media_header = find_box(track, &[MEDIA, MEDIA_HEADER])?;
+	let timescale = match media_header.first()? {
+		0 => read_u32(media_header, 12)?,
+		1 => read_u32(media_header, 20)?,
+		_ => return None,
+	};
+	let target = (seconds.max(0.0) * f64::from(timescale)) as u64;
+	let table = find_box(track, &[MEDIA, MEDIA_INFORMATION, SAMPLE_TABLE])?;
+	let times = full_box_entries(table, TIME_TO_SAMPLE, 8)?;
+	let sample_count = read_u32(find_box(table, &[SAMPLE_SIZE])?, 8)?;
+
+	let chosen = match full_box_entries(table, SYNC_SAMPLE, 4) {
+		Some(entries) => {
+			let numbers = entries.chunks_exact(4).filter_map(|entry| read_u32(entry, 0));
+			sync_sample_by_time(numbers, times, target)
+		}
+		None => sync_sample_by_time(1 ..= sample_count, times, target),
+	}?;
+	sample_range(table, chosen)
+}
+
+/// Of the ascending 1-based sample `numbers`, the last whose decode time per
+/// the `stts` runs in `times` is at or before `target`; the first when all
+/// start later. The numbers and the runs are walked together.
+fn sync_sample_by_time(
+	numbers: impl Iterator<Item = u32>,
+	times: &[u8],
+	target: u64,
+) -> Option<u32> {
+	let mut runs =
+		times.chunks_exact(8).filter_map(|run| Some((read_u32(run, 0)?, read_u32(run, 4)?)));
+	// The first sample of the current run, and its decode time.
+	let (mut run_start, mut run_time) = (1u64, 0u64);
+	let mut run = runs.next();
+	let mut chosen = None;
+	for number in numbers {
+		let number = u64::from(number);
+		let time = loop {
+			let (count, delta) = run?;
+			let run_end = run_start + u64::from(count);
+			if number < run_end {
+				break run_time
+					.checked_add(number.checked_sub(run_start)?.checked_mul(u64::from(delta))?)?;
+			}
+			run_time = run_time.checked_add(u64::from(count).checked_mul(u64::from(delta))?)?;
+			run_start = run_end;
+			run = runs.next();
+		};
+		if time > target && chosen.is_some() {
+			break;
+		}
+		chosen = Some(u32::try_from(number).ok()?);
+		if time >= target {
+			break;
+		}
+	}
+	chosen
+}
+
+/// Finds the file offset and size of 1-based `sample` from the chunk tables of
+/// an `stbl` payload.
+fn sample_range(
+	table: &[u8],
+	sample: u32,
+) -> Option<SampleRange> {
+	let sizes = find_box(table, &[SAMPLE_SIZE])?;
+	let fixed_size = read_u32(sizes, 4)?;
+	let size_of = |sample: u32| match fixed_size {
+		0 => read_u32(sizes, 12 + 4 * usize::try_from(sample.checked_sub(1)?).ok()?),
+		size => Some(size),
+	};
+	let (chunk_offsets, offset_width) = match full_box_entries(table, CHUNK_OFFSET, 4) {
+		Some(offsets) => (offsets, 4),
+		None => (full_box_entries(table, CHUNK_OFFSET_64, 8)?, 8),
+	};
+	let chunk_count = u32::try_from(chunk_offsets.len() / offset_width).ok()?;
+
+	// Each `stsc` run gives the samples per chunk from its first chunk up to the
+	// next run's first chunk.
+	let runs = full_box_entries(table, SAMPLE_TO_CHUNK, 12)?;
+	let mut remaining = sample.checked_sub(1)?;
+	let mut location = None;
+	let mut entries = runs.chunks_exact(12).peekable();
+	while let Some(entry) = entries.next() {
+		let first_chunk = read_u32(entry, 0)?;
+		let samples_per_chunk = read_u32(entry, 4)?;
+		let next_chunk = match entries.peek() {
+			Some(next) => read_u32(next, 0)?,
+			None => chunk_count.checked_add(1)?,
+		};
+		let run_samples = next_chunk.checked_sub(first_chunk)?.checked_mul(samples_per_chunk)?;
+		if remaining < run_samples {
+			location =
+				Some((first_chunk + remaining / samples_per_chunk, remaining % samples_per_chunk));
+			break;
+		}
+		remaining -= run_samples;
+	}
+	let (chunk, index_in_chunk) = location?;
+
+	let entry = usize::try_from(chunk.checked_sub(1)?).ok()? * offset_width;
+	let mut offset = match offset_width {
+		4 => u64::from(read_u32(chunk_offsets, entry)?),
+		_ => read_u64(chunk_offsets, entry)?,
+	};
+	for earlier in sample - index_in_chunk .. sample {
+		offset = offset.checked_add(u64::from(size_of(earlier)?))?;
+	}
+	Some(SampleRange {
+		offset,
+		length: u64::from(size_of(sample)?),
+	})
+}
+
+/// The entries of a full box in an `stbl` payload that holds a 32-bit entry
+/// count followed by `entry_len`-byte entries, cut to the entries present.
+fn full_box_entries(
+	table: &[u8],
+	box_type: [u8; 4],
+	entry_len: usize,
+) -> Option<&[u8]> {
+	let payload = find_box(table, &[box_type])?;
+	let count = usize::try_from(read_u32(payload, 4)?).ok()?;
+	let entries = payload.get(8 ..)?;
+	let present = entries.len() - entries.len() % entry_len;
+	entries.get(.. count.saturating_mul(entry_len).min(present))
+}
+
+/// Returns the image bytes of iTunes-style cover art (`udta/meta/ilst/covr`)
+/// in a `moov` payload.
+pub fn cover_art(movie: &[u8]) -> Option<&[u8]> {
+	let metadata = find_box(movie, &[USER_DATA, METADATA])?;
+	// ISO `meta` is a full box with a version/flags word before its children;
+	// QuickTime writes it as a plain container.
+	let children = if metadata.get(4 .. 8) == Some(HANDLER.as_slice()) {
+		metadata
+	} else {
+		metadata.get(4 ..)?
+	};
+	// A `data` payload opens with a type indicator and a locale word.
+	find_box(children, &[ITEM_LIST, COVER_ART, DATA])?.get(8 ..).filter(|image| !image.is_empty())
+}
+
+/// Decodes a QuickTime `\xa9xyz` user-data payload: a 16-bit string length, a
+/// 16-bit language code, then an ISO 6709 string such as `+37.7749-122.4194/`.
+pub fn quicktime_location_text(payload: &[u8]) -> Option<String> {
+	let length = usize::from(u16::from_be_bytes(payload.get(0 .. 2)?.try_into().ok()?));
+	let text = payload.get(4 .. 4 + length).or_else(|| payload.get(4 ..))?;
+	let text = String::from_utf8_lossy(text).trim_end_matches('\0').trim().to_string();
+	(!text.is_empty()).then_some(text)
+}
+
+/// Parses the latitude/longitude prefix of an ISO 6709 point string. Degrees
+/// may be given as decimal degrees (`+DD.D+DDD.D`), degrees-minutes
+/// (`+DDMM.M+DDDMM.M`), or degrees-minutes-seconds (`+DDMMSS.S+DDDMMSS.S`).
+pub fn parse_iso6709(text: &str) -> Option<Location> {
+	let mut components = Vec::new();
+	let mut start = None;
+	for (index, character) in text.char_indices() {
+		if matches!(character, '+' | '-' | '/') {
+			if let Some(start) = start {
+				components.push(text.get(start .. index)?);
+			}
+			start = (character != '/').then_some(index);
+			if character == '/' || components.len() == 2 {
+				break;
+			}
+		}
+	}
+	let [latitude, longitude] = components.as_slice() else {
+		return None;
+	};
+	Location {
+		latitude: iso6709_degrees(latitude, 2)?,
+		longitude: iso6709_degrees(longitude, 3)?,
+	}
+	.validated()
+	.ok()
+}
+
+fn iso6709_degrees(
+	component: &str,
+	degree_digits: usize,
+) -> Option<f64> {
+	let (sign, digits) = component.split_at_checked(1)?;
+	let sign = if sign == "-" { -1.0 } else { 1.0 };
+	let integer_digits = digits.find('.').unwrap_or(digits.len());
+	let value: f64 = digits.parse().ok()?;
+	let degrees = match integer_digits.checked_sub(degree_digits)? {
+		0 => value,
+		2 => (value / 100.0).trunc() + (value % 100.0) / 60.0,
+		4 =>
+			(value / 10_000.0).trunc() +
+				((value % 10_000.0) / 100.0).trunc() / 60.0 +
+				(value % 100.0) / 3600.0,
+		_ => return None,
+	};
+	Some(sign * degrees)
+}
+
+fn read_u32(
+	bytes: &[u8],
+	offset: usize,
+) -> Option<u32> {
+	Some(u32::from_be_bytes(bytes.get(offset .. offset + 4)?.try_into().ok()?))
+}
+
+fn read_u64(
+	bytes: &[u8],
+	offset: usize,
+) -> Option<u64> {
+	Some(u64::from_be_bytes(bytes.get(offset .. offset + 8)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		Boxes,
+		MOVIE,
+		MOVIE_HEADER,
+		MovieHeader,
+		QUICKTIME_LOCATION,
+		USER_DATA,
+		find_box,
+		parse_iso6709,
+		quicktime_location_text,
+	};
+
+	fn boxed(
+		box_type: [u8; 4],
+		payload: &[u8],
+	) -> Vec<u8> {
+		let mut bytes = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+		bytes.extend_from_slice(&box_type);
+		bytes.extend_from_slice(payload);
+		bytes
+	}
+
+	#[test]
+	fn find_box_descends_nested_containers_and_tolerates_truncation() {
+		let location = boxed(QUICKTIME_LOCATION, b"\0\x12\x15\xc7+37.7749-122.4194/");
+		let movie = boxed(MOVIE, &boxed(USER_DATA, &location));
+		let mut file = boxed(*b"ftyp", b"isom");
+		file.extend_from_slice(&movie);
+		file.extend_from_slice(&boxed(*b"mdat", &[0; 32]));
+		file.truncate(file.len() - 10);
+
+		let found = find_box(&file, &[MOVIE, USER_DATA, QUICKTIME_LOCATION]);
+		assert_eq!(found.and_then(quicktime_location_text).as_deref(), Some("+37.7749-122.4194/"));
+		assert_eq!(Boxes::new(&file).count(), 3);
+	}
+
+	#[test]
+	fn movie_header_reads_creation_time_and_duration() {
+		// Version 0: version/flags, creation, modification, timescale, duration.
+		let payload = [
+			0u32,
+			// 2021-01-01T00:00:00Z in QuickTime seconds.
+			1_609_459_200 + 2_082_844_800,
+			0,
+			1000,
+			2500,
+		]
+		.iter()
+		.flat_map(|word| word.to_be_bytes())
+		.chain([0; 80])
+		.collect::<Vec<_>>();
+		let movie = boxed(MOVIE, &boxed(MOVIE_HEADER, &payload));
+
+		let header = find_box(&movie, &[MOVIE, MOVIE_HEADER]).and_then(MovieHeader::parse);
+		assert_eq!(
+			header.and_then(|header| header.creation_time).map(|time| time.as_second()),
+			Some(1_609_459_200)
+		);
+		assert_eq!(header.and_then(|header| header.duration_seconds()), Some(2.5));
+	}
+
+	#[test]
+	fn iso6709_parses_decimal_and_sexagesimal_forms() {
+		let decimal = parse_iso6709("+37.7749-122.4194+010.000/");
+		assert_eq!(decimal.map(|l| (l.latitude, l.longitude)), Some((37.7749, -122.4194)));
+
+		let minutes = parse_iso6709("+3730.0-12215.0/");
+		assert_eq!(minutes.map(|l| (l.latitude, l.longitude)), Some((37.5, -122.25)));
+
+		let seconds = parse_iso6709("-335200+1511230/");
+		assert!(seconds.is_some_and(|l| {
+			(l.latitude + 33.866_666).abs() < 1e-5 && (l.longitude - 151.208_333).abs() < 1e-5
+		}));
+
+		assert!(parse_iso6709("+99.0+000.0/").is_none());
+		assert!(parse_iso6709("garbage").is_none());
+	}
+}
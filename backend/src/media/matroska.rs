@@ -0,0 +1,297 @@
+//! A minimal Matroska/WebM (EBML) element reader.
+//!
+//! Only the segment metadata the media inspection needs is decoded: the
+//! duration from `Info`, the first video and audio tracks from `Tracks`, and
+//! image attachments. These elements precede the clusters in files written by
+//! common muxers, so a head read is enough. As with the ISO-BMFF walker,
+//! truncated input is tolerated, and an element of unknown size (live-streamed
+//! WebM) runs to the end of its parent.
+
+use super::MediaInfo;
+
+pub const EBML: u32 = 0x1a45_dfa3;
+pub const SEGMENT: u32 = 0x1853_8067;
+pub const INFO: u32 = 0x1549_a966;
+pub const TIMESTAMP_SCALE: u32 = 0x2a_d7b1;
+pub const DURATION: u32 = 0x4489;
+pub const TRACKS: u32 = 0x1654_ae6b;
+pub const TRACK_ENTRY: u32 = 0xae;
+pub const TRACK_TYPE: u32 = 0x83;
+pub const CODEC_ID: u32 = 0x86;
+pub const VIDEO: u32 = 0xe0;
+pub const PIXEL_WIDTH: u32 = 0xb0;
+pub const PIXEL_HEIGHT: u32 = 0xba;
+pub const ATTACHMENTS: u32 = 0x1941_a469;
+pub const ATTACHED_FILE: u32 = 0x61a7;
+pub const FILE_MIME_TYPE: u32 = 0x4660;
+pub const FILE_DATA: u32 = 0x465c;
+
+const TRACK_TYPE_VIDEO: u64 = 1;
+const TRACK_TYPE_AUDIO: u64 = 2;
+/// Nanoseconds per `Duration` tick when `TimestampScale` is absent.
+const DEFAULT_TIMESTAMP_SCALE: u64 = 1_000_000;
+
+/// Reads an EBML variable-length integer: the count of leading zero bits in the
+/// first byte gives the number of bytes that follow. Element IDs keep their
+/// length marker bit; sizes drop it. Returns the value and its encoded length.
+fn read_vint(
+	bytes: &[u8],
+	keep_marker: bool,
+) -> Option<(u64, usize)> {
+	let first = *bytes.first()?;
+	let len = first.leading_zeros() as usize + 1;
+	if len > 8 {
+		return None;
+	}
+	let mut value = u64::from(first);
+	if !keep_marker {
+		value &= 0xff >> len;
+	}
+	for byte in bytes.get(1 .. len)? {
+		value = (value << 8) | u64::from(*byte);
+	}
+	Some((value, len))
+}
+
+/// Iterates the sibling elements in `data`, yielding each element ID with its
+/// payload.
+pub struct Elements<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> Elements<'a> {
+	pub fn new(data: &'a [u8]) -> Self {
+		Self {
+			data,
+		}
+	}
+}
+
+impl<'a> Iterator for Elements<'a> {
+	type Item = (u32, &'a [u8]);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (id, id_len) = read_vint(self.data, true)?;
+		let id = u32::try_from(id).ok()?;
+		let (size, size_len) = read_vint(self.data.get(id_len ..)?, false)?;
+		let header_len = id_len + size_len;
+		// A size with every value bit set means "unknown".
+		let unknown = size == (1 << (7 * size_len)) - 1;
+		let end = if unknown {
+			self.data.len()
+		} else {
+			usize::try_from(size)
+				.ok()
+				.and_then(|size| size.checked_add(header_len))
+				.unwrap_or(self.data.len())
+				.min(self.data.len())
+		};
+		let payload = self.data.get(header_len .. end).unwrap_or_default();
+		self.data = self.data.get(end ..).unwrap_or_default();
+		Some((id, payload))
+	}
+}
+
+/// Whether `bytes` begins with an EBML header, as Matroska and WebM files do.
+pub fn is_matroska(bytes: &[u8]) -> bool {
+	Elements::new(bytes).next().is_some_and(|(id, _)| id == EBML)
+}
+
+fn find_element(
+	data: &[u8],
+	id: u32,
+) -> Option<&[u8]> {
+	Elements::new(data).find(|(element_id, _)| *element_id == id).map(|(_, payload)| payload)
+}
+
+fn read_uint(payload: &[u8]) -> Option<u64> {
+	if payload.is_empty() || payload.len() > 8 {
+		return None;
+	}
+	Some(payload.iter().fold(0, |value, byte| (value << 8) | u64::from(*byte)))
+}
+
+fn read_float(payload: &[u8]) -> Option<f64> {
+	match payload.len() {
+		4 => Some(f64::from(f32::from_be_bytes(payload.try_into().ok()?))),
+		8 => Some(f64::from_be_bytes(payload.try_into().ok()?)),
+		_ => None,
+	}
+}
+
+fn read_string(payload: &[u8]) -> Option<String> {
+	// Strings may be zero-padded to their element size.
+	let end = payload.iter().position(|byte| *byte == 0).unwrap_or(payload.len());
+	let value = std::str::from_utf8(payload.get(.. end)?).ok()?.trim();
+	(!value.is_empty()).then(|| value.to_string())
+}
+
+/// Reads the segment duration and the first video and audio tracks.
+pub fn media_info(data: &[u8]) -> Option<MediaInfo> {
+	let segment = find_element(data, SEGMENT)?;
+	let mut info = MediaInfo::default();
+
+	if let Some(segment_info) = find_element(segment, INFO) {
+		let scale = find_element(segment_info, TIMESTAMP_SCALE)
+			.and_then(read_uint)
+			.unwrap_or(DEFAULT_TIMESTAMP_SCALE);
+		info.duration_seconds = find_element(segment_info, DURATION)
+			.and_then(read_float)
+			.map(|duration| duration * scale as f64 / 1e9)
+			.filter(|seconds| seconds.is_finite() && *seconds > 0.0);
+	}
+
+	let entries = find_element(segment, TRACKS).map(Elements::new).into_iter().flatten();
+	for (_, entry) in entries.filter(|(id, _)| *id == TRACK_ENTRY) {
+		let codec = find_element(entry, CODEC_ID).and_then(read_string).map(codec_name);
+		match find_element(entry, TRACK_TYPE).and_then(read_uint) {
+			Some(TRACK_TYPE_VIDEO) if info.video_codec.is_none() => {
+				let video = find_element(entry, VIDEO);
+				let dimension = |id| {
+					video
+						.and_then(|video| find_element(video, id))
+						.and_then(read_uint)
+						.and_then(|value| u32::try_from(value).ok())
+				};
+				info.width = dimension(PIXEL_WIDTH);
+				info.height = dimension(PIXEL_HEIGHT);
+				info.video_codec = codec;
+			}
+			Some(TRACK_TYPE_AUDIO) if info.audio_codec.is_none() => info.audio_codec = codec,
+			_ => {}
+		}
+	}
+
+	Some(info)
+}
+
+/// Returns the data of the first image attachment, which players conventionally
+/// show as cover art.
+pub fn cover_art(data: &[u8]) -> Option<&[u8]> {
+	let attachments = find_element(find_element(data, SEGMENT)?, ATTACHMENTS)?;
+	Elements::new(attachments)
+		.filter(|(id, _)| *id == ATTACHED_FILE)
+		.map(|(_, file)| file)
+		.find(|file| {
+			find_element(file, FILE_MIME_TYPE)
+				.and_then(read_string)
+				.is_some_and(|mime| mime.starts_with("image/"))
+		})
+		.and_then(|file| find_element(file, FILE_DATA))
+		.filter(|image| !image.is_empty())
+}
+
+/// Maps a Matroska codec ID to the short name used across containers.
+fn codec_name(codec_id: String) -> String {
+	let name = match codec_id.as_str() {
+		"V_MPEG4/ISO/AVC" => "h264",
+		"V_MPEGH/ISO/HEVC" => "hevc",
+		"V_AV1" => "av1",
+		"V_VP8" => "vp8",
+		"V_VP9" => "vp9",
+		"V_THEORA" => "theora",
+		"A_OPUS" => "opus",
+		"A_VORBIS" => "vorbis",
+		"A_FLAC" => "flac",
+		"A_MPEG/L3" => "mp3",
+		id if id.starts_with("A_AAC") => "aac",
+		_ => return codec_id.to_ascii_lowercase(),
+	};
+	name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		ATTACHED_FILE,
+		ATTACHMENTS,
+		CODEC_ID,
+		DURATION,
+		EBML,
+		FILE_DATA,
+		FILE_MIME_TYPE,
+		INFO,
+		PIXEL_HEIGHT,
+		PIXEL_WIDTH,
+		TIMESTAMP_SCALE,
+		TRACK_ENTRY,
+		TRACK_TYPE,
+		TRACKS,
+		VIDEO,
+		cover_art,
+		is_matroska,
+		media_info,
+	};
+
+	/// Encodes an element with a minimal-length ID and an 8-byte size.
+	fn element(
+		id: u32,
+		payload: &[u8],
+	) -> Vec<u8> {
+		let mut bytes =
+			id.to_be_bytes().into_iter().skip_while(|byte| *byte == 0).collect::<Vec<_>>();
+		bytes.push(0x01);
+		bytes.extend_from_slice(&(payload.len() as u64).to_be_bytes()[1 ..]);
+		bytes.extend_from_slice(payload);
+		bytes
+	}
+
+	fn webm() -> Vec<u8> {
+		let info = [
+			element(TIMESTAMP_SCALE, &[0x0f, 0x42, 0x40]),
+			element(DURATION, &2500.0f64.to_be_bytes()),
+		]
+		.concat();
+		let video =
+			[element(PIXEL_WIDTH, &[0x02, 0x80]), element(PIXEL_HEIGHT, &[0x01, 0xe0])].concat();
+		let video_track =
+			[element(TRACK_TYPE, &[1]), element(CODEC_ID, b"V_VP9"), element(VIDEO, &video)]
+				.concat();
+		let audio_track = [element(TRACK_TYPE, &[2]), element(CODEC_ID, b"A_OPUS\0\0")].concat();
+		let tracks =
+			[element(TRACK_ENTRY, &audio_track), element(TRACK_ENTRY, &video_track)].concat();
+		let font = [element(FILE_MIME_TYPE, b"font/ttf"), element(FILE_DATA, b"font")].concat();
+		let cover =
+			[element(FILE_MIME_TYPE, b"image/jpeg"), element(FILE_DATA, b"\xff\xd8cover")].concat();
+		let attachments = [element(ATTACHED_FILE, &font), element(ATTACHED_FILE, &cover)].concat();
+		let segment = [
+			element(INFO, &info),
+			element(TRACKS, &tracks),
+			element(ATTACHMENTS, &attachments),
+			// A cluster cut off by the head read.
+			element(0x1f43_b675, &[0; 64]),
+		]
+		.concat();
+
+		// The segment size is unknown, as in a live-streamed file.
+		let mut file = element(EBML, &element(0x4282, b"webm"));
+		file.extend_from_slice(&[0x18, 0x53, 0x80, 0x67, 0xff]);
+		file.extend_from_slice(&segment);
+		file.truncate(file.len() - 32);
+		file
+	}
+
+	#[test]
+	fn media_info_reads_duration_and_first_tracks() {
+		let file = webm();
+		assert!(is_matroska(&file));
+
+		let info = media_info(&file).unwrap_or_default();
+		assert_eq!(info.duration_seconds, Some(2.5));
+		assert_eq!((info.width, info.height), (Some(640), Some(480)));
+		assert_eq!(info.video_codec.as_deref(), Some("vp9"));
+		assert_eq!(info.audio_codec.as_deref(), Some("opus"));
+	}
+
+	#[test]
+	fn cover_art_is_the_first_image_attachment() {
+		assert_eq!(cover_art(&webm()), Some(b"\xff\xd8cover".as_slice()));
+	}
+
+	#[test]
+	fn non_matroska_input_yields_nothing() {
+		assert!(!is_matroska(b"OggS\0\x02"));
+		assert!(media_info(b"\x1a\x45\xdf\xa3\x80").is_none());
+		assert!(!is_matroska(b""));
+	}
+}
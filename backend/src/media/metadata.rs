@@ -0,0 +1,444 @@
+//! Capture-time and GPS extraction from embedded EXIF, XMP, and QuickTime
+//! metadata.
+//!
+//! Every source is best-effort: a malformed or absent block contributes nothing
+//! rather than failing the extraction. When sources disagree, EXIF wins over
+//! XMP, which wins over QuickTime, matching how reliably each is written by
+//! cameras and phones.
+
+use {
+	super::{
+		MetadataSource,
+		isobmff::{
+			self,
+			MOVIE,
+			MOVIE_HEADER,
+			MovieHeader,
+			QUICKTIME_LOCATION,
+			USER_DATA,
+		},
+	},
+	crate::graphql::objects::location::Location,
+	exif::{
+		Exif,
+		Field,
+		In,
+		Tag,
+		Value,
+	},
+	jiff::{
+		Timestamp,
+		civil,
+		tz::Offset,
+	},
+	serde_json::{
+		Map,
+		Value as JsonValue,
+	},
+	std::io::Cursor,
+};
+
+/// Longest raw value kept per field. Maker notes and embedded blobs can run to
+/// kilobytes and are useless for display.
+const MAX_RAW_VALUE_LENGTH: usize = 256;
+
+/// Capture metadata extracted from a stored object.
+#[derive(Clone, Debug, Default)]
+pub struct ExtractedMetadata {
+	pub captured_at: Option<Timestamp>,
+	pub location: Option<Location>,
+	/// Raw fields grouped by source (`exif`, `xmp`, `quicktime`), as displayable
+	/// strings.
+	pub raw: Map<String, JsonValue>,
+}
+
+impl ExtractedMetadata {
+	pub fn is_empty(&self) -> bool {
+		self.captured_at.is_none() && self.location.is_none() && self.raw.is_empty()
+	}
+
+	fn merge_fallback(
+		&mut self,
+		source: &str,
+		fallback: Self,
+	) {
+		self.captured_at = self.captured_at.or(fallback.captured_at);
+		self.location = self.location.take().or(fallback.location);
+		if !fallback.raw.is_empty() {
+			self.raw.insert(source.to_string(), JsonValue::Object(fallback.raw));
+		}
+	}
+}
+
+/// Extracts capture time, location, and raw fields from `source`.
+pub fn extract_metadata(source: &MetadataSource) -> ExtractedMetadata {
+	let mut metadata = ExtractedMetadata::default();
+	metadata.merge_fallback("exif", exif_metadata(&source.head));
+	metadata.merge_fallback("xmp", xmp_metadata(&source.head));
+	if isobmff::is_isobmff(&source.head) {
+		let movie = source.movie.as_deref().unwrap_or(&source.head);
+		metadata.merge_fallback("quicktime", quicktime_metadata(movie));
+	}
+	metadata
+}
+
+fn exif_metadata(bytes: &[u8]) -> ExtractedMetadata {
+	let Ok(exif) = exif::Reader::new().read_from_container(&mut Cursor::new(bytes)) else {
+		return ExtractedMetadata::default();
+	};
+
+	let raw = exif
+		.fields()
+		.filter(|field| field.ifd_num == In::PRIMARY && field.tag != Tag::MakerNote)
+		.map(|field| {
+			let value = field.display_value().with_unit(&exif).to_string();
+			(field.tag.to_string(), JsonValue::String(truncate_raw_value(value)))
+		})
+		.collect();
+	ExtractedMetadata {
+		captured_at: exif_captured_at(&exif),
+		location: exif_location(&exif),
+		raw,
+	}
+}
+
+/// The first usable of DateTimeOriginal, DateTimeDigitized, and DateTime, with
+/// its matching OffsetTime*/SubSecTime* companions when present. EXIF times
+/// without an offset are local wall-clock times in an unknown zone; they are
+/// interpreted as UTC, which is what a user would have typed in anyway.
+fn exif_captured_at(exif: &Exif) -> Option<Timestamp> {
+	[
+		(Tag::DateTimeOriginal, Tag::OffsetTimeOriginal, Tag::SubSecTimeOriginal),
+		(Tag::DateTimeDigitized, Tag::OffsetTimeDigitized, Tag::SubSecTimeDigitized),
+		(Tag::DateTime, Tag::OffsetTime, Tag::SubSecTime),
+	]
+	.into_iter()
+	.find_map(|(date_time_tag, offset_tag, subsec_tag)| {
+		let mut date_time = exif::DateTime::from_ascii(exif_ascii(exif, date_time_tag)?).ok()?;
+		if let Some(offset) = exif_ascii(exif, offset_tag) {
+			let _ = date_time.parse_offset(offset);
+		}
+		if let Some(subsec) = exif_ascii(exif, subsec_tag) {
+			let _ = date_time.parse_subsec(subsec);
+		}
+		let civil = civil::DateTime::new(
+			i16::try_from(date_time.year).ok()?,
+			i8::try_from(date_time.month).ok()?,
+			i8::try_from(date_time.day).ok()?,
+			i8::try_from(date_time.hour).ok()?,
+			i8::try_from(date_time.minute).ok()?,
+			i8::try_from(date_time.second).ok()?,
+			i32::try_from(date_time.nanosecond.unwrap_or(0)).ok()?,
+		)
+		.ok()?;
+		let offset = Offset::from_seconds(i32::from(date_time.offset.unwrap_or(0)) * 60).ok()?;
+		offset.to_timestamp(civil).ok()
+	})
+}
+
+fn exif_location(exif: &Exif) -> Option<Location> {
+	let latitude = exif_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S')?;
+	let longitude = exif_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W')?;
+	Location {
+		latitude,
+		longitude,
+	}
+	.validated()
+	.ok()
+}
+
+fn exif_coordinate(
+	exif: &Exif,
+	tag: Tag,
+	reference_tag: Tag,
+	negative_reference: u8,
+) -> Option<f64> {
+	let Value::Rational(parts) = &exif_field(exif, tag)?.value else {
+		return None;
+	};
+	let mut degrees = 0.0;
+	for (part, divisor) in parts.iter().zip([1.0, 60.0, 3600.0]) {
+		if part.denom == 0 {
+			return None;
+		}
+		degrees += part.to_f64() / divisor;
+	}
+	let negative = exif_ascii(exif, reference_tag)
+		.and_then(|reference| reference.first())
+		.is_some_and(|reference| reference.eq_ignore_ascii_case(&negative_reference));
+	Some(if negative { -degrees } else { degrees })
+}
+
+fn exif_field(
+	exif: &Exif,
+	tag: Tag,
+) -> Option<&Field> {
+	exif.get_field(tag, In::PRIMARY)
+}
+
+fn exif_ascii(
+	exif: &Exif,
+	tag: Tag,
+) -> Option<&[u8]> {
+	match &exif_field(exif, tag)?.value {
+		Value::Ascii(values) => values.first().map(Vec::as_slice),
+		_ => None,
+	}
+}
+
+/// XMP properties read from the first `x:xmpmeta` packet, in capture-time
+/// priority order for the date properties.
+const XMP_DATE_PROPERTIES: [&str; 4] =
+	["exif:DateTimeOriginal", "photoshop:DateCreated", "xmp:CreateDate", "exif:DateTimeDigitized"];
+const XMP_GPS_PROPERTIES: [&str; 2] = ["exif:GPSLatitude", "exif:GPSLongitude"];
+
+fn xmp_metadata(bytes: &[u8]) -> ExtractedMetadata {
+	let Some(packet) = xmp_packet(bytes) else {
+		return ExtractedMetadata::default();
+	};
+
+	let mut raw = Map::new();
+	for property in XMP_DATE_PROPERTIES.iter().chain(&XMP_GPS_PROPERTIES) {
+		if let Some(value) = xmp_property(packet, property) {
+			raw.insert((*property).to_string(), JsonValue::String(truncate_raw_value(value)));
+		}
+	}
+	let captured_at = XMP_DATE_PROPERTIES
+		.iter()
+		.find_map(|property| parse_xmp_date(&xmp_property(packet, property)?));
+	let location = xmp_location(packet);
+
+	ExtractedMetadata {
+		captured_at,
+		location,
+		raw,
+	}
+}
+
+fn xmp_packet(bytes: &[u8]) -> Option<&str> {
+	let start = find_subslice(bytes, b"<x:xmpmeta")?;
+	let rest = bytes.get(start ..)?;
+	let end = find_subslice(rest, b"</x:xmpmeta>")? + b"</x:xmpmeta>".len();
+	std::str::from_utf8(rest.get(.. end)?).ok()
+}
+
+/// Reads an XMP property written either as an attribute (`name="value"`) or as
+/// a simple element (`<name>value</name>`).
+fn xmp_property(
+	packet: &str,
+	name: &str,
+) -> Option<String> {
+	let attribute = format!("{name}=");
+	if let Some(index) = packet.find(&attribute) {
+		let rest = packet.get(index + attribute.len() ..)?;
+		let quote = rest.chars().next().filter(|quote| matches!(quote, '"' | '\''))?;
+		let value = rest.get(1 ..)?.split(quote).next()?;
+		return Some(value.trim().to_string());
+	}
+	let open = format!("<{name}>");
+	let index = packet.find(&open)?;
+	let rest = packet.get(index + open.len() ..)?;
+	let value = rest.get(.. rest.find(&format!("</{name}>"))?)?;
+	(!value.contains('<')).then(|| value.trim().to_string())
+}
+
+/// XMP dates are ISO 8601 with optional time, fraction, and offset. Values
+/// without an offset are interpreted as UTC, as for EXIF.
+fn parse_xmp_date(value: &str) -> Option<Timestamp> {
+	if let Ok(timestamp) = value.parse::<Timestamp>() {
+		return Some(timestamp);
+	}
+	let civil = value
+		.parse::<civil::DateTime>()
+		.or_else(|_| value.parse::<civil::Date>().map(civil::DateTime::from))
+		.ok()?;
+	Offset::UTC.to_timestamp(civil).ok()
+}
+
+fn xmp_location(packet: &str) -> Option<Location> {
+	Location {
+		latitude: xmp_coordinate(&xmp_property(packet, "exif:GPSLatitude")?)?,
+		longitude: xmp_coordinate(&xmp_property(packet, "exif:GPSLongitude")?)?,
+	}
+	.validated()
+	.ok()
+}
+
+/// XMP GPS coordinates are `DDD,MM.mmk` or `DDD,MM,SSk`, where `k` is one of
+/// N, S, E, W.
+fn xmp_coordinate(value: &str) -> Option<f64> {
+	let (numbers, reference) = value.split_at_checked(value.len().checked_sub(1)?)?;
+	let mut degrees = 0.0;
+	for (part, divisor) in numbers.split(',').zip([1.0, 60.0, 3600.0]) {
+		degrees += part.trim().parse::<f64>().ok()? / divisor;
+	}
+	match reference {
+		"N" | "E" => Some(degrees),
+		"S" | "W" => Some(-degrees),
+		_ => None,
+	}
+}
+
+fn quicktime_metadata(movie: &[u8]) -> ExtractedMetadata {
+	let mut raw = Map::new();
+	let creation_time = isobmff::find_box(movie, &[MOVIE, MOVIE_HEADER])
+		.and_then(MovieHeader::parse)
+		.and_then(|header| header.creation_time);
+	if let Some(creation_time) = creation_time {
+		raw.insert("creation_time".to_string(), JsonValue::String(creation_time.to_string()));
+	}
+	let location_text = isobmff::find_box(movie, &[MOVIE, USER_DATA, QUICKTIME_LOCATION])
+		.and_then(isobmff::quicktime_location_text);
+	let location = location_text.as_deref().and_then(isobmff::parse_iso6709);
+	if let Some(location_text) = location_text {
+		raw.insert("location".to_string(), JsonValue::String(truncate_raw_value(location_text)));
+	}
+
+	ExtractedMetadata {
+		captured_at: creation_time,
+		location,
+		raw,
+	}
+}
+
+fn truncate_raw_value(mut value: String) -> String {
+	if value.len() > MAX_RAW_VALUE_LENGTH {
+		let boundary =
+			(0 ..= MAX_RAW_VALUE_LENGTH).rev().find(|index| value.is_char_boundary(*index));
+		value.truncate(boundary.unwrap_or(0));
+	}
+	value
+}
+
+pub(super) fn find_subslice(
+	haystack: &[u8],
+	needle: &[u8],
+) -> Option<usize> {
+	haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			extract_metadata,
+			parse_xmp_date,
+			xmp_coordinate,
+		},
+		crate::media::MetadataSource,
+		exif::{
+			Field,
+			In,
+			Rational,
+			Tag,
+			Value,
+			experimental::Writer,
+		},
+	};
+
+	fn source(head: &[u8]) -> MetadataSource {
+		MetadataSource {
+			head: head.to_vec(),
+			movie: None,
+			tail: None,
+		}
+	}
+
+	fn ascii_field(
+		tag: Tag,
+		value: &str,
+	) -> Field {
+		Field {
+			tag,
+			ifd_num: In::PRIMARY,
+			value: Value::Ascii(vec![value.as_bytes().to_vec()]),
+		}
+	}
+
+	fn degrees_field(
+		tag: Tag,
+		degrees: u32,
+		minutes: u32,
+	) -> Field {
+		Field {
+			tag,
+			ifd_num: In::PRIMARY,
+			value: Value::Rational(
+				[degrees, minutes, 0]
+					.into_iter()
+					.map(|num| Rational {
+						num,
+						denom: 1,
+					})
+					.collect(),
+			),
+		}
+	}
+
+	#[test]
+	fn exif_supplies_offset_capture_time_and_signed_location() -> anyhow::Result<()> {
+		let fields = [
+			ascii_field(Tag::DateTimeOriginal, "2022:03:04 05:06:07"),
+			ascii_field(Tag::OffsetTimeOriginal, "+09:00"),
+			degrees_field(Tag::GPSLatitude, 33, 30),
+			ascii_field(Tag::GPSLatitudeRef, "S"),
+			degrees_field(Tag::GPSLongitude, 151, 15),
+			ascii_field(Tag::GPSLongitudeRef, "E"),
+		];
+		let mut writer = Writer::new();
+		for field in &fields {
+			writer.push_field(field);
+		}
+		let mut tiff = std::io::Cursor::new(Vec::new());
+		writer.write(&mut tiff, false)?;
+
+		let metadata = extract_metadata(&source(tiff.get_ref()));
+
+		assert_eq!(
+			metadata.captured_at.map(|time| time.to_string()).as_deref(),
+			Some("2022-03-03T20:06:07Z")
+		);
+		assert_eq!(
+			metadata.location.map(|location| (location.latitude, location.longitude)),
+			Some((-33.5, 151.25))
+		);
+		assert!(
+			metadata.raw.get("exif").is_some_and(|exif| exif.get("DateTimeOriginal").is_some())
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn xmp_packet_supplies_capture_time_and_location() {
+		let head = br#"garbage<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description
+			exif:DateTimeOriginal="2023-06-01T12:30:00+02:00"
+			exif:GPSLatitude="51,30.0N" exif:GPSLongitude="0,7,30W"/></x:xmpmeta>trailer"#;
+
+		let metadata = extract_metadata(&source(head));
+
+		assert_eq!(
+			metadata.captured_at.map(|time| time.to_string()).as_deref(),
+			Some("2023-06-01T10:30:00Z")
+		);
+		assert_eq!(
+			metadata.location.map(|location| (location.latitude, location.longitude)),
+			Some((51.5, -0.125))
+		);
+		assert!(metadata.raw.get("xmp").is_some_and(|xmp| xmp.get("exif:GPSLatitude").is_some()));
+	}
+
+	#[test]
+	fn xmp_values_parse_in_their_documented_forms() {
+		assert_eq!(xmp_coordinate("37,46.5N"), Some(37.775));
+		assert_eq!(xmp_coordinate("122,25,12W"), Some(-122.42));
+		assert_eq!(xmp_coordinate("12.5X"), None);
+		assert!(parse_xmp_date("2020-02-03").is_some());
+		assert!(parse_xmp_date("2020-02-03T04:05:06").is_some());
+		assert!(parse_xmp_date("not a date").is_none());
+	}
+
+	#[test]
+	fn unrecognised_bytes_extract_nothing() {
+		assert!(extract_metadata(&source(b"plain bytes with no metadata")).is_empty());
+	}
+}
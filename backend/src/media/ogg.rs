@@ -0,0 +1,290 @@
+//! A minimal Ogg page reader for Theora, Vorbis and Opus streams.
+//!
+//! Stream types and parameters come from the identification header that opens
+//! each logical stream, found in the head of the file. Ogg stores no duration,
+//! so it is derived from the granule position of each stream's last page,
+//! which lies in the tail of the file.
+
+use super::MediaInfo;
+
+const CAPTURE_PATTERN: &[u8] = b"OggS";
+/// Capture pattern, version, header type, granule position, serial number,
+/// page sequence number, checksum and segment count.
+const PAGE_HEADER_LEN: usize = 27;
+const BEGINNING_OF_STREAM: u8 = 0x02;
+/// The sample rate Opus granule positions count in, whatever the input rate.
+const OPUS_GRANULE_RATE: f64 = 48_000.0;
+
+/// One page, with its body clipped to the bytes present.
+#[derive(Clone, Copy, Debug)]
+struct Page<'a> {
+	header_type: u8,
+	/// `None` when no packet ends on the page (encoded as -1).
+	granule_position: Option<u64>,
+	serial: u32,
+	body: &'a [u8],
+	len: usize,
+}
+
+impl<'a> Page<'a> {
+	fn parse(bytes: &'a [u8]) -> Option<Self> {
+		if bytes.get(.. 4)? != CAPTURE_PATTERN || *bytes.get(4)? != 0 {
+			return None;
+		}
+		let granule_position = u64::from_le_bytes(bytes.get(6 .. 14)?.try_into().ok()?);
+		let segments = usize::from(*bytes.get(26)?);
+		let lacing = bytes.get(PAGE_HEADER_LEN .. PAGE_HEADER_LEN + segments)?;
+		let body_start = PAGE_HEADER_LEN + segments;
+		let len = body_start + lacing.iter().map(|value| usize::from(*value)).sum::<usize>();
+		Some(Self {
+			header_type: *bytes.get(5)?,
+			granule_position: (granule_position != u64::MAX).then_some(granule_position),
+			serial: u32::from_le_bytes(bytes.get(14 .. 18)?.try_into().ok()?),
+			body: bytes.get(body_start .. len.min(bytes.len()))?,
+			len,
+		})
+	}
+}
+
+/// Yields the pages of `data`, resynchronizing on the capture pattern so a
+/// slice that starts mid-page (such as the tail of a file) still parses.
+fn pages(data: &[u8]) -> impl Iterator<Item = Page<'_>> {
+	let mut rest = data;
+	std::iter::from_fn(move || {
+		loop {
+			let start = super::metadata::find_subslice(rest, CAPTURE_PATTERN)?;
+			rest = rest.get(start ..)?;
+			match Page::parse(rest) {
+				Some(page) => {
+					rest = rest.get(page.len ..).unwrap_or_default();
+					return Some(page);
+				}
+				None => rest = rest.get(1 ..)?,
+			}
+		}
+	})
+}
+
+/// Whether `bytes` begins with an Ogg page.
+pub fn is_ogg(bytes: &[u8]) -> bool {
+	bytes.starts_with(CAPTURE_PATTERN)
+}
+
+/// A logical stream as described by its identification header.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stream {
+	Theora { width: u32, height: u32, frames_per_second: f64, keyframe_granule_shift: u8 },
+	Vorbis { sample_rate: u32 },
+	Opus { pre_skip: u16 },
+}
+
+impl Stream {
+	fn parse(header: &[u8]) -> Option<Self> {
+		if header.starts_with(b"\x80theora") {
+			let u24 = |offset: usize| {
+				let bytes = header.get(offset .. offset + 3)?;
+				Some(bytes.iter().fold(0, |value, byte| (value << 8) | u32::from(*byte)))
+			};
+			let u32_at = |offset: usize| {
+				Some(u32::from_be_bytes(header.get(offset .. offset + 4)?.try_into().ok()?))
+			};
+			let numerator = u32_at(22)?;
+			let denominator = u32_at(26)?;
+			let shift_bits = u16::from_be_bytes(header.get(40 .. 42)?.try_into().ok()?);
+			Some(Self::Theora {
+				width: u24(14)?,
+				height: u24(17)?,
+				frames_per_second: (denominator > 0)
+					.then(|| f64::from(numerator) / f64::from(denominator))?,
+				// Five bits following the six-bit quality hint.
+				keyframe_granule_shift: ((shift_bits >> 5) & 0x1f) as u8,
+			})
+		} else if header.starts_with(b"\x01vorbis") {
+			Some(Self::Vorbis {
+				sample_rate: u32::from_le_bytes(header.get(12 .. 16)?.try_into().ok()?),
+			})
+		} else if header.starts_with(b"OpusHead") {
+			Some(Self::Opus {
+				pre_skip: u16::from_le_bytes(header.get(10 .. 12)?.try_into().ok()?),
+			})
+		} else {
+			None
+		}
+	}
+
+	/// The playback time at the end of a page with `granule_position`.
+	fn seconds_at(
+		self,
+		granule_position: u64,
+	) -> Option<f64> {
+		match self {
+			Self::Theora {
+				frames_per_second,
+				keyframe_granule_shift,
+				..
+			} => {
+				// The granule is the last keyframe's index in the upper bits
+				// plus the frames since it in the lower bits.
+				let shift = u32::from(keyframe_granule_shift);
+				let keyframe = granule_position.checked_shr(shift).unwrap_or(0);
+				let delta =
+					granule_position & (1u64.checked_shl(shift).unwrap_or(0).wrapping_sub(1));
+				Some((keyframe + delta) as f64 / frames_per_second)
+			}
+			Self::Vorbis {
+				sample_rate,
+			} => (sample_rate > 0).then(|| granule_position as f64 / f64::from(sample_rate)),
+			Self::Opus {
+				pre_skip,
+			} => Some(
+				granule_position.saturating_sub(u64::from(pre_skip)) as f64 / OPUS_GRANULE_RATE,
+			),
+		}
+	}
+
+	fn codec_name(self) -> &'static str {
+		match self {
+			Self::Theora {
+				..
+			} => "theora",
+			Self::Vorbis {
+				..
+			} => "vorbis",
+			Self::Opus {
+				..
+			} => "opus",
+		}
+	}
+}
+
+/// Reads the stream types from the beginning-of-stream pages in `head`, and the
+/// duration of the longest stream from the last pages found in `tail` (or in
+/// `head`, when it holds the whole file).
+pub fn media_info(
+	head: &[u8],
+	tail: Option<&[u8]>,
+) -> Option<MediaInfo> {
+	let streams = pages(head)
+		.take_while(|page| page.header_type & BEGINNING_OF_STREAM != 0)
+		.filter_map(|page| Some((page.serial, Stream::parse(page.body)?)))
+		.collect::<Vec<_>>();
+	if streams.is_empty() {
+		return None;
+	}
+
+	let mut info = MediaInfo::default();
+	for (_, stream) in &streams {
+		match *stream {
+			Stream::Theora {
+				width,
+				height,
+				..
+			} if info.video_codec.is_none() => {
+				info.width = Some(width);
+				info.height = Some(height);
+				info.video_codec = Some(stream.codec_name().to_string());
+			}
+			Stream::Vorbis {
+				..
+			} |
+			Stream::Opus {
+				..
+			} if info.audio_codec.is_none() => {
+				info.audio_codec = Some(stream.codec_name().to_string());
+			}
+			_ => {}
+		}
+	}
+
+	info.duration_seconds = pages(tail.unwrap_or(head))
+		.filter_map(|page| {
+			let (_, stream) = streams.iter().find(|(serial, _)| *serial == page.serial)?;
+			stream.seconds_at(page.granule_position?)
+		})
+		.fold(None, |longest: Option<f64>, seconds| {
+			Some(longest.map_or(seconds, |longest| longest.max(seconds)))
+		})
+		.filter(|seconds| *seconds > 0.0);
+	Some(info)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{
+		is_ogg,
+		media_info,
+	};
+
+	fn page(
+		header_type: u8,
+		granule_position: u64,
+		serial: u32,
+		body: &[u8],
+	) -> Vec<u8> {
+		let mut bytes = b"OggS\0".to_vec();
+		bytes.push(header_type);
+		bytes.extend_from_slice(&granule_position.to_le_bytes());
+		bytes.extend_from_slice(&serial.to_le_bytes());
+		bytes.extend_from_slice(&[0; 8]);
+		let mut lacing = vec![255; body.len() / 255];
+		lacing.push((body.len() % 255) as u8);
+		bytes.push(lacing.len() as u8);
+		bytes.extend_from_slice(&lacing);
+		bytes.extend_from_slice(body);
+		bytes
+	}
+
+	fn theora_header() -> Vec<u8> {
+		let mut header = b"\x80theora\x03\x02\x01".to_vec();
+		// Frame size in macroblocks, then the 320x240 picture at offset 0,0.
+		header.extend_from_slice(&[0, 20, 0, 15, 0, 1, 0x40, 0, 0, 0xf0, 0, 0]);
+		// 25 frames per second, square pixels, colour space and bitrate.
+		header.extend_from_slice(&[0, 0, 0, 25, 0, 0, 0, 1, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0]);
+		// Quality hint 0, keyframe granule shift 6.
+		header.extend_from_slice(&[0b0000_0000, 0b1100_0000]);
+		header
+	}
+
+	#[test]
+	fn media_info_reads_streams_from_head_and_duration_from_tail() {
+		let mut vorbis = b"\x01vorbis\0\0\0\0\x02".to_vec();
+		vorbis.extend_from_slice(&44_100u32.to_le_bytes());
+		vorbis.extend_from_slice(&[0; 14]);
+		let mut head = page(0x02, 0, 1, &theora_header());
+		head.extend_from_slice(&page(0x02, 0, 2, &vorbis));
+		head.extend_from_slice(&page(0x00, u64::MAX, 1, &[0; 300]));
+
+		// The tail starts mid-page. Theora reaches keyframe 100 plus 24 frames
+		// (4.96s); Vorbis ends at 5s.
+		let mut tail = vec![0x4f, 0x67, 0x67, 0x53, 0xff];
+		tail.extend_from_slice(&page(0x00, (100 << 6) | 24, 1, &[0; 10]));
+		tail.extend_from_slice(&page(0x04, 220_500, 2, &[0; 10]));
+
+		assert!(is_ogg(&head));
+		let info = media_info(&head, Some(&tail)).unwrap_or_default();
+		assert_eq!((info.width, info.height), (Some(320), Some(240)));
+		assert_eq!(info.video_codec.as_deref(), Some("theora"));
+		assert_eq!(info.audio_codec.as_deref(), Some("vorbis"));
+		assert_eq!(info.duration_seconds, Some(5.0));
+	}
+
+	#[test]
+	fn opus_durations_discount_pre_skip() {
+		let mut opus_head = b"OpusHead\x01\x02".to_vec();
+		opus_head.extend_from_slice(&312u16.to_le_bytes());
+		opus_head.extend_from_slice(&[0x80, 0xbb, 0, 0, 0, 0, 0]);
+		let mut file = page(0x02, 0, 9, &opus_head);
+		file.extend_from_slice(&page(0x04, 96_312, 9, &[0; 10]));
+
+		let info = media_info(&file, None).unwrap_or_default();
+		assert_eq!(info.audio_codec.as_deref(), Some("opus"));
+		assert_eq!(info.video_codec, None);
+		assert_eq!(info.duration_seconds, Some(2.0));
+	}
+
+	#[test]
+	fn unknown_streams_yield_nothing() {
+		assert!(media_info(&page(0x02, 0, 1, b"\x80kate\0\0\0"), None).is_none());
+		assert!(media_info(b"not ogg", None).is_none());
+	}
+}
@@ -0,0 +1,107 @@
+//! Perceptual hashes of uploaded images, for finding near-duplicates.
+//!
+//! A difference hash ("dHash") shrinks the image to 9x8 greyscale pixels and
+//! records, for each of the 64 horizontally adjacent pairs, whether brightness
+//! rises. Resizing, recompression and small edits barely change the bits, so
+//! the Hamming distance between two hashes (the number of differing bits,
+//! which the database computes) measures how alike the images look.
+
+use {
+	super::thumbnail::decode_oriented,
+	image::imageops::FilterType,
+};
+
+/// Content types the hash is computed for: the raster formats the image
+/// decoder reads.
+pub const PERCEPTUAL_HASH_CONTENT_TYPES: [&str; 5] =
+	["image/png", "image/apng", "image/jpeg", "image/gif", "image/webp"];
+/// The hash compares each of this many columns with the one to its right.
+const HASH_WIDTH: u32 = 8;
+const HASH_HEIGHT: u32 = 8;
+
+/// Hashes `source` after applying its EXIF orientation, so a rotated copy
+/// hashes like the original.
+pub fn difference_hash(source: &[u8]) -> anyhow::Result<u64> {
+	let image = decode_oriented(source)?
+		.resize_exact(HASH_WIDTH + 1, HASH_HEIGHT, FilterType::Triangle)
+		.into_luma8();
+	let mut hash = 0u64;
+	for y in 0 .. HASH_HEIGHT {
+		for x in 0 .. HASH_WIDTH {
+			let left = image.get_pixel(x, y).0;
+			let right = image.get_pixel(x + 1, y).0;
+			hash = (hash << 1) | u64::from(left < right);
+		}
+	}
+	Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::difference_hash,
+		image::{
+			DynamicImage,
+			ImageFormat,
+			RgbImage,
+			imageops::FilterType,
+		},
+		std::io::Cursor,
+	};
+
+	/// A diagonal gradient with a bright square, distinct enough in every row
+	/// for the hash to carry information.
+	fn scene(
+		width: u32,
+		height: u32,
+		flipped: bool,
+	) -> DynamicImage {
+		DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+			let x = if flipped { width - 1 - x } else { x };
+			let shade = ((x * 255 / width + y * 128 / height) % 256) as u8;
+			let square = x > width / 3 && x < width / 2 && y > height / 4 && y < height / 2;
+			if square { image::Rgb([255, 255, 255]) } else { image::Rgb([shade, shade / 2, 64]) }
+		}))
+	}
+
+	fn hamming_distance(
+		a: u64,
+		b: u64,
+	) -> u32 {
+		(a ^ b).count_ones()
+	}
+
+	fn encode(
+		image: &DynamicImage,
+		format: ImageFormat,
+	) -> anyhow::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		image.write_to(&mut Cursor::new(&mut bytes), format)?;
+		Ok(bytes)
+	}
+
+	#[test]
+	fn resized_and_recompressed_copies_hash_alike() -> anyhow::Result<()> {
+		let original = scene(640, 480, false);
+		let original_hash = difference_hash(&encode(&original, ImageFormat::Png)?)?;
+		let resized = original.resize(200, 150, FilterType::Lanczos3);
+		let resized_hash = difference_hash(&encode(&resized, ImageFormat::Jpeg)?)?;
+
+		assert!(hamming_distance(original_hash, resized_hash) <= 4);
+		Ok(())
+	}
+
+	#[test]
+	fn different_images_hash_apart() -> anyhow::Result<()> {
+		let original = difference_hash(&encode(&scene(640, 480, false), ImageFormat::Png)?)?;
+		let mirrored = difference_hash(&encode(&scene(640, 480, true), ImageFormat::Png)?)?;
+
+		assert!(hamming_distance(original, mirrored) > 20);
+		Ok(())
+	}
+
+	#[test]
+	fn undecodable_input_is_an_error() {
+		assert!(difference_hash(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>").is_err());
+	}
+}
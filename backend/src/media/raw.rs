@@ -0,0 +1,364 @@
+//! Previews embedded in TIFF-based camera RAW files (DNG and CR2).
+//!
+//! Sensor data has to be demosaiced before it is an image, which is well beyond
+//! what the derivative worker does. Cameras and DNG converters embed JPEG
+//! previews, though, usually one at full size, and those are all a
+//! browser-viewable rendition needs. They are found by walking the file's
+//! image file directories (IFDs) and their sub-IFDs.
+
+use {
+	image::{
+		DynamicImage,
+		ImageFormat,
+		ImageReader,
+		metadata::Orientation,
+	},
+	std::io::Cursor,
+};
+
+const COMPRESSION: u16 = 0x0103;
+const STRIP_OFFSETS: u16 = 0x0111;
+const ORIENTATION: u16 = 0x0112;
+const STRIP_BYTE_COUNTS: u16 = 0x0117;
+const SUB_IFDS: u16 = 0x014a;
+const JPEG_INTERCHANGE_FORMAT: u16 = 0x0201;
+const JPEG_INTERCHANGE_FORMAT_LENGTH: u16 = 0x0202;
+const DNG_VERSION: u16 = 0xc612;
+/// Compression values of JPEG-coded images, old-style and new-style.
+const JPEG_COMPRESSIONS: [u32; 2] = [6, 7];
+/// How many IFDs are visited at most, so a malformed file cannot keep the walk
+/// going.
+const MAX_IFDS: usize = 32;
+/// How many values of one entry are read at most.
+const MAX_VALUES: usize = 64;
+
+/// Whether `head` starts with a TIFF header, in either byte order.
+pub fn is_tiff(head: &[u8]) -> bool {
+	Tiff::parse(head).is_some()
+}
+
+/// A Canon CR2 file: a TIFF header followed by `CR` and major version 2.
+pub fn is_cr2(head: &[u8]) -> bool {
+	is_tiff(head) && head.get(8 .. 10) == Some(b"CR") && head.get(10) == Some(&2)
+}
+
+/// A DNG file: a TIFF whose first IFD carries a DNGVersion tag.
+pub fn is_dng(head: &[u8]) -> bool {
+	Tiff::parse(head)
+		.and_then(|tiff| tiff.ifd(tiff.first_ifd()?))
+		.is_some_and(|ifd| ifd.entry(DNG_VERSION).is_some())
+}
+
+/// Whether previews can be taken from objects of `content_type`.
+pub fn supports_raw_previews(content_type: &str) -> bool {
+	matches!(content_type, "image/x-adobe-dng" | "image/x-canon-cr2")
+}
+
+/// Decodes the largest embedded JPEG preview the decoder accepts and orients it
+/// as the file's first IFD says; the previews themselves rarely carry an
+/// orientation. Lossless JPEG, which most RAW files code their sensor data
+/// in, is not decodable and so is passed over. `None` when no preview decodes.
+pub fn decode_embedded_preview(source: &[u8]) -> Option<DynamicImage> {
+	let tiff = Tiff::parse(source)?;
+	let first_ifd = tiff.ifd(tiff.first_ifd()?)?;
+	let orientation = first_ifd
+		.value(&tiff, ORIENTATION)
+		.and_then(|value| u8::try_from(value).ok())
+		.and_then(Orientation::from_exif)
+		.unwrap_or(Orientation::NoTransforms);
+	tiff.jpeg_previews().into_iter().find_map(|preview| {
+		let mut image =
+			ImageReader::with_format(Cursor::new(preview), ImageFormat::Jpeg).decode().ok()?;
+		image.apply_orientation(orientation);
+		Some(image)
+	})
+}
+
+#[derive(Clone, Copy)]
+enum ByteOrder {
+	Little,
+	Big,
+}
+
+struct Tiff<'a> {
+	data: &'a [u8],
+	order: ByteOrder,
+}
+
+/// One IFD entry. `value_offset` is where the value starts: inside the entry
+/// when it fits in four bytes, elsewhere in the file otherwise.
+struct Entry {
+	tag: u16,
+	field_type: u16,
+	count: usize,
+	value_offset: usize,
+}
+
+struct Ifd {
+	entries: Vec<Entry>,
+	/// The offset of the next IFD in the chain, 0 after the last.
+	next: usize,
+}
+
+impl<'a> Tiff<'a> {
+	fn parse(data: &'a [u8]) -> Option<Self> {
+		let order = match data.get(.. 4)? {
+			b"II*\0" => ByteOrder::Little,
+			b"MM\0*" => ByteOrder::Big,
+			_ => return None,
+		};
+		Some(Self {
+			data,
+			order,
+		})
+	}
+
+	fn bytes<const N: usize>(
+		&self,
+		offset: usize,
+	) -> Option<[u8; N]> {
+		self.data.get(offset .. offset.checked_add(N)?)?.try_into().ok()
+	}
+
+	fn u16_at(
+		&self,
+		offset: usize,
+	) -> Option<u16> {
+		let bytes = self.bytes(offset)?;
+		Some(match self.order {
+			ByteOrder::Little => u16::from_le_bytes(bytes),
+			ByteOrder::Big => u16::from_be_bytes(bytes),
+		})
+	}
+
+	fn u32_at(
+		&self,
+		offset: usize,
+	) -> Option<u32> {
+		let bytes = self.bytes(offset)?;
+		Some(match self.order {
+			ByteOrder::Little => u32::from_le_bytes(bytes),
+			ByteOrder::Big => u32::from_be_bytes(bytes),
+		})
+	}
+
+	fn first_ifd(&self) -> Option<usize> {
+		self.u32_at(4).and_then(|offset| usize::try_from(offset).ok())
+	}
+
+	fn ifd(
+		&self,
+		offset: usize,
+	) -> Option<Ifd> {
+		let count = usize::from(self.u16_at(offset)?);
+		let mut entries = Vec::with_capacity(count);
+		for index in 0 .. count {
+			let entry = offset + 2 + index * 12;
+			let field_type = self.u16_at(entry + 2)?;
+			let count = usize::try_from(self.u32_at(entry + 4)?).ok()?;
+			let size = match field_type {
+				1 | 2 | 6 | 7 => 1,
+				3 | 8 => 2,
+				_ => 4,
+			};
+			let value_offset = if count.saturating_mul(size) <= 4 {
+				entry + 8
+			} else {
+				usize::try_from(self.u32_at(entry + 8)?).ok()?
+			};
+			entries.push(Entry {
+				tag: self.u16_at(entry)?,
+				field_type,
+				count,
+				value_offset,
+			});
+		}
+		let next = usize::try_from(self.u32_at(offset + 2 + count * 12)?).ok()?;
+		Some(Ifd {
+			entries,
+			next,
+		})
+	}
+
+	/// The integer values of `entry`: BYTE, SHORT, LONG or IFD.
+	fn values(
+		&self,
+		entry: &Entry,
+	) -> Vec<u32> {
+		(0 .. entry.count.min(MAX_VALUES))
+			.map_while(|index| match entry.field_type {
+				1 => self.data.get(entry.value_offset + index).copied().map(u32::from),
+				3 => self.u16_at(entry.value_offset + index * 2).map(u32::from),
+				4 | 13 => self.u32_at(entry.value_offset + index * 4),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Every JPEG embedded as a single strip or a JPEG interchange format
+	/// stream, in any IFD or sub-IFD, largest first.
+	fn jpeg_previews(&self) -> Vec<&'a [u8]> {
+		let mut pending = self.first_ifd().into_iter().collect::<Vec<_>>();
+		let mut visited = Vec::new();
+		let mut previews = Vec::new();
+		while let Some(offset) = pending.pop() {
+			if offset == 0 || visited.contains(&offset) || visited.len() >= MAX_IFDS {
+				continue;
+			}
+			visited.push(offset);
+			let Some(ifd) = self.ifd(offset) else {
+				continue;
+			};
+			pending.push(ifd.next);
+			if let Some(entry) = ifd.entry(SUB_IFDS) {
+				pending.extend(
+					self.values(entry)
+						.into_iter()
+						.filter_map(|offset| usize::try_from(offset).ok()),
+				);
+			}
+
+			let interchange = ifd
+				.value(self, JPEG_INTERCHANGE_FORMAT)
+				.zip(ifd.value(self, JPEG_INTERCHANGE_FORMAT_LENGTH));
+			let strip = ifd
+				.value(self, COMPRESSION)
+				.filter(|compression| JPEG_COMPRESSIONS.contains(compression))
+				.and_then(|_| {
+					Some((
+						ifd.only_value(self, STRIP_OFFSETS)?,
+						ifd.only_value(self, STRIP_BYTE_COUNTS)?,
+					))
+				});
+			for (start, length) in interchange.into_iter().chain(strip) {
+				let (Ok(start), Ok(length)) = (usize::try_from(start), usize::try_from(length))
+				else {
+					continue;
+				};
+				let Some(preview) = self.data.get(start .. start.saturating_add(length)) else {
+					continue;
+				};
+				if preview.starts_with(b"\xff\xd8") && !previews.contains(&preview) {
+					previews.push(preview);
+				}
+			}
+		}
+		previews.sort_by_key(|preview| std::cmp::Reverse(preview.len()));
+		previews
+	}
+}
+
+impl Ifd {
+	fn entry(
+		&self,
+		tag: u16,
+	) -> Option<&Entry> {
+		self.entries.iter().find(|entry| entry.tag == tag)
+	}
+
+	fn value(
+		&self,
+		tiff: &Tiff<'_>,
+		tag: u16,
+	) -> Option<u32> {
+		tiff.values(self.entry(tag)?).first().copied()
+	}
+
+	/// The value of a tag that must hold exactly one, as strip offsets and
+	/// lengths do for an image stored in one piece.
+	fn only_value(
+		&self,
+		tiff: &Tiff<'_>,
+		tag: u16,
+	) -> Option<u32> {
+		match tiff.values(self.entry(tag)?).as_slice() {
+			[value] => Some(*value),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			decode_embedded_preview,
+			is_cr2,
+			is_dng,
+		},
+		image::{
+			DynamicImage,
+			ImageFormat,
+			RgbImage,
+		},
+		std::io::Cursor,
+	};
+
+	/// A little-endian IFD entry whose value fits in the entry.
+	fn entry(
+		tag: u16,
+		field_type: u16,
+		value: u32,
+	) -> Vec<u8> {
+		[
+			tag.to_le_bytes().as_slice(),
+			&field_type.to_le_bytes(),
+			&1u32.to_le_bytes(),
+			&value.to_le_bytes(),
+		]
+		.concat()
+	}
+
+	fn ifd(entries: &[Vec<u8>]) -> Vec<u8> {
+		let count = u16::try_from(entries.len()).unwrap_or_default();
+		[count.to_le_bytes().as_slice(), &entries.concat(), &0u32.to_le_bytes()].concat()
+	}
+
+	/// A TIFF whose only IFD is turned a quarter by its orientation, with a
+	/// JPEG preview and a larger lossless JPEG stream that does not decode.
+	fn raw_file(preview: &[u8]) -> Vec<u8> {
+		let undecodable = [b"\xff\xd8\xff\xc3".as_slice(), &[0; 256]].concat();
+		let ifd_len = 2 + 6 * 12 + 4;
+		let preview_start = 8 + ifd_len;
+		let undecodable_start = preview_start + preview.len();
+		let as_u32 = |value: usize| u32::try_from(value).unwrap_or_default();
+		let directory = ifd(&[
+			entry(0x0103, 3, 7),
+			entry(0x0111, 4, as_u32(undecodable_start)),
+			entry(0x0112, 3, 6),
+			entry(0x0117, 4, as_u32(undecodable.len())),
+			entry(0x0201, 4, as_u32(preview_start)),
+			entry(0x0202, 4, as_u32(preview.len())),
+		]);
+		[b"II*\0\x08\0\0\0".as_slice(), &directory, preview, &undecodable].concat()
+	}
+
+	#[test]
+	fn raw_formats_are_told_apart_from_plain_tiff() {
+		assert!(is_cr2(b"II*\0\x10\0\0\0CR\x02\0"));
+		assert!(!is_cr2(b"II*\0\x08\0\0\0\0\0"));
+		let dng_ifd = ifd(&[entry(0xc612, 1, 0x0000_0401)]);
+		assert!(is_dng(&[b"II*\0\x08\0\0\0".as_slice(), &dng_ifd].concat()));
+		let tiff_ifd = ifd(&[entry(0x0100, 3, 16)]);
+		assert!(!is_dng(&[b"II*\0\x08\0\0\0".as_slice(), &tiff_ifd].concat()));
+	}
+
+	#[test]
+	fn the_largest_decodable_preview_is_oriented_by_the_raw_file() -> anyhow::Result<()> {
+		let mut preview = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::new(40, 20))
+			.write_to(&mut Cursor::new(&mut preview), ImageFormat::Jpeg)?;
+
+		let image = decode_embedded_preview(&raw_file(&preview))
+			.ok_or_else(|| anyhow::anyhow!("no preview decoded"))?;
+
+		assert_eq!((image.width(), image.height()), (20, 40));
+		Ok(())
+	}
+
+	#[test]
+	fn files_without_previews_decode_nothing() {
+		assert!(decode_embedded_preview(b"II*\0\x08\0\0\0\0\0\0\0\0\0").is_none());
+		assert!(decode_embedded_preview(b"not a tiff").is_none());
+	}
+}
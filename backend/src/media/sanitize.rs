@@ -0,0 +1,1346 @@
+//! Metadata removal for originals served to people other than their owner.
+//!
+//! Photos carry EXIF with the exact place they were taken and the camera's
+//! serial numbers, and XMP or IPTC blocks can repeat both. Sanitizing rewrites
+//! the file's container without the metadata segments and never re-encodes the
+//! pixels, so the copy is identical to the original apart from what it no
+//! longer says. Only the EXIF orientation survives stripping everything, as
+//! without it a portrait photo would display on its side.
+//!
+//! In [`MetadataPrivacy::StripLocation`] mode the EXIF block is rewritten
+//! without its GPS directory, maker notes and embedded thumbnail (which has EXIF
+//! of its own); XMP, IPTC and text chunks are dropped in both modes, since any
+//! of them may hold coordinates or place names.
+//!
+//! MP4 and AVIF files point at their media data by absolute offset, so their
+//! metadata is blanked where it lies rather than cut out: an MP4's user data
+//! and metadata boxes, where the `\xa9xyz` location and QuickTime keys live,
+//! become `free` boxes of the same size, and an AVIF's Exif and XMP items are
+//! zeroed (or, for Exif, rewritten in the space the original took).
+//! Since an MP4's media data never changes, its copy is worked out from ranged
+//! reads of its top-level boxes and written as patches to the original, so
+//! only the movie box is ever read whole.
+
+use {
+	super::isobmff::{
+		self,
+		BoxHeader,
+		Boxes,
+	},
+	anyhow::Context as AnyhowContext,
+	async_graphql::Enum,
+	exif::{
+		Context,
+		Field,
+		In,
+		Tag,
+		Value,
+		experimental::Writer,
+	},
+	postgres_types::{
+		FromSql,
+		ToSql,
+	},
+	std::{
+		io::Cursor,
+		ops::Range,
+	},
+};
+
+/// The `object_derivatives.kind` of an object's sanitized copy.
+pub const SANITIZED_KIND: &str = "sanitized";
+/// Content types a sanitized copy can be written for. Other types are always
+/// served as uploaded.
+pub const SANITIZED_CONTENT_TYPES: [&str; 8] = [
+	"image/jpeg",
+	"image/png",
+	"image/apng",
+	"image/webp",
+	"image/gif",
+	"image/avif",
+	"video/mp4",
+	"audio/mp4",
+];
+
+const EXIF_PREFIX: &[u8] = b"Exif\0\0";
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// The VP8X header flags announcing EXIF and XMP chunks.
+const WEBP_EXIF_FLAG: u8 = 0x08;
+const WEBP_XMP_FLAG: u8 = 0x04;
+/// The largest payload a JPEG segment's 16-bit length can describe.
+const JPEG_MAX_SEGMENT_PAYLOAD: usize = u16::MAX as usize - 2;
+/// GIF application extensions that are kept: animation looping and colour
+/// profiles, each behind its identifier's length byte.
+const GIF_KEPT_APPLICATIONS: [&[u8]; 3] =
+	[b"\x0bNETSCAPE2.0", b"\x0bANIMEXTS1.0", b"\x0bICCRGBG1012"];
+/// ISO-BMFF boxes the sanitizer reads or blanks beyond those the media
+/// inspection knows.
+const FREE: [u8; 4] = *b"free";
+const UUID: [u8; 4] = *b"uuid";
+const ITEM_INFO: [u8; 4] = *b"iinf";
+const ITEM_INFO_ENTRY: [u8; 4] = *b"infe";
+const ITEM_LOCATION: [u8; 4] = *b"iloc";
+const ITEM_DATA: [u8; 4] = *b"idat";
+const ITEM_PROPERTIES: [u8; 4] = *b"iprp";
+const ITEM_PROPERTY_CONTAINER: [u8; 4] = *b"ipco";
+const IMAGE_SPATIAL_EXTENTS: [u8; 4] = *b"ispe";
+/// The user type of the `uuid` box Adobe registered for XMP packets.
+const XMP_UUID: [u8; 16] = [
+	0xbe, 0x7a, 0xcf, 0xcb, 0x97, 0xa9, 0x42, 0xe8, 0x9c, 0x71, 0x99, 0x94, 0x91, 0xe3, 0xaf, 0xac,
+];
+/// How much of the start of a top-level MP4 box is read to judge it: a
+/// 64-bit box header and a `uuid` box's user type.
+const MP4_BOX_HEAD_BYTES: u64 = 32;
+
+/// How much embedded metadata other people get to see in an original.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Default, ToSql, FromSql)]
+#[postgres(name = "metadata_privacy")]
+pub enum MetadataPrivacy {
+	/// Serve the original as uploaded.
+	#[default]
+	#[postgres(name = "keep")]
+	Keep,
+	/// Serve a copy without GPS coordinates.
+	#[postgres(name = "strip_location")]
+	StripLocation,
+	/// Serve a copy without any embedded metadata.
+	#[postgres(name = "strip_all")]
+	StripAll,
+}
+
+/// The file extension of a sanitized copy of `content_type`, or `None` when the
+/// type cannot be sanitized and is always served as uploaded.
+pub fn sanitized_extension(content_type: &str) -> Option<&'static str> {
+	match content_type {
+		"image/jpeg" => Some("jpg"),
+		"image/png" | "image/apng" => Some("png"),
+		"image/webp" => Some("webp"),
+		"image/gif" => Some("gif"),
+		"image/avif" => Some("avif"),
+		"video/mp4" => Some("mp4"),
+		"audio/mp4" => Some("m4a"),
+		_ => None,
+	}
+}
+
+/// A copy of an original without some of its metadata, stored as written.
+/// Audio has no `dimensions`.
+#[derive(Clone, Debug)]
+pub struct SanitizedCopy {
+	pub extension: &'static str,
+	pub dimensions: Option<(u32, u32)>,
+	pub bytes: Vec<u8>,
+}
+
+/// Writes the copy of `source` that people other than its owner are served, or
+/// `None` when they are served the original: `privacy` keeps its metadata, or
+/// `content_type` cannot be sanitized.
+pub fn sanitize_copy(
+	source: &[u8],
+	content_type: &str,
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Option<SanitizedCopy>> {
+	let Some(extension) = sanitized_extension(content_type) else {
+		return Ok(None);
+	};
+	if privacy == MetadataPrivacy::Keep {
+		return Ok(None);
+	}
+	let bytes = sanitize(source, content_type, privacy)?;
+	let dimensions = dimensions(&bytes, content_type)?;
+	Ok(Some(SanitizedCopy {
+		extension,
+		dimensions,
+		bytes,
+	}))
+}
+
+/// Rewrites `source`, a file of `content_type`, without the metadata `privacy`
+/// removes.
+pub fn sanitize(
+	source: &[u8],
+	content_type: &str,
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Vec<u8>> {
+	if privacy == MetadataPrivacy::Keep {
+		return Ok(source.to_vec());
+	}
+	match content_type {
+		"image/jpeg" => sanitize_jpeg(source, privacy),
+		"image/png" | "image/apng" => sanitize_png(source, privacy),
+		"image/webp" => sanitize_webp(source, privacy),
+		"image/gif" => sanitize_gif(source, privacy),
+		"image/avif" => sanitize_avif(source, privacy),
+		"video/mp4" | "audio/mp4" => sanitize_mp4(source),
+		_ => anyhow::bail!("Objects of type {content_type} cannot be sanitized"),
+	}
+}
+
+/// The width and height of a sanitized copy: of a video's first visual track,
+/// of the largest image an AVIF file describes (its primary image, in
+/// practice), or of a decodable image. Audio has none.
+fn dimensions(
+	bytes: &[u8],
+	content_type: &str,
+) -> anyhow::Result<Option<(u32, u32)>> {
+	let dimensions = match content_type {
+		"audio/mp4" => return Ok(None),
+		"video/mp4" => isobmff::find_box(bytes, &[isobmff::MOVIE]).and_then(|movie| {
+			isobmff::tracks(movie)
+				.find(|track| {
+					track.handler == Some(isobmff::VIDEO_HANDLER) &&
+						track.width > 0 && track.height > 0
+				})
+				.map(|track| (track.width, track.height))
+		}),
+		"image/avif" => isobmff::find_box(bytes, &[isobmff::METADATA])
+			.and_then(|metadata| metadata.get(4 ..))
+			.and_then(|children| {
+				isobmff::find_box(children, &[ITEM_PROPERTIES, ITEM_PROPERTY_CONTAINER])
+			})
+			.and_then(|properties| {
+				// A full box: the version and flags precede the width and height.
+				Boxes::new(properties)
+					.filter(|(box_type, _)| *box_type == IMAGE_SPATIAL_EXTENTS)
+					.filter_map(|(_, extents)| {
+						Some((read_u32(extents, 4, false)?, read_u32(extents, 8, false)?))
+					})
+					.filter_map(|(width, height)| {
+						Some((u32::try_from(width).ok()?, u32::try_from(height).ok()?))
+					})
+					.max_by_key(|(width, height)| u64::from(*width) * u64::from(*height))
+			}),
+		_ => Some(
+			image::ImageReader::new(Cursor::new(bytes))
+				.with_guessed_format()?
+				.into_dimensions()
+				.context("Failed to read sanitized image dimensions")?,
+		),
+	};
+	dimensions
+		.filter(|(width, height)| *width > 0 && *height > 0)
+		.with_context(|| format!("Sanitized {content_type} copy has no dimensions"))
+		.map(Some)
+}
+
+/// Rewrites a raw TIFF-structured EXIF block with only the fields `privacy`
+/// keeps, or `None` when none are kept or the block does not parse (in which
+/// case it is dropped whole).
+fn rewrite_exif(
+	tiff: &[u8],
+	privacy: MetadataPrivacy,
+) -> Option<Vec<u8>> {
+	let exif = exif::Reader::new().read_raw(tiff.to_vec()).ok()?;
+	let keep = |field: &&Field| {
+		field.ifd_num == In::PRIMARY &&
+			!matches!(field.value, Value::Unknown(..)) &&
+			match privacy {
+				MetadataPrivacy::Keep => true,
+				MetadataPrivacy::StripLocation =>
+					field.tag.context() != Context::Gps && field.tag != Tag::MakerNote,
+				MetadataPrivacy::StripAll => field.tag == Tag::Orientation,
+			}
+	};
+	let fields = exif.fields().filter(keep).collect::<Vec<_>>();
+	if fields.is_empty() {
+		return None;
+	}
+	let mut writer = Writer::new();
+	for field in fields {
+		writer.push_field(field);
+	}
+	let mut rewritten = Cursor::new(Vec::new());
+	writer.write(&mut rewritten, exif.little_endian()).ok()?;
+	Some(rewritten.into_inner())
+}
+
+fn read_u16_be(
+	bytes: &[u8],
+	offset: usize,
+) -> Option<usize> {
+	let bytes = bytes.get(offset .. offset + 2)?;
+	Some(usize::from(u16::from_be_bytes([*bytes.first()?, *bytes.get(1)?])))
+}
+
+fn read_u32(
+	bytes: &[u8],
+	offset: usize,
+	little_endian: bool,
+) -> Option<usize> {
+	let bytes: [u8; 4] = bytes.get(offset .. offset + 4)?.try_into().ok()?;
+	let value = if little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) };
+	usize::try_from(value).ok()
+}
+
+/// What becomes of one metadata-carrying segment or chunk.
+enum Rewrite {
+	Keep,
+	Drop,
+	Replace(Vec<u8>),
+}
+
+/// Decides the fate of a JPEG marker segment from its marker and payload.
+fn rewrite_jpeg_segment(
+	marker: u8,
+	payload: &[u8],
+	privacy: MetadataPrivacy,
+) -> Rewrite {
+	match marker {
+		// APP1: EXIF, XMP or extended XMP.
+		0xe1 =>
+			match payload.strip_prefix(EXIF_PREFIX).and_then(|tiff| rewrite_exif(tiff, privacy)) {
+				Some(tiff) if EXIF_PREFIX.len() + tiff.len() <= JPEG_MAX_SEGMENT_PAYLOAD =>
+					Rewrite::Replace([EXIF_PREFIX, &tiff].concat()),
+				_ => Rewrite::Drop,
+			},
+		// APP13: Photoshop resources, which hold IPTC.
+		0xed => Rewrite::Drop,
+		// Comments.
+		0xfe if privacy == MetadataPrivacy::StripAll => Rewrite::Drop,
+		_ => Rewrite::Keep,
+	}
+}
+
+/// Copies a JPEG segment by segment, keeping the entropy-coded scans verbatim
+/// and anything trailing the end-of-image marker (where phones append
+/// secondary images, with EXIF of their own) not at all.
+fn sanitize_jpeg(
+	source: &[u8],
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(source.starts_with(b"\xff\xd8"), "Not a JPEG file");
+	let mut sanitized = Vec::with_capacity(source.len());
+	sanitized.extend_from_slice(b"\xff\xd8");
+	let mut offset = 2;
+	loop {
+		anyhow::ensure!(source.get(offset) == Some(&0xff), "Malformed JPEG marker");
+		while source.get(offset) == Some(&0xff) {
+			offset += 1;
+		}
+		let Some(&marker) = source.get(offset) else {
+			anyhow::bail!("JPEG ended before its end-of-image marker");
+		};
+		offset += 1;
+		match marker {
+			0xd9 => {
+				sanitized.extend_from_slice(b"\xff\xd9");
+				return Ok(sanitized);
+			}
+			0x01 | 0xd0 ..= 0xd7 => {
+				sanitized.extend_from_slice(&[0xff, marker]);
+				continue;
+			}
+			_ => {}
+		}
+		let length = read_u16_be(source, offset).context("Truncated JPEG segment length")?;
+		anyhow::ensure!(length >= 2, "Malformed JPEG segment length");
+		let segment = source.get(offset .. offset + length).context("Truncated JPEG segment")?;
+		match rewrite_jpeg_segment(marker, segment.get(2 ..).unwrap_or_default(), privacy) {
+			Rewrite::Keep => {
+				sanitized.extend_from_slice(&[0xff, marker]);
+				sanitized.extend_from_slice(segment);
+			}
+			Rewrite::Drop => {}
+			Rewrite::Replace(payload) => {
+				let length = u16::try_from(payload.len() + 2).context("JPEG segment too long")?;
+				sanitized.extend_from_slice(&[0xff, marker]);
+				sanitized.extend_from_slice(&length.to_be_bytes());
+				sanitized.extend_from_slice(&payload);
+			}
+		}
+		offset += length;
+		if marker == 0xda {
+			// Scan data runs to the next marker that is neither a stuffed
+			// zero byte nor a restart marker.
+			let scan_start = offset;
+			while let Some(&byte) = source.get(offset) {
+				if byte == 0xff {
+					match source.get(offset + 1) {
+						Some(0x00 | 0xd0 ..= 0xd7) => offset += 2,
+						Some(0xff) => offset += 1,
+						_ => break,
+					}
+				} else {
+					offset += 1;
+				}
+			}
+			sanitized.extend_from_slice(source.get(scan_start .. offset).unwrap_or_default());
+			if offset >= source.len() {
+				// Tolerate a missing end-of-image marker, as decoders do.
+				return Ok(sanitized);
+			}
+		}
+	}
+}
+
+fn png_chunk(
+	chunk_type: &[u8],
+	data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+	let length = u32::try_from(data.len()).context("PNG chunk too long")?;
+	let mut crc = crc32fast::Hasher::new();
+	crc.update(chunk_type);
+	crc.update(data);
+	Ok([&length.to_be_bytes(), chunk_type, data, &crc.finalize().to_be_bytes()].concat())
+}
+
+/// Copies a PNG chunk by chunk up to `IEND`, dropping text and timestamp chunks
+/// and rewriting `eXIf`.
+fn sanitize_png(
+	source: &[u8],
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(source.starts_with(PNG_SIGNATURE), "Not a PNG file");
+	let mut sanitized = Vec::with_capacity(source.len());
+	sanitized.extend_from_slice(PNG_SIGNATURE);
+	let mut offset = PNG_SIGNATURE.len();
+	loop {
+		let length = read_u32(source, offset, false).context("Truncated PNG chunk length")?;
+		let end = offset + 12 + length;
+		let chunk = source.get(offset .. end).context("Truncated PNG chunk")?;
+		let chunk_type = chunk.get(4 .. 8).unwrap_or_default();
+		let data = chunk.get(8 .. 8 + length).unwrap_or_default();
+		let rewrite = match chunk_type {
+			b"eXIf" => rewrite_exif(data, privacy).map_or(Rewrite::Drop, Rewrite::Replace),
+			b"tEXt" | b"zTXt" | b"iTXt" => Rewrite::Drop,
+			b"tIME" if privacy == MetadataPrivacy::StripAll => Rewrite::Drop,
+			_ => Rewrite::Keep,
+		};
+		match rewrite {
+			Rewrite::Keep => sanitized.extend_from_slice(chunk),
+			Rewrite::Drop => {}
+			Rewrite::Replace(data) => sanitized.extend(png_chunk(chunk_type, &data)?),
+		}
+		if chunk_type == b"IEND" {
+			return Ok(sanitized);
+		}
+		offset = end;
+	}
+}
+
+/// Copies a WebP chunk by chunk, dropping `XMP ` and rewriting `EXIF`, then
+/// corrects the VP8X flags and the RIFF size to match.
+fn sanitize_webp(
+	source: &[u8],
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(
+		source.starts_with(b"RIFF") && source.get(8 .. 12) == Some(b"WEBP"),
+		"Not a WebP file"
+	);
+	let riff_end = read_u32(source, 4, true).context("Truncated RIFF header")? + 8;
+	let mut chunks = Vec::with_capacity(source.len());
+	let mut has_exif = false;
+	let mut offset = 12;
+	while offset + 8 <= riff_end.min(source.len()) {
+		let length = read_u32(source, offset + 4, true).context("Truncated WebP chunk length")?;
+		let end = offset + 8 + length + length % 2;
+		let chunk = source.get(offset .. end).context("Truncated WebP chunk")?;
+		let fourcc = chunk.get(.. 4).unwrap_or_default();
+		let data = chunk.get(8 .. 8 + length).unwrap_or_default();
+		let rewrite = match fourcc {
+			// Some writers keep the JPEG APP1 prefix.
+			b"EXIF" => rewrite_exif(data.strip_prefix(EXIF_PREFIX).unwrap_or(data), privacy)
+				.map_or(Rewrite::Drop, Rewrite::Replace),
+			b"XMP " => Rewrite::Drop,
+			_ => Rewrite::Keep,
+		};
+		match rewrite {
+			Rewrite::Keep => chunks.extend_from_slice(chunk),
+			Rewrite::Drop => {}
+			Rewrite::Replace(data) => {
+				has_exif = true;
+				let length = u32::try_from(data.len()).context("WebP chunk too long")?;
+				chunks.extend_from_slice(fourcc);
+				chunks.extend_from_slice(&length.to_le_bytes());
+				chunks.extend_from_slice(&data);
+				if data.len() % 2 == 1 {
+					chunks.push(0);
+				}
+			}
+		}
+		offset = end;
+	}
+	if chunks.starts_with(b"VP8X") &&
+		let Some(flags) = chunks.get_mut(8)
+	{
+		*flags &= !WEBP_XMP_FLAG;
+		if !has_exif {
+			*flags &= !WEBP_EXIF_FLAG;
+		}
+	}
+	let riff_size = u32::try_from(chunks.len() + 4).context("WebP file too long")?;
+	Ok([b"RIFF".as_slice(), &riff_size.to_le_bytes(), b"WEBP", &chunks].concat())
+}
+
+/// The length of the colour table a GIF screen or image descriptor's packed
+/// `flags` announce.
+fn gif_color_table_len(flags: u8) -> usize {
+	if flags & 0x80 == 0 { 0 } else { 3 << ((flags & 0x07) + 1) }
+}
+
+/// The offset just past the data sub-blocks starting at `offset`: a run of
+/// length-prefixed blocks ended by an empty one.
+fn gif_sub_blocks_end(
+	source: &[u8],
+	mut offset: usize,
+) -> anyhow::Result<usize> {
+	loop {
+		let length = usize::from(*source.get(offset).context("Truncated GIF data")?);
+		offset += 1 + length;
+		if length == 0 {
+			return Ok(offset);
+		}
+	}
+}
+
+/// Copies a GIF block by block up to its trailer, dropping comments when
+/// everything is stripped and application extensions other than looping and
+/// colour profiles (XMP travels in one), plus extensions it does not know.
+fn sanitize_gif(
+	source: &[u8],
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(
+		source.starts_with(b"GIF87a") || source.starts_with(b"GIF89a"),
+		"Not a GIF file"
+	);
+	// The header and logical screen descriptor, then the global colour table.
+	let flags = *source.get(10).context("Truncated GIF header")?;
+	let mut offset = 13 + gif_color_table_len(flags);
+	let mut sanitized = source.get(.. offset).context("Truncated GIF header")?.to_vec();
+	loop {
+		let start = offset;
+		match source.get(offset) {
+			// Tolerate a missing trailer, as decoders do.
+			Some(0x3b) | None => {
+				sanitized.push(0x3b);
+				return Ok(sanitized);
+			}
+			Some(0x2c) => {
+				let flags = *source.get(offset + 9).context("Truncated GIF image descriptor")?;
+				// The descriptor, its local colour table and the LZW minimum
+				// code size precede the image data.
+				offset = gif_sub_blocks_end(source, offset + 10 + gif_color_table_len(flags) + 1)?;
+				sanitized.extend_from_slice(source.get(start .. offset).unwrap_or_default());
+			}
+			Some(0x21) => {
+				let label = *source.get(offset + 1).context("Truncated GIF extension")?;
+				let data = offset + 2;
+				offset = gif_sub_blocks_end(source, data)?;
+				let keep = match label {
+					// Graphic control and plain text, which are part of the
+					// animation.
+					0xf9 | 0x01 => true,
+					0xfe => privacy != MetadataPrivacy::StripAll,
+					0xff => source
+						.get(data .. data + 12)
+						.is_some_and(|identifier| GIF_KEPT_APPLICATIONS.contains(&identifier)),
+					_ => false,
+				};
+				if keep {
+					sanitized.extend_from_slice(source.get(start .. offset).unwrap_or_default());
+				}
+			}
+			Some(_) => anyhow::bail!("Malformed GIF block"),
+		}
+	}
+}
+
+/// Where one ISO-BMFF box lies in the buffer it was read from.
+struct BoxRange {
+	box_type: [u8; 4],
+	start: usize,
+	payload: usize,
+	end: usize,
+}
+
+/// The boxes directly inside `range` of `data`, which they must fill exactly.
+fn child_boxes(
+	data: &[u8],
+	range: Range<usize>,
+) -> anyhow::Result<Vec<BoxRange>> {
+	let mut boxes = Vec::new();
+	let mut offset = range.start;
+	while offset < range.end {
+		let header = data
+			.get(offset .. range.end)
+			.and_then(BoxHeader::parse)
+			.context("Malformed ISO-BMFF box header")?;
+		let end = match header.size {
+			Some(size) => offset.saturating_add(usize::try_from(size)?),
+			None => range.end,
+		};
+		anyhow::ensure!(end <= range.end, "Truncated ISO-BMFF box");
+		boxes.push(BoxRange {
+			box_type: header.box_type,
+			start: offset,
+			payload: offset + usize::try_from(header.header_len)?,
+			end,
+		});
+		offset = end;
+	}
+	Ok(boxes)
+}
+
+/// Turns a box into a `free` box of the same size with a zeroed payload, so
+/// nothing after it moves.
+fn blank_box(
+	data: &mut [u8],
+	range: &BoxRange,
+) {
+	if let Some(box_type) = data.get_mut(range.start + 4 .. range.start + 8) {
+		box_type.copy_from_slice(&FREE);
+	}
+	if let Some(payload) = data.get_mut(range.payload .. range.end) {
+		payload.fill(0);
+	}
+}
+
+/// Blanks the user data and metadata boxes at the top level of an MP4 and
+/// in its movie and tracks, and any XMP packet. The media data and sample
+/// tables are left byte for byte, so both modes strip the same boxes.
+fn sanitize_mp4(source: &[u8]) -> anyhow::Result<Vec<u8>> {
+	let mut sanitizer = Mp4Sanitizer::new(source.len() as u64, u64::MAX);
+	while let Some(range) = sanitizer.next_read() {
+		let range = usize::try_from(range.start)? .. usize::try_from(range.end)?;
+		sanitizer.feed(source.get(range).context("Truncated MP4")?)?;
+	}
+	let mut sanitized = source.to_vec();
+	for patch in &sanitizer.patches {
+		patch.apply(0, &mut sanitized);
+	}
+	Ok(sanitized)
+}
+
+fn blank_mp4_metadata(
+	data: &mut [u8],
+	range: Range<usize>,
+) -> anyhow::Result<()> {
+	for child in child_boxes(data, range)? {
+		match child.box_type {
+			isobmff::MOVIE | isobmff::TRACK =>
+				blank_mp4_metadata(data, child.payload .. child.end)?,
+			box_type
+				if is_mp4_metadata(
+					box_type,
+					data.get(child.payload .. child.end).unwrap_or_default(),
+				) =>
+				blank_box(data, &child),
+			_ => {}
+		}
+	}
+	Ok(())
+}
+
+/// Whether an MP4 box holds metadata: user data, a metadata box, or an XMP
+/// packet, told apart from other `uuid` boxes by the start of its `payload`.
+fn is_mp4_metadata(
+	box_type: [u8; 4],
+	payload: &[u8],
+) -> bool {
+	match box_type {
+		isobmff::USER_DATA | isobmff::METADATA => true,
+		UUID => payload.starts_with(&XMP_UUID),
+		_ => false,
+	}
+}
+
+/// One change the sanitized copy of an MP4 makes to its original.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Mp4Patch {
+	/// Writes `bytes` at `offset`.
+	Write { offset: u64, bytes: Vec<u8> },
+	/// Zeroes a range.
+	Zero(Range<u64>),
+}
+
+impl Mp4Patch {
+	/// The range of the original the patch changes.
+	pub fn range(&self) -> Range<u64> {
+		match self {
+			Self::Write {
+				offset,
+				bytes,
+			} => *offset .. offset.saturating_add(bytes.len() as u64),
+			Self::Zero(range) => range.clone(),
+		}
+	}
+
+	/// Applies the patch to `part`, the bytes of the original from `offset`.
+	pub fn apply(
+		&self,
+		offset: u64,
+		part: &mut [u8],
+	) {
+		let range = self.range();
+		let start = range.start.max(offset);
+		let end = range.end.min(offset.saturating_add(part.len() as u64));
+		if start >= end {
+			return;
+		}
+		let (Ok(from), Ok(to)) = (usize::try_from(start - offset), usize::try_from(end - offset))
+		else {
+			return;
+		};
+		let Some(target) = part.get_mut(from .. to) else {
+			return;
+		};
+		match self {
+			Self::Write {
+				bytes, ..
+			} => {
+				let skip = usize::try_from(start - range.start).unwrap_or(usize::MAX);
+				if let Some(source) = bytes.get(skip .. skip.saturating_add(target.len())) {
+					target.copy_from_slice(source);
+				}
+			}
+			Self::Zero(_) => target.fill(0),
+		}
+	}
+}
+
+/// The changes that make the sanitized copy of an MP4, and the copy's
+/// dimensions.
+#[derive(Clone, Debug)]
+pub struct SanitizedMp4 {
+	pub extension: &'static str,
+	pub dimensions: Option<(u32, u32)>,
+	pub patches: Vec<Mp4Patch>,
+}
+
+/// Works out the sanitized copy of an MP4 from ranged reads of the original,
+/// so its media data is never read: the caller reads each range
+/// [`Self::next_read`] asks for and passes it to [`Self::feed`] until none
+/// are left. Top-level boxes are judged from their first bytes, and only the
+/// movie box is read whole.
+pub struct Mp4Sanitizer {
+	length: u64,
+	max_movie_bytes: u64,
+	offset: u64,
+	/// The size of the movie box at `offset`, once its header has been read.
+	movie_size: Option<u64>,
+	/// The movie box as rewritten.
+	movie: Option<Vec<u8>>,
+	patches: Vec<Mp4Patch>,
+}
+
+impl Mp4Sanitizer {
+	/// Sanitizes an original of `length` bytes whose movie box is no larger
+	/// than `max_movie_bytes`.
+	pub fn new(
+		length: u64,
+		max_movie_bytes: u64,
+	) -> Self {
+		Self {
+			length,
+			max_movie_bytes,
+			offset: 0,
+			movie_size: None,
+			movie: None,
+			patches: Vec::new(),
+		}
+	}
+
+	/// The range of the original to read next, or `None` once every
+	/// top-level box has been seen.
+	pub fn next_read(&self) -> Option<Range<u64>> {
+		if self.offset >= self.length {
+			return None;
+		}
+		let end = match self.movie_size {
+			Some(size) => self.offset.saturating_add(size),
+			None => self.length.min(self.offset.saturating_add(MP4_BOX_HEAD_BYTES)),
+		};
+		Some(self.offset .. end)
+	}
+
+	/// Takes the bytes of the range [`Self::next_read`] last asked for.
+	pub fn feed(
+		&mut self,
+		bytes: &[u8],
+	) -> anyhow::Result<()> {
+		if let Some(size) = self.movie_size.take() {
+			anyhow::ensure!(bytes.len() as u64 == size, "Truncated MP4 movie box");
+			let header = BoxHeader::parse(bytes).context("Malformed ISO-BMFF box header")?;
+			let mut movie = bytes.to_vec();
+			blank_mp4_metadata(&mut movie, usize::try_from(header.header_len)? .. bytes.len())?;
+			if movie != bytes {
+				self.patches.push(Mp4Patch::Write {
+					offset: self.offset,
+					bytes: movie.clone(),
+				});
+			}
+			self.movie = Some(movie);
+			self.offset += size;
+			return Ok(());
+		}
+		anyhow::ensure!(self.offset > 0 || isobmff::is_isobmff(bytes), "Not an MP4 file");
+		let header = BoxHeader::parse(bytes).context("Malformed ISO-BMFF box header")?;
+		let remaining = self.length - self.offset;
+		let size = header.size.unwrap_or(remaining);
+		anyhow::ensure!(size <= remaining, "Truncated ISO-BMFF box");
+		if header.box_type == isobmff::MOVIE {
+			anyhow::ensure!(
+				size <= self.max_movie_bytes,
+				"MP4 movie box is larger than {} bytes",
+				self.max_movie_bytes
+			);
+			self.movie_size = Some(size);
+			return Ok(());
+		}
+		let payload = bytes.get(usize::try_from(header.header_len)? ..).unwrap_or_default();
+		if is_mp4_metadata(header.box_type, payload) {
+			self.patches.push(Mp4Patch::Write {
+				offset: self.offset + 4,
+				bytes: FREE.to_vec(),
+			});
+			self.patches
+				.push(Mp4Patch::Zero(self.offset + header.header_len .. self.offset + size));
+		}
+		self.offset += size;
+		Ok(())
+	}
+
+	/// The copy of an original of `content_type`, once every range has been
+	/// fed.
+	pub fn finish(
+		self,
+		content_type: &str,
+	) -> anyhow::Result<SanitizedMp4> {
+		anyhow::ensure!(self.offset > 0 && self.next_read().is_none(), "MP4 was not read in full");
+		let extension = sanitized_extension(content_type)
+			.with_context(|| format!("Objects of type {content_type} cannot be sanitized"))?;
+		let dimensions = dimensions(self.movie.as_deref().unwrap_or_default(), content_type)?;
+		Ok(SanitizedMp4 {
+			extension,
+			dimensions,
+			patches: self.patches,
+		})
+	}
+}
+
+/// The metadata an AVIF item can hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MetadataItem {
+	Exif,
+	Xmp,
+}
+
+/// The IDs of the Exif and XMP items an `iinf` payload lists.
+fn metadata_items(item_info: &[u8]) -> anyhow::Result<Vec<(usize, MetadataItem)>> {
+	// A full box, then a 16-bit entry count in version 0 and 32-bit after.
+	let entries_start = if item_info.first() == Some(&0) { 6 } else { 8 };
+	let entries = item_info.get(entries_start ..).context("Truncated AVIF item info")?;
+	let mut items = Vec::new();
+	for (box_type, entry) in Boxes::new(entries) {
+		if box_type != ITEM_INFO_ENTRY {
+			continue;
+		}
+		// Versions 0 and 1 predate item types, so cannot mark metadata.
+		let (id, rest) = match entry.first() {
+			Some(2) => (read_u16_be(entry, 4), entry.get(6 ..)),
+			Some(3) => (read_u32(entry, 4, false), entry.get(8 ..)),
+			_ => continue,
+		};
+		let (Some(id), Some(rest)) = (id, rest) else {
+			anyhow::bail!("Truncated AVIF item info entry");
+		};
+		// A 16-bit protection index precedes the item type and name.
+		let item = match rest.get(2 .. 6) {
+			Some(b"Exif") => MetadataItem::Exif,
+			Some(b"mime") => {
+				let mut strings = rest.get(6 ..).unwrap_or_default().split(|byte| *byte == 0);
+				match strings.nth(1) {
+					Some(b"application/rdf+xml") => MetadataItem::Xmp,
+					_ => continue,
+				}
+			}
+			_ => continue,
+		};
+		items.push((id, item));
+	}
+	Ok(items)
+}
+
+/// The file ranges holding item `id`'s data according to an `iloc` payload,
+/// or `None` when the item is not placed. Items stored in the `idat` box at
+/// `item_data` are resolved too; those built from other items are refused.
+fn item_ranges(
+	item_location: &[u8],
+	item_data: Option<Range<usize>>,
+	file_len: usize,
+	id: usize,
+) -> anyhow::Result<Option<Vec<Range<usize>>>> {
+	let mut offset = 4;
+	let mut read = |size: usize| -> anyhow::Result<usize> {
+		let field =
+			item_location.get(offset .. offset + size).context("Truncated AVIF item location")?;
+		offset += size;
+		let value = field.iter().fold(0_u64, |value, byte| value << 8 | u64::from(*byte));
+		Ok(usize::try_from(value)?)
+	};
+	let version = *item_location.first().context("Truncated AVIF item location")?;
+	let sizes = read(1)?;
+	let (offset_size, length_size) = (sizes >> 4, sizes & 0x0f);
+	let sizes = read(1)?;
+	let base_offset_size = sizes >> 4;
+	let index_size = if matches!(version, 1 | 2) { sizes & 0x0f } else { 0 };
+	let wide = if version < 2 { 2 } else { 4 };
+	for _ in 0 .. read(wide)? {
+		let item_id = read(wide)?;
+		let construction_method = if matches!(version, 1 | 2) { read(2)? & 0x0f } else { 0 };
+		// The data reference index.
+		read(2)?;
+		let base_offset = read(base_offset_size)?;
+		let mut ranges = Vec::new();
+		for _ in 0 .. read(2)? {
+			read(index_size)?;
+			let extent_offset = read(offset_size)?;
+			let extent_length = read(length_size)?;
+			ranges.push((base_offset.saturating_add(extent_offset), extent_length));
+		}
+		if item_id != id {
+			continue;
+		}
+		let data = match construction_method {
+			0 => 0 .. file_len,
+			1 => item_data.clone().context("AVIF item data box is missing")?,
+			_ => anyhow::bail!("AVIF metadata built from other items cannot be sanitized"),
+		};
+		return ranges
+			.into_iter()
+			.map(|(start, length)| {
+				let start = data.start.saturating_add(start);
+				// A zero length runs to the end of the data.
+				let end = if length == 0 { data.end } else { start.saturating_add(length) };
+				anyhow::ensure!(start <= end && end <= data.end, "AVIF item lies outside the file");
+				Ok(start .. end)
+			})
+			.collect::<anyhow::Result<_>>()
+			.map(Some);
+	}
+	Ok(None)
+}
+
+/// Clears an AVIF's Exif and XMP items where they lie. An Exif item held in
+/// one extent is rewritten in place when what `privacy` keeps of it fits,
+/// with zeros filling the space it no longer needs.
+fn sanitize_avif(
+	source: &[u8],
+	privacy: MetadataPrivacy,
+) -> anyhow::Result<Vec<u8>> {
+	anyhow::ensure!(isobmff::is_isobmff(source), "Not an AVIF file");
+	let metadata = child_boxes(source, 0 .. source.len())?
+		.into_iter()
+		.find(|child| child.box_type == isobmff::METADATA)
+		.context("AVIF file has no meta box")?;
+	// A full box: the version and flags precede the children.
+	let children = child_boxes(source, metadata.payload + 4 .. metadata.end)?;
+	let child = |box_type: [u8; 4]| children.iter().find(|child| child.box_type == box_type);
+	let payload = |child: &BoxRange| source.get(child.payload .. child.end).unwrap_or_default();
+	let items = child(ITEM_INFO).map(|info| metadata_items(payload(info))).transpose()?;
+	let mut sanitized = source.to_vec();
+	for (id, item) in items.unwrap_or_default() {
+		let location = child(ITEM_LOCATION).context("AVIF file has no item locations")?;
+		let item_data = child(ITEM_DATA).map(|data| data.payload .. data.end);
+		let Some(ranges) = item_ranges(payload(location), item_data, source.len(), id)? else {
+			continue;
+		};
+		let rewritten = match (item, ranges.as_slice()) {
+			(MetadataItem::Exif, [range]) => source.get(range.clone()).and_then(|exif| {
+				// The TIFF header follows a 32-bit offset to it.
+				let tiff_offset = read_u32(exif, 0, false)?;
+				let tiff = rewrite_exif(exif.get(4 + tiff_offset ..)?, privacy)?;
+				(4 + tiff.len() <= exif.len()).then(|| [[0; 4].as_slice(), &tiff].concat())
+			}),
+			_ => None,
+		};
+		for range in &ranges {
+			if let Some(bytes) = sanitized.get_mut(range.clone()) {
+				bytes.fill(0);
+			}
+		}
+		if let (Some(exif), Some(range)) = (rewritten, ranges.first()) &&
+			let Some(bytes) = sanitized.get_mut(range.start .. range.start + exif.len())
+		{
+			bytes.copy_from_slice(&exif);
+		}
+	}
+	Ok(sanitized)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			EXIF_PREFIX,
+			MetadataPrivacy,
+			Mp4Patch,
+			Mp4Sanitizer,
+			XMP_UUID,
+			gif_color_table_len,
+			sanitize,
+			sanitize_copy,
+		},
+		anyhow::Context,
+		exif::{
+			Field,
+			In,
+			Rational,
+			Tag,
+			Value,
+			experimental::Writer,
+		},
+		image::{
+			DynamicImage,
+			ImageFormat,
+			RgbImage,
+		},
+		std::io::Cursor,
+	};
+
+	/// Ten 64x48 AV1 frames with JPEG cover art and a location.
+	const MP4: &[u8] = include_bytes!("../../tests/fixtures/av1-cover-art.mp4");
+
+	/// A raw EXIF block with an orientation, a camera serial number and a GPS
+	/// position.
+	fn exif_block() -> anyhow::Result<Vec<u8>> {
+		let fields = [
+			Field {
+				tag: Tag::Orientation,
+				ifd_num: In::PRIMARY,
+				value: Value::Short(vec![6]),
+			},
+			Field {
+				tag: Tag::BodySerialNumber,
+				ifd_num: In::PRIMARY,
+				value: Value::Ascii(vec![b"SN12345".to_vec()]),
+			},
+			Field {
+				tag: Tag::GPSLatitudeRef,
+				ifd_num: In::PRIMARY,
+				value: Value::Ascii(vec![b"N".to_vec()]),
+			},
+			Field {
+				tag: Tag::GPSLatitude,
+				ifd_num: In::PRIMARY,
+				value: Value::Rational(vec![
+					Rational::from((51, 1)),
+					Rational::from((30, 1)),
+					Rational::from((0, 1)),
+				]),
+			},
+		];
+		let mut writer = Writer::new();
+		for field in &fields {
+			writer.push_field(field);
+		}
+		let mut block = Cursor::new(Vec::new());
+		writer.write(&mut block, false)?;
+		Ok(block.into_inner())
+	}
+
+	fn encode(format: ImageFormat) -> anyhow::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::from_fn(16, 8, |x, y| {
+			image::Rgb([(x * 16) as u8, (y * 32) as u8, 128])
+		}))
+		.write_to(&mut Cursor::new(&mut bytes), format)?;
+		Ok(bytes)
+	}
+
+	/// A JPEG carrying the EXIF block, an XMP packet and a comment.
+	fn tagged_jpeg() -> anyhow::Result<Vec<u8>> {
+		let jpeg = encode(ImageFormat::Jpeg)?;
+		let segment = |marker: u8, payload: &[u8]| -> anyhow::Result<Vec<u8>> {
+			let length = u16::try_from(payload.len() + 2)?;
+			Ok([&[0xff, marker], length.to_be_bytes().as_slice(), payload].concat())
+		};
+		Ok([
+			b"\xff\xd8".as_slice(),
+			&segment(0xe1, &[EXIF_PREFIX, &exif_block()?].concat())?,
+			&segment(0xe1, b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta>51.5</x:xmpmeta>")?,
+			&segment(0xfe, b"taken at home")?,
+			jpeg.get(2 ..).unwrap_or_default(),
+			b"trailing secondary image",
+		]
+		.concat())
+	}
+
+	fn parsed_exif(bytes: &[u8]) -> Option<exif::Exif> {
+		exif::Reader::new().read_from_container(&mut Cursor::new(bytes)).ok()
+	}
+
+	fn contains(
+		haystack: &[u8],
+		needle: &[u8],
+	) -> bool {
+		haystack.windows(needle.len()).any(|window| window == needle)
+	}
+
+	#[test]
+	fn strip_location_keeps_exif_but_not_gps() -> anyhow::Result<()> {
+		let sanitized = sanitize(&tagged_jpeg()?, "image/jpeg", MetadataPrivacy::StripLocation)?;
+
+		let exif = parsed_exif(&sanitized).ok_or_else(|| anyhow::anyhow!("EXIF was dropped"))?;
+		assert!(exif.get_field(Tag::BodySerialNumber, In::PRIMARY).is_some());
+		assert!(exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_none());
+		assert!(!contains(&sanitized, b"xmpmeta"));
+		assert!(contains(&sanitized, b"taken at home"));
+		assert!(!contains(&sanitized, b"trailing secondary image"));
+		let image = image::load_from_memory(&sanitized)?;
+		assert_eq!((image.width(), image.height()), (16, 8));
+		Ok(())
+	}
+
+	#[test]
+	fn strip_all_keeps_only_the_orientation() -> anyhow::Result<()> {
+		let sanitized = sanitize(&tagged_jpeg()?, "image/jpeg", MetadataPrivacy::StripAll)?;
+
+		let exif = parsed_exif(&sanitized).ok_or_else(|| anyhow::anyhow!("EXIF was dropped"))?;
+		let tags = exif.fields().map(|field| field.tag).collect::<Vec<_>>();
+		assert_eq!(tags, vec![Tag::Orientation]);
+		assert!(!contains(&sanitized, b"xmpmeta"));
+		assert!(!contains(&sanitized, b"taken at home"));
+		image::load_from_memory(&sanitized)?;
+		Ok(())
+	}
+
+	#[test]
+	fn png_chunks_are_rewritten_with_valid_checksums() -> anyhow::Result<()> {
+		let png = encode(ImageFormat::Png)?;
+		// The signature and IHDR come first.
+		let (header, chunks) = png.split_at(33);
+		let text = super::png_chunk(b"tEXt", b"Location\0Home")?;
+		let exif = super::png_chunk(b"eXIf", &exif_block()?)?;
+		let tagged = [header, &exif, &text, chunks].concat();
+
+		let sanitized = sanitize(&tagged, "image/png", MetadataPrivacy::StripLocation)?;
+
+		assert!(!contains(&sanitized, b"Home"));
+		let exif = parsed_exif(&sanitized).ok_or_else(|| anyhow::anyhow!("EXIF was dropped"))?;
+		assert!(exif.get_field(Tag::GPSLatitude, In::PRIMARY).is_none());
+		assert!(exif.get_field(Tag::Orientation, In::PRIMARY).is_some());
+		// The decoder rejects chunks whose checksum does not match.
+		image::load_from_memory(&sanitized)?;
+		Ok(())
+	}
+
+	#[test]
+	fn webp_metadata_chunks_and_flags_are_removed() -> anyhow::Result<()> {
+		let webp = encode(ImageFormat::WebP)?;
+		let image_chunks = webp.get(12 ..).unwrap_or_default();
+		let exif = exif_block()?;
+		let xmp = b"<x:xmpmeta>51.5</x:xmpmeta>";
+		let mut chunks = b"VP8X\x0a\0\0\0".to_vec();
+		chunks.extend_from_slice(&[0x0c, 0, 0, 0, 15, 0, 0, 7, 0, 0]);
+		chunks.extend_from_slice(image_chunks);
+		for (fourcc, data) in [(b"EXIF", exif.as_slice()), (b"XMP ", xmp.as_slice())] {
+			chunks.extend_from_slice(fourcc);
+			chunks.extend_from_slice(&u32::try_from(data.len())?.to_le_bytes());
+			chunks.extend_from_slice(data);
+			if data.len() % 2 == 1 {
+				chunks.push(0);
+			}
+		}
+		let riff_size = u32::try_from(chunks.len() + 4)?.to_le_bytes();
+		let tagged = [b"RIFF".as_slice(), &riff_size, b"WEBP", &chunks].concat();
+
+		let sanitized = sanitize(&tagged, "image/webp", MetadataPrivacy::StripAll)?;
+
+		assert!(!contains(&sanitized, b"xmpmeta"));
+		assert!(!contains(&sanitized, b"SN12345"));
+		assert_eq!(sanitized.get(20), Some(&0x08));
+		let riff_size = u32::from_le_bytes(sanitized.get(4 .. 8).unwrap_or_default().try_into()?);
+		assert_eq!(usize::try_from(riff_size)?, sanitized.len() - 8);
+		image::load_from_memory(&sanitized)?;
+		Ok(())
+	}
+
+	#[test]
+	fn gif_comments_and_xmp_are_dropped() -> anyhow::Result<()> {
+		let gif = encode(ImageFormat::Gif)?;
+		// The header, logical screen descriptor and global colour table.
+		let header_len = 13 + gif_color_table_len(gif.get(10).copied().unwrap_or_default());
+		let (header, blocks) = gif.split_at_checked(header_len).context("encoded GIF is empty")?;
+		let comment = b"\x21\xfe\x0dtaken at home\x00";
+		let xmp = b"\x21\xff\x0bXMP DataXMP\x0551.5N\x00";
+		let tagged = [header, comment, xmp, blocks].concat();
+
+		let sanitized = sanitize(&tagged, "image/gif", MetadataPrivacy::StripLocation)?;
+		assert!(contains(&sanitized, b"taken at home"));
+		assert!(!contains(&sanitized, b"XMP DataXMP"));
+		let sanitized = sanitize(&tagged, "image/gif", MetadataPrivacy::StripAll)?;
+		assert!(!contains(&sanitized, b"taken at home"));
+		let image = image::load_from_memory(&sanitized)?;
+		assert_eq!((image.width(), image.height()), (16, 8));
+		Ok(())
+	}
+
+	#[test]
+	fn mp4_user_data_is_blanked_in_place() -> anyhow::Result<()> {
+		assert!(contains(MP4, b"\xa9xyz"));
+
+		let copy = sanitize_copy(MP4, "video/mp4", MetadataPrivacy::StripLocation)?
+			.ok_or_else(|| anyhow::anyhow!("no copy was written"))?;
+
+		assert_eq!((copy.extension, copy.dimensions), ("mp4", Some((64, 48))));
+		assert!(!contains(&copy.bytes, b"\xa9xyz"));
+		assert!(!contains(&copy.bytes, b"covr"));
+		// Sample offsets still point at the same media data.
+		assert_eq!(copy.bytes.len(), MP4.len());
+		let media_data = MP4.windows(4).position(|window| window == b"mdat").unwrap_or_default();
+		assert_eq!(copy.bytes.get(media_data ..), MP4.get(media_data ..));
+
+		// Audio in the same container is rewritten the same way.
+		let audio = sanitize_copy(MP4, "audio/mp4", MetadataPrivacy::StripAll)?
+			.ok_or_else(|| anyhow::anyhow!("no audio copy was written"))?;
+		assert_eq!((audio.extension, audio.dimensions), ("m4a", None));
+		assert_eq!(audio.bytes, copy.bytes);
+		Ok(())
+	}
+
+	#[test]
+	fn mp4_copies_are_planned_from_top_level_boxes() -> anyhow::Result<()> {
+		let file_type = iso_box(b"ftyp", b"isom\0\0\0\0")?;
+		let xmp = iso_box(b"uuid", &[XMP_UUID.as_slice(), b"<x:xmpmeta/>"].concat())?;
+		let media_data = iso_box(b"mdat", &[7; 64])?;
+		let mp4 = [file_type.as_slice(), &xmp, &media_data].concat();
+
+		let mut sanitizer = Mp4Sanitizer::new(mp4.len() as u64, 1024);
+		let mut reads = Vec::new();
+		while let Some(range) = sanitizer.next_read() {
+			let bytes = mp4.get(usize::try_from(range.start)? .. usize::try_from(range.end)?);
+			sanitizer.feed(bytes.context("read past the end")?)?;
+			reads.push(range);
+		}
+		let copy = sanitizer.finish("audio/mp4")?;
+
+		// Only the start of each box is read, never the media data.
+		let xmp_start = file_type.len() as u64;
+		let media_start = xmp_start + xmp.len() as u64;
+		assert_eq!(reads, [0 .. 32, xmp_start .. xmp_start + 32, media_start .. media_start + 32]);
+		assert_eq!(
+			copy.patches,
+			[
+				Mp4Patch::Write {
+					offset: xmp_start + 4,
+					bytes: b"free".to_vec(),
+				},
+				Mp4Patch::Zero(xmp_start + 8 .. media_start),
+			]
+		);
+
+		// A movie box over the limit is refused rather than read.
+		let mut sanitizer = Mp4Sanitizer::new(MP4.len() as u64, 64);
+		while let Some(range) = sanitizer.next_read() {
+			let bytes = MP4.get(usize::try_from(range.start)? .. usize::try_from(range.end)?);
+			if sanitizer.feed(bytes.context("read past the end")?).is_err() {
+				return Ok(());
+			}
+		}
+		anyhow::bail!("an oversized movie box was accepted")
+	}
+
+	/// An ISO-BMFF box of `box_type` around `payload`.
+	fn iso_box(
+		box_type: &[u8; 4],
+		payload: &[u8],
+	) -> anyhow::Result<Vec<u8>> {
+		let size = u32::try_from(payload.len() + 8)?.to_be_bytes();
+		Ok([size.as_slice(), box_type, payload].concat())
+	}
+
+	/// A 16x8 AVIF without image data whose Exif item, the EXIF block, ends
+	/// the file, and whose XMP item is kept in the `meta` box's `idat`.
+	fn tagged_avif() -> anyhow::Result<Vec<u8>> {
+		let exif = [[0; 4].as_slice(), &exif_block()?].concat();
+		let exif_len = u32::try_from(exif.len())?.to_be_bytes();
+		let xmp = b"<x:xmpmeta>51.5</x:xmpmeta>";
+		let xmp_len = u32::try_from(xmp.len())?.to_be_bytes();
+		let file_type = iso_box(b"ftyp", b"avif\0\0\0\0avifmif1")?;
+		let info_entry = |id: u8, item_type: &[u8; 4], name_and_type: &[u8]| {
+			iso_box(
+				b"infe",
+				&[[2, 0, 0, 0, 0, id, 0, 0].as_slice(), item_type, name_and_type].concat(),
+			)
+		};
+		let item_info = iso_box(
+			b"iinf",
+			&[
+				[0, 0, 0, 0, 0, 2].as_slice(),
+				&info_entry(1, b"Exif", b"\0")?,
+				&info_entry(2, b"mime", b"\0application/rdf+xml\0")?,
+			]
+			.concat(),
+		)?;
+		let metadata = |exif_offset: u32| -> anyhow::Result<Vec<u8>> {
+			// Version 1 with 32-bit offsets and lengths: the Exif item lies
+			// in the file, the XMP item in `idat`.
+			let item_location = iso_box(
+				b"iloc",
+				&[
+					[1, 0, 0, 0, 0x44, 0, 0, 2].as_slice(),
+					&[0, 1, 0, 0, 0, 0, 0, 1],
+					&exif_offset.to_be_bytes(),
+					&exif_len,
+					&[0, 2, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0],
+					&xmp_len,
+				]
+				.concat(),
+			)?;
+			let properties = iso_box(
+				b"iprp",
+				&iso_box(b"ipco", &iso_box(b"ispe", &[0, 0, 0, 0, 0, 0, 0, 16, 0, 0, 0, 8])?)?,
+			)?;
+			let children = [
+				[0; 4].as_slice(),
+				&item_info,
+				&item_location,
+				&iso_box(b"idat", xmp)?,
+				&properties,
+			]
+			.concat();
+			iso_box(b"meta", &children)
+		};
+		let exif_offset = file_type.len() + metadata(0)?.len() + 8;
+		Ok([file_type, metadata(u32::try_from(exif_offset)?)?, iso_box(b"mdat", &exif)?].concat())
+	}
+
+	#[test]
+	fn avif_metadata_items_are_cleared_in_place() -> anyhow::Result<()> {
+		let avif = tagged_avif()?;
+		let exif_start = avif.len() - exif_block()?.len();
+		let exif_fields = |bytes: &[u8]| -> anyhow::Result<Vec<Tag>> {
+			let tiff = bytes.get(exif_start ..).unwrap_or_default().to_vec();
+			Ok(exif::Reader::new().read_raw(tiff)?.fields().map(|field| field.tag).collect())
+		};
+		assert!(exif_fields(&avif)?.contains(&Tag::GPSLatitude));
+
+		let copy = sanitize_copy(&avif, "image/avif", MetadataPrivacy::StripLocation)?
+			.ok_or_else(|| anyhow::anyhow!("no copy was written"))?;
+		assert_eq!((copy.extension, copy.dimensions), ("avif", Some((16, 8))));
+		assert_eq!(copy.bytes.len(), avif.len());
+		assert!(!contains(&copy.bytes, b"xmpmeta"));
+		let tags = exif_fields(&copy.bytes)?;
+		assert!(tags.contains(&Tag::BodySerialNumber));
+		assert!(!tags.contains(&Tag::GPSLatitude));
+
+		let sanitized = sanitize(&avif, "image/avif", MetadataPrivacy::StripAll)?;
+		assert_eq!(exif_fields(&sanitized)?, vec![Tag::Orientation]);
+		Ok(())
+	}
+
+	#[test]
+	fn copies_are_only_written_when_metadata_is_withheld() -> anyhow::Result<()> {
+		let jpeg = tagged_jpeg()?;
+		assert!(sanitize_copy(&jpeg, "image/jpeg", MetadataPrivacy::Keep)?.is_none());
+		assert!(sanitize_copy(b"ID3", "audio/mpeg", MetadataPrivacy::StripAll)?.is_none());
+
+		let copy = sanitize_copy(&jpeg, "image/jpeg", MetadataPrivacy::StripAll)?
+			.ok_or_else(|| anyhow::anyhow!("no copy was written"))?;
+		assert_eq!((copy.extension, copy.dimensions), ("jpg", Some((16, 8))));
+		Ok(())
+	}
+}
@@ -0,0 +1,258 @@
+//! Content type detection from magic bytes.
+//!
+//! Uploads declare their content type, and the upload is stored under it, so a
+//! completed upload is checked against its first bytes before it is made
+//! available. Several accepted types share a container (MP4, WebM and Ogg carry
+//! audio or video; APNG is a PNG), so detection yields every accepted type the
+//! bytes are valid as, most specific first, rather than a single answer.
+//! Camera RAW formats are TIFF files, told apart by their header or tags.
+
+use super::{
+	isobmff::{
+		self,
+		FILE_TYPE,
+	},
+	matroska,
+	ogg,
+	raw,
+};
+
+/// How much of an object is read to sniff its type. SVG needs the most, as its
+/// root element may follow an XML declaration, comments and a doctype.
+pub const SNIFF_READ_BYTES: u64 = 4096;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+/// Brands of ISO-BMFF files holding only audio.
+const AUDIO_BRANDS: [&[u8; 4]; 3] = [b"M4A ", b"M4B ", b"M4P "];
+const AVIF_BRANDS: [&[u8; 4]; 2] = [b"avif", b"avis"];
+/// Brands of HEIF files coded with HEVC, which are named HEIC.
+const HEIC_BRANDS: [&[u8; 4]; 6] = [b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx"];
+/// Brands of HEIF files of any other coding.
+const HEIF_BRANDS: [&[u8; 4]; 2] = [b"mif1", b"msf1"];
+
+/// Returns the accepted content types `head` is valid as, most specific first,
+/// or an empty slice when the bytes match none of them.
+pub fn sniff_content_types(head: &[u8]) -> &'static [&'static str] {
+	if head.starts_with(PNG_SIGNATURE) {
+		if is_animated_png(head) {
+			&["image/apng", "image/png"]
+		} else {
+			&["image/png", "image/apng"]
+		}
+	} else if head.starts_with(b"\xff\xd8\xff") {
+		&["image/jpeg"]
+	} else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+		&["image/gif"]
+	} else if head.starts_with(b"RIFF") && head.get(8 .. 12) == Some(b"WEBP") {
+		&["image/webp"]
+	} else if head.starts_with(b"RIFF") && head.get(8 .. 12) == Some(b"WAVE") {
+		&["audio/wav"]
+	} else if isobmff::is_isobmff(head) {
+		sniff_isobmff(head)
+	} else if matroska::is_matroska(head) {
+		if matroska::media_info(head).is_some_and(|info| info.video_codec.is_none()) {
+			&["audio/webm", "video/webm"]
+		} else {
+			&["video/webm", "audio/webm"]
+		}
+	} else if ogg::is_ogg(head) {
+		if ogg::media_info(head, None).is_some_and(|info| info.video_codec.is_some()) {
+			&["video/ogg", "audio/ogg"]
+		} else {
+			&["audio/ogg", "video/ogg"]
+		}
+	} else if raw::is_tiff(head) {
+		sniff_tiff(head)
+	} else if head.starts_with(b"fLaC") {
+		&["audio/flac"]
+	} else if let Some(stream) = head.strip_prefix(b"ID3") {
+		sniff_id3_tagged(stream)
+	} else if is_adts_frame(head) {
+		&["audio/aac"]
+	} else if is_mpeg_audio_frame(head) {
+		&["audio/mpeg"]
+	} else if is_svg(head) {
+		&["image/svg+xml"]
+	} else {
+		&[]
+	}
+}
+
+/// Whether an acTL chunk precedes the image data, which marks an APNG.
+fn is_animated_png(data: &[u8]) -> bool {
+	let mut rest = data.get(PNG_SIGNATURE.len() ..).unwrap_or_default();
+	while let Some(header) = rest.get(.. 8) {
+		let len = header.get(.. 4).and_then(|len| len.try_into().ok()).map(u32::from_be_bytes);
+		match header.get(4 ..) {
+			Some(b"acTL") => return true,
+			Some(b"IDAT") => return false,
+			_ => {}
+		}
+		let Some(next) = len.and_then(|len| usize::try_from(len).ok()).map(|len| 12 + len) else {
+			return false;
+		};
+		rest = rest.get(next ..).unwrap_or_default();
+	}
+	false
+}
+
+fn sniff_isobmff(head: &[u8]) -> &'static [&'static str] {
+	let brands = isobmff::find_box(head, &[FILE_TYPE])
+		.map(|file_type| {
+			// The major brand, then the compatible brands after the minor version.
+			let major = file_type.get(.. 4).into_iter();
+			major.chain(file_type.get(8 ..).unwrap_or_default().chunks_exact(4)).collect::<Vec<_>>()
+		})
+		.unwrap_or_default();
+	let has_brand = |candidates: &[&[u8; 4]]| {
+		brands.iter().any(|brand| candidates.iter().any(|candidate| **brand == **candidate))
+	};
+	if has_brand(&AVIF_BRANDS) {
+		&["image/avif"]
+	} else if has_brand(&HEIC_BRANDS) {
+		&["image/heic", "image/heif"]
+	} else if has_brand(&HEIF_BRANDS) {
+		&["image/heif", "image/heic"]
+	} else if has_brand(&AUDIO_BRANDS) {
+		&["audio/mp4", "video/mp4"]
+	} else {
+		&["video/mp4", "audio/mp4"]
+	}
+}
+
+/// Only TIFF-based RAW formats are accepted, not TIFF itself.
+fn sniff_tiff(head: &[u8]) -> &'static [&'static str] {
+	if raw::is_cr2(head) {
+		&["image/x-canon-cr2"]
+	} else if raw::is_dng(head) {
+		&["image/x-adobe-dng"]
+	} else {
+		&[]
+	}
+}
+
+/// Sniffs the stream after an ID3v2 tag, which usually fronts MP3 but may front
+/// AAC or FLAC. A tag longer than the sniffed bytes is taken to front MP3.
+fn sniff_id3_tagged(stream: &[u8]) -> &'static [&'static str] {
+	// Version and flags, then the tag size as a 28-bit "syncsafe" integer.
+	let Some(size) = stream.get(3 .. 7) else {
+		return &[];
+	};
+	let size = size.iter().fold(0usize, |size, byte| (size << 7) | usize::from(byte & 0x7f));
+	let footer = if stream.get(2).is_some_and(|flags| flags & 0x10 != 0) { 10 } else { 0 };
+	match stream.get(7 + size + footer ..) {
+		None | Some([]) => &["audio/mpeg"],
+		Some(frames) if frames.starts_with(b"fLaC") => &["audio/flac"],
+		Some(frames) if is_adts_frame(frames) => &["audio/aac"],
+		Some(frames) if is_mpeg_audio_frame(frames) => &["audio/mpeg"],
+		Some(_) => &[],
+	}
+}
+
+/// An ADTS frame header: a 12-bit sync word and layer 0.
+fn is_adts_frame(data: &[u8]) -> bool {
+	matches!(data, [0xff, second, ..] if second & 0xf6 == 0xf0)
+}
+
+/// An MPEG audio frame header: an 11-bit sync word, a defined version and a
+/// non-reserved layer.
+fn is_mpeg_audio_frame(data: &[u8]) -> bool {
+	matches!(data, [0xff, second, ..]
+		if second & 0xe0 == 0xe0 && second & 0x18 != 0x08 && second & 0x06 != 0)
+}
+
+/// Whether the text starts as markup and its root element is `<svg>`. HTML is
+/// markup too, so a document that mentions `<svg>` inside `<html>` (inline SVG)
+/// is not accepted.
+fn is_svg(head: &[u8]) -> bool {
+	let text = String::from_utf8_lossy(head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head))
+		.to_ascii_lowercase();
+	let text = text.trim_start();
+	if !text.starts_with('<') || text.contains("<html") {
+		return false;
+	}
+	text.contains("<svg")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::sniff_content_types;
+
+	#[test]
+	fn images_are_recognised_by_signature() {
+		assert_eq!(sniff_content_types(b"\xff\xd8\xff\xe0\0\x10JFIF"), ["image/jpeg"]);
+		assert_eq!(sniff_content_types(b"GIF89a\x01\0\x01\0"), ["image/gif"]);
+		assert_eq!(sniff_content_types(b"RIFF\x10\0\0\0WEBPVP8 "), ["image/webp"]);
+		assert_eq!(sniff_content_types(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf"), ["image/avif"]);
+
+		let png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x01\0\0\0\x01\x08\x02\0\0\0\0\0\0\0";
+		assert_eq!(sniff_content_types(png), ["image/png", "image/apng"]);
+		let apng = [png.as_slice(), b"\0\0\0\x08acTL\0\0\0\x02\0\0\0\0\0\0\0\0"].concat();
+		assert_eq!(sniff_content_types(&apng), ["image/apng", "image/png"]);
+	}
+
+	#[test]
+	fn heif_and_camera_raw_are_recognised() {
+		assert_eq!(
+			sniff_content_types(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic"),
+			["image/heic", "image/heif"]
+		);
+		assert_eq!(
+			sniff_content_types(b"\0\0\0\x14ftypmif1\0\0\0\0mif1"),
+			["image/heif", "image/heic"]
+		);
+
+		let cr2 = b"II*\0\x10\0\0\0CR\x02\0\0\0\0\0";
+		assert_eq!(sniff_content_types(cr2), ["image/x-canon-cr2"]);
+		// One IFD entry: DNGVersion 1.4.0.0.
+		let dng = b"II*\0\x08\0\0\0\x01\0\x12\xc6\x01\0\x04\0\0\0\x01\x04\0\0\0\0\0\0";
+		assert_eq!(sniff_content_types(dng), ["image/x-adobe-dng"]);
+		// The same IFD without the tag is a plain TIFF.
+		let tiff = b"II*\0\x08\0\0\0\x01\0\x00\x01\x03\0\x01\0\0\0\x10\0\0\0\0\0\0\0";
+		assert!(sniff_content_types(tiff).is_empty());
+	}
+
+	#[test]
+	fn svg_is_recognised_but_html_is_not() {
+		let svg = b"\xef\xbb\xbf<?xml version=\"1.0\"?>\n<!-- drawn by hand -->\n<svg \
+			xmlns=\"http://www.w3.org/2000/svg\" />";
+		assert_eq!(sniff_content_types(svg), ["image/svg+xml"]);
+		assert!(sniff_content_types(b"<!DOCTYPE html><html><body><svg></svg>").is_empty());
+		assert!(sniff_content_types(b"<script>alert(1)</script>").is_empty());
+		assert!(sniff_content_types(b"plain text").is_empty());
+	}
+
+	#[test]
+	fn audio_is_recognised_by_signature() {
+		assert_eq!(sniff_content_types(b"RIFF\x10\0\0\0WAVEfmt "), ["audio/wav"]);
+		assert_eq!(sniff_content_types(b"fLaC\0\0\0\x22"), ["audio/flac"]);
+		assert_eq!(sniff_content_types(b"\xff\xfb\x90\x64"), ["audio/mpeg"]);
+		assert_eq!(sniff_content_types(b"\xff\xf1\x50\x80"), ["audio/aac"]);
+		assert_eq!(
+			sniff_content_types(b"ID3\x04\0\0\0\0\0\x02\0\0\xff\xfb\x90\x64"),
+			["audio/mpeg"]
+		);
+		assert_eq!(sniff_content_types(b"ID3\x04\0\0\0\0\0\0fLaC"), ["audio/flac"]);
+		assert_eq!(
+			sniff_content_types(b"\0\0\0\x18ftypM4A \0\0\0\0M4A isom"),
+			["audio/mp4", "video/mp4"]
+		);
+	}
+
+	#[test]
+	fn video_containers_accept_either_media_type() {
+		let mp4 = include_bytes!("../../tests/fixtures/av1-cover-art.mp4");
+		assert_eq!(sniff_content_types(mp4), ["video/mp4", "audio/mp4"]);
+		assert_eq!(
+			sniff_content_types(include_bytes!("../../tests/fixtures/tagged-tone.wav")),
+			["audio/wav"]
+		);
+		let ogg_opus = b"OggS\0\x02\0\0\0\0\0\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\x01\x13\
+			OpusHead\x01\x02\0\0\x80\xbb\0\0\0\0\0";
+		assert_eq!(sniff_content_types(ogg_opus), ["audio/ogg", "video/ogg"]);
+		assert_eq!(
+			sniff_content_types(b"\x1a\x45\xdf\xa3\x87\x42\x82\x84webm"),
+			["video/webm", "audio/webm"]
+		);
+	}
+}
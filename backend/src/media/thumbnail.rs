@@ -0,0 +1,359 @@
+//! Sized still renditions ("thumbnails") of uploaded images.
+//!
+//! Rendering is CPU-bound and synchronous; the derivative worker runs it on the
+//! blocking pool with the full source bytes. Each size is produced in every
+//! [`ThumbnailFormat`], oriented according to the source's EXIF orientation and
+//! never upscaled, so a small source yields renditions at its own dimensions.
+//!
+//! Videos get a [`Poster`] instead: a still frame kept in its own format, from
+//! which the same thumbnails are rendered. Photos in formats browsers cannot
+//! show (HEIC, camera RAW) get a full-size [`Display`] JPEG as well.
+
+use {
+	anyhow::Context,
+	async_graphql::Enum,
+	image::{
+		DynamicImage,
+		ImageDecoder,
+		ImageReader,
+		codecs::{
+			avif::AvifEncoder,
+			jpeg::JpegEncoder,
+			webp::WebPEncoder,
+		},
+		imageops::FilterType,
+	},
+	std::io::Cursor,
+};
+
+/// AVIF encoder speed (1 slowest to 10 fastest). Thumbnails favour throughput.
+const AVIF_SPEED: u8 = 8;
+/// AVIF encoder quality (1 to 100).
+const AVIF_QUALITY: u8 = 70;
+
+/// JPEG encoder quality (1 to 100) of display renditions, which are looked at
+/// full screen.
+const DISPLAY_QUALITY: u8 = 90;
+
+/// The `object_derivatives.kind` of a video's poster frame.
+pub const POSTER_KIND: &str = "poster";
+/// The `object_derivatives.kind` of the browser-viewable rendition of a photo
+/// in a format browsers cannot show.
+pub const DISPLAY_KIND: &str = "display";
+
+#[derive(Enum, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ThumbnailSize {
+	Small,
+	Medium,
+	Large,
+}
+
+impl ThumbnailSize {
+	/// Every size, largest first: each rendition is resized from the previous
+	/// one, which is much cheaper than resizing the full source every time.
+	pub const ALL: [Self; 3] = [Self::Large, Self::Medium, Self::Small];
+
+	/// The longest edge of the rendition, in pixels.
+	pub const fn max_edge(self) -> u32 {
+		match self {
+			Self::Small => 256,
+			Self::Medium => 1024,
+			Self::Large => 2048,
+		}
+	}
+
+	const fn label(self) -> &'static str {
+		match self {
+			Self::Small => "small",
+			Self::Medium => "medium",
+			Self::Large => "large",
+		}
+	}
+}
+
+#[derive(Enum, Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ThumbnailFormat {
+	#[default]
+	Webp,
+	Avif,
+}
+
+impl ThumbnailFormat {
+	pub const ALL: [Self; 2] = [Self::Webp, Self::Avif];
+
+	pub const fn content_type(self) -> &'static str {
+		match self {
+			Self::Webp => "image/webp",
+			Self::Avif => "image/avif",
+		}
+	}
+
+	pub const fn extension(self) -> &'static str {
+		match self {
+			Self::Webp => "webp",
+			Self::Avif => "avif",
+		}
+	}
+}
+
+/// The `object_derivatives.kind` of a thumbnail rendition, e.g.
+/// `thumbnail_small_webp`.
+pub fn thumbnail_kind(
+	size: ThumbnailSize,
+	format: ThumbnailFormat,
+) -> String {
+	format!("thumbnail_{}_{}", size.label(), format.extension())
+}
+
+/// Whether thumbnails can be rendered from objects of `content_type`. SVG is
+/// left to the browser, and AVIF sources have no pure-Rust decoder.
+pub fn supports_thumbnails(content_type: &str) -> bool {
+	matches!(content_type, "image/png" | "image/apng" | "image/jpeg" | "image/gif" | "image/webp")
+}
+
+/// Whether objects of `content_type` are photos browsers cannot show, which
+/// get a [`Display`] rendition. HEIC and HEIF need the configured `ffmpeg`;
+/// camera RAW files fall back to it when they embed no decodable preview.
+pub fn needs_display_rendition(content_type: &str) -> bool {
+	matches!(content_type, "image/heic" | "image/heif" | "image/x-adobe-dng" | "image/x-canon-cr2")
+}
+
+/// One encoded rendition.
+#[derive(Clone, Debug)]
+pub struct Thumbnail {
+	pub size: ThumbnailSize,
+	pub format: ThumbnailFormat,
+	pub width: u32,
+	pub height: u32,
+	pub bytes: Vec<u8>,
+}
+
+impl Thumbnail {
+	pub fn kind(&self) -> String {
+		thumbnail_kind(self.size, self.format)
+	}
+}
+
+/// A still frame standing in for a video, stored as extracted.
+#[derive(Clone, Debug)]
+pub struct Poster {
+	pub content_type: &'static str,
+	pub extension: &'static str,
+	pub width: u32,
+	pub height: u32,
+	pub bytes: Vec<u8>,
+}
+
+/// A full-size JPEG standing in for a photo browsers cannot show.
+#[derive(Clone, Debug)]
+pub struct Display {
+	pub width: u32,
+	pub height: u32,
+	pub bytes: Vec<u8>,
+}
+
+impl Display {
+	pub const CONTENT_TYPE: &str = "image/jpeg";
+	pub const EXTENSION: &str = "jpg";
+}
+
+/// Decodes `source` and renders every size in every format. Animated sources
+/// contribute their first frame.
+pub fn render_thumbnails(source: &[u8]) -> anyhow::Result<Vec<Thumbnail>> {
+	resize_and_encode(decode_oriented(source)?)
+}
+
+/// Checks that `frame` is a decodable image and renders thumbnails from it.
+pub fn render_poster(frame: Vec<u8>) -> anyhow::Result<(Poster, Vec<Thumbnail>)> {
+	let format = image::guess_format(&frame).context("Failed to detect poster format")?;
+	let image = decode_oriented(&frame)?;
+	let poster = Poster {
+		content_type: format.to_mime_type(),
+		extension: format.extensions_str().first().copied().unwrap_or("img"),
+		width: image.width(),
+		height: image.height(),
+		bytes: frame,
+	};
+	Ok((poster, resize_and_encode(image)?))
+}
+
+/// Encodes an already decoded and oriented photo as a display JPEG and renders
+/// thumbnails from it.
+pub fn render_display(image: DynamicImage) -> anyhow::Result<(Display, Vec<Thumbnail>)> {
+	let mut bytes = Vec::new();
+	// JPEG has no alpha channel.
+	DynamicImage::ImageRgb8(image.to_rgb8())
+		.write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, DISPLAY_QUALITY))
+		.context("Failed to encode display rendition")?;
+	let display = Display {
+		width: image.width(),
+		height: image.height(),
+		bytes,
+	};
+	Ok((display, resize_and_encode(image)?))
+}
+
+fn resize_and_encode(mut image: DynamicImage) -> anyhow::Result<Vec<Thumbnail>> {
+	let mut thumbnails = Vec::with_capacity(ThumbnailSize::ALL.len() * ThumbnailFormat::ALL.len());
+	for size in ThumbnailSize::ALL {
+		let max_edge = size.max_edge();
+		if image.width() > max_edge || image.height() > max_edge {
+			image = image.resize(max_edge, max_edge, FilterType::Lanczos3);
+		}
+		for format in ThumbnailFormat::ALL {
+			thumbnails.push(Thumbnail {
+				size,
+				format,
+				width: image.width(),
+				height: image.height(),
+				bytes: encode(&image, format)?,
+			});
+		}
+	}
+	Ok(thumbnails)
+}
+
+/// Decodes `source`, applies its EXIF orientation, and normalizes the pixels to
+/// 8-bit RGB(A), the layouts both encoders accept.
+pub fn decode_oriented(source: &[u8]) -> anyhow::Result<DynamicImage> {
+	let mut decoder = ImageReader::new(Cursor::new(source))
+		.with_guessed_format()
+		.context("Failed to detect image format")?
+		.into_decoder()
+		.context("Failed to open image decoder")?;
+	let orientation = decoder.orientation().context("Failed to read image orientation")?;
+	let mut image = DynamicImage::from_decoder(decoder).context("Failed to decode image")?;
+	image.apply_orientation(orientation);
+	Ok(if image.color().has_alpha() {
+		DynamicImage::ImageRgba8(image.into_rgba8())
+	} else {
+		DynamicImage::ImageRgb8(image.into_rgb8())
+	})
+}
+
+fn encode(
+	image: &DynamicImage,
+	format: ThumbnailFormat,
+) -> anyhow::Result<Vec<u8>> {
+	let mut bytes = Vec::new();
+	match format {
+		// The pure-Rust WebP encoder only writes lossless images.
+		ThumbnailFormat::Webp => image.write_with_encoder(WebPEncoder::new_lossless(&mut bytes)),
+		ThumbnailFormat::Avif => image.write_with_encoder(AvifEncoder::new_with_speed_quality(
+			&mut bytes,
+			AVIF_SPEED,
+			AVIF_QUALITY,
+		)),
+	}
+	.with_context(|| format!("Failed to encode {} thumbnail", format.extension()))?;
+	Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			ThumbnailFormat,
+			ThumbnailSize,
+			render_display,
+			render_poster,
+			render_thumbnails,
+			thumbnail_kind,
+		},
+		image::{
+			DynamicImage,
+			ImageFormat,
+			RgbImage,
+		},
+		std::io::Cursor,
+	};
+
+	fn png(
+		width: u32,
+		height: u32,
+	) -> anyhow::Result<Vec<u8>> {
+		let mut bytes = Vec::new();
+		DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+			image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+		}))
+		.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+		Ok(bytes)
+	}
+
+	#[test]
+	fn render_thumbnails_fits_each_size_without_upscaling() -> anyhow::Result<()> {
+		let thumbnails = render_thumbnails(&png(400, 200)?)?;
+
+		let dimensions = |size| {
+			thumbnails
+				.iter()
+				.filter(|thumbnail| thumbnail.size == size)
+				.map(|thumbnail| (thumbnail.format, thumbnail.width, thumbnail.height))
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(
+			dimensions(ThumbnailSize::Large),
+			vec![(ThumbnailFormat::Webp, 400, 200), (ThumbnailFormat::Avif, 400, 200),]
+		);
+		assert_eq!(
+			dimensions(ThumbnailSize::Medium),
+			vec![(ThumbnailFormat::Webp, 400, 200), (ThumbnailFormat::Avif, 400, 200),]
+		);
+		assert_eq!(
+			dimensions(ThumbnailSize::Small),
+			vec![(ThumbnailFormat::Webp, 256, 128), (ThumbnailFormat::Avif, 256, 128),]
+		);
+
+		for thumbnail in &thumbnails {
+			let format = image::guess_format(&thumbnail.bytes)?;
+			let expected = match thumbnail.format {
+				ThumbnailFormat::Webp => ImageFormat::WebP,
+				ThumbnailFormat::Avif => ImageFormat::Avif,
+			};
+			assert_eq!(format, expected);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn render_thumbnails_rejects_undecodable_input() {
+		assert!(render_thumbnails(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>").is_err());
+	}
+
+	#[test]
+	fn render_poster_keeps_the_frame_and_renders_thumbnails() -> anyhow::Result<()> {
+		let frame = png(320, 180)?;
+		let (poster, thumbnails) = render_poster(frame.clone())?;
+
+		assert_eq!(
+			(poster.content_type, poster.extension, poster.width, poster.height),
+			("image/png", "png", 320, 180)
+		);
+		assert_eq!(poster.bytes, frame);
+		assert_eq!(thumbnails.len(), ThumbnailSize::ALL.len() * ThumbnailFormat::ALL.len());
+		Ok(())
+	}
+
+	#[test]
+	fn render_display_encodes_a_full_size_jpeg() -> anyhow::Result<()> {
+		let image = image::load_from_memory(&png(300, 100)?)?;
+		let (display, thumbnails) = render_display(image)?;
+
+		assert_eq!((display.width, display.height), (300, 100));
+		assert_eq!(image::guess_format(&display.bytes)?, ImageFormat::Jpeg);
+		assert_eq!(thumbnails.len(), ThumbnailSize::ALL.len() * ThumbnailFormat::ALL.len());
+		Ok(())
+	}
+
+	#[test]
+	fn thumbnail_kinds_name_size_and_format() {
+		assert_eq!(
+			thumbnail_kind(ThumbnailSize::Small, ThumbnailFormat::Webp),
+			"thumbnail_small_webp"
+		);
+		assert_eq!(
+			thumbnail_kind(ThumbnailSize::Large, ThumbnailFormat::Avif),
+			"thumbnail_large_avif"
+		);
+	}
+}
@@ -0,0 +1,212 @@
+//! Container probing for uploaded videos.
+//!
+//! Duration, dimensions and codecs are read from container metadata alone, so
+//! probing needs no decoder. A poster frame is taken from embedded cover art
+//! when the file carries some, then from an H.264 keyframe decoded in process;
+//! other codecs fall back to an external `ffmpeg`, when one is configured.
+
+use super::{
+	MediaInfo,
+	MetadataSource,
+	h264::Keyframe,
+	isobmff::{
+		self,
+		Boxes,
+		MOVIE,
+		SOUND_HANDLER,
+		TRACK,
+		VIDEO_HANDLER,
+	},
+	matroska,
+	ogg,
+};
+
+/// Whether `content_type` names a container [`probe_video`] understands.
+pub fn supports_probing(content_type: &str) -> bool {
+	matches!(content_type, "video/mp4" | "video/webm" | "video/ogg")
+}
+
+/// Reads stream properties from whichever container `source` holds, or `None`
+/// when the container is not recognised.
+pub fn probe_video(source: &MetadataSource) -> Option<MediaInfo> {
+	if isobmff::is_isobmff(&source.head) {
+		movie_payload(source).map(movie_info)
+	} else if matroska::is_matroska(&source.head) {
+		matroska::media_info(&source.head)
+	} else if ogg::is_ogg(&source.head) {
+		ogg::media_info(&source.head, source.tail.as_deref())
+	} else {
+		None
+	}
+}
+
+/// Returns the bytes of an image embedded in the container as cover art.
+pub fn embedded_poster(source: &MetadataSource) -> Option<&[u8]> {
+	if isobmff::is_isobmff(&source.head) {
+		movie_payload(source).and_then(isobmff::cover_art)
+	} else if matroska::is_matroska(&source.head) {
+		matroska::cover_art(&source.head)
+	} else {
+		None
+	}
+}
+
+/// Locates the keyframe of an MP4's first video track to take a poster from
+/// at `seek_seconds`, when that track is H.264.
+pub fn h264_keyframe(
+	source: &MetadataSource,
+	seek_seconds: f64,
+) -> Option<Keyframe> {
+	if !isobmff::is_isobmff(&source.head) {
+		return None;
+	}
+	let track = Boxes::new(movie_payload(source)?)
+		.filter(|(box_type, _)| *box_type == TRACK)
+		.map(|(_, payload)| payload)
+		.find(|track| isobmff::Track::parse(track).handler == Some(VIDEO_HANDLER))?;
+	let (entry_type, entry) = isobmff::sample_entry(track)?;
+	if !matches!(&entry_type, b"avc1" | b"avc3") {
+		return None;
+	}
+	// The visual sample entry's fixed fields take 78 bytes before its child
+	// boxes.
+	let configuration = isobmff::find_box(entry.get(78 ..)?, &[*b"avcC"])?;
+	let sample = isobmff::sync_sample_at(track, seek_seconds)?;
+	Keyframe::new(configuration, sample.offset, sample.length)
+}
+
+fn movie_payload(source: &MetadataSource) -> Option<&[u8]> {
+	isobmff::find_box(source.movie.as_deref().unwrap_or(&source.head), &[MOVIE])
+}
+
+fn movie_info(movie: &[u8]) -> MediaInfo {
+	let mut info = MediaInfo {
+		duration_seconds: isobmff::find_box(movie, &[isobmff::MOVIE_HEADER])
+			.and_then(isobmff::MovieHeader::parse)
+			.and_then(|header| header.duration_seconds())
+			.filter(|seconds| *seconds > 0.0),
+		..MediaInfo::default()
+	};
+	for track in isobmff::tracks(movie) {
+		let codec = track.sample_entry.map(sample_entry_codec_name);
+		match track.handler {
+			Some(VIDEO_HANDLER) if info.video_codec.is_none() => {
+				info.width = (track.width > 0).then_some(track.width);
+				info.height = (track.height > 0).then_some(track.height);
+				info.video_codec = codec;
+			}
+			Some(SOUND_HANDLER) if info.audio_codec.is_none() => info.audio_codec = codec,
+			_ => {}
+		}
+	}
+	info
+}
+
+/// Maps an ISO-BMFF sample entry type to the short name used across containers.
+fn sample_entry_codec_name(entry_type: [u8; 4]) -> String {
+	let name = match &entry_type {
+		b"avc1" | b"avc3" => "h264",
+		b"hvc1" | b"hev1" => "hevc",
+		b"av01" => "av1",
+		b"vp08" => "vp8",
+		b"vp09" => "vp9",
+		b"mp4a" => "aac",
+		b"Opus" => "opus",
+		b"fLaC" => "flac",
+		b"ac-3" => "ac3",
+		b"ec-3" => "eac3",
+		_ => return String::from_utf8_lossy(&entry_type).trim().to_ascii_lowercase(),
+	};
+	name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use {
+		super::{
+			embedded_poster,
+			h264_keyframe,
+			probe_video,
+		},
+		crate::media::MetadataSource,
+	};
+
+	/// Ten 64x48 AV1 frames at 10 fps, with JPEG cover art and a location.
+	const FIXTURE: &[u8] = include_bytes!("../../tests/fixtures/av1-cover-art.mp4");
+
+	/// Three 48x32 H.264 frames at 0.4s intervals and no cover art: a keyframe
+	/// with a horizontal ramp, a frame that repeats it, and a flat keyframe.
+	const H264_FIXTURE: &[u8] = include_bytes!("../../tests/fixtures/h264-keyframes.mp4");
+
+	fn source(head: &[u8]) -> MetadataSource {
+		MetadataSource {
+			head: head.to_vec(),
+			..MetadataSource::default()
+		}
+	}
+
+	#[test]
+	fn probe_video_reads_the_fixture_movie() {
+		let info = probe_video(&source(FIXTURE)).unwrap_or_default();
+		assert_eq!(info.duration_seconds, Some(1.0));
+		assert_eq!((info.width, info.height), (Some(64), Some(48)));
+		assert_eq!(info.video_codec.as_deref(), Some("av1"));
+		assert_eq!(info.audio_codec, None);
+	}
+
+	#[test]
+	fn embedded_poster_is_the_fixture_cover_art() -> anyhow::Result<()> {
+		let source = source(FIXTURE);
+		let poster =
+			embedded_poster(&source).ok_or_else(|| anyhow::anyhow!("fixture has cover art"))?;
+		assert_eq!(image::guess_format(poster)?, image::ImageFormat::Jpeg);
+		Ok(())
+	}
+
+	#[test]
+	fn probe_video_uses_a_separately_read_movie_box() {
+		// A head that stops before the movie box, as for a large file.
+		let moov_start = FIXTURE
+			.windows(4)
+			.position(|window| window == b"moov")
+			.map_or(0, |position| position.saturating_sub(4));
+		let source = MetadataSource {
+			head: FIXTURE.get(.. moov_start).unwrap_or_default().to_vec(),
+			movie: FIXTURE.get(moov_start ..).map(<[u8]>::to_vec),
+			tail: None,
+		};
+		assert!(probe_video(&source).is_some_and(|info| info.duration_seconds == Some(1.0)));
+		assert!(probe_video(&MetadataSource::default()).is_none());
+	}
+
+	#[test]
+	fn h264_keyframe_is_the_last_sync_sample_by_the_seek_time() -> anyhow::Result<()> {
+		assert!(h264_keyframe(&source(FIXTURE), 0.0).is_none());
+
+		let source = source(H264_FIXTURE);
+		let info = probe_video(&source).unwrap_or_default();
+		assert_eq!(info.video_codec.as_deref(), Some("h264"));
+		assert_eq!((info.width, info.height), (Some(48), Some(32)));
+		assert!(embedded_poster(&source).is_none());
+
+		let first = h264_keyframe(&source, 0.12).ok_or_else(|| anyhow::anyhow!("keyframe"))?;
+		// The second frame is not a sync sample, so seeking into it still lands
+		// on the first.
+		assert_eq!(h264_keyframe(&source, 0.5), Some(first.clone()));
+		let last = h264_keyframe(&source, 0.9).ok_or_else(|| anyhow::anyhow!("keyframe"))?;
+		assert!(last.offset > first.offset);
+		assert_eq!(last.length, first.length);
+
+		let sample = usize::try_from(first.offset)?;
+		let sample = H264_FIXTURE
+			.get(sample .. sample + usize::try_from(first.length)?)
+			.ok_or_else(|| anyhow::anyhow!("sample inside the fixture"))?;
+		let frame = image::load_from_memory(&first.decode(sample)?)?.to_rgb8();
+		assert_eq!(frame.dimensions(), (48, 32));
+		// The luma ramp brightens left to right.
+		let left = frame.get_pixel(0, 16).0.iter().map(|c| u32::from(*c)).sum::<u32>();
+		let right = frame.get_pixel(47, 16).0.iter().map(|c| u32::from(*c)).sum::<u32>();
+		assert!(right > left);
+		Ok(())
+	}
+}
@@ -15,7 +15,9 @@ pub use {
 	config::ObjectLifecycleConfig,
 	service::{
 		CreatedObjectUploadSession,
+		DeduplicatedUploadSession,
 		ObjectLifecycleService,
+		ObjectMetadataUpdate,
 		ObjectUploadSessionCreate,
 		PresignedObjectUploadPart,
 	},
@@ -38,6 +38,31 @@ pub struct ObjectLifecycleConfig {
 	pub storage_deletion_batch_size: i64,
 	#[serde(default = "ObjectLifecycleConfig::default_storage_deletion_max_attempts")]
 	pub storage_deletion_max_attempts: i32,
+	#[serde(default = "ObjectLifecycleConfig::default_metadata_read_max_bytes")]
+	pub metadata_read_max_bytes: i64,
+	#[serde(default = "ObjectLifecycleConfig::default_trash_retention_seconds")]
+	pub trash_retention_seconds: i64,
+	#[serde(default)]
+	pub content_type_mismatch_policy: ContentTypeMismatchPolicy,
+	/// Whether HEIC photos may be uploaded. Not read from the configuration:
+	/// set at startup once the configured `ffmpeg` proves it can decode them,
+	/// since browsers cannot show a HEIC photo without a display rendition.
+	#[serde(skip)]
+	pub heic_uploads: bool,
+}
+
+/// What completing an upload does when the object's magic bytes do not match
+/// its declared content type.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentTypeMismatchPolicy {
+	/// Fail the upload with a validation error and queue the object for
+	/// deletion.
+	#[default]
+	Reject,
+	/// Keep the object under the detected content type. Objects whose bytes
+	/// match no accepted type are still rejected.
+	Relabel,
 }
 
 impl ObjectLifecycleConfig {
@@ -104,6 +129,19 @@ impl ObjectLifecycleConfig {
 		10
 	}
 
+	/// Upper bound on each ranged read made to extract embedded metadata after
+	/// an upload completes. Image metadata sits in the first few kilobytes; the
+	/// bound mostly matters for the `moov` box of long videos.
+	pub const fn default_metadata_read_max_bytes() -> i64 {
+		8 * 1024 * 1024
+	}
+
+	/// How long a deleted object stays in its owner's trash, restorable, before
+	/// maintenance queues it for storage deletion. Thirty days.
+	pub const fn default_trash_retention_seconds() -> i64 {
+		30 * 24 * 60 * 60
+	}
+
 	pub fn validate(&self) -> anyhow::Result<()> {
 		ensure_positive!(self, pending_upload_timeout_seconds);
 		if self.upload_max_file_size_bytes <= 0 {
@@ -133,6 +171,8 @@ impl ObjectLifecycleConfig {
 		self.upload_session_total_parts(self.upload_max_file_size_bytes)?;
 		ensure_positive!(self, maintenance_interval_seconds);
 		self.storage_deletion().validate("storage_deletion")?;
+		ensure_positive!(self, metadata_read_max_bytes);
+		ensure_positive!(self, trash_retention_seconds);
 		Ok(())
 	}
 
@@ -219,6 +259,10 @@ impl Default for ObjectLifecycleConfig {
 			maintenance_interval_seconds: Self::default_maintenance_interval_seconds(),
 			storage_deletion_batch_size: Self::default_storage_deletion_batch_size(),
 			storage_deletion_max_attempts: Self::default_storage_deletion_max_attempts(),
+			metadata_read_max_bytes: Self::default_metadata_read_max_bytes(),
+			trash_retention_seconds: Self::default_trash_retention_seconds(),
+			content_type_mismatch_policy: ContentTypeMismatchPolicy::default(),
+			heic_uploads: false,
 		}
 	}
 }
@@ -326,6 +370,14 @@ mod tests {
 				storage_deletion_max_attempts: 0,
 				..valid.clone()
 			},
+			ObjectLifecycleConfig {
+				metadata_read_max_bytes: 0,
+				..valid.clone()
+			},
+			ObjectLifecycleConfig {
+				trash_retention_seconds: 0,
+				..valid.clone()
+			},
 		] {
 			assert!(invalid.validate().is_err());
 		}
@@ -1,6 +1,7 @@
 use {
 	super::{
 		config::{
+			ContentTypeMismatchPolicy,
 			ObjectLifecycleConfig,
 			part_count,
 			part_length,
@@ -11,35 +12,76 @@ use {
 		},
 	},
 	crate::{
+		constants::{
+			OBJECT_TEXT_MAX_LENGTH,
+			OBJECT_TITLE_MAX_LENGTH,
+		},
 		db::queries::{
 			CLAIM_EXPIRED_OBJECT_UPLOAD_SESSIONS_QUERY,
 			COUNT_PARKED_OBJECT_STORAGE_DELETIONS_QUERY,
 			COUNT_PARKED_OBJECT_UPLOAD_SESSIONS_QUERY,
+			DELETE_OBJECT_ALLOWED_GROUPS_QUERY,
 			DELETE_OBJECT_ALLOWED_USERS_QUERY,
 			DELETE_OBJECT_UPLOAD_SESSION_QUERY,
 			DELETE_PENDING_OBJECT_UPLOAD_BY_SESSION_QUERY,
 			DELETE_PENDING_OBJECT_UPLOAD_QUERY,
+			FILL_OBJECT_METADATA_FROM_MEDIA_QUERY,
 			FINALIZE_OBJECT_UPLOAD_QUERY,
+			INSERT_OBJECT_DERIVATIVE_JOB_QUERY,
+			INSERT_OBJECT_DERIVATIVE_STORAGE_DELETIONS_QUERY,
 			INSERT_OBJECT_QUERY,
+			INSERT_OBJECT_SANITIZED_COPY_JOB_QUERY,
 			INSERT_OBJECT_STORAGE_DELETIONS_QUERY,
 			INSERT_OBJECT_UPLOAD_SESSION_QUERY,
+			LOCK_AVAILABLE_OBJECT_FOR_UPDATE_QUERY,
+			MARK_EXPIRED_TRASH_DELETE_PENDING_QUERY,
 			MARK_OBJECT_UPLOAD_SESSION_CLEANUP_FAILED_QUERY,
-			MARK_OBJECTS_DELETE_PENDING_QUERY,
+			MARK_OBJECTS_TRASHED_QUERY,
 			MARK_STALE_UPLOADS_DELETE_PENDING_QUERY,
 			MARK_UPLOAD_DELETE_PENDING_QUERY,
+			MARK_USER_TRASH_DELETE_PENDING_QUERY,
+			REPLACE_OBJECT_ALLOWED_GROUPS_QUERY,
 			REPLACE_OBJECT_ALLOWED_USERS_QUERY,
+			RESTORE_TRASHED_OBJECT_QUERY,
 			SELECT_ACTIVE_OBJECT_UPLOAD_SESSION_FOR_USER_QUERY,
+			SELECT_AVAILABLE_OBJECT_BY_CONTENT_HASH_FOR_USER_QUERY,
 			SELECT_AVAILABLE_OBJECT_FOR_USER_QUERY,
+			SELECT_OBJECT_BY_ID_QUERY,
+			SELECT_OBJECT_REVISION_OLD_VALUES_QUERY,
 			SELECT_OBJECT_UPLOAD_SESSION_FOR_USER_QUERY,
 			UPDATE_OBJECT_QUERY,
+			UPSERT_OBJECT_MEDIA_METADATA_QUERY,
 		},
 		errors::AppError,
 		graphql::objects::{
+			album::distinct_ids,
 			location::Location,
+			object_revision::{
+				ObjectMetadataSnapshot,
+				record_object_revision,
+				revision_values,
+			},
 			s3_object::{
+				LocationPrecisionOverride,
+				MetadataPrivacyOverride,
 				PublicityOverride,
 				S3Object,
 			},
+			tag::{
+				add_object_tags,
+				normalize_tags,
+				object_tag_names,
+				replace_object_tags,
+			},
+			user_group::require_granted_groups,
+		},
+		media::{
+			MetadataSource,
+			extract_metadata,
+			sniff::{
+				SNIFF_READ_BYTES,
+				sniff_content_types,
+			},
 		},
 		outbox::drain_outbox,
 		storage::{
@@ -85,6 +127,32 @@ pub struct ObjectUploadSessionCreate {
 	pub user_id: i64,
 	pub publicity: PublicityOverride,
 	pub allowed_users: Vec<String>,
+	pub allowed_groups: Vec<i64>,
+	pub tags: Vec<String>,
+	pub title: String,
+	pub description: String,
+	pub note: String,
+}
+
+/// New metadata for an available object. A `None` metadata privacy, location
+/// precision, allowed groups, tags, title, description or note keeps the
+/// object's current one.
+pub struct ObjectMetadataUpdate {
+	pub id: i64,
+	/// The user making the change, recorded in the object's history.
+	pub edited_by: i64,
+	pub name: String,
+	pub made_on: Option<String>,
+	pub location: Option<Location>,
+	pub publicity: PublicityOverride,
+	pub metadata_privacy: Option<MetadataPrivacyOverride>,
+	pub location_precision: Option<LocationPrecisionOverride>,
+	pub allowed_users: Vec<String>,
+	pub allowed_groups: Option<Vec<i64>>,
+	pub tags: Option<Vec<String>>,
+	pub title: Option<String>,
+	pub description: Option<String>,
+	pub note: Option<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -95,6 +163,14 @@ pub struct CreatedObjectUploadSession {
 	pub expires_at: Timestamp,
 }
 
+/// The outcome of a deduplicated upload: the user's existing object with the
+/// same content, or a new session to upload it through.
+#[derive(Debug)]
+pub enum DeduplicatedUploadSession {
+	Existing(Box<S3Object>),
+	Created(CreatedObjectUploadSession),
+}
+
 #[derive(Clone, Debug)]
 struct ObjectUploadSession {
 	object_id: i64,
@@ -163,13 +239,16 @@ impl<'a> ObjectLifecycleService<'a> {
 		upload: ObjectUploadSessionCreate,
 	) -> Result<CreatedObjectUploadSession, AppError> {
 		validate_upload_name(&upload.name)?;
-		validate_upload_content_type(&upload.content_type)?;
+		validate_upload_content_type(&upload.content_type, self.config.heic_uploads)?;
+		validate_object_text(Some(&upload.title), Some(&upload.description), Some(&upload.note))?;
 		let parsed_made_on = parse_made_on(upload.made_on.clone())?;
 		let location_geometry = location_geometry(upload.location.as_ref())?;
 		let total_parts = self.config.upload_session_total_parts(upload.file_size_bytes)?;
 		let part_size_bytes = self.config.upload_part_size_bytes;
 		let session_ttl_seconds = self.config.upload_session_ttl_seconds;
 		let allowed_users = upload.allowed_users.clone();
+		let allowed_groups = upload.allowed_groups.clone();
+		let tags = normalize_tags(&upload.tags)?;
 		let storage_key = generate_storage_key();
 		let upload_id = self
 			.storage
@@ -188,6 +267,8 @@ impl<'a> ObjectLifecycleService<'a> {
 			)
 			.await?;
 			replace_allowed_users(&transaction, object_id, allowed_users).await?;
+			replace_allowed_groups(&transaction, object_id, allowed_groups).await?;
+			add_object_tags(&transaction, &[object_id], &tags).await?;
 			let session = insert_object_upload_session(
 				&transaction,
 				object_id,
@@ -219,6 +300,35 @@ impl<'a> ObjectLifecycleService<'a> {
 		})
 	}
 
+	/// Creates an upload session unless the user already has an available object
+	/// whose content hashes to `content_hash`, in which case that object is
+	/// returned and nothing is created. Only the user's own objects are matched,
+	/// so a client that sends a wrong hash can at worst get back one of its own
+	/// objects; hashes are recorded by the derivative worker once an upload
+	/// completes.
+	pub async fn create_deduplicated_upload_session(
+		&mut self,
+		upload: ObjectUploadSessionCreate,
+		content_hash: &str,
+	) -> Result<DeduplicatedUploadSession, AppError> {
+		let content_hash = parse_content_hash(content_hash)?;
+		let existing = self
+			.db_client
+			.query_opt(
+				SELECT_AVAILABLE_OBJECT_BY_CONTENT_HASH_FOR_USER_QUERY,
+				&[&upload.user_id, &content_hash],
+			)
+			.await
+			.context("Failed to look up object by content hash")?
+			.map(S3Object::try_from)
+			.transpose()?;
+		match existing {
+			Some(object) => Ok(DeduplicatedUploadSession::Existing(Box::new(object))),
+			None =>
+				self.create_upload_session(upload).await.map(DeduplicatedUploadSession::Created),
+		}
+	}
+
 	pub async fn presign_upload_parts(
 		&mut self,
 		object_id: i64,
@@ -312,18 +422,55 @@ impl<'a> ObjectLifecycleService<'a> {
 		}
 	}
 
-	pub async fn delete_objects(
+	/// Moves the objects `ids` to their owners' trash, from which
+	/// [`Self::restore_objects`] takes them back until the retention window ends.
+	pub async fn trash_objects(
 		&mut self,
 		ids: &[i64],
 	) -> Result<Vec<S3Object>, AppError> {
-		tracing::debug!("IDs to delete: {:?}", ids);
+		tracing::debug!("IDs to trash: {:?}", ids);
+		let rows = self
+			.db_client
+			.query(MARK_OBJECTS_TRASHED_QUERY, &[&ids])
+			.await
+			.context("Failed to move objects to the trash")?;
+		collect_s3_objects(rows)
+	}
+
+	/// Takes `objects` back out of the trash. Fails, restoring none of them,
+	/// when one's owner has since given another object its name.
+	pub async fn restore_objects(
+		&mut self,
+		objects: &[S3Object],
+	) -> Result<Vec<S3Object>, AppError> {
 		let transaction = self.db_client.transaction().await?;
-		let rows = transaction.query(MARK_OBJECTS_DELETE_PENDING_QUERY, &[&ids]).await?;
+		let mut restored = Vec::with_capacity(objects.len());
+		for object in objects {
+			if let Some(row) = transaction
+				.query_opt(RESTORE_TRASHED_OBJECT_QUERY, &[&object.id])
+				.await
+				.map_err(|error| object_name_error(error, &object.name))?
+			{
+				restored.push(S3Object::try_from(row)?);
+			}
+		}
+		transaction.commit().await?;
+		Ok(restored)
+	}
+
+	/// Queues everything in `user_id`'s trash for storage deletion.
+	pub async fn empty_trash(
+		&mut self,
+		user_id: i64,
+	) -> Result<Vec<S3Object>, AppError> {
+		let transaction = self.db_client.transaction().await?;
+		let rows = transaction
+			.query(MARK_USER_TRASH_DELETE_PENDING_QUERY, &[&user_id])
+			.await
+			.context("Failed to empty the trash")?;
 		let objects = collect_s3_objects(rows)?;
 		enqueue_storage_deletions(&transaction, &objects).await?;
-
 		transaction.commit().await?;
-
 		Ok(objects)
 	}
 
@@ -336,6 +483,7 @@ impl<'a> ObjectLifecycleService<'a> {
 	pub async fn run_storage_maintenance(&mut self) -> Result<(), AppError> {
 		let reconcile = self.reconcile_expired_upload_sessions().await;
 		let reap = self.reap_stale_pending_uploads().await;
+		let purge = self.purge_expired_trash().await;
 		let drain = self.drain_storage_deletions().await;
 
 		match &reconcile {
@@ -360,6 +508,17 @@ impl<'a> ObjectLifecycleService<'a> {
 				"Failed to mark stale pending uploads for storage cleanup"
 			),
 		}
+		match &purge {
+			Ok(expired) if !expired.is_empty() => tracing::info!(
+				count = expired.len(),
+				"Marked expired trashed objects for storage cleanup"
+			),
+			Ok(_) => {}
+			Err(error) => tracing::warn!(
+				error = ?error,
+				"Failed to mark expired trashed objects for storage cleanup"
+			),
+		}
 		if let Err(error) = &drain {
 			tracing::warn!(error = ?error, "Failed to drain object storage deletions");
 		}
@@ -371,7 +530,7 @@ impl<'a> ObjectLifecycleService<'a> {
 
 		// `.and(backlog)` last so a backlog-query failure is surfaced but never
 		// masks an earlier stage's error.
-		reconcile.map(|_| ()).and(reap.map(|_| ())).and(drain).and(backlog)
+		reconcile.map(|_| ()).and(reap.map(|_| ())).and(purge.map(|_| ())).and(drain).and(backlog)
 	}
 
 	/// Logs a warning summarising cleanups and deletions that have exhausted their
@@ -460,6 +619,20 @@ impl<'a> ObjectLifecycleService<'a> {
 		Ok(objects)
 	}
 
+	/// Queues the objects that have outlived `trash_retention_seconds` in the
+	/// trash for storage deletion.
+	pub async fn purge_expired_trash(&mut self) -> Result<Vec<S3Object>, AppError> {
+		let transaction = self.db_client.transaction().await?;
+		let rows = transaction
+			.query(MARK_EXPIRED_TRASH_DELETE_PENDING_QUERY, &[&self.config.trash_retention_seconds])
+			.await
+			.context("Failed to mark expired trashed objects for cleanup")?;
+		let objects = collect_s3_objects(rows)?;
+		enqueue_storage_deletions(&transaction, &objects).await?;
+		transaction.commit().await?;
+		Ok(objects)
+	}
+
 	pub async fn drain_storage_deletions(&mut self) -> Result<(), AppError> {
 		let processor = StorageDeletionProcessor {
 			storage: self.storage,
@@ -530,34 +703,141 @@ impl<'a> ObjectLifecycleService<'a> {
 
 	pub async fn update_object_metadata(
 		&mut self,
-		id: i64,
-		name: String,
-		made_on: Option<String>,
-		location: Option<Location>,
-		publicity: PublicityOverride,
-		allowed_users: Vec<String>,
+		update: ObjectMetadataUpdate,
 	) -> Result<S3Object, AppError> {
+		let ObjectMetadataUpdate {
+			id,
+			edited_by,
+			name,
+			made_on,
+			location,
+			publicity,
+			metadata_privacy,
+			location_precision,
+			allowed_users,
+			allowed_groups,
+			tags,
+			title,
+			description,
+			note,
+		} = update;
 		let parsed_made_on = parse_made_on(made_on)?;
+		let tags = tags.map(|tags| normalize_tags(&tags)).transpose()?;
+		let title = title.map(|title| title.trim().to_string());
+		validate_object_text(title.as_deref(), description.as_deref(), note.as_deref())?;
 		let location_geometry = location_geometry(location.as_ref())?;
 		// Log only the object id and whether a location was supplied; object name,
 		// timestamp, and coordinates are user data and are re-queryable by id.
 		tracing::debug!(id, has_location = location_geometry.is_some(), "Updating object metadata");
 
 		let transaction = self.db_client.transaction().await?;
-		let mut s3_object = S3Object::try_from(
+		transaction
+			.query_opt(LOCK_AVAILABLE_OBJECT_FOR_UPDATE_QUERY, &[&id])
+			.await
+			.context("Failed to lock object")?
+			.ok_or_else(|| AppError::NotFound("Object not found".to_string()))?;
+		let before = transaction
+			.query_one(SELECT_OBJECT_BY_ID_QUERY, &[&id])
+			.await
+			.context("Failed to load object")?;
+		let before = S3Object::try_from(before)?;
+		let current_allowed_groups = before.allowed_groups.clone();
+		let before = ObjectMetadataSnapshot::from(&before);
+		let row = transaction
+			.query_one(
+				UPDATE_OBJECT_QUERY,
+				&[
+					&id,
+					&name,
+					&parsed_made_on,
+					&location_geometry,
+					&publicity,
+					&metadata_privacy,
+					&title,
+					&description,
+					&note,
+					&location_precision,
+				],
+			)
+			.await
+			.map_err(|error| object_name_error(error, &name))?;
+		let metadata_privacy_changed: bool = row
+			.try_get("metadata_privacy_changed")
+			.context("Failed to read object metadata privacy change")?;
+		let mut s3_object = S3Object::try_from(row)?;
+		if metadata_privacy_changed {
+			// Rewrites the sanitized copy other people are served.
 			transaction
-				.query_one(
-					UPDATE_OBJECT_QUERY,
-					&[&id, &name, &parsed_made_on, &location_geometry, &publicity],
-				)
-				.await?,
-		)?;
+				.execute(INSERT_OBJECT_SANITIZED_COPY_JOB_QUERY, &[&id])
+				.await
+				.context("Failed to enqueue object sanitized copy job")?;
+		}
 
 		s3_object.allowed_users = replace_allowed_users(&transaction, id, allowed_users).await?;
+		s3_object.allowed_groups = match allowed_groups {
+			Some(allowed_groups) =>
+				replace_allowed_groups(&transaction, id, allowed_groups).await?,
+			None => current_allowed_groups,
+		};
+		s3_object.tags = match tags {
+			Some(tags) => replace_object_tags(&transaction, id, &tags).await?,
+			None => object_tag_names(&transaction, id).await?,
+		};
+		record_object_revision(
+			&transaction,
+			id,
+			edited_by,
+			&before,
+			&ObjectMetadataSnapshot::from(&s3_object),
+		)
+		.await?;
 		transaction.commit().await?;
 		Ok(s3_object)
 	}
 
+	/// Puts back the values that `revision` of the object `id` changed, as a new
+	/// revision by `edited_by`. Fields changed by later revisions only are kept.
+	pub async fn revert_object_metadata(
+		&mut self,
+		id: i64,
+		revision: i32,
+		edited_by: i64,
+	) -> Result<S3Object, AppError> {
+		let old_values = self
+			.db_client
+			.query_opt(SELECT_OBJECT_REVISION_OLD_VALUES_QUERY, &[&id, &revision])
+			.await
+			.context("Failed to load object revision")?
+			.ok_or_else(|| AppError::NotFound("Revision not found".to_string()))?
+			.try_get("old_values")
+			.context("Failed to read object revision old_values")?;
+		let current = self
+			.db_client
+			.query_opt(SELECT_OBJECT_BY_ID_QUERY, &[&id])
+			.await
+			.context("Failed to load object")?
+			.ok_or_else(|| AppError::NotFound("Object not found".to_string()))?;
+		let reverted = ObjectMetadataSnapshot::from(&S3Object::try_from(current)?)
+			.with_values(revision_values(old_values)?)?;
+		self.update_object_metadata(ObjectMetadataUpdate {
+			id,
+			edited_by,
+			name: reverted.name,
+			made_on: reverted.made_on,
+			location: reverted.location,
+			publicity: reverted.publicity,
+			metadata_privacy: Some(reverted.metadata_privacy),
+			location_precision: Some(reverted.location_precision),
+			allowed_users: reverted.allowed_users,
+			allowed_groups: Some(reverted.allowed_groups),
+			tags: Some(reverted.tags),
+			title: Some(reverted.title),
+			description: Some(reverted.description),
+			note: Some(reverted.note),
+		})
+		.await
+	}
+
 	async fn active_upload_session_for_user(
 		&mut self,
 		object_id: i64,
@@ -610,17 +890,52 @@ impl<'a> ObjectLifecycleService<'a> {
 			return Err(AppError::Validation(error_message));
 		}
 
-		self.finalize_upload_session(session, user_id).await
+		let head = self
+			.storage
+			.get_object_range(&session.storage_key, 0, SNIFF_READ_BYTES)
+			.await
+			.context("Failed to read completed upload for content type detection")?;
+		let content_type = match verified_content_type(
+			&session.content_type,
+			sniff_content_types(&head),
+			self.config.content_type_mismatch_policy,
+		) {
+			Ok(content_type) => content_type,
+			Err(error_message) => {
+				self.enqueue_completed_upload_cleanup(session).await?;
+				return Err(AppError::Validation(error_message));
+			}
+		};
+		// Relabelling can turn a declared JPEG into a HEIC photo.
+		if let Err(error) = validate_decodable_content_type(content_type, self.config.heic_uploads)
+		{
+			self.enqueue_completed_upload_cleanup(session).await?;
+			return Err(error);
+		}
+		if content_type != session.content_type {
+			tracing::info!(
+				object_id = session.object_id,
+				declared = %session.content_type,
+				detected = %content_type,
+				"Relabelled completed upload with its detected content type"
+			);
+		}
+
+		self.finalize_upload_session(session, user_id, content_type).await
 	}
 
 	async fn finalize_upload_session(
 		&mut self,
 		session: &ObjectUploadSession,
 		user_id: i64,
+		content_type: &str,
 	) -> Result<S3Object, AppError> {
 		let transaction = self.db_client.transaction().await?;
 		let finalized = transaction
-			.query_opt(FINALIZE_OBJECT_UPLOAD_QUERY, &[&session.object_id, &session.storage_key])
+			.query_opt(
+				FINALIZE_OBJECT_UPLOAD_QUERY,
+				&[&session.object_id, &session.storage_key, &content_type],
+			)
 			.await
 			.context("Failed to finalize object upload")?;
 		let Some(finalized) = finalized else {
@@ -634,8 +949,81 @@ impl<'a> ObjectLifecycleService<'a> {
 			.execute(DELETE_OBJECT_UPLOAD_SESSION_QUERY, &[&session.object_id])
 			.await
 			.context("Failed to delete finalized object upload session")?;
+		transaction
+			.execute(INSERT_OBJECT_DERIVATIVE_JOB_QUERY, &[&session.object_id])
+			.await
+			.context("Failed to enqueue object derivative job")?;
 		transaction.commit().await?;
-		S3Object::try_from(finalized)
+		let object = S3Object::try_from(finalized)?;
+		Ok(self.apply_media_metadata(object, session.file_size_bytes).await)
+	}
+
+	/// Best-effort extraction of embedded capture metadata for a just-finalized
+	/// object. The upload has already succeeded, so a failure here is logged and
+	/// the object is returned as finalized.
+	async fn apply_media_metadata(
+		&mut self,
+		object: S3Object,
+		file_size_bytes: i64,
+	) -> S3Object {
+		match self.extract_media_metadata(&object, file_size_bytes).await {
+			Ok(Some(updated)) => S3Object {
+				allowed_users: object.allowed_users,
+				allowed_groups: object.allowed_groups,
+				tags: object.tags,
+				..updated
+			},
+			Ok(None) => object,
+			Err(error) => {
+				tracing::warn!(
+					object_id = object.id,
+					error = ?error,
+					"Failed to extract media metadata from uploaded object"
+				);
+				object
+			}
+		}
+	}
+
+	async fn extract_media_metadata(
+		&mut self,
+		object: &S3Object,
+		file_size_bytes: i64,
+	) -> Result<Option<S3Object>, AppError> {
+		let source = MetadataSource::read(
+			self.storage,
+			&object.storage_key,
+			file_size_bytes,
+			self.config.metadata_read_max_bytes,
+		)
+		.await?;
+		let metadata = extract_metadata(&source);
+		if metadata.is_empty() {
+			return Ok(None);
+		}
+		let location_geometry = location_geometry(metadata.location.as_ref())?;
+		let raw = serde_json::to_string(&metadata.raw)
+			.context("Failed to serialize extracted media metadata")?;
+
+		let transaction = self.db_client.transaction().await?;
+		transaction
+			.execute(
+				UPSERT_OBJECT_MEDIA_METADATA_QUERY,
+				&[&object.id, &metadata.captured_at, &location_geometry, &raw],
+			)
+			.await
+			.context("Failed to store extracted media metadata")?;
+		let updated = transaction
+			.query_opt(
+				FILL_OBJECT_METADATA_FROM_MEDIA_QUERY,
+				&[&object.id, &metadata.captured_at, &location_geometry],
+			)
+			.await
+			.context("Failed to fill object metadata from extracted media metadata")?
+			.map(S3Object::try_from)
+			.transpose()?;
+		transaction.commit().await?;
+		Ok(updated)
 	}
 
 	async fn delete_pending_upload_metadata(
@@ -741,10 +1129,13 @@ async fn insert_pending_upload_object(
 				&location_geometry,
 				&upload.user_id,
 				&upload.publicity,
+				&upload.title.trim(),
+				&upload.description,
+				&upload.note,
 			],
 		)
 		.await
-		.map_err(|error| insert_object_error(error, &upload.name))?;
+		.map_err(|error| object_name_error(error, &upload.name))?;
 	row.try_get("id").context("Failed to read inserted object id").map_err(AppError::from)
 }
 
@@ -782,11 +1173,61 @@ fn validate_upload_name(name: &str) -> Result<(), AppError> {
 	Ok(())
 }
 
-fn validate_upload_content_type(content_type: &str) -> Result<(), AppError> {
-	if ALLOWED_MIME_TYPES.contains(&content_type) {
-		return Ok(());
+/// Normalises a hex-encoded BLAKE3 digest to the lowercase form it is stored in.
+fn parse_content_hash(content_hash: &str) -> Result<String, AppError> {
+	let content_hash = content_hash.trim().to_ascii_lowercase();
+	if content_hash.len() != 64 || !content_hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+		return Err(AppError::Validation(
+			"Content hash must be a hex-encoded BLAKE3 digest".to_string(),
+		));
 	}
-	Err(AppError::Validation(format!("Unsupported file type: {content_type}")))
+	Ok(content_hash)
+}
+
+/// Checks that an object's title, description and note are no longer than
+/// they may be. `None` is not checked.
+fn validate_object_text(
+	title: Option<&str>,
+	description: Option<&str>,
+	note: Option<&str>,
+) -> Result<(), AppError> {
+	if title.is_some_and(|title| title.trim().chars().count() > OBJECT_TITLE_MAX_LENGTH) {
+		return Err(AppError::Validation(format!(
+			"Titles can be at most {OBJECT_TITLE_MAX_LENGTH} characters long"
+		)));
+	}
+	for (field, text) in [("Descriptions", description), ("Notes", note)] {
+		if text.is_some_and(|text| text.chars().count() > OBJECT_TEXT_MAX_LENGTH) {
+			return Err(AppError::Validation(format!(
+				"{field} can be at most {OBJECT_TEXT_MAX_LENGTH} characters long"
+			)));
+		}
+	}
+	Ok(())
+}
+
+fn validate_upload_content_type(
+	content_type: &str,
+	heic_uploads: bool,
+) -> Result<(), AppError> {
+	if !ALLOWED_MIME_TYPES.contains(&content_type) {
+		return Err(AppError::Validation(format!("Unsupported file type: {content_type}")));
+	}
+	validate_decodable_content_type(content_type, heic_uploads)
+}
+
+/// Refuses HEIC photos when the server has no decoder for them, rather than
+/// keeping photos nobody can view.
+fn validate_decodable_content_type(
+	content_type: &str,
+	heic_uploads: bool,
+) -> Result<(), AppError> {
+	if !heic_uploads && matches!(content_type, "image/heic" | "image/heif") {
+		return Err(AppError::Validation(
+			"HEIC photos cannot be uploaded: this server has no decoder for them".to_string(),
+		));
+	}
+	Ok(())
 }
 
 fn validate_part_numbers(
@@ -893,6 +1334,30 @@ fn completed_upload_metadata_error(
 	None
 }
 
+/// Decides the content type a completed upload is made available under, given
+/// the types its magic bytes are valid as. A mismatch is relabelled only when
+/// the policy allows it and the bytes match some accepted type; otherwise the
+/// returned message explains the rejection.
+fn verified_content_type<'a>(
+	declared: &'a str,
+	detected: &'static [&'static str],
+	policy: ContentTypeMismatchPolicy,
+) -> Result<&'a str, String> {
+	if detected.contains(&declared) {
+		return Ok(declared);
+	}
+	match (detected.first(), policy) {
+		(Some(detected), ContentTypeMismatchPolicy::Relabel) => Ok(*detected),
+		(Some(detected), ContentTypeMismatchPolicy::Reject) =>
+			Err(format!("Uploaded file was declared as {declared} but its contents are {detected}")),
+		(None, _) => Err(format!(
+			"Uploaded file was declared as {declared} but its contents are not a supported file type"
+		)),
+	}
+}
+
+/// Queues the objects' originals and their recorded derivatives for storage
+/// deletion.
 async fn enqueue_storage_deletions(
 	transaction: &Transaction<'_>,
 	objects: &[S3Object],
@@ -907,6 +1372,10 @@ async fn enqueue_storage_deletions(
 		.execute(INSERT_OBJECT_STORAGE_DELETIONS_QUERY, &[&storage_keys, &object_ids])
 		.await
 		.context("Failed to enqueue object storage deletions")?;
+	transaction
+		.execute(INSERT_OBJECT_DERIVATIVE_STORAGE_DELETIONS_QUERY, &[&object_ids])
+		.await
+		.context("Failed to enqueue object derivative storage deletions")?;
 	Ok(())
 }
 
@@ -938,6 +1407,31 @@ async fn replace_allowed_users(
 		.map_err(AppError::from)
 }
 
+/// Shares the object with `allowed_groups` instead of the groups it was shared
+/// with, all of which must be its owner's.
+async fn replace_allowed_groups(
+	transaction: &Transaction<'_>,
+	object_id: i64,
+	allowed_groups: Vec<i64>,
+) -> Result<Vec<i64>, AppError> {
+	transaction
+		.execute(DELETE_OBJECT_ALLOWED_GROUPS_QUERY, &[&object_id])
+		.await
+		.context("Failed to delete object allowed groups from database")?;
+
+	let allowed_groups = distinct_ids(&allowed_groups);
+	let mut granted = transaction
+		.query(REPLACE_OBJECT_ALLOWED_GROUPS_QUERY, &[&object_id, &allowed_groups])
+		.await
+		.context("Failed to replace object allowed groups in database")?
+		.into_iter()
+		.map(|row| row.try_get("group_id").context("Failed to get group id from database row"))
+		.collect::<Result<Vec<i64>, _>>()?;
+	require_granted_groups(&allowed_groups, &granted)?;
+	granted.sort_unstable();
+	Ok(granted)
+}
+
 /// Generates an unguessable storage key used directly in presigned S3 URLs.
 ///
 /// MUST use a cryptographically secure RNG. `rand::rng()` (the default
@@ -949,15 +1443,17 @@ fn generate_storage_key() -> String {
 	format!("objects/{key}")
 }
 
-fn insert_object_error(
+/// `error`, or a validation error when it is because the owner already has an
+/// object named `name`.
+fn object_name_error(
 	error: tokio_postgres::Error,
 	name: &str,
 ) -> AppError {
 	if error.as_db_error().is_some_and(|db_error| {
 		db_error.code() == &SqlState::UNIQUE_VIOLATION &&
-			matches!(db_error.constraint(), Some("objects_active_name_key" | "objects_name_key"))
+			db_error.constraint() == Some("objects_active_user_name_key")
 	}) {
-		return AppError::Validation(format!("Object named '{name}' already exists"));
+		return AppError::Validation(format!("You already have an object named '{name}'"));
 	}
 
 	AppError::from(error)
@@ -967,14 +1463,25 @@ fn insert_object_error(
 mod tests {
 	use {
 		super::{
+			ContentTypeMismatchPolicy,
 			ObjectLifecycleConfig,
 			ObjectUploadSession,
 			completed_upload_metadata_error,
+			parse_content_hash,
 			validate_completed_parts,
+			validate_object_text,
+			validate_upload_content_type,
+			verified_content_type,
 		},
-		crate::storage::{
-			CompletedUploadPart,
-			StoredObjectMetadata,
+		crate::{
+			constants::{
+				OBJECT_TEXT_MAX_LENGTH,
+				OBJECT_TITLE_MAX_LENGTH,
+			},
+			storage::{
+				CompletedUploadPart,
+				StoredObjectMetadata,
+			},
 		},
 		jiff::Timestamp,
 	};
@@ -1048,6 +1555,64 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn content_type_mismatches_follow_the_configured_policy() {
+		let png: &'static [&'static str] = &["image/png", "image/apng"];
+		for policy in [ContentTypeMismatchPolicy::Reject, ContentTypeMismatchPolicy::Relabel] {
+			assert_eq!(verified_content_type("image/png", png, policy), Ok("image/png"));
+			assert_eq!(verified_content_type("image/apng", png, policy), Ok("image/apng"));
+			assert!(verified_content_type("image/png", &[], policy).is_err_and(|message| {
+				message.contains("image/png") && message.contains("not a supported file type")
+			}));
+		}
+
+		assert_eq!(
+			verified_content_type("image/jpeg", png, ContentTypeMismatchPolicy::Reject),
+			Err("Uploaded file was declared as image/jpeg but its contents are image/png"
+				.to_string())
+		);
+		assert_eq!(
+			verified_content_type("image/jpeg", png, ContentTypeMismatchPolicy::Relabel),
+			Ok("image/png")
+		);
+	}
+
+	#[test]
+	fn heic_uploads_need_a_decoder() {
+		assert!(validate_upload_content_type("image/heic", true).is_ok());
+		assert!(validate_upload_content_type("image/heic", false).is_err());
+		assert!(validate_upload_content_type("image/jpeg", false).is_ok());
+		assert!(validate_upload_content_type("text/html", true).is_err());
+	}
+
+	#[test]
+	fn content_hashes_are_normalised_hex_blake3_digests() -> anyhow::Result<()> {
+		let digest = blake3::hash(b"camera roll").to_string();
+
+		assert_eq!(parse_content_hash(&digest.to_ascii_uppercase())?, digest);
+		assert_eq!(parse_content_hash(&format!(" {digest}\n"))?, digest);
+		assert!(parse_content_hash(digest.get(.. 63).unwrap_or_default()).is_err());
+		assert!(
+			parse_content_hash(&format!("{}g", digest.get(.. 63).unwrap_or_default())).is_err()
+		);
+		assert!(parse_content_hash("").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn object_text_is_limited_in_length() {
+		let title = format!("  {}  ", "t".repeat(OBJECT_TITLE_MAX_LENGTH));
+		let text = "x".repeat(OBJECT_TEXT_MAX_LENGTH);
+		assert!(validate_object_text(Some(&title), Some(&text), Some(&text)).is_ok());
+		assert!(validate_object_text(None, None, None).is_ok());
+
+		let long_title = "t".repeat(OBJECT_TITLE_MAX_LENGTH + 1);
+		let long_text = "x".repeat(OBJECT_TEXT_MAX_LENGTH + 1);
+		assert!(validate_object_text(Some(&long_title), None, None).is_err());
+		assert!(validate_object_text(None, Some(&long_text), None).is_err());
+		assert!(validate_object_text(None, None, Some(&long_text)).is_err());
+	}
+
 	fn completed_part(
 		part_number: i32,
 		e_tag: &str,
@@ -0,0 +1,179 @@
+use {
+	crate::{
+		constants::DUPLICATE_CANDIDATE_MAX_DISTANCE,
+		db::queries::{
+			INSERT_OBJECT_DUPLICATE_CANDIDATES_QUERY,
+			SELECT_UNHASHED_IMAGE_OBJECTS_QUERY,
+			UPSERT_OBJECT_PERCEPTUAL_HASH_QUERY,
+		},
+		errors::AppError,
+		media::perceptual::{
+			PERCEPTUAL_HASH_CONTENT_TYPES,
+			difference_hash,
+		},
+		outbox::ensure_positive,
+		storage::StorageClient,
+		worker::MaintenanceTask,
+	},
+	anyhow::Context,
+	deadpool::managed::Pool,
+	deadpool_postgres::Manager,
+	serde::Deserialize,
+	std::time::Duration,
+};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PerceptualHashConfig {
+	#[serde(default = "PerceptualHashConfig::default_worker_interval_seconds")]
+	pub worker_interval_seconds: i64,
+	#[serde(default = "PerceptualHashConfig::default_batch_size")]
+	pub batch_size: i64,
+	/// Images larger than this are not downloaded for hashing and are never
+	/// offered as possible duplicates.
+	#[serde(default = "PerceptualHashConfig::default_max_source_bytes")]
+	pub max_source_bytes: i64,
+}
+
+impl PerceptualHashConfig {
+	pub const fn default_worker_interval_seconds() -> i64 {
+		60
+	}
+
+	pub const fn default_batch_size() -> i64 {
+		20
+	}
+
+	pub const fn default_max_source_bytes() -> i64 {
+		100 * 1024 * 1024
+	}
+
+	pub fn validate(&self) -> anyhow::Result<()> {
+		ensure_positive!(self, worker_interval_seconds);
+		ensure_positive!(self, batch_size);
+		ensure_positive!(self, max_source_bytes);
+		Ok(())
+	}
+
+	fn worker_interval(&self) -> Duration {
+		Duration::from_secs(self.worker_interval_seconds as u64)
+	}
+}
+
+impl Default for PerceptualHashConfig {
+	fn default() -> Self {
+		Self {
+			worker_interval_seconds: Self::default_worker_interval_seconds(),
+			batch_size: Self::default_batch_size(),
+			max_source_bytes: Self::default_max_source_bytes(),
+		}
+	}
+}
+
+/// Hashes available images that have no perceptual hash yet, including those
+/// uploaded before hashing existed, so they can be compared for near-duplicates.
+#[derive(Clone)]
+pub struct PerceptualHashWorker {
+	pool: Pool<Manager>,
+	storage: StorageClient,
+	config: PerceptualHashConfig,
+}
+
+impl PerceptualHashWorker {
+	pub fn new(
+		pool: Pool<Manager>,
+		storage: StorageClient,
+		config: PerceptualHashConfig,
+	) -> Self {
+		Self {
+			pool,
+			storage,
+			config,
+		}
+	}
+
+	/// Reads and hashes one image. `None` records it as unhashable: too large,
+	/// or bytes the decoder rejects, neither of which a retry would change.
+	async fn hash_object(
+		&self,
+		object_id: i64,
+		storage_key: &str,
+	) -> anyhow::Result<Option<u64>> {
+		let metadata = self.storage.head_object(storage_key).await?;
+		if metadata.content_length > self.config.max_source_bytes {
+			return Ok(None);
+		}
+		let length = u64::try_from(metadata.content_length).context("Negative object length")?;
+		let source = self.storage.get_object_range(storage_key, 0, length).await?;
+		let hash = tokio::task::spawn_blocking(move || difference_hash(&source))
+			.await
+			.context("Perceptual hashing task failed")?;
+		match hash {
+			Ok(hash) => Ok(Some(hash)),
+			Err(error) => {
+				tracing::warn!(
+					object_id,
+					error = ?error,
+					"Image could not be decoded for perceptual hashing"
+				);
+				Ok(None)
+			}
+		}
+	}
+}
+
+impl MaintenanceTask for PerceptualHashWorker {
+	fn name(&self) -> &'static str {
+		"perceptual_hashes"
+	}
+
+	fn interval(&self) -> Duration {
+		self.config.worker_interval()
+	}
+
+	/// Works through every unhashed image in batches. An object that cannot be
+	/// read is skipped for the rest of the pass and retried on the next one.
+	/// Each hash is stored together with the possible duplicates it forms, so
+	/// these never need finding by comparing every pair of images.
+	async fn run_once(&self) -> Result<(), AppError> {
+		let mut client = self.pool.get().await?;
+		let select = client.prepare_cached(SELECT_UNHASHED_IMAGE_OBJECTS_QUERY).await?;
+		let upsert = client.prepare_cached(UPSERT_OBJECT_PERCEPTUAL_HASH_QUERY).await?;
+		let insert_candidates =
+			client.prepare_cached(INSERT_OBJECT_DUPLICATE_CANDIDATES_QUERY).await?;
+		let content_types = PERCEPTUAL_HASH_CONTENT_TYPES.as_slice();
+		let mut after_id = 0i64;
+		loop {
+			let rows = client
+				.query(&select, &[&content_types, &self.config.batch_size, &after_id])
+				.await?;
+			for row in &rows {
+				let object_id: i64 = row.try_get("id")?;
+				let storage_key: String = row.try_get("storage_key")?;
+				after_id = object_id;
+				let hash = match self.hash_object(object_id, &storage_key).await {
+					Ok(hash) => hash,
+					Err(error) => {
+						tracing::warn!(
+							object_id,
+							error = ?error,
+							"Failed to read image for perceptual hashing"
+						);
+						continue;
+					}
+				};
+				// Stored as the signed column's bit pattern; only XOR and bit
+				// counts are taken of it.
+				let hash = hash.map(|hash| hash as i64);
+				let transaction = client.transaction().await?;
+				transaction.execute(&upsert, &[&object_id, &hash]).await?;
+				transaction
+					.execute(&insert_candidates, &[&object_id, &DUPLICATE_CANDIDATE_MAX_DISTANCE])
+					.await?;
+				transaction.commit().await?;
+			}
+			if (rows.len() as i64) < self.config.batch_size {
+				return Ok(());
+			}
+		}
+	}
+}
@@ -0,0 +1,61 @@
+use {
+	crate::{
+		AuthConfig,
+		constants::SESSION_CLEANUP_INTERVAL_SECONDS,
+		db::queries::DELETE_EXPIRED_SESSIONS_QUERY,
+		errors::AppError,
+		worker::MaintenanceTask,
+	},
+	anyhow::Context,
+	deadpool::managed::Pool,
+	deadpool_postgres::Manager,
+	std::time::Duration,
+};
+
+/// Deletes sessions past their idle or absolute timeout. They no longer sign
+/// anyone in, so there is no reason to keep their user agents and addresses.
+#[derive(Clone)]
+pub struct SessionWorker {
+	pool: Pool<Manager>,
+	config: AuthConfig,
+}
+
+impl SessionWorker {
+	pub fn new(
+		pool: Pool<Manager>,
+		config: AuthConfig,
+	) -> Self {
+		Self {
+			pool,
+			config,
+		}
+	}
+}
+
+impl MaintenanceTask for SessionWorker {
+	fn name(&self) -> &'static str {
+		"expired_sessions"
+	}
+
+	fn interval(&self) -> Duration {
+		Duration::from_secs(SESSION_CLEANUP_INTERVAL_SECONDS)
+	}
+
+	async fn run_once(&self) -> Result<(), AppError> {
+		let client = self.pool.get().await?;
+		let deleted = client
+			.execute(
+				DELETE_EXPIRED_SESSIONS_QUERY,
+				&[
+					&self.config.session_idle_timeout_seconds,
+					&self.config.session_absolute_timeout_seconds,
+				],
+			)
+			.await
+			.context("Failed to delete expired sessions")?;
+		if deleted > 0 {
+			tracing::info!(count = deleted, "Deleted expired sessions");
+		}
+		Ok(())
+	}
+}
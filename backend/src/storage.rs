@@ -33,6 +33,7 @@ use {
 	serde::Deserialize,
 	std::{
 		fmt,
+		ops::Range,
 		time::Duration,
 	},
 	tokio::time::{
@@ -199,9 +200,9 @@ impl StorageClient {
 		})
 	}
 
-	/// Uploads an object directly. Now exercised only by the integration tests
-	/// (the production upload path is presigned multipart, driven by the client);
-	/// kept as a public storage primitive and test seam.
+	/// Uploads an object directly. User uploads go through presigned multipart,
+	/// driven by the client; this writes server-rendered derivatives and seeds
+	/// objects in the integration tests.
 	pub async fn upload_object(
 		&self,
 		storage_key: &str,
@@ -230,14 +231,46 @@ impl StorageClient {
 		Ok(self.head_object(storage_key).await?.content_type)
 	}
 
+	/// Reads `length` bytes of `storage_key` starting at `offset`. A range that
+	/// runs past the end of the object returns the bytes that exist.
+	pub async fn get_object_range(
+		&self,
+		storage_key: &str,
+		offset: u64,
+		length: u64,
+	) -> anyhow::Result<Vec<u8>> {
+		if length == 0 {
+			return Ok(Vec::new());
+		}
+		let last_byte = offset.saturating_add(length - 1);
+		let output = self
+			.client
+			.get_object()
+			.bucket(&self.bucket_name)
+			.key(storage_key)
+			.range(format!("bytes={offset}-{last_byte}"))
+			.send()
+			.await
+			.context("Failed to read S3 object range")?;
+		let bytes = output
+			.body
+			.collect()
+			.await
+			.context("Failed to read S3 object range body")?
+			.into_bytes();
+		Ok(bytes.to_vec())
+	}
+
 	/// Presigns a GET for `storage_key`. When `content_disposition` is set it is
 	/// signed into the URL as `response-content-disposition`, so the storage
 	/// response carries that header (used to force `attachment` for script-capable
-	/// types like SVG; see the object resolver).
+	/// types like SVG; see the object resolver). `content_type` likewise overrides
+	/// the stored `Content-Type`, for uploads relabelled after content sniffing.
 	pub async fn presigned_get_url(
 		&self,
 		storage_key: &str,
 		content_disposition: Option<&str>,
+		content_type: Option<&str>,
 	) -> anyhow::Result<String> {
 		let request = self
 			.presigning_client
@@ -245,6 +278,7 @@ impl StorageClient {
 			.bucket(&self.bucket_name)
 			.key(storage_key)
 			.set_response_content_disposition(content_disposition.map(str::to_string))
+			.set_response_content_type(content_type.map(str::to_string))
 			.presigned(self.presigning_config.clone())
 			.await
 			.context("Failed to generate S3 presigned GET URL")?;
@@ -306,6 +340,62 @@ impl StorageClient {
 		})
 	}
 
+	/// Uploads one part of a multipart upload from the server, returning its
+	/// ETag. Clients upload theirs through [`Self::presigned_upload_part_url`].
+	pub async fn upload_part(
+		&self,
+		storage_key: &str,
+		upload_id: &str,
+		part_number: i32,
+		bytes: Vec<u8>,
+	) -> anyhow::Result<String> {
+		let output = self
+			.client
+			.upload_part()
+			.bucket(&self.bucket_name)
+			.key(storage_key)
+			.upload_id(upload_id)
+			.part_number(part_number)
+			.body(bytes.into())
+			.send()
+			.await
+			.context("Failed to upload S3 multipart part")?;
+		output
+			.e_tag()
+			.map(str::to_string)
+			.context("S3 upload-part response did not include an ETag")
+	}
+
+	/// Fills one part of a multipart upload with `range` of `source_key`,
+	/// copied within the storage service, returning the part's ETag.
+	pub async fn upload_part_copy(
+		&self,
+		storage_key: &str,
+		upload_id: &str,
+		part_number: i32,
+		source_key: &str,
+		range: Range<u64>,
+	) -> anyhow::Result<String> {
+		anyhow::ensure!(range.start < range.end, "Cannot copy an empty part");
+		let output = self
+			.client
+			.upload_part_copy()
+			.bucket(&self.bucket_name)
+			.key(storage_key)
+			.upload_id(upload_id)
+			.part_number(part_number)
+			.copy_source(format!("{}/{source_key}", self.bucket_name))
+			.copy_source_range(format!("bytes={}-{}", range.start, range.end - 1))
+			.send()
+			.await
+			.context("Failed to copy S3 multipart part")?;
+		output
+			.copy_part_result()
+			.and_then(|result| result.e_tag())
+			.map(str::to_string)
+			.context("S3 upload-part-copy response did not include an ETag")
+	}
+
 	pub async fn complete_multipart_upload(
 		&self,
 		storage_key: &str,
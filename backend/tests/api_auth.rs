@@ -24,16 +24,25 @@ use {
 			build_app,
 			build_shared_state,
 		},
+		constants::SHARE_LINK_MAX_PASSWORD_ATTEMPTS,
 		db::queries::{
 			CLAIM_OBJECT_STORAGE_DELETIONS_QUERY,
 			MARK_OBJECT_STORAGE_DELETIONS_FAILED_QUERY,
 		},
+		derivative_worker::{
+			ObjectDerivativeConfig,
+			ObjectDerivativeWorker,
+		},
 		email_worker::EmailOutboxConfig,
 		migrations,
 		object_lifecycle::{
 			ObjectLifecycleConfig,
 			ObjectLifecycleWorker,
 		},
+		perceptual_hash_worker::{
+			PerceptualHashConfig,
+			PerceptualHashWorker,
+		},
 		storage::{
 			CompletedUploadPart,
 			MultipartUploadCompleteOutcome,
@@ -64,14 +73,26 @@ use {
 		Manager,
 		Runtime,
 	},
+	image::{
+		DynamicImage,
+		ImageFormat,
+		Rgb,
+		RgbImage,
+		imageops::FilterType,
+	},
 	serde_json::{
 		Value,
 		json,
 	},
 	std::{
+		io::Cursor,
 		ops::DerefMut,
 		path::Path,
 		sync::Arc,
+		time::{
+			SystemTime,
+			UNIX_EPOCH,
+		},
 	},
 	tokio::sync::RwLock,
 	tokio_postgres::NoTls,
@@ -284,6 +305,45 @@ impl TestApp {
 		.await
 	}
 
+	async fn create_deduplicated_object_upload_session(
+		&self,
+		cookie: &str,
+		upload: &DirectUploadRequest<'_>,
+		content_hash: &str,
+	) -> anyhow::Result<TestResponse> {
+		self.graphql(
+			"mutation CreateDeduplicatedObjectUploadSession(
+				$input: CreateObjectUploadSessionInput!
+				$contentHash: String!
+			) {
+				createDeduplicatedObjectUploadSession(input: $input, contentHash: $contentHash) {
+					__typename
+					... on S3Object {
+						id
+						name
+					}
+					... on CreatedObjectUploadSession {
+						objectId
+					}
+				}
+			}",
+			json!({
+				"input": {
+					"name": upload.object_name,
+					"contentType": upload.content_type,
+					"fileSizeBytes": upload.body.len() as i64,
+					"madeOn": upload.made_on,
+					"location": upload.location,
+					"publicity": "DEFAULT",
+					"allowedUsers": [],
+				},
+				"contentHash": content_hash,
+			}),
+			Some(cookie),
+		)
+		.await
+	}
+
 	async fn direct_upload_object(
 		&self,
 		cookie: &str,
@@ -426,6 +486,7 @@ impl TestApp {
 				completeObjectUpload(objectId: $objectId, parts: $parts) {
 					id
 					name
+					madeOn
 					location { latitude longitude }
 					contentType
 				}
@@ -549,6 +610,41 @@ impl TestApp {
 		Ok(())
 	}
 
+	async fn expire_share_link_lock(
+		&self,
+		link_id: &str,
+	) -> anyhow::Result<()> {
+		let link_id = link_id.parse::<i64>()?;
+		let client = self.state.pool.get().await?;
+		client
+			.execute(
+				"UPDATE share_links
+				SET password_locked_until = now() - interval '1 second'
+				WHERE id = $1",
+				&[&link_id],
+			)
+			.await?;
+		Ok(())
+	}
+
+	async fn expire_trashed_object(
+		&self,
+		object_id: &str,
+		retention_seconds: i64,
+	) -> anyhow::Result<()> {
+		let object_id = object_id.parse::<i64>()?;
+		let client = self.state.pool.get().await?;
+		client
+			.execute(
+				"UPDATE objects
+				SET trashed_at = now() - ($2::BIGINT + 1) * interval '1 second'
+				WHERE id = $1 AND storage_state = 'trashed'",
+				&[&object_id, &retention_seconds],
+			)
+			.await?;
+		Ok(())
+	}
+
 	async fn upload_session_count(
 		&self,
 		object_name: &str,
@@ -591,6 +687,45 @@ impl TestApp {
 			.await
 			.map_err(Into::into)
 	}
+
+	async fn run_object_derivative_worker(&self) -> anyhow::Result<()> {
+		ObjectDerivativeWorker::new(
+			self.state.pool.clone(),
+			self.state.storage.clone(),
+			ObjectDerivativeConfig::default(),
+		)
+		.run_once()
+		.await
+		.map_err(Into::into)
+	}
+
+	async fn run_perceptual_hash_worker(&self) -> anyhow::Result<()> {
+		PerceptualHashWorker::new(
+			self.state.pool.clone(),
+			self.state.storage.clone(),
+			PerceptualHashConfig::default(),
+		)
+		.run_once()
+		.await
+		.map_err(Into::into)
+	}
+
+	async fn object_derivative_storage_keys(
+		&self,
+		object_id: i64,
+	) -> anyhow::Result<Vec<String>> {
+		let client = self.state.pool.get().await?;
+		let storage_keys = client
+			.query(
+				"SELECT storage_key FROM object_derivatives WHERE object_id = $1 ORDER BY kind",
+				&[&object_id],
+			)
+			.await?
+			.into_iter()
+			.map(|row| row.get(0))
+			.collect();
+		Ok(storage_keys)
+	}
 }
 
 struct TestUser {
@@ -598,9 +733,11 @@ struct TestUser {
 	cookie: String,
 }
 
+const TEST_USER_PASSWORD: &str = "memory-map-test-password";
+
 async fn register_and_login(app: &TestApp) -> anyhow::Result<TestUser> {
 	let email = format!("api-auth-{}@example.test", unique_suffix()?);
-	let password = "memory-map-test-password";
+	let password = TEST_USER_PASSWORD;
 
 	let register = app
 		.graphql(
@@ -616,7 +753,20 @@ async fn register_and_login(app: &TestApp) -> anyhow::Result<TestUser> {
 		.await?;
 	assert_eq!(register.status, StatusCode::OK);
 	assert_graphql_success(&register.json()?)?;
+	let cookie = log_in(app, &email, password).await?;
+
+	Ok(TestUser {
+		email,
+		cookie,
+	})
+}
 
+/// Signs `email` in, returning the cookie of the new session.
+async fn log_in(
+	app: &TestApp,
+	email: &str,
+	password: &str,
+) -> anyhow::Result<String> {
 	let login = app
 		.graphql(
 			"mutation Login($email: String!, $password: String!) {
@@ -631,12 +781,17 @@ async fn register_and_login(app: &TestApp) -> anyhow::Result<TestUser> {
 		.await?;
 	assert_eq!(login.status, StatusCode::OK);
 	assert_graphql_success(&login.json()?)?;
-	let cookie = auth_cookie(&login.headers)?;
+	auth_cookie(&login.headers)
+}
 
-	Ok(TestUser {
-		email,
-		cookie,
-	})
+/// The email of whoever `cookie` signs in, if anyone.
+async fn signed_in_email(
+	app: &TestApp,
+	cookie: &str,
+) -> anyhow::Result<Option<String>> {
+	let me = app.graphql("query Me { me { email } }", json!({}), Some(cookie)).await?.json()?;
+	assert_graphql_success(&me)?;
+	Ok(json_path(&me, &["data", "me"])?.get("email").and_then(Value::as_str).map(str::to_string))
 }
 
 #[tokio::test]
@@ -825,6 +980,13 @@ async fn authenticated_upload_preserves_content_type_and_delete_cleans_up() -> a
 	assert_eq!(delete.status, StatusCode::OK);
 	assert_graphql_success(&delete.json()?)?;
 	assert_eq!(app.object_count(&object_name).await?, 1);
+	assert_eq!(app.storage_deletion_outbox_count_for_key(&storage_key).await?, 0);
+
+	let empty_trash = app
+		.graphql("mutation EmptyTrash { emptyTrash { id } }", json!({}), Some(&user.cookie))
+		.await?;
+	assert_eq!(empty_trash.status, StatusCode::OK);
+	assert_graphql_success(&empty_trash.json()?)?;
 	assert_eq!(app.storage_deletion_outbox_count_for_key(&storage_key).await?, 1);
 
 	let visible_objects = app
@@ -865,7 +1027,10 @@ async fn direct_upload_accepts_multiple_parts() -> anyhow::Result<()> {
 	let user = register_and_login(&app).await?;
 	let object_name = format!("multipart-upload-{}.svg", unique_suffix()?);
 	let part_size = app.state.config.object_lifecycle.upload_part_size_bytes as usize;
-	let body = vec![b'x'; part_size + 17];
+	// Padded past one part with a comment, so the contents still sniff as SVG.
+	let mut body = b"<svg xmlns=\"http://www.w3.org/2000/svg\"><!--".to_vec();
+	body.resize(part_size + 17 - b"--></svg>".len(), b'x');
+	body.extend_from_slice(b"--></svg>");
 	let upload = DirectUploadRequest::svg(&object_name, 12.5, -45.25, &body);
 
 	let uploaded_object = app.direct_upload_object(&user.cookie, &upload).await?;
@@ -931,28 +1096,114 @@ async fn upload_without_coordinates_stores_no_location() -> anyhow::Result<()> {
 
 #[tokio::test]
 #[ignore = "requires the local PostgreSQL and RustFS service graph"]
-async fn partial_upload_coordinates_do_not_leave_side_effects() -> anyhow::Result<()> {
+async fn upload_completion_fills_blank_metadata_from_embedded_xmp() -> anyhow::Result<()> {
 	let Some(app) = TestApp::new().await? else {
 		return Ok(());
 	};
 	let user = register_and_login(&app).await?;
+	let body = br#"<svg xmlns="http://www.w3.org/2000/svg"><metadata><x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:Description exif:DateTimeOriginal="2019-07-14T08:30:00Z" exif:GPSLatitude="48,51.5N" exif:GPSLongitude="2,21W"/></x:xmpmeta></metadata></svg>"#;
 
-	for (label, location, expected_error) in [
-		("latitude-only", json!({ "latitude": 12.5 }), "longitude"),
-		("longitude-only", json!({ "longitude": -45.25 }), "latitude"),
-	] {
-		let object_name = format!("partial-{label}-upload-{}.svg", unique_suffix()?);
-		let upload = DirectUploadRequest::svg_without_location(
-			&object_name,
-			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	let blank_name = format!("xmp-blank-{}.svg", unique_suffix()?);
+	let blank = DirectUploadRequest {
+		made_on: None,
+		..DirectUploadRequest::svg_without_location(&blank_name, body)
+	};
+	let filled = app.direct_upload_object(&user.cookie, &blank).await?;
+	assert_eq!(json_path(&filled, &["madeOn"])?, "2019-07-14T08:30:00Z");
+	let latitude = json_path(&filled, &["location", "latitude"])?.as_f64();
+	let longitude = json_path(&filled, &["location", "longitude"])?.as_f64();
+	assert!(latitude.is_some_and(|latitude| (latitude - 48.858_333).abs() < 1e-6));
+	assert!(longitude.is_some_and(|longitude| (longitude + 2.35).abs() < 1e-6));
+
+	// Values the uploader supplied are never overwritten by embedded metadata.
+	let supplied_name = format!("xmp-supplied-{}.svg", unique_suffix()?);
+	let supplied = DirectUploadRequest::svg(&supplied_name, 12.5, -45.25, body);
+	let kept = app.direct_upload_object(&user.cookie, &supplied).await?;
+	assert_eq!(json_path(&kept, &["madeOn"])?, "2026-05-31T12:00:00Z");
+	assert_eq!(json_path(&kept, &["location", "latitude"])?.as_f64(), Some(12.5));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn image_uploads_get_thumbnails_deleted_with_the_original() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("thumbnail-{}.png", unique_suffix()?);
+	let mut body = Vec::new();
+	DynamicImage::ImageRgb8(RgbImage::new(640, 320))
+		.write_to(&mut Cursor::new(&mut body), ImageFormat::Png)?;
+	let upload =
+		DirectUploadRequest::svg(&object_name, 12.5, -45.25, &body).with_content_type("image/png");
+
+	let uploaded_object = app.direct_upload_object(&user.cookie, &upload).await?;
+	let object_id = json_path(&uploaded_object, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
+
+	let derivative_keys = app.object_derivative_storage_keys(object_id.parse()?).await?;
+	assert_eq!(derivative_keys.len(), 6);
+	for storage_key in &derivative_keys {
+		let metadata = app.state.storage.head_object(storage_key).await?;
+		let expected_type =
+			if storage_key.ends_with(".avif") { "image/avif" } else { "image/webp" };
+		assert_eq!(metadata.content_type, expected_type);
+	}
+
+	let objects = app
+		.graphql(
+			"query Objects {
+				s3Objects {
+					id
+					thumbnailUrl(size: SMALL)
+					avifUrl: thumbnailUrl(size: LARGE, format: AVIF)
+				}
+			}",
+			json!({}),
+			Some(&user.cookie),
 		)
-		.with_location(Some(location));
+		.await?;
+	assert_eq!(objects.status, StatusCode::OK);
+	let objects = objects.json()?;
+	assert_graphql_success(&objects)?;
+	let object = json_path(&objects, &["data", "s3Objects"])?
+		.as_array()
+		.context("s3Objects response is not an array")?
+		.iter()
+		.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+		.context("uploaded object is missing from s3Objects")?;
+	assert!(json_path(object, &["thumbnailUrl"])?.as_str().is_some());
+	assert!(json_path(object, &["avifUrl"])?.as_str().is_some());
 
-		let response = app.create_object_upload_session(Some(&user.cookie), &upload).await?;
+	let delete = app
+		.graphql(
+			"mutation Delete($ids: [ID!]!) {
+				deleteS3Objects(ids: $ids) { id }
+			}",
+			json!({
+				"ids": [object_id],
+			}),
+			Some(&user.cookie),
+		)
+		.await?;
+	assert_graphql_success(&delete.json()?)?;
+	let empty_trash = app
+		.graphql("mutation EmptyTrash { emptyTrash { id } }", json!({}), Some(&user.cookie))
+		.await?;
+	assert_graphql_success(&empty_trash.json()?)?;
+	for storage_key in &derivative_keys {
+		assert_eq!(app.storage_deletion_outbox_count_for_key(storage_key).await?, 1);
+	}
 
-		assert_eq!(response.status, StatusCode::OK);
-		assert_graphql_error_contains(&response.json()?, expected_error)?;
-		assert_eq!(app.object_count(&object_name).await?, 0);
+	app.run_object_lifecycle_maintenance(ObjectLifecycleConfig::default()).await?;
+	for storage_key in &derivative_keys {
+		assert!(app.state.storage.head_object(storage_key).await.is_err());
+		assert_eq!(app.storage_deletion_outbox_count_for_key(storage_key).await?, 0);
 	}
 
 	Ok(())
@@ -960,137 +1211,2603 @@ async fn partial_upload_coordinates_do_not_leave_side_effects() -> anyhow::Resul
 
 #[tokio::test]
 #[ignore = "requires the local PostgreSQL and RustFS service graph"]
-async fn invalid_upload_timestamp_does_not_leave_side_effects() -> anyhow::Result<()> {
+async fn video_uploads_are_probed_and_get_a_poster() -> anyhow::Result<()> {
 	let Some(app) = TestApp::new().await? else {
 		return Ok(());
 	};
 	let user = register_and_login(&app).await?;
-	let object_name = format!("invalid-timestamp-upload-{}.svg", unique_suffix()?);
+	let object_name = format!("video-{}.mp4", unique_suffix()?);
 	let upload = DirectUploadRequest::svg(
 		&object_name,
 		12.5,
 		-45.25,
-		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		include_bytes!("fixtures/av1-cover-art.mp4"),
 	)
-	.with_made_on("not-a-timestamp");
+	.with_content_type("video/mp4");
 
-	let response = app.create_object_upload_session(Some(&user.cookie), &upload).await?;
+	let uploaded_object = app.direct_upload_object(&user.cookie, &upload).await?;
+	let object_id = json_path(&uploaded_object, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
 
-	assert_eq!(response.status, StatusCode::OK);
-	assert_graphql_error_contains(&response.json()?, "Invalid timestamp format")?;
-	assert_eq!(app.object_count(&object_name).await?, 0);
+	let objects = app
+		.graphql(
+			"query Objects {
+				s3Objects {
+					id
+					durationSeconds
+					width
+					height
+					videoCodec
+					posterUrl
+					thumbnailUrl(size: SMALL)
+				}
+			}",
+			json!({}),
+			Some(&user.cookie),
+		)
+		.await?;
+	assert_eq!(objects.status, StatusCode::OK);
+	let objects = objects.json()?;
+	assert_graphql_success(&objects)?;
+	let object = json_path(&objects, &["data", "s3Objects"])?
+		.as_array()
+		.context("s3Objects response is not an array")?
+		.iter()
+		.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+		.context("uploaded object is missing from s3Objects")?;
+	assert_eq!(json_path(object, &["durationSeconds"])?.as_f64(), Some(1.0));
+	assert_eq!(json_path(object, &["width"])?.as_i64(), Some(64));
+	assert_eq!(json_path(object, &["height"])?.as_i64(), Some(48));
+	assert_eq!(json_path(object, &["videoCodec"])?.as_str(), Some("av1"));
+	assert!(json_path(object, &["posterUrl"])?.as_str().is_some());
+	assert!(json_path(object, &["thumbnailUrl"])?.as_str().is_some());
+
+	let derivative_keys = app.object_derivative_storage_keys(object_id.parse()?).await?;
+	let poster_key = derivative_keys
+		.iter()
+		.find(|storage_key| storage_key.ends_with("/poster.jpg"))
+		.context("poster derivative was not recorded")?;
+	assert_eq!(app.state.storage.head_object(poster_key).await?.content_type, "image/jpeg");
 
 	Ok(())
 }
 
 #[tokio::test]
 #[ignore = "requires the local PostgreSQL and RustFS service graph"]
-async fn duplicate_upload_name_does_not_overwrite_existing_object() -> anyhow::Result<()> {
+async fn audio_uploads_expose_tags_and_waveform_peaks() -> anyhow::Result<()> {
 	let Some(app) = TestApp::new().await? else {
 		return Ok(());
 	};
 	let user = register_and_login(&app).await?;
-	let object_name = format!("duplicate-upload-{}.svg", unique_suffix()?);
+	let object_name = format!("tone-{}.wav", unique_suffix()?);
 	let upload = DirectUploadRequest::svg(
 		&object_name,
 		12.5,
 		-45.25,
-		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
-	);
-
-	app.direct_upload_object(&user.cookie, &upload).await?;
-	assert_eq!(app.object_count(&object_name).await?, 1);
-	assert_eq!(app.object_content_type(&object_name).await?, "image/svg+xml");
-	let storage_key = app.object_storage_key(&object_name).await?;
-	assert_eq!(app.state.storage.object_content_type(&storage_key).await?, "image/svg+xml");
+		include_bytes!("fixtures/tagged-tone.wav"),
+	)
+	.with_content_type("audio/wav");
 
-	let duplicate = DirectUploadRequest::svg(&object_name, 12.5, -45.25, b"not really a jpeg")
-		.with_content_type("image/jpeg");
-	let duplicate_upload = app.create_object_upload_session(Some(&user.cookie), &duplicate).await?;
+	let uploaded_object = app.direct_upload_object(&user.cookie, &upload).await?;
+	let object_id = json_path(&uploaded_object, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
 
-	assert_eq!(duplicate_upload.status, StatusCode::OK);
-	assert_graphql_error_contains(&duplicate_upload.json()?, "already exists")?;
-	assert_eq!(app.object_count(&object_name).await?, 1);
-	assert_eq!(app.object_storage_key(&object_name).await?, storage_key);
-	assert_eq!(app.object_content_type(&object_name).await?, "image/svg+xml");
-	assert_eq!(app.state.storage.object_content_type(&storage_key).await?, "image/svg+xml");
+	let objects = app
+		.graphql(
+			"query Objects {
+				s3Objects {
+					id
+					durationSeconds
+					audioCodec
+					mediaTags {
+						title
+						artist
+						recordedDate
+					}
+					waveformPeaks
+				}
+			}",
+			json!({}),
+			Some(&user.cookie),
+		)
+		.await?;
+	assert_eq!(objects.status, StatusCode::OK);
+	let objects = objects.json()?;
+	assert_graphql_success(&objects)?;
+	let object = json_path(&objects, &["data", "s3Objects"])?
+		.as_array()
+		.context("s3Objects response is not an array")?
+		.iter()
+		.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+		.context("uploaded object is missing from s3Objects")?;
+	assert_eq!(json_path(object, &["durationSeconds"])?.as_f64(), Some(1.0));
+	assert_eq!(json_path(object, &["audioCodec"])?.as_str(), Some("pcm_s16le"));
+	assert_eq!(json_path(object, &["mediaTags", "title"])?.as_str(), Some("Harbour at dawn"));
+	assert_eq!(json_path(object, &["mediaTags", "artist"])?.as_str(), Some("Memory Map"));
+	assert_eq!(json_path(object, &["mediaTags", "recordedDate"])?.as_str(), Some("2024-05-18"));
+	let peaks = json_path(object, &["waveformPeaks"])?
+		.as_array()
+		.context("waveformPeaks is not an array")?;
+	assert_eq!(peaks.len(), 100);
+	assert!(app.object_derivative_storage_keys(object_id.parse()?).await?.is_empty());
 
 	Ok(())
 }
 
 #[tokio::test]
 #[ignore = "requires the local PostgreSQL and RustFS service graph"]
-async fn stale_pending_upload_cleanup_removes_blob_metadata_and_releases_name() -> anyhow::Result<()>
+async fn deduplicated_upload_returns_the_owners_object_with_the_same_content() -> anyhow::Result<()>
 {
 	let Some(app) = TestApp::new().await? else {
 		return Ok(());
 	};
-	let user = register_and_login(&app).await?;
-	let object_name = format!("stale-pending-upload-{}.svg", unique_suffix()?);
-	let storage_key = format!("objects/stale-pending-upload-{}", unique_suffix()?);
-	let body = b"<svg xmlns=\"http://www.w3.org/2000/svg\" />";
-
-	app.state
-		.storage
-		.upload_object(&storage_key, ByteStream::from_static(body), "image/svg+xml")
-		.await?;
-
-	let client = app.state.pool.get().await?;
-	let user_id: i64 =
-		client.query_one("SELECT id FROM users WHERE email = $1", &[&user.email]).await?.get(0);
-	client
-		.execute(
-			"INSERT INTO objects (
-				name,
-				storage_key,
-				content_type,
-				storage_state,
-				storage_state_updated_at,
-				user_id,
-				publicity
-			)
-			VALUES ($1, $2, 'image/svg+xml', 'pending_upload', now() - interval '2 hours', $3, 'default')",
-			&[&object_name, &storage_key, &user_id],
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	let suffix = unique_suffix()?;
+	let body = format!("<svg xmlns=\"http://www.w3.org/2000/svg\"><title>{suffix}</title></svg>");
+	let object_name = format!("camera-roll-{suffix}.svg");
+	let upload = DirectUploadRequest::svg(&object_name, 12.5, -45.25, body.as_bytes());
+	let uploaded_object = app.direct_upload_object(&owner.cookie, &upload).await?;
+	app.run_object_derivative_worker().await?;
+
+	let content_hash = blake3::hash(body.as_bytes()).to_string();
+	let copy_name = format!("camera-roll-copy-{suffix}.svg");
+	let copy = DirectUploadRequest::svg(&copy_name, 12.5, -45.25, body.as_bytes());
+	let existing = app
+		.create_deduplicated_object_upload_session(
+			&owner.cookie,
+			&copy,
+			&content_hash.to_ascii_uppercase(),
 		)
 		.await?;
-	drop(client);
-
-	let lifecycle_config = ObjectLifecycleConfig {
-		pending_upload_timeout_seconds: 1,
-		storage_deletion_retry_seconds: 1,
-		storage_deletion_lease_seconds: 30,
-		maintenance_interval_seconds: 1,
-		storage_deletion_batch_size: 1000,
-		storage_deletion_max_attempts: 10,
-		..ObjectLifecycleConfig::default()
-	}
-	.validated()?;
-	app.run_object_lifecycle_maintenance(lifecycle_config).await?;
-
-	assert_eq!(app.object_count(&object_name).await?, 0);
-	assert!(app.state.storage.object_content_type(&storage_key).await.is_err());
-
-	let upload = DirectUploadRequest::svg(&object_name, 12.5, -45.25, body);
-	app.direct_upload_object(&user.cookie, &upload).await?;
-	assert_eq!(app.object_count(&object_name).await?, 1);
+	assert_eq!(existing.status, StatusCode::OK);
+	let existing = existing.json()?;
+	assert_graphql_success(&existing)?;
+	let existing = json_path(&existing, &["data", "createDeduplicatedObjectUploadSession"])?;
+	assert_eq!(json_path(existing, &["__typename"])?.as_str(), Some("S3Object"));
+	assert_eq!(json_path(existing, &["id"])?, json_path(&uploaded_object, &["id"])?);
+	assert_eq!(json_path(existing, &["name"])?.as_str(), Some(object_name.as_str()));
+	assert_eq!(app.object_count(&copy_name).await?, 0);
+
+	// Another user's identical content is not theirs to reuse.
+	let created =
+		app.create_deduplicated_object_upload_session(&other.cookie, &copy, &content_hash).await?;
+	assert_eq!(created.status, StatusCode::OK);
+	let created = created.json()?;
+	assert_graphql_success(&created)?;
+	assert_eq!(
+		json_path(&created, &["data", "createDeduplicatedObjectUploadSession", "__typename"])?
+			.as_str(),
+		Some("CreatedObjectUploadSession")
+	);
+	assert_eq!(app.object_count(&copy_name).await?, 1);
 
 	Ok(())
 }
 
 #[tokio::test]
 #[ignore = "requires the local PostgreSQL and RustFS service graph"]
-async fn expired_incomplete_upload_session_cleanup_removes_metadata() -> anyhow::Result<()> {
+async fn resized_copies_are_offered_as_duplicates_until_dismissed() -> anyhow::Result<()> {
 	let Some(app) = TestApp::new().await? else {
 		return Ok(());
 	};
-	let user = register_and_login(&app).await?;
-	let object_name = format!("expired-upload-session-{}.svg", unique_suffix()?);
-	let upload = DirectUploadRequest::svg(
-		&object_name,
-		12.5,
-		-45.25,
-		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
-	);
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	let suffix = unique_suffix()?;
+	let scene = DynamicImage::ImageRgb8(RgbImage::from_fn(640, 480, |x, y| {
+		let shade = ((x * 255 / 640 + y * 128 / 480) % 256) as u8;
+		Rgb([shade, shade / 2, 64])
+	}));
+	let mut original_body = Vec::new();
+	scene.write_to(&mut Cursor::new(&mut original_body), ImageFormat::Png)?;
+	let mut copy_body = Vec::new();
+	scene
+		.resize(320, 240, FilterType::Lanczos3)
+		.write_to(&mut Cursor::new(&mut copy_body), ImageFormat::Jpeg)?;
+	let original_name = format!("near-duplicate-{suffix}.png");
+	let copy_name = format!("near-duplicate-copy-{suffix}.jpg");
+	let original = app
+		.direct_upload_object(
+			&owner.cookie,
+			&DirectUploadRequest::svg(&original_name, 12.5, -45.25, &original_body)
+				.with_content_type("image/png"),
+		)
+		.await?;
+	let copy = app
+		.direct_upload_object(
+			&owner.cookie,
+			&DirectUploadRequest::svg(&copy_name, 12.5, -45.25, &copy_body)
+				.with_content_type("image/jpeg"),
+		)
+		.await?;
+	let original_id = json_path(&original, &["id"])?.clone();
+	let copy_id = json_path(&copy, &["id"])?.clone();
+	app.run_perceptual_hash_worker().await?;
+
+	let similar_query = "query SimilarObjects($id: ID!) { similarObjects(id: $id) { id } }";
+	let similar = app
+		.graphql(similar_query, json!({ "id": original_id }), Some(&owner.cookie))
+		.await?
+		.json()?;
+	assert_graphql_success(&similar)?;
+	assert_eq!(json_path(&similar, &["data", "similarObjects"])?, &json!([{ "id": copy_id }]));
+	let forbidden =
+		app.graphql(similar_query, json!({ "id": original_id }), Some(&other.cookie)).await?;
+	assert_graphql_error_contains(&forbidden.json()?, "Forbidden")?;
+
+	let duplicates_query = "query { possibleDuplicates { first { id } second { id } distance } }";
+	let duplicates = app.graphql(duplicates_query, json!({}), Some(&owner.cookie)).await?.json()?;
+	assert_graphql_success(&duplicates)?;
+	let pairs = json_path(&duplicates, &["data", "possibleDuplicates"])?
+		.as_array()
+		.context("possibleDuplicates is not a list")?;
+	assert_eq!(pairs.len(), 1);
+	let pair = pairs.first().context("possibleDuplicates is empty")?;
+	assert_eq!(json_path(pair, &["first", "id"])?, &original_id);
+	assert_eq!(json_path(pair, &["second", "id"])?, &copy_id);
+	assert!(json_path(pair, &["distance"])?.as_i64().is_some_and(|distance| distance <= 6));
+
+	// Either order names the same pair.
+	let dismissed = app
+		.graphql(
+			"mutation Dismiss($firstId: ID!, $secondId: ID!) { \
+				dismissDuplicatePair(firstId: $firstId, secondId: $secondId) }",
+			json!({ "firstId": copy_id, "secondId": original_id }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&dismissed)?;
+	let duplicates = app.graphql(duplicates_query, json!({}), Some(&owner.cookie)).await?.json()?;
+	assert_graphql_success(&duplicates)?;
+	assert_eq!(json_path(&duplicates, &["data", "possibleDuplicates"])?, &json!([]));
+
+	// Pairs are only recorded up to the candidate distance.
+	let too_wide = app
+		.graphql(
+			"query { possibleDuplicates(maxDistance: 11) { distance } }",
+			json!({}),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_error_contains(&too_wide, "maxDistance must be between 0 and 10")?;
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn other_users_are_served_a_sanitized_copy_of_public_photos() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	for (query, variables) in [
+		(
+			"mutation ($publicity: PublicityDefault!) { \
+				updateUserPublicity(defaultPublicity: $publicity) { id } }",
+			json!({ "publicity": "PUBLIC" }),
+		),
+		(
+			"mutation ($privacy: MetadataPrivacy!) { \
+				updateUserMetadataPrivacy(metadataPrivacy: $privacy) { metadataPrivacy } }",
+			json!({ "privacy": "STRIP_ALL" }),
+		),
+	] {
+		let updated = app.graphql(query, variables, Some(&owner.cookie)).await?.json()?;
+		assert_graphql_success(&updated)?;
+	}
+
+	// A JPEG with a comment segment, which only the owner should see.
+	let mut jpeg = Vec::new();
+	DynamicImage::ImageRgb8(RgbImage::new(64, 32))
+		.write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)?;
+	let comment = b"taken at the summer house";
+	let comment_length = u16::try_from(comment.len() + 2)?.to_be_bytes();
+	let body = [
+		b"\xff\xd8\xff\xfe".as_slice(),
+		&comment_length,
+		comment,
+		jpeg.get(2 ..).context("encoded JPEG is empty")?,
+	]
+	.concat();
+	let object_name = format!("sanitized-{}.jpg", unique_suffix()?);
+	let uploaded = app
+		.direct_upload_object(
+			&owner.cookie,
+			&DirectUploadRequest::svg(&object_name, 12.5, -45.25, &body)
+				.with_content_type("image/jpeg"),
+		)
+		.await?;
+	let object_id = json_path(&uploaded, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
+
+	let sanitized_key = app
+		.object_derivative_storage_keys(object_id.parse()?)
+		.await?
+		.into_iter()
+		.find(|storage_key| storage_key.ends_with("/sanitized.jpg"))
+		.context("no sanitized copy was stored")?;
+	let length = app.state.storage.head_object(&sanitized_key).await?.content_length;
+	let sanitized =
+		app.state.storage.get_object_range(&sanitized_key, 0, length.try_into()?).await?;
+	assert!(!sanitized.windows(comment.len()).any(|window| window == comment));
+
+	let served_url = |cookie: Option<String>| {
+		let app = &app;
+		let object_id = object_id.clone();
+		async move {
+			let objects = app
+				.graphql("query { s3Objects { id url } }", json!({}), cookie.as_deref())
+				.await?
+				.json()?;
+			assert_graphql_success(&objects)?;
+			let object = json_path(&objects, &["data", "s3Objects"])?
+				.as_array()
+				.context("s3Objects response is not an array")?
+				.iter()
+				.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+				.context("uploaded object is missing from s3Objects")?
+				.clone();
+			anyhow::Ok(json_path(&object, &["url"])?.as_str().unwrap_or_default().to_string())
+		}
+	};
+	assert!(!served_url(Some(owner.cookie.clone())).await?.contains("sanitized.jpg"));
+	assert!(served_url(Some(other.cookie.clone())).await?.contains("sanitized.jpg"));
+	assert!(served_url(None).await?.contains("sanitized.jpg"));
+
+	// An object's own setting overrides the owner's.
+	let updated = app
+		.graphql(
+			"mutation Update($input: UpdateS3ObjectInput!) { \
+				updateS3Object(input: $input) { metadataPrivacy } }",
+			json!({
+				"input": {
+					"id": object_id,
+					"name": object_name,
+					"publicity": "DEFAULT",
+					"metadataPrivacy": "KEEP",
+				},
+			}),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&updated)?;
+	assert_eq!(json_path(&updated, &["data", "updateS3Object", "metadataPrivacy"])?, "KEEP");
+	assert!(!served_url(Some(other.cookie.clone())).await?.contains("sanitized.jpg"));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn other_users_are_served_videos_without_their_location() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	for (query, variables) in [
+		(
+			"mutation ($publicity: PublicityDefault!) { \
+				updateUserPublicity(defaultPublicity: $publicity) { id } }",
+			json!({ "publicity": "PUBLIC" }),
+		),
+		(
+			"mutation ($privacy: MetadataPrivacy!) { \
+				updateUserMetadataPrivacy(metadataPrivacy: $privacy) { metadataPrivacy } }",
+			json!({ "privacy": "STRIP_LOCATION" }),
+		),
+	] {
+		let updated = app.graphql(query, variables, Some(&owner.cookie)).await?.json()?;
+		assert_graphql_success(&updated)?;
+	}
+
+	// The fixture records where it was filmed in a QuickTime `\xa9xyz` box.
+	let video = include_bytes!("fixtures/av1-cover-art.mp4");
+	let location = b"\xa9xyz";
+	assert!(video.windows(location.len()).any(|window| window == location));
+	let object_name = format!("located-{}.mp4", unique_suffix()?);
+	let uploaded = app
+		.direct_upload_object(
+			&owner.cookie,
+			&DirectUploadRequest::svg(&object_name, 12.5, -45.25, video)
+				.with_content_type("video/mp4"),
+		)
+		.await?;
+	let object_id = json_path(&uploaded, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
+
+	let served = |cookie: Option<String>| {
+		let app = &app;
+		let object_id = object_id.clone();
+		async move {
+			let objects = app
+				.graphql("query { s3Objects { id url } }", json!({}), cookie.as_deref())
+				.await?
+				.json()?;
+			assert_graphql_success(&objects)?;
+			let object = json_path(&objects, &["data", "s3Objects"])?
+				.as_array()
+				.context("s3Objects response is not an array")?
+				.iter()
+				.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+				.context("uploaded object is missing from s3Objects")?
+				.clone();
+			let url = json_path(&object, &["url"])?.as_str().unwrap_or_default().to_string();
+			let response = reqwest::get(&url).await?;
+			let status = response.status();
+			assert!(status.is_success(), "download failed with {status}");
+			anyhow::Ok(response.bytes().await?.to_vec())
+		}
+	};
+	let owned = served(Some(owner.cookie.clone())).await?;
+	assert_eq!(owned, video);
+	for cookie in [Some(other.cookie.clone()), None] {
+		let sanitized = served(cookie).await?;
+		assert_eq!(sanitized.len(), video.len());
+		assert!(!sanitized.windows(location.len()).any(|window| window == location));
+	}
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn other_users_are_served_the_sanitized_copy_of_mp4_audio() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	for (query, variables) in [
+		(
+			"mutation ($publicity: PublicityDefault!) { \
+				updateUserPublicity(defaultPublicity: $publicity) { id } }",
+			json!({ "publicity": "PUBLIC" }),
+		),
+		(
+			"mutation ($privacy: MetadataPrivacy!) { \
+				updateUserMetadataPrivacy(metadataPrivacy: $privacy) { metadataPrivacy } }",
+			json!({ "privacy": "STRIP_ALL" }),
+		),
+	] {
+		let updated = app.graphql(query, variables, Some(&owner.cookie)).await?.json()?;
+		assert_graphql_success(&updated)?;
+	}
+
+	// The same container as the located video, uploaded as audio.
+	let audio = include_bytes!("fixtures/av1-cover-art.mp4");
+	let location = b"\xa9xyz";
+	let object_name = format!("located-{}.m4a", unique_suffix()?);
+	let uploaded = app
+		.direct_upload_object(
+			&owner.cookie,
+			&DirectUploadRequest::svg(&object_name, 12.5, -45.25, audio)
+				.with_content_type("audio/mp4"),
+		)
+		.await?;
+	let object_id = json_path(&uploaded, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
+
+	let objects = app
+		.graphql("query { s3Objects { id url } }", json!({}), Some(&other.cookie))
+		.await?
+		.json()?;
+	assert_graphql_success(&objects)?;
+	let object = json_path(&objects, &["data", "s3Objects"])?
+		.as_array()
+		.context("s3Objects response is not an array")?
+		.iter()
+		.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+		.context("uploaded object is missing from s3Objects")?
+		.clone();
+	let url = json_path(&object, &["url"])?.as_str().unwrap_or_default().to_string();
+	assert!(url.contains("/sanitized.m4a"), "{url} is not the sanitized copy");
+	let response = reqwest::get(&url).await?;
+	let status = response.status();
+	assert!(status.is_success(), "download failed with {status}");
+	let sanitized = response.bytes().await?;
+	assert_eq!(sanitized.len(), audio.len());
+	assert!(!sanitized.windows(location.len()).any(|window| window == location));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn raw_photos_get_a_display_rendition_and_keep_their_original() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+
+	// A CR2 header, then an IFD pointing at an embedded JPEG preview.
+	let mut jpeg = Vec::new();
+	DynamicImage::ImageRgb8(RgbImage::new(48, 32))
+		.write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)?;
+	let entry = |tag: u16, value: u32| {
+		[
+			tag.to_le_bytes().as_slice(),
+			&4u16.to_le_bytes(),
+			&1u32.to_le_bytes(),
+			&value.to_le_bytes(),
+		]
+		.concat()
+	};
+	let body = [
+		b"II*\0\x10\0\0\0CR\x02\0\0\0\0\0\x02\0".as_slice(),
+		&entry(0x0201, 46),
+		&entry(0x0202, u32::try_from(jpeg.len())?),
+		&0u32.to_le_bytes(),
+		&jpeg,
+	]
+	.concat();
+	let object_name = format!("raw-{}.cr2", unique_suffix()?);
+	let uploaded = app
+		.direct_upload_object(
+			&owner.cookie,
+			&DirectUploadRequest::svg(&object_name, 12.5, -45.25, &body)
+				.with_content_type("image/x-canon-cr2"),
+		)
+		.await?;
+	let object_id = json_path(&uploaded, &["id"])?
+		.as_str()
+		.context("uploaded object id is not a string")?
+		.to_string();
+	app.run_object_derivative_worker().await?;
+
+	let objects = app
+		.graphql("query { s3Objects { id url displayUrl } }", json!({}), Some(&owner.cookie))
+		.await?
+		.json()?;
+	assert_graphql_success(&objects)?;
+	let object = json_path(&objects, &["data", "s3Objects"])?
+		.as_array()
+		.context("s3Objects response is not an array")?
+		.iter()
+		.find(|object| json_path(object, &["id"]).is_ok_and(|id| id == object_id.as_str()))
+		.context("uploaded object is missing from s3Objects")?;
+	let url = json_path(object, &["url"])?.as_str().context("url is not a string")?;
+	let display_url =
+		json_path(object, &["displayUrl"])?.as_str().context("displayUrl is not a string")?;
+	assert!(!url.contains("display.jpg"));
+	assert!(display_url.contains("display.jpg"));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn s3_objects_connection_pages_through_filtered_objects() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let me = app.graphql("query { me { id } }", json!({}), Some(&user.cookie)).await?.json()?;
+	assert_graphql_success(&me)?;
+	let owner_id = json_path(&me, &["data", "me", "id"])?.clone();
+
+	let suffix = unique_suffix()?;
+	for (year, located) in [("2020", true), ("2021", false), ("2022", true)] {
+		let object_name = format!("page-{year}-{suffix}.svg");
+		let made_on = format!("{year}-01-01T00:00:00Z");
+		let upload = DirectUploadRequest::svg(
+			&object_name,
+			12.5,
+			-45.25,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		)
+		.with_made_on(&made_on);
+		let upload = if located { upload } else { upload.with_location(None) };
+		app.direct_upload_object(&user.cookie, &upload).await?;
+	}
+
+	let page = |filter: Value, after: Option<String>| {
+		let app = &app;
+		let cookie = user.cookie.clone();
+		async move {
+			let page = app
+				.graphql(
+					"query Page($filter: S3ObjectFilter!, $after: String) {
+						s3ObjectsConnection(first: 2, after: $after, filter: $filter) {
+							edges { node { name } }
+							pageInfo { hasNextPage endCursor }
+						}
+					}",
+					json!({ "filter": filter, "after": after }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&page)?;
+			let connection = json_path(&page, &["data", "s3ObjectsConnection"])?;
+			let names = json_path(connection, &["edges"])?
+				.as_array()
+				.context("edges is not an array")?
+				.iter()
+				.map(|edge| {
+					json_path(edge, &["node", "name"])
+						.map(|name| name.as_str().unwrap_or_default().to_string())
+				})
+				.collect::<anyhow::Result<Vec<_>>>()?;
+			let has_next_page = json_path(connection, &["pageInfo", "hasNextPage"])? == true;
+			let end_cursor =
+				json_path(connection, &["pageInfo", "endCursor"])?.as_str().map(str::to_string);
+			anyhow::Ok((names, has_next_page, end_cursor))
+		}
+	};
+	let name = |year: &str| format!("page-{year}-{suffix}.svg");
+
+	let (names, has_next_page, end_cursor) = page(json!({ "ownerId": owner_id }), None).await?;
+	assert_eq!(names, [name("2022"), name("2021")]);
+	assert!(has_next_page);
+	let (names, has_next_page, _) = page(json!({ "ownerId": owner_id }), end_cursor).await?;
+	assert_eq!(names, [name("2020")]);
+	assert!(!has_next_page);
+
+	let (names, ..) = page(json!({ "ownerId": owner_id, "hasLocation": false }), None).await?;
+	assert_eq!(names, [name("2021")]);
+	let (names, ..) = page(
+		json!({
+			"ownerId": owner_id,
+			"contentTypeFamily": "IMAGE",
+			"madeOnFrom": "2020-06-01T00:00:00Z",
+			"madeOnTo": "2022-01-01T00:00:00Z",
+		}),
+		None,
+	)
+	.await?;
+	assert_eq!(names, [name("2022"), name("2021")]);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn spatial_queries_find_objects_across_the_antimeridian() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+
+	let suffix = unique_suffix()?;
+	let name = |label: &str| format!("spatial-{label}-{suffix}.svg");
+	for (label, longitude) in [("east", 179.5), ("west", -179.5), ("far", 10.0)] {
+		let object_name = name(label);
+		let upload = DirectUploadRequest::svg(
+			&object_name,
+			-61.5,
+			longitude,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		);
+		app.direct_upload_object(&user.cookie, &upload).await?;
+	}
+
+	// Other tests' objects may share the database, so only this test's count.
+	let names = |response: &Value, field: &str| {
+		let names = json_path(response, &["data", field])?
+			.as_array()
+			.context("the result is not an array")?
+			.iter()
+			.map(|object| {
+				json_path(object, &["name"])
+					.map(|name| name.as_str().unwrap_or_default().to_string())
+			})
+			.collect::<anyhow::Result<Vec<_>>>()?;
+		anyhow::Ok(
+			names
+				.into_iter()
+				.filter(|name| name.ends_with(&format!("-{suffix}.svg")))
+				.collect::<Vec<_>>(),
+		)
+	};
+
+	let in_bounds = app
+		.graphql(
+			"query { s3ObjectsInBounds(south: -62, west: 179, north: -61, east: -179) { name } }",
+			json!({}),
+			Some(&user.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&in_bounds)?;
+	let mut found = names(&in_bounds, "s3ObjectsInBounds")?;
+	found.sort();
+	assert_eq!(found, [name("east"), name("west")]);
+
+	let near = app
+		.graphql(
+			"query {
+				s3ObjectsNear(location: { latitude: -61.5, longitude: 179.5 }, radiusMeters: 100000) {
+					name
+				}
+			}",
+			json!({}),
+			Some(&user.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&near)?;
+	assert_eq!(names(&near, "s3ObjectsNear")?, [name("east"), name("west")]);
+
+	let inverted = app
+		.graphql(
+			"query { s3ObjectsInBounds(south: 10, west: 0, north: -10, east: 1) { name } }",
+			json!({}),
+			Some(&user.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_error_contains(&inverted, "is north of the north edge")?;
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn map_clusters_split_as_the_map_zooms_in() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+
+	// A spot of this run's own, so objects left by earlier runs stay out of the
+	// box queried below.
+	let suffix = unique_suffix()?;
+	let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+	let longitude = -170.0 + (nanos % 10_000) as f64 * 0.001;
+	let latitude = 40.0;
+	for (label, offset, made_on) in [
+		("first", 0.0, "2020-01-01T00:00:00Z"),
+		("second", 0.0, "2021-06-01T00:00:00Z"),
+		("nearby", 0.0005, "2022-01-01T00:00:00Z"),
+	] {
+		let object_name = format!("cluster-{label}-{suffix}.svg");
+		let upload = DirectUploadRequest::svg(
+			&object_name,
+			latitude,
+			longitude + offset,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		)
+		.with_made_on(made_on);
+		app.direct_upload_object(&user.cookie, &upload).await?;
+	}
+
+	let clusters = |zoom: i32| {
+		let app = &app;
+		let cookie = user.cookie.clone();
+		async move {
+			let response = app
+				.graphql(
+					"query Clusters($bounds: BoundsInput!, $zoom: Int!) {
+						mapClusters(bounds: $bounds, zoom: $zoom) {
+							count
+							bounds { south west north east }
+							earliestMadeOn
+							latestMadeOn
+							objectIds
+						}
+					}",
+					json!({
+						"bounds": {
+							"south": latitude - 0.001,
+							"west": longitude - 0.001,
+							"north": latitude + 0.001,
+							"east": longitude + 0.001,
+						},
+						"zoom": zoom,
+					}),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&response)?;
+			anyhow::Ok(json_path(&response, &["data", "mapClusters"])?.clone())
+		}
+	};
+
+	let zoomed_out = clusters(2).await?;
+	assert_eq!(zoomed_out.as_array().map(Vec::len), Some(1));
+	assert_eq!(zoomed_out.pointer("/0/count"), Some(&json!(3)));
+	assert_eq!(zoomed_out.pointer("/0/earliestMadeOn"), Some(&json!("2020-01-01T00:00:00Z")));
+	assert_eq!(zoomed_out.pointer("/0/latestMadeOn"), Some(&json!("2022-01-01T00:00:00Z")));
+
+	let zoomed_in = clusters(20).await?;
+	assert_eq!(zoomed_in.as_array().map(Vec::len), Some(2));
+	assert_eq!(zoomed_in.pointer("/0/count"), Some(&json!(2)));
+	assert_eq!(zoomed_in.pointer("/0/bounds/west"), zoomed_in.pointer("/0/bounds/east"));
+	assert_eq!(zoomed_in.pointer("/0/objectIds").and_then(Value::as_array).map(Vec::len), Some(2));
+	assert_eq!(zoomed_in.pointer("/1/count"), Some(&json!(1)));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn timeline_histogram_counts_dated_objects_per_bucket() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let me = app.graphql("query { me { id } }", json!({}), Some(&user.cookie)).await?.json()?;
+	assert_graphql_success(&me)?;
+	let owner_id = json_path(&me, &["data", "me", "id"])?.clone();
+
+	let suffix = unique_suffix()?;
+	for (label, made_on) in [
+		("first", Some("2019-03-02T10:00:00Z")),
+		("second", Some("2019-03-30T18:00:00Z")),
+		("third", Some("2020-07-14T08:00:00Z")),
+		("undated", None),
+	] {
+		let object_name = format!("timeline-{label}-{suffix}.svg");
+		let upload = DirectUploadRequest::svg(
+			&object_name,
+			12.5,
+			-45.25,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		);
+		let upload = match made_on {
+			Some(made_on) => upload.with_made_on(made_on),
+			None => upload,
+		};
+		app.direct_upload_object(&user.cookie, &upload).await?;
+	}
+
+	let histogram = |bucket: &'static str, filter: Value| {
+		let app = &app;
+		let cookie = user.cookie.clone();
+		async move {
+			let response = app
+				.graphql(
+					"query Timeline($bucket: TimelineBucket!, $filter: S3ObjectFilter!) {
+						timelineHistogram(bucket: $bucket, filter: $filter) {
+							start
+							count
+							latestMadeOn
+						}
+					}",
+					json!({ "bucket": bucket, "filter": filter }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&response)?;
+			anyhow::Ok(json_path(&response, &["data", "timelineHistogram"])?.clone())
+		}
+	};
+
+	let months = histogram("MONTH", json!({ "ownerId": owner_id })).await?;
+	assert_eq!(
+		months,
+		json!([
+			{
+				"start": "2019-03-01T00:00:00Z",
+				"count": 2,
+				"latestMadeOn": "2019-03-30T18:00:00Z",
+			},
+			{
+				"start": "2020-07-01T00:00:00Z",
+				"count": 1,
+				"latestMadeOn": "2020-07-14T08:00:00Z",
+			},
+		])
+	);
+
+	let years =
+		histogram("YEAR", json!({ "ownerId": owner_id, "madeOnFrom": "2020-01-01T00:00:00Z" }))
+			.await?;
+	assert_eq!(years.as_array().map(Vec::len), Some(1));
+	assert_eq!(years.pointer("/0/start"), Some(&json!("2020-01-01T00:00:00Z")));
+
+	// The map narrows to a period the same way.
+	let clusters = app
+		.graphql(
+			"query Clusters($filter: S3ObjectFilter!) {
+				mapClusters(
+					bounds: { south: 12, west: -46, north: 13, east: -45 }
+					zoom: 20
+					filter: $filter
+				) {
+					count
+				}
+			}",
+			json!({
+				"filter": {
+					"ownerId": owner_id,
+					"madeOnFrom": "2019-03-01T00:00:00Z",
+					"madeOnTo": "2019-03-30T18:00:00Z",
+				},
+			}),
+			Some(&user.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&clusters)?;
+	assert_eq!(json_path(&clusters, &["data", "mapClusters"])?, &json!([{ "count": 2 }]));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn search_matches_name_words_content_types_and_owners() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+
+	let suffix = unique_suffix()?;
+	let beach = format!("IMG_2023-05_beach-{suffix}.svg");
+	let harbour = format!("harbour-{suffix}.svg");
+	for object_name in [&beach, &harbour] {
+		let upload = DirectUploadRequest::svg(
+			object_name,
+			12.5,
+			-45.25,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		);
+		app.direct_upload_object(&owner.cookie, &upload).await?;
+	}
+
+	let search = |cookie: String, query: String, after: Option<String>| {
+		let app = &app;
+		async move {
+			let response = app
+				.graphql(
+					"query Search($query: String!, $after: String) {
+						searchS3Objects(query: $query, first: 1, after: $after) {
+							edges { cursor node { name } }
+							pageInfo { hasNextPage }
+						}
+					}",
+					json!({ "query": query, "after": after }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&response)?;
+			let results = json_path(&response, &["data", "searchS3Objects"])?;
+			let names: Vec<Value> = json_path(results, &["edges"])?
+				.as_array()
+				.context("edges is not an array")?
+				.iter()
+				.filter_map(|edge| edge.pointer("/node/name").cloned())
+				.collect();
+			let has_next_page = json_path(results, &["pageInfo", "hasNextPage"])? == &json!(true);
+			let end_cursor =
+				results.pointer("/edges/0/cursor").and_then(Value::as_str).map(String::from);
+			anyhow::Ok((names, has_next_page, end_cursor))
+		}
+	};
+
+	// Words of a name are found on their own, and by their starts.
+	let (names, has_next_page, _) =
+		search(owner.cookie.clone(), format!("bea 2023 {suffix}"), None).await?;
+	assert_eq!(names, vec![json!(beach)]);
+	assert!(!has_next_page);
+
+	// Both objects are SVGs; the second page follows on from the first.
+	let (first_page, has_next_page, end_cursor) =
+		search(owner.cookie.clone(), format!("svg {suffix}"), None).await?;
+	assert!(has_next_page);
+	let (second_page, has_next_page, _) =
+		search(owner.cookie.clone(), format!("svg {suffix}"), end_cursor).await?;
+	assert!(!has_next_page);
+	let mut names = [first_page, second_page].concat();
+	names.sort_by_key(ToString::to_string);
+	assert_eq!(names, vec![json!(beach), json!(harbour)]);
+
+	// Owners' emails match as substrings.
+	let owner_local_part = owner.email.split('@').next().context("email has no local part")?;
+	let (names, ..) = search(owner.cookie.clone(), owner_local_part.to_string(), None).await?;
+	assert_eq!(names.len(), 1);
+
+	// Private objects stay hidden from other users.
+	let (names, ..) = search(other.cookie.clone(), format!("beach {suffix}"), None).await?;
+	assert!(names.is_empty());
+
+	let invalid = app
+		.graphql(
+			"query { searchS3Objects(query: \" -- \") { edges { cursor } } }",
+			json!({}),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_error_contains(&invalid, "must contain a letter or digit")?;
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn titles_and_descriptions_are_shared_and_notes_kept_private() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+
+	let suffix = unique_suffix()?;
+	let object_name = format!("story-{suffix}.svg");
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+	let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+	let object_id = json_path(&object, &["id"])?.clone();
+
+	let update = |input: Value| {
+		let app = &app;
+		let cookie = owner.cookie.clone();
+		async move {
+			app.graphql(
+				"mutation Update($input: UpdateS3ObjectInput!) {
+					updateS3Object(input: $input) { title description note }
+				}",
+				json!({ "input": input }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	let updated = update(json!({
+		"id": object_id,
+		"name": object_name,
+		"publicity": "PUBLIC",
+		"title": "  Sunset at the pier ",
+		"description": format!("Walked to the *pier{suffix}* after dinner"),
+		"note": format!("Ask about the lighthouse{suffix}"),
+	}))
+	.await?;
+	assert_graphql_success(&updated)?;
+	assert_eq!(
+		json_path(&updated, &["data", "updateS3Object"])?,
+		&json!({
+			"title": "Sunset at the pier",
+			"description": format!("Walked to the *pier{suffix}* after dinner"),
+			"note": format!("Ask about the lighthouse{suffix}"),
+		})
+	);
+
+	// Leaving them out keeps them.
+	let kept =
+		update(json!({ "id": object_id, "name": object_name, "publicity": "PUBLIC" })).await?;
+	assert_graphql_success(&kept)?;
+	assert_eq!(json_path(&kept, &["data", "updateS3Object", "title"])?, "Sunset at the pier");
+
+	let too_long = update(json!({
+		"id": object_id,
+		"name": object_name,
+		"publicity": "PUBLIC",
+		"title": "t".repeat(201),
+	}))
+	.await?;
+	assert_graphql_error_contains(&too_long, "Titles can be at most 200 characters long")?;
+
+	let search = |cookie: String, query: String| {
+		let app = &app;
+		async move {
+			let response = app
+				.graphql(
+					"query Search($query: String!) {
+						searchS3Objects(query: $query) { edges { node { title note } } }
+					}",
+					json!({ "query": query }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&response)?;
+			anyhow::Ok(json_path(&response, &["data", "searchS3Objects", "edges"])?.clone())
+		}
+	};
+
+	// Descriptions are searched for everyone who can see the object, and notes
+	// only for their owner, who alone can read them.
+	assert_eq!(
+		search(other.cookie.clone(), format!("pier{suffix}")).await?,
+		json!([{ "node": { "title": "Sunset at the pier", "note": null } }])
+	);
+	assert_eq!(
+		search(owner.cookie.clone(), format!("lighthouse{suffix}")).await?,
+		json!([{
+			"node": {
+				"title": "Sunset at the pier",
+				"note": format!("Ask about the lighthouse{suffix}"),
+			},
+		}])
+	);
+	assert_eq!(search(other.cookie.clone(), format!("lighthouse{suffix}")).await?, json!([]));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn metadata_edits_are_recorded_and_can_be_undone() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	let object_name = format!("history-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+	let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+	let object_id = json_path(&object, &["id"])?.clone();
+
+	let update = |input: Value| {
+		let app = &app;
+		let cookie = owner.cookie.clone();
+		async move {
+			app.graphql(
+				"mutation Update($input: UpdateS3ObjectInput!) {
+					updateS3Object(input: $input) { id }
+				}",
+				json!({ "input": input }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	let history = |cookie: String| {
+		let app = &app;
+		let object_id = object_id.clone();
+		async move {
+			app.graphql(
+				"query History($id: ID!) {
+					objectHistory(id: $id) {
+						revision
+						editor
+						changes { field oldValue newValue }
+					}
+				}",
+				json!({ "id": object_id }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+
+	// The location is wiped by accident while a title is added.
+	let wiped = update(json!({
+		"id": object_id,
+		"name": object_name,
+		"madeOn": "2026-05-31T12:00:00Z",
+		"publicity": "DEFAULT",
+		"title": "Harbour",
+	}))
+	.await?;
+	assert_graphql_success(&wiped)?;
+	// Saving without changes records nothing.
+	let unchanged = update(json!({
+		"id": object_id,
+		"name": object_name,
+		"madeOn": "2026-05-31T12:00:00Z",
+		"publicity": "DEFAULT",
+	}))
+	.await?;
+	assert_graphql_success(&unchanged)?;
+
+	let revisions = history(owner.cookie.clone()).await?;
+	assert_graphql_success(&revisions)?;
+	assert_eq!(
+		json_path(&revisions, &["data", "objectHistory"])?,
+		&json!([{
+			"revision": 1,
+			"editor": owner.email,
+			"changes": [
+				{ "field": "location", "oldValue": "12.5, -45.25", "newValue": null },
+				{ "field": "title", "oldValue": null, "newValue": "Harbour" },
+			],
+		}])
+	);
+	assert_graphql_error_contains(&history(other.cookie.clone()).await?, "Forbidden")?;
+
+	let revert = |cookie: String, revision: i32| {
+		let app = &app;
+		let object_id = object_id.clone();
+		async move {
+			app.graphql(
+				"mutation Revert($id: ID!, $revision: Int!) {
+					revertObject(id: $id, revision: $revision) {
+						title
+						location { latitude longitude }
+					}
+				}",
+				json!({ "id": object_id, "revision": revision }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	assert_graphql_error_contains(&revert(other.cookie.clone(), 1).await?, "Forbidden")?;
+	assert_graphql_error_contains(&revert(owner.cookie.clone(), 5).await?, "Revision not found")?;
+
+	let reverted = revert(owner.cookie.clone(), 1).await?;
+	assert_graphql_success(&reverted)?;
+	assert_eq!(
+		json_path(&reverted, &["data", "revertObject"])?,
+		&json!({ "title": "", "location": { "latitude": 12.5, "longitude": -45.25 } })
+	);
+
+	// The undo is an edit of its own.
+	let revisions = history(owner.cookie.clone()).await?;
+	assert_graphql_success(&revisions)?;
+	assert_eq!(
+		json_path(&revisions, &["data", "objectHistory"])?.get(0),
+		Some(&json!({
+			"revision": 2,
+			"editor": owner.email,
+			"changes": [
+				{ "field": "location", "oldValue": null, "newValue": "12.5, -45.25" },
+				{ "field": "title", "oldValue": "Harbour", "newValue": null },
+			],
+		}))
+	);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn albums_keep_their_order_and_share_by_album_publicity() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let friend = register_and_login(&app).await?;
+	let stranger = register_and_login(&app).await?;
+
+	let suffix = unique_suffix()?;
+	let mut ids = Vec::new();
+	for label in ["first", "second", "third"] {
+		let object_name = format!("album-{label}-{suffix}.svg");
+		let upload = DirectUploadRequest::svg(
+			&object_name,
+			12.5,
+			-45.25,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		);
+		let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+		ids.push(json_path(&object, &["id"])?.clone());
+	}
+	let [first, second, third] = ids.as_slice() else {
+		anyhow::bail!("expected three uploaded objects");
+	};
+
+	// Private objects in an album shared with selected users.
+	let created = app
+		.graphql(
+			"mutation CreateAlbum($input: CreateAlbumInput!) {
+				createAlbum(input: $input) { id title publicity allowedUsers cover { id } }
+			}",
+			json!({ "input": {
+				"title": " Summer ",
+				"description": "Beach days",
+				"publicity": "SELECTED_USERS",
+				"allowedUsers": [friend.email],
+			} }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&created)?;
+	let album = json_path(&created, &["data", "createAlbum"])?;
+	assert_eq!(json_path(album, &["title"])?, &json!("Summer"));
+	assert_eq!(json_path(album, &["allowedUsers"])?, &json!([friend.email]));
+	assert_eq!(json_path(album, &["cover"])?, &Value::Null);
+	let album_id = json_path(album, &["id"])?.clone();
+
+	let change_objects = |cookie: String, mutation: &'static str, object_ids: Value| {
+		let app = &app;
+		let album_id = album_id.clone();
+		async move {
+			let query = format!(
+				"mutation Change($albumId: ID!, $objectIds: [ID!]!) {{
+					{mutation}(albumId: $albumId, objectIds: $objectIds) {{
+						objects {{ id }}
+						cover {{ id }}
+					}}
+				}}"
+			);
+			app.graphql(
+				&query,
+				json!({ "albumId": album_id, "objectIds": object_ids }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+
+	// Objects are appended in the order given; ones already there stay put.
+	change_objects(owner.cookie.clone(), "addObjectsToAlbum", json!([first, second])).await?;
+	let added =
+		change_objects(owner.cookie.clone(), "addObjectsToAlbum", json!([third, first])).await?;
+	assert_graphql_success(&added)?;
+	assert_eq!(
+		json_path(&added, &["data", "addObjectsToAlbum", "objects"])?,
+		&json!([{ "id": first }, { "id": second }, { "id": third }])
+	);
+
+	let reordered =
+		change_objects(owner.cookie.clone(), "reorderAlbum", json!([third, first, second])).await?;
+	assert_graphql_success(&reordered)?;
+	let album = json_path(&reordered, &["data", "reorderAlbum"])?;
+	assert_eq!(
+		json_path(album, &["objects"])?,
+		&json!([{ "id": third }, { "id": first }, { "id": second }])
+	);
+	assert_eq!(json_path(album, &["cover"])?, &json!({ "id": third }));
+
+	let incomplete =
+		change_objects(owner.cookie.clone(), "reorderAlbum", json!([third, first])).await?;
+	assert_graphql_error_contains(&incomplete, "must list each of the album's objects once")?;
+
+	let update = |cover: &Value| {
+		json!({ "input": {
+			"id": album_id,
+			"title": "Summer",
+			"coverObjectId": cover,
+			"publicity": "SELECTED_USERS",
+			"allowedUsers": [friend.email],
+		} })
+	};
+	let update_query = "mutation UpdateAlbum($input: UpdateAlbumInput!) {
+		updateAlbum(input: $input) { description cover { id } }
+	}";
+	let updated = app.graphql(update_query, update(second), Some(&owner.cookie)).await?.json()?;
+	assert_graphql_success(&updated)?;
+	assert_eq!(
+		json_path(&updated, &["data", "updateAlbum"])?,
+		&json!({ "description": "", "cover": { "id": second } })
+	);
+
+	// Others' objects cannot be added, nor can others change the album.
+	let foreign = app
+		.direct_upload_object(
+			&friend.cookie,
+			&DirectUploadRequest::svg(
+				&format!("album-foreign-{suffix}.svg"),
+				12.5,
+				-45.25,
+				b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+			),
+		)
+		.await?;
+	let foreign_id = json_path(&foreign, &["id"])?.clone();
+	let forbidden =
+		change_objects(owner.cookie.clone(), "addObjectsToAlbum", json!([foreign_id])).await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+	let forbidden =
+		change_objects(friend.cookie.clone(), "reorderAlbum", json!([first, second, third]))
+			.await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+
+	// The album shares its objects with the selected users only.
+	let album_query = "query Album($id: ID!) { album(id: $id) { title objects { id } } }";
+	let shared =
+		app.graphql(album_query, json!({ "id": album_id }), Some(&friend.cookie)).await?.json()?;
+	assert_graphql_success(&shared)?;
+	assert_eq!(
+		json_path(&shared, &["data", "album", "objects"])?,
+		&json!([{ "id": third }, { "id": first }, { "id": second }])
+	);
+	let hidden = app
+		.graphql(album_query, json!({ "id": album_id }), Some(&stranger.cookie))
+		.await?
+		.json()?;
+	assert_graphql_error_contains(&hidden, "Album not found")?;
+
+	let albums_query = "query Albums($ownerId: ID) { albums(ownerId: $ownerId) { id } }";
+	let owner_id = app
+		.graphql("query { me { id } }", json!({}), Some(&owner.cookie))
+		.await?
+		.json()?
+		.pointer("/data/me/id")
+		.cloned()
+		.context("me has no id")?;
+	for (user, expected) in [(&friend, json!([{ "id": album_id }])), (&stranger, json!([]))] {
+		let albums = app
+			.graphql(albums_query, json!({ "ownerId": owner_id }), Some(&user.cookie))
+			.await?
+			.json()?;
+		assert_graphql_success(&albums)?;
+		assert_eq!(json_path(&albums, &["data", "albums"])?, &expected);
+	}
+
+	// Removing the cover falls back to the first object.
+	let removed =
+		change_objects(owner.cookie.clone(), "removeObjectsFromAlbum", json!([second])).await?;
+	assert_graphql_success(&removed)?;
+	assert_eq!(
+		json_path(&removed, &["data", "removeObjectsFromAlbum"])?,
+		&json!({ "objects": [{ "id": third }, { "id": first }], "cover": { "id": third } })
+	);
+
+	let deleted = app
+		.graphql(
+			"mutation DeleteAlbum($id: ID!) { deleteAlbum(id: $id) }",
+			json!({ "id": album_id }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&deleted)?;
+	let gone =
+		app.graphql(album_query, json!({ "id": album_id }), Some(&owner.cookie)).await?.json()?;
+	assert_graphql_error_contains(&gone, "Album not found")?;
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn tags_filter_objects_and_autocomplete_by_prefix() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+
+	let suffix = unique_suffix()?;
+	let mut ids = Vec::new();
+	for label in ["beach", "city"] {
+		let object_name = format!("tagged-{label}-{suffix}.svg");
+		let upload = DirectUploadRequest::svg(
+			&object_name,
+			12.5,
+			-45.25,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		);
+		let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+		ids.push(json_path(&object, &["id"])?.clone());
+	}
+	let [beach, city] = ids.as_slice() else {
+		anyhow::bail!("expected two uploaded objects");
+	};
+
+	let change_tags = |cookie: String, mutation: &'static str, ids: Value, tags: Value| {
+		let app = &app;
+		async move {
+			let query = format!(
+				"mutation Change($ids: [ID!]!, $tags: [String!]!) {{
+					{mutation}(ids: $ids, tags: $tags) {{ id tags }}
+				}}"
+			);
+			app.graphql(&query, json!({ "ids": ids, "tags": tags }), Some(&cookie)).await?.json()
+		}
+	};
+
+	// Tags are stored normalized and listed alphabetically.
+	let added = change_tags(
+		owner.cookie.clone(),
+		"addTags",
+		json!([beach, city]),
+		json!(["  Summer  Holiday ", "summer holiday"]),
+	)
+	.await?;
+	assert_graphql_success(&added)?;
+	let added =
+		change_tags(owner.cookie.clone(), "addTags", json!([beach]), json!(["Sea"])).await?;
+	assert_graphql_success(&added)?;
+	assert_eq!(
+		json_path(&added, &["data", "addTags"])?,
+		&json!([{ "id": beach, "tags": ["sea", "summer holiday"] }])
+	);
+
+	let invalid =
+		change_tags(owner.cookie.clone(), "addTags", json!([beach]), json!(["sea, sand"])).await?;
+	assert_graphql_error_contains(&invalid, "Tags must not contain commas")?;
+	let forbidden =
+		change_tags(other.cookie.clone(), "addTags", json!([beach]), json!(["mine"])).await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+
+	// Only objects with every listed tag match the filter.
+	let tagged_edges = |tags: Value| {
+		let app = &app;
+		let cookie = owner.cookie.clone();
+		async move {
+			let page = app
+				.graphql(
+					"query Tagged($filter: S3ObjectFilter!) {
+						s3ObjectsConnection(first: 10, filter: $filter) { edges { node { id } } }
+					}",
+					json!({ "filter": { "tags": tags } }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&page)?;
+			anyhow::Ok(json_path(&page, &["data", "s3ObjectsConnection", "edges"])?.clone())
+		}
+	};
+	assert_eq!(
+		tagged_edges(json!(["Summer Holiday", "sea"])).await?,
+		json!([{ "node": { "id": beach } }])
+	);
+	assert_eq!(tagged_edges(json!(["summer holiday"])).await?.as_array().map(Vec::len), Some(2));
+
+	// Suggestions start with the prefix, most used first.
+	let tags_query = "query Tags($prefix: String!) { tags(prefix: $prefix) { name objectCount } }";
+	let suggested =
+		app.graphql(tags_query, json!({ "prefix": "S" }), Some(&owner.cookie)).await?.json()?;
+	assert_graphql_success(&suggested)?;
+	assert_eq!(
+		json_path(&suggested, &["data", "tags"])?,
+		&json!([
+			{ "name": "summer holiday", "objectCount": 2 },
+			{ "name": "sea", "objectCount": 1 },
+		])
+	);
+	let others =
+		app.graphql(tags_query, json!({ "prefix": "s" }), Some(&other.cookie)).await?.json()?;
+	assert_graphql_success(&others)?;
+	assert_eq!(json_path(&others, &["data", "tags"])?, &json!([]));
+
+	let removed = change_tags(
+		owner.cookie.clone(),
+		"removeTags",
+		json!([beach, city]),
+		json!(["Summer Holiday"]),
+	)
+	.await?;
+	assert_graphql_success(&removed)?;
+	let removed = json_path(&removed, &["data", "removeTags"])?
+		.as_array()
+		.context("removeTags is not an array")?;
+	assert_eq!(removed.len(), 2);
+	assert!(removed.contains(&json!({ "id": beach, "tags": ["sea"] })));
+	assert!(removed.contains(&json!({ "id": city, "tags": [] })));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn vector_tiles_carry_the_callers_objects() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("tile-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+	app.direct_upload_object(&user.cookie, &upload).await?;
+
+	let tile = |path: &str| {
+		Request::builder()
+			.method("GET")
+			.uri(path)
+			.header(header::COOKIE, &user.cookie)
+			.body(Body::empty())
+	};
+	// The whole world is one tile at zoom 0.
+	let response = app.request(tile("/tiles/0/0/0.mvt")?).await?;
+	assert_eq!(response.status, StatusCode::OK);
+	assert_eq!(
+		response.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()),
+		Some("application/vnd.mapbox-vector-tile")
+	);
+	// Layer names and attribute keys are stored as plain strings.
+	for expected in [&b"objects"[..], b"content_type_family", b"made_on"] {
+		assert!(
+			response.body.windows(expected.len()).any(|window| window == expected),
+			"the tile is missing {:?}",
+			String::from_utf8_lossy(expected)
+		);
+	}
+
+	let cached = app.request(tile("/tiles/0/0/0.mvt")?).await?;
+	assert_eq!(cached.body, response.body);
+
+	for path in ["/tiles/1/2/0.mvt", "/tiles/0/0/0.png"] {
+		assert_eq!(app.request(tile(path)?).await?.status, StatusCode::BAD_REQUEST);
+	}
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn partial_upload_coordinates_do_not_leave_side_effects() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+
+	for (label, location, expected_error) in [
+		("latitude-only", json!({ "latitude": 12.5 }), "longitude"),
+		("longitude-only", json!({ "longitude": -45.25 }), "latitude"),
+	] {
+		let object_name = format!("partial-{label}-upload-{}.svg", unique_suffix()?);
+		let upload = DirectUploadRequest::svg_without_location(
+			&object_name,
+			b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+		)
+		.with_location(Some(location));
+
+		let response = app.create_object_upload_session(Some(&user.cookie), &upload).await?;
+
+		assert_eq!(response.status, StatusCode::OK);
+		assert_graphql_error_contains(&response.json()?, expected_error)?;
+		assert_eq!(app.object_count(&object_name).await?, 0);
+	}
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn invalid_upload_timestamp_does_not_leave_side_effects() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("invalid-timestamp-upload-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	)
+	.with_made_on("not-a-timestamp");
+
+	let response = app.create_object_upload_session(Some(&user.cookie), &upload).await?;
+
+	assert_eq!(response.status, StatusCode::OK);
+	assert_graphql_error_contains(&response.json()?, "Invalid timestamp format")?;
+	assert_eq!(app.object_count(&object_name).await?, 0);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn duplicate_upload_name_does_not_overwrite_existing_object() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("duplicate-upload-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+
+	app.direct_upload_object(&user.cookie, &upload).await?;
+	assert_eq!(app.object_count(&object_name).await?, 1);
+	assert_eq!(app.object_content_type(&object_name).await?, "image/svg+xml");
+	let storage_key = app.object_storage_key(&object_name).await?;
+	assert_eq!(app.state.storage.object_content_type(&storage_key).await?, "image/svg+xml");
+
+	let duplicate = DirectUploadRequest::svg(&object_name, 12.5, -45.25, b"not really a jpeg")
+		.with_content_type("image/jpeg");
+	let duplicate_upload = app.create_object_upload_session(Some(&user.cookie), &duplicate).await?;
+
+	assert_eq!(duplicate_upload.status, StatusCode::OK);
+	assert_graphql_error_contains(&duplicate_upload.json()?, "You already have an object named")?;
+	assert_eq!(app.object_count(&object_name).await?, 1);
+	assert_eq!(app.object_storage_key(&object_name).await?, storage_key);
+	assert_eq!(app.object_content_type(&object_name).await?, "image/svg+xml");
+	assert_eq!(app.state.storage.object_content_type(&storage_key).await?, "image/svg+xml");
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn object_names_are_unique_per_owner() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let first = register_and_login(&app).await?;
+	let second = register_and_login(&app).await?;
+	let object_name = format!("IMG_0001-{}.svg", unique_suffix()?);
+
+	let mut ids = Vec::new();
+	for (user, contents) in [
+		(&first, b"<svg xmlns=\"http://www.w3.org/2000/svg\" />".as_slice()),
+		(&second, b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>".as_slice()),
+	] {
+		let upload = DirectUploadRequest::svg(&object_name, 12.5, -45.25, contents);
+		let object = app.direct_upload_object(&user.cookie, &upload).await?;
+		ids.push(json_path(&object, &["id"])?.clone());
+	}
+	let [first_id, second_id] = ids.as_slice() else {
+		anyhow::bail!("expected two uploaded objects");
+	};
+	assert_ne!(first_id, second_id);
+	assert_eq!(app.object_count(&object_name).await?, 2);
+
+	let by_name = |cookie: String, owner_id: Value| {
+		let app = &app;
+		let object_name = object_name.clone();
+		async move {
+			app.graphql(
+				"query ByName($name: String!, $ownerId: ID) {
+					s3ObjectByName(name: $name, ownerId: $ownerId) { id }
+				}",
+				json!({ "name": object_name, "ownerId": owner_id }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+
+	// Without an owner, the name is looked up among the caller's objects.
+	for (user, id) in [(&first, first_id), (&second, second_id)] {
+		let found = by_name(user.cookie.clone(), Value::Null).await?;
+		assert_graphql_success(&found)?;
+		assert_eq!(json_path(&found, &["data", "s3ObjectByName", "id"])?, id);
+	}
+
+	let me = app.graphql("query { me { id } }", json!({}), Some(&first.cookie)).await?.json()?;
+	assert_graphql_success(&me)?;
+	let first_owner_id = json_path(&me, &["data", "me", "id"])?.clone();
+	let found = by_name(first.cookie.clone(), first_owner_id.clone()).await?;
+	assert_graphql_success(&found)?;
+	assert_eq!(json_path(&found, &["data", "s3ObjectByName", "id"])?, first_id);
+	let forbidden = by_name(second.cookie.clone(), first_owner_id).await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+
+	let duplicate = DirectUploadRequest::svg(&object_name, 12.5, -45.25, b"<svg />");
+	let duplicate_upload =
+		app.create_object_upload_session(Some(&first.cookie), &duplicate).await?;
+	assert_eq!(duplicate_upload.status, StatusCode::OK);
+	assert_graphql_error_contains(&duplicate_upload.json()?, "You already have an object named")?;
+	assert_eq!(app.object_count(&object_name).await?, 2);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn deleted_objects_can_be_restored_from_the_trash_until_it_expires() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	let object_name = format!("trashed-{}.svg", unique_suffix()?);
+
+	let upload = |contents: &'static [u8]| {
+		let app = &app;
+		let cookie = owner.cookie.clone();
+		let object_name = object_name.clone();
+		async move {
+			let upload = DirectUploadRequest::svg(&object_name, 12.5, -45.25, contents);
+			let object = app.direct_upload_object(&cookie, &upload).await?;
+			anyhow::Ok(json_path(&object, &["id"])?.clone())
+		}
+	};
+	let ids_mutation = |mutation: &'static str, cookie: String, id: Value| {
+		let app = &app;
+		async move {
+			app.graphql(
+				&format!("mutation Ids($ids: [ID!]!) {{ {mutation}(ids: $ids) {{ id }} }}"),
+				json!({ "ids": [id] }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	let trashed_ids = |cookie: String| {
+		let app = &app;
+		async move {
+			let trashed = app
+				.graphql(
+					"query Trash { trashedObjects { object { id } trashedAt purgeAt } }",
+					json!({}),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&trashed)?;
+			json_path(&trashed, &["data", "trashedObjects"])?
+				.as_array()
+				.context("trashedObjects response is not an array")?
+				.iter()
+				.map(|trashed| json_path(trashed, &["object", "id"]).cloned())
+				.collect::<anyhow::Result<Vec<_>>>()
+		}
+	};
+
+	let first_id = upload(b"<svg xmlns=\"http://www.w3.org/2000/svg\" />".as_slice()).await?;
+	let deleted = ids_mutation("deleteS3Objects", owner.cookie.clone(), first_id.clone()).await?;
+	assert_graphql_success(&deleted)?;
+	assert_eq!(trashed_ids(owner.cookie.clone()).await?, vec![first_id.clone()]);
+	assert!(trashed_ids(other.cookie.clone()).await?.is_empty());
+
+	// A trashed object gives up its name, so restoring it waits until the
+	// name is free again.
+	let second_id = upload(b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>".as_slice()).await?;
+	let conflict = ids_mutation("restoreS3Objects", owner.cookie.clone(), first_id.clone()).await?;
+	assert_graphql_error_contains(&conflict, "You already have an object named")?;
+	let deleted = ids_mutation("deleteS3Objects", owner.cookie.clone(), second_id.clone()).await?;
+	assert_graphql_success(&deleted)?;
+
+	let forbidden =
+		ids_mutation("restoreS3Objects", other.cookie.clone(), first_id.clone()).await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+	let restored = ids_mutation("restoreS3Objects", owner.cookie.clone(), first_id.clone()).await?;
+	assert_graphql_success(&restored)?;
+	assert_eq!(
+		json_path(&restored, &["data", "restoreS3Objects"])?.get(0),
+		Some(&json!({ "id": first_id }))
+	);
+	assert_eq!(trashed_ids(owner.cookie.clone()).await?, vec![second_id.clone()]);
+
+	// Emptying another user's trash leaves this one alone, and maintenance
+	// leaves the object until the retention window ends.
+	let empty_trash = app
+		.graphql("mutation EmptyTrash { emptyTrash { id } }", json!({}), Some(&other.cookie))
+		.await?
+		.json()?;
+	assert_graphql_success(&empty_trash)?;
+	let config = ObjectLifecycleConfig::default();
+	app.run_object_lifecycle_maintenance(config.clone()).await?;
+	assert_eq!(trashed_ids(owner.cookie.clone()).await?, vec![second_id.clone()]);
+	assert_eq!(app.object_count(&object_name).await?, 2);
+
+	let second_id = second_id.as_str().context("object id is not a string")?;
+	app.expire_trashed_object(second_id, config.trash_retention_seconds).await?;
+	app.run_object_lifecycle_maintenance(config).await?;
+	assert!(trashed_ids(owner.cookie.clone()).await?.is_empty());
+	assert_eq!(app.object_count(&object_name).await?, 1);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn share_links_open_for_anyone_until_revoked_expired_or_used_up() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let other = register_and_login(&app).await?;
+	let object_name = format!("shared-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+	let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+	let object_id = json_path(&object, &["id"])?.clone();
+
+	// Shared with a user and a group, neither of which a link opener should see.
+	let group = app
+		.graphql(
+			"mutation Create($name: String!) { createUserGroup(name: $name) { id } }",
+			json!({ "name": "Friends" }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&group)?;
+	let group_id = json_path(&group, &["data", "createUserGroup", "id"])?.clone();
+	let restricted = app
+		.graphql(
+			"mutation Update($input: UpdateS3ObjectInput!) {
+				updateS3Object(input: $input) { allowedUsers allowedGroups }
+			}",
+			json!({ "input": {
+				"id": object_id,
+				"name": object_name,
+				"publicity": "SELECTED_USERS",
+				"allowedUsers": [other.email],
+				"allowedGroups": [group_id],
+			} }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&restricted)?;
+
+	let create_link = |cookie: String, input: Value| {
+		let app = &app;
+		async move {
+			app.graphql(
+				"mutation Create($input: CreateShareLinkInput!) {
+					createShareLink(input: $input) { token link { id hasPassword maxViews } }
+				}",
+				json!({ "input": input }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	let open_link = |token: Value, password: Option<&'static str>| {
+		let app = &app;
+		async move {
+			app.graphql(
+				"mutation Open($token: String!, $password: String) {
+					openShareLink(token: $token, password: $password) {
+						passwordRequired title objects { id url allowedUsers allowedGroups }
+					}
+				}",
+				json!({ "token": token, "password": password }),
+				None,
+			)
+			.await?
+			.json()
+		}
+	};
+	let revoke_link = |cookie: String, id: Value| {
+		let app = &app;
+		async move {
+			app.graphql(
+				"mutation Revoke($id: ID!) { revokeShareLink(id: $id) { active revokedAt } }",
+				json!({ "id": id }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+
+	let forbidden = create_link(other.cookie.clone(), json!({ "objectId": object_id })).await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+	let past = create_link(
+		owner.cookie.clone(),
+		json!({ "objectId": object_id, "expiresAt": "2000-01-01T00:00:00Z" }),
+	)
+	.await?;
+	assert_graphql_error_contains(&past, "must be in the future")?;
+
+	let created = create_link(
+		owner.cookie.clone(),
+		json!({ "objectId": object_id, "password": "open sesame", "maxViews": 2 }),
+	)
+	.await?;
+	assert_graphql_success(&created)?;
+	let token = json_path(&created, &["data", "createShareLink", "token"])?.clone();
+	assert_eq!(
+		json_path(&created, &["data", "createShareLink", "link", "hasPassword"])?,
+		&json!(true)
+	);
+
+	// Asking for the password does not count as a view.
+	for _ in 0 .. 3 {
+		let locked = open_link(token.clone(), None).await?;
+		assert_graphql_success(&locked)?;
+		assert_eq!(
+			json_path(&locked, &["data", "openShareLink"])?,
+			&json!({ "passwordRequired": true, "title": "", "objects": [] })
+		);
+	}
+	let wrong = open_link(token.clone(), Some("open barley")).await?;
+	assert_graphql_error_contains(&wrong, "Incorrect share link password")?;
+
+	for _ in 0 .. 2 {
+		let opened = open_link(token.clone(), Some("open sesame")).await?;
+		assert_graphql_success(&opened)?;
+		let objects = json_path(&opened, &["data", "openShareLink", "objects"])?
+			.as_array()
+			.context("openShareLink objects is not an array")?;
+		assert_eq!(objects.len(), 1);
+		let shared = objects.first().context("openShareLink returned no object")?;
+		assert_eq!(json_path(shared, &["id"])?, &object_id);
+		assert!(json_path(shared, &["url"])?.is_string());
+		assert_eq!(json_path(shared, &["allowedUsers"])?, &json!([]));
+		assert_eq!(json_path(shared, &["allowedGroups"])?, &json!([]));
+	}
+	let used_up = open_link(token.clone(), Some("open sesame")).await?;
+	assert_graphql_error_contains(&used_up, "Invalid or expired share link")?;
+
+	let links = app
+		.graphql(
+			"query Links($objectId: ID) {
+				shareLinks(objectId: $objectId) { viewCount maxViews active }
+			}",
+			json!({ "objectId": object_id }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&links)?;
+	assert_eq!(
+		json_path(&links, &["data", "shareLinks"])?,
+		&json!([{ "viewCount": 2, "maxViews": 2, "active": false }])
+	);
+
+	// Too many wrong passwords in a row lock a link, even against the right one.
+	let guarded = create_link(
+		owner.cookie.clone(),
+		json!({ "objectId": object_id, "password": "open sesame" }),
+	)
+	.await?;
+	assert_graphql_success(&guarded)?;
+	let guarded_token = json_path(&guarded, &["data", "createShareLink", "token"])?.clone();
+	let wrong = open_link(guarded_token.clone(), Some("open barley")).await?;
+	assert_graphql_error_contains(&wrong, "Incorrect share link password")?;
+	let opened = open_link(guarded_token.clone(), Some("open sesame")).await?;
+	assert_graphql_success(&opened)?;
+	for _ in 0 .. SHARE_LINK_MAX_PASSWORD_ATTEMPTS {
+		let wrong = open_link(guarded_token.clone(), Some("open barley")).await?;
+		assert_graphql_error_contains(&wrong, "Incorrect share link password")?;
+	}
+	let locked = open_link(guarded_token.clone(), Some("open sesame")).await?;
+	assert_graphql_error_contains(&locked, "Too many incorrect share link passwords")?;
+	let asked = open_link(guarded_token.clone(), None).await?;
+	assert_eq!(json_path(&asked, &["data", "openShareLink", "passwordRequired"])?, &json!(true));
+
+	// Once the lock expires the count starts over, so one wrong guess does not
+	// lock the link again.
+	let guarded_id = json_path(&guarded, &["data", "createShareLink", "link", "id"])?
+		.as_str()
+		.context("share link id is not a string")?;
+	app.expire_share_link_lock(guarded_id).await?;
+	let wrong = open_link(guarded_token.clone(), Some("open barley")).await?;
+	assert_graphql_error_contains(&wrong, "Incorrect share link password")?;
+	let opened = open_link(guarded_token, Some("open sesame")).await?;
+	assert_graphql_success(&opened)?;
+
+	let created = create_link(owner.cookie.clone(), json!({ "objectId": object_id })).await?;
+	assert_graphql_success(&created)?;
+	let token = json_path(&created, &["data", "createShareLink", "token"])?.clone();
+	let link_id = json_path(&created, &["data", "createShareLink", "link", "id"])?.clone();
+	let opened = open_link(token.clone(), None).await?;
+	assert_graphql_success(&opened)?;
+	assert_eq!(json_path(&opened, &["data", "openShareLink", "passwordRequired"])?, &json!(false));
+
+	let forbidden = revoke_link(other.cookie.clone(), link_id.clone()).await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+	let revoked = revoke_link(owner.cookie.clone(), link_id).await?;
+	assert_graphql_success(&revoked)?;
+	assert_eq!(json_path(&revoked, &["data", "revokeShareLink", "active"])?, &json!(false));
+	let closed = open_link(token, None).await?;
+	assert_graphql_error_contains(&closed, "Invalid or expired share link")?;
+	let unknown = open_link(json!("not-a-share-token"), None).await?;
+	assert_graphql_error_contains(&unknown, "Invalid or expired share link")?;
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn groups_share_objects_and_albums_with_their_members() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let member = register_and_login(&app).await?;
+	let stranger = register_and_login(&app).await?;
+	let suffix = unique_suffix()?;
+	let object_name = format!("grouped-{suffix}.svg");
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+	let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+	let object_id = json_path(&object, &["id"])?.clone();
+
+	let group_mutation = |cookie: String, mutation: &'static str, variables: Value| {
+		let app = &app;
+		async move { app.graphql(mutation, variables, Some(&cookie)).await?.json() }
+	};
+	let create_group = "mutation Create($name: String!) {
+		createUserGroup(name: $name) { id name members }
+	}";
+	let add_members = "mutation Add($id: ID!, $emails: [String!]!) {
+		addUserGroupMembers(id: $id, emails: $emails) { members }
+	}";
+	let remove_members = "mutation Remove($id: ID!, $emails: [String!]!) {
+		removeUserGroupMembers(id: $id, emails: $emails) { members }
+	}";
+
+	let created =
+		group_mutation(owner.cookie.clone(), create_group, json!({ "name": " Family " })).await?;
+	assert_graphql_success(&created)?;
+	let group = json_path(&created, &["data", "createUserGroup"])?;
+	assert_eq!(json_path(group, &["name"])?, &json!("Family"));
+	assert_eq!(json_path(group, &["members"])?, &json!([]));
+	let group_id = json_path(group, &["id"])?.clone();
+	let duplicate =
+		group_mutation(owner.cookie.clone(), create_group, json!({ "name": "Family" })).await?;
+	assert_graphql_error_contains(&duplicate, "You already have a group named 'Family'")?;
+
+	// Unknown emails are reported, and none of the others are added.
+	let unknown = format!("nobody-{suffix}@example.com");
+	let partly_unknown = group_mutation(
+		owner.cookie.clone(),
+		add_members,
+		json!({ "id": group_id, "emails": [member.email, unknown] }),
+	)
+	.await?;
+	assert_graphql_error_contains(&partly_unknown, &unknown)?;
+	let added = group_mutation(
+		owner.cookie.clone(),
+		add_members,
+		json!({ "id": group_id, "emails": [member.email] }),
+	)
+	.await?;
+	assert_graphql_success(&added)?;
+	assert_eq!(
+		json_path(&added, &["data", "addUserGroupMembers", "members"])?,
+		&json!([member.email])
+	);
+	let forbidden = group_mutation(
+		stranger.cookie.clone(),
+		add_members,
+		json!({ "id": group_id, "emails": [stranger.email] }),
+	)
+	.await?;
+	assert_graphql_error_contains(&forbidden, "Forbidden")?;
+
+	let update_object = |allowed_groups: Value| {
+		let app = &app;
+		let cookie = &owner.cookie;
+		let variables = json!({ "input": {
+			"id": object_id,
+			"name": object_name,
+			"publicity": "SELECTED_USERS",
+			"allowedGroups": allowed_groups,
+		} });
+		async move {
+			app.graphql(
+				"mutation Update($input: UpdateS3ObjectInput!) {
+					updateS3Object(input: $input) { allowedUsers allowedGroups }
+				}",
+				variables,
+				Some(cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	let sees_object = |cookie: String| {
+		let app = &app;
+		let object_id = object_id.clone();
+		async move {
+			let objects = app
+				.graphql("query { s3Objects { id } }", json!({}), Some(&cookie))
+				.await?
+				.json()?;
+			assert_graphql_success(&objects)?;
+			Ok::<_, anyhow::Error>(
+				json_path(&objects, &["data", "s3Objects"])?
+					.as_array()
+					.context("s3Objects is not a list")?
+					.iter()
+					.any(|object| object.get("id") == Some(&object_id)),
+			)
+		}
+	};
+
+	// Only the owner's own groups can be shared with.
+	let strangers_group = group_mutation(
+		stranger.cookie.clone(),
+		create_group,
+		json!({ "name": format!("Club {suffix}") }),
+	)
+	.await?;
+	let strangers_group_id =
+		json_path(&strangers_group, &["data", "createUserGroup", "id"])?.clone();
+	let foreign = update_object(json!([group_id, strangers_group_id])).await?;
+	assert_graphql_error_contains(&foreign, "Only your own groups can be shared with")?;
+
+	let shared = update_object(json!([group_id])).await?;
+	assert_graphql_success(&shared)?;
+	assert_eq!(
+		json_path(&shared, &["data", "updateS3Object"])?,
+		&json!({ "allowedUsers": [], "allowedGroups": [group_id] })
+	);
+	assert!(sees_object(member.cookie.clone()).await?);
+	assert!(!sees_object(stranger.cookie.clone()).await?);
+
+	// Albums shared with a group are visible to its members.
+	let album = app
+		.graphql(
+			"mutation CreateAlbum($input: CreateAlbumInput!) {
+				createAlbum(input: $input) { id allowedGroups }
+			}",
+			json!({ "input": {
+				"title": "Holidays",
+				"publicity": "SELECTED_USERS",
+				"allowedGroups": [group_id],
+			} }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&album)?;
+	assert_eq!(json_path(&album, &["data", "createAlbum", "allowedGroups"])?, &json!([group_id]));
+	let album_id = json_path(&album, &["data", "createAlbum", "id"])?.clone();
+	let album_query = "query Album($id: ID!) { album(id: $id) { id } }";
+	let album_for = |cookie: String| {
+		let app = &app;
+		let album_id = album_id.clone();
+		async move { app.graphql(album_query, json!({ "id": album_id }), Some(&cookie)).await?.json() }
+	};
+	assert_graphql_success(&album_for(member.cookie.clone()).await?)?;
+	assert_graphql_error_contains(&album_for(stranger.cookie.clone()).await?, "Album not found")?;
+
+	// Leaving a group stops sharing through it.
+	let removed = group_mutation(
+		owner.cookie.clone(),
+		remove_members,
+		json!({ "id": group_id, "emails": [member.email] }),
+	)
+	.await?;
+	assert_graphql_success(&removed)?;
+	assert_eq!(json_path(&removed, &["data", "removeUserGroupMembers", "members"])?, &json!([]));
+	assert!(!sees_object(member.cookie.clone()).await?);
+	assert_graphql_error_contains(&album_for(member.cookie.clone()).await?, "Album not found")?;
+
+	let groups = app
+		.graphql("query { userGroups { id name } }", json!({}), Some(&owner.cookie))
+		.await?
+		.json()?;
+	assert_graphql_success(&groups)?;
+	assert_eq!(
+		json_path(&groups, &["data", "userGroups"])?,
+		&json!([{ "id": group_id, "name": "Family" }])
+	);
+	let deleted = group_mutation(
+		owner.cookie.clone(),
+		"mutation Delete($id: ID!) { deleteUserGroup(id: $id) }",
+		json!({ "id": group_id }),
+	)
+	.await?;
+	assert_graphql_success(&deleted)?;
+	let kept = update_object(Value::Null).await?;
+	assert_graphql_success(&kept)?;
+	assert_eq!(json_path(&kept, &["data", "updateS3Object", "allowedGroups"])?, &json!([]));
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn other_people_see_locations_at_their_precision() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let owner = register_and_login(&app).await?;
+	let viewer = register_and_login(&app).await?;
+	let object_name = format!("coarse-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		51.50735,
+		-0.12776,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
+	let object = app.direct_upload_object(&owner.cookie, &upload).await?;
+	let object_id = json_path(&object, &["id"])?.clone();
+	let object_number: i64 = object_id.as_str().context("object id is not a string")?.parse()?;
+
+	let updated = app
+		.graphql(
+			"mutation Update($input: UpdateS3ObjectInput!) {
+				updateS3Object(input: $input) { locationPrecision }
+			}",
+			json!({ "input": {
+				"id": object_id,
+				"name": object_name,
+				"location": { "latitude": 51.50735, "longitude": -0.12776 },
+				"publicity": "PUBLIC",
+				"locationPrecision": "APPROX_1KM",
+			} }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&updated)?;
+	assert_eq!(
+		json_path(&updated, &["data", "updateS3Object", "locationPrecision"])?,
+		&json!("APPROX_1KM")
+	);
+
+	let location_for = |cookie: String| {
+		let app = &app;
+		async move {
+			let object = app
+				.graphql(
+					"query Object($id: Int!) {
+						s3ObjectById(id: $id) { location { latitude longitude } }
+					}",
+					json!({ "id": object_number }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&object)?;
+			let location = json_path(&object, &["data", "s3ObjectById", "location"])?;
+			anyhow::Ok(
+				location
+					.get("latitude")
+					.and_then(Value::as_f64)
+					.zip(location.get("longitude").and_then(Value::as_f64)),
+			)
+		}
+	};
+	assert_eq!(location_for(owner.cookie.clone()).await?, Some((51.50735, -0.12776)));
+	let (latitude, longitude) =
+		location_for(viewer.cookie.clone()).await?.context("the viewer sees no location")?;
+	assert!((latitude - 51.505).abs() < 1e-9 && (longitude + 0.125).abs() < 1e-9);
+
+	// Boxes and radii match the coarsened location, not the exact one.
+	let found_in = |cookie: String, south: f64, west: f64, north: f64, east: f64| {
+		let app = &app;
+		let object_id = object_id.clone();
+		async move {
+			let objects = app
+				.graphql(
+					"query InBounds($south: Float!, $west: Float!, $north: Float!, $east: Float!) {
+						s3ObjectsInBounds(south: $south, west: $west, north: $north, east: $east) {
+							id
+						}
+					}",
+					json!({ "south": south, "west": west, "north": north, "east": east }),
+					Some(&cookie),
+				)
+				.await?
+				.json()?;
+			assert_graphql_success(&objects)?;
+			anyhow::Ok(
+				json_path(&objects, &["data", "s3ObjectsInBounds"])?
+					.as_array()
+					.context("s3ObjectsInBounds is not a list")?
+					.iter()
+					.any(|object| object.get("id") == Some(&object_id)),
+			)
+		}
+	};
+	assert!(found_in(owner.cookie.clone(), 51.507, -0.128, 51.508, -0.1275).await?);
+	assert!(!found_in(viewer.cookie.clone(), 51.507, -0.128, 51.508, -0.1275).await?);
+	assert!(found_in(viewer.cookie.clone(), 51.5049, -0.1251, 51.5051, -0.1249).await?);
+
+	// Objects that follow the owner's setting are hidden along with it.
+	let defaulted = app
+		.graphql(
+			"mutation Update($input: UpdateS3ObjectInput!) {
+				updateS3Object(input: $input) { locationPrecision }
+			}",
+			json!({ "input": {
+				"id": object_id,
+				"name": object_name,
+				"location": { "latitude": 51.50735, "longitude": -0.12776 },
+				"publicity": "PUBLIC",
+				"locationPrecision": "DEFAULT",
+			} }),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&defaulted)?;
+	let hidden = app
+		.graphql(
+			"mutation {
+				updateUserLocationPrecision(locationPrecision: HIDDEN) { locationPrecision }
+			}",
+			json!({}),
+			Some(&owner.cookie),
+		)
+		.await?
+		.json()?;
+	assert_graphql_success(&hidden)?;
+	assert_eq!(
+		json_path(&hidden, &["data", "updateUserLocationPrecision", "locationPrecision"])?,
+		&json!("HIDDEN")
+	);
+	assert_eq!(location_for(viewer.cookie.clone()).await?, None);
+	assert!(!found_in(viewer.cookie.clone(), 51.0, -1.0, 52.0, 1.0).await?);
+	assert!(found_in(owner.cookie.clone(), 51.0, -1.0, 52.0, 1.0).await?);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn sessions_can_be_listed_and_revoked() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let laptop = log_in(&app, &user.email, TEST_USER_PASSWORD).await?;
+
+	let sessions = app
+		.graphql("query { mySessions { id current expiresAt } }", json!({}), Some(&laptop))
+		.await?
+		.json()?;
+	assert_graphql_success(&sessions)?;
+	let sessions = json_path(&sessions, &["data", "mySessions"])?
+		.as_array()
+		.context("mySessions response is not an array")?
+		.clone();
+	assert_eq!(sessions.len(), 2);
+	let current = sessions
+		.iter()
+		.filter(|session| session.get("current").and_then(Value::as_bool) == Some(true))
+		.collect::<Vec<_>>();
+	assert_eq!(current.len(), 1);
+	let other_id = sessions
+		.iter()
+		.find(|session| session.get("current").and_then(Value::as_bool) == Some(false))
+		.and_then(|session| session.get("id"))
+		.and_then(Value::as_str)
+		.context("mySessions is missing the other session")?
+		.to_string();
+
+	// A session is revoked by id, and only by its owner.
+	let stranger = register_and_login(&app).await?;
+	let revoke = |cookie: String, id: String| {
+		let app = &app;
+		async move {
+			app.graphql(
+				"mutation Revoke($id: ID!) { revokeSession(id: $id) }",
+				json!({ "id": id }),
+				Some(&cookie),
+			)
+			.await?
+			.json()
+		}
+	};
+	assert_graphql_error_contains(
+		&revoke(stranger.cookie.clone(), other_id.clone()).await?,
+		"Session not found",
+	)?;
+	assert_eq!(signed_in_email(&app, &user.cookie).await?, Some(user.email.clone()));
+	assert_graphql_success(&revoke(laptop.clone(), other_id).await?)?;
+	assert_eq!(signed_in_email(&app, &user.cookie).await?, None);
+	assert_eq!(signed_in_email(&app, &laptop).await?, Some(user.email.clone()));
+
+	let phone = log_in(&app, &user.email, TEST_USER_PASSWORD).await?;
+	let revoke_others = app
+		.graphql("mutation { revokeAllOtherSessions }", json!({}), Some(&laptop))
+		.await?
+		.json()?;
+	assert_graphql_success(&revoke_others)?;
+	assert_eq!(signed_in_email(&app, &phone).await?, None);
+	assert_eq!(signed_in_email(&app, &laptop).await?, Some(user.email.clone()));
+
+	// Changing the password signs out every session, but gives the client that
+	// changed it a new one.
+	let phone = log_in(&app, &user.email, TEST_USER_PASSWORD).await?;
+	let new_password = "memory-map-new-test-password";
+	let change = app
+		.graphql(
+			"mutation Change($old: String!, $new: String!) {
+				changePassword(oldPassword: $old, newPassword: $new)
+			}",
+			json!({ "old": TEST_USER_PASSWORD, "new": new_password }),
+			Some(&laptop),
+		)
+		.await?;
+	assert_graphql_success(&change.json()?)?;
+	let laptop_after_change = auth_cookie(&change.headers)?;
+	assert_eq!(signed_in_email(&app, &phone).await?, None);
+	assert_eq!(signed_in_email(&app, &laptop).await?, None);
+	assert_eq!(signed_in_email(&app, &laptop_after_change).await?, Some(user.email.clone()));
+
+	// Logging out ends the session even if the client keeps its cookie.
+	let logout = app.graphql("mutation { logout }", json!({}), Some(&laptop_after_change)).await?;
+	assert_graphql_success(&logout.json()?)?;
+	assert_eq!(signed_in_email(&app, &laptop_after_change).await?, None);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn stale_pending_upload_cleanup_removes_blob_metadata_and_releases_name() -> anyhow::Result<()>
+{
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("stale-pending-upload-{}.svg", unique_suffix()?);
+	let storage_key = format!("objects/stale-pending-upload-{}", unique_suffix()?);
+	let body = b"<svg xmlns=\"http://www.w3.org/2000/svg\" />";
+
+	app.state
+		.storage
+		.upload_object(&storage_key, ByteStream::from_static(body), "image/svg+xml")
+		.await?;
+
+	let client = app.state.pool.get().await?;
+	let user_id: i64 =
+		client.query_one("SELECT id FROM users WHERE email = $1", &[&user.email]).await?.get(0);
+	client
+		.execute(
+			"INSERT INTO objects (
+				name,
+				storage_key,
+				content_type,
+				storage_state,
+				storage_state_updated_at,
+				user_id,
+				publicity
+			)
+			VALUES ($1, $2, 'image/svg+xml', 'pending_upload', now() - interval '2 hours', $3, 'default')",
+			&[&object_name, &storage_key, &user_id],
+		)
+		.await?;
+	drop(client);
+
+	let lifecycle_config = ObjectLifecycleConfig {
+		pending_upload_timeout_seconds: 1,
+		storage_deletion_retry_seconds: 1,
+		storage_deletion_lease_seconds: 30,
+		maintenance_interval_seconds: 1,
+		storage_deletion_batch_size: 1000,
+		storage_deletion_max_attempts: 10,
+		..ObjectLifecycleConfig::default()
+	}
+	.validated()?;
+	app.run_object_lifecycle_maintenance(lifecycle_config).await?;
+
+	assert_eq!(app.object_count(&object_name).await?, 0);
+	assert!(app.state.storage.object_content_type(&storage_key).await.is_err());
+
+	let upload = DirectUploadRequest::svg(&object_name, 12.5, -45.25, body);
+	app.direct_upload_object(&user.cookie, &upload).await?;
+	assert_eq!(app.object_count(&object_name).await?, 1);
+
+	Ok(())
+}
+
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn expired_incomplete_upload_session_cleanup_removes_metadata() -> anyhow::Result<()> {
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("expired-upload-session-{}.svg", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<svg xmlns=\"http://www.w3.org/2000/svg\" />",
+	);
 
 	let create = app.create_object_upload_session(Some(&user.cookie), &upload).await?;
 	assert_eq!(create.status, StatusCode::OK);
@@ -1219,6 +3936,52 @@ async fn completed_upload_metadata_mismatch_queues_completed_object_cleanup() ->
 	Ok(())
 }
 
+#[tokio::test]
+#[ignore = "requires the local PostgreSQL and RustFS service graph"]
+async fn completed_upload_with_mismatched_contents_is_rejected_and_cleaned_up() -> anyhow::Result<()>
+{
+	let Some(app) = TestApp::new().await? else {
+		return Ok(());
+	};
+	let user = register_and_login(&app).await?;
+	let object_name = format!("disguised-html-{}.png", unique_suffix()?);
+	let upload = DirectUploadRequest::svg(
+		&object_name,
+		12.5,
+		-45.25,
+		b"<!DOCTYPE html><html><script>alert(1)</script></html>",
+	)
+	.with_content_type("image/png");
+
+	let prepared = app.prepare_direct_upload(&user.cookie, &upload).await?;
+	let session = app.upload_session_storage(&prepared.object_id).await?;
+	let complete = app
+		.complete_object_upload(&user.cookie, &prepared.object_id, prepared.completed_parts)
+		.await?;
+	assert_eq!(complete.status, StatusCode::OK);
+	let complete = complete.json()?;
+	assert_graphql_error_contains(
+		&complete,
+		"declared as image/png but its contents are not a supported file type",
+	)?;
+	assert_eq!(
+		json_path(&complete, &["errors"])?
+			.get(0)
+			.and_then(|error| error.pointer("/extensions/code"))
+			.and_then(Value::as_str),
+		Some("VALIDATION")
+	);
+	assert_eq!(app.upload_session_count(&object_name).await?, 0);
+	assert_eq!(app.storage_deletion_outbox_count_for_key(&session.storage_key).await?, 1);
+
+	app.run_object_lifecycle_maintenance(ObjectLifecycleConfig::default()).await?;
+
+	assert_eq!(app.object_count(&object_name).await?, 0);
+	assert!(app.state.storage.object_content_type(&session.storage_key).await.is_err());
+
+	Ok(())
+}
+
 #[tokio::test]
 #[ignore = "requires the local PostgreSQL and RustFS service graph"]
 async fn object_storage_deletion_claims_respect_lease_and_retry() -> anyhow::Result<()> {
@@ -1351,6 +4114,10 @@ fn test_config() -> anyhow::Result<Config> {
 			),
 			enable_registration: true,
 			cookie_secure: None,
+			session_idle_timeout_seconds: backend::AuthConfig::default_session_idle_timeout_seconds(
+			),
+			session_absolute_timeout_seconds:
+				backend::AuthConfig::default_session_absolute_timeout_seconds(),
 		},
 		frontend: backend::FrontendConfig {
 			url: frontend_url.clone(),
@@ -1380,6 +4147,8 @@ fn test_config() -> anyhow::Result<Config> {
 		},
 		object_lifecycle: ObjectLifecycleConfig::default(),
 		email_outbox: EmailOutboxConfig::default(),
+		object_derivatives: ObjectDerivativeConfig::default(),
+		perceptual_hashes: PerceptualHashConfig::default(),
 	};
 	config.validated()
 }
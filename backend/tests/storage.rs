@@ -53,13 +53,13 @@ async fn storage_roundtrip_against_configured_service() -> anyhow::Result<()> {
 
 	assert_eq!(storage.object_content_type(&first_object).await?, "text/plain");
 
-	let url = storage.presigned_get_url(&first_object, None).await?;
+	let url = storage.presigned_get_url(&first_object, None, None).await?;
 	let body = reqwest::get(url).await?.error_for_status()?.bytes().await?;
 	assert_eq!(body.as_ref(), first_body);
 
 	// A signed response-content-disposition must round-trip through storage so the
 	// object resolver can force `attachment` for script-capable types (e.g. SVG).
-	let attachment_url = storage.presigned_get_url(&first_object, Some("attachment")).await?;
+	let attachment_url = storage.presigned_get_url(&first_object, Some("attachment"), None).await?;
 	let attachment_response = reqwest::get(attachment_url).await?.error_for_status()?;
 	assert_eq!(
 		attachment_response
@@ -69,6 +69,21 @@ async fn storage_roundtrip_against_configured_service() -> anyhow::Result<()> {
 		Some("attachment")
 	);
 
+	// Likewise a signed response-content-type, so uploads relabelled after
+	// content sniffing are served under their detected type.
+	let relabelled_url = storage.presigned_get_url(&first_object, None, Some("image/png")).await?;
+	let relabelled_response = reqwest::get(relabelled_url).await?.error_for_status()?;
+	assert_eq!(
+		relabelled_response
+			.headers()
+			.get(reqwest::header::CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok()),
+		Some("image/png")
+	);
+
+	assert_eq!(storage.get_object_range(&first_object, 7, 3).await?, b"map");
+	assert_eq!(storage.get_object_range(&second_object, 11, 100).await?, b"oo\n");
+
 	storage.delete_objects(&[first_object.clone(), second_object.clone()]).await?;
 	assert!(storage.object_content_type(&first_object).await.is_err());
 	assert!(storage.object_content_type(&second_object).await.is_err());
@@ -1,14 +1,23 @@
+pub mod audio_waveform;
 pub mod carousel;
+pub mod cluster_marker;
 pub mod edit_s3_object_form;
 pub mod file_upload;
 pub mod full_size_s3_object;
 pub mod gallery;
+pub mod group_picker;
 pub mod header;
-pub mod location_marker;
 pub mod location_markers;
+pub mod markdown;
+pub mod object_history;
 pub mod password_input;
 pub mod protected_route;
 pub mod s3_object;
 pub mod s3_object_table_row;
 pub mod s3_object_table_rows;
 pub mod s3_objects_table;
+pub mod search_box;
+pub mod session_list;
+pub mod share_links;
+pub mod tag_input;
+pub mod timeline_slider;
@@ -0,0 +1,97 @@
+use {
+	crate::graphql_queries::s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+	leptos::{
+		ev,
+		html,
+		prelude::*,
+	},
+};
+
+/// An audio player with a waveform scrubber drawn from the object's analysed
+/// peaks. Clicking the waveform seeks to that point, and the played portion is
+/// highlighted as playback moves.
+#[component]
+pub fn AudioWaveform(
+	#[prop(into)] s3_object: Signal<Option<S3Object>>,
+	#[prop(optional, into)] class: MaybeProp<String>,
+) -> impl IntoView {
+	let audio_ref = NodeRef::<html::Audio>::new();
+	let scrubber_ref = NodeRef::<html::Div>::new();
+	let progress = RwSignal::new(0.0);
+
+	let update_progress = move |_: ev::Event| {
+		if let Some(audio) = audio_ref.get() {
+			let duration = audio.duration();
+			if duration.is_finite() && duration > 0.0 {
+				progress.set(audio.current_time() / duration);
+			}
+		}
+	};
+	let seek = move |ev: ev::MouseEvent| {
+		let (Some(audio), Some(scrubber)) = (audio_ref.get(), scrubber_ref.get()) else {
+			return;
+		};
+		let bounds = scrubber.get_bounding_client_rect();
+		let duration = audio.duration();
+		if bounds.width() > 0.0 && duration.is_finite() {
+			let fraction =
+				((f64::from(ev.client_x()) - bounds.left()) / bounds.width()).clamp(0.0, 1.0);
+			audio.set_current_time(fraction * duration);
+			progress.set(fraction);
+		}
+	};
+
+	move || {
+		s3_object.get().map(|s3_object| {
+			let peaks = s3_object.waveform_peaks.clone().unwrap_or_default();
+			let count = peaks.len().max(1);
+			let bars = peaks
+				.into_iter()
+				.enumerate()
+				.map(|(index, peak)| {
+					let height = (peak.clamp(0.0, 1.0) * 100.0).max(1.0);
+					let played = move || (index as f64 + 0.5) / count as f64 <= progress.get();
+					view! {
+						<rect
+							x=index
+							y=(100.0 - height) / 2.0
+							width="0.8"
+							height=height
+							class=move || if played() { "fill-white" } else { "fill-gray-500" }
+						/>
+					}
+				})
+				.collect_view();
+			view! {
+				<div class=move || {
+					format!("grid gap-4 w-[min(90dvw,960px)] {}", class.get().unwrap_or_default())
+				}>
+					<div
+						class="relative h-160px cursor-pointer bg-[rgba(0,0,0,0.4)]"
+						node_ref=scrubber_ref
+						on:click=seek
+					>
+						<svg
+							class="w-full h-full"
+							viewBox=format!("0 0 {count} 100")
+							preserveAspectRatio="none"
+							aria-hidden="true"
+						>
+							{bars}
+						</svg>
+					</div>
+					<audio
+						aria-label=s3_object.name.clone()
+						class="w-full"
+						src=s3_object.url.clone()
+						node_ref=audio_ref
+						on:timeupdate=update_progress
+						on:seeked=update_progress
+						controls
+						autoplay
+					/>
+				</div>
+			}
+		})
+	}
+}
@@ -4,8 +4,13 @@ use {
 		ModularAdd,
 		ModularSubtract,
 		components::{
+			audio_waveform::AudioWaveform,
 			full_size_s3_object::FullSizeS3Object,
-			s3_object::S3Object as S3ObjectComponent,
+			markdown::Markdown,
+			s3_object::{
+				Rendition,
+				S3Object as S3ObjectComponent,
+			},
 		},
 		graphql_queries::s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
 	},
@@ -299,12 +304,57 @@ pub fn Carousel(
 	let metadata_content = move || {
 		s3_objects.with(|objects| {
 			objects.get(index.get()).and_then(|object| {
-				object.made_on.as_ref().map(|made_on| {
+				let made_on = object.made_on.as_ref().map(|made_on| {
 					let date = js_sys::Date::new(&JsValue::from_str(made_on.as_ref()));
 					date.to_locale_string("en-GB", &JsValue::UNDEFINED)
 						.as_string()
 						.unwrap_or(made_on.clone())
-				})
+				});
+				let duration = object.duration_seconds.map(format_duration);
+				let made_on = match (made_on, duration) {
+					(Some(made_on), Some(duration)) => Some(format!("{made_on} ({duration})")),
+					(made_on, duration) => made_on.or(duration),
+				};
+				let tags = object.media_tags.as_ref().map(|tags| {
+					[tags.title.as_deref(), tags.artist.as_deref(), tags.recorded_date.as_deref()]
+						.into_iter()
+						.flatten()
+						.collect::<Vec<_>>()
+						.join(" - ")
+				});
+				match (tags, made_on) {
+					(Some(tags), Some(made_on)) => Some(format!("{tags} | {made_on}")),
+					(tags, made_on) => tags.or(made_on),
+				}
+			})
+		})
+	};
+	// The current object's title, description and note, each `None` when
+	// empty. Notes only reach their owner.
+	let caption = move || {
+		s3_objects.with(|objects| {
+			objects.get(index.get()).map(|object| {
+				let non_empty = |text: &str| (!text.trim().is_empty()).then(|| text.to_string());
+				(
+					non_empty(&object.title),
+					non_empty(&object.description),
+					object.note.as_deref().and_then(non_empty),
+				)
+			})
+		})
+	};
+	let has_caption = move || {
+		caption().is_some_and(|(title, description, note)| {
+			title.is_some() || description.is_some() || note.is_some()
+		})
+	};
+	// Audio with analysed peaks gets a waveform scrubber in place of the plain
+	// player.
+	let current_has_waveform = move || {
+		s3_objects.with(|objects| {
+			objects.get(index.get()).is_some_and(|object| {
+				object.content_type.starts_with("audio/") &&
+					object.waveform_peaks.as_ref().is_some_and(|peaks| !peaks.is_empty())
 			})
 		})
 	};
@@ -320,7 +370,10 @@ pub fn Carousel(
 					is_open.set(true);
 					index.set(s3_object_index.get());
 				}>
-					<S3ObjectComponent s3_object=Signal::derive(move || Some(s3_object.clone())) />
+					<S3ObjectComponent
+						s3_object=Signal::derive(move || Some(s3_object.clone()))
+						rendition=Rendition::Thumbnail
+					/>
 				</Button>
 			</ForEnumerate>
 		</div>
@@ -351,13 +404,31 @@ pub fn Carousel(
 								<div class="relative w-full h-full grid items-end">
 									<div class="flex flex-wrap justify-between flex-row-reverse">
 										<Show when=move || {
-											show_metadata_content.get() && metadata_content().is_some()
+											show_metadata_content.get() &&
+												(metadata_content().is_some() || has_caption())
 										}>
 											<div
-												class="relative z-1 order-2 grid place-items-center w-fit h-100px bg-[rgba(0,0,0,0.4)] text-white px-4"
+												class="relative z-1 order-2 grid gap-2 content-center w-fit max-w-[60ch] min-h-100px max-h-[50dvh] overflow-auto bg-[rgba(0,0,0,0.4)] text-white p-4"
 												on:mouseenter=move |_| is_hovering.set(true)
 												on:mouseleave=move |_| is_hovering.set(false)
 											>
+												{move || {
+													caption()
+														.and_then(|(title, _, _)| title)
+														.map(|title| view! { <div class="font-bold">{title}</div> })
+												}}
+												{move || {
+													caption()
+														.and_then(|(_, description, _)| description)
+														.map(|description| view! { <Markdown text=description /> })
+												}}
+												{move || {
+													caption()
+														.and_then(|(_, _, note)| note)
+														.map(|note| {
+															view! { <Markdown text=note class="italic opacity-80" /> }
+														})
+												}}
 												{metadata_content}
 											</div>
 										</Show>
@@ -461,9 +532,10 @@ pub fn Carousel(
 												index.set(s3_object_index.get());
 											}
 										>
-											<S3ObjectComponent s3_object=Signal::derive(move || {
-												Some(s3_object.clone())
-											}) />
+											<S3ObjectComponent
+												s3_object=Signal::derive(move || Some(s3_object.clone()))
+												rendition=Rendition::Thumbnail
+											/>
 										</Button>
 									</ForEnumerate>
 								</div>
@@ -477,16 +549,41 @@ pub fn Carousel(
 					></Button>
 					// Content
 					<Show when=move || { s3_objects.get().get(index.get()).is_some() }>
-						<FullSizeS3Object
-							class="full-size-s3-object absolute w-fit h-auto"
-							rotation=current_rotation
-							s3_object=Signal::derive(move || {
-								s3_objects.get().get(index.get()).cloned()
-							})
-						/>
+						<Show
+							when=move || current_has_waveform()
+							fallback=move || {
+								view! {
+									<FullSizeS3Object
+										class="full-size-s3-object absolute w-fit h-auto"
+										rotation=current_rotation
+										s3_object=Signal::derive(move || {
+											s3_objects.get().get(index.get()).cloned()
+										})
+									/>
+								}
+							}
+						>
+							<AudioWaveform
+								class="absolute z-1"
+								s3_object=Signal::derive(move || {
+									s3_objects.get().get(index.get()).cloned()
+								})
+							/>
+						</Show>
 					</Show>
 				</div>
 			</DialogSurface>
 		</Dialog>
 	}
 }
+
+/// Formats a playback length as `m:ss`, or `h:mm:ss` from an hour up.
+fn format_duration(seconds: f64) -> String {
+	let total = seconds.round().max(0.0) as u64;
+	let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+	if hours > 0 {
+		format!("{hours}:{minutes:02}:{seconds:02}")
+	} else {
+		format!("{minutes}:{seconds:02}")
+	}
+}
@@ -0,0 +1,147 @@
+use {
+	crate::{
+		AppConfig,
+		components::{
+			gallery::Gallery,
+			timeline_slider::MadeOnRange,
+		},
+		constants::ERR_SYSTEM_CONFIG_MISSING,
+		dump_errors,
+		graphql_queries::{
+			map_clusters::MapCluster,
+			s3_objects_in_bounds::{
+				S3ObjectsInBoundsQuery,
+				s3_objects_in_bounds_query::Variables as ObjectsInBounds,
+			},
+		},
+	},
+	leaflet::{
+		LatLng,
+		LatLngBounds,
+	},
+	leptos::prelude::*,
+	leptos_leaflet::prelude::*,
+	thaw::Button,
+};
+
+/// The classes of a cluster's round count badge. Leaflet sizes and offsets div
+/// icons inline, hence the overrides.
+const CLUSTER_ICON_CLASS: &str = "!w-10 !h-10 !-ml-5 !-mt-5 grid place-items-center rounded-full \
+                                  bg-blue-600/80 text-white text-sm font-bold shadow";
+
+/// The date part of an RFC 3339 timestamp.
+fn date(made_on: &str) -> &str {
+	made_on.split('T').next().unwrap_or(made_on)
+}
+
+/// When a cluster's objects were made, if any of them say.
+fn time_span(
+	earliest: Option<&str>,
+	latest: Option<&str>,
+) -> Option<String> {
+	match (earliest.map(date), latest.map(date)) {
+		(Some(earliest), Some(latest)) if earliest == latest => Some(earliest.to_string()),
+		(Some(earliest), Some(latest)) => Some(format!("{earliest} to {latest}")),
+		_ => None,
+	}
+}
+
+/// The objects taken at one spot, fetched once asked for.
+#[component]
+fn ClusterObjects(bounds: ObjectsInBounds) -> impl IntoView {
+	let config = match use_context::<AppConfig>() {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let requested = RwSignal::new(false);
+	let s3_objects_resource = LocalResource::new(move || {
+		let api_url = config.api_url.clone();
+		let bounds = bounds.clone();
+		let requested = requested.get();
+		async move {
+			if !requested {
+				return Ok(Vec::new());
+			}
+			crate::graphql_queries::run::<S3ObjectsInBoundsQuery>(api_url, bounds).await
+		}
+	});
+	view! {
+		<Show
+			when=move || requested.get()
+			fallback=move || {
+				view! { <Button on_click=move |_| requested.set(true)>"Show objects"</Button> }
+			}
+		>
+			<ErrorBoundary fallback=dump_errors>
+				<Transition fallback=move || {
+					view! { <p>"Loading objects..."</p> }
+				}>
+					{move || {
+						s3_objects_resource
+							.get()
+							.map(|data| {
+								Ok::<_, Error>(view! { <Gallery s3_objects=data? /> })
+							})
+					}}
+				</Transition>
+			</ErrorBoundary>
+		</Show>
+	}
+	.into_any()
+}
+
+/// A map marker standing for a cluster of objects, labelled with how many it
+/// holds. Its popup zooms the map in to split the cluster up, or lists the
+/// objects once they were all taken at the same spot. `made_on_range` is the
+/// one the cluster was fetched with, so the list holds the same objects.
+#[component]
+pub fn ClusterMarker(
+	cluster: MapCluster,
+	made_on_range: MadeOnRange,
+) -> impl IntoView {
+	let MapCluster {
+		count,
+		centroid,
+		bounds,
+		earliest_made_on,
+		latest_made_on,
+	} = cluster;
+	let heading = if count == 1 { "1 object".to_string() } else { format!("{count} objects") };
+	let time_span = time_span(earliest_made_on.as_deref(), latest_made_on.as_deref());
+	let same_spot = bounds.south == bounds.north && bounds.west == bounds.east;
+	let objects = ObjectsInBounds {
+		south: bounds.south,
+		west: bounds.west,
+		north: bounds.north,
+		east: bounds.east,
+		filter: made_on_range.filter(),
+	};
+	let map_context = use_leaflet_context();
+	let zoom_in = move |_| {
+		if let Some(map) = map_context.as_ref().and_then(|context| context.map()) {
+			map.fit_bounds(&LatLngBounds::new(
+				&LatLng::new(objects.south, objects.west),
+				&LatLng::new(objects.north, objects.east),
+			));
+		}
+	};
+	view! {
+		<Marker
+			position=position!(centroid.latitude, centroid.longitude)
+			icon_class=Some(CLUSTER_ICON_CLASS.to_string())
+			html=Some(count.to_string())
+		>
+			<Popup>
+				<div class="grid gap-4">
+					<h2>{heading}</h2>
+					{time_span.map(|time_span| view! { <p>{time_span}</p> })}
+					{if same_spot {
+						view! { <ClusterObjects bounds=objects.clone() /> }.into_any()
+					} else {
+						view! { <Button on_click=zoom_in>"Zoom in"</Button> }.into_any()
+					}}
+				</div>
+			</Popup>
+		</Marker>
+	}
+}
@@ -1,15 +1,27 @@
 use {
 	crate::{
 		AppConfig,
+		components::{
+			group_picker::GroupPicker,
+			tag_input::{
+				TagInput,
+				split_tags,
+			},
+		},
 		constants::{
 			BUTTON_CANCEL,
 			BUTTON_SUBMIT,
 			LABEL_ALLOWED_USERS,
+			LABEL_DESCRIPTION,
 			LABEL_NAME,
+			LABEL_NOTE,
+			LABEL_OBJECT_LOCATION_PRECISION,
+			LABEL_OBJECT_METADATA_PRIVACY,
 			LABEL_PUBLICITY,
 			LABEL_SET_DATE_TIME,
 			LABEL_SET_LATITUDE,
 			LABEL_SET_LONGITUDE,
+			LABEL_TITLE,
 			LATITUDE_MAX,
 			LATITUDE_MIN,
 			LONGITUDE_MAX,
@@ -19,10 +31,18 @@ use {
 			MSG_MISSING_USERS,
 			MSG_OBJECT_UPDATED,
 			MSG_UPDATE_FAILED,
+			OPTION_APPROX_1KM,
+			OPTION_APPROX_100M,
+			OPTION_CITY,
 			OPTION_DEFAULT,
+			OPTION_EXACT_LOCATION,
+			OPTION_HIDDEN_LOCATION,
+			OPTION_KEEP_METADATA,
 			OPTION_PRIVATE,
 			OPTION_PUBLIC,
 			OPTION_SELECTED_USERS,
+			OPTION_STRIP_ALL_METADATA,
+			OPTION_STRIP_LOCATION,
 			PLACEHOLDER_ALLOWED_USERS,
 			TITLE_INVALID_EMAILS,
 			TITLE_SUCCESS,
@@ -36,7 +56,11 @@ use {
 				s3_object_by_id_query,
 			},
 			s3_objects::s3_objects_query::S3ObjectsQueryS3Objects,
-			types::PublicityOverride,
+			types::{
+				LocationPrecisionOverride,
+				MetadataPrivacyOverride,
+				PublicityOverride,
+			},
 			update_s3_object::{
 				UpdateS3ObjectMutation,
 				update_s3_object_mutation::{
@@ -118,7 +142,14 @@ pub fn EditS3ObjectForm(
 	let (longitude, set_longitude) = signal(None::<f64>);
 	let (made_on, set_made_on) = signal(String::new());
 	let (publicity, set_publicity) = signal(PublicityOverride::Default);
+	let (metadata_privacy, set_metadata_privacy) = signal(MetadataPrivacyOverride::Default);
+	let (location_precision, set_location_precision) = signal(LocationPrecisionOverride::Default);
 	let (allowed_users, set_allowed_users) = signal(String::new());
+	let allowed_groups = RwSignal::new(Vec::<String>::new());
+	let tags = RwSignal::new(String::new());
+	let (title, set_title) = signal(String::new());
+	let (description, set_description) = signal(String::new());
+	let (note, set_note) = signal(String::new());
 
 	// Populate form from initial data (Optimistic UI)
 	Effect::new(move |_| {
@@ -137,6 +168,9 @@ pub fn EditS3ObjectForm(
 			if !s3_object.allowed_users.is_empty() {
 				set_allowed_users.set(s3_object.allowed_users.join(", "));
 			}
+			set_title.set(s3_object.title);
+			set_description.set(s3_object.description);
+			set_note.set(s3_object.note.unwrap_or_default());
 		}
 	});
 
@@ -154,9 +188,16 @@ pub fn EditS3ObjectForm(
 				set_made_on.set(local_str);
 			}
 			set_publicity.set(s3_object.publicity);
+			set_metadata_privacy.set(s3_object.metadata_privacy);
+			set_location_precision.set(s3_object.location_precision);
 			if !s3_object.allowed_users.is_empty() {
 				set_allowed_users.set(s3_object.allowed_users.join(", "));
 			}
+			allowed_groups.set(s3_object.allowed_groups);
+			tags.set(s3_object.tags.join(", "));
+			set_title.set(s3_object.title);
+			set_description.set(s3_object.description);
+			set_note.set(s3_object.note.unwrap_or_default());
 		}
 	});
 
@@ -168,7 +209,14 @@ pub fn EditS3ObjectForm(
 		let lon_val = longitude.get();
 		let made_on_val = made_on.get();
 		let publicity_val = publicity.get();
+		let metadata_privacy_val = metadata_privacy.get();
+		let location_precision_val = location_precision.get();
 		let allowed_users_val = allowed_users.get();
+		let allowed_groups_val = allowed_groups.get();
+		let tags_vec = split_tags(&tags.get());
+		let title_val = title.get();
+		let description_val = description.get();
+		let note_val = note.get();
 
 		let location = if let (Some(lat), Some(lon)) = (lat_val, lon_val) {
 			Some(LocationInput {
@@ -222,7 +270,14 @@ pub fn EditS3ObjectForm(
 					made_on: made_on_iso,
 					location,
 					publicity: publicity_val,
+					metadata_privacy: Some(metadata_privacy_val),
+					location_precision: Some(location_precision_val),
 					allowed_users: Some(allowed_users_vec.clone()),
+					allowed_groups: Some(allowed_groups_val),
+					tags: Some(tags_vec),
+					title: Some(title_val),
+					description: Some(description_val),
+					note: Some(note_val),
 				},
 			};
 
@@ -317,6 +372,33 @@ pub fn EditS3ObjectForm(
 														class="bg-gray-200 cursor-not-allowed"
 													/>
 												</label>
+												<label>
+													<div class="font-bold">{LABEL_TITLE}</div>
+													<input
+														type="text"
+														name="title"
+														prop:value=title
+														on:input=move |ev| set_title.set(event_target_value(&ev))
+													/>
+												</label>
+												<label>
+													<div class="font-bold">{LABEL_DESCRIPTION}</div>
+													<textarea
+														name="description"
+														rows="4"
+														prop:value=description
+														on:input=move |ev| set_description.set(event_target_value(&ev))
+													/>
+												</label>
+												<label>
+													<div class="font-bold">{LABEL_NOTE}</div>
+													<textarea
+														name="note"
+														rows="4"
+														prop:value=note
+														on:input=move |ev| set_note.set(event_target_value(&ev))
+													/>
+												</label>
 												<label>
 													<div class="font-bold">{LABEL_PUBLICITY}</div>
 													<select
@@ -350,7 +432,47 @@ pub fn EditS3ObjectForm(
 															placeholder=PLACEHOLDER_ALLOWED_USERS
 														/>
 													</label>
+													<GroupPicker selected=allowed_groups />
 												</Show>
+												<TagInput value=tags />
+												<label>
+													<div class="font-bold">{LABEL_OBJECT_METADATA_PRIVACY}</div>
+													<select
+														class="p-2 border rounded bg-white"
+														on:change=move |ev| {
+															let val = event_target_value(&ev);
+															if let Ok(new_privacy) = val.parse() {
+																set_metadata_privacy.set(new_privacy);
+															}
+														}
+														prop:value=move || metadata_privacy.get().to_string()
+													>
+														<option value="Default">{OPTION_DEFAULT}</option>
+														<option value="Keep">{OPTION_KEEP_METADATA}</option>
+														<option value="Strip Location">{OPTION_STRIP_LOCATION}</option>
+														<option value="Strip All">{OPTION_STRIP_ALL_METADATA}</option>
+													</select>
+												</label>
+												<label>
+													<div class="font-bold">{LABEL_OBJECT_LOCATION_PRECISION}</div>
+													<select
+														class="p-2 border rounded bg-white"
+														on:change=move |ev| {
+															let val = event_target_value(&ev);
+															if let Ok(new_precision) = val.parse() {
+																set_location_precision.set(new_precision);
+															}
+														}
+														prop:value=move || location_precision.get().to_string()
+													>
+														<option value="Default">{OPTION_DEFAULT}</option>
+														<option value="Exact">{OPTION_EXACT_LOCATION}</option>
+														<option value="Approx 100m">{OPTION_APPROX_100M}</option>
+														<option value="Approx 1km">{OPTION_APPROX_1KM}</option>
+														<option value="City">{OPTION_CITY}</option>
+														<option value="Hidden">{OPTION_HIDDEN_LOCATION}</option>
+													</select>
+												</label>
 												<label>
 													<div class="font-bold">{LABEL_SET_LATITUDE}</div>
 													<input
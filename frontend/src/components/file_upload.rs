@@ -1,8 +1,13 @@
 use {
 	crate::{
 		AppConfig,
+		components::tag_input::{
+			TagInput,
+			split_tags,
+		},
 		constants::{
 			BUTTON_CANCEL,
+			BUTTON_DONE,
 			BUTTON_SUBMIT,
 			ERR_NETWORK_UPLOAD_FAILED,
 			ERR_SYSTEM_NO_WINDOW,
@@ -10,6 +15,7 @@ use {
 			ERR_SYSTEM_RESPONSE_CAST,
 			ERR_UNSUPPORTED_FILE_TYPE,
 			ERROR_SELECT_FILE,
+			LABEL_ALREADY_UPLOADED_AS,
 			LABEL_SELECT_FILES,
 			LABEL_SET_DATE_TIME,
 			LABEL_SET_LATITUDE,
@@ -18,6 +24,7 @@ use {
 			LATITUDE_MIN,
 			LONGITUDE_MAX,
 			LONGITUDE_MIN,
+			MSG_ALREADY_UPLOADED,
 		},
 		dump_errors,
 		errors::{
@@ -35,12 +42,13 @@ use {
 				CompletedUploadPartInput,
 				complete_object_upload_mutation,
 			},
-			create_object_upload_session::{
+			create_deduplicated_object_upload_session::{
+				CreateDeduplicatedObjectUploadSessionMutation,
 				CreateObjectUploadSessionInput,
-				CreateObjectUploadSessionMutation,
+				DeduplicatedObjectUpload,
 				PublicityOverride,
 				UploadLocationInput,
-				create_object_upload_session_mutation,
+				create_deduplicated_object_upload_session_mutation,
 			},
 			presign_object_upload_parts::{
 				PresignObjectUploadPartsMutation,
@@ -68,20 +76,36 @@ use {
 			RequestMode,
 			Response,
 			SubmitEvent,
+			js_sys::Uint8Array,
 		},
 	},
 	shared::{
 		ALLOWED_MIME_TYPES,
+		CONTENT_TYPES_BY_EXTENSION,
 		MAX_PRESIGN_PARTS_PER_REQUEST,
+		content_type_for_file_name,
 	},
 	thaw::*,
 	wasm_bindgen_futures::JsFuture,
 };
 
+/// How much of a file is read at a time while hashing it.
+const HASH_CHUNK_BYTES: f64 = 8.0 * 1024.0 * 1024.0;
+
 #[derive(Clone, Debug)]
 struct UploadMetadata {
 	made_on: Option<String>,
 	location: Option<(f64, f64)>,
+	tags: Vec<String>,
+}
+
+/// A selected file that matched an object the user already has, which is
+/// linked to instead of uploading the file again.
+#[derive(Clone, Debug)]
+struct AlreadyUploaded {
+	file_name: String,
+	name: String,
+	url: String,
 }
 
 impl UploadMetadata {
@@ -107,6 +131,8 @@ pub fn FileUpload(
 	let file_input_ref = NodeRef::<Input>::new();
 	let made_on_input_ref = NodeRef::<Input>::new();
 	let (uploading, set_uploading) = signal(false);
+	let tags = RwSignal::new(String::new());
+	let already_uploaded = RwSignal::new(Vec::<AlreadyUploaded>::new());
 
 	let on_submit = move |event: SubmitEvent| {
 		event.prevent_default();
@@ -150,11 +176,15 @@ pub fn FileUpload(
 
 		let api_url = config.api_url.clone();
 		set_uploading.set(true);
+		already_uploaded.set(Vec::new());
 		spawn_local(async move {
 			let result = upload_files(api_url, files, metadata).await;
 			set_uploading.set(false);
+			// Duplicates keep the form open so the user sees which files were
+			// skipped; closing it then refreshes the parent.
 			match result {
-				Ok(()) => on_success.run(()),
+				Ok(existing) if existing.is_empty() => on_success.run(()),
+				Ok(existing) => already_uploaded.set(existing),
 				Err(error) => error_ctx.report(error),
 			}
 		});
@@ -187,16 +217,55 @@ pub fn FileUpload(
 						<div class="font-bold">{LABEL_SET_DATE_TIME}</div>
 						<input type="datetime-local" node_ref=made_on_input_ref />
 					</label>
+					<TagInput value=tags />
 					<label>
 						<div class="font-bold">{LABEL_SELECT_FILES}</div>
 						<input
 							type="file"
 							name="files"
-							accept=ALLOWED_MIME_TYPES.join(",")
+							accept=accepted_file_types()
 							multiple
 							node_ref=file_input_ref
 						/>
 					</label>
+					<Show when=move || !already_uploaded.get().is_empty() fallback=|| ()>
+						<div class="grid gap-2" role="status">
+							<p class="font-bold">{MSG_ALREADY_UPLOADED}</p>
+							<ul class="grid gap-1">
+								{move || {
+									already_uploaded
+										.get()
+										.into_iter()
+										.map(|existing| {
+											view! {
+												<li class="text-sm break-words">
+													{existing.file_name}
+													" "
+													{LABEL_ALREADY_UPLOADED_AS}
+													" "
+													<a
+														class="underline"
+														href=existing.url
+														target="_blank"
+														rel="noopener noreferrer"
+													>
+														{existing.name}
+													</a>
+												</li>
+											}
+										})
+										.collect_view()
+								}}
+							</ul>
+							<Button
+								attr:r#type="button"
+								class="w-fit"
+								on_click=move |_| on_success.run(())
+							>
+								{BUTTON_DONE}
+							</Button>
+						</div>
+					</Show>
 					<div class="grid grid-flow-col justify-start gap-4">
 						<Button attr:r#type="submit" attr:disabled=move || uploading.get() class="w-fit">
 							{move || if uploading.get() { "Uploading..." } else { BUTTON_SUBMIT }}
@@ -237,7 +306,7 @@ fn selected_files(file_input_ref: NodeRef<Input>) -> Result<Vec<File>, AppError>
 		let Some(file) = files.item(index) else {
 			continue;
 		};
-		let file_type = file.type_();
+		let file_type = file_content_type(&file);
 		if !ALLOWED_MIME_TYPES.contains(&file_type.as_str()) {
 			return Err(AppError::Validation(format!(
 				"{}{} ({})",
@@ -258,6 +327,27 @@ fn selected_files(file_input_ref: NodeRef<Input>) -> Result<Vec<File>, AppError>
 	Ok(selected)
 }
 
+/// The `accept` list of the file input. Extensions are listed as well, since
+/// browsers that do not know a type's name cannot match a file against it.
+fn accepted_file_types() -> String {
+	ALLOWED_MIME_TYPES
+		.iter()
+		.map(|content_type| content_type.to_string())
+		.chain(CONTENT_TYPES_BY_EXTENSION.iter().map(|(extension, _)| format!(".{extension}")))
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+/// The file's content type as the browser reports it, or going by its extension
+/// when the browser reports none or a name the backend does not accept.
+fn file_content_type(file: &File) -> String {
+	let reported = file.type_();
+	if ALLOWED_MIME_TYPES.contains(&reported.as_str()) {
+		return reported;
+	}
+	content_type_for_file_name(&file.name()).map_or(reported, str::to_string)
+}
+
 fn upload_metadata(
 	form_data: &FormData,
 	made_on: Option<String>,
@@ -276,9 +366,12 @@ fn upload_metadata(
 		)),
 	};
 
+	let tags = form_value(form_data, "tags").map(|tags| split_tags(&tags)).unwrap_or_default();
+
 	Ok(UploadMetadata {
 		made_on,
 		location,
+		tags,
 	})
 }
 
@@ -305,27 +398,32 @@ fn parse_coordinate(
 	Ok(coordinate)
 }
 
+/// Uploads each file in turn, returning the ones the user already had.
 async fn upload_files(
 	api_url: String,
 	files: Vec<File>,
 	metadata: UploadMetadata,
-) -> Result<(), AppError> {
+) -> Result<Vec<AlreadyUploaded>, AppError> {
+	let mut already_uploaded = Vec::new();
 	for file in files {
-		upload_file(api_url.clone(), file, &metadata).await?;
+		already_uploaded.extend(upload_file(api_url.clone(), file, &metadata).await?);
 	}
-	Ok(())
+	Ok(already_uploaded)
 }
 
+/// Uploads one file, or returns the existing object with the same content
+/// without uploading anything.
 async fn upload_file(
 	api_url: String,
 	file: File,
 	metadata: &UploadMetadata,
-) -> Result<(), AppError> {
-	let content_type = file.type_();
+) -> Result<Option<AlreadyUploaded>, AppError> {
+	let content_type = file_content_type(&file);
 	let file_size_bytes = file_size_bytes(&file)?;
-	let session = crate::graphql_queries::run::<CreateObjectUploadSessionMutation>(
+	let content_hash = file_content_hash(&file).await?;
+	let upload = crate::graphql_queries::run::<CreateDeduplicatedObjectUploadSessionMutation>(
 		api_url.clone(),
-		create_object_upload_session_mutation::Variables {
+		create_deduplicated_object_upload_session_mutation::Variables {
 			input: CreateObjectUploadSessionInput {
 				name: file.name(),
 				content_type,
@@ -334,10 +432,26 @@ async fn upload_file(
 				location: metadata.location_input(),
 				publicity: PublicityOverride::Default,
 				allowed_users: Some(Vec::new()),
+				allowed_groups: None,
+				tags: Some(metadata.tags.clone()),
+				title: None,
+				description: None,
+				note: None,
 			},
+			content_hash,
 		},
 	)
 	.await?;
+	let session = match upload {
+		DeduplicatedObjectUpload::CreatedObjectUploadSession(session) => session,
+		DeduplicatedObjectUpload::S3Object(existing) => {
+			return Ok(Some(AlreadyUploaded {
+				file_name: file.name(),
+				name: existing.name,
+				url: existing.url,
+			}));
+		}
+	};
 
 	let completed_parts = match upload_file_parts(
 		api_url.clone(),
@@ -369,7 +483,23 @@ async fn upload_file(
 		},
 	)
 	.await?;
-	Ok(())
+	Ok(None)
+}
+
+/// Hashes the file with BLAKE3 a chunk at a time, so large videos are never
+/// read into memory whole.
+async fn file_content_hash(file: &File) -> Result<String, AppError> {
+	let mut hasher = blake3::Hasher::new();
+	let size = file.size();
+	let mut offset = 0.0;
+	while offset < size {
+		let end = (offset + HASH_CHUNK_BYTES).min(size);
+		let chunk = file.slice_with_f64_and_f64(offset, end).map_err(AppError::from)?;
+		let buffer = JsFuture::from(chunk.array_buffer()).await?;
+		hasher.update(&Uint8Array::new(&buffer).to_vec());
+		offset = end;
+	}
+	Ok(hasher.finalize().to_string())
 }
 
 fn file_size_bytes(file: &File) -> Result<i64, AppError> {
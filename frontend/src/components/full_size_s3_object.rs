@@ -1,6 +1,9 @@
 use {
 	crate::{
-		components::s3_object::S3Object as S3ObjectComponent,
+		components::s3_object::{
+			Rendition,
+			S3Object as S3ObjectComponent,
+		},
 		graphql_queries::s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
 	},
 	leptos::prelude::*,
@@ -32,7 +35,11 @@ pub fn FullSizeS3Object(
 				on_click=move |_| open.set(true)
 			>
 				// Constrained size content
-				<S3ObjectComponent class="max-w-dvw max-h-dvh object-scale-down" s3_object />
+				<S3ObjectComponent
+					class="max-w-dvw max-h-dvh object-scale-down"
+					s3_object
+					rendition=Rendition::Preview
+				/>
 			</Button>
 			<Dialog open>
 				<DialogSurface class="dialog-surface border-none rounded-none m-unset p-unset bg-transparent">
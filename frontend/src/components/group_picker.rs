@@ -0,0 +1,91 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			LABEL_ALLOWED_GROUPS,
+			LINK_MANAGE_GROUPS,
+			MSG_NO_GROUPS,
+		},
+		errors::use_context_safe,
+		graphql_queries::user_groups::{
+			UserGroup,
+			UserGroupsQuery,
+			user_groups_query,
+		},
+	},
+	leptos::prelude::*,
+	leptos_router::components::A,
+};
+
+/// A checkbox for each of the user's groups, ticking those whose ids are in
+/// `selected`.
+#[component]
+pub fn GroupPicker(
+	/// The ids of the ticked groups.
+	selected: RwSignal<Vec<String>>
+) -> impl IntoView {
+	let api_url = use_context_safe::<AppConfig>("AppConfig").map(|config| config.api_url);
+	let groups_resource = LocalResource::new(move || {
+		let api_url = api_url.clone();
+		async move {
+			let Some(api_url) = api_url else {
+				return Vec::new();
+			};
+			crate::graphql_queries::run::<UserGroupsQuery>(api_url, user_groups_query::Variables {})
+				.await
+				.unwrap_or_default()
+		}
+	});
+
+	view! {
+		<div>
+			<div class="font-bold">{LABEL_ALLOWED_GROUPS}</div>
+			<Suspense>
+				{move || {
+					groups_resource
+						.get()
+						.map(|groups| {
+							if groups.is_empty() {
+								view! { <p class="text-sm">{MSG_NO_GROUPS}</p> }.into_any()
+							} else {
+								groups
+									.into_iter()
+									.map(|group| view! { <GroupCheckbox group selected /> })
+									.collect_view()
+									.into_any()
+							}
+						})
+				}}
+			</Suspense>
+			<A href="/groups" attr:class="text-sm underline">
+				{LINK_MANAGE_GROUPS}
+			</A>
+		</div>
+	}
+}
+
+/// One group, ticked when its id is in `selected`.
+#[component]
+fn GroupCheckbox(
+	group: UserGroup,
+	selected: RwSignal<Vec<String>>,
+) -> impl IntoView {
+	let id = StoredValue::new(group.id);
+	let checked = move || id.with_value(|id| selected.with(|selected| selected.contains(id)));
+	let on_change = move |ev| {
+		let ticked = event_target_checked(&ev);
+		let id = id.get_value();
+		selected.update(|selected| {
+			selected.retain(|selected_id| *selected_id != id);
+			if ticked {
+				selected.push(id);
+			}
+		});
+	};
+	view! {
+		<label class="flex items-center gap-2">
+			<input type="checkbox" prop:checked=checked on:change=on_change />
+			{group.name}
+		</label>
+	}
+}
@@ -2,6 +2,7 @@ use {
 	crate::{
 		AppConfig,
 		auth::UserContext,
+		components::search_box::SearchBox,
 		constants::{
 			ARIA_CLOSE_MENU,
 			ARIA_OPEN_MENU,
@@ -9,9 +10,12 @@ use {
 			ERR_SYSTEM_CONFIG_MISSING,
 			HEADER_LAYER_CLASSES,
 			LINK_ACCOUNT,
+			LINK_DUPLICATES,
+			LINK_GROUPS,
 			LINK_MAP,
 			LINK_OBJECTS,
 			LINK_SIGN_IN,
+			LINK_TRASH,
 			LINK_USERS,
 		},
 		errors::use_context_safe,
@@ -34,7 +38,8 @@ use {
 	},
 };
 
-/// The Header component containing the navigation menu.
+/// The Header component containing the navigation menu, and a search box once
+/// signed in.
 /// It supports a toggleable menu for mobile/desktop views and handles
 /// the visual state when the menu is open or closed.
 #[component]
@@ -75,6 +80,7 @@ pub fn Header(#[prop(into)] menu_open: RwSignal<bool>) -> impl IntoView {
 		});
 	};
 	let on_logout = StoredValue::new(on_logout);
+	let signed_in = move || user_ctx.and_then(|ctx| ctx.user.get()).flatten().is_some();
 
 	view! {
 		<header class="fixed z-1">
@@ -131,6 +137,27 @@ pub fn Header(#[prop(into)] menu_open: RwSignal<bool>) -> impl IntoView {
 																	>
 																		{LINK_OBJECTS}
 																	</A>
+																	<A
+																		attr:class="py-4 w-full grid place-items-center"
+																		href="/duplicates"
+																		on:click=move |_| close_header_menu()
+																	>
+																		{LINK_DUPLICATES}
+																	</A>
+																	<A
+																		attr:class="py-4 w-full grid place-items-center"
+																		href="/trash"
+																		on:click=move |_| close_header_menu()
+																	>
+																		{LINK_TRASH}
+																	</A>
+																	<A
+																		attr:class="py-4 w-full grid place-items-center"
+																		href="/groups"
+																		on:click=move |_| close_header_menu()
+																	>
+																		{LINK_GROUPS}
+																	</A>
 																	<A
 																		attr:class="py-4 w-full grid place-items-center"
 																		href="/account"
@@ -184,9 +211,12 @@ pub fn Header(#[prop(into)] menu_open: RwSignal<bool>) -> impl IntoView {
 				</div>
 			</Show>
 
-			// Header controls (Menu button)
+			// Header controls (search and menu button)
 			<div class=format!("relative pointer-events-none {HEADER_LAYER_CLASSES}")>
 				<div class="absolute inset-0 h-full px-4 grid grid-flow-col items-center gap-4">
+					<Show when=signed_in>
+						<SearchBox />
+					</Show>
 					<button
 						class="pointer-events-auto relative z-1 cursor-pointer justify-self-end rounded-full grid place-items-center w-40px aspect-square bg-#666"
 						on:click=move |_| toggle_header_menu()
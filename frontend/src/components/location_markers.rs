@@ -1,82 +1,355 @@
 use {
 	crate::{
 		AppConfig,
-		LocationStrings,
-		components::location_marker::LocationMarker,
-		constants::ERR_SYSTEM_CONFIG_MISSING,
+		components::{
+			cluster_marker::ClusterMarker,
+			timeline_slider::MadeOnRange,
+		},
+		constants::{
+			ERR_SYSTEM_CONFIG_MISSING,
+			ERR_SYSTEM_NO_WINDOW,
+			ERR_SYSTEM_REQUEST_FAILED,
+			ERR_SYSTEM_RESPONSE_CAST,
+		},
 		dump_errors,
-		graphql_queries::s3_objects::{
-			S3ObjectsQuery,
-			s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+		errors::AppError,
+		graphql_queries::map_clusters::{
+			BoundsInput,
+			MapClustersQuery,
+			map_clusters_query::Variables,
 		},
+		vector_tile::object_points,
+	},
+	leaflet::{
+		Layer,
+		Map,
+		MoveEvents,
 	},
 	leptos::{
 		logging::debug_error,
 		prelude::*,
+		task::spawn_local,
+		wasm_bindgen::{
+			JsCast,
+			JsValue,
+			closure::Closure,
+		},
+		web_sys::{
+			self,
+			CanvasRenderingContext2d,
+			HtmlCanvasElement,
+			Request,
+			RequestCredentials,
+			RequestInit,
+			RequestMode,
+			Response,
+			js_sys::{
+				Date,
+				Function,
+				Object,
+				Reflect,
+				Uint8Array,
+			},
+		},
 	},
-	std::collections::HashMap,
+	leptos_leaflet::prelude::use_leaflet_context,
+	std::f64::consts::TAU,
+	wasm_bindgen::prelude::wasm_bindgen,
+	wasm_bindgen_futures::JsFuture,
 };
 
-fn render_markers(s3_objects: Vec<S3Object>) -> impl IntoView {
-	s3_objects
-		.into_iter()
-		.map(|s3_object: S3Object| {
-			(
-				s3_object.location.as_ref().map(|location| LocationStrings {
-					latitude: location.latitude.to_string(),
-					longitude: location.longitude.to_string(),
-				}),
-				s3_object,
-			)
-		})
-		.fold(HashMap::<Option<LocationStrings>, Vec<S3Object>>::new(), |mut carry, (key, item)| {
-			carry.entry(key).or_default().push(item);
-			carry
-		})
-		.into_iter()
-		.map(|(location, s3_objects)| {
-			location.as_ref().map(|location_strings| {
-				// This should just work, since the location strings were serialised from actual f64 values.
-				match (
-					location_strings.latitude.parse::<f64>(),
-					location_strings.longitude.parse::<f64>(),
-				) {
-					(Ok(latitude), Ok(longitude)) =>
-						Some(view! { <LocationMarker latitude longitude s3_objects /> }),
-					_ => None,
-				}
-			})
+/// The width and height of an object points tile, in pixels.
+const OBJECT_POINTS_TILE_SIZE: u32 = 256;
+
+/// The radius of an object's point, in pixels.
+const OBJECT_POINT_RADIUS: f64 = 3.0;
+
+/// Above the base map's tiles, below the cluster markers.
+const OBJECT_POINTS_Z_INDEX: i32 = 2;
+
+/// Wraps a longitude leaflet reports for a map panned across the antimeridian,
+/// such as 190, back into -180..180.
+fn wrap_longitude(longitude: f64) -> f64 {
+	(longitude + 180.0).rem_euclid(360.0) - 180.0
+}
+
+/// The area `map` shows and its zoom level. A viewport spanning the
+/// antimeridian comes out with `west` east of `east`, which the API reads as
+/// crossing it.
+fn viewport(map: &Map) -> (BoundsInput, i64) {
+	let bounds = map.get_bounds();
+	let (west, east) = if bounds.get_east() - bounds.get_west() >= 360.0 {
+		(-180.0, 180.0)
+	} else {
+		(wrap_longitude(bounds.get_west()), wrap_longitude(bounds.get_east()))
+	};
+	(
+		BoundsInput {
+			south: bounds.get_south().max(-90.0),
+			west,
+			north: bounds.get_north().min(90.0),
+			east,
+		},
+		map.get_zoom().round() as i64,
+	)
+}
+
+/// The colour of an object's point, by its content type family.
+fn point_colour(content_type_family: Option<&str>) -> &'static str {
+	match content_type_family {
+		Some("image") => "#2563eb",
+		Some("video") => "#dc2626",
+		Some("audio") => "#16a34a",
+		_ => "#6b7280",
+	}
+}
+
+/// Whether an object made at `made_on`, in seconds since the Unix epoch, is in
+/// `range`. As with the API's filter, one without a capture time is only in an
+/// open range.
+fn in_range(
+	made_on: Option<i64>,
+	range: &MadeOnRange,
+) -> bool {
+	let bound = |timestamp: &Option<String>| {
+		timestamp.as_deref().map(|timestamp| Date::parse(timestamp) / 1000.0)
+	};
+	match (made_on, bound(&range.from), bound(&range.to)) {
+		(_, None, None) => true,
+		(None, ..) => false,
+		(Some(made_on), from, to) =>
+			from.is_none_or(|from| made_on as f64 >= from) &&
+				to.is_none_or(|to| made_on as f64 <= to),
+	}
+}
+
+/// Fetches a vector tile with the session cookie, so it holds the objects the
+/// caller can see.
+async fn fetch_tile(url: &str) -> Result<Vec<u8>, AppError> {
+	let options = RequestInit::new();
+	options.set_method("GET");
+	options.set_mode(RequestMode::Cors);
+	options.set_credentials(RequestCredentials::Include);
+	let request = Request::new_with_str_and_init(url, &options)
+		.map_err(|_| AppError::System(ERR_SYSTEM_REQUEST_FAILED.to_string()))?;
+	let window =
+		web_sys::window().ok_or_else(|| AppError::System(ERR_SYSTEM_NO_WINDOW.to_string()))?;
+	let response = JsFuture::from(window.fetch_with_request(&request))
+		.await?
+		.dyn_into::<Response>()
+		.map_err(|_| AppError::System(ERR_SYSTEM_RESPONSE_CAST.to_string()))?;
+	if !response.ok() {
+		return Err(AppError::Network(format!("Failed to load {url}: {}", response.status())));
+	}
+	let buffer = JsFuture::from(response.array_buffer()?).await?;
+	Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+/// Draws the objects of the tile at `url` made within `range` onto `canvas`.
+async fn draw_tile(
+	canvas: &HtmlCanvasElement,
+	url: &str,
+	range: &MadeOnRange,
+) -> Result<(), AppError> {
+	let points = object_points(&fetch_tile(url).await?)?;
+	let context = canvas
+		.get_context("2d")?
+		.ok_or_else(|| AppError::Js("Canvas has no 2d context".to_string()))?
+		.dyn_into::<CanvasRenderingContext2d>()?;
+	let size = f64::from(OBJECT_POINTS_TILE_SIZE);
+	for point in points.iter().filter(|point| in_range(point.made_on, range)) {
+		context.begin_path();
+		context.arc(point.x * size, point.y * size, OBJECT_POINT_RADIUS, 0.0, TAU)?;
+		context.set_fill_style_str(point_colour(point.content_type_family.as_deref()));
+		context.fill();
+	}
+	Ok(())
+}
+
+#[wasm_bindgen(inline_js = "
+export function objectPointsGridLayer(createTile, options) {
+	const ObjectPoints = L.GridLayer.extend({
+		createTile(coords, done) {
+			return createTile(coords, done);
+		},
+	});
+	return new ObjectPoints(options);
+}
+")]
+extern "C" {
+	/// A leaflet grid layer whose tiles `createTile` makes. Leaflet only waits
+	/// for a tile's `done` callback when `createTile` declares both of its
+	/// parameters, which a wasm closure does not, hence the JavaScript.
+	#[wasm_bindgen(extends = Layer)]
+	type ObjectPointsGridLayer;
+
+	#[wasm_bindgen(js_name = objectPointsGridLayer, catch)]
+	fn object_points_grid_layer(
+		create_tile: &JsValue,
+		options: &JsValue,
+	) -> Result<ObjectPointsGridLayer, JsValue>;
+
+	/// Draws every tile again.
+	#[wasm_bindgen(method)]
+	fn redraw(this: &ObjectPointsGridLayer);
+}
+
+/// A leaflet grid layer drawing a dot for every object the caller can see,
+/// coloured by content type family, from the backend's `/tiles` vector tiles.
+/// Leaflet loads the tiles in view as the map moves, so however many objects
+/// there are, only those tiles are fetched. Removed from the map when dropped.
+struct ObjectPointsLayer {
+	layer: ObjectPointsGridLayer,
+	/// Called by leaflet for each tile, so kept for as long as the layer.
+	_create_tile: Closure<dyn FnMut(JsValue, Function) -> JsValue>,
+}
+
+impl ObjectPointsLayer {
+	/// Builds the layer for the API at `api_url`, showing only the objects
+	/// made within `made_on_range` when the tiles are drawn.
+	fn new(
+		api_url: &str,
+		made_on_range: Signal<MadeOnRange>,
+	) -> Result<Self, AppError> {
+		let tiles_url = format!("{}/tiles", api_url.trim_end_matches('/'));
+		let create_tile = Closure::<dyn FnMut(JsValue, Function) -> JsValue>::new(
+			move |coords: JsValue, done: Function| {
+				let coordinate = |axis: &str| {
+					Reflect::get(&coords, &JsValue::from_str(axis))
+						.ok()
+						.and_then(|value| value.as_f64())
+						.unwrap_or_default() as i64
+				};
+				let url = format!(
+					"{tiles_url}/{}/{}/{}.mvt",
+					coordinate("z"),
+					coordinate("x"),
+					coordinate("y")
+				);
+				let Ok(canvas) = document()
+					.create_element("canvas")
+					.map(JsCast::unchecked_into::<HtmlCanvasElement>)
+				else {
+					return JsValue::NULL;
+				};
+				canvas.set_width(OBJECT_POINTS_TILE_SIZE);
+				canvas.set_height(OBJECT_POINTS_TILE_SIZE);
+				let tile = canvas.clone();
+				let range = made_on_range.get_untracked();
+				spawn_local(async move {
+					let error = match draw_tile(&tile, &url, &range).await {
+						Ok(()) => JsValue::NULL,
+						Err(error) => {
+							debug_error!("Failed to draw object points: {error}");
+							JsValue::from_str(&error.to_string())
+						}
+					};
+					let _ = done.call2(&JsValue::NULL, &error, &tile);
+				});
+				canvas.into()
+			},
+		);
+		let options = Object::new();
+		Reflect::set(&options, &"tileSize".into(), &OBJECT_POINTS_TILE_SIZE.into())?;
+		Reflect::set(&options, &"zIndex".into(), &OBJECT_POINTS_Z_INDEX.into())?;
+		let layer = object_points_grid_layer(create_tile.as_ref(), &options)?;
+		Ok(Self {
+			layer,
+			_create_tile: create_tile,
 		})
-		.collect_view()
+	}
 }
 
-/// Location markers to add to the map.
+impl Drop for ObjectPointsLayer {
+	fn drop(&mut self) {
+		self.layer.remove();
+	}
+}
+
+/// Location markers to add to the map: one per cluster of the objects in
+/// view made within `made_on_range`, refetched whenever the map stops moving
+/// or zooming or the range changes, over a dot for each of those objects.
 #[component]
-pub fn LocationMarkers() -> impl IntoView {
+pub fn LocationMarkers(#[prop(into)] made_on_range: Signal<MadeOnRange>) -> impl IntoView {
 	let config = match use_context::<AppConfig>() {
 		Some(c) => c,
 		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
 	};
-	let s3_objects_resource = LocalResource::new(move || {
-		crate::graphql_queries::run::<S3ObjectsQuery>(
-			config.api_url.clone(),
-			crate::graphql_queries::s3_objects::s3_objects_query::Variables {},
-		)
+	let map_context = use_leaflet_context();
+	let (current_viewport, set_current_viewport) = signal(None::<(BoundsInput, i64)>);
+	let points_layer = StoredValue::new_local(None::<ObjectPointsLayer>);
+	let points_api_url = config.api_url.clone();
+	Effect::new(move |_| {
+		if let Some(map) = map_context.as_ref().and_then(|context| context.map()) {
+			set_current_viewport.set(Some(viewport(&map)));
+			let moved = map.clone();
+			map.on_move_end(Box::new(move |_| set_current_viewport.set(Some(viewport(&moved)))));
+			if points_layer.with_value(Option::is_none) {
+				match ObjectPointsLayer::new(&points_api_url, made_on_range) {
+					Ok(layer) => {
+						layer.layer.add_to(&map);
+						points_layer.set_value(Some(layer));
+					}
+					Err(error) => debug_error!("Failed to add object points: {error}"),
+				}
+			}
+		}
+	});
+	Effect::new(move |_| {
+		made_on_range.track();
+		points_layer.with_value(|layer| {
+			if let Some(layer) = layer {
+				layer.layer.redraw();
+			}
+		});
+	});
+	let clusters_resource = LocalResource::new(move || {
+		let api_url = config.api_url.clone();
+		let current_viewport = current_viewport.get();
+		let made_on_range = made_on_range.get();
+		async move {
+			let clusters = match current_viewport {
+				Some((bounds, zoom)) =>
+					crate::graphql_queries::run::<MapClustersQuery>(
+						api_url,
+						Variables {
+							bounds,
+							zoom,
+							filter: made_on_range.filter(),
+						},
+					)
+					.await?,
+				None => Vec::new(),
+			};
+			Ok::<_, AppError>((clusters, made_on_range))
+		}
 	});
 	view! {
 		<ErrorBoundary fallback=|errors| {
 			debug_error!("Failed to load markers: {:?}", errors.get());
 			dump_errors(errors)
 		}>
-			<Suspense fallback=move || {
+			<Transition fallback=move || {
 				view! { <p>"Loading map data..."</p> }
 			}>
 				{move || {
-					s3_objects_resource
+					clusters_resource
 						.get()
-						.map(|data| { Ok::<_, Error>(view! { {render_markers(data?)} }) })
+						.map(|data| {
+							let (clusters, made_on_range) = data?;
+							Ok::<_, Error>(
+								clusters
+									.into_iter()
+									.map(|cluster| {
+										view! {
+											<ClusterMarker cluster made_on_range=made_on_range.clone() />
+										}
+									})
+									.collect_view(),
+							)
+						})
 				}}
-			</Suspense>
+			</Transition>
 		</ErrorBoundary>
 	}
 	.into_any()
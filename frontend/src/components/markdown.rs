@@ -0,0 +1,73 @@
+use {
+	leptos::prelude::*,
+	pulldown_cmark::{
+		CowStr,
+		Event,
+		Options,
+		Parser,
+		Tag,
+		html,
+	},
+};
+
+/// Whether a link or image may point at `url`: web and mail addresses, and
+/// ones relative to the page. Anything else, such as a `javascript:` URL, could
+/// run script when followed.
+fn is_safe_url(url: &str) -> bool {
+	match url.split_once(':') {
+		Some((scheme, _)) if !scheme.contains(['/', '?', '#']) =>
+			["http", "https", "mailto"].iter().any(|safe| scheme.eq_ignore_ascii_case(safe)),
+		_ => true,
+	}
+}
+
+/// `markdown` as HTML that is safe to insert into the page: raw HTML in it is
+/// shown as text, and links and images to unsafe URLs lead nowhere.
+pub fn render_markdown(markdown: &str) -> String {
+	let events = Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES)
+		.map(|event| match event {
+			Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+			Event::Start(Tag::Link {
+				link_type,
+				dest_url,
+				title,
+				id,
+			}) if !is_safe_url(&dest_url) => Event::Start(Tag::Link {
+				link_type,
+				dest_url: CowStr::Borrowed(""),
+				title,
+				id,
+			}),
+			Event::Start(Tag::Image {
+				link_type,
+				dest_url,
+				title,
+				id,
+			}) if !is_safe_url(&dest_url) => Event::Start(Tag::Image {
+				link_type,
+				dest_url: CowStr::Borrowed(""),
+				title,
+				id,
+			}),
+			event => event,
+		});
+	let mut rendered = String::new();
+	html::push_html(&mut rendered, events);
+	rendered
+}
+
+/// Markdown written by a user, such as an object's description, rendered
+/// with [`render_markdown`].
+#[component]
+pub fn Markdown(
+	/// The Markdown to render.
+	#[prop(into)]
+	text: Signal<String>,
+	#[prop(into, optional)] class: String,
+) -> impl IntoView {
+	// The CSS reset strips lists and links of their look, so it is put back.
+	let class = format!(
+		"grid gap-2 [&_a]:underline [&_ul]:list-disc [&_ol]:list-decimal [&_ul]:pl-6 [&_ol]:pl-6 {class}"
+	);
+	view! { <div class=class inner_html=move || text.with(|text| render_markdown(text)) /> }
+}
@@ -0,0 +1,192 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			BUTTON_UNDO,
+			ERR_SYSTEM_CONFIG_MISSING,
+			HEADER_LOCATION,
+			HEADER_MADE_ON,
+			LABEL_BY,
+			LABEL_DELETED_USER,
+			LABEL_FIELD_ALLOWED_GROUPS,
+			LABEL_FIELD_ALLOWED_USERS,
+			LABEL_FIELD_DESCRIPTION,
+			LABEL_FIELD_NOTE,
+			LABEL_FIELD_TAGS,
+			LABEL_NAME,
+			LABEL_NO_VALUE,
+			LABEL_OBJECT_LOCATION_PRECISION,
+			LABEL_OBJECT_METADATA_PRIVACY,
+			LABEL_PUBLICITY,
+			LABEL_REVISION,
+			LABEL_TITLE,
+			LOADING_TEXT,
+			MSG_FAILED_LOAD_HISTORY,
+			MSG_NO_HISTORY,
+		},
+		errors::{
+			use_context_safe,
+			use_error_context,
+		},
+		graphql_queries::{
+			object_history::{
+				ObjectHistoryQuery,
+				object_history_query,
+			},
+			revert_object::{
+				RevertObjectMutation,
+				revert_object_mutation,
+			},
+		},
+	},
+	leptos::{
+		prelude::*,
+		task::spawn_local,
+	},
+	thaw::*,
+};
+
+/// The label the edit form gives the `S3Object` field `field`.
+fn field_label(field: &str) -> &str {
+	match field {
+		"name" => LABEL_NAME,
+		"title" => LABEL_TITLE,
+		"description" => LABEL_FIELD_DESCRIPTION,
+		"note" => LABEL_FIELD_NOTE,
+		"madeOn" => HEADER_MADE_ON,
+		"location" => HEADER_LOCATION,
+		"publicity" => LABEL_PUBLICITY,
+		"metadataPrivacy" => LABEL_OBJECT_METADATA_PRIVACY,
+		"locationPrecision" => LABEL_OBJECT_LOCATION_PRECISION,
+		"allowedUsers" => LABEL_FIELD_ALLOWED_USERS,
+		"allowedGroups" => LABEL_FIELD_ALLOWED_GROUPS,
+		"tags" => LABEL_FIELD_TAGS,
+		field => field,
+	}
+}
+
+/// The edits made to an object's metadata, newest first, each with a button
+/// that undoes it.
+#[component]
+pub fn ObjectHistory(
+	/// The ID of the object.
+	#[prop(into)]
+	id: Signal<i64>,
+	/// Callback invoked after an edit is undone.
+	#[prop(into)]
+	on_revert: Callback<()>,
+) -> impl IntoView {
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let error_ctx = use_error_context();
+	let trigger = RwSignal::new(0usize);
+	let history_resource = LocalResource::new(move || {
+		trigger.get();
+		crate::graphql_queries::run::<ObjectHistoryQuery>(
+			config.with_value(|c| c.api_url.clone()),
+			object_history_query::Variables {
+				id: id.get().to_string(),
+			},
+		)
+	});
+
+	let reverting = RwSignal::new(false);
+	let on_undo = move |revision: i64| {
+		reverting.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			match crate::graphql_queries::run::<RevertObjectMutation>(
+				api_url,
+				revert_object_mutation::Variables {
+					id: id.get_untracked().to_string(),
+					revision,
+				},
+			)
+			.await
+			{
+				Ok(_) => on_revert.run(()),
+				Err(e) => error_ctx.report(e),
+			}
+			reverting.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	view! {
+		<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+			{move || {
+				history_resource
+					.get()
+					.map(|result| match result {
+						Err(e) => view! { <p>{MSG_FAILED_LOAD_HISTORY} {e.to_string()}</p> }.into_any(),
+						Ok(revisions) if revisions.is_empty() => {
+							view! { <p>{MSG_NO_HISTORY}</p> }.into_any()
+						}
+						Ok(revisions) => {
+							view! {
+								<ol class="grid gap-4">
+									{revisions
+										.into_iter()
+										.map(|revision| {
+											let number = revision.revision;
+											let editor = revision
+												.editor
+												.unwrap_or_else(|| LABEL_DELETED_USER.to_string());
+											view! {
+												<li class="grid gap-2 border-b pb-4">
+													<div class="flex justify-between items-center gap-4">
+														<p class="text-sm">
+															{format!(
+																"{LABEL_REVISION} {number}, {} {LABEL_BY} {editor}",
+																revision.created_at,
+															)}
+														</p>
+														<Button
+															disabled=reverting
+															on_click=move |_| on_undo(number)
+														>
+															{BUTTON_UNDO}
+														</Button>
+													</div>
+													<dl class="grid grid-cols-[auto_1fr] gap-x-4 gap-y-1 text-sm">
+														{revision
+															.changes
+															.into_iter()
+															.map(|change| {
+																view! {
+																	<dt class="font-bold">
+																		{field_label(&change.field).to_string()}
+																	</dt>
+																	<dd class="grid gap-1">
+																		<del class="text-gray-500 break-words">
+																			{change
+																				.old_value
+																				.unwrap_or_else(|| LABEL_NO_VALUE.to_string())}
+																		</del>
+																		<ins class="no-underline break-words">
+																			{change
+																				.new_value
+																				.unwrap_or_else(|| LABEL_NO_VALUE.to_string())}
+																		</ins>
+																	</dd>
+																}
+															})
+															.collect_view()}
+													</dl>
+												</li>
+											}
+										})
+										.collect_view()}
+								</ol>
+							}
+								.into_any()
+						}
+					})
+			}}
+		</Suspense>
+	}
+	.into_any()
+}
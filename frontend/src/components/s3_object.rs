@@ -7,32 +7,71 @@ use {
 	mime::Mime,
 };
 
+/// Which stored rendition of an image to display. Renditions are rendered in
+/// the background after upload, so each falls back to the original until ready.
+/// Videos with a poster show their thumbnail as a still image. Photos browsers
+/// cannot show, such as HEIC, are shown through their display rendition.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Rendition {
+	#[default]
+	Original,
+	/// A small thumbnail for grids and strips.
+	Thumbnail,
+	/// A screen-sized preview for the lightbox.
+	Preview,
+}
+
+impl Rendition {
+	fn image_url(
+		self,
+		s3_object: &S3ObjectsQueryS3Objects,
+	) -> String {
+		match self {
+			Rendition::Original => None,
+			Rendition::Thumbnail => s3_object.thumbnail_url.clone(),
+			Rendition::Preview => s3_object.preview_url.clone(),
+		}
+		.or_else(|| s3_object.display_url.clone())
+		.unwrap_or_else(|| s3_object.url.clone())
+	}
+}
+
 #[component]
 pub fn S3Object(
 	#[prop(into)] s3_object: Signal<Option<S3ObjectsQueryS3Objects>>,
 	#[prop(optional, into)] class: MaybeProp<String>,
+	#[prop(optional)] rendition: Rendition,
 ) -> impl IntoView {
 	move || {
 		if let Some(s3_object) = s3_object.get() {
+			let mut media_type = s3_object
+				.content_type
+				.parse::<Mime>()
+				.map(|m| m.type_().as_str().to_string())
+				.unwrap_or_default();
+			// A video's thumbnail is rendered from its poster, so show it as a
+			// still rather than loading the video.
+			if media_type == "video" &&
+				rendition == Rendition::Thumbnail &&
+				s3_object.thumbnail_url.is_some()
+			{
+				media_type = "image".to_string();
+			}
 			either!(
-				s3_object
-					.content_type
-					.parse::<Mime>()
-					.map(|m| m.type_().as_str().to_string())
-					.unwrap_or_default()
-					.as_str(),
+				media_type.as_str(),
 				"image" => view! {
 					<img
 						alt=s3_object.name.clone()
 						class=move || class.get()
-						src=move || s3_object.url.clone()
+						src=rendition.image_url(&s3_object)
 					/>
 				},
 				"video" | "audio" => view! {
 					<video
 						aria-label=s3_object.name.clone()
 						class=move || class.get()
-						src=move || s3_object.url.clone()
+						src=s3_object.url.clone()
+						poster=s3_object.poster_url.clone()
 						controls
 						autoplay
 					/>
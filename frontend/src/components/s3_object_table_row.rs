@@ -2,7 +2,10 @@ use {
 	crate::{
 		AppConfig,
 		CallbackAnyView,
-		components::s3_object::S3Object as S3ObjectComponent,
+		components::s3_object::{
+			Rendition,
+			S3Object as S3ObjectComponent,
+		},
 		constants::{
 			BUTTON_CANCEL,
 			BUTTON_CLOSE,
@@ -20,6 +23,7 @@ use {
 			OPTION_SELECTED_USERS,
 			PLACEHOLDER_ALLOWED_USERS,
 			TITLE_INVALID_EMAILS,
+			TITLE_SHOW_ON_MAP,
 			TITLE_SUCCESS,
 			TITLE_WARNING,
 		},
@@ -43,6 +47,7 @@ use {
 		prelude::*,
 		task::spawn_local,
 	},
+	leptos_router::components::A,
 	lucide_leptos::Users,
 	std::{
 		collections::HashSet,
@@ -107,9 +112,16 @@ pub fn S3ObjectTableRow(
 					made_on,
 					location,
 					publicity: new_publicity,
+					metadata_privacy: None,
+					location_precision: None,
 					allowed_users: new_allowed_users
 						.clone()
 						.or(Some(s3_object.allowed_users.clone())),
+					allowed_groups: None,
+					tags: None,
+					title: None,
+					description: None,
+					note: None,
 				},
 			};
 
@@ -258,7 +270,19 @@ pub fn S3ObjectTableRow(
 						.get()
 						.location
 						.map(|location| {
-							format!("{}, {}", location.latitude, location.longitude)
+							view! {
+								<A
+									href=format!(
+										"/?lat={}&lng={}",
+										location.latitude,
+										location.longitude,
+									)
+									attr:class="underline"
+									attr:title=TITLE_SHOW_ON_MAP
+								>
+									{format!("{}, {}", location.latitude, location.longitude)}
+								</A>
+							}
 						})
 				}}
 			</TableCell>
@@ -270,7 +294,11 @@ pub fn S3ObjectTableRow(
 						open_view.set(true);
 					}
 				>
-					<S3ObjectComponent s3_object=s3_object class="w-20 h-20 object-cover" />
+					<S3ObjectComponent
+						s3_object=s3_object
+						class="w-20 h-20 object-cover"
+						rendition=Rendition::Thumbnail
+					/>
 				</Button>
 			</TableCell>
 			<TableCell class="wrap-anywhere">{move || s3_object.get().content_type}</TableCell>
@@ -328,6 +356,7 @@ pub fn S3ObjectTableRow(
 												<S3ObjectComponent
 													s3_object=Signal::derive(move || obj.clone())
 													class="max-w-[80vw] max-h-[80vh]"
+													rendition=Rendition::Preview
 												/>
 											}
 										})
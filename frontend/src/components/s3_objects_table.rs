@@ -20,6 +20,7 @@ use {
 			MSG_CONFIRM_DELETE,
 			MSG_DELETE_FAILED,
 			MSG_DELETE_SUCCESS,
+			MSG_DELETE_TO_TRASH,
 		},
 		dump_errors,
 		errors::AppError,
@@ -243,6 +244,7 @@ pub fn S3ObjectsTable(
 												.join(", ")
 										}}"?"
 									</h2>
+									<p class="text-sm">{MSG_DELETE_TO_TRASH}</p>
 									<div class="relative grid gap-4 grid-flow-col">
 										<Button on_click=move |_| {
 											delete_objects(selected_objects.get());
@@ -0,0 +1,66 @@
+use {
+	crate::constants::{
+		ARIA_SEARCH,
+		PLACEHOLDER_SEARCH,
+	},
+	leptos::prelude::*,
+	leptos_router::hooks::use_navigate,
+	lucide_leptos::Search,
+};
+
+/// Percent-encodes `value` for a URL query string, leaving only the characters
+/// that never need it.
+fn encode_query_component(value: &str) -> String {
+	value
+		.bytes()
+		.map(|byte| {
+			if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+				char::from(byte).to_string()
+			} else {
+				format!("%{byte:02X}")
+			}
+		})
+		.collect()
+}
+
+/// A search field that opens the objects table on the objects matching what
+/// was typed.
+#[component]
+pub fn SearchBox() -> impl IntoView {
+	let navigate = use_navigate();
+	let query = RwSignal::new(String::new());
+	let on_submit = move |ev: leptos::ev::SubmitEvent| {
+		ev.prevent_default();
+		let search = query.get_untracked();
+		let search = search.trim();
+		if !search.is_empty() {
+			navigate(
+				&format!("/objects?search={}", encode_query_component(search)),
+				Default::default(),
+			);
+		}
+	};
+	view! {
+		<form
+			role="search"
+			class="pointer-events-auto relative grid grid-flow-col items-center gap-2"
+			on:submit=on_submit
+		>
+			<input
+				type="search"
+				class="rounded-full px-4 h-40px w-full max-w-[320px] bg-white/90 text-black"
+				placeholder=PLACEHOLDER_SEARCH
+				aria-label=ARIA_SEARCH
+				prop:value=move || query.get()
+				on:input=move |ev| query.set(event_target_value(&ev))
+			/>
+			<button
+				type="submit"
+				class="cursor-pointer rounded-full grid place-items-center w-40px aspect-square bg-#666"
+				aria-label=ARIA_SEARCH
+			>
+				<Search color="#fff" />
+			</button>
+		</form>
+	}
+}
@@ -0,0 +1,182 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			BUTTON_REVOKE,
+			BUTTON_REVOKE_OTHER_SESSIONS,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_SESSION_FROM,
+			LABEL_SESSION_LAST_SEEN,
+			LABEL_SESSION_SIGNED_IN,
+			LABEL_THIS_DEVICE,
+			LABEL_UNKNOWN_DEVICE,
+			LOADING_TEXT,
+			MSG_FAILED_LOAD_SESSIONS,
+		},
+		errors::{
+			use_context_safe,
+			use_error_context,
+		},
+		graphql_queries::{
+			my_sessions::{
+				MySessionsQuery,
+				Session,
+				my_sessions_query,
+			},
+			revoke_all_other_sessions::{
+				RevokeAllOtherSessionsMutation,
+				revoke_all_other_sessions_mutation,
+			},
+			revoke_session::{
+				RevokeSessionMutation,
+				revoke_session_mutation,
+			},
+		},
+	},
+	leptos::{
+		prelude::*,
+		task::spawn_local,
+	},
+	thaw::*,
+};
+
+/// A one-line account of when and where a session was used.
+fn describe(session: &Session) -> String {
+	let mut description = format!(
+		"{LABEL_SESSION_SIGNED_IN} {}, {LABEL_SESSION_LAST_SEEN} {}",
+		session.created_at, session.last_seen_at
+	);
+	if let Some(ip_address) = &session.ip_address {
+		description.push_str(&format!(" {LABEL_SESSION_FROM} {ip_address}"));
+	}
+	description
+}
+
+/// The signed-in user's sessions, most recently used first, with a button
+/// that signs out each other device and one that signs out all of them. The
+/// current session is left to the header's log out button.
+#[component]
+pub fn SessionList() -> impl IntoView {
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let error_ctx = use_error_context();
+	let trigger = RwSignal::new(0usize);
+	let sessions_resource = LocalResource::new(move || {
+		trigger.get();
+		crate::graphql_queries::run::<MySessionsQuery>(
+			config.with_value(|c| c.api_url.clone()),
+			my_sessions_query::Variables {},
+		)
+	});
+	let loading = RwSignal::new(false);
+
+	let on_revoke = move |session_id: String| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<RevokeSessionMutation>(
+				api_url,
+				revoke_session_mutation::Variables {
+					id: session_id,
+				},
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	let on_revoke_others = move |_| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<RevokeAllOtherSessionsMutation>(
+				api_url,
+				revoke_all_other_sessions_mutation::Variables {},
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	view! {
+		<div class="grid gap-4">
+			<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+				{move || {
+					sessions_resource
+						.get()
+						.map(|result| match result {
+							Err(e) => {
+								view! { <p>{MSG_FAILED_LOAD_SESSIONS} {e.to_string()}</p> }
+									.into_any()
+							}
+							Ok(sessions) => {
+								view! {
+									<ul class="grid gap-4">
+										{sessions
+											.into_iter()
+											.map(|session| {
+												view! {
+													<SessionItem
+														session
+														loading
+														on_revoke=Callback::new(on_revoke)
+													/>
+												}
+											})
+											.collect_view()}
+									</ul>
+								}
+									.into_any()
+							}
+						})
+				}}
+			</Suspense>
+			<Button class="w-fit" disabled=loading on_click=on_revoke_others>
+				{BUTTON_REVOKE_OTHER_SESSIONS}
+			</Button>
+		</div>
+	}
+	.into_any()
+}
+
+/// One session, marked if it is the one viewing the page and otherwise with a
+/// button that revokes it.
+#[component]
+fn SessionItem(
+	session: Session,
+	loading: RwSignal<bool>,
+	on_revoke: Callback<String>,
+) -> impl IntoView {
+	let id = session.id.clone();
+	let current = session.current;
+	let device = session.user_agent.clone().unwrap_or_else(|| LABEL_UNKNOWN_DEVICE.to_string());
+	view! {
+		<li class="flex justify-between items-center gap-4 border-b pb-4">
+			<div class="grid gap-1">
+				<p class="text-sm font-bold break-words">{device}</p>
+				<p class="text-sm break-words">{describe(&session)}</p>
+			</div>
+			{if current {
+				view! { <span class="text-sm italic">{LABEL_THIS_DEVICE}</span> }.into_any()
+			} else {
+				view! {
+					<Button disabled=loading on_click=move |_| on_revoke.run(id.clone())>
+						{BUTTON_REVOKE}
+					</Button>
+				}
+					.into_any()
+			}}
+		</li>
+	}
+}
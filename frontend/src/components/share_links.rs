@@ -0,0 +1,262 @@
+use {
+	crate::{
+		AppConfig,
+		components::password_input::PasswordInput,
+		constants::{
+			BUTTON_CREATE_SHARE_LINK,
+			BUTTON_REVOKE,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_SHARE_EXPIRES_AT,
+			LABEL_SHARE_LINK_CREATED,
+			LABEL_SHARE_LINK_EXPIRES,
+			LABEL_SHARE_LINK_HAS_PASSWORD,
+			LABEL_SHARE_LINK_INACTIVE,
+			LABEL_SHARE_LINK_VIEWS,
+			LABEL_SHARE_MAX_VIEWS,
+			LABEL_SHARE_PASSWORD,
+			LOADING_TEXT,
+			MSG_FAILED_LOAD_SHARE_LINKS,
+			MSG_NO_SHARE_LINKS,
+			MSG_SHARE_LINK_CREATED,
+		},
+		errors::{
+			use_context_safe,
+			use_error_context,
+		},
+		graphql_queries::{
+			create_share_link::{
+				CreateShareLinkInput,
+				CreateShareLinkMutation,
+				create_share_link_mutation,
+			},
+			revoke_share_link::{
+				RevokeShareLinkMutation,
+				revoke_share_link_mutation,
+			},
+			share_links::{
+				ShareLink,
+				ShareLinksQuery,
+				share_links_query,
+			},
+		},
+		js_date_value_to_iso,
+	},
+	leptos::{
+		prelude::*,
+		task::spawn_local,
+	},
+	thaw::*,
+};
+
+/// The address a visitor opens a share link at.
+fn share_url(token: &str) -> String {
+	let origin = window().location().origin().unwrap_or_default();
+	format!("{origin}/s/{token}")
+}
+
+/// A one-line account of a share link's limits and use.
+fn describe(link: &ShareLink) -> String {
+	let mut parts = vec![format!("{LABEL_SHARE_LINK_CREATED} {}", link.created_at)];
+	if let Some(expires_at) = &link.expires_at {
+		parts.push(format!("{LABEL_SHARE_LINK_EXPIRES} {expires_at}"));
+	}
+	parts.push(match link.max_views {
+		Some(max_views) => format!("{LABEL_SHARE_LINK_VIEWS} {} / {max_views}", link.view_count),
+		None => format!("{LABEL_SHARE_LINK_VIEWS} {}", link.view_count),
+	});
+	if link.has_password {
+		parts.push(LABEL_SHARE_LINK_HAS_PASSWORD.to_string());
+	}
+	if !link.active {
+		parts.push(LABEL_SHARE_LINK_INACTIVE.to_string());
+	}
+	parts.join(", ")
+}
+
+/// The links an object is shared by, newest first, with a form that creates
+/// another and a button that revokes each one still opening. A new link's
+/// address is shown once, as only a hash of its token is kept.
+#[component]
+pub fn ShareLinks(
+	/// The ID of the object.
+	#[prop(into)]
+	id: Signal<i64>
+) -> impl IntoView {
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let error_ctx = use_error_context();
+	let trigger = RwSignal::new(0usize);
+	let links_resource = LocalResource::new(move || {
+		trigger.get();
+		crate::graphql_queries::run::<ShareLinksQuery>(
+			config.with_value(|c| c.api_url.clone()),
+			share_links_query::Variables {
+				object_id: Some(id.get().to_string()),
+				album_id: None,
+			},
+		)
+	});
+
+	let expires_at = RwSignal::new(String::new());
+	let password = RwSignal::new(String::new());
+	let max_views = RwSignal::new(String::new());
+	let created_url = RwSignal::new(None::<String>);
+	let loading = RwSignal::new(false);
+
+	let on_create = move |_| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		let password = password.get();
+		let input = CreateShareLinkInput {
+			object_id: Some(id.get_untracked().to_string()),
+			album_id: None,
+			expires_at: js_date_value_to_iso(&expires_at.get()),
+			password: (!password.is_empty()).then_some(password),
+			max_views: max_views.get().trim().parse::<i64>().ok(),
+		};
+		spawn_local(async move {
+			match crate::graphql_queries::run::<CreateShareLinkMutation>(
+				api_url,
+				create_share_link_mutation::Variables {
+					input,
+				},
+			)
+			.await
+			{
+				Ok(created) => created_url.set(Some(share_url(&created.token))),
+				Err(e) => error_ctx.report(e),
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	let on_revoke = move |link_id: String| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<RevokeShareLinkMutation>(
+				api_url,
+				revoke_share_link_mutation::Variables {
+					id: link_id,
+				},
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	view! {
+		<div class="grid gap-4">
+			<div class="grid gap-4">
+				<label>
+					<div class="font-bold">{LABEL_SHARE_EXPIRES_AT}</div>
+					<input
+						type="datetime-local"
+						on:input=move |ev| expires_at.set(event_target_value(&ev))
+						prop:value=expires_at
+					/>
+				</label>
+				<label>
+					<div class="font-bold">{LABEL_SHARE_PASSWORD}</div>
+					<PasswordInput
+						value=password
+						placeholder=LABEL_SHARE_PASSWORD
+						disabled=loading
+					/>
+				</label>
+				<label>
+					<div class="font-bold">{LABEL_SHARE_MAX_VIEWS}</div>
+					<input
+						type="number"
+						min="1"
+						step="1"
+						on:input=move |ev| max_views.set(event_target_value(&ev))
+						prop:value=max_views
+					/>
+				</label>
+				<Button class="w-fit" disabled=loading on_click=on_create>
+					{BUTTON_CREATE_SHARE_LINK}
+				</Button>
+				{move || {
+					created_url
+						.get()
+						.map(|url| {
+							view! {
+								<div class="grid gap-1">
+									<p class="text-sm">{MSG_SHARE_LINK_CREATED}</p>
+									<input class="w-full" readonly prop:value=url />
+								</div>
+							}
+						})
+				}}
+			</div>
+			<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+				{move || {
+					links_resource
+						.get()
+						.map(|result| match result {
+							Err(e) => {
+								view! { <p>{MSG_FAILED_LOAD_SHARE_LINKS} {e.to_string()}</p> }
+									.into_any()
+							}
+							Ok(links) if links.is_empty() => {
+								view! { <p>{MSG_NO_SHARE_LINKS}</p> }.into_any()
+							}
+							Ok(links) => {
+								view! {
+									<ul class="grid gap-4">
+										{links
+											.into_iter()
+											.map(|link| {
+												view! {
+													<ShareLinkItem
+														link
+														loading
+														on_revoke=Callback::new(on_revoke)
+													/>
+												}
+											})
+											.collect_view()}
+									</ul>
+								}
+									.into_any()
+							}
+						})
+				}}
+			</Suspense>
+		</div>
+	}
+	.into_any()
+}
+
+/// One share link, with a button that revokes it unless it already is.
+#[component]
+fn ShareLinkItem(
+	link: ShareLink,
+	loading: RwSignal<bool>,
+	on_revoke: Callback<String>,
+) -> impl IntoView {
+	let id = link.id.clone();
+	let revocable = link.revoked_at.is_none();
+	view! {
+		<li class="flex justify-between items-center gap-4 border-b pb-4">
+			<p class="text-sm break-words">{describe(&link)}</p>
+			{revocable
+				.then(|| {
+					view! {
+						<Button disabled=loading on_click=move |_| on_revoke.run(id.clone())>
+							{BUTTON_REVOKE}
+						</Button>
+					}
+				})}
+		</li>
+	}
+}
@@ -0,0 +1,93 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			LABEL_TAGS,
+			PLACEHOLDER_TAGS,
+		},
+		errors::use_context_safe,
+		graphql_queries::tags::{
+			TagsQuery,
+			tags_query,
+		},
+	},
+	leptos::prelude::*,
+	std::sync::atomic::{
+		AtomicUsize,
+		Ordering,
+	},
+};
+
+/// Gives each tag input its own suggestion list.
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The tags in comma-separated `value`, without blanks. The server normalizes
+/// them further.
+pub fn split_tags(value: &str) -> Vec<String> {
+	value.split(',').map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect()
+}
+
+/// A comma-separated list of tags, suggesting the user's own tags as the last
+/// one is typed.
+#[component]
+pub fn TagInput(
+	/// The comma-separated tags.
+	value: RwSignal<String>
+) -> impl IntoView {
+	let api_url = use_context_safe::<AppConfig>("AppConfig").map(|config| config.api_url);
+	let list_id = format!("tag-suggestions-{}", NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed));
+
+	// Everything up to and including the last comma, and the tag after it.
+	let parts = Memo::new(move |_| {
+		value.with(|value| match value.rsplit_once(',') {
+			Some((done, last)) => (format!("{done}, "), last.trim().to_string()),
+			None => (String::new(), value.trim().to_string()),
+		})
+	});
+	let suggestions_resource = LocalResource::new(move || {
+		let (done, prefix) = parts.get();
+		let api_url = api_url.clone();
+		async move {
+			let Some(api_url) = api_url else {
+				return Vec::new();
+			};
+			if prefix.is_empty() {
+				return Vec::new();
+			}
+			crate::graphql_queries::run::<TagsQuery>(
+				api_url,
+				tags_query::Variables {
+					prefix,
+				},
+			)
+			.await
+			.map(|tags| tags.into_iter().map(|tag| format!("{done}{}", tag.name)).collect())
+			.unwrap_or_default()
+		}
+	});
+
+	view! {
+		<label>
+			<div class="font-bold">{LABEL_TAGS}</div>
+			<input
+				type="text"
+				name="tags"
+				list=list_id.clone()
+				autocomplete="off"
+				prop:value=value
+				on:input=move |ev| value.set(event_target_value(&ev))
+				placeholder=PLACEHOLDER_TAGS
+			/>
+			<datalist id=list_id>
+				{move || {
+					suggestions_resource
+						.get()
+						.unwrap_or_default()
+						.into_iter()
+						.map(|suggestion| view! { <option value=suggestion /> })
+						.collect_view()
+				}}
+			</datalist>
+		</label>
+	}
+}
@@ -0,0 +1,242 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			BUTTON_PAUSE_TIMELINE,
+			BUTTON_PLAY_TIMELINE,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_TIMELINE_FROM,
+			LABEL_TIMELINE_TO,
+			TIMELINE_PLAYBACK_STEP_MS,
+		},
+		dump_errors,
+		graphql_queries::{
+			map_clusters::S3ObjectFilter,
+			timeline_histogram::{
+				TimelineBucket,
+				TimelineBucketCount,
+				TimelineHistogramQuery,
+				timeline_histogram_query::Variables,
+			},
+		},
+	},
+	leptos::{
+		logging::debug_error,
+		prelude::*,
+	},
+	std::time::Duration,
+	thaw::Button,
+};
+
+/// The capture times the map is narrowed to. Unset ends are open, so the
+/// default covers every object, undated ones included.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MadeOnRange {
+	pub from: Option<String>,
+	pub to: Option<String>,
+}
+
+impl MadeOnRange {
+	/// The range as an object query filter.
+	pub fn filter(&self) -> S3ObjectFilter {
+		S3ObjectFilter {
+			owner_id: None,
+			content_type_family: None,
+			made_on_from: self.from.clone(),
+			made_on_to: self.to.clone(),
+			publicity: None,
+			has_location: None,
+			tags: None,
+		}
+	}
+}
+
+/// The range covering the buckets from `first` to `last`. Spanning every
+/// bucket leaves it open, so objects without a capture time still show.
+fn range_of(
+	buckets: &[TimelineBucketCount],
+	first: usize,
+	last: usize,
+) -> MadeOnRange {
+	if first == 0 && last + 1 >= buckets.len() {
+		return MadeOnRange::default();
+	}
+	MadeOnRange {
+		from: buckets.get(first).map(|bucket| bucket.start.clone()),
+		to: buckets.get(last).map(|bucket| bucket.latest_made_on.clone()),
+	}
+}
+
+/// The year and month of an RFC 3339 timestamp.
+fn month(timestamp: &str) -> &str {
+	timestamp.get(.. 7).unwrap_or(timestamp)
+}
+
+/// A histogram of when the objects were made, one bar per month, with sliders
+/// picking the months `range` is narrowed to. Playing slides the picked window
+/// forward a month at a time until it reaches the latest.
+#[component]
+fn TimelineControls(
+	buckets: Vec<TimelineBucketCount>,
+	range: RwSignal<MadeOnRange>,
+) -> impl IntoView {
+	let last_index = buckets.len().saturating_sub(1);
+	let max_count = buckets.iter().map(|bucket| bucket.count).max().unwrap_or(1).max(1);
+	let buckets = StoredValue::new(buckets);
+	let first = RwSignal::new(0_usize);
+	let last = RwSignal::new(last_index);
+	Effect::new(move |_| {
+		let (first, last) = (first.get(), last.get());
+		let next = buckets.with_value(|buckets| range_of(buckets, first, last));
+		if range.get_untracked() != next {
+			range.set(next);
+		}
+	});
+
+	let playback = StoredValue::new(None::<IntervalHandle>);
+	let playing = RwSignal::new(false);
+	let stop = move || {
+		if let Some(handle) = playback.get_value() {
+			handle.clear();
+		}
+		playback.set_value(None);
+		playing.set(false);
+	};
+	let step = move || {
+		if last.get_untracked() >= last_index {
+			stop();
+		} else {
+			first.update(|first| *first += 1);
+			last.update(|last| *last += 1);
+		}
+	};
+	let play = move |_| {
+		if playing.get_untracked() {
+			stop();
+			return;
+		}
+		// Start over from the earliest month once the window reached the end.
+		if last.get_untracked() >= last_index {
+			let width = last.get_untracked() - first.get_untracked();
+			first.set(0);
+			last.set(width.min(last_index));
+		}
+		match set_interval_with_handle(step, Duration::from_millis(TIMELINE_PLAYBACK_STEP_MS)) {
+			Ok(handle) => {
+				playback.set_value(Some(handle));
+				playing.set(true);
+			}
+			Err(error) => debug_error!("Failed to start timeline playback: {error:?}"),
+		}
+	};
+	on_cleanup(stop);
+
+	let label = move |index: usize| {
+		buckets.with_value(|buckets| {
+			buckets.get(index).map(|bucket| month(&bucket.start).to_string()).unwrap_or_default()
+		})
+	};
+	let bars = buckets.with_value(|buckets| {
+		buckets
+			.iter()
+			.enumerate()
+			.map(|(index, bucket)| {
+				let height = format!("height: {}%", bucket.count * 100 / max_count);
+				let title = format!("{}: {}", month(&bucket.start), bucket.count);
+				let class = move || {
+					if (first.get() ..= last.get()).contains(&index) {
+						"flex-1 min-h-px bg-blue-600"
+					} else {
+						"flex-1 min-h-px bg-gray-300"
+					}
+				};
+				view! { <div class=class style=height title=title></div> }
+			})
+			.collect_view()
+	});
+	view! {
+		<div class="grid gap-2">
+			<div class="flex items-end gap-px h-12">{bars}</div>
+			<label class="grid grid-cols-[4rem_1fr_5rem] items-center gap-2">
+				<span>{LABEL_TIMELINE_FROM}</span>
+				<input
+					type="range"
+					min="0"
+					max=last_index.to_string()
+					prop:value=move || first.get().to_string()
+					on:input=move |event| {
+						if let Ok(value) = event_target_value(&event).parse::<usize>() {
+							first.set(value);
+							last.update(|last| *last = (*last).max(value));
+						}
+					}
+				/>
+				<span>{move || label(first.get())}</span>
+			</label>
+			<label class="grid grid-cols-[4rem_1fr_5rem] items-center gap-2">
+				<span>{LABEL_TIMELINE_TO}</span>
+				<input
+					type="range"
+					min="0"
+					max=last_index.to_string()
+					prop:value=move || last.get().to_string()
+					on:input=move |event| {
+						if let Ok(value) = event_target_value(&event).parse::<usize>() {
+							last.set(value);
+							first.update(|first| *first = (*first).min(value));
+						}
+					}
+				/>
+				<span>{move || label(last.get())}</span>
+			</label>
+			<Button class="w-fit" on_click=play>
+				{move || if playing.get() { BUTTON_PAUSE_TIMELINE } else { BUTTON_PLAY_TIMELINE }}
+			</Button>
+		</div>
+	}
+}
+
+/// Lets the map be narrowed to a period: fetches how many objects were made
+/// each month and keeps `range` in step with the months picked.
+#[component]
+pub fn TimelineSlider(range: RwSignal<MadeOnRange>) -> impl IntoView {
+	let config = match use_context::<AppConfig>() {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let histogram_resource = LocalResource::new(move || {
+		let api_url = config.api_url.clone();
+		async move {
+			crate::graphql_queries::run::<TimelineHistogramQuery>(
+				api_url,
+				Variables {
+					bucket: TimelineBucket::MONTH,
+				},
+			)
+			.await
+		}
+	});
+	view! {
+		<ErrorBoundary fallback=|errors| {
+			debug_error!("Failed to load the timeline: {:?}", errors.get());
+			dump_errors(errors)
+		}>
+			<Transition fallback=move || {
+				view! { <p>"Loading timeline..."</p> }
+			}>
+				{move || {
+					histogram_resource
+						.get()
+						.map(|data| {
+							let buckets = data?;
+							Ok::<_, Error>(
+								(!buckets.is_empty())
+									.then(|| view! { <TimelineControls buckets range /> }),
+							)
+						})
+				}}
+			</Transition>
+		</ErrorBoundary>
+	}
+	.into_any()
+}
@@ -7,6 +7,10 @@ pub const LABEL_SET_DATE_TIME: &str = "Set date and time";
 pub const LABEL_SELECT_FILES: &str = "Select files to upload";
 pub const BUTTON_SUBMIT: &str = "Submit";
 pub const BUTTON_CANCEL: &str = "Cancel";
+pub const BUTTON_DONE: &str = "Done";
+pub const MSG_ALREADY_UPLOADED: &str =
+	"Some files are already in your library, so they were not uploaded again:";
+pub const LABEL_ALREADY_UPLOADED_AS: &str = "is already uploaded as";
 
 pub const LATITUDE_MIN: &str = "-90";
 pub const LATITUDE_MAX: &str = "90";
@@ -18,9 +22,16 @@ pub const MAP_TITLE: &str = "Map";
 pub const MAP_INITIAL_LAT: f64 = 51.505;
 pub const MAP_INITIAL_LNG: f64 = -0.09;
 pub const MAP_INITIAL_ZOOM: f64 = 3.0;
+// How far in the map starts when opened on an object's location.
+pub const MAP_FOCUS_ZOOM: f64 = 16.0;
 pub const TILE_LAYER_URL: &str = "https://tile.openstreetmap.org/{z}/{x}/{y}.png";
 pub const TILE_LAYER_ATTRIBUTION: &str =
 	"&copy; <a href=\"https://www.openstreetmap.org/copyright\">OpenStreetMap</a> contributors";
+pub const LABEL_TIMELINE_FROM: &str = "From";
+pub const LABEL_TIMELINE_TO: &str = "To";
+pub const BUTTON_PLAY_TIMELINE: &str = "Play";
+pub const BUTTON_PAUSE_TIMELINE: &str = "Pause";
+pub const TIMELINE_PLAYBACK_STEP_MS: u64 = 800;
 
 // Sign In
 pub const TITLE_SIGN_IN: &str = "Sign In";
@@ -55,7 +66,29 @@ pub const BUTTON_UPDATE_PASSWORD: &str = "Update Password";
 pub const MSG_EMAIL_UPDATED: &str = "Email updated successfully";
 pub const MSG_PASSWORD_UPDATED: &str = "Password updated successfully";
 pub const MSG_PUBLICITY_UPDATED: &str = "Default publicity updated successfully";
+pub const TITLE_METADATA_PRIVACY: &str = "Photo Metadata";
+pub const LABEL_METADATA_PRIVACY: &str = "Embedded Metadata Shown to Others";
+pub const MSG_METADATA_PRIVACY_UPDATED: &str = "Metadata privacy updated successfully";
+pub const OPTION_KEEP_METADATA: &str = "Everything";
+pub const OPTION_STRIP_LOCATION: &str = "Everything but the location";
+pub const OPTION_STRIP_ALL_METADATA: &str = "Nothing";
+pub const TITLE_LOCATION_PRECISION: &str = "Location Privacy";
+pub const LABEL_LOCATION_PRECISION: &str = "Location Shown to Others";
+pub const MSG_LOCATION_PRECISION_UPDATED: &str = "Location precision updated successfully";
+pub const OPTION_EXACT_LOCATION: &str = "Exact";
+pub const OPTION_APPROX_100M: &str = "Within about 100 m";
+pub const OPTION_APPROX_1KM: &str = "Within about 1 km";
+pub const OPTION_CITY: &str = "City only";
+pub const OPTION_HIDDEN_LOCATION: &str = "Hidden";
 pub const MSG_NEW_PASSWORDS_DO_NOT_MATCH: &str = "New passwords do not match";
+pub const TITLE_SESSIONS: &str = "Signed-in Devices";
+pub const BUTTON_REVOKE_OTHER_SESSIONS: &str = "Sign out everywhere else";
+pub const LABEL_THIS_DEVICE: &str = "This device";
+pub const LABEL_UNKNOWN_DEVICE: &str = "Unknown device";
+pub const LABEL_SESSION_SIGNED_IN: &str = "Signed in";
+pub const LABEL_SESSION_LAST_SEEN: &str = "last seen";
+pub const LABEL_SESSION_FROM: &str = "from";
+pub const MSG_FAILED_LOAD_SESSIONS: &str = "Failed to load your sessions: ";
 
 // Admin Users
 pub const TITLE_USERS: &str = "Users";
@@ -81,21 +114,58 @@ pub const BUTTON_ADD_OBJECT: &str = "Add Object";
 pub const TITLE_ADD_OBJECT: &str = "Add Object";
 pub const TITLE_EDIT_OBJECT: &str = "Edit Object";
 pub const BUTTON_CLOSE: &str = "Close";
+pub const LINK_CLEAR_SEARCH: &str = "Show all objects";
+pub const SEARCH_RESULTS_LIMIT: i64 = 100;
 
 // Objects Table
 pub const BUTTON_DELETE_SELECTED: &str = "Delete selected";
-pub const MSG_DELETE_SUCCESS: &str = "Deleted objects successfully";
+pub const MSG_DELETE_SUCCESS: &str = "Moved objects to the trash";
 pub const MSG_DELETE_FAILED: &str = "Failed to delete objects";
 pub const HEADER_SELECT: &str = "Select";
+pub const TITLE_SHOW_ON_MAP: &str = "Show on the map";
 pub const HEADER_NAME: &str = "Name";
 pub const HEADER_MADE_ON: &str = "Made On";
 pub const HEADER_LOCATION: &str = "Location";
 pub const HEADER_VIEW: &str = "View";
 pub const HEADER_CONTENT_TYPE: &str = "Content Type";
 pub const MSG_CONFIRM_DELETE: &str = "Are you sure you want to delete ";
+pub const MSG_DELETE_TO_TRASH: &str =
+	"Deleted objects go to the trash, where they can be restored until it is emptied.";
 pub const BUTTON_YES: &str = "Yes";
 pub const BUTTON_NO: &str = "No";
 
+// Duplicates
+pub const TITLE_DUPLICATES: &str = "Possible Duplicates";
+pub const BUTTON_KEEP_THIS: &str = "Keep this one";
+pub const BUTTON_KEEP_BOTH: &str = "Not duplicates";
+pub const LABEL_HASH_DISTANCE: &str = "Differing hash bits: ";
+pub const MSG_NO_DUPLICATES: &str = "No possible duplicates found.";
+pub const MSG_FAILED_LOAD_DUPLICATES: &str = "Failed to load possible duplicates";
+
+// Trash
+pub const TITLE_TRASH: &str = "Trash";
+pub const BUTTON_RESTORE: &str = "Restore";
+pub const BUTTON_EMPTY_TRASH: &str = "Empty trash";
+pub const LABEL_TRASHED_AT: &str = "Deleted ";
+pub const LABEL_PURGE_AT: &str = "Deleted for good ";
+pub const MSG_CONFIRM_EMPTY_TRASH: &str =
+	"Delete everything in the trash for good? This cannot be undone.";
+pub const MSG_TRASH_EMPTY: &str = "The trash is empty.";
+pub const MSG_FAILED_LOAD_TRASH: &str = "Failed to load the trash";
+
+// Groups
+pub const TITLE_GROUPS: &str = "Groups";
+pub const LABEL_NEW_GROUP_NAME: &str = "New group name";
+pub const PLACEHOLDER_GROUP_NAME: &str = "Family";
+pub const BUTTON_CREATE_GROUP: &str = "Create group";
+pub const BUTTON_DELETE_GROUP: &str = "Delete group";
+pub const PLACEHOLDER_GROUP_MEMBERS: &str = "user1@example.com, user2@example.com";
+pub const BUTTON_ADD_MEMBERS: &str = "Add members";
+pub const BUTTON_REMOVE: &str = "Remove";
+pub const MSG_NO_GROUPS: &str = "You have no groups yet.";
+pub const MSG_GROUP_NO_MEMBERS: &str = "No members yet.";
+pub const MSG_FAILED_LOAD_GROUPS: &str = "Failed to load your groups";
+
 // Errors
 pub const TITLE_404: &str = "Uh oh!";
 pub const MSG_404: &str = "We couldn't find that page!";
@@ -105,12 +175,17 @@ pub const MSG_403: &str = "You do not have permission to view this page.";
 // Header
 pub const LINK_MAP: &str = "Map";
 pub const LINK_OBJECTS: &str = "Objects";
+pub const LINK_DUPLICATES: &str = "Duplicates";
+pub const LINK_TRASH: &str = "Trash";
+pub const LINK_GROUPS: &str = "Groups";
 pub const LINK_ACCOUNT: &str = "Account";
 pub const LINK_USERS: &str = "Users";
 pub const BUTTON_LOGOUT: &str = "Log Out";
 pub const LINK_SIGN_IN: &str = "Sign In";
 pub const ARIA_CLOSE_MENU: &str = "Close menu";
 pub const ARIA_OPEN_MENU: &str = "Open menu";
+pub const ARIA_SEARCH: &str = "Search objects";
+pub const PLACEHOLDER_SEARCH: &str = "Search objects";
 
 // Edit Object Form
 pub const TITLE_INVALID_EMAILS: &str = "Invalid Emails";
@@ -124,10 +199,59 @@ pub const LABEL_NAME: &str = "Name";
 pub const LABEL_PUBLICITY: &str = "Publicity";
 pub const OPTION_DEFAULT: &str = "Default";
 pub const OPTION_SELECTED_USERS: &str = "Selected Users";
+pub const LABEL_OBJECT_METADATA_PRIVACY: &str = "Metadata Shown to Others";
+pub const LABEL_OBJECT_LOCATION_PRECISION: &str = "Location Shown to Others";
 pub const LABEL_ALLOWED_USERS: &str = "Allowed Users (comma separated emails)";
 pub const PLACEHOLDER_ALLOWED_USERS: &str = "user1@example.com, user2@example.com";
+pub const LABEL_ALLOWED_GROUPS: &str = "Allowed Groups";
+pub const LINK_MANAGE_GROUPS: &str = "Manage groups";
+pub const LABEL_TITLE: &str = "Title";
+pub const LABEL_DESCRIPTION: &str = "Description (Markdown)";
+pub const LABEL_NOTE: &str = "Private Note (Markdown, only you can see it)";
+pub const LABEL_TAGS: &str = "Tags (comma separated)";
+pub const PLACEHOLDER_TAGS: &str = "beach, summer holiday";
 pub const MSG_ERROR_LOADING_OBJECT: &str = "Error loading object: ";
 
+// Object History
+pub const TAB_DETAILS: &str = "Details";
+pub const TAB_HISTORY: &str = "History";
+pub const BUTTON_UNDO: &str = "Undo";
+pub const LABEL_REVISION: &str = "Revision";
+pub const LABEL_BY: &str = "by";
+pub const LABEL_DELETED_USER: &str = "a deleted user";
+pub const LABEL_NO_VALUE: &str = "(none)";
+pub const LABEL_FIELD_ALLOWED_USERS: &str = "Allowed Users";
+pub const LABEL_FIELD_ALLOWED_GROUPS: &str = "Allowed Groups";
+pub const LABEL_FIELD_DESCRIPTION: &str = "Description";
+pub const LABEL_FIELD_NOTE: &str = "Private Note";
+pub const LABEL_FIELD_TAGS: &str = "Tags";
+pub const MSG_NO_HISTORY: &str = "This object has not been edited yet.";
+pub const MSG_FAILED_LOAD_HISTORY: &str = "Failed to load the object's history: ";
+
+// Share Links
+pub const TAB_SHARING: &str = "Sharing";
+pub const LABEL_SHARE_EXPIRES_AT: &str = "Expires (optional)";
+pub const LABEL_SHARE_PASSWORD: &str = "Password (optional)";
+pub const LABEL_SHARE_MAX_VIEWS: &str = "Maximum views (optional)";
+pub const BUTTON_CREATE_SHARE_LINK: &str = "Create link";
+pub const BUTTON_REVOKE: &str = "Revoke";
+pub const MSG_SHARE_LINK_CREATED: &str = "Copy the link now, as it will not be shown again:";
+pub const MSG_NO_SHARE_LINKS: &str = "This object has not been shared by link yet.";
+pub const MSG_FAILED_LOAD_SHARE_LINKS: &str = "Failed to load the object's share links: ";
+pub const LABEL_SHARE_LINK_CREATED: &str = "Created";
+pub const LABEL_SHARE_LINK_EXPIRES: &str = "expires";
+pub const LABEL_SHARE_LINK_VIEWS: &str = "Views:";
+pub const LABEL_SHARE_LINK_HAS_PASSWORD: &str = "password protected";
+pub const LABEL_SHARE_LINK_INACTIVE: &str = "no longer opens";
+
+// Shared
+pub const TITLE_SHARED: &str = "Shared with you";
+pub const MSG_SHARE_PASSWORD_REQUIRED: &str = "This link is password protected.";
+pub const BUTTON_OPEN_SHARE_LINK: &str = "Open";
+pub const LABEL_SHARED_UNTIL: &str = "This link expires ";
+pub const BUTTON_VIEW_ALL: &str = "View all";
+pub const MSG_FAILED_OPEN_SHARE_LINK: &str = "Failed to open the link";
+
 pub const HEADER_HEIGHT: f64 = 100.0;
 pub const HEADER_LAYER_CLASSES: &str = "hide-on-scroll inset-0 h-100px w-dvw translate-y-[--hide-on-scroll-translate-y] group-[:not(.scrolling)]/page:transition-all";
 
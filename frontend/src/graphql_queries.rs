@@ -11,25 +11,51 @@ use {
 };
 
 pub mod abort_object_upload;
+pub mod add_user_group_members;
 pub mod admin_update_user;
 pub mod change_email;
 pub mod change_password;
 pub mod complete_object_upload;
 pub mod config;
-pub mod create_object_upload_session;
+pub mod create_deduplicated_object_upload_session;
+pub mod create_share_link;
+pub mod create_user_group;
 pub mod delete_s3_objects;
+pub mod delete_user_group;
+pub mod dismiss_duplicate_pair;
+pub mod empty_trash;
 pub mod login;
 pub mod logout;
+pub mod map_clusters;
 pub mod me;
+pub mod my_sessions;
+pub mod object_history;
+pub mod open_share_link;
+pub mod possible_duplicates;
 pub mod presign_object_upload_parts;
 pub mod register;
+pub mod remove_user_group_members;
 pub mod request_password_reset;
 pub mod reset_password;
+pub mod restore_s3_objects;
+pub mod revert_object;
+pub mod revoke_all_other_sessions;
+pub mod revoke_session;
+pub mod revoke_share_link;
 pub mod s3_object_by_id;
 pub mod s3_objects;
+pub mod s3_objects_in_bounds;
+pub mod search_s3_objects;
+pub mod share_links;
+pub mod tags;
+pub mod timeline_histogram;
+pub mod trashed_objects;
 pub mod types;
 pub mod update_s3_object;
+pub mod update_user_location_precision;
+pub mod update_user_metadata_privacy;
 pub mod update_user_publicity;
+pub mod user_groups;
 pub mod users;
 
 pub trait GraphqlOp: GraphQLQuery {
@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/addUserGroupMembers.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct AddUserGroupMembersMutation;
+
+use self::add_user_group_members_mutation::AddUserGroupMembersMutationAddUserGroupMembers as UpdatedUserGroup;
+
+impl GraphqlOp for AddUserGroupMembersMutation {
+	type Output = UpdatedUserGroup;
+
+	fn extract(data: add_user_group_members_mutation::ResponseData) -> Self::Output {
+		data.add_user_group_members
+	}
+}
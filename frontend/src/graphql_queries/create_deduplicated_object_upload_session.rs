@@ -0,0 +1,32 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/createDeduplicatedObjectUploadSession.graphql",
+	extern_enums("PublicityOverride"),
+	response_derives = "Clone,Debug"
+)]
+pub struct CreateDeduplicatedObjectUploadSessionMutation;
+
+pub use {
+	self::create_deduplicated_object_upload_session_mutation::{
+		CreateDeduplicatedObjectUploadSessionMutationCreateDeduplicatedObjectUploadSession as DeduplicatedObjectUpload,
+		CreateObjectUploadSessionInput,
+		LocationInput as UploadLocationInput,
+	},
+	crate::graphql_queries::types::PublicityOverride,
+};
+
+impl GraphqlOp for CreateDeduplicatedObjectUploadSessionMutation {
+	type Output = DeduplicatedObjectUpload;
+
+	fn extract(
+		data: create_deduplicated_object_upload_session_mutation::ResponseData
+	) -> Self::Output {
+		data.create_deduplicated_object_upload_session
+	}
+}
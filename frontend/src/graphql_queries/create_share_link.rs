@@ -0,0 +1,25 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/createShareLink.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct CreateShareLinkMutation;
+
+pub use self::create_share_link_mutation::{
+	CreateShareLinkInput,
+	CreateShareLinkMutationCreateShareLink as CreatedShareLink,
+};
+
+impl GraphqlOp for CreateShareLinkMutation {
+	type Output = CreatedShareLink;
+
+	fn extract(data: create_share_link_mutation::ResponseData) -> Self::Output {
+		data.create_share_link
+	}
+}
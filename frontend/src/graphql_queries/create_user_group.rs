@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/createUserGroup.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct CreateUserGroupMutation;
+
+use self::create_user_group_mutation::CreateUserGroupMutationCreateUserGroup as CreatedUserGroup;
+
+impl GraphqlOp for CreateUserGroupMutation {
+	type Output = CreatedUserGroup;
+
+	fn extract(data: create_user_group_mutation::ResponseData) -> Self::Output {
+		data.create_user_group
+	}
+}
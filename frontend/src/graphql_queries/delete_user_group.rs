@@ -0,0 +1,20 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/deleteUserGroup.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct DeleteUserGroupMutation;
+
+impl GraphqlOp for DeleteUserGroupMutation {
+	type Output = bool;
+
+	fn extract(data: delete_user_group_mutation::ResponseData) -> Self::Output {
+		data.delete_user_group
+	}
+}
@@ -0,0 +1,20 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/dismissDuplicatePair.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct DismissDuplicatePairMutation;
+
+impl GraphqlOp for DismissDuplicatePairMutation {
+	type Output = bool;
+
+	fn extract(data: dismiss_duplicate_pair_mutation::ResponseData) -> Self::Output {
+		data.dismiss_duplicate_pair
+	}
+}
@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/emptyTrash.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct EmptyTrashMutation;
+
+use self::empty_trash_mutation::EmptyTrashMutationEmptyTrash as PurgedS3Object;
+
+impl GraphqlOp for EmptyTrashMutation {
+	type Output = Vec<PurgedS3Object>;
+
+	fn extract(data: empty_trash_mutation::ResponseData) -> Self::Output {
+		data.empty_trash
+	}
+}
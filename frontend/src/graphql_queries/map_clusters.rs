@@ -0,0 +1,27 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/mapClusters.graphql",
+	response_derives = "Clone,Debug",
+	variables_derives = "Clone,Debug,PartialEq"
+)]
+pub struct MapClustersQuery;
+
+pub use self::map_clusters_query::{
+	BoundsInput,
+	MapClustersQueryMapClusters as MapCluster,
+	S3ObjectFilter,
+};
+
+impl GraphqlOp for MapClustersQuery {
+	type Output = Vec<MapCluster>;
+
+	fn extract(data: map_clusters_query::ResponseData) -> Self::Output {
+		data.map_clusters
+	}
+}
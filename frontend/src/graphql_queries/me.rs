@@ -10,12 +10,14 @@ use {
 #[graphql(
 	schema_path = "graphql/schema.json",
 	query_path = "graphql/me.graphql",
-	extern_enums("PublicityDefault", "UserRole"),
+	extern_enums("LocationPrecision", "MetadataPrivacy", "PublicityDefault", "UserRole"),
 	response_derives = "Clone,Debug,PartialEq"
 )]
 pub struct MeQuery;
 
 pub use crate::graphql_queries::types::{
+	LocationPrecision,
+	MetadataPrivacy,
 	PublicityDefault,
 	UserRole,
 };
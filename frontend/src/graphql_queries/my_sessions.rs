@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/mySessions.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct MySessionsQuery;
+
+pub use self::my_sessions_query::MySessionsQueryMySessions as Session;
+
+impl GraphqlOp for MySessionsQuery {
+	type Output = Vec<Session>;
+
+	fn extract(data: my_sessions_query::ResponseData) -> Self::Output {
+		data.my_sessions
+	}
+}
@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/objectHistory.graphql",
+	response_derives = "Clone,Debug,PartialEq"
+)]
+pub struct ObjectHistoryQuery;
+
+pub use self::object_history_query::ObjectHistoryQueryObjectHistory as ObjectRevision;
+
+impl GraphqlOp for ObjectHistoryQuery {
+	type Output = Vec<ObjectRevision>;
+
+	fn extract(data: object_history_query::ResponseData) -> Self::Output {
+		data.object_history
+	}
+}
@@ -0,0 +1,73 @@
+use {
+	crate::graphql_queries::{
+		GraphqlOp,
+		s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+	},
+	graphql_client::{
+		GraphQLQuery,
+		QueryBody,
+	},
+};
+
+/// Opens a share link. Implemented by hand rather than derived, like
+/// `S3ObjectsInBoundsQuery`, so the shared objects decode into the `s3Objects`
+/// object type the map's markers and galleries take.
+pub struct OpenShareLinkMutation;
+
+pub mod open_share_link_mutation {
+	use {
+		super::S3Object,
+		serde::{
+			Deserialize,
+			Serialize,
+		},
+	};
+
+	pub const OPERATION_NAME: &str = "OpenShareLinkMutation";
+	pub const QUERY: &str = include_str!("../../graphql/openShareLink.graphql");
+
+	#[derive(Clone, Debug, PartialEq, Serialize)]
+	pub struct Variables {
+		pub token: String,
+		pub password: Option<String>,
+	}
+
+	#[derive(Clone, Debug, Deserialize)]
+	#[serde(rename_all = "camelCase")]
+	pub struct SharedItems {
+		pub password_required: bool,
+		pub title: String,
+		pub description: String,
+		pub expires_at: Option<String>,
+		pub objects: Vec<S3Object>,
+	}
+
+	#[derive(Clone, Debug, Deserialize)]
+	pub struct ResponseData {
+		#[serde(rename = "openShareLink")]
+		pub open_share_link: SharedItems,
+	}
+}
+
+pub use self::open_share_link_mutation::SharedItems;
+
+impl GraphQLQuery for OpenShareLinkMutation {
+	type ResponseData = open_share_link_mutation::ResponseData;
+	type Variables = open_share_link_mutation::Variables;
+
+	fn build_query(variables: Self::Variables) -> QueryBody<Self::Variables> {
+		QueryBody {
+			variables,
+			query: open_share_link_mutation::QUERY,
+			operation_name: open_share_link_mutation::OPERATION_NAME,
+		}
+	}
+}
+
+impl GraphqlOp for OpenShareLinkMutation {
+	type Output = SharedItems;
+
+	fn extract(data: open_share_link_mutation::ResponseData) -> Self::Output {
+		data.open_share_link
+	}
+}
@@ -0,0 +1,25 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/possibleDuplicates.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct PossibleDuplicatesQuery;
+
+pub use self::possible_duplicates_query::{
+	DuplicateObject,
+	PossibleDuplicatesQueryPossibleDuplicates as DuplicatePair,
+};
+
+impl GraphqlOp for PossibleDuplicatesQuery {
+	type Output = Vec<DuplicatePair>;
+
+	fn extract(data: possible_duplicates_query::ResponseData) -> Self::Output {
+		data.possible_duplicates
+	}
+}
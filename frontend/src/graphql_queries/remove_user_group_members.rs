@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/removeUserGroupMembers.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct RemoveUserGroupMembersMutation;
+
+use self::remove_user_group_members_mutation::RemoveUserGroupMembersMutationRemoveUserGroupMembers as UpdatedUserGroup;
+
+impl GraphqlOp for RemoveUserGroupMembersMutation {
+	type Output = UpdatedUserGroup;
+
+	fn extract(data: remove_user_group_members_mutation::ResponseData) -> Self::Output {
+		data.remove_user_group_members
+	}
+}
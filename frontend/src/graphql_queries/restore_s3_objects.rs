@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/restoreS3Objects.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct RestoreS3ObjectsMutation;
+
+use self::restore_s3_objects_mutation::RestoreS3ObjectsMutationRestoreS3Objects as RestoredS3Object;
+
+impl GraphqlOp for RestoreS3ObjectsMutation {
+	type Output = Vec<RestoredS3Object>;
+
+	fn extract(data: restore_s3_objects_mutation::ResponseData) -> Self::Output {
+		data.restore_s3_objects
+	}
+}
@@ -0,0 +1,20 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/revertObject.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct RevertObjectMutation;
+
+impl GraphqlOp for RevertObjectMutation {
+	type Output = String;
+
+	fn extract(data: revert_object_mutation::ResponseData) -> Self::Output {
+		data.revert_object.id
+	}
+}
@@ -0,0 +1,20 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/revokeAllOtherSessions.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct RevokeAllOtherSessionsMutation;
+
+impl GraphqlOp for RevokeAllOtherSessionsMutation {
+	type Output = bool;
+
+	fn extract(data: revoke_all_other_sessions_mutation::ResponseData) -> Self::Output {
+		data.revoke_all_other_sessions
+	}
+}
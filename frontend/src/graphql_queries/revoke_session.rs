@@ -0,0 +1,20 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/revokeSession.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct RevokeSessionMutation;
+
+impl GraphqlOp for RevokeSessionMutation {
+	type Output = bool;
+
+	fn extract(data: revoke_session_mutation::ResponseData) -> Self::Output {
+		data.revoke_session
+	}
+}
@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/revokeShareLink.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct RevokeShareLinkMutation;
+
+use self::revoke_share_link_mutation::RevokeShareLinkMutationRevokeShareLink as RevokedShareLink;
+
+impl GraphqlOp for RevokeShareLinkMutation {
+	type Output = RevokedShareLink;
+
+	fn extract(data: revoke_share_link_mutation::ResponseData) -> Self::Output {
+		data.revoke_share_link
+	}
+}
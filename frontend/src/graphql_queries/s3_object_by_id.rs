@@ -10,12 +10,16 @@ use {
 #[graphql(
 	schema_path = "graphql/schema.json",
 	query_path = "graphql/s3ObjectById.graphql",
-	extern_enums("PublicityOverride"),
+	extern_enums("LocationPrecisionOverride", "MetadataPrivacyOverride", "PublicityOverride"),
 	response_derives = "Clone,Debug,Serialize,Deserialize"
 )]
 pub struct S3ObjectByIdQuery;
 
-pub use crate::graphql_queries::types::PublicityOverride;
+pub use crate::graphql_queries::types::{
+	LocationPrecisionOverride,
+	MetadataPrivacyOverride,
+	PublicityOverride,
+};
 
 impl GraphqlOp for S3ObjectByIdQuery {
 	type Output = S3Object;
@@ -0,0 +1,70 @@
+use {
+	crate::graphql_queries::{
+		GraphqlOp,
+		map_clusters::S3ObjectFilter,
+		s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+	},
+	graphql_client::{
+		GraphQLQuery,
+		QueryBody,
+	},
+};
+
+/// The objects in a map viewport. Implemented by hand rather than derived so the
+/// results decode into the `s3Objects` object type the map's markers and
+/// galleries take; the derive would generate an identical type of its own. The
+/// filter is the one `mapClusters` takes, so a cluster's objects are narrowed
+/// the way the cluster was.
+pub struct S3ObjectsInBoundsQuery;
+
+pub mod s3_objects_in_bounds_query {
+	use {
+		super::{
+			S3Object,
+			S3ObjectFilter,
+		},
+		serde::{
+			Deserialize,
+			Serialize,
+		},
+	};
+
+	pub const OPERATION_NAME: &str = "S3ObjectsInBoundsQuery";
+	pub const QUERY: &str = include_str!("../../graphql/s3ObjectsInBounds.graphql");
+
+	#[derive(Clone, Debug, PartialEq, Serialize)]
+	pub struct Variables {
+		pub south: f64,
+		pub west: f64,
+		pub north: f64,
+		pub east: f64,
+		pub filter: S3ObjectFilter,
+	}
+
+	#[derive(Clone, Debug, Deserialize)]
+	pub struct ResponseData {
+		#[serde(rename = "s3ObjectsInBounds")]
+		pub s3_objects_in_bounds: Vec<S3Object>,
+	}
+}
+
+impl GraphQLQuery for S3ObjectsInBoundsQuery {
+	type ResponseData = s3_objects_in_bounds_query::ResponseData;
+	type Variables = s3_objects_in_bounds_query::Variables;
+
+	fn build_query(variables: Self::Variables) -> QueryBody<Self::Variables> {
+		QueryBody {
+			variables,
+			query: s3_objects_in_bounds_query::QUERY,
+			operation_name: s3_objects_in_bounds_query::OPERATION_NAME,
+		}
+	}
+}
+
+impl GraphqlOp for S3ObjectsInBoundsQuery {
+	type Output = Vec<S3Object>;
+
+	fn extract(data: s3_objects_in_bounds_query::ResponseData) -> Self::Output {
+		data.s3_objects_in_bounds
+	}
+}
@@ -0,0 +1,66 @@
+use {
+	crate::graphql_queries::{
+		GraphqlOp,
+		s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+	},
+	graphql_client::{
+		GraphQLQuery,
+		QueryBody,
+	},
+};
+
+/// The first page of objects matching a search, best match first. Implemented
+/// by hand for the same reason as `S3ObjectsInBoundsQuery`: the results feed
+/// the objects table, which takes the `s3Objects` object type.
+pub struct SearchS3ObjectsQuery;
+
+pub mod search_s3_objects_query {
+	use {
+		super::S3Object,
+		serde::{
+			Deserialize,
+			Serialize,
+		},
+	};
+
+	pub const OPERATION_NAME: &str = "SearchS3ObjectsQuery";
+	pub const QUERY: &str = include_str!("../../graphql/searchS3Objects.graphql");
+
+	#[derive(Clone, Debug, PartialEq, Serialize)]
+	pub struct Variables {
+		pub query: String,
+		pub first: i64,
+	}
+
+	#[derive(Clone, Debug, Deserialize)]
+	pub struct SearchResults {
+		pub nodes: Vec<S3Object>,
+	}
+
+	#[derive(Clone, Debug, Deserialize)]
+	pub struct ResponseData {
+		#[serde(rename = "searchS3Objects")]
+		pub search_s3_objects: SearchResults,
+	}
+}
+
+impl GraphQLQuery for SearchS3ObjectsQuery {
+	type ResponseData = search_s3_objects_query::ResponseData;
+	type Variables = search_s3_objects_query::Variables;
+
+	fn build_query(variables: Self::Variables) -> QueryBody<Self::Variables> {
+		QueryBody {
+			variables,
+			query: search_s3_objects_query::QUERY,
+			operation_name: search_s3_objects_query::OPERATION_NAME,
+		}
+	}
+}
+
+impl GraphqlOp for SearchS3ObjectsQuery {
+	type Output = Vec<S3Object>;
+
+	fn extract(data: search_s3_objects_query::ResponseData) -> Self::Output {
+		data.search_s3_objects.nodes
+	}
+}
@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/shareLinks.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct ShareLinksQuery;
+
+pub use self::share_links_query::ShareLinksQueryShareLinks as ShareLink;
+
+impl GraphqlOp for ShareLinksQuery {
+	type Output = Vec<ShareLink>;
+
+	fn extract(data: share_links_query::ResponseData) -> Self::Output {
+		data.share_links
+	}
+}
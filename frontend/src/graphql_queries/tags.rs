@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/tags.graphql",
+	response_derives = "Clone,Debug,PartialEq"
+)]
+pub struct TagsQuery;
+
+pub use self::tags_query::TagsQueryTags as Tag;
+
+impl GraphqlOp for TagsQuery {
+	type Output = Vec<Tag>;
+
+	fn extract(data: tags_query::ResponseData) -> Self::Output {
+		data.tags
+	}
+}
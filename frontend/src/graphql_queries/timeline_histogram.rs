@@ -0,0 +1,26 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/timelineHistogram.graphql",
+	response_derives = "Clone,Debug,PartialEq",
+	variables_derives = "Clone,Debug,PartialEq"
+)]
+pub struct TimelineHistogramQuery;
+
+pub use self::timeline_histogram_query::{
+	TimelineBucket,
+	TimelineHistogramQueryTimelineHistogram as TimelineBucketCount,
+};
+
+impl GraphqlOp for TimelineHistogramQuery {
+	type Output = Vec<TimelineBucketCount>;
+
+	fn extract(data: timeline_histogram_query::ResponseData) -> Self::Output {
+		data.timeline_histogram
+	}
+}
@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/trashedObjects.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct TrashedObjectsQuery;
+
+pub use self::trashed_objects_query::TrashedObjectsQueryTrashedObjects as TrashedObject;
+
+impl GraphqlOp for TrashedObjectsQuery {
+	type Output = Vec<TrashedObject>;
+
+	fn extract(data: trashed_objects_query::ResponseData) -> Self::Output {
+		data.trashed_objects
+	}
+}
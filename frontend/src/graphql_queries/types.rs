@@ -78,6 +78,174 @@ impl std::str::FromStr for PublicityDefault {
 	}
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataPrivacy {
+	#[serde(rename = "KEEP")]
+	Keep,
+	#[serde(rename = "STRIP_LOCATION")]
+	StripLocation,
+	#[serde(rename = "STRIP_ALL")]
+	StripAll,
+}
+
+impl fmt::Display for MetadataPrivacy {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		match self {
+			MetadataPrivacy::Keep => write!(f, "Keep"),
+			MetadataPrivacy::StripLocation => write!(f, "Strip Location"),
+			MetadataPrivacy::StripAll => write!(f, "Strip All"),
+		}
+	}
+}
+
+impl std::str::FromStr for MetadataPrivacy {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Keep" => Ok(MetadataPrivacy::Keep),
+			"Strip Location" => Ok(MetadataPrivacy::StripLocation),
+			"Strip All" => Ok(MetadataPrivacy::StripAll),
+			_ => Err(()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataPrivacyOverride {
+	#[serde(rename = "DEFAULT")]
+	Default,
+	#[serde(rename = "KEEP")]
+	Keep,
+	#[serde(rename = "STRIP_LOCATION")]
+	StripLocation,
+	#[serde(rename = "STRIP_ALL")]
+	StripAll,
+}
+
+impl fmt::Display for MetadataPrivacyOverride {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		match self {
+			MetadataPrivacyOverride::Default => write!(f, "Default"),
+			MetadataPrivacyOverride::Keep => write!(f, "Keep"),
+			MetadataPrivacyOverride::StripLocation => write!(f, "Strip Location"),
+			MetadataPrivacyOverride::StripAll => write!(f, "Strip All"),
+		}
+	}
+}
+
+impl std::str::FromStr for MetadataPrivacyOverride {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Default" => Ok(MetadataPrivacyOverride::Default),
+			"Keep" => Ok(MetadataPrivacyOverride::Keep),
+			"Strip Location" => Ok(MetadataPrivacyOverride::StripLocation),
+			"Strip All" => Ok(MetadataPrivacyOverride::StripAll),
+			_ => Err(()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LocationPrecision {
+	#[serde(rename = "EXACT")]
+	Exact,
+	#[serde(rename = "APPROX_100M")]
+	Approx100M,
+	#[serde(rename = "APPROX_1KM")]
+	Approx1Km,
+	#[serde(rename = "CITY")]
+	City,
+	#[serde(rename = "HIDDEN")]
+	Hidden,
+}
+
+impl fmt::Display for LocationPrecision {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		match self {
+			LocationPrecision::Exact => write!(f, "Exact"),
+			LocationPrecision::Approx100M => write!(f, "Approx 100m"),
+			LocationPrecision::Approx1Km => write!(f, "Approx 1km"),
+			LocationPrecision::City => write!(f, "City"),
+			LocationPrecision::Hidden => write!(f, "Hidden"),
+		}
+	}
+}
+
+impl std::str::FromStr for LocationPrecision {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Exact" => Ok(LocationPrecision::Exact),
+			"Approx 100m" => Ok(LocationPrecision::Approx100M),
+			"Approx 1km" => Ok(LocationPrecision::Approx1Km),
+			"City" => Ok(LocationPrecision::City),
+			"Hidden" => Ok(LocationPrecision::Hidden),
+			_ => Err(()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LocationPrecisionOverride {
+	#[serde(rename = "DEFAULT")]
+	Default,
+	#[serde(rename = "EXACT")]
+	Exact,
+	#[serde(rename = "APPROX_100M")]
+	Approx100M,
+	#[serde(rename = "APPROX_1KM")]
+	Approx1Km,
+	#[serde(rename = "CITY")]
+	City,
+	#[serde(rename = "HIDDEN")]
+	Hidden,
+}
+
+impl fmt::Display for LocationPrecisionOverride {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		match self {
+			LocationPrecisionOverride::Default => write!(f, "Default"),
+			LocationPrecisionOverride::Exact => write!(f, "Exact"),
+			LocationPrecisionOverride::Approx100M => write!(f, "Approx 100m"),
+			LocationPrecisionOverride::Approx1Km => write!(f, "Approx 1km"),
+			LocationPrecisionOverride::City => write!(f, "City"),
+			LocationPrecisionOverride::Hidden => write!(f, "Hidden"),
+		}
+	}
+}
+
+impl std::str::FromStr for LocationPrecisionOverride {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Default" => Ok(LocationPrecisionOverride::Default),
+			"Exact" => Ok(LocationPrecisionOverride::Exact),
+			"Approx 100m" => Ok(LocationPrecisionOverride::Approx100M),
+			"Approx 1km" => Ok(LocationPrecisionOverride::Approx1Km),
+			"City" => Ok(LocationPrecisionOverride::City),
+			"Hidden" => Ok(LocationPrecisionOverride::Hidden),
+			_ => Err(()),
+		}
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UserRole {
 	#[serde(rename = "USER")]
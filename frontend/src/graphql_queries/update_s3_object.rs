@@ -10,12 +10,16 @@ use {
 #[graphql(
 	schema_path = "graphql/schema.json",
 	query_path = "graphql/updateS3Object.graphql",
-	extern_enums("PublicityOverride"),
+	extern_enums("LocationPrecisionOverride", "MetadataPrivacyOverride", "PublicityOverride"),
 	response_derives = "Clone,Debug"
 )]
 pub struct UpdateS3ObjectMutation;
 
-pub use crate::graphql_queries::types::PublicityOverride;
+pub use crate::graphql_queries::types::{
+	LocationPrecisionOverride,
+	MetadataPrivacyOverride,
+	PublicityOverride,
+};
 
 impl GraphqlOp for UpdateS3ObjectMutation {
 	type Output = S3Object;
@@ -0,0 +1,25 @@
+use {
+	crate::graphql_queries::{
+		GraphqlOp,
+		types::LocationPrecision,
+		update_user_location_precision::update_user_location_precision_mutation::UpdateUserLocationPrecisionMutationUpdateUserLocationPrecision as User,
+	},
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/updateUserLocationPrecision.graphql",
+	extern_enums("LocationPrecision"),
+	response_derives = "Clone,Debug"
+)]
+pub struct UpdateUserLocationPrecisionMutation;
+
+impl GraphqlOp for UpdateUserLocationPrecisionMutation {
+	type Output = User;
+
+	fn extract(data: update_user_location_precision_mutation::ResponseData) -> Self::Output {
+		data.update_user_location_precision
+	}
+}
@@ -0,0 +1,25 @@
+use {
+	crate::graphql_queries::{
+		GraphqlOp,
+		types::MetadataPrivacy,
+		update_user_metadata_privacy::update_user_metadata_privacy_mutation::UpdateUserMetadataPrivacyMutationUpdateUserMetadataPrivacy as User,
+	},
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/updateUserMetadataPrivacy.graphql",
+	extern_enums("MetadataPrivacy"),
+	response_derives = "Clone,Debug"
+)]
+pub struct UpdateUserMetadataPrivacyMutation;
+
+impl GraphqlOp for UpdateUserMetadataPrivacyMutation {
+	type Output = User;
+
+	fn extract(data: update_user_metadata_privacy_mutation::ResponseData) -> Self::Output {
+		data.update_user_metadata_privacy
+	}
+}
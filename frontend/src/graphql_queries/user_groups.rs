@@ -0,0 +1,22 @@
+use {
+	crate::graphql_queries::GraphqlOp,
+	graphql_client::GraphQLQuery,
+};
+
+#[derive(GraphQLQuery)]
+#[graphql(
+	schema_path = "graphql/schema.json",
+	query_path = "graphql/userGroups.graphql",
+	response_derives = "Clone,Debug"
+)]
+pub struct UserGroupsQuery;
+
+pub use self::user_groups_query::UserGroupsQueryUserGroups as UserGroup;
+
+impl GraphqlOp for UserGroupsQuery {
+	type Output = Vec<UserGroup>;
+
+	fn extract(data: user_groups_query::ResponseData) -> Self::Output {
+		data.user_groups
+	}
+}
@@ -12,11 +12,15 @@ use {
 		pages::{
 			account::Account,
 			admin::users::Users,
+			duplicates::Duplicates,
+			groups::Groups,
 			home::Home,
 			objects::Objects,
 			register::Register,
 			reset_password::ResetPassword,
+			shared::Shared,
 			sign_in::SignIn,
+			trash::Trash,
 		},
 	},
 	auth::UserContext,
@@ -75,6 +79,7 @@ pub mod constants;
 pub mod errors;
 pub mod graphql_queries;
 mod pages;
+mod vector_tile;
 
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct AppConfig {
@@ -270,6 +275,36 @@ pub fn App() -> impl IntoView {
 									}
 								}
 							/>
+							<Route
+								path=path!("/duplicates")
+								view=|| {
+									view! {
+										<ProtectedRoute>
+											<Duplicates />
+										</ProtectedRoute>
+									}
+								}
+							/>
+							<Route
+								path=path!("/trash")
+								view=|| {
+									view! {
+										<ProtectedRoute>
+											<Trash />
+										</ProtectedRoute>
+									}
+								}
+							/>
+							<Route
+								path=path!("/groups")
+								view=|| {
+									view! {
+										<ProtectedRoute>
+											<Groups />
+										</ProtectedRoute>
+									}
+								}
+							/>
 							<Route path=path!("/sign-in") view=SignIn />
 							<Route path=path!("/register") view=Register />
 							<Route
@@ -283,6 +318,7 @@ pub fn App() -> impl IntoView {
 								}
 							/>
 							<Route path=path!("/reset-password") view=ResetPassword />
+							<Route path=path!("/s/:token") view=Shared />
 							<Route
 								path=path!("/admin/users")
 								view=|| {
@@ -368,12 +404,6 @@ pub async fn post_graphql_with_auth<Q: graphql_client::GraphQLQuery, U: reqwest:
 	Ok(response_data)
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct LocationStrings {
-	pub latitude: String,
-	pub longitude: String,
-}
-
 pub fn dump_errors(errors: ArcRwSignal<Errors>) -> impl IntoView {
 	view! {
 		<h1>"Uh oh! Something went wrong!"</h1>
@@ -1,9 +1,13 @@
 pub mod account;
 pub mod admin;
+pub mod duplicates;
 pub mod forbidden;
+pub mod groups;
 pub mod home;
 pub mod not_found;
 pub mod objects;
 pub mod register;
 pub mod reset_password;
+pub mod shared;
 pub mod sign_in;
+pub mod trash;
@@ -2,7 +2,10 @@ use {
 	crate::{
 		AppConfig,
 		auth::UserContext,
-		components::password_input::PasswordInput,
+		components::{
+			password_input::PasswordInput,
+			session_list::SessionList,
+		},
 		constants::{
 			BUTTON_UPDATE_EMAIL,
 			BUTTON_UPDATE_PASSWORD,
@@ -10,19 +13,34 @@ use {
 			ERR_SYSTEM_USER_CONTEXT_MISSING_MSG,
 			LABEL_CONFIRM_NEW_PASSWORD,
 			LABEL_DEFAULT_PUBLICITY,
+			LABEL_LOCATION_PRECISION,
+			LABEL_METADATA_PRIVACY,
 			LABEL_NEW_EMAIL,
 			LABEL_NEW_PASSWORD,
 			LABEL_OLD_PASSWORD,
 			MSG_EMAIL_UPDATED,
+			MSG_LOCATION_PRECISION_UPDATED,
+			MSG_METADATA_PRIVACY_UPDATED,
 			MSG_NEW_PASSWORDS_DO_NOT_MATCH,
 			MSG_PASSWORD_UPDATED,
 			MSG_PUBLICITY_UPDATED,
+			OPTION_APPROX_1KM,
+			OPTION_APPROX_100M,
+			OPTION_CITY,
+			OPTION_EXACT_LOCATION,
+			OPTION_HIDDEN_LOCATION,
+			OPTION_KEEP_METADATA,
 			OPTION_PRIVATE,
 			OPTION_PUBLIC,
+			OPTION_STRIP_ALL_METADATA,
+			OPTION_STRIP_LOCATION,
 			TITLE_ACCOUNT_SETTINGS,
 			TITLE_CHANGE_EMAIL,
 			TITLE_CHANGE_PASSWORD,
 			TITLE_DEFAULT_PUBLICITY,
+			TITLE_LOCATION_PRECISION,
+			TITLE_METADATA_PRIVACY,
+			TITLE_SESSIONS,
 		},
 		errors::use_context_safe,
 		graphql_queries::{
@@ -34,7 +52,19 @@ use {
 				ChangePasswordMutation,
 				change_password_mutation,
 			},
-			me::PublicityDefault,
+			me::{
+				LocationPrecision,
+				MetadataPrivacy,
+				PublicityDefault,
+			},
+			update_user_location_precision::{
+				UpdateUserLocationPrecisionMutation,
+				update_user_location_precision_mutation,
+			},
+			update_user_metadata_privacy::{
+				UpdateUserMetadataPrivacyMutation,
+				update_user_metadata_privacy_mutation,
+			},
 			update_user_publicity::{
 				UpdateUserPublicityMutation,
 				update_user_publicity_mutation,
@@ -64,22 +94,32 @@ pub fn Account() -> impl IntoView {
 	let new_password = RwSignal::new(String::new());
 	let confirm_new_password = RwSignal::new(String::new());
 	let default_publicity = RwSignal::new(PublicityDefault::Private);
+	let metadata_privacy = RwSignal::new(MetadataPrivacy::Keep);
+	let location_precision = RwSignal::new(LocationPrecision::Exact);
 
 	let email_message = RwSignal::new(Option::<String>::None);
 	let password_message = RwSignal::new(Option::<String>::None);
 	let publicity_message = RwSignal::new(Option::<String>::None);
+	let metadata_privacy_message = RwSignal::new(Option::<String>::None);
+	let location_precision_message = RwSignal::new(Option::<String>::None);
 	let email_error = RwSignal::new(Option::<String>::None);
 	let password_error = RwSignal::new(Option::<String>::None);
 	let publicity_error = RwSignal::new(Option::<String>::None);
+	let metadata_privacy_error = RwSignal::new(Option::<String>::None);
+	let location_precision_error = RwSignal::new(Option::<String>::None);
 
 	let is_email_loading = RwSignal::new(false);
 	let is_password_loading = RwSignal::new(false);
 	let is_publicity_loading = RwSignal::new(false);
+	let is_metadata_privacy_loading = RwSignal::new(false);
+	let is_location_precision_loading = RwSignal::new(false);
 
 	Effect::new(move |_| {
 		if let Some(Some(user)) = user_ctx.user.get() {
 			email.set(user.email);
 			default_publicity.set(user.default_publicity);
+			metadata_privacy.set(user.metadata_privacy);
+			location_precision.set(user.location_precision);
 		}
 	});
 
@@ -164,6 +204,68 @@ pub fn Account() -> impl IntoView {
 		}
 	};
 
+	let on_change_metadata_privacy = move |ev| {
+		let val = event_target_value(&ev);
+		if let Ok(new_privacy) = val.parse::<MetadataPrivacy>() {
+			metadata_privacy.set(new_privacy.clone());
+
+			is_metadata_privacy_loading.set(true);
+			let api_url = config.with_value(|c| c.api_url.clone());
+			spawn_local(async move {
+				let variables = update_user_metadata_privacy_mutation::Variables {
+					metadata_privacy: new_privacy,
+				};
+				match crate::graphql_queries::run::<UpdateUserMetadataPrivacyMutation>(
+					api_url, variables,
+				)
+				.await
+				{
+					Ok(_) => {
+						metadata_privacy_message
+							.set(Some(MSG_METADATA_PRIVACY_UPDATED.to_string()));
+						metadata_privacy_error.set(None);
+					}
+					Err(e) => {
+						metadata_privacy_error.set(Some(e.to_string()));
+						metadata_privacy_message.set(None);
+					}
+				}
+				is_metadata_privacy_loading.set(false);
+			});
+		}
+	};
+
+	let on_change_location_precision = move |ev| {
+		let val = event_target_value(&ev);
+		if let Ok(new_precision) = val.parse::<LocationPrecision>() {
+			location_precision.set(new_precision.clone());
+
+			is_location_precision_loading.set(true);
+			let api_url = config.with_value(|c| c.api_url.clone());
+			spawn_local(async move {
+				let variables = update_user_location_precision_mutation::Variables {
+					location_precision: new_precision,
+				};
+				match crate::graphql_queries::run::<UpdateUserLocationPrecisionMutation>(
+					api_url, variables,
+				)
+				.await
+				{
+					Ok(_) => {
+						location_precision_message
+							.set(Some(MSG_LOCATION_PRECISION_UPDATED.to_string()));
+						location_precision_error.set(None);
+					}
+					Err(e) => {
+						location_precision_error.set(Some(e.to_string()));
+						location_precision_message.set(None);
+					}
+				}
+				is_location_precision_loading.set(false);
+			});
+		}
+	};
+
 	view! {
 		<div class="grid gap-4 place-items-center h-full pt-10 gap-10">
 			<h1 class="text-2xl font-bold">{TITLE_ACCOUNT_SETTINGS}</h1>
@@ -191,6 +293,56 @@ pub fn Account() -> impl IntoView {
 				</Show>
 			</div>
 
+			// Metadata Privacy
+			<div class="grid gap-4 w-full max-w-md p-4 bg-white rounded shadow-md border border-gray-200">
+				<h2 class="text-xl font-bold">{TITLE_METADATA_PRIVACY}</h2>
+				<label class="grid gap-2">
+					<div class="block text-gray-700 text-sm font-bold">{LABEL_METADATA_PRIVACY}</div>
+					<select
+						class="p-2 border rounded bg-white w-full"
+						on:change=on_change_metadata_privacy
+						prop:value=move || metadata_privacy.get().to_string()
+						disabled=is_metadata_privacy_loading
+					>
+						<option value="Keep">{OPTION_KEEP_METADATA}</option>
+						<option value="Strip Location">{OPTION_STRIP_LOCATION}</option>
+						<option value="Strip All">{OPTION_STRIP_ALL_METADATA}</option>
+					</select>
+				</label>
+				<Show when=move || metadata_privacy_message.with(Option::is_some)>
+					<p class="text-green-500 text-xs italic">{metadata_privacy_message}</p>
+				</Show>
+				<Show when=move || metadata_privacy_error.with(Option::is_some)>
+					<p class="text-red-500 text-xs italic">{metadata_privacy_error}</p>
+				</Show>
+			</div>
+
+			// Location Precision
+			<div class="grid gap-4 w-full max-w-md p-4 bg-white rounded shadow-md border border-gray-200">
+				<h2 class="text-xl font-bold">{TITLE_LOCATION_PRECISION}</h2>
+				<label class="grid gap-2">
+					<div class="block text-gray-700 text-sm font-bold">{LABEL_LOCATION_PRECISION}</div>
+					<select
+						class="p-2 border rounded bg-white w-full"
+						on:change=on_change_location_precision
+						prop:value=move || location_precision.get().to_string()
+						disabled=is_location_precision_loading
+					>
+						<option value="Exact">{OPTION_EXACT_LOCATION}</option>
+						<option value="Approx 100m">{OPTION_APPROX_100M}</option>
+						<option value="Approx 1km">{OPTION_APPROX_1KM}</option>
+						<option value="City">{OPTION_CITY}</option>
+						<option value="Hidden">{OPTION_HIDDEN_LOCATION}</option>
+					</select>
+				</label>
+				<Show when=move || location_precision_message.with(Option::is_some)>
+					<p class="text-green-500 text-xs italic">{location_precision_message}</p>
+				</Show>
+				<Show when=move || location_precision_error.with(Option::is_some)>
+					<p class="text-red-500 text-xs italic">{location_precision_error}</p>
+				</Show>
+			</div>
+
 			// Change Email
 			<div class="grid gap-4 w-full max-w-md p-4 bg-white rounded shadow-md border border-gray-200">
 				<h2 class="text-xl font-bold">{TITLE_CHANGE_EMAIL}</h2>
@@ -254,6 +406,12 @@ pub fn Account() -> impl IntoView {
 					{BUTTON_UPDATE_PASSWORD}
 				</Button>
 			</div>
+
+			// Sessions
+			<div class="grid gap-4 w-full max-w-md p-4 bg-white rounded shadow-md border border-gray-200">
+				<h2 class="text-xl font-bold">{TITLE_SESSIONS}</h2>
+				<SessionList />
+			</div>
 		</div>
 	}
 	.into_any()
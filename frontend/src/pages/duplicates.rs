@@ -0,0 +1,218 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			BUTTON_KEEP_BOTH,
+			BUTTON_KEEP_THIS,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_HASH_DISTANCE,
+			LOADING_TEXT,
+			MSG_FAILED_LOAD_DUPLICATES,
+			MSG_NO_DUPLICATES,
+			TITLE_DUPLICATES,
+		},
+		errors::{
+			AppError,
+			use_context_safe,
+			use_error_context,
+		},
+		graphql_queries::{
+			delete_s3_objects::{
+				DeleteS3ObjectsMutation,
+				delete_s3_objects_mutation,
+			},
+			dismiss_duplicate_pair::{
+				DismissDuplicatePairMutation,
+				dismiss_duplicate_pair_mutation,
+			},
+			possible_duplicates::{
+				DuplicateObject,
+				PossibleDuplicatesQuery,
+				possible_duplicates_query,
+			},
+		},
+	},
+	leptos::{
+		prelude::*,
+		task::spawn_local,
+	},
+	thaw::*,
+};
+
+/// How a reviewed pair is resolved.
+#[derive(Clone, Debug)]
+enum Resolution {
+	/// Delete the object with this id and keep the other.
+	Delete(String),
+	/// Keep both and stop offering the pair.
+	KeepBoth { first_id: String, second_id: String },
+}
+
+/// The duplicates review page.
+///
+/// Lists pairs of the user's images that look alike, side by side, so one of
+/// each can be deleted or the pair marked as not duplicates.
+#[component]
+pub fn Duplicates() -> impl IntoView {
+	let trigger = RwSignal::new(0usize);
+	let error_ctx = use_error_context();
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let duplicates_resource = LocalResource::new(move || {
+		trigger.get();
+		crate::graphql_queries::run::<PossibleDuplicatesQuery>(
+			config.with_value(|c| c.api_url.clone()),
+			possible_duplicates_query::Variables {},
+		)
+	});
+
+	let on_resolve = move |resolution: Resolution, loading: RwSignal<bool>| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			let result = match resolution {
+				Resolution::Delete(id) => crate::graphql_queries::run::<DeleteS3ObjectsMutation>(
+					api_url,
+					delete_s3_objects_mutation::Variables {
+						ids: vec![id],
+					},
+				)
+				.await
+				.map(|_| ()),
+				Resolution::KeepBoth {
+					first_id,
+					second_id,
+				} => crate::graphql_queries::run::<DismissDuplicatePairMutation>(
+					api_url,
+					dismiss_duplicate_pair_mutation::Variables {
+						first_id,
+						second_id,
+					},
+				)
+				.await
+				.map(|_| ()),
+			};
+			if let Err(e) = result {
+				error_ctx.report(AppError::GraphQL(e.to_string()));
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+	let on_resolve = StoredValue::new(on_resolve);
+
+	view! {
+		<div class="container mx-auto pt-10">
+			<h1 class="text-2xl font-bold mb-4">{TITLE_DUPLICATES}</h1>
+			<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+				{move || {
+					duplicates_resource
+						.get()
+						.map(|res| match res {
+							Err(e) => {
+								view! {
+									<div class="p-4 bg-red-100 text-red-700 rounded">
+										<p class="font-bold">{MSG_FAILED_LOAD_DUPLICATES}</p>
+										<p>{e.to_string()}</p>
+									</div>
+								}
+									.into_any()
+							}
+							Ok(pairs) if pairs.is_empty() => {
+								view! { <p>{MSG_NO_DUPLICATES}</p> }.into_any()
+							}
+							Ok(pairs) => {
+								pairs
+									.into_iter()
+									.map(|pair| {
+										let is_loading = RwSignal::new(false);
+										let keep_both = Resolution::KeepBoth {
+											first_id: pair.first.id.clone(),
+											second_id: pair.second.id.clone(),
+										};
+										view! {
+											<section class="mb-8 grid gap-4">
+												<p class="text-sm">
+													{format!("{LABEL_HASH_DISTANCE}{}", pair.distance)}
+												</p>
+												<div class="grid grid-cols-2 gap-4">
+													<DuplicateCandidate
+														candidate=pair.first.clone()
+														other_id=pair.second.id.clone()
+														loading=is_loading
+														on_resolve=Callback::new(move |resolution| {
+															on_resolve.with_value(|f| f(resolution, is_loading))
+														})
+													/>
+													<DuplicateCandidate
+														candidate=pair.second.clone()
+														other_id=pair.first.id.clone()
+														loading=is_loading
+														on_resolve=Callback::new(move |resolution| {
+															on_resolve.with_value(|f| f(resolution, is_loading))
+														})
+													/>
+												</div>
+												<Button
+													class="justify-self-center"
+													disabled=is_loading
+													on_click=move |_| {
+														let keep_both = keep_both.clone();
+														on_resolve.with_value(|f| f(keep_both, is_loading))
+													}
+												>
+													{BUTTON_KEEP_BOTH}
+												</Button>
+											</section>
+										}
+									})
+									.collect_view()
+									.into_any()
+							}
+						})
+				}}
+			</Suspense>
+		</div>
+	}
+	.into_any()
+}
+
+/// One image of a pair, with a button that keeps it and deletes the other.
+#[component]
+fn DuplicateCandidate(
+	candidate: DuplicateObject,
+	/// The id of the other image in the pair, deleted when this one is kept.
+	other_id: String,
+	loading: RwSignal<bool>,
+	on_resolve: Callback<Resolution>,
+) -> impl IntoView {
+	let details = match &candidate.made_on {
+		Some(made_on) => format!("{} ({made_on})", candidate.content_type),
+		None => candidate.content_type.clone(),
+	};
+	view! {
+		<figure class="grid gap-2 justify-items-center">
+			<a href=candidate.url.clone() target="_blank" rel="noopener noreferrer">
+				<img
+					class="max-h-64 object-contain"
+					alt=candidate.name.clone()
+					src=candidate.thumbnail_url.clone().unwrap_or_else(|| candidate.url.clone())
+				/>
+			</a>
+			<figcaption class="text-center break-all">
+				<p>{candidate.name.clone()}</p>
+				<p class="text-sm">{details}</p>
+			</figcaption>
+			<Button
+				appearance=ButtonAppearance::Primary
+				disabled=loading
+				on_click=move |_| on_resolve.run(Resolution::Delete(other_id.clone()))
+			>
+				{BUTTON_KEEP_THIS}
+			</Button>
+		</figure>
+	}
+}
@@ -0,0 +1,297 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			BUTTON_ADD_MEMBERS,
+			BUTTON_CREATE_GROUP,
+			BUTTON_DELETE_GROUP,
+			BUTTON_REMOVE,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_NEW_GROUP_NAME,
+			LOADING_TEXT,
+			MSG_FAILED_LOAD_GROUPS,
+			MSG_GROUP_NO_MEMBERS,
+			MSG_NO_GROUPS,
+			PLACEHOLDER_GROUP_MEMBERS,
+			PLACEHOLDER_GROUP_NAME,
+			TITLE_GROUPS,
+		},
+		errors::{
+			use_context_safe,
+			use_error_context,
+		},
+		graphql_queries::{
+			add_user_group_members::{
+				AddUserGroupMembersMutation,
+				add_user_group_members_mutation,
+			},
+			create_user_group::{
+				CreateUserGroupMutation,
+				create_user_group_mutation,
+			},
+			delete_user_group::{
+				DeleteUserGroupMutation,
+				delete_user_group_mutation,
+			},
+			remove_user_group_members::{
+				RemoveUserGroupMembersMutation,
+				remove_user_group_members_mutation,
+			},
+			user_groups::{
+				UserGroup,
+				UserGroupsQuery,
+				user_groups_query,
+			},
+		},
+	},
+	leptos::{
+		prelude::*,
+		task::spawn_local,
+	},
+	thaw::*,
+};
+
+/// The groups page.
+///
+/// Lists the user's groups with their members, so groups can be created and
+/// deleted and members added or removed. Objects and albums shared with a
+/// group are shared with whoever is in it at the time.
+#[component]
+pub fn Groups() -> impl IntoView {
+	let trigger = RwSignal::new(0usize);
+	let error_ctx = use_error_context();
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let groups_resource = LocalResource::new(move || {
+		trigger.get();
+		crate::graphql_queries::run::<UserGroupsQuery>(
+			config.with_value(|c| c.api_url.clone()),
+			user_groups_query::Variables {},
+		)
+	});
+
+	let loading = RwSignal::new(false);
+	let name = RwSignal::new(String::new());
+	let on_create = move |_| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		let variables = create_user_group_mutation::Variables {
+			name: name.get(),
+		};
+		spawn_local(async move {
+			match crate::graphql_queries::run::<CreateUserGroupMutation>(api_url, variables).await {
+				Ok(_) => name.set(String::new()),
+				Err(e) => error_ctx.report(e),
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	view! {
+		<div class="container mx-auto pt-10 grid gap-4">
+			<h1 class="text-2xl font-bold">{TITLE_GROUPS}</h1>
+			<div class="flex items-end gap-4">
+				<label class="grow">
+					<div class="font-bold">{LABEL_NEW_GROUP_NAME}</div>
+					<input
+						class="w-full"
+						type="text"
+						placeholder=PLACEHOLDER_GROUP_NAME
+						prop:value=name
+						on:input=move |ev| name.set(event_target_value(&ev))
+					/>
+				</label>
+				<Button disabled=loading on_click=on_create>
+					{BUTTON_CREATE_GROUP}
+				</Button>
+			</div>
+			<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+				{move || {
+					groups_resource
+						.get()
+						.map(|res| match res {
+							Err(e) => {
+								view! {
+									<div class="p-4 bg-red-100 text-red-700 rounded">
+										<p class="font-bold">{MSG_FAILED_LOAD_GROUPS}</p>
+										<p>{e.to_string()}</p>
+									</div>
+								}
+									.into_any()
+							}
+							Ok(groups) if groups.is_empty() => {
+								view! { <p>{MSG_NO_GROUPS}</p> }.into_any()
+							}
+							Ok(groups) => {
+								view! {
+									<ul class="grid gap-4">
+										{groups
+											.into_iter()
+											.map(|group| view! { <GroupItem group loading trigger /> })
+											.collect_view()}
+									</ul>
+								}
+									.into_any()
+							}
+						})
+				}}
+			</Suspense>
+		</div>
+	}
+	.into_any()
+}
+
+/// One group with its members, each with a button that removes them, and a
+/// form that adds more.
+#[component]
+fn GroupItem(
+	group: UserGroup,
+	loading: RwSignal<bool>,
+	/// Bumped to reload the groups after a change.
+	trigger: RwSignal<usize>,
+) -> impl IntoView {
+	let error_ctx = use_error_context();
+	let api_url =
+		use_context_safe::<AppConfig>("AppConfig").map(|config| config.api_url).unwrap_or_default();
+	let api_url = StoredValue::new(api_url);
+	let id = StoredValue::new(group.id);
+	let new_members = RwSignal::new(String::new());
+
+	let on_delete = move |_| {
+		loading.set(true);
+		let variables = delete_user_group_mutation::Variables {
+			id: id.get_value(),
+		};
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<DeleteUserGroupMutation>(
+				api_url.get_value(),
+				variables,
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	let on_add = move |_| {
+		let emails = new_members
+			.get()
+			.split(',')
+			.map(|email| email.trim().to_string())
+			.filter(|email| !email.is_empty())
+			.collect::<Vec<_>>();
+		if emails.is_empty() {
+			return;
+		}
+		loading.set(true);
+		let variables = add_user_group_members_mutation::Variables {
+			id: id.get_value(),
+			emails,
+		};
+		spawn_local(async move {
+			match crate::graphql_queries::run::<AddUserGroupMembersMutation>(
+				api_url.get_value(),
+				variables,
+			)
+			.await
+			{
+				Ok(_) => new_members.set(String::new()),
+				Err(e) => error_ctx.report(e),
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	let on_remove = move |email: String| {
+		loading.set(true);
+		let variables = remove_user_group_members_mutation::Variables {
+			id: id.get_value(),
+			emails: vec![email],
+		};
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<RemoveUserGroupMembersMutation>(
+				api_url.get_value(),
+				variables,
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	let members = if group.members.is_empty() {
+		view! { <p class="text-sm">{MSG_GROUP_NO_MEMBERS}</p> }.into_any()
+	} else {
+		view! {
+			<ul class="grid gap-2">
+				{group
+					.members
+					.into_iter()
+					.map(|email| {
+						view! {
+							<GroupMemberItem email loading on_remove=Callback::new(on_remove) />
+						}
+					})
+					.collect_view()}
+			</ul>
+		}
+		.into_any()
+	};
+
+	view! {
+		<li class="grid gap-2 border-b pb-4">
+			<div class="flex justify-between items-center gap-4">
+				<h2 class="text-xl font-bold break-all">{group.name}</h2>
+				<Button disabled=loading on_click=on_delete>
+					{BUTTON_DELETE_GROUP}
+				</Button>
+			</div>
+			{members}
+			<div class="flex items-center gap-4">
+				<input
+					class="grow"
+					type="text"
+					placeholder=PLACEHOLDER_GROUP_MEMBERS
+					prop:value=new_members
+					on:input=move |ev| new_members.set(event_target_value(&ev))
+				/>
+				<Button disabled=loading on_click=on_add>
+					{BUTTON_ADD_MEMBERS}
+				</Button>
+			</div>
+		</li>
+	}
+}
+
+/// One member of a group, with a button that removes them from it.
+#[component]
+fn GroupMemberItem(
+	email: String,
+	loading: RwSignal<bool>,
+	on_remove: Callback<String>,
+) -> impl IntoView {
+	let removed = email.clone();
+	view! {
+		<li class="flex justify-between items-center gap-4">
+			<span class="break-all">{email}</span>
+			<Button
+				appearance=ButtonAppearance::Subtle
+				disabled=loading
+				on_click=move |_| on_remove.run(removed.clone())
+			>
+				{BUTTON_REMOVE}
+			</Button>
+		</li>
+	}
+}
@@ -1,7 +1,14 @@
 use {
 	crate::{
-		components::location_markers::LocationMarkers,
+		components::{
+			location_markers::LocationMarkers,
+			timeline_slider::{
+				MadeOnRange,
+				TimelineSlider,
+			},
+		},
 		constants::{
+			MAP_FOCUS_ZOOM,
 			MAP_INITIAL_LAT,
 			MAP_INITIAL_LNG,
 			MAP_INITIAL_ZOOM,
@@ -13,24 +20,37 @@ use {
 	},
 	leptos::prelude::*,
 	leptos_leaflet::prelude::*,
+	leptos_router::hooks::use_query_map,
 };
 
-/// Default Home Page
+/// Default Home Page. Opens centred on the `lat` and `lng` query parameters
+/// when given, as the objects table links to an object's location that way.
 #[component]
 pub fn Home() -> impl IntoView {
+	let query = use_query_map();
+	let focus = query.with_untracked(|query| {
+		let coordinate = |key: &str| query.get(key).and_then(|value| value.parse::<f64>().ok());
+		coordinate("lat").zip(coordinate("lng"))
+	});
+	let (center, zoom) = match focus {
+		Some((latitude, longitude)) => (Position::new(latitude, longitude), MAP_FOCUS_ZOOM),
+		None => (Position::new(MAP_INITIAL_LAT, MAP_INITIAL_LNG), MAP_INITIAL_ZOOM),
+	};
+	let made_on_range = RwSignal::new(MadeOnRange::default());
 	view! {
 		<ErrorBoundary fallback=dump_errors>
 			<div class="relative w-dvw">
 				<div class="container mx-auto grid gap-4">
 					<h1 class="text-22px font-bold">{MAP_TITLE}</h1>
+					<TimelineSlider range=made_on_range />
 					<MapContainer
 						class="w-full h-dvh z-0"
-						center=Position::new(MAP_INITIAL_LAT, MAP_INITIAL_LNG)
-						zoom=MAP_INITIAL_ZOOM
+						center=center
+						zoom=zoom
 						set_view=true
 					>
 						<TileLayer url=TILE_LAYER_URL attribution=TILE_LAYER_ATTRIBUTION />
-						<LocationMarkers />
+						<LocationMarkers made_on_range />
 					</MapContainer>
 				</div>
 			</div>
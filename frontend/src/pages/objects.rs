@@ -4,31 +4,57 @@ use {
 		components::{
 			edit_s3_object_form::EditS3ObjectForm,
 			file_upload::FileUpload,
+			object_history::ObjectHistory,
 			s3_objects_table::S3ObjectsTable,
+			share_links::ShareLinks,
 		},
 		constants::{
 			BUTTON_ADD_OBJECT,
 			BUTTON_CLOSE,
 			ERR_SYSTEM_CONFIG_MISSING,
+			LINK_CLEAR_SEARCH,
+			SEARCH_RESULTS_LIMIT,
+			TAB_DETAILS,
+			TAB_HISTORY,
+			TAB_SHARING,
 			TITLE_ADD_OBJECT,
 			TITLE_EDIT_OBJECT,
 			TITLE_OBJECTS,
 		},
 		dump_errors,
 		errors::use_context_safe,
-		graphql_queries::s3_objects::{
-			S3ObjectsQuery,
-			s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+		graphql_queries::{
+			s3_objects::{
+				S3ObjectsQuery,
+				s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+			},
+			search_s3_objects::{
+				SearchS3ObjectsQuery,
+				search_s3_objects_query,
+			},
 		},
 	},
 	leptos::prelude::*,
+	leptos_router::{
+		components::A,
+		hooks::use_query_map,
+	},
 	thaw::*,
 };
 
+/// The parts of the edit dialog.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EditTab {
+	Details,
+	History,
+	Sharing,
+}
+
 /// The Objects page component.
 ///
 /// Displays a table of S3 objects and provides functionality to add new objects
-/// via a file upload dialog and edit existing objects via an edit dialog.
+/// via a file upload dialog and edit existing objects via an edit dialog. With
+/// a `search` query parameter, the table holds the best matches for it instead.
 #[component]
 pub fn Objects() -> impl IntoView {
 	// Signal to trigger resource refetching
@@ -41,18 +67,45 @@ pub fn Objects() -> impl IntoView {
 	let editing_object_id = RwSignal::new(0i64);
 	// Signal to store the object being edited for optimistic UI
 	let editing_object = RwSignal::new(None::<S3Object>);
+	// Signal to store which part of the edit dialog is shown
+	let editing_tab = RwSignal::new(EditTab::Details);
 
 	// Resource that fetches S3 objects, re-running whenever `trigger` changes
 	let config = match use_context_safe::<AppConfig>("AppConfig") {
 		Some(c) => c,
 		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
 	};
+	let query = use_query_map();
+	let search = Memo::new(move |_| {
+		query
+			.get()
+			.get("search")
+			.map(|search| search.trim().to_string())
+			.filter(|search| !search.is_empty())
+	});
 	let s3_objects_resource = LocalResource::new(move || {
 		trigger.get();
-		crate::graphql_queries::run::<S3ObjectsQuery>(
-			config.api_url.clone(),
-			crate::graphql_queries::s3_objects::s3_objects_query::Variables {},
-		)
+		let api_url = config.api_url.clone();
+		let search = search.get();
+		async move {
+			match search {
+				Some(search) =>
+					crate::graphql_queries::run::<SearchS3ObjectsQuery>(
+						api_url,
+						search_s3_objects_query::Variables {
+							query: search,
+							first: SEARCH_RESULTS_LIMIT,
+						},
+					)
+					.await,
+				None =>
+					crate::graphql_queries::run::<S3ObjectsQuery>(
+						api_url,
+						crate::graphql_queries::s3_objects::s3_objects_query::Variables {},
+					)
+					.await,
+			}
+		}
 	});
 
 	// Callback to update the trigger, effectively reloading the table
@@ -69,6 +122,7 @@ pub fn Objects() -> impl IntoView {
 		if let Ok(id) = s3_object.id.parse::<i64>() {
 			editing_object_id.set(id);
 			editing_object.set(Some(s3_object));
+			editing_tab.set(EditTab::Details);
 			open_edit_object.set(true);
 		}
 	});
@@ -79,12 +133,47 @@ pub fn Objects() -> impl IntoView {
 		on_change.run(());
 	});
 
+	// Callback for an undone edit: the table row no longer matches the object,
+	// so the form loads it afresh when shown again
+	let on_revert = Callback::new(move |_| {
+		editing_object.set(None);
+		on_change.run(());
+	});
+
+	let tab_button = move |tab: EditTab, label: &'static str| {
+		view! {
+			<button
+				type="button"
+				role="tab"
+				aria-selected=move || (editing_tab.get() == tab).to_string()
+				class="px-3 py-1 border-b-2"
+				class=("border-blue-500", move || editing_tab.get() == tab)
+				class=("border-transparent", move || editing_tab.get() != tab)
+				on:click=move |_| editing_tab.set(tab)
+			>
+				{label}
+			</button>
+		}
+	};
+
 	view! {
 		<ErrorBoundary fallback=dump_errors>
 			<div class="relative w-dvw">
 				<div class="container mx-auto grid gap-4">
 					<div class="flex justify-between items-center">
-						<h1 class="text-22px font-bold">{TITLE_OBJECTS}</h1>
+						<div class="grid gap-1">
+							<h1 class="text-22px font-bold">
+								{move || match search.get() {
+									Some(search) => format!("{TITLE_OBJECTS} matching \"{search}\""),
+									None => TITLE_OBJECTS.to_string(),
+								}}
+							</h1>
+							<Show when=move || search.get().is_some()>
+								<A href="/objects" attr:class="underline">
+									{LINK_CLEAR_SEARCH}
+								</A>
+							</Show>
+						</div>
 						<Button
 							on_click=move |_| open_add_object.set(true)
 							class="bg-blue-500 hover:bg-blue-700 text-white font-bold py-2 px-4 rounded"
@@ -132,12 +221,31 @@ pub fn Objects() -> impl IntoView {
 										open_edit_object.set(false)
 									}>{BUTTON_CLOSE}</Button>
 								</div>
-								<EditS3ObjectForm
-									id=editing_object_id
-									initial_data=editing_object
-									on_success=on_edit_success
-									on_cancel=move |_| open_edit_object.set(false)
-								/>
+								<div role="tablist" class="flex gap-2 border-b">
+									{tab_button(EditTab::Details, TAB_DETAILS)}
+									{tab_button(EditTab::History, TAB_HISTORY)}
+									{tab_button(EditTab::Sharing, TAB_SHARING)}
+								</div>
+								{move || match editing_tab.get() {
+									EditTab::Details => {
+										view! {
+											<EditS3ObjectForm
+												id=editing_object_id
+												initial_data=editing_object
+												on_success=on_edit_success
+												on_cancel=move |_| open_edit_object.set(false)
+											/>
+										}
+											.into_any()
+									}
+									EditTab::History => {
+										view! { <ObjectHistory id=editing_object_id on_revert /> }
+											.into_any()
+									}
+									EditTab::Sharing => {
+										view! { <ShareLinks id=editing_object_id /> }.into_any()
+									}
+								}}
 							</div>
 						</DialogContent>
 					</DialogBody>
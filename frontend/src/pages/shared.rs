@@ -0,0 +1,189 @@
+use {
+	crate::{
+		AppConfig,
+		components::{
+			gallery::Gallery,
+			markdown::Markdown,
+			password_input::PasswordInput,
+		},
+		constants::{
+			BUTTON_OPEN_SHARE_LINK,
+			BUTTON_VIEW_ALL,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_PASSWORD,
+			LABEL_SHARED_UNTIL,
+			LOADING_TEXT,
+			MAP_FOCUS_ZOOM,
+			MAP_INITIAL_LAT,
+			MAP_INITIAL_LNG,
+			MAP_INITIAL_ZOOM,
+			MSG_FAILED_OPEN_SHARE_LINK,
+			MSG_SHARE_PASSWORD_REQUIRED,
+			TILE_LAYER_ATTRIBUTION,
+			TILE_LAYER_URL,
+			TITLE_SHARED,
+		},
+		errors::use_context_safe,
+		graphql_queries::{
+			open_share_link::{
+				OpenShareLinkMutation,
+				SharedItems,
+				open_share_link_mutation,
+			},
+			s3_objects::s3_objects_query::S3ObjectsQueryS3Objects as S3Object,
+		},
+	},
+	leptos::prelude::*,
+	leptos_leaflet::prelude::*,
+	leptos_router::hooks::use_params_map,
+	thaw::*,
+};
+
+/// The page a share link opens at, `/s/{token}`, for anyone holding the link
+/// whether signed in or not. Asks for the password first when the link has
+/// one, then shows the shared objects on a map and in a gallery. Each time it
+/// opens the link counts as a view, so it does so once rather than on every
+/// render.
+#[component]
+pub fn Shared() -> impl IntoView {
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let params = use_params_map();
+	let token = params.with_untracked(|params| params.get("token").unwrap_or_default());
+	let token = StoredValue::new(token);
+	let password = RwSignal::new(String::new());
+	// The password the link was last opened with, if any
+	let submitted_password = RwSignal::new(None::<String>);
+	let shared_resource = LocalResource::new(move || {
+		let password = submitted_password.get();
+		crate::graphql_queries::run_unauthenticated::<OpenShareLinkMutation>(
+			config.with_value(|c| c.api_url.clone()),
+			open_share_link_mutation::Variables {
+				token: token.get_value(),
+				password,
+			},
+		)
+	});
+	let on_submit_password = move |_| submitted_password.set(Some(password.get()));
+	let password_form = move || {
+		view! {
+			<div class="grid gap-4 w-full max-w-md">
+				<h1 class="text-2xl font-bold">{TITLE_SHARED}</h1>
+				<p>{MSG_SHARE_PASSWORD_REQUIRED}</p>
+				<PasswordInput value=password placeholder=LABEL_PASSWORD disabled=false />
+				<Button class="w-fit" on_click=on_submit_password>
+					{BUTTON_OPEN_SHARE_LINK}
+				</Button>
+			</div>
+		}
+	};
+
+	view! {
+		<div class="relative w-dvw">
+			<div class="container mx-auto grid gap-4 pt-10">
+				<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+					{move || {
+						shared_resource
+							.get()
+							.map(|result| match result {
+								// A wrong password can be tried again
+								Err(e) => {
+									view! {
+										<div class="p-4 bg-red-100 text-red-700 rounded">
+											<p class="font-bold">{MSG_FAILED_OPEN_SHARE_LINK}</p>
+											<p>{e.to_string()}</p>
+										</div>
+										{submitted_password
+											.with_untracked(Option::is_some)
+											.then(password_form)}
+									}
+										.into_any()
+								}
+								Ok(shared) if shared.password_required => {
+									password_form().into_any()
+								}
+								Ok(shared) => view! { <SharedItemsView shared /> }.into_any(),
+							})
+					}}
+				</Suspense>
+			</div>
+		</div>
+	}
+	.into_any()
+}
+
+/// The objects a share link opened, on a map centred on the first of them with
+/// a location, and in a gallery.
+#[component]
+fn SharedItemsView(shared: SharedItems) -> impl IntoView {
+	let SharedItems {
+		title,
+		description,
+		expires_at,
+		objects,
+		..
+	} = shared;
+	let title = if title.is_empty() { TITLE_SHARED.to_string() } else { title };
+	let (center, zoom) = match objects.iter().find_map(|object| object.location.as_ref()) {
+		Some(location) => (Position::new(location.latitude, location.longitude), MAP_FOCUS_ZOOM),
+		None => (Position::new(MAP_INITIAL_LAT, MAP_INITIAL_LNG), MAP_INITIAL_ZOOM),
+	};
+	let located = objects
+		.iter()
+		.filter_map(|object| {
+			object
+				.location
+				.as_ref()
+				.map(|location| (location.latitude, location.longitude, object.clone()))
+		})
+		.collect::<Vec<_>>();
+	view! {
+		<div class="grid gap-4">
+			<div class="flex justify-between items-center gap-4">
+				<h1 class="text-22px font-bold">{title}</h1>
+				<Gallery
+					s3_objects=objects
+					open_button_content=Callback::new(|_| BUTTON_VIEW_ALL.into_any())
+				/>
+			</div>
+			{(!description.is_empty()).then(|| view! { <Markdown text=description /> })}
+			{expires_at
+				.map(|expires_at| {
+					view! { <p class="text-sm">{LABEL_SHARED_UNTIL}{expires_at}</p> }
+				})}
+			<MapContainer class="w-full h-dvh z-0" center=center zoom=zoom set_view=true>
+				<TileLayer url=TILE_LAYER_URL attribution=TILE_LAYER_ATTRIBUTION />
+				{located
+					.into_iter()
+					.map(|(latitude, longitude, object)| {
+					view! { <SharedObjectMarker latitude longitude object /> }
+				})
+					.collect_view()}
+			</MapContainer>
+		</div>
+	}
+}
+
+/// A map marker at a shared object's location, whose popup opens it in a
+/// gallery.
+#[component]
+fn SharedObjectMarker(
+	latitude: f64,
+	longitude: f64,
+	object: S3Object,
+) -> impl IntoView {
+	let heading = if object.title.is_empty() { object.name.clone() } else { object.title.clone() };
+	view! {
+		<Marker position=position!(latitude, longitude)>
+			<Popup>
+				<div class="grid gap-4">
+					<h2>{heading}</h2>
+					<Gallery s3_objects=vec![object] />
+				</div>
+			</Popup>
+		</Marker>
+	}
+}
@@ -0,0 +1,206 @@
+use {
+	crate::{
+		AppConfig,
+		constants::{
+			BUTTON_EMPTY_TRASH,
+			BUTTON_NO,
+			BUTTON_RESTORE,
+			BUTTON_YES,
+			ERR_SYSTEM_CONFIG_MISSING,
+			LABEL_PURGE_AT,
+			LABEL_TRASHED_AT,
+			LOADING_TEXT,
+			MSG_CONFIRM_EMPTY_TRASH,
+			MSG_FAILED_LOAD_TRASH,
+			MSG_TRASH_EMPTY,
+			TITLE_TRASH,
+		},
+		errors::{
+			use_context_safe,
+			use_error_context,
+		},
+		graphql_queries::{
+			empty_trash::{
+				EmptyTrashMutation,
+				empty_trash_mutation,
+			},
+			restore_s3_objects::{
+				RestoreS3ObjectsMutation,
+				restore_s3_objects_mutation,
+			},
+			trashed_objects::{
+				TrashedObject,
+				TrashedObjectsQuery,
+				trashed_objects_query,
+			},
+		},
+	},
+	leptos::{
+		prelude::*,
+		task::spawn_local,
+	},
+	thaw::*,
+};
+
+/// The trash page.
+///
+/// Lists the user's deleted objects with when each will be deleted for good,
+/// so they can be restored or the whole trash emptied early.
+#[component]
+pub fn Trash() -> impl IntoView {
+	let trigger = RwSignal::new(0usize);
+	let error_ctx = use_error_context();
+	let config = match use_context_safe::<AppConfig>("AppConfig") {
+		Some(c) => c,
+		None => return view! { <p>{ERR_SYSTEM_CONFIG_MISSING}</p> }.into_any(),
+	};
+	let config = StoredValue::new(config);
+	let trash_resource = LocalResource::new(move || {
+		trigger.get();
+		crate::graphql_queries::run::<TrashedObjectsQuery>(
+			config.with_value(|c| c.api_url.clone()),
+			trashed_objects_query::Variables {},
+		)
+	});
+
+	let loading = RwSignal::new(false);
+	let on_restore = move |id: String| {
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<RestoreS3ObjectsMutation>(
+				api_url,
+				restore_s3_objects_mutation::Variables {
+					ids: vec![id],
+				},
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	let trash_is_empty =
+		move || !matches!(trash_resource.get(), Some(Ok(objects)) if !objects.is_empty());
+	let open_empty = RwSignal::new(false);
+	let on_empty = move |_| {
+		open_empty.set(false);
+		loading.set(true);
+		let api_url = config.with_value(|c| c.api_url.clone());
+		spawn_local(async move {
+			if let Err(e) = crate::graphql_queries::run::<EmptyTrashMutation>(
+				api_url,
+				empty_trash_mutation::Variables {},
+			)
+			.await
+			{
+				error_ctx.report(e);
+			}
+			loading.set(false);
+			trigger.update(|n| *n = n.wrapping_add(1));
+		});
+	};
+
+	view! {
+		<div class="container mx-auto pt-10">
+			<div class="flex justify-between items-center mb-4">
+				<h1 class="text-2xl font-bold">{TITLE_TRASH}</h1>
+				<Button
+					disabled=Signal::derive(move || loading.get() || trash_is_empty())
+					on_click=move |_| open_empty.set(true)
+				>
+					{BUTTON_EMPTY_TRASH}
+				</Button>
+			</div>
+			<Suspense fallback=move || view! { <p>{LOADING_TEXT}</p> }>
+				{move || {
+					trash_resource
+						.get()
+						.map(|res| match res {
+							Err(e) => {
+								view! {
+									<div class="p-4 bg-red-100 text-red-700 rounded">
+										<p class="font-bold">{MSG_FAILED_LOAD_TRASH}</p>
+										<p>{e.to_string()}</p>
+									</div>
+								}
+									.into_any()
+							}
+							Ok(objects) if objects.is_empty() => {
+								view! { <p>{MSG_TRASH_EMPTY}</p> }.into_any()
+							}
+							Ok(objects) => {
+								view! {
+									<ul class="grid gap-4">
+										{objects
+											.into_iter()
+											.map(|trashed| {
+												view! {
+													<TrashedObjectItem
+														trashed=trashed
+														loading=loading
+														on_restore=Callback::new(on_restore)
+													/>
+												}
+											})
+											.collect_view()}
+									</ul>
+								}
+									.into_any()
+							}
+						})
+				}}
+			</Suspense>
+			<Dialog open=open_empty>
+				<DialogSurface>
+					<DialogBody>
+						<DialogContent>
+							<div class="relative grid gap-4">
+								<h2>{MSG_CONFIRM_EMPTY_TRASH}</h2>
+								<div class="relative grid gap-4 grid-flow-col">
+									<Button on_click=on_empty>{BUTTON_YES}</Button>
+									<Button on_click=move |_| open_empty.set(false)>{BUTTON_NO}</Button>
+								</div>
+							</div>
+						</DialogContent>
+					</DialogBody>
+				</DialogSurface>
+			</Dialog>
+		</div>
+	}
+	.into_any()
+}
+
+/// One deleted object, with a button that restores it.
+#[component]
+fn TrashedObjectItem(
+	trashed: TrashedObject,
+	loading: RwSignal<bool>,
+	on_restore: Callback<String>,
+) -> impl IntoView {
+	let object = trashed.object;
+	let id = object.id.clone();
+	view! {
+		<li class="flex items-center gap-4 border-b pb-4">
+			<a href=object.url.clone() target="_blank" rel="noopener noreferrer">
+				<img
+					class="h-16 w-16 object-cover"
+					alt=object.name.clone()
+					src=object.thumbnail_url.clone().unwrap_or_else(|| object.url.clone())
+				/>
+			</a>
+			<div class="grow break-all">
+				<p>{object.name.clone()}</p>
+				<p class="text-sm">{object.content_type.clone()}</p>
+				<p class="text-sm">{format!("{LABEL_TRASHED_AT}{}", trashed.trashed_at)}</p>
+				<p class="text-sm">{format!("{LABEL_PURGE_AT}{}", trashed.purge_at)}</p>
+			</div>
+			<Button disabled=loading on_click=move |_| on_restore.run(id.clone())>
+				{BUTTON_RESTORE}
+			</Button>
+		</li>
+	}
+}
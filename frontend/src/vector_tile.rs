@@ -0,0 +1,238 @@
+//! Reading the Mapbox Vector Tiles served at `/tiles/{z}/{x}/{y}.mvt`, as far
+//! as the map's object points need: the point geometry and the attributes of
+//! the `objects` layer. A tile is a protocol buffer, see
+//! <https://github.com/mapbox/vector-tile-spec/tree/master/2.1>.
+
+use crate::errors::AppError;
+
+/// The layer the backend puts objects in.
+const OBJECTS_LAYER: &str = "objects";
+
+/// The spec's default extent, used when a layer does not give one.
+const DEFAULT_EXTENT: u32 = 4096;
+
+/// Protocol buffer wire types.
+const VARINT: u64 = 0;
+const FIXED_64: u64 = 1;
+const LENGTH_DELIMITED: u64 = 2;
+const FIXED_32: u64 = 5;
+
+/// The geometry command that places a point.
+const MOVE_TO: u32 = 1;
+
+/// One object in a tile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjectPoint {
+	/// Where the object lies, as a fraction of the tile's width from its west
+	/// edge and of its height from its north edge. Points in the buffer around
+	/// a tile fall just outside 0..1.
+	pub x: f64,
+	pub y: f64,
+	/// `image`, `video`, `audio` and so on.
+	pub content_type_family: Option<String>,
+	/// When the object was made, in seconds since the Unix epoch.
+	pub made_on: Option<i64>,
+}
+
+/// A protocol buffer message, read one field at a time.
+struct Message<'a> {
+	bytes: &'a [u8],
+}
+
+/// A field's value, by wire type. Fixed-width values, which only floating
+/// point attributes use, are skipped.
+enum Field<'a> {
+	Varint(u64),
+	Bytes(&'a [u8]),
+	Fixed,
+}
+
+fn malformed() -> AppError {
+	AppError::Validation("Malformed vector tile".to_string())
+}
+
+impl<'a> Message<'a> {
+	fn new(bytes: &'a [u8]) -> Self {
+		Self {
+			bytes,
+		}
+	}
+
+	fn varint(&mut self) -> Result<u64, AppError> {
+		let mut value = 0_u64;
+		for shift in (0 .. 64).step_by(7) {
+			let (byte, rest) = self.bytes.split_first().ok_or_else(malformed)?;
+			self.bytes = rest;
+			value |= u64::from(byte & 0x7f) << shift;
+			if byte & 0x80 == 0 {
+				return Ok(value);
+			}
+		}
+		Err(malformed())
+	}
+
+	fn take(
+		&mut self,
+		length: usize,
+	) -> Result<&'a [u8], AppError> {
+		let (taken, rest) = self.bytes.split_at_checked(length).ok_or_else(malformed)?;
+		self.bytes = rest;
+		Ok(taken)
+	}
+
+	/// The next field's number and value, or `None` at the end.
+	fn field(&mut self) -> Result<Option<(u64, Field<'a>)>, AppError> {
+		if self.bytes.is_empty() {
+			return Ok(None);
+		}
+		let key = self.varint()?;
+		let value = match key & 0b111 {
+			VARINT => Field::Varint(self.varint()?),
+			FIXED_64 => {
+				self.take(8)?;
+				Field::Fixed
+			}
+			LENGTH_DELIMITED => {
+				let length = usize::try_from(self.varint()?).map_err(|_| malformed())?;
+				Field::Bytes(self.take(length)?)
+			}
+			FIXED_32 => {
+				self.take(4)?;
+				Field::Fixed
+			}
+			_ => return Err(malformed()),
+		};
+		Ok(Some((key >> 3, value)))
+	}
+
+	/// The values of a packed repeated `uint32` field.
+	fn packed(bytes: &'a [u8]) -> Result<Vec<u32>, AppError> {
+		let mut message = Self::new(bytes);
+		let mut values = Vec::new();
+		while !message.bytes.is_empty() {
+			values.push(u32::try_from(message.varint()?).map_err(|_| malformed())?);
+		}
+		Ok(values)
+	}
+}
+
+fn string(bytes: &[u8]) -> Result<String, AppError> {
+	String::from_utf8(bytes.to_vec()).map_err(|_| malformed())
+}
+
+/// Decodes a signed integer stored zigzag-encoded, as 0, -1, 1, -2 and so on.
+fn zigzag(value: u64) -> i64 {
+	(value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// An attribute value. Only strings and integers are kept, the only kinds the
+/// backend writes.
+#[derive(Debug)]
+enum Value {
+	String(String),
+	Integer(i64),
+	Other,
+}
+
+fn value(bytes: &[u8]) -> Result<Value, AppError> {
+	let mut message = Message::new(bytes);
+	let mut value = Value::Other;
+	while let Some((number, field)) = message.field()? {
+		value = match (number, field) {
+			(1, Field::Bytes(bytes)) => Value::String(string(bytes)?),
+			// int64 and uint64, then sint64.
+			(4 | 5, Field::Varint(integer)) => Value::Integer(integer as i64),
+			(6, Field::Varint(integer)) => Value::Integer(zigzag(integer)),
+			_ => Value::Other,
+		};
+	}
+	Ok(value)
+}
+
+/// The points of one feature, each placed by a `MoveTo` command in tile
+/// coordinates relative to the one before.
+fn points(geometry: &[u32]) -> Result<Vec<(i64, i64)>, AppError> {
+	let mut points = Vec::new();
+	let mut cursor = (0, 0);
+	let mut rest = geometry;
+	while let Some((command, parameters)) = rest.split_first() {
+		if command & 0b111 != MOVE_TO {
+			return Err(malformed());
+		}
+		let count = usize::try_from(command >> 3).map_err(|_| malformed())?;
+		let (deltas, next) = parameters.split_at_checked(count * 2).ok_or_else(malformed)?;
+		for delta in deltas.chunks_exact(2) {
+			if let [dx, dy] = delta {
+				cursor = (cursor.0 + zigzag(u64::from(*dx)), cursor.1 + zigzag(u64::from(*dy)));
+				points.push(cursor);
+			}
+		}
+		rest = next;
+	}
+	Ok(points)
+}
+
+/// The objects in a tile's `objects` layer. An empty tile holds none.
+pub fn object_points(tile: &[u8]) -> Result<Vec<ObjectPoint>, AppError> {
+	let mut object_points = Vec::new();
+	let mut message = Message::new(tile);
+	while let Some((number, field)) = message.field()? {
+		let (3, Field::Bytes(layer)) = (number, field) else {
+			continue;
+		};
+		let mut name = String::new();
+		let mut extent = DEFAULT_EXTENT;
+		let mut keys = Vec::new();
+		let mut values = Vec::new();
+		let mut features = Vec::new();
+		let mut layer = Message::new(layer);
+		while let Some((number, field)) = layer.field()? {
+			match (number, field) {
+				(1, Field::Bytes(bytes)) => name = string(bytes)?,
+				(2, Field::Bytes(bytes)) => features.push(bytes),
+				(3, Field::Bytes(bytes)) => keys.push(string(bytes)?),
+				(4, Field::Bytes(bytes)) => values.push(value(bytes)?),
+				(5, Field::Varint(size)) =>
+					extent = u32::try_from(size).map_err(|_| malformed())?,
+				_ => {}
+			}
+		}
+		if name != OBJECTS_LAYER {
+			continue;
+		}
+
+		for feature in features {
+			let mut tags = Vec::new();
+			let mut geometry = Vec::new();
+			let mut feature = Message::new(feature);
+			while let Some((number, field)) = feature.field()? {
+				match (number, field) {
+					(2, Field::Bytes(bytes)) => tags = Message::packed(bytes)?,
+					(4, Field::Bytes(bytes)) => geometry = Message::packed(bytes)?,
+					_ => {}
+				}
+			}
+			let mut attributes = ObjectPoint::default();
+			for tag in tags.chunks_exact(2) {
+				let [key, value] = tag else {
+					continue;
+				};
+				let key = keys.get(*key as usize).ok_or_else(malformed)?;
+				match (key.as_str(), values.get(*value as usize).ok_or_else(malformed)?) {
+					("content_type_family", Value::String(family)) =>
+						attributes.content_type_family = Some(family.clone()),
+					("made_on", Value::Integer(made_on)) => attributes.made_on = Some(*made_on),
+					_ => {}
+				}
+			}
+			for (x, y) in points(&geometry)? {
+				object_points.push(ObjectPoint {
+					x: x as f64 / f64::from(extent),
+					y: y as f64 / f64::from(extent),
+					..attributes.clone()
+				});
+			}
+		}
+	}
+	Ok(object_points)
+}
@@ -1,4 +1,4 @@
-pub const ALLOWED_MIME_TYPES: [&str; 17] = [
+pub const ALLOWED_MIME_TYPES: [&str; 21] = [
 	"image/png",
 	"image/jpeg",
 	"image/gif",
@@ -6,6 +6,10 @@ pub const ALLOWED_MIME_TYPES: [&str; 17] = [
 	"image/svg+xml",
 	"image/avif",
 	"image/apng",
+	"image/heic",
+	"image/heif",
+	"image/x-adobe-dng",
+	"image/x-canon-cr2",
 	"video/mp4",
 	"video/webm",
 	"video/ogg",
@@ -18,6 +22,26 @@ pub const ALLOWED_MIME_TYPES: [&str; 17] = [
 	"audio/mp4",
 ];
 
+/// Content types of accepted files by extension, for the types browsers often
+/// report no content type for (HEIC outside Apple platforms, camera RAW).
+pub const CONTENT_TYPES_BY_EXTENSION: [(&str, &str); 4] = [
+	("heic", "image/heic"),
+	("heif", "image/heif"),
+	("dng", "image/x-adobe-dng"),
+	("cr2", "image/x-canon-cr2"),
+];
+
+/// The content type of an accepted file named `file_name`, going by its
+/// extension, or `None` when the extension is not in
+/// [`CONTENT_TYPES_BY_EXTENSION`].
+pub fn content_type_for_file_name(file_name: &str) -> Option<&'static str> {
+	let (_, extension) = file_name.rsplit_once('.')?;
+	CONTENT_TYPES_BY_EXTENSION
+		.iter()
+		.find(|(known, _)| known.eq_ignore_ascii_case(extension))
+		.map(|(_, content_type)| *content_type)
+}
+
 /// Maximum number of upload parts the backend will presign in one request, and
 /// the chunk size the frontend uses when batching presign requests. The two
 /// MUST agree, so this constant is the single source of truth shared across the